@@ -0,0 +1,97 @@
+//! Benchmarks for how ACP sessions are locked while prompts stream.
+//!
+//! `GooseAcpAgent` keeps its sessions in a map and locks a session for each message it
+//! streams back, holding the lock while the update goes out to the client. These benchmarks
+//! stream to several sessions at once with the session storage the agent used before, one
+//! mutex over the whole map, and the one it uses now, a read-write lock over the map with a
+//! mutex per session.
+//!
+//! Run with `just bench`, which also runs the agent hot path benchmarks.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+const SESSIONS: usize = 8;
+const MESSAGES: usize = 200;
+
+#[derive(Default)]
+struct Session {
+    messages: Vec<String>,
+}
+
+/// Stand-in for sending a session update to the client while the session is locked
+async fn send_update(message: &str) {
+    black_box(message);
+    tokio::task::yield_now().await;
+}
+
+async fn stream_with_map_mutex(sessions: Arc<Mutex<HashMap<String, Session>>>, id: String) {
+    for i in 0..MESSAGES {
+        let message = format!("chunk {}", i);
+        let mut sessions = sessions.lock().await;
+        let session = sessions.get_mut(&id).unwrap();
+        session.messages.push(message.clone());
+        send_update(&message).await;
+    }
+}
+
+async fn stream_with_session_locks(
+    sessions: Arc<RwLock<HashMap<String, Arc<Mutex<Session>>>>>,
+    id: String,
+) {
+    let session = sessions.read().await.get(&id).cloned().unwrap();
+    for i in 0..MESSAGES {
+        let message = format!("chunk {}", i);
+        let mut session = session.lock().await;
+        session.messages.push(message.clone());
+        send_update(&message).await;
+    }
+}
+
+fn bench_concurrent_prompts(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .build()
+        .unwrap();
+    let ids: Vec<String> = (0..SESSIONS).map(|i| format!("session_{}", i)).collect();
+
+    let mut group = c.benchmark_group("acp_sessions/8_prompts_streaming");
+    group.bench_function("map_mutex", |b| {
+        b.to_async(&runtime).iter(|| {
+            let sessions = Arc::new(Mutex::new(
+                ids.iter()
+                    .map(|id| (id.clone(), Session::default()))
+                    .collect::<HashMap<_, _>>(),
+            ));
+            let ids = ids.clone();
+            async move {
+                let prompts = ids
+                    .into_iter()
+                    .map(|id| tokio::spawn(stream_with_map_mutex(sessions.clone(), id)));
+                futures::future::join_all(prompts).await
+            }
+        })
+    });
+    group.bench_function("session_locks", |b| {
+        b.to_async(&runtime).iter(|| {
+            let sessions = Arc::new(RwLock::new(
+                ids.iter()
+                    .map(|id| (id.clone(), Arc::new(Mutex::new(Session::default()))))
+                    .collect::<HashMap<_, _>>(),
+            ));
+            let ids = ids.clone();
+            async move {
+                let prompts = ids
+                    .into_iter()
+                    .map(|id| tokio::spawn(stream_with_session_locks(sessions.clone(), id)));
+                futures::future::join_all(prompts).await
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_concurrent_prompts);
+criterion_main!(benches);
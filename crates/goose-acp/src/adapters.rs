@@ -3,10 +3,10 @@
 
 use std::{
     pin::Pin,
-    task::{Context, Poll},
+    task::{ready, Context, Poll},
 };
 use tokio::sync::mpsc;
-use tracing::error;
+use tokio_util::sync::PollSender;
 
 /// Converts an mpsc::Receiver<String> to AsyncRead
 /// Each message is terminated with a newline for JSON-RPC framing
@@ -62,55 +62,49 @@ impl tokio::io::AsyncRead for ReceiverToAsyncRead {
 }
 
 /// Converts an mpsc::Sender<String> to AsyncWrite
-/// Splits incoming data on newlines for JSON-RPC framing
+/// Splits incoming data on newlines for JSON-RPC framing. When the channel is full, writes
+/// wait for the receiver to catch up instead of dropping messages.
 pub(crate) struct SenderToAsyncWrite {
-    tx: mpsc::Sender<String>,
+    tx: PollSender<String>,
     buffer: Vec<u8>,
 }
 
 impl SenderToAsyncWrite {
     pub(crate) fn new(tx: mpsc::Sender<String>) -> Self {
         Self {
-            tx,
+            tx: PollSender::new(tx),
             buffer: Vec::new(),
         }
     }
 }
 
+fn channel_closed() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::BrokenPipe, "Channel closed")
+}
+
 impl tokio::io::AsyncWrite for SenderToAsyncWrite {
     fn poll_write(
         mut self: Pin<&mut Self>,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<std::io::Result<usize>> {
-        self.buffer.extend_from_slice(buf);
-
-        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
-            let line = String::from_utf8_lossy(&self.buffer[..pos]).to_string();
-            self.buffer.drain(..=pos);
-
-            if !line.is_empty() {
-                if let Err(e) = self.tx.try_send(line.clone()) {
-                    match e {
-                        mpsc::error::TrySendError::Full(_) => {
-                            let truncated: String = line.chars().take(100).collect();
-                            error!(
-                                "Channel full, dropping message (backpressure): {}",
-                                truncated
-                            );
-                        }
-                        mpsc::error::TrySendError::Closed(_) => {
-                            return Poll::Ready(Err(std::io::Error::new(
-                                std::io::ErrorKind::BrokenPipe,
-                                "Channel closed",
-                            )));
-                        }
-                    }
-                }
-            }
+        let Some(newline) = buf.iter().position(|&b| b == b'\n') else {
+            self.buffer.extend_from_slice(buf);
+            return Poll::Ready(Ok(buf.len()));
+        };
+        if self.buffer.is_empty() && newline == 0 {
+            return Poll::Ready(Ok(1));
         }
 
-        Poll::Ready(Ok(buf.len()))
+        // Accept the bytes that complete a line only once the channel has room for it;
+        // the caller retries the rest of the write afterwards.
+        ready!(self.tx.poll_reserve(cx)).map_err(|_| channel_closed())?;
+        self.buffer.extend_from_slice(&buf[..newline]);
+        let line = String::from_utf8_lossy(&self.buffer).to_string();
+        self.buffer.clear();
+        self.tx.send_item(line).map_err(|_| channel_closed())?;
+
+        Poll::Ready(Ok(newline + 1))
     }
 
     fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
@@ -121,3 +115,35 @@ impl tokio::io::AsyncWrite for SenderToAsyncWrite {
         Poll::Ready(Ok(()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn slow_reader_receives_every_line() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut writer = SenderToAsyncWrite::new(tx);
+
+        let lines: Vec<String> = (0..100)
+            .map(|i| format!(r#"{{"method":"session/update","params":{{"toolCallId":"{i}"}}}}"#))
+            .collect();
+        let payload = lines.join("\n") + "\n";
+
+        let write = tokio::spawn(async move {
+            for chunk in payload.as_bytes().chunks(37) {
+                writer.write_all(chunk).await.unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        while received.len() < lines.len() {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            received.push(rx.recv().await.unwrap());
+        }
+        write.await.unwrap();
+
+        assert_eq!(received, lines);
+    }
+}
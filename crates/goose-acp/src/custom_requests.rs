@@ -82,6 +82,32 @@ pub struct GetSessionResponse {
     pub session: serde_json::Value,
 }
 
+/// Get the modes a session can run in.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetSessionModesRequest {
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GetSessionModesResponse {
+    pub modes: SessionModes,
+}
+
+/// The mode a session runs in and the modes there are, in the shape of ACP's SessionModeState.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionModes {
+    pub current_mode_id: String,
+    pub available_modes: Vec<SessionModeInfo>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SessionModeInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
 /// Delete a session.
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct DeleteSessionRequest {
@@ -36,7 +36,7 @@ use sacp::schema::{
 use sacp::{AgentToClient, ByteStreams, Handled, JrConnectionCx, JrMessageHandler, MessageCx};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock};
 use tokio_util::compat::{TokioAsyncReadCompatExt as _, TokioAsyncWriteCompatExt as _};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
@@ -51,8 +51,15 @@ struct GooseAcpSession {
     cancel_token: Option<CancellationToken>,
 }
 
+// Each session has its own lock so streaming one prompt never waits on another session;
+// the map itself is only locked long enough to look a session up.
+type SharedSession = Arc<Mutex<GooseAcpSession>>;
+
+// Text that streams in faster than it is sent is merged into chunks of up to this size.
+const MAX_COALESCED_TEXT_BYTES: usize = 16 * 1024;
+
 pub struct GooseAcpAgent {
-    sessions: Arc<Mutex<HashMap<String, GooseAcpSession>>>,
+    sessions: Arc<RwLock<HashMap<String, SharedSession>>>,
     provider_factory: ProviderConstructor,
     config_dir: std::path::PathBuf,
     session_manager: Arc<SessionManager>,
@@ -304,6 +311,39 @@ async fn build_model_state(provider: &dyn Provider, current_model: &str) -> Sess
     )
 }
 
+// Reported through _goose/session/modes rather than the session responses: the mode is
+// fixed when the session's agent is built, and advertising modes there tells clients
+// session/set_mode works.
+fn build_mode_state(current_mode: goose::config::GooseMode) -> SessionModes {
+    use goose::config::GooseMode;
+
+    let modes = [
+        (GooseMode::Auto, "Autonomous", "Run tools without asking"),
+        (
+            GooseMode::Approve,
+            "Manual approval",
+            "Ask before every tool call",
+        ),
+        (
+            GooseMode::SmartApprove,
+            "Smart approval",
+            "Ask only before tool calls that may change things",
+        ),
+        (GooseMode::Chat, "Chat only", "Answer without running tools"),
+    ];
+    SessionModes {
+        current_mode_id: current_mode.to_string(),
+        available_modes: modes
+            .into_iter()
+            .map(|(mode, name, description)| SessionModeInfo {
+                id: mode.to_string(),
+                name: name.to_string(),
+                description: description.to_string(),
+            })
+            .collect(),
+    }
+}
+
 impl GooseAcpAgent {
     pub fn permission_manager(&self) -> Arc<PermissionManager> {
         Arc::clone(&self.permission_manager)
@@ -321,7 +361,7 @@ impl GooseAcpAgent {
         let permission_manager = Arc::new(PermissionManager::new(config_dir.clone()));
 
         Ok(Self {
-            sessions: Arc::new(Mutex::new(HashMap::new())),
+            sessions: Arc::new(RwLock::new(HashMap::new())),
             provider_factory,
             config_dir,
             session_manager,
@@ -354,7 +394,18 @@ impl GooseAcpAgent {
     }
 
     pub async fn has_session(&self, session_id: &str) -> bool {
-        self.sessions.lock().await.contains_key(session_id)
+        self.sessions.read().await.contains_key(session_id)
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<SharedSession, sacp::Error> {
+        self.sessions
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| {
+                sacp::Error::invalid_params().data(format!("Session not found: {}", session_id))
+            })
     }
 
     fn convert_acp_prompt_to_message(&self, prompt: Vec<ContentBlock>) -> Message {
@@ -389,22 +440,42 @@ impl GooseAcpAgent {
         user_message
     }
 
+    fn flush_text(
+        session_id: &SessionId,
+        pending_text: &mut String,
+        cx: &JrConnectionCx<AgentToClient>,
+    ) -> Result<(), sacp::Error> {
+        if pending_text.is_empty() {
+            return Ok(());
+        }
+        cx.send_notification(SessionNotification::new(
+            session_id.clone(),
+            SessionUpdate::AgentMessageChunk(ContentChunk::new(ContentBlock::Text(
+                TextContent::new(std::mem::take(pending_text)),
+            ))),
+        ))?;
+        Ok(())
+    }
+
     async fn handle_message_content(
         &self,
         content_item: &MessageContent,
         session_id: &SessionId,
         session: &mut GooseAcpSession,
+        pending_text: &mut String,
         cx: &JrConnectionCx<AgentToClient>,
     ) -> Result<(), sacp::Error> {
-        match content_item {
-            MessageContent::Text(text) => {
-                cx.send_notification(SessionNotification::new(
-                    session_id.clone(),
-                    SessionUpdate::AgentMessageChunk(ContentChunk::new(ContentBlock::Text(
-                        TextContent::new(text.text.clone()),
-                    ))),
-                ))?;
+        if let MessageContent::Text(text) = content_item {
+            pending_text.push_str(&text.text);
+            if pending_text.len() >= MAX_COALESCED_TEXT_BYTES {
+                Self::flush_text(session_id, pending_text, cx)?;
             }
+            return Ok(());
+        }
+        // Everything else is sent in order after the text that preceded it
+        Self::flush_text(session_id, pending_text, cx)?;
+
+        match content_item {
             MessageContent::ToolRequest(tool_request) => {
                 self.handle_tool_request(tool_request, session_id, session, cx)
                     .await?;
@@ -540,20 +611,12 @@ impl GooseAcpAgent {
         }
         let tool_call_update = ToolCallUpdate::new(ToolCallId::new(request_id.clone()), fields);
 
-        fn option(kind: PermissionOptionKind) -> PermissionOption {
-            let id = serde_json::to_value(kind)
-                .unwrap()
-                .as_str()
-                .unwrap()
-                .to_string();
-            PermissionOption::new(id.clone(), id, kind)
-        }
-        let options = vec![
-            option(PermissionOptionKind::AllowAlways),
-            option(PermissionOptionKind::AllowOnce),
-            option(PermissionOptionKind::RejectOnce),
-            option(PermissionOptionKind::RejectAlways),
-        ];
+        let options: Vec<PermissionOption> = PERMISSION_OPTIONS
+            .iter()
+            .map(|(id, name, kind, _)| {
+                PermissionOption::new(id.to_string(), name.to_string(), *kind)
+            })
+            .collect();
 
         let permission_request =
             RequestPermissionRequest::new(session_id, tool_call_update, options);
@@ -590,20 +653,60 @@ impl GooseAcpAgent {
     }
 }
 
+/// The options offered for a tool call: option id, label, ACP kind and the goose decision.
+/// ACP only has once and always kinds, so the scoped grants are offered as `allow_always`
+/// options and told apart by their id.
+const PERMISSION_OPTIONS: &[(&str, &str, PermissionOptionKind, Permission)] = &[
+    (
+        "allow_always",
+        "Always allow",
+        PermissionOptionKind::AllowAlways,
+        Permission::AlwaysAllow,
+    ),
+    (
+        "allow_session",
+        "Allow for this session",
+        PermissionOptionKind::AllowAlways,
+        Permission::AllowForSession,
+    ),
+    (
+        "allow_project",
+        "Allow in this project",
+        PermissionOptionKind::AllowAlways,
+        Permission::AllowForProject,
+    ),
+    (
+        "allow_hour",
+        "Allow for one hour",
+        PermissionOptionKind::AllowAlways,
+        Permission::AllowForHour,
+    ),
+    (
+        "allow_once",
+        "Allow once",
+        PermissionOptionKind::AllowOnce,
+        Permission::AllowOnce,
+    ),
+    (
+        "reject_once",
+        "Reject once",
+        PermissionOptionKind::RejectOnce,
+        Permission::DenyOnce,
+    ),
+    (
+        "reject_always",
+        "Always reject",
+        PermissionOptionKind::RejectAlways,
+        Permission::AlwaysDeny,
+    ),
+];
+
 fn outcome_to_confirmation(outcome: &RequestPermissionOutcome) -> PermissionConfirmation {
     let permission = match outcome {
-        RequestPermissionOutcome::Cancelled => Permission::Cancel,
-        RequestPermissionOutcome::Selected(selected) => {
-            match serde_json::from_value::<PermissionOptionKind>(serde_json::Value::String(
-                selected.option_id.0.to_string(),
-            )) {
-                Ok(PermissionOptionKind::AllowAlways) => Permission::AlwaysAllow,
-                Ok(PermissionOptionKind::AllowOnce) => Permission::AllowOnce,
-                Ok(PermissionOptionKind::RejectOnce) => Permission::DenyOnce,
-                Ok(PermissionOptionKind::RejectAlways) => Permission::AlwaysDeny,
-                _ => Permission::Cancel,
-            }
-        }
+        RequestPermissionOutcome::Selected(selected) => PERMISSION_OPTIONS
+            .iter()
+            .find(|(id, ..)| *id == &*selected.option_id.0)
+            .map_or(Permission::Cancel, |(.., permission)| permission.clone()),
         _ => Permission::Cancel,
     };
     PermissionConfirmation {
@@ -731,8 +834,10 @@ impl GooseAcpAgent {
             cancel_token: None,
         };
 
-        let mut sessions = self.sessions.lock().await;
-        sessions.insert(goose_session.id.clone(), session);
+        self.sessions
+            .write()
+            .await
+            .insert(goose_session.id.clone(), Arc::new(Mutex::new(session)));
 
         info!(
             session_id = %goose_session.id,
@@ -856,8 +961,10 @@ impl GooseAcpAgent {
             }
         }
 
-        let mut sessions = self.sessions.lock().await;
-        sessions.insert(session_id.clone(), session);
+        self.sessions
+            .write()
+            .await
+            .insert(session_id.clone(), Arc::new(Mutex::new(session)));
 
         info!(
             session_id = %session_id,
@@ -879,11 +986,9 @@ impl GooseAcpAgent {
         let session_id = args.session_id.0.to_string();
         let cancel_token = CancellationToken::new();
 
+        let shared_session = self.get_session(&session_id).await?;
         let agent = {
-            let mut sessions = self.sessions.lock().await;
-            let session = sessions.get_mut(&session_id).ok_or_else(|| {
-                sacp::Error::invalid_params().data(format!("Session not found: {}", session_id))
-            })?;
+            let mut session = shared_session.lock().await;
             session.cancel_token = Some(cancel_token.clone());
             session.agent.clone()
         };
@@ -904,11 +1009,23 @@ impl GooseAcpAgent {
                 sacp::Error::internal_error().data(format!("Error getting agent reply: {}", e))
             })?;
 
-        use futures::StreamExt;
+        use futures::{FutureExt, StreamExt};
 
         let mut was_cancelled = false;
-
-        while let Some(event) = stream.next().await {
+        let mut pending_text = String::new();
+
+        loop {
+            // Only wait for the next event once what has arrived so far is sent
+            let event = match stream.next().now_or_never() {
+                Some(event) => event,
+                None => {
+                    Self::flush_text(&args.session_id, &mut pending_text, cx)?;
+                    stream.next().await
+                }
+            };
+            let Some(event) = event else {
+                break;
+            };
             if cancel_token.is_cancelled() {
                 was_cancelled = true;
                 break;
@@ -916,31 +1033,31 @@ impl GooseAcpAgent {
 
             match event {
                 Ok(goose::agents::AgentEvent::Message(message)) => {
-                    let mut sessions = self.sessions.lock().await;
-                    let session = sessions.get_mut(&session_id).ok_or_else(|| {
-                        sacp::Error::invalid_params()
-                            .data(format!("Session not found: {}", session_id))
-                    })?;
-
+                    let mut session = shared_session.lock().await;
                     session.messages.push(message.clone());
 
                     for content_item in &message.content {
-                        self.handle_message_content(content_item, &args.session_id, session, cx)
-                            .await?;
+                        self.handle_message_content(
+                            content_item,
+                            &args.session_id,
+                            &mut session,
+                            &mut pending_text,
+                            cx,
+                        )
+                        .await?;
                     }
                 }
                 Ok(_) => {}
                 Err(e) => {
+                    let _ = Self::flush_text(&args.session_id, &mut pending_text, cx);
                     return Err(sacp::Error::internal_error()
                         .data(format!("Error in agent response stream: {}", e)));
                 }
             }
         }
+        Self::flush_text(&args.session_id, &mut pending_text, cx)?;
 
-        let mut sessions = self.sessions.lock().await;
-        if let Some(session) = sessions.get_mut(&session_id) {
-            session.cancel_token = None;
-        }
+        shared_session.lock().await.cancel_token = None;
 
         Ok(PromptResponse::new(if was_cancelled {
             StopReason::Cancelled
@@ -953,10 +1070,10 @@ impl GooseAcpAgent {
         debug!(?args, "cancel request");
 
         let session_id = args.session_id.0.to_string();
-        let mut sessions = self.sessions.lock().await;
+        let shared_session = self.sessions.read().await.get(&session_id).cloned();
 
-        if let Some(session) = sessions.get_mut(&session_id) {
-            if let Some(ref token) = session.cancel_token {
+        if let Some(shared_session) = shared_session {
+            if let Some(ref token) = shared_session.lock().await.cancel_token {
                 info!(session_id = %session_id, "prompt cancelled");
                 token.cancel();
             }
@@ -990,13 +1107,13 @@ impl GooseAcpAgent {
                 sacp::Error::internal_error().data(format!("Failed to create provider: {}", e))
             })?;
 
-        let agent = {
-            let sessions = self.sessions.lock().await;
-            let session = sessions.get(session_id).ok_or_else(|| {
-                sacp::Error::invalid_params().data(format!("Session not found: {}", session_id))
-            })?;
-            session.agent.clone()
-        };
+        let agent = self
+            .get_session(session_id)
+            .await?
+            .lock()
+            .await
+            .agent
+            .clone();
         agent
             .update_provider(provider, session_id)
             .await
@@ -1127,6 +1244,17 @@ impl GooseAcpAgent {
         })
     }
 
+    #[custom_method("session/modes")]
+    async fn on_get_session_modes(
+        &self,
+        req: GetSessionModesRequest,
+    ) -> Result<GetSessionModesResponse, sacp::Error> {
+        let agent = self.get_agent_for_session(&req.session_id).await?;
+        Ok(GetSessionModesResponse {
+            modes: build_mode_state(agent.config.goose_mode),
+        })
+    }
+
     #[custom_method("session/delete")]
     async fn on_delete_session(
         &self,
@@ -1185,14 +1313,13 @@ impl GooseAcpAgent {
     }
 
     async fn get_agent_for_session(&self, session_id: &str) -> Result<Arc<Agent>, sacp::Error> {
-        self.sessions
-            .lock()
-            .await
-            .get(session_id)
-            .map(|s| Arc::clone(&s.agent))
-            .ok_or_else(|| {
-                sacp::Error::invalid_params().data(format!("no active session: {session_id}"))
-            })
+        let session = self.sessions.read().await.get(session_id).cloned();
+        match session {
+            Some(session) => Ok(Arc::clone(&session.lock().await.agent)),
+            None => {
+                Err(sacp::Error::invalid_params().data(format!("no active session: {session_id}")))
+            }
+        }
     }
 }
 
@@ -1479,6 +1606,21 @@ print(\"hello, world\")
         PermissionConfirmation { principal_type: PrincipalType::Tool, permission: Permission::AlwaysAllow };
         "allow_always_maps_to_always_allow"
     )]
+    #[test_case(
+        RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(PermissionOptionId::from("allow_session".to_string()))),
+        PermissionConfirmation { principal_type: PrincipalType::Tool, permission: Permission::AllowForSession };
+        "allow_session_maps_to_allow_for_session"
+    )]
+    #[test_case(
+        RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(PermissionOptionId::from("allow_project".to_string()))),
+        PermissionConfirmation { principal_type: PrincipalType::Tool, permission: Permission::AllowForProject };
+        "allow_project_maps_to_allow_for_project"
+    )]
+    #[test_case(
+        RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(PermissionOptionId::from("allow_hour".to_string()))),
+        PermissionConfirmation { principal_type: PrincipalType::Tool, permission: Permission::AllowForHour };
+        "allow_hour_maps_to_allow_for_hour"
+    )]
     #[test_case(
         RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(PermissionOptionId::from("reject_once".to_string()))),
         PermissionConfirmation { principal_type: PrincipalType::Tool, permission: Permission::DenyOnce };
@@ -86,6 +86,43 @@ fn test_custom_session_get() {
     });
 }
 
+#[test]
+fn test_custom_session_modes() {
+    run_test(async {
+        let openai = OpenAiFixture::new(vec![], ExpectedSessionId::default()).await;
+        let mut conn = ClientToAgentConnection::new(TestConnectionConfig::default(), openai).await;
+
+        let (session, _models) = conn.new_session().await;
+        let session_id = session.session_id().0.clone();
+
+        let result = send_custom(
+            conn.cx(),
+            "_goose/session/modes",
+            serde_json::json!({ "session_id": session_id }),
+        )
+        .await;
+        assert!(result.is_ok(), "expected ok, got: {:?}", result);
+
+        let modes = result.unwrap()["modes"].clone();
+        assert_eq!(modes["currentModeId"], "auto");
+        let ids: Vec<&str> = modes["availableModes"]
+            .as_array()
+            .expect("availableModes should be array")
+            .iter()
+            .filter_map(|mode| mode["id"].as_str())
+            .collect();
+        assert_eq!(ids, vec!["auto", "approve", "smart_approve", "chat"]);
+
+        let result = send_custom(
+            conn.cx(),
+            "_goose/session/modes",
+            serde_json::json!({ "session_id": "missing" }),
+        )
+        .await;
+        assert!(result.is_err(), "expected error for unknown session");
+    });
+}
+
 #[test]
 fn test_custom_session_delete() {
     run_test(async {
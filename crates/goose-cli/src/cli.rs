@@ -2,6 +2,7 @@ use anyhow::Result;
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{generate, Shell as ClapShell};
 use goose::builtin_extension::register_builtin_extensions;
+use goose::config::profiles::configured_profiles;
 use goose::config::Config;
 use goose::posthog::get_telemetry_choice;
 use goose::recipe::Recipe;
@@ -9,7 +10,9 @@ use goose_mcp::mcp_server_runner::{serve, McpCommand};
 use goose_mcp::{AutoVisualiserRouter, ComputerControllerServer, MemoryServer, TutorialServer};
 
 use crate::commands::configure::{configure_telemetry_consent_dialog, handle_configure};
-use crate::commands::info::handle_info;
+use crate::commands::doctor::handle_doctor;
+use crate::commands::eval::handle_eval;
+use crate::commands::info::{handle_config_schema, handle_info};
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
 use crate::commands::recipe::{handle_deeplink, handle_list, handle_open, handle_validate};
 use crate::commands::term::{
@@ -35,6 +38,15 @@ use tracing::warn;
 #[derive(Parser)]
 #[command(name = "goose", author, version, display_name = "", about, long_about = None)]
 pub struct Cli {
+    #[arg(
+        long,
+        global = true,
+        value_name = "PROFILE",
+        help = "Use a named configuration profile",
+        long_help = "Use a profile from GOOSE_PROFILES in the config file. Overrides the GOOSE_PROFILE environment variable and any .goose/profile file in the project."
+    )]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -232,6 +244,7 @@ pub struct InputOptions {
 
     #[arg(
         long,
+        visible_alias = "param",
         value_name = "KEY=VALUE",
         help = "Dynamic parameters (e.g., --params username=alice --params channel_name=goose-channel)",
         long_help = "Key-value parameters to pass to the recipe file. Can be specified multiple times.",
@@ -559,6 +572,21 @@ enum SchedulerCommand {
             help = "Recipe source (path to file, or base64 encoded recipe string)"
         )]
         recipe_source: String,
+        #[arg(
+            long,
+            help = "Provider to run the job with, instead of the recipe or default"
+        )]
+        provider: Option<String>,
+        #[arg(
+            long,
+            help = "Model to run the job with, instead of the recipe or default"
+        )]
+        model: Option<String>,
+        #[arg(
+            long = "permission-profile",
+            help = "Permission profile the job's sessions run under"
+        )]
+        permission_profile: Option<String>,
     },
     #[command(about = "List all scheduled jobs")]
     List {},
@@ -628,6 +656,35 @@ enum GatewayCommand {
     },
 }
 
+#[derive(Subcommand)]
+enum ExtensionsCommand {
+    #[command(
+        about = "Update locked npx/uvx extensions to their latest versions",
+        long_about = "Resolve the latest published version of npx/uvx extensions and record it in the extension lockfile. Without names, all configured extensions are updated."
+    )]
+    Update {
+        #[arg(help = "Names of the extensions to update")]
+        names: Vec<String>,
+    },
+
+    #[command(
+        about = "Show the log of an extension",
+        long_about = "Show the last lines an extension wrote to stderr or sent as MCP log messages"
+    )]
+    Logs {
+        #[arg(help = "Name of the extension")]
+        name: String,
+
+        #[arg(
+            short = 'n',
+            long,
+            default_value_t = 50,
+            help = "Number of lines to show"
+        )]
+        lines: usize,
+    },
+}
+
 #[derive(Subcommand)]
 enum RecipeCommand {
     /// Validate a recipe file
@@ -706,8 +763,23 @@ enum Command {
         /// Show verbose information including current configuration
         #[arg(short, long, help = "Show verbose information including config.yaml")]
         verbose: bool,
+
+        /// Print the JSON schema for config.yaml instead
+        #[arg(
+            long,
+            help = "Print the JSON schema for config.yaml",
+            long_help = "Print the JSON schema for config.yaml, for editors and CI to validate the file against"
+        )]
+        config_schema: bool,
     },
 
+    /// Check the configuration for problems
+    #[command(
+        about = "Check the configuration for problems",
+        long_about = "Check that the provider is set up, the model is known, extension commands can be found and hook files parse, and suggest fixes for what is wrong."
+    )]
+    Doctor {},
+
     /// Manage system prompts and behaviors
     #[command(about = "Run one of the mcp servers bundled with goose")]
     Mcp {
@@ -864,6 +936,13 @@ enum Command {
         #[command(subcommand)]
         command: TermCommand,
     },
+    /// Manage installed extensions
+    #[command(about = "Manage installed extensions", visible_alias = "ext")]
+    Extensions {
+        #[command(subcommand)]
+        command: ExtensionsCommand,
+    },
+
     /// Manage local inference models
     #[command(about = "Manage local inference models", visible_alias = "lm")]
     LocalModels {
@@ -871,6 +950,40 @@ enum Command {
         command: LocalModelsCommand,
     },
 
+    /// Run an eval suite across providers and models
+    #[command(about = "Run an eval suite across providers and models")]
+    Eval {
+        #[arg(help = "Path to the eval suite (YAML or JSON)")]
+        suite: PathBuf,
+
+        #[arg(
+            long = "target",
+            value_name = "PROVIDER/MODEL",
+            help = "Provider and model to evaluate; repeat for a matrix (default: the suite's targets, then the configured provider)"
+        )]
+        targets: Vec<String>,
+
+        #[arg(
+            long,
+            value_name = "DIR",
+            help = "Record provider traffic here on the first run and replay it afterwards"
+        )]
+        recordings: Option<PathBuf>,
+
+        #[arg(
+            long,
+            help = "Fail cases that have no recording instead of calling the provider"
+        )]
+        replay_only: bool,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Write the report to a file (.json for JSON, markdown otherwise)"
+        )]
+        report: Option<PathBuf>,
+    },
+
     /// Generate completions for various shells
     #[command(about = "Generate the autocompletion script for the specified shell")]
     Completion {
@@ -1002,6 +1115,7 @@ fn get_command_name(command: &Option<Command>) -> &'static str {
     match command {
         Some(Command::Configure {}) => "configure",
         Some(Command::Info { .. }) => "info",
+        Some(Command::Doctor {}) => "doctor",
         Some(Command::Mcp { .. }) => "mcp",
         Some(Command::Acp { .. }) => "acp",
         Some(Command::Session { .. }) => "session",
@@ -1013,7 +1127,9 @@ fn get_command_name(command: &Option<Command>) -> &'static str {
         Some(Command::Update { .. }) => "update",
         Some(Command::Recipe { .. }) => "recipe",
         Some(Command::Term { .. }) => "term",
+        Some(Command::Extensions { .. }) => "extensions",
         Some(Command::LocalModels { .. }) => "local-models",
+        Some(Command::Eval { .. }) => "eval",
         Some(Command::Completion { .. }) => "completion",
         Some(Command::ValidateExtensions { .. }) => "validate-extensions",
         None => "default_session",
@@ -1424,7 +1540,17 @@ async fn handle_schedule_command(command: SchedulerCommand) -> Result<()> {
             schedule_id,
             cron,
             recipe_source,
-        } => handle_schedule_add(schedule_id, cron, recipe_source).await,
+            provider,
+            model,
+            permission_profile,
+        } => {
+            let settings = goose::scheduler::JobRunSettings {
+                provider,
+                model,
+                permission_profile,
+            };
+            handle_schedule_add(schedule_id, cron, recipe_source, settings).await
+        }
         SchedulerCommand::List {} => handle_schedule_list().await,
         SchedulerCommand::Remove { schedule_id } => handle_schedule_remove(schedule_id).await,
         SchedulerCommand::Sessions { schedule_id, limit } => {
@@ -1437,6 +1563,52 @@ async fn handle_schedule_command(command: SchedulerCommand) -> Result<()> {
     }
 }
 
+async fn handle_extensions_command(command: ExtensionsCommand) -> Result<()> {
+    use goose::agents::extension_lock::update_locked_extensions;
+    use goose::agents::extension_logs::ExtensionLogs;
+
+    match command {
+        ExtensionsCommand::Update { names } => {
+            let configs: Vec<_> = goose::config::get_all_extensions()
+                .into_iter()
+                .map(|entry| entry.config)
+                .collect();
+            let updates = update_locked_extensions(&configs, &names).await?;
+            if updates.is_empty() {
+                println!("No npx/uvx extensions to update");
+            }
+            for update in updates {
+                match update.previous {
+                    Some(previous) if previous != update.current => println!(
+                        "{}: {} {} -> {}",
+                        update.extension, update.package, previous, update.current
+                    ),
+                    Some(_) => println!(
+                        "{}: {} {} (up to date)",
+                        update.extension, update.package, update.current
+                    ),
+                    None => println!(
+                        "{}: {} locked at {}",
+                        update.extension, update.package, update.current
+                    ),
+                }
+            }
+            Ok(())
+        }
+        ExtensionsCommand::Logs { name, lines } => {
+            let logs = ExtensionLogs::global();
+            let tail = logs.tail(&name, lines);
+            if tail.is_empty() {
+                println!("No logs for {} at {}", name, logs.path(&name).display());
+            }
+            for line in tail {
+                println!("{}", line);
+            }
+            Ok(())
+        }
+    }
+}
+
 fn handle_recipe_subcommand(command: RecipeCommand) -> Result<()> {
     match command {
         RecipeCommand::Validate { recipe_name } => handle_validate(&recipe_name),
@@ -1667,11 +1839,46 @@ async fn handle_default_session() -> Result<()> {
     session.interactive(None).await
 }
 
+fn select_profile(name: &str) -> Result<()> {
+    let config = Config::global();
+    let profiles = configured_profiles(config);
+    if !profiles.contains_key(name) {
+        let mut names: Vec<&String> = profiles.keys().collect();
+        names.sort();
+        let available = if names.is_empty() {
+            "none are configured".to_string()
+        } else {
+            format!(
+                "available: {}",
+                names
+                    .iter()
+                    .map(|n| n.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+        return Err(anyhow::anyhow!(
+            "Unknown profile '{}' ({}). Profiles are defined under GOOSE_PROFILES in {}",
+            name,
+            available,
+            config.path()
+        ));
+    }
+    config.set_active_profile(Some(name.to_string()));
+    Ok(())
+}
+
 pub async fn cli() -> anyhow::Result<()> {
     register_builtin_extensions(goose_mcp::BUILTIN_EXTENSIONS.clone());
 
     let cli = Cli::parse();
 
+    if let Some(profile) = &cli.profile {
+        select_profile(profile)?;
+    }
+    // Picked up by the next run; config loading never waits on the network
+    tokio::spawn(goose::config::org_config::refresh_in_background());
+
     if let Err(e) = crate::project_tracker::update_project_tracker(None, None) {
         warn!("Warning: Failed to update project tracker: {}", e);
     }
@@ -1690,7 +1897,17 @@ pub async fn cli() -> anyhow::Result<()> {
             Ok(())
         }
         Some(Command::Configure {}) => handle_configure().await,
-        Some(Command::Info { verbose }) => handle_info(verbose),
+        Some(Command::Info {
+            verbose,
+            config_schema,
+        }) => {
+            if config_schema {
+                handle_config_schema()
+            } else {
+                handle_info(verbose)
+            }
+        }
+        Some(Command::Doctor {}) => handle_doctor().await,
         Some(Command::Mcp { server }) => handle_mcp_command(server).await,
         Some(Command::Acp { builtins }) => goose_acp::server::run(builtins).await,
         Some(Command::Session {
@@ -1754,7 +1971,15 @@ pub async fn cli() -> anyhow::Result<()> {
         }
         Some(Command::Recipe { command }) => handle_recipe_subcommand(command),
         Some(Command::Term { command }) => handle_term_subcommand(command).await,
+        Some(Command::Extensions { command }) => handle_extensions_command(command).await,
         Some(Command::LocalModels { command }) => handle_local_models_command(command).await,
+        Some(Command::Eval {
+            suite,
+            targets,
+            recordings,
+            replay_only,
+            report,
+        }) => handle_eval(&suite, targets, recordings, replay_only, report).await,
         Some(Command::ValidateExtensions { file }) => {
             use goose::agents::validate_extensions::validate_bundled_extensions;
             match validate_bundled_extensions(&file) {
@@ -2,7 +2,9 @@ use crate::recipes::github_recipe::GOOSE_RECIPE_GITHUB_REPO_CONFIG_KEY;
 use cliclack::spinner;
 use console::style;
 use goose::agents::extension::{ToolInfo, PLATFORM_EXTENSIONS};
+use goose::agents::extension_capabilities::CapabilityManifest;
 use goose::agents::extension_manager::get_parameter_names;
+use goose::agents::lifecycle::{self, LifecycleEvent};
 use goose::agents::Agent;
 use goose::agents::{extension::Envs, ExtensionConfig};
 use goose::config::declarative_providers::{
@@ -22,7 +24,10 @@ use goose::config::{
 use goose::model::ModelConfig;
 use goose::posthog::{get_telemetry_choice, TELEMETRY_ENABLED_KEY};
 use goose::providers::base::ConfigKey;
+use goose::providers::cli_common::{cli_login_available, run_cli_login};
+use goose::providers::errors::ProviderError;
 use goose::providers::formats::anthropic::supports_adaptive_thinking;
+use goose::providers::ollama::OllamaProvider;
 use goose::providers::provider_test::test_provider_configuration;
 use goose::providers::{create, providers, retry_operation, RetryConfig};
 use goose::session::SessionType;
@@ -754,6 +759,10 @@ pub async fn configure_provider_dialog() -> anyhow::Result<bool> {
         }
     };
 
+    if provider_name == "ollama" && !ensure_ollama_model(&model).await? {
+        return Ok(false);
+    }
+
     if model.to_lowercase().starts_with("gemini-3") {
         let thinking_level: &str = cliclack::select("Select thinking level for Gemini 3:")
             .item("low", "Low - Better latency, lighter reasoning", "")
@@ -810,7 +819,7 @@ pub async fn configure_provider_dialog() -> anyhow::Result<bool> {
     }
 
     // Test the configuration
-    let spin = spinner();
+    let mut spin = spinner();
     spin.start("Checking your configuration...");
 
     let toolshim_enabled = std::env::var("GOOSE_TOOLSHIM")
@@ -818,8 +827,38 @@ pub async fn configure_provider_dialog() -> anyhow::Result<bool> {
         .unwrap_or(false);
     let toolshim_model = std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL").ok();
 
-    match test_provider_configuration(provider_name, &model, toolshim_enabled, toolshim_model).await
-    {
+    let mut result = test_provider_configuration(
+        provider_name,
+        &model,
+        toolshim_enabled,
+        toolshim_model.clone(),
+    )
+    .await;
+    if let Err(e) = &result {
+        let needs_sign_in = matches!(
+            e.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Authentication(_))
+        );
+        if needs_sign_in && cli_login_available(provider_name) {
+            spin.stop(style(e.to_string()).yellow());
+            let sign_in = cliclack::confirm(format!("Sign in to {} now?", provider_name))
+                .initial_value(true)
+                .interact()?;
+            if sign_in && run_cli_login(provider_name).await? {
+                spin = spinner();
+                spin.start("Checking your configuration...");
+                result = test_provider_configuration(
+                    provider_name,
+                    &model,
+                    toolshim_enabled,
+                    toolshim_model,
+                )
+                .await;
+            }
+        }
+    }
+
+    match result {
         Ok(()) => {
             config.set_goose_provider(provider_name)?;
             config.set_goose_model(&model)?;
@@ -834,6 +873,63 @@ pub async fn configure_provider_dialog() -> anyhow::Result<bool> {
     }
 }
 
+/// Offer to pull an Ollama model that isn't installed yet. Returns false when the model is
+/// still missing.
+async fn ensure_ollama_model(model: &str) -> anyhow::Result<bool> {
+    const PULL_SESSION_ID: &str = "goose-configure";
+
+    let provider = OllamaProvider::from_env(ModelConfig::new(model)?).await?;
+    match provider.is_installed(model).await {
+        Ok(false) => {}
+        // Installed, or the server can't say; the configuration check reports the latter
+        _ => return Ok(true),
+    }
+    let pull = cliclack::confirm(format!("{} isn't installed in Ollama. Pull it now?", model))
+        .initial_value(true)
+        .interact()?;
+    if !pull {
+        return Ok(false);
+    }
+
+    let mut events = lifecycle::subscribe();
+    let spin = spinner();
+    spin.start(format!("Pulling {}...", model));
+    let pull = provider.pull_model(model, PULL_SESSION_ID);
+    tokio::pin!(pull);
+    let result = loop {
+        tokio::select! {
+            result = &mut pull => break result,
+            Ok(event) = events.recv() => {
+                if let LifecycleEvent::ModelPullProgress {
+                    session_id, status, completed, total, ..
+                } = event
+                {
+                    if session_id != PULL_SESSION_ID {
+                        continue;
+                    }
+                    let message = match (completed, total) {
+                        (Some(completed), Some(total)) if total > 0 => {
+                            format!("Pulling {}: {} {}%", model, status, completed * 100 / total)
+                        }
+                        _ => format!("Pulling {}: {}", model, status),
+                    };
+                    spin.set_message(message);
+                }
+            }
+        }
+    };
+    match result {
+        Ok(()) => {
+            spin.stop(style(format!("Pulled {}", model)).green());
+            Ok(true)
+        }
+        Err(e) => {
+            spin.stop(style(e.to_string()).red());
+            Ok(false)
+        }
+    }
+}
+
 /// Configure extensions that can be used with goose
 /// Dialog for toggling which extensions are enabled/disabled
 pub fn toggle_extensions_dialog() -> anyhow::Result<()> {
@@ -997,6 +1093,40 @@ fn collect_headers() -> anyhow::Result<HashMap<String, String>> {
     Ok(headers)
 }
 
+/// Declare what a third-party extension may do; calls beyond that are held for approval
+fn prompt_capability_manifest(name: &str, credentials: &[String]) -> anyhow::Result<()> {
+    if !cliclack::confirm("Would you like to limit what this extension is allowed to do?")
+        .initial_value(true)
+        .interact()?
+    {
+        return Ok(());
+    }
+
+    let selected: Vec<&str> = cliclack::multiselect(
+        "allow capabilities: (use \"space\" to toggle and \"enter\" to submit)",
+    )
+    .required(false)
+    .item("network", "Network access", "reach hosts on the network")
+    .item(
+        "filesystem_write",
+        "Filesystem writes",
+        "create, change or delete files",
+    )
+    .interact()?;
+
+    let manifest = CapabilityManifest {
+        network: selected.contains(&"network"),
+        filesystem_write: selected.contains(&"filesystem_write"),
+        credentials: credentials.to_vec(),
+    };
+    cliclack::note(
+        format!("{} capabilities", name),
+        manifest.summary().join("\n"),
+    )?;
+    CapabilityManifest::save_for_extension(name, manifest)?;
+    Ok(())
+}
+
 fn configure_builtin_extension() -> anyhow::Result<()> {
     let extensions = vec![
         (
@@ -1088,6 +1218,7 @@ fn configure_stdio_extension() -> anyhow::Result<()> {
 
     let description = prompt_extension_description()?;
     let (envs, env_keys) = collect_env_vars()?;
+    prompt_capability_manifest(&name, &env_keys)?;
 
     set_extension(ExtensionEntry {
         enabled: true,
@@ -1127,6 +1258,7 @@ fn configure_streamable_http_extension() -> anyhow::Result<()> {
     let timeout = prompt_extension_timeout()?;
     let description = prompt_extension_description()?;
     let headers = collect_headers()?;
+    prompt_capability_manifest(&name, &[])?;
 
     // Original behavior: no env var collection for Streamable HTTP
     let envs = HashMap::new();
@@ -0,0 +1,37 @@
+use anyhow::Result;
+use console::style;
+use goose::config::doctor::{diagnose, Severity};
+
+pub async fn handle_doctor() -> Result<()> {
+    let working_dir = std::env::current_dir()?;
+    let diagnostics = diagnose(&working_dir).await;
+
+    if diagnostics.is_empty() {
+        println!("{} No problems found", style("✓").green().bold());
+        return Ok(());
+    }
+
+    for diagnostic in &diagnostics {
+        let label = match diagnostic.severity {
+            Severity::Error => style("error").red().bold(),
+            Severity::Warning => style("warning").yellow().bold(),
+            Severity::Info => style("info").cyan(),
+        };
+        println!("{} [{}] {}", label, diagnostic.check, diagnostic.message);
+        if let Some(subject) = &diagnostic.subject {
+            println!("  {} {}", style("at:").dim(), subject);
+        }
+        if let Some(fix) = &diagnostic.fix {
+            println!("  {} {}", style("fix:").dim(), fix);
+        }
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!("{} configuration problem(s) need fixing", errors);
+    }
+    Ok(())
+}
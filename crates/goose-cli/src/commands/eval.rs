@@ -0,0 +1,72 @@
+use anyhow::{bail, Result};
+use console::style;
+use goose::config::Config;
+use goose::eval::{run_suite, EvalOptions, EvalSuite, EvalTarget};
+use std::path::{Path, PathBuf};
+
+pub async fn handle_eval(
+    suite_path: &Path,
+    targets: Vec<String>,
+    recordings: Option<PathBuf>,
+    replay_only: bool,
+    report: Option<PathBuf>,
+) -> Result<()> {
+    let suite = EvalSuite::from_file(suite_path)?;
+    let suite_dir = suite_path.parent().unwrap_or(Path::new("."));
+
+    let targets = if !targets.is_empty() {
+        targets
+            .iter()
+            .map(|spec| EvalTarget::parse(spec))
+            .collect::<Result<Vec<_>>>()?
+    } else if !suite.targets.is_empty() {
+        suite.targets.clone()
+    } else {
+        let config = Config::global();
+        vec![EvalTarget {
+            provider: config.get_goose_provider()?,
+            model: config.get_goose_model()?,
+        }]
+    };
+    if replay_only && recordings.is_none() {
+        bail!("--replay-only needs --recordings");
+    }
+
+    let options = EvalOptions {
+        recordings_dir: recordings,
+        replay_only,
+        ..EvalOptions::default()
+    };
+    println!(
+        "Running {} case(s) of '{}' against {}",
+        suite.cases.len(),
+        suite.name,
+        targets
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let result = run_suite(&suite, suite_dir, &targets, &options).await;
+
+    let markdown = result.to_markdown();
+    println!("\n{}", markdown);
+    if let Some(path) = report {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::to_string_pretty(&result)?,
+            _ => markdown,
+        };
+        std::fs::write(&path, content)?;
+        println!("Report written to {}", path.display());
+    }
+
+    if !result.passed() {
+        bail!(
+            "{} of {} eval run(s) failed",
+            result.results.iter().filter(|r| !r.passed()).count(),
+            result.results.len()
+        );
+    }
+    println!("{}", style("All eval runs passed").green());
+    Ok(())
+}
@@ -57,6 +57,9 @@ pub fn handle_info(verbose: bool) -> Result<()> {
 
     println!("{}", style("goose Version:").cyan().bold());
     print_aligned("Version:", env!("CARGO_PKG_VERSION"), label_padding);
+    if let Some(profile) = config.active_profile_name() {
+        print_aligned("Profile:", &profile, label_padding);
+    }
     println!();
 
     println!("{}", style("Paths:").cyan().bold());
@@ -92,3 +95,9 @@ pub fn handle_info(verbose: bool) -> Result<()> {
 
     Ok(())
 }
+
+pub fn handle_config_schema() -> Result<()> {
+    let schema = goose::config::schema::config_json_schema();
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
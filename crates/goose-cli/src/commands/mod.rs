@@ -1,4 +1,6 @@
 pub mod configure;
+pub mod doctor;
+pub mod eval;
 pub mod gateway;
 pub mod info;
 pub mod project;
@@ -1,7 +1,7 @@
 use anyhow::{bail, Context, Result};
 use goose::scheduler::{
-    get_default_scheduled_recipes_dir, get_default_scheduler_storage_path, ScheduledJob, Scheduler,
-    SchedulerError,
+    get_default_scheduled_recipes_dir, get_default_scheduler_storage_path, JobRunSettings,
+    ScheduledJob, Scheduler, SchedulerError,
 };
 use goose::session::SessionManager;
 use std::path::Path;
@@ -69,6 +69,7 @@ pub async fn handle_schedule_add(
     schedule_id: String,
     cron: String,
     recipe_source_arg: String, // This is expected to be a file path by the Scheduler
+    settings: JobRunSettings,
 ) -> Result<()> {
     println!(
         "[CLI Debug] Scheduling job ID: {}, Cron: {}, Recipe Source Path: {}",
@@ -88,6 +89,8 @@ pub async fn handle_schedule_add(
         paused: false,
         current_session_id: None,
         process_start_time: None,
+        settings,
+        last_run_status: None,
     };
 
     let scheduler_storage_path =
@@ -167,6 +170,22 @@ pub async fn handle_schedule_list() -> Result<()> {
                 job.last_run
                     .map_or_else(|| "Never".to_string(), |dt| dt.to_rfc3339())
             );
+            if let Some(last) = &job.last_run_status {
+                println!(
+                    "  Last Result: {:?}{}",
+                    last.outcome,
+                    last.error
+                        .as_ref()
+                        .map_or_else(String::new, |e| format!(" ({})", e))
+                );
+            }
+            if let Some(follow_up) = job
+                .last_run_status
+                .as_ref()
+                .and_then(|s| s.follow_up.as_ref())
+            {
+                println!("  Needs Follow-up: {}", follow_up);
+            }
         }
     }
     Ok(())
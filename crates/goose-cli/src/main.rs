@@ -1,5 +1,10 @@
 use anyhow::Result;
+use goose::shutdown::ShutdownCoordinator;
 use goose_cli::cli::cli;
+use goose_cli::signal::terminate_signal;
+
+/// Exit code for a process ended by SIGTERM
+const TERMINATED_EXIT_CODE: i32 = 143;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -7,12 +12,16 @@ async fn main() -> Result<()> {
         eprintln!("Warning: Failed to initialize logging: {}", e);
     }
 
-    let result = cli().await;
+    let result = tokio::select! {
+        result = cli() => Some(result),
+        _ = terminate_signal() => None,
+    };
 
-    if goose::otel::otlp::is_otlp_initialized() {
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        goose::otel::otlp::shutdown_otlp();
-    }
+    let Some(result) = result else {
+        ShutdownCoordinator::global().shutdown("terminated").await;
+        std::process::exit(TERMINATED_EXIT_CODE);
+    };
+    ShutdownCoordinator::global().shutdown("exit").await;
 
     result
 }
@@ -52,7 +52,7 @@ pub fn extract_recipe_info_from_cli(
 
     let input_config = InputConfig {
         contents: recipe.prompt.clone().filter(|s| !s.trim().is_empty()),
-        additional_system_prompt: recipe.instructions.clone(),
+        additional_system_prompt: recipe.system_instructions(),
     };
 
     Ok((input_config, recipe))
@@ -178,6 +178,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            success_criteria: None,
         }
     }
 
@@ -221,6 +222,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            success_criteria: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
@@ -266,6 +268,7 @@ mod tests {
             response: None,
             sub_recipes: None,
             retry: None,
+            success_criteria: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
@@ -319,6 +322,7 @@ mod tests {
             parameters: None,
             response: None,
             retry: None,
+            success_criteria: None,
         };
 
         let secrets = discover_recipe_secrets(&recipe);
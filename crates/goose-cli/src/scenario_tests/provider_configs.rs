@@ -87,8 +87,10 @@ static PROVIDER_CONFIGS: LazyLock<Vec<ProviderConfig>> = LazyLock::new(|| {
             Some("No keys available"),
         ),
         ProviderConfig::simple_skip("gemini-cli", "gemini-2.5-flash", Some("No keys available")),
+        ProviderConfig::simple_skip("iflow", "glm-4.6", Some("No keys available")),
         ProviderConfig::simple_skip("litellm", "gpt-4o", Some("No keys available")),
         ProviderConfig::simple_skip("ollama", "qwen3", Some("Ollama not supported")),
+        ProviderConfig::simple_skip("qwen-code", "qwen3-coder-plus", Some("No keys available")),
         ProviderConfig::simple_skip(
             "sagemaker_tgi",
             "meta-llama/Llama-2-7b-chat-hf",
@@ -1,11 +1,13 @@
 use crate::cli::StreamableHttpOptions;
 
 use super::output;
+use super::startup::{timed, StartupReport};
 use super::CliSession;
 use console::style;
 use goose::agents::{Agent, Container, ExtensionError};
 use goose::config::resolve_extensions_for_new_session;
 use goose::config::{get_all_extensions, Config, ExtensionConfig};
+use goose::providers::canonical::CanonicalModelRegistry;
 use goose::providers::create;
 use goose::recipe::Recipe;
 use goose::session::session_manager::SessionType;
@@ -13,6 +15,7 @@ use goose::session::EnabledExtensionsState;
 use rustyline::EditMode;
 use std::collections::BTreeSet;
 use std::io::IsTerminal;
+use std::path::Path;
 use std::process;
 use std::sync::Arc;
 use tokio::task::JoinSet;
@@ -277,22 +280,24 @@ async fn offer_extension_debugging_help(
     Ok(())
 }
 
-async fn load_extensions(
-    agent: Agent,
-    extensions_to_load: Vec<(String, ExtensionConfig)>,
-    provider_for_debug: Arc<dyn goose::providers::base::Provider>,
-    interactive: bool,
+/// Start every extension at once, recording how long each took. Returns the failures.
+async fn start_extensions(
+    agent_ptr: &Arc<Agent>,
+    extensions_to_load: &[(String, ExtensionConfig)],
     session_id: &str,
-) -> Arc<Agent> {
+    report: &mut StartupReport,
+) -> Vec<(usize, anyhow::Error)> {
     let mut set = JoinSet::new();
-    let agent_ptr = Arc::new(agent);
 
     let mut waiting_ids: BTreeSet<usize> = (0..extensions_to_load.len()).collect();
     for (id, (_label, extension)) in extensions_to_load.iter().enumerate() {
         let agent_ptr = agent_ptr.clone();
         let cfg = extension.clone();
         let sid = session_id.to_string();
-        set.spawn(async move { (id, agent_ptr.add_extension(cfg, &sid).await) });
+        set.spawn(async move {
+            let (result, elapsed) = timed(agent_ptr.add_extension(cfg, &sid)).await;
+            (id, result, elapsed)
+        });
     }
 
     let get_message = |waiting_ids: &BTreeSet<usize>| {
@@ -315,21 +320,40 @@ async fn load_extensions(
     let spinner = cliclack::spinner();
     spinner.start(get_message(&waiting_ids));
 
-    let mut offer_debug: Vec<(usize, anyhow::Error)> = Vec::new();
+    let mut failures: Vec<(usize, anyhow::Error)> = Vec::new();
     while let Some(result) = set.join_next().await {
         match result {
-            Ok((id, Ok(_))) => {
-                waiting_ids.remove(&id);
-                spinner.set_message(get_message(&waiting_ids));
+            Ok((id, result, elapsed)) => {
+                let label = format!("extension {}", extensions_to_load[id].0);
+                match result {
+                    Ok(_) => {
+                        report.record(label, elapsed, None);
+                        waiting_ids.remove(&id);
+                        spinner.set_message(get_message(&waiting_ids));
+                    }
+                    Err(e) => {
+                        report.record(label, elapsed, Some(e.to_string()));
+                        failures.push((id, e.into()));
+                    }
+                }
             }
-            Ok((id, Err(e))) => offer_debug.push((id, e.into())),
             Err(e) => tracing::error!("failed to add extension: {}", e),
         }
     }
 
     spinner.clear();
+    failures
+}
 
-    for (id, err) in offer_debug {
+async fn report_extension_failures(
+    agent_ptr: &Arc<Agent>,
+    extensions_to_load: &[(String, ExtensionConfig)],
+    failures: Vec<(usize, anyhow::Error)>,
+    provider_for_debug: Arc<dyn goose::providers::base::Provider>,
+    interactive: bool,
+    session_id: &str,
+) {
+    for (id, err) in failures {
         let label = extensions_to_load
             .get(id)
             .map(|e| e.0.clone())
@@ -355,7 +379,9 @@ async fn load_extensions(
         }
     }
 
-    agent_ptr
+    for conflict in agent_ptr.extension_manager.tool_conflicts(session_id).await {
+        eprintln!("{}", style(format!("Warning: {}", conflict)).yellow());
+    }
 }
 
 struct ResolvedProviderConfig {
@@ -456,6 +482,53 @@ async fn resolve_session_id(
     }
 }
 
+/// Apply the project config of `working_dir`, asking the user to trust it first if needed
+fn confirm_project_config(config: &Config, working_dir: &Path, interactive: bool) {
+    let project = match config.set_project_dir(working_dir) {
+        None => return,
+        Some(Ok(project)) => project,
+        Some(Err(e)) => {
+            output::render_error(&format!("Ignoring the project config: {}", e));
+            return;
+        }
+    };
+    if config.is_project_config_trusted(&project) {
+        return;
+    }
+    let changes = project.summary();
+    if changes.is_empty() {
+        return;
+    }
+
+    let path = style(project.path.display()).cyan();
+    if !interactive || !std::io::stdin().is_terminal() {
+        eprintln!(
+            "{}",
+            style(format!(
+                "Warning: {} is not trusted, so its settings are not applied. \
+                 Start an interactive session here to review it.",
+                project.path.display()
+            ))
+            .yellow()
+        );
+        return;
+    }
+
+    let trust = cliclack::confirm(format!(
+        "This project's config at {} changes these settings:\n  {}\nApply it to sessions in this project?",
+        path,
+        changes.join("\n  ")
+    ))
+    .initial_value(false)
+    .interact()
+    .unwrap_or(false);
+    if trust {
+        if let Err(e) = config.trust_project_config(&project) {
+            output::render_error(&format!("Failed to save trust for {}: {}", path, e));
+        }
+    }
+}
+
 async fn handle_resumed_session_workdir(agent: &Agent, session_id: &str, interactive: bool) {
     let session = agent
         .config
@@ -543,32 +616,6 @@ async fn collect_extension_configs(
     Ok(all)
 }
 
-async fn resolve_and_load_extensions(
-    agent: Agent,
-    extensions: Vec<ExtensionConfig>,
-    provider_for_debug: Arc<dyn goose::providers::base::Provider>,
-    interactive: bool,
-    session_id: &str,
-) -> Arc<Agent> {
-    for warning in goose::config::get_warnings() {
-        eprintln!("{}", style(format!("Warning: {}", warning)).yellow());
-    }
-
-    let extensions_to_load: Vec<(String, ExtensionConfig)> = extensions
-        .into_iter()
-        .map(|cfg| (cfg.name(), cfg))
-        .collect();
-
-    load_extensions(
-        agent,
-        extensions_to_load,
-        provider_for_debug,
-        interactive,
-        session_id,
-    )
-    .await
-}
-
 async fn configure_session_prompts(
     session: &CliSession,
     config: &Config,
@@ -606,17 +653,18 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
 
     let session_manager = agent.config.session_manager.clone();
 
-    let (saved_provider, saved_model_config) = if session_config.resume {
-        if let Some(ref session_id) = session_config.session_id {
-            match session_manager.get_session(session_id, false).await {
-                Ok(session_data) => (session_data.provider_name, session_data.model_config),
-                Err(_) => (None, None),
-            }
-        } else {
-            (None, None)
-        }
-    } else {
-        (None, None)
+    let saved_session = match (session_config.resume, &session_config.session_id) {
+        (true, Some(session_id)) => session_manager.get_session(session_id, false).await.ok(),
+        _ => None,
+    };
+    let project_dir = match &saved_session {
+        Some(session_data) => session_data.working_dir.clone(),
+        None => std::env::current_dir().expect("Failed to get current working directory"),
+    };
+    confirm_project_config(config, &project_dir, session_config.interactive);
+    let (saved_provider, saved_model_config) = match saved_session {
+        Some(session_data) => (session_data.provider_name, session_data.model_config),
+        None => (None, None),
     };
 
     let resolved =
@@ -643,13 +691,50 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
             }
         };
 
-    let new_provider = match create(
-        &resolved.provider_name,
-        resolved.model_config,
-        extensions_for_provider.clone(),
-    )
-    .await
-    {
+    for warning in goose::config::get_warnings() {
+        eprintln!("{}", style(format!("Warning: {}", warning)).yellow());
+    }
+    let extensions_to_load: Vec<(String, ExtensionConfig)> = extensions_for_provider
+        .iter()
+        .map(|cfg| (cfg.name(), cfg.clone()))
+        .collect();
+    let extensions_for_provider = agent
+        .extensions_for_provider(
+            &resolved.provider_name,
+            &session_id,
+            extensions_for_provider,
+        )
+        .await;
+
+    // The provider, the model registry and the extensions do not depend on each other, so
+    // they are started together. Extensions pick the provider up once it is set.
+    let agent_ptr = Arc::new(agent);
+    let mut startup = StartupReport::new();
+    let ((provider, provider_elapsed), (registry, registry_elapsed), extension_failures) = tokio::join!(
+        timed(create(
+            &resolved.provider_name,
+            resolved.model_config,
+            extensions_for_provider,
+        )),
+        timed(tokio::task::spawn_blocking(|| {
+            CanonicalModelRegistry::bundled()
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })),
+        start_extensions(&agent_ptr, &extensions_to_load, &session_id, &mut startup),
+    );
+    startup.record(
+        "provider",
+        provider_elapsed,
+        provider.as_ref().err().map(|e| e.to_string()),
+    );
+    startup.record(
+        "model registry",
+        registry_elapsed,
+        registry.unwrap_or_else(|e| Err(e.to_string())).err(),
+    );
+
+    let new_provider = match provider {
         Ok(provider) => provider,
         Err(e) => {
             output::render_error(&format!(
@@ -675,7 +760,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         tracing::info!("🤖 Using model: {}", resolved.model_name);
     }
 
-    agent
+    agent_ptr
         .update_provider(new_provider, &session_id)
         .await
         .unwrap_or_else(|e| {
@@ -694,10 +779,10 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
         }
     }
 
-    // Extensions are loaded after session creation because we may change directory when resuming
-    let agent_ptr = resolve_and_load_extensions(
-        agent,
-        extensions_for_provider,
+    report_extension_failures(
+        &agent_ptr,
+        &extensions_to_load,
+        extension_failures,
         Arc::clone(&provider_for_display),
         session_config.interactive,
         &session_id,
@@ -744,6 +829,10 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> CliSession {
             Some(&provider_for_display),
         );
     }
+    startup.log();
+    if debug_mode {
+        eprintln!("{}", style(startup.summary()).dim());
+    }
     session
 }
 
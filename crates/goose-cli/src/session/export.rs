@@ -1,5 +1,6 @@
 use goose::conversation::message::{
-    ActionRequiredData, Message, MessageContent, ToolRequest, ToolResponse,
+    ActionRequiredData, AnnotationContent, CitationSource, Message, MessageContent, ToolRequest,
+    ToolResponse,
 };
 use goose::utils::safe_truncate;
 use rmcp::model::{RawContent, ResourceContents, Role};
@@ -220,7 +221,7 @@ pub fn tool_response_to_markdown(resp: &ToolResponse, export_all_content: bool)
 
     match &resp.tool_result {
         Ok(result) => {
-            if result.content.is_empty() {
+            if result.content.is_empty() && result.structured_content.is_none() {
                 md.push_str("*No textual output from tool.*\n");
             }
 
@@ -324,6 +325,30 @@ pub fn tool_response_to_markdown(resp: &ToolResponse, export_all_content: bool)
                     }
                 }
             }
+
+            // Tools usually also send structured content serialized as text; show it once
+            if let Some(structured) = &result.structured_content {
+                let shown_as_text = result.content.iter().any(|content| {
+                    content
+                        .as_text()
+                        .and_then(|text| serde_json::from_str::<Value>(&text.text).ok())
+                        .is_some_and(|value| &value == structured)
+                });
+                if !shown_as_text {
+                    md.push_str(&format!(
+                        "**Structured content:**\n```json\n{}\n```\n",
+                        serde_json::to_string_pretty(structured).unwrap_or_default()
+                    ));
+                }
+            }
+            if export_all_content {
+                if let Some(meta) = result.meta.as_ref().filter(|meta| !meta.0.is_empty()) {
+                    md.push_str(&format!(
+                        "**Metadata:**\n```json\n{}\n```\n",
+                        serde_json::to_string_pretty(&meta.0).unwrap_or_default()
+                    ));
+                }
+            }
         }
         Err(e) => {
             md.push_str(&format!(
@@ -336,6 +361,33 @@ pub fn tool_response_to_markdown(resp: &ToolResponse, export_all_content: bool)
     md
 }
 
+fn annotation_to_markdown(annotation: &AnnotationContent) -> String {
+    let source = match &annotation.source {
+        CitationSource::Url {
+            url,
+            title: Some(title),
+        } => format!("[{}]({})", title, url),
+        CitationSource::Url { url, title: None } => format!("<{}>", url),
+        CitationSource::File {
+            path,
+            start_line: Some(start),
+            end_line,
+        } => format!("`{}` lines {}-{}", path, start, end_line.unwrap_or(*start)),
+        CitationSource::File { path, .. } => format!("`{}`", path),
+        CitationSource::ToolResult { tool_call_id } => {
+            format!("result of tool call `{}`", tool_call_id)
+        }
+    };
+    match &annotation.cited_text {
+        Some(quote) => format!(
+            "**Source:** {}\n> {}\n\n",
+            source,
+            quote.trim().replace('\n', "\n> ")
+        ),
+        None => format!("**Source:** {}\n\n", source),
+    }
+}
+
 pub fn message_to_markdown(message: &Message, export_all_content: bool) -> String {
     let mut md = String::new();
     for content in &message.content {
@@ -394,6 +446,15 @@ pub fn message_to_markdown(message: &Message, export_all_content: bool) -> Strin
             MessageContent::SystemNotification(notification) => {
                 md.push_str(&format!("*{}*\n\n", notification.msg));
             }
+            MessageContent::Attachment(attachment) => {
+                md.push_str(&format!(
+                    "**Attachment:** `{}` ({}, {} bytes)\n\n",
+                    attachment.name, attachment.mime_type, attachment.size
+                ));
+            }
+            MessageContent::Annotation(annotation) => {
+                md.push_str(&annotation_to_markdown(annotation));
+            }
             _ => {
                 md.push_str(
                     "`WARNING: Message content type could not be rendered to Markdown`\n\n",
@@ -612,6 +673,30 @@ mod tests {
         assert!(result.contains(json_text));
     }
 
+    #[test]
+    fn test_tool_response_to_markdown_structured_content() {
+        let mut result = rmcp::model::CallToolResult::success(vec![]);
+        result.structured_content = Some(serde_json::json!({"files": ["a.rs"]}));
+        let tool_response = ToolResponse {
+            metadata: None,
+            id: "test-id".to_string(),
+            tool_result: Ok(result.clone()),
+        };
+        let md = tool_response_to_markdown(&tool_response, true);
+        assert!(md.contains("**Structured content:**"));
+        assert!(md.contains("\"a.rs\""));
+        assert!(!md.contains("No textual output"));
+
+        // Not repeated when the text already holds it
+        result.content = vec![Content::text(r#"{"files": ["a.rs"]}"#)];
+        let tool_response = ToolResponse {
+            tool_result: Ok(result),
+            ..tool_response
+        };
+        let md = tool_response_to_markdown(&tool_response, true);
+        assert!(!md.contains("**Structured content:**"));
+    }
+
     #[test]
     fn test_message_to_markdown_text() {
         let message = Message::user().with_text("Hello, this is a test message");
@@ -1160,4 +1245,23 @@ found 0 vulnerabilities"#;
         assert!(response_result.contains("added 57 packages"));
         assert!(response_result.contains("found 0 vulnerabilities"));
     }
+
+    #[test]
+    fn test_message_to_markdown_keeps_citations() {
+        let message = Message::assistant()
+            .with_text("Rust 1.0 shipped in May 2015.")
+            .with_annotation(
+                AnnotationContent::new(CitationSource::Url {
+                    url: "https://blog.rust-lang.org/2015/05/15/Rust-1.0.html".to_string(),
+                    title: Some("Announcing Rust 1.0".to_string()),
+                })
+                .with_cited_text("We are very proud to announce the 1.0 release of Rust"),
+            );
+
+        let md = message_to_markdown(&message, true);
+        assert!(md.contains(
+            "**Source:** [Announcing Rust 1.0](https://blog.rust-lang.org/2015/05/15/Rust-1.0.html)"
+        ));
+        assert!(md.contains("> We are very proud"));
+    }
 }
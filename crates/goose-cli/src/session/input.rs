@@ -26,6 +26,7 @@ pub enum InputResult {
     Recipe(Option<String>),
     Compact,
     ToggleFullToolOutput,
+    Attach(String),
 }
 
 #[derive(Debug)]
@@ -240,6 +241,7 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
     const CMD_CLEAR: &str = "/clear";
     const CMD_RECIPE: &str = "/recipe";
     const CMD_COMPACT: &str = "/compact";
+    const CMD_ATTACH: &str = "/attach ";
     const CMD_SUMMARIZE_DEPRECATED: &str = "/summarize";
 
     match input {
@@ -304,6 +306,9 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s == CMD_CLEAR => Some(InputResult::Clear),
         s if s.starts_with(CMD_RECIPE) => parse_recipe_command(s),
         s if s == CMD_COMPACT => Some(InputResult::Compact),
+        s if s.starts_with(CMD_ATTACH) => Some(InputResult::Attach(
+            s.get(CMD_ATTACH.len()..).unwrap_or("").trim().to_string(),
+        )),
         s if s == CMD_SUMMARIZE_DEPRECATED => {
             println!("{}", console::style("⚠️  Note: /summarize has been renamed to /compact and will be removed in a future release.").yellow());
             Some(InputResult::Compact)
@@ -430,6 +435,7 @@ fn print_help() {
 /recipe [filepath] - Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
                        If no filepath is provided, it will be saved to ./recipe.yaml.
 /compact - Compact the current conversation to reduce context length while preserving key information.
+/attach <path> - Attach a file to your next message
 /? or /help - Display this help message
 /clear - Clears the current chat history
 
@@ -513,6 +519,13 @@ mod tests {
             panic!("Expected AddBuiltin");
         }
 
+        // Test attach command
+        if let Some(InputResult::Attach(path)) = handle_slash_command("/attach notes.md ") {
+            assert_eq!(path, "notes.md");
+        } else {
+            panic!("Expected Attach");
+        }
+
         // Test unknown commands
         assert!(handle_slash_command("/unknown").is_none());
     }
@@ -5,6 +5,7 @@ mod elicitation;
 mod export;
 mod input;
 mod output;
+mod startup;
 pub mod status_bar;
 pub mod streaming_buffer;
 mod task_execution_display;
@@ -44,13 +45,16 @@ use rmcp::model::{ErrorCode, ErrorData};
 use strum::VariantNames;
 
 use goose::config::paths::Paths;
-use goose::conversation::message::{ActionRequiredData, Message, MessageContent};
+use goose::conversation::attachments::AttachmentStore;
+use goose::conversation::message::{
+    ActionRequiredData, AttachmentContent, Message, MessageContent, SystemNotificationType,
+};
 use rustyline::EditMode;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio;
@@ -171,6 +175,7 @@ pub struct CliSession {
     output_format: String,
     tui_enabled: bool,
     status_bar: Option<status_bar::StatusBar>,
+    pending_attachments: Vec<AttachmentContent>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -271,6 +276,7 @@ impl CliSession {
             output_format,
             tui_enabled,
             status_bar: None,
+            pending_attachments: Vec::new(),
         }
     }
 
@@ -640,6 +646,10 @@ impl CliSession {
                 history.save(editor);
                 self.handle_compact().await?;
             }
+            InputResult::Attach(path) => {
+                history.save(editor);
+                self.handle_attach(&path);
+            }
         }
         Ok(())
     }
@@ -653,7 +663,13 @@ impl CliSession {
         match self.run_mode {
             RunMode::Normal => {
                 history.save(editor);
-                self.push_message(Message::user().with_text(content));
+                let message = self
+                    .pending_attachments
+                    .drain(..)
+                    .fold(Message::user().with_text(content), |message, attachment| {
+                        message.with_attachment(attachment)
+                    });
+                self.push_message(message);
 
                 if let Err(e) = crate::project_tracker::update_project_tracker(
                     Some(content),
@@ -690,6 +706,27 @@ impl CliSession {
         Ok(())
     }
 
+    fn handle_attach(&mut self, path: &str) {
+        if path.is_empty() {
+            output::render_error("Usage: /attach <path>");
+            return;
+        }
+        match AttachmentStore::for_session(&self.session_id).add_file(Path::new(path)) {
+            Ok(attachment) => {
+                println!(
+                    "{}",
+                    console::style(format!(
+                        "📎 {} will be attached to your next message",
+                        attachment.name
+                    ))
+                    .dim()
+                );
+                self.pending_attachments.push(attachment);
+            }
+            Err(e) => output::render_error(&format!("{:#}", e)),
+        }
+    }
+
     fn handle_toggle_theme(&self) {
         let current = output::get_theme();
         let new_theme = match current {
@@ -1073,6 +1110,8 @@ impl CliSession {
                                         break;
                                     }
                                 }
+                            } else if is_tool_progress(&message) {
+                                continue;
                             } else {
                                 log_tool_metrics(&message, &self.messages);
                                 self.messages.push(message.clone());
@@ -1639,6 +1678,21 @@ fn prompt_tool_confirmation(security_prompt: &Option<String>) -> Result<Permissi
     let permission_result = if security_prompt.is_none() {
         cliclack::select(prompt)
             .item(Permission::AllowOnce, "Allow", "Allow the tool call once")
+            .item(
+                Permission::AllowForSession,
+                "Allow for Session",
+                "Allow this tool for the rest of this session",
+            )
+            .item(
+                Permission::AllowForHour,
+                "Allow for 1 Hour",
+                "Allow this tool in any session for the next hour",
+            )
+            .item(
+                Permission::AllowForProject,
+                "Allow in Project",
+                "Allow this tool in sessions in this project directory",
+            )
             .item(
                 Permission::AlwaysAllow,
                 "Always Allow",
@@ -1704,6 +1758,17 @@ fn find_elicitation_request(message: &Message) -> Option<(String, String, Value)
     })
 }
 
+/// Whether a message only carries tool progress, which the CLI shows as progress bars
+/// from the MCP notification instead
+fn is_tool_progress(message: &Message) -> bool {
+    !message.content.is_empty()
+        && message.content.iter().all(|content| {
+            content
+                .as_system_notification()
+                .is_some_and(|n| n.notification_type == SystemNotificationType::ToolProgress)
+        })
+}
+
 /// Handle MCP notification event (logging or progress)
 fn handle_mcp_notification(
     extension_id: &str,
@@ -1990,6 +2055,19 @@ fn handle_agent_error(e: &anyhow::Error, is_stream_json_mode: bool) {
     if !is_stream_json_mode {
         eprintln!("Error: {}", error_msg);
     }
+
+    if !is_stream_json_mode
+        && matches!(
+            e.downcast_ref::<goose::providers::errors::ProviderError>(),
+            Some(goose::providers::errors::ProviderError::ModelNotFound { .. })
+        )
+    {
+        output::render_text(
+            &goose::i18n::text("error.advice.model"),
+            Some(Color::Yellow),
+            true,
+        );
+    }
 }
 
 async fn get_reasoner() -> Result<Arc<dyn Provider>, anyhow::Error> {
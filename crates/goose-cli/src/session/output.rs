@@ -3,8 +3,8 @@ use bat::WrappingMode;
 use console::{measure_text_width, style, Color, Term};
 use goose::config::Config;
 use goose::conversation::message::{
-    ActionRequiredData, Message, MessageContent, SystemNotificationContent, SystemNotificationType,
-    ToolRequest, ToolResponse,
+    ActionRequiredData, AnnotationContent, AttachmentContent, CitationSource, Message,
+    MessageContent, SystemNotificationContent, SystemNotificationType, ToolRequest, ToolResponse,
 };
 use goose::providers::canonical::maybe_get_canonical_model;
 #[cfg(target_os = "windows")]
@@ -254,6 +254,8 @@ pub fn render_message(message: &Message, debug: bool) {
             MessageContent::Image(image) => {
                 println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
             }
+            MessageContent::Attachment(attachment) => render_attachment(attachment),
+            MessageContent::Annotation(annotation) => render_annotation(annotation),
             MessageContent::Thinking(t) => render_thinking(&t.thinking, theme),
             MessageContent::Reasoning(r) => render_thinking(&r.text, theme),
             MessageContent::RedactedThinking(_) => {
@@ -273,6 +275,8 @@ pub fn render_message(message: &Message, debug: bool) {
                     SystemNotificationType::CreditsExhausted => {
                         render_credits_exhausted_notification(notification);
                     }
+                    // Shown as progress bars from the MCP notification
+                    SystemNotificationType::ToolProgress => {}
                 }
             }
             _ => {
@@ -334,6 +338,14 @@ pub fn render_message_streaming(
                 flush_markdown_buffer(buffer, theme);
                 println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
             }
+            MessageContent::Attachment(attachment) => {
+                flush_markdown_buffer(buffer, theme);
+                render_attachment(attachment);
+            }
+            MessageContent::Annotation(annotation) => {
+                flush_markdown_buffer(buffer, theme);
+                render_annotation(annotation);
+            }
             MessageContent::Thinking(t) => {
                 render_thinking_streaming(&t.thinking, buffer, thinking_header_shown, theme);
             }
@@ -360,6 +372,7 @@ pub fn render_message_streaming(
                         flush_markdown_buffer(buffer, theme);
                         render_credits_exhausted_notification(notification);
                     }
+                    SystemNotificationType::ToolProgress => {}
                 }
             }
             _ => {
@@ -473,6 +486,35 @@ fn should_show_thinking() -> bool {
     *SHOW_THINKING
 }
 
+fn render_attachment(attachment: &AttachmentContent) {
+    println!(
+        "{}",
+        style(format!(
+            "📎 {} ({}, {} bytes)",
+            attachment.name, attachment.mime_type, attachment.size
+        ))
+        .dim()
+    );
+}
+
+fn render_annotation(annotation: &AnnotationContent) {
+    let source = match &annotation.source {
+        CitationSource::Url {
+            url,
+            title: Some(title),
+        } => format!("{} <{}>", title, url),
+        CitationSource::Url { url, title: None } => url.clone(),
+        CitationSource::File {
+            path,
+            start_line: Some(start),
+            end_line,
+        } => format!("{}:{}-{}", path, start, end_line.unwrap_or(*start)),
+        CitationSource::File { path, .. } => path.clone(),
+        CitationSource::ToolResult { tool_call_id } => format!("tool result {}", tool_call_id),
+    };
+    println!("{}", style(format!("  ↳ source: {}", source)).dim());
+}
+
 fn render_thinking(text: &str, theme: Theme) {
     if should_show_thinking() {
         println!("\n{}", style("Thinking:").dim().italic());
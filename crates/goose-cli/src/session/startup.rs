@@ -0,0 +1,98 @@
+//! Readiness report for session startup.
+//!
+//! The provider, the model registry and the extensions are started together; each records
+//! how long it took and whether it failed, so slow starts can be traced to their cause.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+pub(super) struct StartupStep {
+    pub name: String,
+    pub elapsed: Duration,
+    pub error: Option<String>,
+}
+
+pub(super) struct StartupReport {
+    started: Instant,
+    steps: Vec<StartupStep>,
+}
+
+impl StartupReport {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, name: impl Into<String>, elapsed: Duration, error: Option<String>) {
+        self.steps.push(StartupStep {
+            name: name.into(),
+            elapsed,
+            error,
+        });
+    }
+
+    pub fn log(&self) {
+        for step in &self.steps {
+            tracing::info!(
+                step = %step.name,
+                elapsed_ms = step.elapsed.as_millis() as u64,
+                failed = step.error.is_some(),
+                "Startup step finished"
+            );
+        }
+        tracing::info!(
+            elapsed_ms = self.started.elapsed().as_millis() as u64,
+            "Session ready"
+        );
+    }
+
+    /// One line listing the steps, slowest first
+    pub fn summary(&self) -> String {
+        let mut steps: Vec<&StartupStep> = self.steps.iter().collect();
+        steps.sort_by(|a, b| b.elapsed.cmp(&a.elapsed));
+        let steps: Vec<String> = steps
+            .into_iter()
+            .map(|step| {
+                let failed = if step.error.is_some() {
+                    " (failed)"
+                } else {
+                    ""
+                };
+                format!("{} {}ms{}", step.name, step.elapsed.as_millis(), failed)
+            })
+            .collect();
+        format!(
+            "Ready in {}ms: {}",
+            self.started.elapsed().as_millis(),
+            steps.join(", ")
+        )
+    }
+}
+
+pub(super) async fn timed<T>(future: impl Future<Output = T>) -> (T, Duration) {
+    let started = Instant::now();
+    let value = future.await;
+    (value, started.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_lists_slowest_steps_first() {
+        let mut report = StartupReport::new();
+        report.record("provider", Duration::from_millis(120), None);
+        report.record(
+            "extension developer",
+            Duration::from_millis(480),
+            Some("exited".to_string()),
+        );
+
+        let summary = report.summary();
+        assert!(summary.starts_with("Ready in "));
+        assert!(summary.ends_with(": extension developer 480ms (failed), provider 120ms"));
+    }
+}
@@ -34,3 +34,17 @@ pub fn shutdown_signal() -> Pin<Box<dyn Future<Output = ()> + Send>> {
             .expect("failed to install Ctrl+C handler");
     })
 }
+
+/// Resolves when the process is asked to terminate (SIGTERM). Never resolves elsewhere.
+#[cfg(unix)]
+pub async fn terminate_signal() {
+    signal::unix::signal(signal::unix::SignalKind::terminate())
+        .expect("failed to install signal handler")
+        .recv()
+        .await;
+}
+
+#[cfg(not(unix))]
+pub async fn terminate_signal() {
+    std::future::pending::<()>().await
+}
@@ -3,6 +3,7 @@ use crate::state;
 use anyhow::Result;
 use axum::middleware;
 use axum_server::Handle;
+use goose::shutdown::ShutdownCoordinator;
 use goose_server::auth::check_token;
 use goose_server::tls::self_signed_config;
 use tower_http::cors::{Any, CorsLayer};
@@ -37,6 +38,8 @@ pub async fn run() -> Result<()> {
 
     let settings = configuration::Settings::new()?;
 
+    tokio::spawn(goose::config::org_config::refresh_in_background());
+
     let secret_key =
         std::env::var("GOOSE_SERVER__SECRET_KEY").unwrap_or_else(|_| "test".to_string());
 
@@ -73,6 +76,7 @@ pub async fn run() -> Result<()> {
         let shutdown_handle = handle.clone();
         tokio::spawn(async move {
             shutdown_signal().await;
+            ShutdownCoordinator::global().begin();
             shutdown_handle.graceful_shutdown(None);
         });
 
@@ -88,14 +92,15 @@ pub async fn run() -> Result<()> {
         info!("listening on http://{}", addr);
 
         axum::serve(listener, app)
-            .with_graceful_shutdown(async { shutdown_signal().await })
+            .with_graceful_shutdown(async {
+                shutdown_signal().await;
+                // End open reply streams so their connections can drain
+                ShutdownCoordinator::global().begin();
+            })
             .await?;
     }
 
-    if goose::otel::otlp::is_otlp_initialized() {
-        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-        goose::otel::otlp::shutdown_otlp();
-    }
+    ShutdownCoordinator::global().shutdown("terminated").await;
 
     info!("server shutdown complete");
     Ok(())
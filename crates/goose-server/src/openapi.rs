@@ -1,7 +1,8 @@
 use goose::agents::extension::Envs;
 use goose::agents::extension::ToolInfo;
+use goose::agents::extension_manager::ToolConflict;
 use goose::agents::ExtensionConfig;
-use goose::config::permission::PermissionLevel;
+use goose::config::permission::{GrantScope, PermissionGrant, PermissionLevel};
 use goose::config::ExtensionEntry;
 use goose::conversation::Conversation;
 use goose::download_manager::{DownloadProgress, DownloadStatus};
@@ -20,11 +21,13 @@ use goose::config::declarative_providers::{
     DeclarativeProviderConfig, LoadedProvider, ProviderEngine,
 };
 use goose::conversation::message::{
-    ActionRequired, ActionRequiredData, FrontendToolRequest, Message, MessageContent,
-    MessageMetadata, ReasoningContent, RedactedThinkingContent, SystemNotificationContent,
-    SystemNotificationType, ThinkingContent, TokenState, ToolConfirmationRequest, ToolRequest,
-    ToolResponse,
+    ActionRequired, ActionRequiredData, AnnotationContent, AttachmentContent, CitationSource,
+    FrontendToolRequest, Message, MessageContent, MessageMetadata, MessageUsage, ReasoningContent,
+    RedactedThinkingContent, SystemNotificationContent, SystemNotificationType, ThinkingContent,
+    TokenState, ToolConfirmationRequest, ToolRequest, ToolResponse,
 };
+use goose::conversation::turns::TurnSummary;
+use goose::session::branch::{BranchModel, BranchReason, BranchState};
 
 use crate::routes::recipe_utils::RecipeManifest;
 use crate::routes::reply::MessageEvent;
@@ -338,18 +341,25 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::config_management::detect_provider,
         super::routes::config_management::recover_config,
         super::routes::config_management::validate_config,
+        super::routes::config_management::doctor,
+        super::routes::config_management::get_project_config,
+        super::routes::config_management::trust_project_config,
+        super::routes::config_management::config_schema,
         super::routes::config_management::init_config,
         super::routes::config_management::upsert_config,
         super::routes::config_management::remove_config,
         super::routes::config_management::read_config,
         super::routes::config_management::add_extension,
         super::routes::config_management::remove_extension,
+        super::routes::config_management::get_extension_logs,
         super::routes::config_management::get_extensions,
         super::routes::config_management::read_all_config,
         super::routes::config_management::providers,
         super::routes::config_management::get_provider_models,
         super::routes::config_management::get_slash_commands,
         super::routes::config_management::upsert_permissions,
+        super::routes::config_management::list_permission_grants,
+        super::routes::config_management::revoke_permission_grant,
         super::routes::config_management::create_custom_provider,
         super::routes::config_management::get_custom_provider,
         super::routes::config_management::update_custom_provider,
@@ -370,6 +380,7 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::agent::restart_agent,
         super::routes::agent::update_working_dir,
         super::routes::agent::get_tools,
+        super::routes::agent::get_tool_conflicts,
         super::routes::agent::read_resource,
         super::routes::agent::call_tool,
         super::routes::agent::list_apps,
@@ -378,22 +389,37 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::agent::update_from_session,
         super::routes::agent::agent_add_extension,
         super::routes::agent::agent_remove_extension,
+        super::routes::agent::set_extension_enabled,
         super::routes::agent::update_agent_provider,
+        super::routes::agent::steer_agent,
         super::routes::action_required::confirm_tool_action,
         super::routes::reply::reply,
+        super::routes::reply::events,
         super::routes::session::list_sessions,
         super::routes::session::search_sessions,
         super::routes::session::get_session,
         super::routes::session::get_session_insights,
         super::routes::session::update_session_name,
+        super::routes::session::regenerate_session_name,
         super::routes::session::delete_session,
         super::routes::session::export_session,
         super::routes::session::import_session,
         super::routes::session::update_session_user_recipe_values,
         super::routes::session::fork_session,
         super::routes::session::get_session_extensions,
+        super::routes::session::list_checkpoints,
+        super::routes::session::diff_checkpoint,
+        super::routes::session::revert_checkpoint,
+        super::routes::session::squash_checkpoints,
+        super::routes::session::add_attachment,
+        super::routes::session::list_turns,
+        super::routes::session::delete_turn,
+        super::routes::session::retry_turn,
+        super::routes::session::edit_message,
+        super::routes::session::regenerate,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
+        super::routes::schedule::get_schedule,
         super::routes::schedule::delete_schedule,
         super::routes::schedule::update_schedule,
         super::routes::schedule::run_now_handler,
@@ -419,6 +445,9 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::tunnel::stop_tunnel,
         super::routes::tunnel::get_tunnel_status,
         super::routes::telemetry::send_telemetry_event,
+        super::routes::audit::export_audit_log,
+        super::routes::audit::verify_audit_log,
+        super::routes::ollama::pull_ollama_model,
         super::routes::dictation::transcribe_dictation,
         super::routes::dictation::get_dictation_config,
         super::routes::dictation::list_models,
@@ -439,6 +468,8 @@ derive_utoipa!(Icon as IconSchema);
     components(schemas(
         super::routes::config_management::UpsertConfigQuery,
         super::routes::config_management::ConfigKeyQuery,
+        super::routes::config_management::ProjectConfigResponse,
+        super::routes::config_management::TrustProjectConfigRequest,
         super::routes::config_management::DetectProviderRequest,
         super::routes::config_management::DetectProviderResponse,
         super::routes::config_management::ConfigResponse,
@@ -452,6 +483,8 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::config_management::ToolPermission,
         super::routes::config_management::UpsertPermissionsQuery,
         super::routes::config_management::UpdateCustomProviderRequest,
+        goose::config::doctor::Diagnostic,
+        goose::config::doctor::Severity,
         goose::providers::catalog::ProviderCatalogEntry,
         goose::providers::catalog::ProviderTemplate,
         goose::providers::catalog::ModelTemplate,
@@ -467,14 +500,27 @@ derive_utoipa!(Icon as IconSchema);
         goose::prompt_template::Template,
         super::routes::action_required::ConfirmToolActionRequest,
         super::routes::reply::ChatRequest,
+        goose::agents::lifecycle::LifecycleEvent,
         super::routes::session::ImportSessionRequest,
         super::routes::session::SessionListResponse,
         super::routes::session::UpdateSessionNameRequest,
+        super::routes::session::SessionNameResponse,
         super::routes::session::UpdateSessionUserRecipeValuesRequest,
         super::routes::session::UpdateSessionUserRecipeValuesResponse,
         super::routes::session::ForkRequest,
         super::routes::session::ForkResponse,
+        super::routes::session::EditMessageRequest,
+        super::routes::session::RegenerateRequest,
+        super::routes::session::BranchResponse,
+        BranchModel,
+        BranchState,
+        BranchReason,
         super::routes::session::SessionExtensionsResponse,
+        super::routes::session::CheckpointListResponse,
+        super::routes::session::CheckpointDiffResponse,
+        super::routes::session::SquashCheckpointsRequest,
+        super::routes::session::AddAttachmentRequest,
+        goose::checkpoints::Checkpoint,
         Message,
         MessageContent,
         MessageMetadata,
@@ -497,6 +543,11 @@ derive_utoipa!(Icon as IconSchema);
         ThinkingContent,
         RedactedThinkingContent,
         ReasoningContent,
+        AttachmentContent,
+        AnnotationContent,
+        CitationSource,
+        MessageUsage,
+        TurnSummary,
         FrontendToolRequest,
         ResourceContentsSchema,
         SystemNotificationType,
@@ -519,7 +570,10 @@ derive_utoipa!(Icon as IconSchema);
         ToolExecutionSchema,
         TaskSupportSchema,
         ToolInfo,
+        ToolConflict,
         PermissionLevel,
+        PermissionGrant,
+        GrantScope,
         Permission,
         PrincipalType,
         ModelInfo,
@@ -536,6 +590,9 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::schedule::KillJobResponse,
         super::routes::schedule::InspectJobResponse,
         goose::scheduler::ScheduledJob,
+        goose::scheduler::JobRunSettings,
+        goose::scheduler::JobRunStatus,
+        goose::scheduler::JobRunOutcome,
         super::routes::schedule::RunNowResponse,
         super::routes::schedule::ListSchedulesResponse,
         super::routes::schedule::SessionsQuery,
@@ -563,6 +620,8 @@ derive_utoipa!(Icon as IconSchema);
         goose::recipe::Recipe,
         goose::recipe::Author,
         goose::recipe::Settings,
+        goose::agents::platform_extensions::developer::sandbox::SandboxConfig,
+        goose::agents::platform_extensions::developer::sandbox::SandboxRuntime,
         goose::recipe::RecipeParameter,
         goose::recipe::RecipeParameterInputType,
         goose::recipe::RecipeParameterRequirement,
@@ -588,6 +647,8 @@ derive_utoipa!(Icon as IconSchema);
         super::routes::agent::UpdateFromSessionRequest,
         super::routes::agent::AddExtensionRequest,
         super::routes::agent::RemoveExtensionRequest,
+        super::routes::agent::SetExtensionEnabledRequest,
+        super::routes::agent::SteerAgentRequest,
         super::routes::agent::ResumeAgentResponse,
         super::routes::agent::RestartAgentResponse,
         goose::agents::ExtensionLoadResult,
@@ -595,6 +656,10 @@ derive_utoipa!(Icon as IconSchema);
         super::tunnel::TunnelInfo,
         super::tunnel::TunnelState,
         super::routes::telemetry::TelemetryEventRequest,
+        goose::security::audit::AuditEntry,
+        goose::security::audit::AuditEvent,
+        goose::security::audit::AuditVerification,
+        super::routes::ollama::PullOllamaModelRequest,
         goose::goose_apps::GooseApp,
         goose::goose_apps::WindowProps,
         goose::goose_apps::McpAppResource,
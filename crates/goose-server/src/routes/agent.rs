@@ -10,7 +10,9 @@ use axum::{
     routing::{get, post},
     Json, Router,
 };
-use goose::agents::{Container, ExtensionLoadResult};
+use goose::agents::dry_run::DryRunState;
+use goose::agents::platform_extensions::developer::sandbox::{set_session_sandbox, SandboxConfig};
+use goose::agents::{Container, ExtensionLoadResult, SteeringMessage};
 use goose::goose_apps::{fetch_mcp_apps, GooseApp, McpAppCache};
 
 use base64::Engine;
@@ -24,7 +26,10 @@ use goose::recipe_deeplink;
 use goose::session::session_manager::SessionType;
 use goose::session::{EnabledExtensionsState, ExtensionState, Session};
 use goose::{
-    agents::{extension::ToolInfo, extension_manager::get_parameter_names},
+    agents::{
+        extension::ToolInfo,
+        extension_manager::{get_parameter_names, ToolConflict},
+    },
     config::permission::PermissionLevel,
 };
 use rmcp::model::{CallToolRequestParams, Content};
@@ -50,6 +55,11 @@ pub struct UpdateProviderRequest {
     request_params: Option<std::collections::HashMap<String, serde_json::Value>>,
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ToolConflictsQuery {
+    session_id: String,
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct GetToolsQuery {
     extension_name: Option<String>,
@@ -103,12 +113,43 @@ pub struct RemoveExtensionRequest {
     session_id: String,
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetExtensionEnabledRequest {
+    session_id: String,
+    /// Name of an extension from the goose config
+    name: String,
+    enabled: bool,
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct SetContainerRequest {
     session_id: String,
     container_id: Option<String>,
 }
 
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SteerAgentRequest {
+    session_id: String,
+    message: String,
+    /// Skip the tool calls the model queued that have not started yet
+    #[serde(default)]
+    cancel_pending_tools: bool,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetSandboxRequest {
+    session_id: String,
+    /// Container settings for the developer tools, or null to run them on the host
+    sandbox: Option<SandboxConfig>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct SetDryRunRequest {
+    session_id: String,
+    /// Preview mutating tools and hold them for approval instead of running them
+    enabled: bool,
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct ReadResourceRequest {
     session_id: String,
@@ -214,6 +255,9 @@ async fn start_agent(
 
     let name = "New Chat".to_string();
 
+    // The project's settings apply once the user has trusted them through /config/project
+    Config::global().set_project_dir(&PathBuf::from(&working_dir));
+
     let manager = state.session_manager();
 
     let mut session = manager
@@ -352,6 +396,8 @@ async fn resume_agent(
             }
         })?;
 
+    Config::global().set_project_dir(&session.working_dir);
+
     let extension_results = if payload.load_model_and_extensions {
         let agent = state
             .get_agent_for_route(payload.session_id.clone())
@@ -456,6 +502,32 @@ async fn update_from_session(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    get,
+    path = "/agent/tool_conflicts",
+    params(
+        ("session_id" = String, Query, description = "Session whose tools to check")
+    ),
+    responses(
+        (status = 200, description = "Tools several extensions offer under the same name", body = Vec<ToolConflict>),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 424, description = "Agent not initialized"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn get_tool_conflicts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ToolConflictsQuery>,
+) -> Result<Json<Vec<ToolConflict>>, StatusCode> {
+    let agent = state.get_agent_for_route(query.session_id.clone()).await?;
+    Ok(Json(
+        agent
+            .extension_manager
+            .tool_conflicts(&query.session_id)
+            .await,
+    ))
+}
+
 #[utoipa::path(
     get,
     path = "/agent/tools",
@@ -556,6 +628,9 @@ async fn update_agent_provider(
     let extensions =
         EnabledExtensionsState::for_session(state.session_manager(), &payload.session_id, config)
             .await;
+    let extensions = agent
+        .extensions_for_provider(&payload.provider, &payload.session_id, extensions)
+        .await;
 
     let new_provider = create(&payload.provider, model_config, extensions)
         .await
@@ -642,6 +717,34 @@ async fn agent_remove_extension(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/agent/set_extension_enabled",
+    request_body = SetExtensionEnabledRequest,
+    responses(
+        (status = 200, description = "Extension enabled or disabled for the session"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 424, description = "Agent not initialized"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn set_extension_enabled(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetExtensionEnabledRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let agent = state.get_agent(request.session_id.clone()).await?;
+
+    agent
+        .set_extension_enabled(&request.session_id, &request.name, request.enabled)
+        .await
+        .map_err(|e| {
+            error!("Failed to update extension: {}", e);
+            ErrorResponse::internal(format!("Failed to update extension: {}", e))
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
 #[utoipa::path(
     post,
     path = "/agent/set_container",
@@ -665,6 +768,108 @@ async fn set_container(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/agent/steer",
+    request_body = SteerAgentRequest,
+    responses(
+        (status = 200, description = "Message queued for the turn in progress"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 409, description = "No turn is in progress for the session"),
+        (status = 424, description = "Agent not initialized"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn steer_agent(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SteerAgentRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let agent = state.get_agent(request.session_id.clone()).await?;
+
+    let queued = agent.steer(SteeringMessage {
+        text: request.message,
+        cancel_pending_tools: request.cancel_pending_tools,
+    });
+    if !queued {
+        return Err(ErrorResponse {
+            message: "No turn is in progress for this session; send the message as a reply"
+                .to_string(),
+            status: StatusCode::CONFLICT,
+        });
+    }
+
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    post,
+    path = "/agent/set_sandbox",
+    request_body = SetSandboxRequest,
+    responses(
+        (status = 200, description = "Sandbox set successfully"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn set_sandbox(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetSandboxRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let manager = state.session_manager();
+    let session = manager
+        .get_session(&request.session_id, false)
+        .await
+        .map_err(|e| ErrorResponse::not_found(format!("Session not found: {}", e)))?;
+
+    let mut extension_data = session.extension_data;
+    set_session_sandbox(&mut extension_data, request.sandbox)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to set sandbox: {}", e)))?;
+    manager
+        .update(&request.session_id)
+        .extension_data(extension_data)
+        .apply()
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to save sandbox: {}", e)))?;
+
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    post,
+    path = "/agent/set_dry_run",
+    request_body = SetDryRunRequest,
+    responses(
+        (status = 200, description = "Dry-run mode updated successfully"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn set_dry_run(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SetDryRunRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let manager = state.session_manager();
+    let session = manager
+        .get_session(&request.session_id, false)
+        .await
+        .map_err(|e| ErrorResponse::not_found(format!("Session not found: {}", e)))?;
+
+    let mut extension_data = session.extension_data;
+    DryRunState::new(request.enabled)
+        .to_extension_data(&mut extension_data)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to set dry run: {}", e)))?;
+    manager
+        .update(&request.session_id)
+        .extension_data(extension_data)
+        .apply()
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to save dry run: {}", e)))?;
+
+    Ok(StatusCode::OK)
+}
+
 #[utoipa::path(
     post,
     path = "/agent/stop",
@@ -683,7 +888,7 @@ async fn stop_agent(
     let session_id = payload.session_id;
     state
         .agent_manager
-        .remove_session(&session_id)
+        .end_session(&session_id)
         .await
         .map_err(|e| ErrorResponse {
             message: format!("Failed to stop agent for session {}: {}", session_id, e),
@@ -1204,6 +1409,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/agent/restart", post(restart_agent))
         .route("/agent/update_working_dir", post(update_working_dir))
         .route("/agent/tools", get(get_tools))
+        .route("/agent/tool_conflicts", get(get_tool_conflicts))
         .route("/agent/read_resource", post(read_resource))
         .route("/agent/call_tool", post(call_tool))
         .route("/agent/list_apps", get(list_apps))
@@ -1213,7 +1419,73 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/agent/update_from_session", post(update_from_session))
         .route("/agent/add_extension", post(agent_add_extension))
         .route("/agent/remove_extension", post(agent_remove_extension))
+        .route("/agent/set_extension_enabled", post(set_extension_enabled))
         .route("/agent/set_container", post(set_container))
+        .route("/agent/steer", post(steer_agent))
+        .route("/agent/set_sandbox", post(set_sandbox))
+        .route("/agent/set_dry_run", post(set_dry_run))
         .route("/agent/stop", post(stop_agent))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod integration_tests {
+        use super::*;
+        use axum::{body::Body, http::Request};
+        use tower::ServiceExt;
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_steer_without_a_turn_in_progress_conflicts() {
+            let state = AppState::new(true).await.unwrap();
+
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/agent/steer")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "session_id": "test-session",
+                        "message": "use the staging database",
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::CONFLICT);
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_enabling_an_unconfigured_extension_fails() {
+            let state = AppState::new(true).await.unwrap();
+
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/agent/set_extension_enabled")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .body(Body::from(
+                    serde_json::json!({
+                        "session_id": "test-session",
+                        "name": "no-such-extension-configured",
+                        "enabled": true,
+                    })
+                    .to_string(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+}
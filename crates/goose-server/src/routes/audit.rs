@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Query, State},
+    routing::get,
+    Json, Router,
+};
+use goose::security::audit::{AuditEntry, AuditLog, AuditVerification};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::routes::errors::ErrorResponse;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AuditExportQuery {
+    /// Only return entries recorded for this session
+    session_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit/export",
+    params(AuditExportQuery),
+    responses(
+        (status = 200, description = "Audit trail entries in recording order", body = [AuditEntry]),
+        (status = 500, description = "Failed to read the audit log")
+    )
+)]
+async fn export_audit_log(
+    State(_state): State<Arc<AppState>>,
+    Query(query): Query<AuditExportQuery>,
+) -> Result<Json<Vec<AuditEntry>>, ErrorResponse> {
+    tokio::task::spawn_blocking(move || AuditLog::global().export(query.session_id.as_deref()))
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to read audit log: {}", e)))?
+        .map(Json)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to read audit log: {}", e)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/audit/verify",
+    responses(
+        (status = 200, description = "Result of checking the audit trail hash chain", body = AuditVerification),
+        (status = 500, description = "Failed to read the audit log")
+    )
+)]
+async fn verify_audit_log(
+    State(_state): State<Arc<AppState>>,
+) -> Result<Json<AuditVerification>, ErrorResponse> {
+    tokio::task::spawn_blocking(|| AuditLog::global().verify())
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to read audit log: {}", e)))?
+        .map(Json)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to read audit log: {}", e)))
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/audit/export", get(export_audit_log))
+        .route("/audit/verify", get(verify_audit_log))
+        .with_state(state)
+}
@@ -1,14 +1,18 @@
 use crate::routes::errors::ErrorResponse;
 use crate::routes::utils::check_provider_configured;
 use crate::state::AppState;
+use axum::http::StatusCode;
 use axum::routing::put;
 use axum::{
-    extract::Path,
+    extract::{Path, Query},
     routing::{delete, get, post},
     Json, Router,
 };
+use goose::agents::extension_logs::ExtensionLogs;
 use goose::config::declarative_providers::LoadedProvider;
+use goose::config::doctor::{diagnose, Diagnostic};
 use goose::config::paths::Paths;
+use goose::config::project_config::{find_project_config, ProjectConfig};
 use goose::config::ExtensionEntry;
 use goose::config::{Config, ConfigError};
 use goose::model::ModelConfig;
@@ -21,15 +25,18 @@ use goose::providers::catalog::{
 };
 use goose::providers::create_with_default_model;
 use goose::providers::providers as get_providers;
+use goose::security::signing::check_extension_definition;
 use goose::{
-    agents::execute_commands, agents::ExtensionConfig, config::permission::PermissionLevel,
+    agents::execute_commands,
+    agents::ExtensionConfig,
+    config::permission::{PermissionGrant, PermissionLevel},
     slash_commands,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_yaml;
 use std::{collections::HashMap, sync::Arc};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Serialize, ToSchema)]
 pub struct ExtensionResponse {
@@ -43,6 +50,10 @@ pub struct ExtensionQuery {
     pub name: String,
     pub config: ExtensionConfig,
     pub enabled: bool,
+    /// Minisign signature over `config` as compact JSON with sorted keys (`jq -cSj`),
+    /// checked against GOOSE_TRUSTED_SIGNING_KEYS when a signature policy is set
+    #[serde(default)]
+    pub signature: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -262,12 +273,16 @@ pub async fn read_config(
     )
 )]
 pub async fn get_extensions() -> Result<Json<ExtensionResponse>, ErrorResponse> {
-    let extensions = goose::config::get_all_extensions();
-    let warnings = goose::config::get_warnings();
-    Ok(Json(ExtensionResponse {
-        extensions,
-        warnings,
-    }))
+    Ok(Json(extensions_response(Config::global())))
+}
+
+/// Extensions are listed with their `${keyring:...}` references unresolved, so the UI never
+/// sees a secret and never writes one back into config.yaml
+fn extensions_response(config: &Config) -> ExtensionResponse {
+    ExtensionResponse {
+        extensions: goose::config::extensions::get_all_extensions_with_config(config),
+        warnings: goose::config::get_warnings(),
+    }
 }
 
 #[utoipa::path(
@@ -277,13 +292,26 @@ pub async fn get_extensions() -> Result<Json<ExtensionResponse>, ErrorResponse>
     responses(
         (status = 200, description = "Extension added or updated successfully", body = String),
         (status = 400, description = "Invalid request"),
+        (status = 403, description = "Extension definition is not signed by a trusted key"),
         (status = 422, description = "Could not serialize config.yaml"),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn add_extension(
-    Json(extension_query): Json<ExtensionQuery>,
-) -> Result<Json<String>, ErrorResponse> {
+pub async fn add_extension(Json(body): Json<Value>) -> Result<Json<String>, ErrorResponse> {
+    // The signature covers the definition as it was sent, not as goose re-serializes it
+    let definition = body.get("config").cloned().unwrap_or_default();
+    let extension_query: ExtensionQuery = serde_json::from_value(body)
+        .map_err(|e| ErrorResponse::bad_request(format!("Invalid extension: {}", e)))?;
+    check_extension_definition(
+        &extension_query.name,
+        &definition,
+        extension_query.signature.as_deref(),
+    )
+    .map_err(|e| ErrorResponse {
+        message: e.to_string(),
+        status: StatusCode::FORBIDDEN,
+    })?;
+
     let extensions = goose::config::get_all_extensions();
     let key = goose::config::extensions::name_to_key(&extension_query.name);
 
@@ -316,6 +344,32 @@ pub async fn remove_extension(Path(name): Path<String>) -> Result<Json<String>,
     Ok(Json(format!("Removed extension {}", name)))
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExtensionLogsQuery {
+    /// Number of lines to return, 50 by default
+    lines: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/config/extensions/{name}/logs",
+    params(
+        ("name" = String, Path, description = "Name of the extension"),
+        ExtensionLogsQuery
+    ),
+    responses(
+        (status = 200, description = "The last lines of the extension's stderr and MCP log messages, oldest first", body = [String])
+    )
+)]
+pub async fn get_extension_logs(
+    Path(name): Path<String>,
+    Query(query): Query<ExtensionLogsQuery>,
+) -> Result<Json<Vec<String>>, ErrorResponse> {
+    Ok(Json(
+        ExtensionLogs::global().tail(&name, query.lines.unwrap_or(50)),
+    ))
+}
+
 #[utoipa::path(
     get,
     path = "/config",
@@ -534,6 +588,40 @@ pub async fn upsert_permissions(
     Ok(Json("Permissions updated successfully".to_string()))
 }
 
+#[utoipa::path(
+    get,
+    path = "/config/permissions/grants",
+    responses(
+        (status = 200, description = "Active time-limited and scoped permission grants", body = [PermissionGrant]),
+    )
+)]
+pub async fn list_permission_grants() -> Result<Json<Vec<PermissionGrant>>, ErrorResponse> {
+    let permission_manager = goose::config::PermissionManager::instance();
+    Ok(Json(permission_manager.list_grants()))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/config/permissions/grants/{id}",
+    params(
+        ("id" = String, Path, description = "Grant ID")
+    ),
+    responses(
+        (status = 200, description = "Grant revoked", body = String),
+        (status = 404, description = "Grant not found"),
+    )
+)]
+pub async fn revoke_permission_grant(
+    Path(id): Path<String>,
+) -> Result<Json<String>, ErrorResponse> {
+    let permission_manager = goose::config::PermissionManager::instance();
+    if permission_manager.revoke_grant(&id) {
+        Ok(Json(format!("Revoked grant {}", id)))
+    } else {
+        Err(ErrorResponse::not_found(format!("Grant {} not found", id)))
+    }
+}
+
 #[utoipa::path(
     post,
     path = "/config/detect-provider",
@@ -633,6 +721,121 @@ pub async fn validate_config() -> Result<Json<String>, ErrorResponse> {
 
     Ok(Json("Config file is valid".to_string()))
 }
+#[utoipa::path(
+    get,
+    path = "/config/schema",
+    responses(
+        (status = 200, description = "JSON schema for config.yaml", body = Value)
+    )
+)]
+pub async fn config_schema() -> Json<Value> {
+    Json(goose::config::schema::config_json_schema())
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DoctorQuery {
+    /// Directory whose project hooks are checked, the server's working directory by default
+    working_dir: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/config/doctor",
+    params(DoctorQuery),
+    responses(
+        (status = 200, description = "Problems found in the configuration, most severe first", body = [Diagnostic])
+    )
+)]
+pub async fn doctor(Query(query): Query<DoctorQuery>) -> Json<Vec<Diagnostic>> {
+    let working_dir = query
+        .working_dir
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_default();
+    Json(diagnose(&working_dir).await)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ProjectConfigQuery {
+    /// Working directory of the session the project config is for
+    working_dir: String,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectConfigResponse {
+    /// The `.goose/config.yaml` that applies to the working directory
+    path: String,
+    /// Hash of its contents, to send back when the user trusts it
+    digest: String,
+    trusted: bool,
+    /// One line per setting the file changes, for the user to review before trusting it
+    changes: Vec<String>,
+    /// Keys in the file that a project is not allowed to set
+    ignored_keys: Vec<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustProjectConfigRequest {
+    working_dir: String,
+    /// The digest the user reviewed; the file is not trusted if it changed since
+    digest: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/config/project",
+    params(ProjectConfigQuery),
+    responses(
+        (status = 200, description = "The project config for the working directory, or null when it has none", body = Option<ProjectConfigResponse>),
+        (status = 400, description = "The project config could not be read", body = ErrorResponse)
+    )
+)]
+pub async fn get_project_config(
+    Query(query): Query<ProjectConfigQuery>,
+) -> Result<Json<Option<ProjectConfigResponse>>, ErrorResponse> {
+    let Some(path) = find_project_config(std::path::Path::new(&query.working_dir)) else {
+        return Ok(Json(None));
+    };
+    let project = ProjectConfig::load(&path).map_err(|e| {
+        ErrorResponse::bad_request(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    Ok(Json(Some(ProjectConfigResponse {
+        path: project.path.display().to_string(),
+        trusted: Config::global().is_project_config_trusted(&project),
+        changes: project.summary(),
+        ignored_keys: project.ignored_keys.clone(),
+        digest: project.digest,
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/config/project/trust",
+    request_body = TrustProjectConfigRequest,
+    responses(
+        (status = 200, description = "The project config is trusted and applies to sessions started in the project"),
+        (status = 404, description = "The working directory has no project config", body = ErrorResponse),
+        (status = 409, description = "The project config changed since it was reviewed", body = ErrorResponse)
+    )
+)]
+pub async fn trust_project_config(
+    Json(request): Json<TrustProjectConfigRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let path = find_project_config(std::path::Path::new(&request.working_dir))
+        .ok_or_else(|| ErrorResponse::not_found("The working directory has no project config"))?;
+    let project = ProjectConfig::load(&path)?;
+    if project.digest != request.digest {
+        return Err(ErrorResponse {
+            message: "The project config changed since it was reviewed".to_string(),
+            status: StatusCode::CONFLICT,
+        });
+    }
+    Config::global().trust_project_config(&project)?;
+    Ok(StatusCode::OK)
+}
+
 #[utoipa::path(
     post,
     path = "/config/custom-providers",
@@ -894,6 +1097,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/config/extensions", get(get_extensions))
         .route("/config/extensions", post(add_extension))
         .route("/config/extensions/{name}", delete(remove_extension))
+        .route("/config/extensions/{name}/logs", get(get_extension_logs))
         .route("/config/providers", get(providers))
         .route("/config/providers/{name}/models", get(get_provider_models))
         .route("/config/provider-catalog", get(get_provider_catalog))
@@ -911,7 +1115,16 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/config/backup", post(backup_config))
         .route("/config/recover", post(recover_config))
         .route("/config/validate", get(validate_config))
+        .route("/config/doctor", get(doctor))
+        .route("/config/project", get(get_project_config))
+        .route("/config/project/trust", post(trust_project_config))
+        .route("/config/schema", get(config_schema))
         .route("/config/permissions", post(upsert_permissions))
+        .route("/config/permissions/grants", get(list_permission_grants))
+        .route(
+            "/config/permissions/grants/{id}",
+            delete(revoke_permission_grant),
+        )
         .route("/config/custom-providers", post(create_custom_provider))
         .route(
             "/config/custom-providers/{id}",
@@ -929,4 +1142,43 @@ pub fn routes(state: Arc<AppState>) -> Router {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use goose::agents::extension::Envs;
+    use goose::agents::ExtensionConfig;
+
+    #[test]
+    fn extensions_route_never_returns_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("s.yaml"),
+        )
+        .unwrap();
+        config
+            .set_secret("GITHUB_TOKEN", &"ghp_very_secret")
+            .unwrap();
+        let mut extension = ExtensionConfig::stdio("github", "github-mcp", "GitHub", 300);
+        if let ExtensionConfig::Stdio { envs, .. } = &mut extension {
+            *envs = Envs::new(HashMap::from([(
+                "GITHUB_TOKEN".to_string(),
+                "${keyring:GITHUB_TOKEN}".to_string(),
+            )]));
+        }
+        let mut extensions = serde_yaml::Mapping::new();
+        extensions.insert(
+            extension.key().into(),
+            serde_yaml::to_value(ExtensionEntry {
+                enabled: true,
+                config: extension,
+            })
+            .unwrap(),
+        );
+        config.set_param("extensions", extensions).unwrap();
+
+        let body = serde_json::to_string(&extensions_response(&config)).unwrap();
+
+        assert!(!body.contains("ghp_very_secret"), "{}", body);
+        assert!(body.contains("${keyring:GITHUB_TOKEN}"), "{}", body);
+    }
+}
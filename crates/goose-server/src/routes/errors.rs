@@ -5,7 +5,7 @@ use axum::{
 };
 use goose::config::ConfigError;
 use goose::model::ConfigError as ModelConfigError;
-use goose::providers::errors::ProviderError;
+use goose::providers::errors::{ProviderError, ProviderErrorCode};
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -107,18 +107,18 @@ impl From<serde_yaml::Error> for ErrorResponse {
 
 impl From<ProviderError> for ErrorResponse {
     fn from(err: ProviderError) -> Self {
-        let (status, message) = match err {
-            ProviderError::Authentication(_) => (
+        let (status, message) = match err.code() {
+            ProviderErrorCode::Auth => (
                 StatusCode::BAD_REQUEST,
                 format!("Authentication failed: {}", err),
             ),
-            ProviderError::UsageError(_) => {
-                (StatusCode::BAD_REQUEST, format!("Usage error: {}", err))
-            }
-            ProviderError::RateLimitExceeded { .. } => (
+            ProviderErrorCode::Usage => (StatusCode::BAD_REQUEST, format!("Usage error: {}", err)),
+            ProviderErrorCode::RateLimit => (
                 StatusCode::TOO_MANY_REQUESTS,
                 format!("Rate limit exceeded: {}", err),
             ),
+            ProviderErrorCode::Overloaded => (StatusCode::SERVICE_UNAVAILABLE, err.to_string()),
+            ProviderErrorCode::ModelNotFound => (StatusCode::BAD_REQUEST, err.to_string()),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Provider error: {}", err),
@@ -1,5 +1,6 @@
 pub mod action_required;
 pub mod agent;
+pub mod audit;
 pub mod config_management;
 pub mod dictation;
 pub mod errors;
@@ -7,6 +8,7 @@ pub mod gateway;
 pub mod local_inference;
 pub mod mcp_app_proxy;
 pub mod mcp_ui_proxy;
+pub mod ollama;
 pub mod prompts;
 pub mod recipe;
 pub mod recipe_utils;
@@ -31,9 +33,11 @@ pub fn configure(state: Arc<crate::state::AppState>, secret_key: String) -> Rout
         .merge(reply::routes(state.clone()))
         .merge(action_required::routes(state.clone()))
         .merge(agent::routes(state.clone()))
+        .merge(audit::routes(state.clone()))
         .merge(dictation::routes(state.clone()))
         .merge(local_inference::routes(state.clone()))
         .merge(config_management::routes(state.clone()))
+        .merge(ollama::routes(state.clone()))
         .merge(prompts::routes())
         .merge(recipe::routes(state.clone()))
         .merge(session::routes(state.clone()))
@@ -0,0 +1,47 @@
+use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use goose::model::ModelConfig;
+use goose::providers::ollama::OllamaProvider;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::routes::errors::ErrorResponse;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PullOllamaModelRequest {
+    /// The model to pull, such as `qwen3:8b`
+    pub model: String,
+    /// The session that `/events` reports the pull progress for
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/ollama/pull",
+    request_body = PullOllamaModelRequest,
+    responses(
+        (status = 200, description = "The model is installed on the Ollama server"),
+        (status = 500, description = "The pull failed or stalled", body = ErrorResponse)
+    )
+)]
+async fn pull_ollama_model(
+    State(_state): State<Arc<AppState>>,
+    Json(request): Json<PullOllamaModelRequest>,
+) -> Result<StatusCode, ErrorResponse> {
+    let provider = OllamaProvider::from_env(ModelConfig::new(&request.model)?).await?;
+    provider
+        .pull_model(
+            &request.model,
+            request.session_id.as_deref().unwrap_or_default(),
+        )
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/ollama/pull", post(pull_ollama_model))
+        .with_state(state)
+}
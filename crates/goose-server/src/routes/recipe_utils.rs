@@ -188,5 +188,5 @@ pub async fn apply_recipe_to_agent(
         .apply_recipe_components(recipe.response.clone(), include_final_output_tool)
         .await;
 
-    recipe.instructions.as_ref().cloned()
+    recipe.system_instructions()
 }
@@ -3,14 +3,15 @@ use crate::state::AppState;
 #[cfg(test)]
 use axum::http::StatusCode;
 use axum::{
-    extract::{DefaultBodyLimit, State},
+    extract::{DefaultBodyLimit, Query, State},
     http::{self},
     response::IntoResponse,
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
 use bytes::Bytes;
 use futures::{stream::StreamExt, Stream};
+use goose::agents::lifecycle::{self, LifecycleEvent};
 use goose::agents::{AgentEvent, SessionConfig};
 use goose::conversation::message::{Message, MessageContent, TokenState};
 use goose::conversation::Conversation;
@@ -24,7 +25,7 @@ use std::{
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 use tokio::time::timeout;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
@@ -459,12 +460,70 @@ pub async fn reply(
     Ok(SseResponse::new(stream))
 }
 
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct EventsQuery {
+    /// Only stream events for this session, and those not about any one session
+    session_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "Stream of agent lifecycle events",
+         body = LifecycleEvent,
+         content_type = "text/event-stream"),
+    )
+)]
+pub async fn events(
+    State(_state): State<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> SseResponse {
+    let (tx, rx) = mpsc::channel(100);
+    let mut events = lifecycle::subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            let event = tokio::select! {
+                _ = tx.closed() => break,
+                event = events.recv() => event,
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event stream fell behind and skipped {} events", skipped);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let for_session = event.session_id();
+            if !for_session.is_empty()
+                && query
+                    .session_id
+                    .as_deref()
+                    .is_some_and(|session_id| session_id != for_session)
+            {
+                continue;
+            }
+            let Ok(json) = serde_json::to_string(&event) else {
+                continue;
+            };
+            if tx.send(format!("data: {}\n\n", json)).await.is_err() {
+                break;
+            }
+        }
+    });
+    SseResponse::new(ReceiverStream::new(rx))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route(
             "/reply",
             post(reply).layer(DefaultBodyLimit::max(50 * 1024 * 1024)),
         )
+        .route("/events", get(events))
         .with_state(state)
 }
 
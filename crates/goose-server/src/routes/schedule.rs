@@ -13,7 +13,7 @@ use crate::routes::errors::ErrorResponse;
 use crate::routes::recipe_utils::validate_recipe;
 use crate::state::AppState;
 use goose::recipe::Recipe;
-use goose::scheduler::{get_default_scheduled_recipes_dir, ScheduledJob};
+use goose::scheduler::{get_default_scheduled_recipes_dir, JobRunSettings, ScheduledJob};
 
 fn validate_schedule_id(id: &str) -> Result<(), ErrorResponse> {
     let is_valid = !id.is_empty()
@@ -35,11 +35,17 @@ pub struct CreateScheduleRequest {
     id: String,
     recipe: Recipe,
     cron: String,
+    /// Provider, model and permission profile the job runs with
+    #[serde(default, flatten)]
+    settings: JobRunSettings,
 }
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
 pub struct UpdateScheduleRequest {
     cron: String,
+    /// Replaces the job's run settings when present
+    #[serde(default)]
+    settings: Option<JobRunSettings>,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -143,6 +149,8 @@ async fn create_schedule(
         paused: false,
         current_session_id: None,
         process_start_time: None,
+        settings: req.settings,
+        last_run_status: None,
     };
 
     let scheduler = state.scheduler();
@@ -156,6 +164,9 @@ async fn create_schedule(
             goose::scheduler::SchedulerError::RecipeLoadError(msg) => {
                 ErrorResponse::bad_request(format!("Recipe load error: {}", msg))
             }
+            goose::scheduler::SchedulerError::InvalidRunSettings(msg) => {
+                ErrorResponse::bad_request(format!("Invalid run settings: {}", msg))
+            }
             goose::scheduler::SchedulerError::JobIdExists(msg) => ErrorResponse {
                 message: format!("Job ID already exists: {}", msg),
                 status: StatusCode::CONFLICT,
@@ -185,6 +196,33 @@ async fn list_schedules(
     Ok(Json(ListSchedulesResponse { jobs }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/schedule/{id}",
+    params(
+        ("id" = String, Path, description = "ID of the schedule")
+    ),
+    responses(
+        (status = 200, description = "The scheduled job with its last run status", body = ScheduledJob),
+        (status = 404, description = "Scheduled job not found")
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+async fn get_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<ScheduledJob>, ErrorResponse> {
+    state
+        .scheduler()
+        .list_scheduled_jobs()
+        .await
+        .into_iter()
+        .find(|job| job.id == id)
+        .map(Json)
+        .ok_or_else(|| ErrorResponse::not_found(format!("Schedule not found: {}", id)))
+}
+
 #[utoipa::path(
     delete,
     path = "/schedule/delete/{id}",
@@ -440,22 +478,32 @@ async fn update_schedule(
     Json(req): Json<UpdateScheduleRequest>,
 ) -> Result<Json<ScheduledJob>, ErrorResponse> {
     let scheduler = state.scheduler();
+    let map_err = |e: goose::scheduler::SchedulerError| match e {
+        goose::scheduler::SchedulerError::JobNotFound(msg) => {
+            ErrorResponse::not_found(format!("Schedule not found: {}", msg))
+        }
+        goose::scheduler::SchedulerError::AnyhowError(err) => {
+            ErrorResponse::bad_request(format!("Cannot update schedule: {}", err))
+        }
+        goose::scheduler::SchedulerError::CronParseError(msg) => {
+            ErrorResponse::bad_request(format!("Invalid cron expression: {}", msg))
+        }
+        goose::scheduler::SchedulerError::InvalidRunSettings(msg) => {
+            ErrorResponse::bad_request(format!("Invalid run settings: {}", msg))
+        }
+        _ => ErrorResponse::internal(format!("Error updating schedule: {}", e)),
+    };
 
     scheduler
         .update_schedule(&id, req.cron)
         .await
-        .map_err(|e| match e {
-            goose::scheduler::SchedulerError::JobNotFound(msg) => {
-                ErrorResponse::not_found(format!("Schedule not found: {}", msg))
-            }
-            goose::scheduler::SchedulerError::AnyhowError(err) => {
-                ErrorResponse::bad_request(format!("Cannot update schedule: {}", err))
-            }
-            goose::scheduler::SchedulerError::CronParseError(msg) => {
-                ErrorResponse::bad_request(format!("Invalid cron expression: {}", msg))
-            }
-            _ => ErrorResponse::internal(format!("Error updating schedule: {}", e)),
-        })?;
+        .map_err(map_err)?;
+    if let Some(settings) = req.settings {
+        scheduler
+            .update_run_settings(&id, settings)
+            .await
+            .map_err(map_err)?;
+    }
 
     let jobs = scheduler.list_scheduled_jobs().await;
     let updated_job = jobs
@@ -547,7 +595,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/schedule/create", post(create_schedule))
         .route("/schedule/list", get(list_schedules))
         .route("/schedule/delete/{id}", delete(delete_schedule)) // Corrected
-        .route("/schedule/{id}", put(update_schedule))
+        .route("/schedule/{id}", get(get_schedule).put(update_schedule))
         .route("/schedule/{id}/run_now", post(run_now_handler)) // Corrected
         .route("/schedule/{id}/pause", post(pause_schedule))
         .route("/schedule/{id}/unpause", post(unpause_schedule))
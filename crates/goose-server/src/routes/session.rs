@@ -9,8 +9,14 @@ use axum::{
     routing::{delete, get, put},
     Json, Router,
 };
+use base64::Engine;
 use goose::agents::ExtensionConfig;
+use goose::checkpoints::{Checkpoint, CheckpointStore};
+use goose::conversation::attachments::AttachmentStore;
+use goose::conversation::message::{AttachmentContent, Message};
+use goose::conversation::turns::TurnSummary;
 use goose::recipe::Recipe;
+use goose::session::branch::{Branch, BranchModel};
 use goose::session::session_manager::SessionInsights;
 use goose::session::{EnabledExtensionsState, Session};
 use serde::{Deserialize, Serialize};
@@ -32,6 +38,13 @@ pub struct UpdateSessionNameRequest {
     name: String,
 }
 
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionNameResponse {
+    /// The session's new name
+    name: String,
+}
+
 #[derive(Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateSessionUserRecipeValuesRequest {
@@ -152,7 +165,7 @@ async fn get_session_insights(
         ("session_id" = String, Path, description = "Unique identifier for the session")
     ),
     responses(
-        (status = 200, description = "Session name updated successfully"),
+        (status = 200, description = "Session name updated successfully; goose no longer renames it"),
         (status = 400, description = "Bad request - Name too long (max 200 characters)"),
         (status = 401, description = "Unauthorized - Invalid or missing API key"),
         (status = 404, description = "Session not found"),
@@ -187,6 +200,49 @@ async fn update_session_name(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/name/regenerate",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Session renamed after its conversation; it is named automatically from now on", body = SessionNameResponse),
+        (status = 400, description = "The session has no model to name it with", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn regenerate_session_name(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionNameResponse>, ErrorResponse> {
+    let session_manager = state.session_manager();
+    let session = session_manager
+        .get_session(&session_id, false)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    let (Some(provider_name), Some(model_config)) = (session.provider_name, session.model_config)
+    else {
+        return Err(ErrorResponse::bad_request(
+            "The session has no model to name it with",
+        ));
+    };
+    let provider = goose::providers::create(&provider_name, model_config, Vec::new())
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("{:#}", e)))?;
+    let name = session_manager
+        .regenerate_name(&session_id, provider)
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("{:#}", e)))?;
+    Ok(Json(SessionNameResponse { name }))
+}
+
 #[utoipa::path(
     put,
     path = "/sessions/{session_id}/user_recipe_values",
@@ -501,6 +557,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
         )
         .route("/sessions/insights", get(get_session_insights))
         .route("/sessions/{session_id}/name", put(update_session_name))
+        .route(
+            "/sessions/{session_id}/name/regenerate",
+            post(regenerate_session_name),
+        )
         .route(
             "/sessions/{session_id}/user_recipe_values",
             put(update_session_user_recipe_values),
@@ -510,6 +570,31 @@ pub fn routes(state: Arc<AppState>) -> Router {
             "/sessions/{session_id}/extensions",
             get(get_session_extensions),
         )
+        .route("/sessions/{session_id}/checkpoints", get(list_checkpoints))
+        .route(
+            "/sessions/{session_id}/checkpoints/{checkpoint_id}/diff",
+            get(diff_checkpoint),
+        )
+        .route(
+            "/sessions/{session_id}/checkpoints/{checkpoint_id}/revert",
+            post(revert_checkpoint),
+        )
+        .route(
+            "/sessions/{session_id}/checkpoints/{checkpoint_id}/squash",
+            post(squash_checkpoints),
+        )
+        .route(
+            "/sessions/{session_id}/attachments",
+            post(add_attachment).layer(DefaultBodyLimit::max(25 * 1024 * 1024)),
+        )
+        .route("/sessions/{session_id}/turns", get(list_turns))
+        .route("/sessions/{session_id}/turns/{turn}", delete(delete_turn))
+        .route(
+            "/sessions/{session_id}/turns/{turn}/retry",
+            post(retry_turn),
+        )
+        .route("/sessions/{session_id}/edit", post(edit_message))
+        .route("/sessions/{session_id}/regenerate", post(regenerate))
         .with_state(state)
 }
 #[derive(Deserialize, ToSchema)]
@@ -597,3 +682,423 @@ async fn search_sessions(
 
     Ok(Json(matching_sessions))
 }
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointListResponse {
+    /// Checkpoints of the session's work tree, newest first
+    checkpoints: Vec<Checkpoint>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointDiffQuery {
+    /// Checkpoint to diff against. Omit to diff against the current work tree.
+    to: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointDiffResponse {
+    diff: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SquashCheckpointsRequest {
+    /// Message for the squashed checkpoint
+    message: Option<String>,
+}
+
+async fn checkpoint_store(
+    state: &AppState,
+    session_id: &str,
+) -> Result<CheckpointStore, ErrorResponse> {
+    let session = state
+        .session_manager()
+        .get_session(session_id, false)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    CheckpointStore::open(&session.working_dir, session_id)
+        .await
+        .ok_or_else(|| {
+            ErrorResponse::bad_request("The session's working directory is not a git repository")
+        })
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/checkpoints",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Checkpoints retrieved successfully", body = CheckpointListResponse),
+        (status = 400, description = "Working directory is not a git repository", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn list_checkpoints(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<CheckpointListResponse>, ErrorResponse> {
+    let store = checkpoint_store(&state, &session_id).await?;
+    let checkpoints = store
+        .list()
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to list checkpoints: {}", e)))?;
+    Ok(Json(CheckpointListResponse { checkpoints }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/checkpoints/{checkpoint_id}/diff",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("checkpoint_id" = String, Path, description = "Checkpoint to diff from"),
+        ("to" = Option<String>, Query, description = "Checkpoint to diff to (default: the current work tree)")
+    ),
+    responses(
+        (status = 200, description = "Diff computed successfully", body = CheckpointDiffResponse),
+        (status = 400, description = "Unknown checkpoint or not a git repository", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn diff_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, checkpoint_id)): Path<(String, String)>,
+    axum::extract::Query(query): axum::extract::Query<CheckpointDiffQuery>,
+) -> Result<Json<CheckpointDiffResponse>, ErrorResponse> {
+    let store = checkpoint_store(&state, &session_id).await?;
+    let diff = store
+        .diff(&checkpoint_id, query.to.as_deref())
+        .await
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+    Ok(Json(CheckpointDiffResponse { diff }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/checkpoints/{checkpoint_id}/revert",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("checkpoint_id" = String, Path, description = "Checkpoint to restore the work tree to")
+    ),
+    responses(
+        (status = 200, description = "Work tree reverted; returns the new checkpoint", body = Checkpoint),
+        (status = 400, description = "Unknown checkpoint or not a git repository", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn revert_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, checkpoint_id)): Path<(String, String)>,
+) -> Result<Json<Checkpoint>, ErrorResponse> {
+    let store = checkpoint_store(&state, &session_id).await?;
+    let checkpoint = store
+        .revert(&checkpoint_id)
+        .await
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+    Ok(Json(checkpoint))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/checkpoints/{checkpoint_id}/squash",
+    request_body = SquashCheckpointsRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("checkpoint_id" = String, Path, description = "Checkpoint after which all checkpoints are squashed")
+    ),
+    responses(
+        (status = 200, description = "Checkpoints squashed successfully", body = Checkpoint),
+        (status = 400, description = "Nothing to squash, unknown checkpoint or not a git repository", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn squash_checkpoints(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, checkpoint_id)): Path<(String, String)>,
+    Json(request): Json<SquashCheckpointsRequest>,
+) -> Result<Json<Checkpoint>, ErrorResponse> {
+    let store = checkpoint_store(&state, &session_id).await?;
+    let checkpoint = store
+        .squash(&checkpoint_id, request.message.as_deref())
+        .await
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+    Ok(Json(checkpoint))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddAttachmentRequest {
+    /// File name shown to the user and the model
+    name: String,
+    /// MIME type of the file. Guessed from the name and contents when omitted.
+    mime_type: Option<String>,
+    /// File contents, base64 encoded
+    data: String,
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/attachments",
+    request_body = AddAttachmentRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "File stored; add the attachment to a message to send it", body = AttachmentContent),
+        (status = 400, description = "Data is not valid base64", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn add_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<AddAttachmentRequest>,
+) -> Result<Json<AttachmentContent>, ErrorResponse> {
+    state
+        .session_manager()
+        .get_session(&session_id, false)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(&request.data)
+        .map_err(|e| ErrorResponse::bad_request(format!("Invalid attachment data: {}", e)))?;
+    let attachment = AttachmentStore::for_session(&session_id)
+        .add_bytes(&request.name, request.mime_type.as_deref(), &data)
+        .map_err(|e| ErrorResponse::internal(format!("{:#}", e)))?;
+    Ok(Json(attachment))
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/turns",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "The session's turns, oldest first", body = Vec<TurnSummary>),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn list_turns(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<TurnSummary>>, ErrorResponse> {
+    let turns = state
+        .session_manager()
+        .list_turns(&session_id)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    Ok(Json(turns))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/sessions/{session_id}/turns/{turn}",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("turn" = u32, Path, description = "Number of the turn to delete")
+    ),
+    responses(
+        (status = 200, description = "Turn deleted"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session or turn not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn delete_turn(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, turn)): Path<(String, u32)>,
+) -> Result<StatusCode, ErrorResponse> {
+    state
+        .session_manager()
+        .delete_turn(&session_id, turn)
+        .await
+        .map_err(|e| ErrorResponse::not_found(format!("{:#}", e)))?;
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/turns/{turn}/retry",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("turn" = u32, Path, description = "Number of the turn to retry")
+    ),
+    responses(
+        (status = 200, description = "Session rewound to before the turn; send the returned prompt to run it again", body = Message),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session or turn not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn retry_turn(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, turn)): Path<(String, u32)>,
+) -> Result<Json<Message>, ErrorResponse> {
+    let prompt = state
+        .session_manager()
+        .retry_turn(&session_id, turn)
+        .await
+        .map_err(|e| ErrorResponse::not_found(format!("{:#}", e)))?;
+    Ok(Json(prompt))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EditMessageRequest {
+    /// Id of the user message to edit
+    message_id: String,
+    /// The message's new text
+    text: String,
+    /// Model settings for the branch, if they should differ from the session's
+    #[serde(default)]
+    model: BranchModel,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerateRequest {
+    /// Model settings for the branch, if they should differ from the session's
+    #[serde(default)]
+    model: BranchModel,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchResponse {
+    /// The new session, a copy of the original up to the prompt
+    session_id: String,
+    /// The prompt to send in the new session
+    prompt: Message,
+}
+
+impl From<Branch> for BranchResponse {
+    fn from(branch: Branch) -> Self {
+        Self {
+            session_id: branch.session.id,
+            prompt: branch.prompt,
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/edit",
+    request_body = EditMessageRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Branch created; resume it and send the prompt", body = BranchResponse),
+        (status = 400, description = "The message can't be edited", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn edit_message(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<EditMessageRequest>,
+) -> Result<Json<BranchResponse>, ErrorResponse> {
+    let session_manager = state.session_manager();
+    session_manager
+        .get_session(&session_id, false)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    let branch = session_manager
+        .edit_message(
+            &session_id,
+            &request.message_id,
+            &request.text,
+            &request.model,
+        )
+        .await
+        .map_err(|e| ErrorResponse::bad_request(format!("{:#}", e)))?;
+    Ok(Json(branch.into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/regenerate",
+    request_body = RegenerateRequest,
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Branch created; resume it and send the prompt", body = BranchResponse),
+        (status = 400, description = "The session has no turn to regenerate", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+async fn regenerate(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<RegenerateRequest>,
+) -> Result<Json<BranchResponse>, ErrorResponse> {
+    let session_manager = state.session_manager();
+    session_manager
+        .get_session(&session_id, false)
+        .await
+        .map_err(|_| ErrorResponse::not_found(format!("Session {} not found", session_id)))?;
+    let branch = session_manager
+        .regenerate(&session_id, &request.model)
+        .await
+        .map_err(|e| ErrorResponse::bad_request(format!("{:#}", e)))?;
+    Ok(Json(branch.into()))
+}
@@ -0,0 +1,152 @@
+//! Benchmarks for the work the agent does on every turn.
+//!
+//! Run with `just bench`. To compare against a baseline, save one on the base branch with
+//! `just bench-baseline` and run `just bench-compare` on the branch under test; criterion
+//! reports the change for each benchmark and flags regressions.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use goose::context_mgmt::budget::{fit_sections, ContextSource};
+use goose::conversation::message::Message;
+use goose::conversation::Conversation;
+use goose::hooks::{HookEvent, HookRuntime};
+use goose::providers::base::{collect_stream, MessageStream, ProviderUsage, Usage};
+use goose::token_counter::TokenCounter;
+use rmcp::model::{CallToolRequestParams, CallToolResult, Content};
+use rmcp::object;
+use serde_json::json;
+
+/// A conversation of `turns` user prompts, each answered with a tool call and its result
+fn conversation(turns: usize) -> Conversation {
+    let mut messages = Vec::with_capacity(turns * 4);
+    for turn in 0..turns {
+        let id = format!("call_{}", turn);
+        messages.push(Message::user().with_text(format!("Please look at file {}.rs", turn)));
+        messages.push(Message::assistant().with_tool_request(
+            &id,
+            Ok(
+                CallToolRequestParams::new("developer__text_editor").with_arguments(object!({
+                    "command": "view",
+                    "path": format!("src/file_{}.rs", turn)
+                })),
+            ),
+        ));
+        messages.push(Message::user().with_tool_response(
+            &id,
+            Ok(CallToolResult::success(vec![Content::text(
+                "fn main() {\n    println!(\"hello\");\n}\n".repeat(40),
+            )])),
+        ));
+        messages.push(
+            Message::assistant().with_text("The file defines a main function that prints hello."),
+        );
+    }
+    Conversation::new_unvalidated(messages)
+}
+
+fn text_stream(chunks: usize) -> MessageStream {
+    let items = (0..chunks).map(|i| {
+        let usage =
+            (i + 1 == chunks).then(|| ProviderUsage::new("bench".to_string(), Usage::default()));
+        Ok((Some(Message::assistant().with_text("token ")), usage))
+    });
+    Box::pin(futures::stream::iter(items.collect::<Vec<_>>()))
+}
+
+fn bench_collect_stream(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    c.bench_function("collect_stream/2000_text_chunks", |b| {
+        b.to_async(&runtime).iter_batched(
+            || text_stream(2000),
+            |stream| async move { black_box(collect_stream(stream).await.unwrap()) },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_conversation_serialization(c: &mut Criterion) {
+    let conversation = conversation(50);
+    let json = serde_json::to_string(&conversation).unwrap();
+
+    let mut group = c.benchmark_group("conversation/200_messages");
+    group.bench_function("serialize", |b| {
+        b.iter(|| black_box(serde_json::to_string(&conversation).unwrap()))
+    });
+    group.bench_function("deserialize", |b| {
+        b.iter(|| black_box(serde_json::from_str::<Conversation>(&json).unwrap()))
+    });
+    group.finish();
+}
+
+fn bench_context_assembly(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let counter = runtime.block_on(TokenCounter::new()).unwrap();
+    let conversation = conversation(50);
+    let hints: Vec<(String, String)> = (0..8)
+        .map(|i| {
+            (
+                format!("hint_{}", i),
+                "Prefer small, focused changes. ".repeat(200),
+            )
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("context");
+    // A fresh counter each time so the token cache does not hide tokenizer cost
+    group.bench_function("count_chat_tokens/200_messages", |b| {
+        b.iter_batched(
+            || runtime.block_on(TokenCounter::new()).unwrap(),
+            |counter| {
+                black_box(counter.count_chat_tokens("You are goose.", conversation.messages(), &[]))
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("fit_sections/8_hints", |b| {
+        b.iter_batched(
+            || hints.clone(),
+            |mut hints| {
+                let sections = hints
+                    .iter_mut()
+                    .map(|(name, content)| (name.clone(), content));
+                black_box(fit_sections(
+                    ContextSource::Hints,
+                    sections,
+                    2_000,
+                    &counter,
+                ))
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn bench_hook_matching(c: &mut Criterion) {
+    let matchers: Vec<_> = (0..20)
+        .map(|i| json!({ "matcher": format!("Bash(tool_{}*)", i), "hooks": [{ "type": "command", "command": "true" }] }))
+        .chain([json!({ "matcher": "developer__text_editor", "hooks": [{ "type": "command", "command": "true" }] })])
+        .collect();
+    let runtime = HookRuntime::from_json(
+        &json!({ "hooks": { "PreToolUse": matchers, "PostToolUse": matchers } }).to_string(),
+    )
+    .unwrap();
+    let event = HookEvent::PreToolUse {
+        session_id: "bench".to_string(),
+        tool_name: "developer__shell".to_string(),
+        tool_input: json!({ "command": "cargo test --workspace" }),
+        cwd: "/tmp".into(),
+    };
+
+    c.bench_function("hooks/match_21_pre_tool_use_matchers", |b| {
+        b.iter(|| black_box(runtime.has_hooks_for(black_box(&event))))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_collect_stream,
+    bench_conversation_serialization,
+    bench_context_assembly,
+    bench_hook_matching
+);
+criterion_main!(benches);
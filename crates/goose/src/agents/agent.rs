@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
@@ -10,22 +10,39 @@ use futures::{stream, FutureExt, Stream, StreamExt, TryStreamExt};
 use uuid::Uuid;
 
 use super::container::Container;
+use super::dry_run::DryRunInspector;
+use super::extension_capabilities::CapabilityInspector;
 use super::final_output_tool::FinalOutputTool;
+use super::lifecycle::{self, LifecycleEvent};
+use super::plan_mode::{self, PlanModeInspector, PlanModeState};
 use super::platform_tools;
-use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE};
+use super::tool_bridge::{self, BridgedCall, ToolBridgeServer};
+use super::tool_execution::{
+    ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE,
+    STEERING_TOOL_SKIPPED_RESPONSE,
+};
+use super::tool_scheduler::{max_parallel_tool_calls, schedule_tool_streams, ToolFootprint};
+use super::turn_validation;
 use crate::action_required_manager::ActionRequiredManager;
 use crate::agents::extension::{ExtensionConfig, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{
-    get_parameter_names, ExtensionManager, ExtensionManagerCapabilities,
+    get_parameter_names, ExtensionManager, ExtensionManagerCapabilities, ToolSetSnapshot,
 };
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
 use crate::agents::platform_extensions::MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE;
 use crate::agents::platform_tools::PLATFORM_MANAGE_SCHEDULE_TOOL_NAME;
 use crate::agents::prompt_manager::PromptManager;
 use crate::agents::retry::{RetryManager, RetryResult};
-use crate::agents::types::{FrontendTool, SessionConfig, SharedProvider, ToolResultReceiver};
+use crate::agents::tool_selection::{self, LIST_ALL_TOOLS_TOOL_NAME};
+use crate::agents::types::{
+    FrontendTool, SessionConfig, SharedProvider, SteeringMessage, ToolResultReceiver,
+};
+use crate::checkpoints::{self, CheckpointStore};
 use crate::config::permission::PermissionManager;
 use crate::config::{get_enabled_extensions, Config, GooseMode};
+use crate::context_mgmt::budget::ContextReport;
+use crate::context_mgmt::recovery::{drop_oldest_tool_outputs, ContextRecovery};
+use crate::context_mgmt::workspace_changes;
 use crate::context_mgmt::{
     check_if_compaction_needed, compact_messages, DEFAULT_COMPACTION_THRESHOLD,
 };
@@ -33,20 +50,34 @@ use crate::conversation::message::{
     ActionRequiredData, Message, MessageContent, ProviderMetadata, SystemNotificationType,
     ToolRequest,
 };
+use crate::conversation::turns::record_usage;
 use crate::conversation::{debug_conversation_fix, fix_conversation, Conversation};
-use crate::hooks::{HookEvent, HookRuntime};
+use crate::hints::load_hints::configured_hint_files;
+use crate::hooks::config_change::{self, ConfigSnapshot};
+use crate::hooks::types::NotificationSeverity;
+use crate::hooks::{HookEvent, HookOutcome, HookRuntime};
+use crate::i18n;
+use crate::loop_guard::{LoopGuardConfig, LoopGuardInspector, LOOP_GUARD_INSPECTOR_NAME};
 use crate::mcp_utils::ToolResult;
+use crate::notifications::{self, Notification, NotificationKind};
 use crate::permission::permission_inspector::PermissionInspector;
 use crate::permission::permission_judge::PermissionCheckResult;
 use crate::permission::PermissionConfirmation;
-use crate::providers::base::{PermissionRouting, Provider};
-use crate::providers::errors::ProviderError;
+use crate::providers::base::{PermissionRouting, Provider, ProviderUsage};
+use crate::providers::canonical::maybe_get_canonical_model;
+use crate::providers::errors::{ProviderError, ProviderErrorCode};
+use crate::providers::preflight;
 use crate::recipe::{Author, Recipe, Response, Settings};
+use crate::retrieval;
 use crate::scheduler_trait::SchedulerTrait;
+use crate::security::audit;
+use crate::security::secrets::SecretsInspector;
 use crate::security::security_inspector::SecurityInspector;
 use crate::session::extension_data::{EnabledExtensionsState, ExtensionState};
-use crate::session::{Session, SessionManager};
-use crate::tool_inspection::ToolInspectionManager;
+use crate::session::{Session, SessionManager, SessionType};
+use crate::shutdown::{ShutdownCoordinator, ShutdownGuard};
+use crate::token_counter::create_token_counter;
+use crate::tool_inspection::{inspect_tool_output, OutputInspectionContext, ToolInspectionManager};
 use crate::tool_monitor::RepetitionInspector;
 use crate::utils::is_token_cancelled;
 use regex::Regex;
@@ -60,7 +91,11 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn};
 
 const DEFAULT_MAX_TURNS: u32 = 1000;
-const COMPACTION_THINKING_TEXT: &str = "goose is compacting the conversation...";
+/// How many times Stop hooks may keep one turn going
+const MAX_STOP_HOOK_CONTINUATIONS: u32 = 10;
+const TOOLS_CHANGED_NOTE: &str = "The available tools changed since your last turn. \
+    Use the current tool definitions; tools you used before may be gone or take different \
+    arguments. Extensions with changed tools: ";
 
 /// Context needed for the reply function
 pub struct ReplyContext {
@@ -150,6 +185,13 @@ pub struct Agent {
     pub(super) retry_manager: RetryManager,
     pub(super) tool_inspection_manager: ToolInspectionManager,
     container: Mutex<Option<Container>>,
+    /// Messages the user sent to steer the turn in progress; `None` while no turn is running
+    steering: std::sync::Mutex<Option<Vec<SteeringMessage>>>,
+    pub(super) context_report: Mutex<Option<ContextReport>>,
+    tool_bridge: Mutex<Option<ToolBridgeServer>>,
+    bridged_calls_tx: mpsc::Sender<BridgedCall>,
+    bridged_calls_rx: Mutex<mpsc::Receiver<BridgedCall>>,
+    _shutdown_guard: ShutdownGuard,
 }
 
 #[derive(Clone, Debug)]
@@ -166,6 +208,21 @@ impl Default for Agent {
     }
 }
 
+/// Closes the steering queue when a turn's stream ends or is dropped
+struct SteeringWindow<'a>(&'a std::sync::Mutex<Option<Vec<SteeringMessage>>>);
+
+impl Drop for SteeringWindow<'_> {
+    fn drop(&mut self) {
+        let unread = self.0.lock().map(|mut queue| queue.take()).ok().flatten();
+        if let Some(unread) = unread.filter(|queue| !queue.is_empty()) {
+            debug!(
+                "Dropping {} steering message(s) sent as the turn ended",
+                unread.len()
+            );
+        }
+    }
+}
+
 pub enum ToolStreamItem<T> {
     Message(ServerNotification),
     Result(T),
@@ -201,6 +258,50 @@ where
     })
 }
 
+/// Why the agent loop is ending a turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TurnEnd {
+    /// The model answered without calling tools
+    Answered,
+    /// The final output tool delivered its result
+    FinalOutput,
+    /// The loop guard paused the session
+    Paused,
+    /// Retry logic failed
+    Failed,
+}
+
+/// The instruction a blocking Stop hook gives the agent, if the turn may go on. Only a turn the
+/// model ended itself can be continued; a pause, a delivered final output or a failure stands.
+fn stop_hook_instruction(
+    outcome: &HookOutcome,
+    end: TurnEnd,
+    continuations: u32,
+) -> Option<String> {
+    if !outcome.blocked {
+        return None;
+    }
+    if end != TurnEnd::Answered {
+        warn!(
+            "Ignoring a blocking Stop hook because the turn ended with {:?}",
+            end
+        );
+        return None;
+    }
+    let Some(reason) = outcome.reason.as_deref() else {
+        warn!("A Stop hook blocked without a reason, so the agent has nothing to continue with");
+        return None;
+    };
+    if continuations >= MAX_STOP_HOOK_CONTINUATIONS {
+        warn!(
+            "Stop hooks kept the turn going {} times; letting it end",
+            continuations
+        );
+        return None;
+    }
+    Some(format!("Stop hook feedback:\n{}", reason))
+}
+
 impl Agent {
     pub fn new() -> Self {
         Self::with_config(AgentConfig::new(
@@ -219,6 +320,7 @@ impl Agent {
         // Create channels with buffer size 32 (adjust if needed)
         let (confirm_tx, confirm_rx) = mpsc::channel(32);
         let (tool_tx, tool_rx) = mpsc::channel(32);
+        let (bridged_calls_tx, bridged_calls_rx) = mpsc::channel(32);
         let provider = Arc::new(Mutex::new(None));
 
         let goose_platform = config.goose_platform.clone();
@@ -228,15 +330,30 @@ impl Agent {
         };
         let session_manager = Arc::clone(&config.session_manager);
         let permission_manager = Arc::clone(&config.permission_manager);
+        let extension_manager = Arc::new(ExtensionManager::new(
+            provider.clone(),
+            session_manager.clone(),
+            goose_platform.to_string(),
+            capabilities,
+        ));
+        // Stop the extensions and drop the provider, ending their child processes, if goose
+        // shuts down while the agent is alive
+        let shutdown_guard = {
+            let extension_manager = Arc::downgrade(&extension_manager);
+            let provider = Arc::downgrade(&provider);
+            ShutdownCoordinator::global().on_shutdown(move || async move {
+                if let Some(extension_manager) = extension_manager.upgrade() {
+                    extension_manager.shutdown().await;
+                }
+                if let Some(provider) = provider.upgrade() {
+                    provider.lock().await.take();
+                }
+            })
+        };
         Self {
             provider: provider.clone(),
             config,
-            extension_manager: Arc::new(ExtensionManager::new(
-                provider.clone(),
-                session_manager,
-                goose_platform.to_string(),
-                capabilities,
-            )),
+            extension_manager: extension_manager.clone(),
             final_output_tool: Arc::new(Mutex::new(None)),
             frontend_tools: Mutex::new(HashMap::new()),
             frontend_instructions: Mutex::new(None),
@@ -249,8 +366,16 @@ impl Agent {
             tool_inspection_manager: Self::create_tool_inspection_manager(
                 permission_manager,
                 provider.clone(),
+                session_manager,
+                extension_manager,
             ),
             container: Mutex::new(None),
+            steering: std::sync::Mutex::new(None),
+            context_report: Mutex::new(None),
+            tool_bridge: Mutex::new(None),
+            bridged_calls_tx,
+            bridged_calls_rx: Mutex::new(bridged_calls_rx),
+            _shutdown_guard: shutdown_guard,
         }
     }
 
@@ -258,21 +383,49 @@ impl Agent {
     fn create_tool_inspection_manager(
         permission_manager: Arc<PermissionManager>,
         provider: SharedProvider,
+        session_manager: Arc<SessionManager>,
+        extension_manager: Arc<ExtensionManager>,
     ) -> ToolInspectionManager {
         let mut tool_inspection_manager = ToolInspectionManager::new();
 
-        // Add security inspector (highest priority - runs first)
+        // Add security inspector (highest priority)
         tool_inspection_manager.add_inspector(Box::new(SecurityInspector::new()));
 
-        // Add permission inspector (medium-high priority)
-        tool_inspection_manager.add_inspector(Box::new(PermissionInspector::new(
-            permission_manager,
-            provider,
+        // Hold mutating tools for approval with a preview in dry-run sessions
+        tool_inspection_manager.add_inspector(Box::new(DryRunInspector::new(
+            session_manager.clone(),
+            extension_manager.clone(),
+        )));
+
+        // Block mutating tools in plan mode until the user approves a plan
+        tool_inspection_manager.add_inspector(Box::new(PlanModeInspector::new(
+            session_manager.clone(),
+            extension_manager.clone(),
+        )));
+
+        // Hold calls that use capabilities an extension did not declare
+        tool_inspection_manager.add_inspector(Box::new(CapabilityInspector::new(
+            extension_manager.clone(),
         )));
 
+        // Add permission inspector (medium-high priority)
+        tool_inspection_manager.add_inspector(Box::new(
+            PermissionInspector::new(permission_manager, provider)
+                .with_session_manager(session_manager),
+        ));
+
         // Add repetition inspector (lower priority - basic repetition checking)
         tool_inspection_manager.add_inspector(Box::new(RepetitionInspector::new(None)));
 
+        // Stop repeated calls, oscillating edits and repeated failures, pausing the session
+        tool_inspection_manager.add_inspector(Box::new(LoopGuardInspector::new(
+            LoopGuardConfig::from_config(),
+            extension_manager,
+        )));
+
+        // Mask credentials in tool results before they enter the conversation
+        tool_inspection_manager.add_output_inspector(Arc::new(SecretsInspector::new()));
+
         tool_inspection_manager
     }
 
@@ -387,7 +540,7 @@ impl Agent {
         }
     }
 
-    async fn handle_approved_and_denied_tools(
+    pub(super) async fn handle_approved_and_denied_tools(
         &self,
         permission_check_result: &PermissionCheckResult,
         request_to_response_map: &HashMap<String, Arc<Mutex<Message>>>,
@@ -489,6 +642,42 @@ impl Agent {
         Ok(())
     }
 
+    /// Show the model a prompt a hook rewrote in place of the user's latest one. The
+    /// original stays in the session for the user to see.
+    async fn apply_rewritten_prompt(
+        session_id: &str,
+        prompt: String,
+        session_manager: &SessionManager,
+        conversation: &mut Conversation,
+    ) -> Result<()> {
+        let mut messages = conversation.messages().clone();
+        let Some(index) = messages
+            .iter()
+            .rposition(|m| m.role == rmcp::model::Role::User)
+        else {
+            return Ok(());
+        };
+
+        let original = &mut messages[index];
+        // Keep attachments such as images; only the text is replaced
+        let mut content: Vec<MessageContent> = original
+            .content
+            .iter()
+            .filter(|c| c.as_text().is_none())
+            .cloned()
+            .collect();
+        content.push(MessageContent::text(prompt));
+        let rewritten = Message::new(rmcp::model::Role::User, original.created, content)
+            .with_visibility(false, true);
+        original.metadata = original.metadata.with_agent_invisible();
+        messages.insert(index + 1, rewritten);
+
+        *conversation = Conversation::new_unvalidated(messages);
+        session_manager
+            .replace_conversation(session_id, conversation)
+            .await
+    }
+
     /// Get a reference count clone to the provider
     pub async fn provider(&self) -> Result<Arc<dyn Provider>, anyhow::Error> {
         match &*self.provider.lock().await {
@@ -506,6 +695,38 @@ impl Agent {
         self.container.lock().await.clone()
     }
 
+    /// How the context window was shared out when the system prompt was last built
+    pub async fn context_report(&self) -> Option<ContextReport> {
+        self.context_report.lock().await.clone()
+    }
+
+    /// Queue a message from the user for the turn in progress. It is added to the conversation
+    /// at the next tool boundary. Returns false, without queueing it, when no turn is running.
+    pub fn steer(&self, message: SteeringMessage) -> bool {
+        match self.steering.lock().unwrap().as_mut() {
+            Some(queue) => {
+                queue.push(message);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Accept steering messages until the returned guard is dropped with the turn's stream
+    fn accept_steering(&self) -> SteeringWindow<'_> {
+        *self.steering.lock().unwrap() = Some(Vec::new());
+        SteeringWindow(&self.steering)
+    }
+
+    fn take_steering(&self) -> Vec<SteeringMessage> {
+        self.steering
+            .lock()
+            .unwrap()
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
     /// Check if a tool is a frontend tool
     pub async fn is_frontend_tool(&self, name: &str) -> bool {
         self.frontend_tools.lock().await.contains_key(name)
@@ -551,6 +772,11 @@ impl Agent {
             "arguments": tool_call.arguments,
         });
         tracing::Span::current().record("input", tracing::field::display(&input_summary));
+        lifecycle::publish(LifecycleEvent::ToolDispatched {
+            session_id: session.id.clone(),
+            request_id: request_id.clone(),
+            tool_name: tool_call.name.to_string(),
+        });
 
         if tool_call.name == PLATFORM_MANAGE_SCHEDULE_TOOL_NAME {
             let arguments = tool_call
@@ -564,6 +790,24 @@ impl Agent {
             return (request_id, Ok(ToolCallResult::from(wrapped_result)));
         }
 
+        if tool_call.name == LIST_ALL_TOOLS_TOOL_NAME {
+            let mut tools = self.list_tools(&session.id, None).await;
+            let frontend_tools = self.frontend_tools.lock().await;
+            tools.extend(
+                frontend_tools
+                    .values()
+                    .map(|frontend| frontend.tool.clone()),
+            );
+            let listing = tool_selection::describe_tools(&tools);
+            let result = Ok(CallToolResult::success(vec![Content::text(listing)]));
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
+        if plan_mode::is_plan_tool(&tool_call.name) {
+            let result = self.handle_plan_tool(&tool_call, &session.id).await;
+            return (request_id, Ok(ToolCallResult::from(result)));
+        }
+
         if tool_call.name == FINAL_OUTPUT_TOOL_NAME {
             return if let Some(final_output_tool) = self.final_output_tool.lock().await.as_mut() {
                 let result = final_output_tool.execute_tool_call(tool_call.clone()).await;
@@ -614,15 +858,26 @@ impl Agent {
 
         debug!("WAITING_TOOL_END: {}", tool_call.name);
 
+        let output_inspectors = self.tool_inspection_manager.output_inspectors();
+        let output_context = OutputInspectionContext {
+            session_id: session.id.clone(),
+            tool_name: tool_call.name.to_string(),
+            extension_data: session.extension_data.clone(),
+        };
+
         (
             request_id,
             Ok(ToolCallResult {
                 notification_stream: result.notification_stream,
-                result: Box::new(
-                    result
-                        .result
-                        .map(super::large_response_handler::process_tool_response),
-                ),
+                result: Box::new(result.result.map(move |response| {
+                    let response = response.map(|result| {
+                        inspect_tool_output(&output_inspectors, &output_context, result)
+                    });
+                    super::large_response_handler::process_tool_response(
+                        &output_context.tool_name,
+                        response,
+                    )
+                })),
             }),
         )
     }
@@ -851,6 +1106,17 @@ impl Agent {
             if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
                 prefixed_tools.push(final_output_tool.tool());
             }
+
+            if let Ok(session) = self
+                .config
+                .session_manager
+                .get_session(session_id, false)
+                .await
+            {
+                if PlanModeState::load(&session.extension_data).enabled {
+                    prefixed_tools.extend(plan_mode::plan_tools());
+                }
+            }
         }
 
         prefixed_tools
@@ -870,6 +1136,93 @@ impl Agent {
         Ok(())
     }
 
+    /// Save the session's extensions and tools as `name`, to switch back to them later
+    pub async fn snapshot_tool_set(
+        &self,
+        session_id: &str,
+        name: &str,
+    ) -> ExtensionResult<ToolSetSnapshot> {
+        self.extension_manager
+            .snapshot_tool_set(session_id, name)
+            .await
+    }
+
+    /// Run the session with exactly the extensions of `snapshot` and persist them with it
+    pub async fn apply_tool_set(
+        &self,
+        snapshot: &ToolSetSnapshot,
+        session_id: &str,
+    ) -> ExtensionResult<()> {
+        let session = self
+            .config
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .map_err(|e| {
+                crate::agents::extension::ExtensionError::SetupError(format!(
+                    "Failed to get session '{}': {}",
+                    session_id, e
+                ))
+            })?;
+        // Frontend tools are kept by the agent rather than the extension manager
+        let (frontend, servers): (Vec<_>, Vec<_>) = snapshot
+            .extensions()
+            .iter()
+            .cloned()
+            .partition(|config| matches!(config, ExtensionConfig::Frontend { .. }));
+        let container = self.container.lock().await.clone();
+        let mut result = self
+            .extension_manager
+            .apply_tool_set(
+                &ToolSetSnapshot::new(servers),
+                Some(session.working_dir),
+                container.as_ref(),
+                Some(session_id),
+            )
+            .await;
+        for config in frontend {
+            if let Err(e) = self.add_extension_inner(config, session_id).await {
+                result = result.and(Err(e));
+            }
+        }
+
+        if let Err(e) = self.persist_extension_state(session_id).await {
+            warn!("Failed to persist extension state: {}", e);
+        }
+        result
+    }
+
+    /// Switch the session back to the tool set saved as `name`
+    pub async fn restore_tool_set(&self, session_id: &str, name: &str) -> ExtensionResult<()> {
+        let snapshot = self.extension_manager.tool_set(name).await.ok_or_else(|| {
+            crate::agents::extension::ExtensionError::ConfigError(format!(
+                "Unknown tool set: {}",
+                name
+            ))
+        })?;
+        self.apply_tool_set(&snapshot, session_id).await
+    }
+
+    /// Enable a configured extension by name for this session, or disable it.
+    /// The change is persisted with the session and the provider sees the new tool list on
+    /// the next turn, even if a reply is in progress.
+    pub async fn set_extension_enabled(
+        &self,
+        session_id: &str,
+        name: &str,
+        enabled: bool,
+    ) -> Result<()> {
+        if !enabled {
+            return self.remove_extension(name, session_id).await;
+        }
+
+        let config = crate::config::get_extension_by_name(name)
+            .ok_or_else(|| anyhow!("Extension '{}' is not configured", name))?;
+        self.add_extension(config, session_id)
+            .await
+            .map_err(|e| anyhow!("Failed to enable extension '{}': {}", name, e))
+    }
+
     pub async fn list_extensions(&self) -> Vec<String> {
         self.extension_manager
             .list_extensions()
@@ -950,6 +1303,10 @@ impl Agent {
             }
         }
 
+        // Everything from here on answers this message, so it starts a turn
+        let turn = session_manager.begin_turn(&session_config.id).await?;
+        let user_message = user_message.with_turn(turn);
+
         let message_text = user_message.as_concat_text();
 
         // Track custom slash command usage (don't track command name for privacy)
@@ -1040,6 +1397,15 @@ impl Agent {
 
         let hooks = HookRuntime::load(&session.working_dir);
 
+        // Stopped when goose shuts down, ending the provider stream
+        let cancel_token = Some(cancel_token.unwrap_or_default());
+        let shutdown = ShutdownCoordinator::global();
+        let shutdown_guard = shutdown.track_reply(cancel_token.clone().unwrap_or_default());
+        // A subagent's session ends with its task, not with goose
+        if session.session_type != SessionType::SubAgent {
+            shutdown.track_session(&session_config.id, session.working_dir.clone());
+        }
+
         let needs_auto_compact = check_if_compaction_needed(
             self.provider().await?.as_ref(),
             &conversation,
@@ -1051,6 +1417,7 @@ impl Agent {
         let conversation_to_compact = conversation.clone();
 
         Ok(Box::pin(async_stream::try_stream! {
+            let _shutdown_guard = shutdown_guard;
             let final_conversation = if !needs_auto_compact {
                 conversation
             } else {
@@ -1060,10 +1427,16 @@ impl Agent {
                     .unwrap_or(DEFAULT_COMPACTION_THRESHOLD);
                 let threshold_percentage = (threshold * 100.0) as u32;
 
-                let inline_msg = format!(
-                    "Exceeded auto-compact threshold of {}%. Performing auto-compaction...",
-                    threshold_percentage
+                let inline_msg = i18n::text_with(
+                    "compaction.auto_threshold",
+                    &[("threshold", &threshold_percentage)],
                 );
+                notifications::notify(Notification::new(
+                    NotificationKind::BudgetWarning,
+                    session.id.clone(),
+                    Some(session.session_type),
+                    inline_msg.clone(),
+                ));
 
                 yield AgentEvent::Message(
                     Message::assistant().with_system_notification(
@@ -1075,7 +1448,7 @@ impl Agent {
                 yield AgentEvent::Message(
                     Message::assistant().with_system_notification(
                         SystemNotificationType::ThinkingMessage,
-                        COMPACTION_THINKING_TEXT,
+                        i18n::text("compaction.thinking"),
                     )
                 );
 
@@ -1104,6 +1477,13 @@ impl Agent {
                         session_manager.replace_conversation(&session_config.id, &compacted_conversation).await?;
                         self.update_session_metrics(&session_config.id, session_config.schedule_id.clone(), &summarization_usage, true).await?;
 
+                        lifecycle::publish(LifecycleEvent::CompactionOccurred {
+                            session_id: session_config.id.clone(),
+                            before_count: pre_len,
+                            after_count: compacted_conversation.messages().len(),
+                            manual: false,
+                        });
+
                         // Fire PostCompact hook
                         let post_outcome = hooks.emit(
                             HookEvent::PostCompact {
@@ -1125,7 +1505,7 @@ impl Agent {
                         yield AgentEvent::Message(
                             Message::assistant().with_system_notification(
                                 SystemNotificationType::InlineMessage,
-                                "Compaction complete",
+                                i18n::text("compaction.complete"),
                             )
                         );
 
@@ -1169,11 +1549,32 @@ impl Agent {
             goose_mode,
             initial_messages,
         } = context;
+        // The prompt to look up in the knowledge bases, once the hooks have seen it
+        let mut retrieval_query = conversation
+            .messages()
+            .last()
+            .filter(|m| m.role == rmcp::model::Role::User && !m.is_tool_response())
+            .map(|m| m.as_concat_text())
+            .filter(|text| !text.trim().is_empty());
+        // Extensions enabled or disabled from outside the loop change the tools on the next turn
+        let mut tools_version = self.extension_manager.tools_version();
         self.reset_retry_attempts().await;
 
         let provider = self.provider().await?;
         let session_manager = self.config.session_manager.clone();
         let session_id = session_config.id.clone();
+        // A model the provider doesn't have would otherwise fail partway into the first turn
+        let first_turn = !conversation
+            .messages()
+            .iter()
+            .any(|m| m.role == rmcp::model::Role::Assistant);
+        if first_turn {
+            if let Err(e) = preflight::check_model(provider.as_ref()).await {
+                crate::posthog::emit_error(e.telemetry_type(), &e.to_string());
+                error!("Model check failed: {}", e);
+                return Err(e.into());
+            }
+        }
         if !self.config.disable_session_naming {
             let manager_for_spawn = session_manager.clone();
             let session_id_for_spawn = session_id.clone();
@@ -1188,6 +1589,11 @@ impl Agent {
         }
 
         let working_dir = session.working_dir.clone();
+        let context_recovery = ContextRecovery::for_session(&session);
+        // Let MCP servers know if the session moved to another directory since they started
+        self.extension_manager
+            .update_workspace_roots(&working_dir)
+            .await;
 
         // Fire SessionStart hook on first reply (1 user message, 0 assistant)
         if conversation.messages().len() == 1
@@ -1207,6 +1613,94 @@ impl Agent {
             }
         }
 
+        // Fire ConfigChange when the config, hooks or hint files changed since the previous
+        // reply. Until the change is undone, a block leaves changed hint files out of the
+        // prompt, or stops the reply when other settings changed too.
+        let hint_files = configured_hint_files(&working_dir);
+        let config_snapshot = ConfigSnapshot::capture(
+            &working_dir,
+            hint_files
+                .iter()
+                .map(|file| (file.path.as_path(), file.content.as_str())),
+        );
+        let mut skipped_instructions = BTreeSet::new();
+        let mut changed_instructions = Vec::new();
+        if let Some(changes) = config_change::changes_since_accepted(&session_id, &config_snapshot)
+        {
+            let instruction_files = config_change::instruction_files(&changes);
+            let only_instructions = instruction_files.len() == changes.len();
+            let outcome = hooks
+                .emit(
+                    HookEvent::ConfigChange {
+                        session_id: session_id.clone(),
+                        changes,
+                        cwd: working_dir.clone(),
+                    },
+                    &working_dir,
+                    cancel_token.clone().unwrap_or_default(),
+                )
+                .await;
+            if outcome.blocked && only_instructions {
+                warn!(
+                    "Leaving out hint files whose change a hook blocked: {:?}",
+                    instruction_files
+                );
+                skipped_instructions = instruction_files.into_iter().collect();
+            } else if outcome.blocked {
+                let text = match outcome.reason {
+                    Some(reason) => format!("Configuration change blocked by hook: {}", reason),
+                    None => "Configuration change blocked by hook.".to_string(),
+                };
+                return Ok(Box::pin(async_stream::try_stream! {
+                    yield AgentEvent::Message(Message::assistant().with_text(text));
+                }));
+            } else {
+                config_change::accept(&session_id, config_snapshot);
+                changed_instructions = instruction_files;
+                if let Some(ctx) = outcome.context {
+                    Self::inject_hook_context(
+                        &session_id,
+                        ctx,
+                        &session_manager,
+                        &mut conversation,
+                    )
+                    .await
+                    .ok();
+                }
+            }
+        }
+        config_change::skip_instructions(&session_id, skipped_instructions);
+
+        // Fire InstructionsChanged for the hint files among the accepted changes
+        if !changed_instructions.is_empty() {
+            let outcome = hooks
+                .emit(
+                    HookEvent::InstructionsChanged {
+                        session_id: session_id.clone(),
+                        files: changed_instructions,
+                        cwd: working_dir.clone(),
+                    },
+                    &working_dir,
+                    cancel_token.clone().unwrap_or_default(),
+                )
+                .await;
+            if let Some(ctx) = outcome.context {
+                Self::inject_hook_context(&session_id, ctx, &session_manager, &mut conversation)
+                    .await
+                    .ok();
+            }
+        }
+
+        // Tell the model about files edited outside the agent since its previous reply. The
+        // note goes with the first request of this reply only and isn't saved to the session.
+        let mut workspace_note = if workspace_changes::enabled() {
+            workspace_changes::changes_since_recorded(&session_id, &working_dir)
+                .await
+                .map(|changes| changes.note())
+        } else {
+            None
+        };
+
         // Fire UserPromptSubmit hook
         if let Some(last_user_msg) = conversation
             .messages()
@@ -1229,15 +1723,70 @@ impl Agent {
                     yield AgentEvent::Message(Message::assistant().with_text("Prompt blocked by hook."));
                 }));
             }
+            if let Some(prompt) = outcome.modified_prompt {
+                retrieval_query = Some(prompt.clone());
+                if let Err(e) = Self::apply_rewritten_prompt(
+                    &session_id,
+                    prompt,
+                    &session_manager,
+                    &mut conversation,
+                )
+                .await
+                {
+                    warn!("Failed to save the prompt rewritten by a hook: {}", e);
+                }
+            }
             if let Some(ctx) = outcome.context {
                 Self::inject_hook_context(&session_id, ctx, &session_manager, &mut conversation)
                     .await.ok();
             }
         }
 
+        // Show the model what the knowledge bases have on the prompt. The note is for this
+        // turn only and not saved, so earlier prompts' documents don't pile up in the session.
+        let mut retrieved_documents = Vec::new();
+        if let Some(query) = retrieval_query.filter(|_| retrieval::enabled()) {
+            let provider = self.provider().await?;
+            if let Some(retrieved) =
+                retrieval::context_for(&query, provider.as_ref(), &session_id).await
+            {
+                conversation.push(
+                    Message::user()
+                        .with_text(retrieved.note)
+                        .with_visibility(false, true),
+                );
+                retrieved_documents = retrieved.documents;
+            }
+        }
+
+        // One checkpoint per reply, taken when it ends. The first reply also records the
+        // workspace it started from, so its changes have something to diff against.
+        let checkpoint_store =
+            if session.session_type != SessionType::SubAgent && checkpoints::enabled() {
+                CheckpointStore::open(&working_dir, &session_id).await
+            } else {
+                None
+            };
+        if let Some(store) = &checkpoint_store {
+            if !store.exists().await.unwrap_or(true) {
+                if let Err(e) = store.create("Workspace before the session").await {
+                    warn!("Failed to create checkpoint: {}", e);
+                }
+            }
+        }
+        let checkpoint_message = conversation
+            .messages()
+            .iter()
+            .rev()
+            .find(|m| m.role == rmcp::model::Role::User)
+            .and_then(|m| m.as_concat_text().lines().next().map(str::to_string))
+            .map(|line| format!("Turn: {}", line.chars().take(72).collect::<String>()))
+            .unwrap_or_else(|| "Turn".to_string());
+
         Ok(Box::pin(async_stream::try_stream! {
             let reply_stream_span = tracing::info_span!(target: "goose::agents::agent", "reply_stream");
             let _stream_guard = reply_stream_span.enter();
+            let _steering = self.accept_steering();
             let mut turns_taken = 0u32;
             let max_turns = session_config.max_turns.unwrap_or_else(|| {
                 Config::global()
@@ -1245,7 +1794,18 @@ impl Agent {
                     .unwrap_or(DEFAULT_MAX_TURNS)
             });
             let mut compaction_attempts = 0;
+            let mut send_all_tools = false;
+            // Ranked once per turn so every request of the turn sends the same tools, which
+            // keeps the prompt cache warm; picked again only when the tools change
+            let mut selected_tools: Option<(Vec<Tool>, Vec<Tool>)> = None;
+            let max_validation_retries = turn_validation::max_retries();
+            let mut validation_retries = 0;
             let mut last_assistant_text = String::new();
+            let mut stop_continuations = 0u32;
+            let mut stop_hook_fired = false;
+            let _turn = lifecycle::TurnGuard::start(session_id.clone());
+            // Sizes the tool responses recorded with each call's usage
+            let token_counter = create_token_counter().await.ok();
 
             loop {
                 if is_token_cancelled(&cancel_token) {
@@ -1279,28 +1839,67 @@ impl Agent {
                     tool_call_cut_off,
                 );
 
-                let conversation_with_moim = super::moim::inject_moim(
+                let mut conversation_with_moim = super::moim::inject_moim(
                     &session_config.id,
                     conversation.clone(),
                     &self.extension_manager,
                     &working_dir,
                 ).await;
+                if let Some(note) = workspace_note.take() {
+                    conversation_with_moim = workspace_changes::with_note(conversation_with_moim, note);
+                }
 
+                let provider = self.provider().await?;
+                if !send_all_tools && selected_tools.is_none() {
+                    selected_tools = Some((
+                        tool_selection::select_tools(provider.as_ref(), &session_config.id, &conversation, &tools).await,
+                        tool_selection::select_tools(provider.as_ref(), &session_config.id, &conversation, &toolshim_tools).await,
+                    ));
+                }
+                let (request_tools, request_toolshim_tools) = match &selected_tools {
+                    Some((selected, selected_toolshim)) if !send_all_tools => (selected, selected_toolshim),
+                    _ => (&tools, &toolshim_tools),
+                };
                 let mut stream = Self::stream_response_from_provider(
-                    self.provider().await?,
+                    provider,
                     &session_config.id,
                     &system_prompt,
                     conversation_with_moim.messages(),
-                    &tools,
-                    &toolshim_tools,
+                    request_tools,
+                    request_toolshim_tools,
                 ).await?;
 
                 let mut no_tools_called = true;
                 let mut messages_to_add = Conversation::default();
+                let mut call_usage: Option<ProviderUsage> = None;
                 let mut tools_updated = false;
                 let mut did_recovery_compact_this_iteration = false;
-
-                while let Some(next) = stream.next().await {
+                let mut loop_pause: Option<String> = None;
+                let mut validation_retry = false;
+
+                let mut bridged_calls = self.bridged_calls_rx.lock().await;
+                loop {
+                    let next = tokio::select! {
+                        next = stream.next() => next,
+                        Some(call) = bridged_calls.recv() => {
+                            // The provider's CLI called a goose tool through the tool bridge
+                            let mut bridged = self.handle_bridged_call(
+                                call,
+                                conversation.messages(),
+                                goose_mode,
+                                cancel_token.clone(),
+                                &session,
+                                &hooks,
+                            );
+                            while let Some(msg) = bridged.try_next().await? {
+                                yield AgentEvent::Message(msg);
+                            }
+                            continue;
+                        }
+                    };
+                    let Some(next) = next else {
+                        break;
+                    };
                     if is_token_cancelled(&cancel_token) {
                         break;
                     }
@@ -1332,6 +1931,7 @@ impl Agent {
 
                             if let Some(ref usage) = usage {
                                 self.update_session_metrics(&session_config.id, session_config.schedule_id.clone(), usage, false).await?;
+                                call_usage = Some(usage.clone());
                             }
 
                             if let Some(response) = response {
@@ -1341,6 +1941,34 @@ impl Agent {
                                     filtered_response,
                                 } = self.categorize_tools(&response, &tools).await;
 
+                                if validation_retries < max_validation_retries {
+                                    let requests = frontend_requests.iter().chain(remaining_requests.iter());
+                                    let mut problems = turn_validation::validate_tool_requests(requests, &tools);
+                                    if !problems.is_empty() {
+                                        // The tool list can change under a running turn, so check again before retrying
+                                        let mut current_tools = self.list_tools(&session_config.id, None).await;
+                                        current_tools.extend(toolshim_tools.iter().cloned());
+                                        current_tools.extend(self.frontend_tools.lock().await.values().map(|t| t.tool.clone()));
+                                        let requests = frontend_requests.iter().chain(remaining_requests.iter());
+                                        problems = turn_validation::validate_tool_requests(requests, &current_tools);
+                                    }
+                                    if !problems.is_empty() {
+                                        validation_retries += 1;
+                                        crate::posthog::emit_error("turn_validation_retry", &problems.join("; "));
+                                        warn!(
+                                            "Retrying turn after invalid tool calls ({}/{}): {}",
+                                            validation_retries, max_validation_retries, problems.join("; ")
+                                        );
+                                        if let Some(message) = turn_validation::without_tool_requests(&filtered_response) {
+                                            messages_to_add.push(message.clone());
+                                            yield AgentEvent::Message(message);
+                                        }
+                                        messages_to_add.push(turn_validation::correction_message(&problems));
+                                        validation_retry = true;
+                                        break;
+                                    }
+                                }
+
                                 yield AgentEvent::Message(filtered_response.clone());
                                 tokio::task::yield_now().await;
 
@@ -1377,14 +2005,27 @@ impl Agent {
                                         yield AgentEvent::Message(msg);
                                     }
                                 }
-                                if goose_mode == GooseMode::Chat {
-                                    // Skip all remaining tool calls in chat mode
+                                let skip_for_steering = self
+                                    .steering
+                                    .lock()
+                                    .unwrap()
+                                    .iter()
+                                    .flatten()
+                                    .any(|message| message.cancel_pending_tools);
+                                if goose_mode == GooseMode::Chat || skip_for_steering {
+                                    // Skip all remaining tool calls in chat mode, or when the user
+                                    // steered the turn and asked for pending calls to be cancelled
+                                    let skipped_response = if skip_for_steering {
+                                        STEERING_TOOL_SKIPPED_RESPONSE
+                                    } else {
+                                        CHAT_MODE_TOOL_SKIPPED_RESPONSE
+                                    };
                                     for request in remaining_requests.iter() {
                                         if let Some(response_msg) = request_to_response_map.get(&request.id) {
                                             let mut response = response_msg.lock().await;
                                             *response = response.clone().with_tool_response_with_metadata(
                                                 request.id.clone(),
-                                                Ok(CallToolResult::success(vec![Content::text(CHAT_MODE_TOOL_SKIPPED_RESPONSE)])),
+                                                Ok(CallToolResult::success(vec![Content::text(skipped_response)])),
                                                 request.metadata.as_ref(),
                                             );
                                         }
@@ -1399,6 +2040,10 @@ impl Agent {
                                             goose_mode,
                                         )
                                         .await?;
+                                    loop_pause = inspection_results
+                                        .iter()
+                                        .find(|result| result.inspector_name == LOOP_GUARD_INSPECTOR_NAME)
+                                        .map(|result| result.reason.clone());
 
                                     let permission_check_result = self.tool_inspection_manager
                                         .process_inspection_results_with_permission_inspector(
@@ -1414,6 +2059,7 @@ impl Agent {
                                             result.needs_approval.extend(remaining_requests.iter().cloned());
                                             result
                                         });
+                                    audit::record_policy_decisions(&session_config.id, &permission_check_result);
 
                                     // Track extension requests
                                     let mut enable_extension_request_ids = vec![];
@@ -1422,6 +2068,9 @@ impl Agent {
                                             if tool_call.name == MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE {
                                                 enable_extension_request_ids.push(request.id.clone());
                                             }
+                                            if tool_call.name == LIST_ALL_TOOLS_TOOL_NAME {
+                                                send_all_tools = true;
+                                            }
                                         }
                                     }
 
@@ -1454,14 +2103,23 @@ impl Agent {
                                         futures_lock.drain(..).collect::<Vec<_>>()
                                     };
 
-                                    let with_id = tool_futures
-                                        .into_iter()
-                                        .map(|(request_id, stream)| {
-                                            stream.map(move |item| (request_id.clone(), item))
-                                        })
-                                        .collect::<Vec<_>>();
+                                    // Independent calls run concurrently; calls touching the same files
+                                    // or extension state run in the order they were requested
+                                    let mut scheduled = Vec::with_capacity(tool_futures.len());
+                                    for (request_id, stream) in tool_futures {
+                                        let footprint = match request_id_to_request.get(&request_id) {
+                                            Some(request) => ToolFootprint::of(
+                                                &self.extension_manager,
+                                                &session_config.id,
+                                                request,
+                                                &working_dir,
+                                            ).await,
+                                            None => ToolFootprint::default(),
+                                        };
+                                        scheduled.push((request_id, stream, footprint));
+                                    }
 
-                                    let mut combined = stream::select_all(with_id);
+                                    let mut combined = schedule_tool_streams(scheduled, max_parallel_tool_calls());
                                     let mut all_install_successful = true;
 
                                     loop {
@@ -1551,6 +2209,14 @@ impl Agent {
                                                                 }
                                                             }
                                                             ToolStreamItem::Message(msg) => {
+                                                                if let ServerNotification::ProgressNotification(progress) = &msg {
+                                                                    yield AgentEvent::Message(Message::tool_progress(
+                                                                        &request_id,
+                                                                        progress.params.progress,
+                                                                        progress.params.total,
+                                                                        progress.params.message.as_deref(),
+                                                                    ));
+                                                                }
                                                                 yield AgentEvent::McpNotification((request_id, msg));
                                                             }
                                                         }
@@ -1570,6 +2236,16 @@ impl Agent {
                                         yield AgentEvent::Message(msg);
                                     }
 
+                                    for event in self.extension_manager.recover_unresponsive_extensions(&session_config.id).await {
+                                        yield AgentEvent::Message(
+                                            Message::assistant().with_system_notification(
+                                                SystemNotificationType::InlineMessage,
+                                                event.to_string(),
+                                            )
+                                        );
+                                        tools_updated = true;
+                                    }
+
                                     if all_install_successful && !enable_extension_request_ids.is_empty() {
                                         if let Err(e) = self.save_extension_state(&session_config).await {
                                             warn!("Failed to save extension state after runtime changes: {}", e);
@@ -1642,14 +2318,40 @@ impl Agent {
                         }
                         Err(ref provider_err @ ProviderError::ContextLengthExceeded(_)) => {
                             crate::posthog::emit_error(provider_err.telemetry_type(), &provider_err.to_string());
-                            compaction_attempts += 1;
 
+                            if context_recovery == ContextRecovery::Off {
+                                yield AgentEvent::Message(
+                                    Message::assistant().with_system_notification(
+                                        SystemNotificationType::InlineMessage,
+                                        i18n::text("context.recovery_off"),
+                                    )
+                                );
+                                break;
+                            }
+
+                            // Dropped tool outputs only shrink what this turn sends; the session
+                            // keeps them. Once there is nothing left to drop, compact.
+                            if context_recovery == ContextRecovery::DropToolOutputs {
+                                if let Some(trimmed) = drop_oldest_tool_outputs(&conversation) {
+                                    yield AgentEvent::Message(
+                                        Message::assistant().with_system_notification(
+                                            SystemNotificationType::InlineMessage,
+                                            i18n::text("context.dropping_tool_outputs"),
+                                        )
+                                    );
+                                    conversation = trimmed;
+                                    did_recovery_compact_this_iteration = true;
+                                    break;
+                                }
+                            }
+
+                            compaction_attempts += 1;
                             if compaction_attempts >= 2 {
                                 error!("Context limit exceeded after compaction - prompt too large");
                                 yield AgentEvent::Message(
                                     Message::assistant().with_system_notification(
                                         SystemNotificationType::InlineMessage,
-                                        "Unable to continue: Context limit still exceeded after compaction. Try using a shorter message, a model with a larger context window, or start a new session."
+                                        i18n::text("context.still_exceeded"),
                                     )
                                 );
                                 break;
@@ -1658,13 +2360,13 @@ impl Agent {
                             yield AgentEvent::Message(
                                 Message::assistant().with_system_notification(
                                     SystemNotificationType::InlineMessage,
-                                    "Context limit reached. Compacting to continue conversation...",
+                                    i18n::text("context.compacting"),
                                 )
                             );
                             yield AgentEvent::Message(
                                 Message::assistant().with_system_notification(
                                     SystemNotificationType::ThinkingMessage,
-                                    COMPACTION_THINKING_TEXT,
+                                    i18n::text("compaction.thinking"),
                                 )
                             );
 
@@ -1693,6 +2395,13 @@ impl Agent {
                                     session_manager.replace_conversation(&session_config.id, &compacted_conversation).await?;
                                     self.update_session_metrics(&session_config.id, session_config.schedule_id.clone(), &usage, true).await?;
 
+                                    lifecycle::publish(LifecycleEvent::CompactionOccurred {
+                                        session_id: session_config.id.clone(),
+                                        before_count: pre_len,
+                                        after_count: compacted_conversation.messages().len(),
+                                        manual: false,
+                                    });
+
                                     // Fire PostCompact hook (recovery)
                                     let pc_outcome = hooks.emit(
                                         HookEvent::PostCompact {
@@ -1727,9 +2436,9 @@ impl Agent {
                             error!("Error: {}", provider_err);
 
                             let user_msg = if top_up_url.is_some() {
-                                "Please add credits to your account, then resend your message to continue.".to_string()
+                                i18n::text("error.credits.top_up")
                             } else {
-                                "Please check your account with your provider to add more credits, then resend your message to continue.".to_string()
+                                i18n::text("error.credits.provider")
                             };
 
                             let notification_data = serde_json::json!({
@@ -1750,29 +2459,91 @@ impl Agent {
                             error!("Error: {}", provider_err);
                             yield AgentEvent::Message(
                                 Message::assistant().with_text(
-                                    format!("{provider_err}\n\nPlease resend your message to try again.")
+                                    format!("{provider_err}\n\n{}", i18n::text("error.advice.network"))
                                 )
                             );
                             break;
                         }
+                        Err(ref provider_err)
+                            if turn_validation::is_tool_validation_error(provider_err)
+                                && validation_retries < max_validation_retries =>
+                        {
+                            validation_retries += 1;
+                            crate::posthog::emit_error("turn_validation_retry", &provider_err.to_string());
+                            warn!(
+                                "Retrying turn after the provider rejected a tool call ({}/{}): {}",
+                                validation_retries, max_validation_retries, provider_err
+                            );
+                            messages_to_add.push(turn_validation::correction_message(&[provider_err.to_string()]));
+                            validation_retry = true;
+                            break;
+                        }
                         Err(ref provider_err) => {
                             crate::posthog::emit_error(provider_err.telemetry_type(), &provider_err.to_string());
                             error!("Error: {}", provider_err);
+                            let advice = match provider_err.code() {
+                                ProviderErrorCode::Auth => "error.advice.auth",
+                                ProviderErrorCode::ModelNotFound => "error.advice.model",
+                                ProviderErrorCode::ContentFilter => "error.advice.content_filter",
+                                ProviderErrorCode::Overloaded | ProviderErrorCode::RateLimit => "error.advice.busy",
+                                _ => "error.advice.retry",
+                            };
                             yield AgentEvent::Message(
-                                Message::assistant().with_text(
-                                    format!("Ran into this error: {provider_err}.\n\nPlease retry if you think this is a transient or recoverable error.")
-                                )
+                                Message::assistant().with_text(format!(
+                                    "{}\n\n{}",
+                                    i18n::text_with("error.provider", &[("error", provider_err)]),
+                                    i18n::text(advice)
+                                ))
                             );
                             break;
                         }
                     }
                 }
-                if tools_updated {
+                if tools_updated || self.extension_manager.tools_version() != tools_version {
+                    tools_version = self.extension_manager.tools_version();
                     (tools, toolshim_tools, system_prompt) =
                         self.prepare_tools_and_prompt(&session_config.id, &session.working_dir).await?;
+                    selected_tools = None;
+
+                    let changed = self.extension_manager.take_tool_list_changes().await;
+                    if !changed.is_empty() {
+                        let changed = changed.join(", ");
+                        let note = Message::user()
+                            .with_text(format!("{}{}", TOOLS_CHANGED_NOTE, changed))
+                            .agent_only();
+                        messages_to_add.push(note.clone());
+                        yield AgentEvent::Message(note);
+                        yield AgentEvent::Message(
+                            Message::assistant().with_system_notification(
+                                SystemNotificationType::InlineMessage,
+                                i18n::text_with("tools.changed", &[("tools", &changed)]),
+                            )
+                        );
+                    }
                 }
-                let mut exit_chat = false;
-                if no_tools_called {
+                let mut turn_end = None;
+                if let Some(reason) = loop_pause {
+                    warn!("Pausing session {}: {}", session_config.id, reason);
+                    hooks.emit(
+                        HookEvent::Notification {
+                            session_id: session_id.clone(),
+                            notification_type: "session_paused".to_string(),
+                            message: i18n::text_with("session.paused", &[("reason", &reason)]),
+                            severity: NotificationSeverity::Warning,
+                            tool_name: None,
+                            detail: None,
+                            cwd: working_dir.clone(),
+                        },
+                        &working_dir,
+                        cancel_token.clone().unwrap_or_default(),
+                    ).await;
+                    let message = Message::assistant().with_text(reason);
+                    messages_to_add.push(message.clone());
+                    yield AgentEvent::Message(message);
+                    turn_end = Some(TurnEnd::Paused);
+                } else if validation_retry {
+                    // Give the model another go with the correction
+                } else if no_tools_called {
                     if let Some(final_output_tool) = self.final_output_tool.lock().await.as_ref() {
                         if final_output_tool.final_output.is_none() {
                             warn!("Final output tool has not been called yet. Continuing agent loop.");
@@ -1783,17 +2554,17 @@ impl Agent {
                             let message = Message::assistant().with_text(final_output_tool.final_output.clone().unwrap());
                             messages_to_add.push(message.clone());
                             yield AgentEvent::Message(message);
-                            exit_chat = true;
+                            turn_end = Some(TurnEnd::FinalOutput);
                         }
                     } else if did_recovery_compact_this_iteration {
-                        // Avoid setting exit_chat; continue from last user message in the conversation
+                        // Avoid ending the turn; continue from last user message in the conversation
                     } else {
                         match self.handle_retry_logic(&mut conversation, &session_config, &initial_messages).await {
                             Ok(should_retry) => {
                                 if should_retry {
                                     info!("Retry logic triggered, restarting agent loop");
                                 } else {
-                                    exit_chat = true;
+                                    turn_end = Some(TurnEnd::Answered);
                                 }
                             }
                             Err(e) => {
@@ -1803,7 +2574,7 @@ impl Agent {
                                         format!("Retry logic encountered an error: {}", e)
                                     )
                                 );
-                                exit_chat = true;
+                                turn_end = Some(TurnEnd::Failed);
                             }
                         }
                     }
@@ -1839,27 +2610,96 @@ impl Agent {
                     }
                 }
 
+                // Attach the sources of the retrieved documents the final answer cites
+                if no_tools_called && !retrieved_documents.is_empty() {
+                    if let Some(sources) = retrieval::cited_sources(&retrieved_documents, messages_to_add.messages()) {
+                        messages_to_add.push(sources.clone());
+                        yield AgentEvent::Message(sources);
+                    }
+                }
+
+                // Steering messages join the conversation at the tool boundary and keep the
+                // turn going
+                let steering = self.take_steering();
+                if !steering.is_empty() && !is_token_cancelled(&cancel_token) {
+                    for steer in steering {
+                        let message = Message::user().with_text(steer.text);
+                        messages_to_add.push(message.clone());
+                        yield AgentEvent::Message(message);
+                    }
+                    turn_end = None;
+                }
+
+                if let Some(usage) = call_usage.take() {
+                    let provider = self.provider().await?;
+                    let pricing = maybe_get_canonical_model(provider.get_name(), &usage.model)
+                        .map(|model| model.cost);
+                    let server_tool_cost = usage.usage.server_tools.cost(provider.get_name());
+                    messages_to_add = record_usage(messages_to_add, &usage.usage, pricing.as_ref(), server_tool_cost, token_counter.as_ref());
+                }
                 for msg in &messages_to_add {
                     session_manager.add_message(&session_config.id, msg).await?;
                 }
                 conversation.extend(messages_to_add);
-                if exit_chat {
+                if let Some(end) = turn_end {
+                    // A Stop hook that blocks keeps the agent going, with its reason as the
+                    // next instruction
+                    let outcome = hooks.emit(
+                        HookEvent::Stop {
+                            session_id: session_id.clone(),
+                            last_assistant_text: last_assistant_text.clone(),
+                            stop_hook_active: stop_continuations > 0,
+                            cwd: working_dir.clone(),
+                        },
+                        &working_dir,
+                        cancel_token.clone().unwrap_or_default(),
+                    ).await;
+                    stop_hook_fired = true;
+                    if let Some(instruction) = stop_hook_instruction(&outcome, end, stop_continuations) {
+                        if !is_token_cancelled(&cancel_token) {
+                            stop_continuations += 1;
+                            stop_hook_fired = false;
+                            let message = Message::user().with_text(instruction);
+                            session_manager.add_message(&session_config.id, &message).await?;
+                            conversation.push(message.clone());
+                            yield AgentEvent::Message(message);
+                            continue;
+                        }
+                    }
                     break;
                 }
 
                 tokio::task::yield_now().await;
             }
 
-            // Fire Stop hook
-            hooks.emit(
-                HookEvent::Stop {
-                    session_id: session_id.clone(),
-                    last_assistant_text: last_assistant_text.clone(),
-                    cwd: working_dir.clone(),
-                },
-                &working_dir,
-                cancel_token.clone().unwrap_or_default(),
-            ).await;
+            if let Err(e) = session_manager.flush().await {
+                warn!("Failed to save session messages: {}", e);
+            }
+
+            if let Some(store) = &checkpoint_store {
+                if let Err(e) = store.create(&checkpoint_message).await {
+                    warn!("Failed to create checkpoint: {}", e);
+                }
+            }
+
+            if workspace_changes::enabled() {
+                workspace_changes::record(&session_id, working_dir.clone()).await;
+            }
+
+            // Fire Stop hook when the turn ended some other way, such as a cancel or the
+            // turn limit; it cannot keep the agent going from here
+            if !stop_hook_fired {
+                hooks.emit(
+                    HookEvent::Stop {
+                        session_id: session_id.clone(),
+                        last_assistant_text: last_assistant_text.clone(),
+                        stop_hook_active: stop_continuations > 0,
+                        cwd: working_dir.clone(),
+                    },
+                    &working_dir,
+                    cancel_token.clone().unwrap_or_default(),
+                ).await;
+            }
 
             if !last_assistant_text.is_empty() {
                 tracing::info!(target: "goose::agents::agent", trace_output = last_assistant_text.as_str());
@@ -1879,6 +2719,20 @@ impl Agent {
     ) -> Result<()> {
         let provider_name = provider.get_name().to_string();
         let model_config = provider.get_model_config();
+        let model_name = model_config.model_name.clone();
+
+        // Restoring a session sets the provider it already had, which is not a change
+        let changed = self
+            .config
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .ok()
+            .is_none_or(|session| {
+                session.provider_name.as_deref() != Some(provider_name.as_str())
+                    || session.model_config.map(|config| config.model_name)
+                        != Some(model_name.clone())
+            });
 
         let mut current_provider = self.provider.lock().await;
         *current_provider = Some(provider);
@@ -1891,7 +2745,59 @@ impl Agent {
             .model_config(model_config)
             .apply()
             .await
-            .context("Failed to persist provider config to session")
+            .context("Failed to persist provider config to session")?;
+
+        if changed {
+            lifecycle::publish(LifecycleEvent::ModelChanged {
+                session_id: session_id.to_string(),
+                provider: provider_name,
+                model: model_name,
+            });
+        }
+        Ok(())
+    }
+
+    /// The extensions to create a provider with. Providers that hand extensions to a CLI as
+    /// MCP servers also get the tool bridge, so builtin and platform tools reach the CLI too.
+    pub async fn extensions_for_provider(
+        &self,
+        provider_name: &str,
+        session_id: &str,
+        mut extensions: Vec<ExtensionConfig>,
+    ) -> Vec<ExtensionConfig> {
+        if !tool_bridge::provider_uses_bridge(provider_name)
+            || !extensions.iter().any(tool_bridge::needs_bridge)
+        {
+            return extensions;
+        }
+
+        let mut bridge = self.tool_bridge.lock().await;
+        // A bridge only ever serves the session it was started for
+        if bridge
+            .as_ref()
+            .is_some_and(|server| server.session_id() != session_id)
+        {
+            *bridge = None;
+        }
+        if bridge.is_none() {
+            match ToolBridgeServer::start(
+                &self.extension_manager,
+                session_id,
+                self.bridged_calls_tx.clone(),
+            )
+            .await
+            {
+                Ok(server) => *bridge = Some(server),
+                Err(e) => warn!(
+                    "Could not start the tool bridge for {}: {}",
+                    provider_name, e
+                ),
+            }
+        }
+        if let Some(server) = bridge.as_ref() {
+            extensions.push(server.extension_config().clone());
+        }
+        extensions
     }
 
     /// Restore the provider from session data or fall back to global config
@@ -1920,6 +2826,9 @@ impl Agent {
 
         let extensions =
             EnabledExtensionsState::extensions_or_default(Some(&session.extension_data), config);
+        let extensions = self
+            .extensions_for_provider(&provider_name, &session.id, extensions)
+            .await;
 
         let provider = crate::providers::create(&provider_name, model_config, extensions)
             .await
@@ -2195,6 +3104,10 @@ impl Agent {
             goose_model: Some(model_name.clone()),
             temperature: Some(model_config.temperature.unwrap_or(0.0)),
             max_turns: None,
+            sandbox: None,
+            permission_profile: None,
+            prompt_sections: None,
+            context_recovery: None,
         };
 
         tracing::debug!(
@@ -2304,6 +3217,175 @@ mod tests {
         }
     }
 
+    #[test]
+    fn steering_is_only_accepted_during_a_turn() {
+        let agent = Agent::new();
+        let steer = |text: &str| SteeringMessage {
+            text: text.to_string(),
+            cancel_pending_tools: false,
+        };
+        assert!(!agent.steer(steer("between turns")));
+
+        let window = agent.accept_steering();
+        assert!(agent.steer(steer("look at the tests first")));
+        let queued = agent.take_steering();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].text, "look at the tests first");
+        assert!(agent.take_steering().is_empty());
+        assert!(agent.steer(steer("too late")));
+        drop(window);
+
+        assert!(!agent.steer(steer("after the turn")));
+        assert!(agent.take_steering().is_empty());
+    }
+
+    #[tokio::test]
+    async fn enabling_an_unconfigured_extension_fails() {
+        let agent = Agent::new();
+        let error = agent
+            .set_extension_enabled("session", "no-such-extension-configured", true)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("is not configured"));
+    }
+
+    #[tokio::test]
+    async fn rewritten_prompt_replaces_the_prompt_for_the_model_only() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let session_manager = SessionManager::new(temp_dir.path().to_path_buf());
+        let session = session_manager
+            .create_session(
+                std::path::PathBuf::from("/tmp"),
+                "rewrite".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        let prompt = Message::user()
+            .with_text("fix #42")
+            .with_image("aGVsbG8=", "image/png");
+        session_manager
+            .add_message(&session.id, &prompt)
+            .await
+            .unwrap();
+        let mut conversation = Conversation::new_unvalidated(vec![prompt]);
+
+        Agent::apply_rewritten_prompt(
+            &session.id,
+            "fix GOOSE-42".to_string(),
+            &session_manager,
+            &mut conversation,
+        )
+        .await
+        .unwrap();
+
+        let stored = session_manager
+            .get_session(&session.id, true)
+            .await
+            .unwrap()
+            .conversation
+            .unwrap();
+        assert_eq!(stored.messages().len(), 2);
+        let [original, rewritten] = stored.messages().as_slice() else {
+            panic!("expected the original and the rewritten prompt");
+        };
+        assert!(original.metadata.user_visible && !original.metadata.agent_visible);
+        assert!(!rewritten.metadata.user_visible && rewritten.metadata.agent_visible);
+        assert_eq!(rewritten.as_concat_text(), "fix GOOSE-42");
+        assert_eq!(rewritten.content.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn update_provider_announces_the_new_model() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let session_manager = Arc::new(SessionManager::new(temp_dir.path().to_path_buf()));
+        let session = session_manager
+            .create_session(
+                std::path::PathBuf::from("/tmp"),
+                "model change".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        let agent = Agent::with_config(AgentConfig::new(
+            Arc::clone(&session_manager),
+            Arc::new(PermissionManager::new(temp_dir.path().to_path_buf())),
+            None,
+            GooseMode::Auto,
+            true,
+            GoosePlatform::GooseCli,
+        ));
+        let mut events = lifecycle::subscribe();
+
+        // Setting the same provider again, as restoring the session does, announces nothing
+        for _ in 0..2 {
+            agent
+                .update_provider(Arc::new(ActionRequiredProvider::new()), &session.id)
+                .await
+                .unwrap();
+        }
+        lifecycle::publish(LifecycleEvent::TurnFinished {
+            session_id: session.id.clone(),
+        });
+
+        // Other tests publish on the same bus, so skip anything from other sessions
+        let mut announced = Vec::new();
+        loop {
+            let event = events.recv().await.unwrap();
+            if event.session_id() != session.id {
+                continue;
+            }
+            if matches!(event, LifecycleEvent::TurnFinished { .. }) {
+                break;
+            }
+            announced.push(event);
+        }
+        assert_eq!(
+            announced,
+            vec![LifecycleEvent::ModelChanged {
+                session_id: session.id.clone(),
+                provider: "test-action-required".to_string(),
+                model: "test".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn stop_hook_block_continues_with_its_reason() {
+        let blocked = HookOutcome {
+            blocked: true,
+            reason: Some("Run the tests first".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            stop_hook_instruction(&blocked, TurnEnd::Answered, 0).as_deref(),
+            Some("Stop hook feedback:\nRun the tests first")
+        );
+        assert!(
+            stop_hook_instruction(&blocked, TurnEnd::Answered, MAX_STOP_HOOK_CONTINUATIONS)
+                .is_none()
+        );
+
+        let without_reason = HookOutcome {
+            blocked: true,
+            ..Default::default()
+        };
+        assert!(stop_hook_instruction(&without_reason, TurnEnd::Answered, 0).is_none());
+        assert!(stop_hook_instruction(&HookOutcome::default(), TurnEnd::Answered, 0).is_none());
+    }
+
+    #[test]
+    fn stop_hook_cannot_override_a_pause_or_final_output() {
+        let blocked = HookOutcome {
+            blocked: true,
+            reason: Some("Keep going".to_string()),
+            ..Default::default()
+        };
+        for end in [TurnEnd::Paused, TurnEnd::FinalOutput, TurnEnd::Failed] {
+            assert!(stop_hook_instruction(&blocked, end, 0).is_none());
+        }
+    }
+
     #[tokio::test]
     async fn test_handle_confirmation_routes_to_provider() {
         let agent = Agent::new();
@@ -2414,6 +3496,29 @@ mod tests {
             inspector_names.contains(&"security"),
             "Tool inspection manager should contain security inspector"
         );
+        assert!(
+            inspector_names.contains(&"dry_run"),
+            "Tool inspection manager should contain dry run inspector"
+        );
+        assert!(
+            inspector_names.contains(&"plan_mode"),
+            "Tool inspection manager should contain plan mode inspector"
+        );
+        assert!(
+            inspector_names.contains(&LOOP_GUARD_INSPECTOR_NAME),
+            "Tool inspection manager should contain loop guard inspector"
+        );
+        assert!(
+            inspector_names.contains(&"capabilities"),
+            "Tool inspection manager should contain capabilities inspector"
+        );
+        assert!(
+            agent
+                .tool_inspection_manager
+                .output_inspector_names()
+                .contains(&"secrets"),
+            "Tool inspection manager should contain secrets output inspector"
+        );
 
         Ok(())
     }
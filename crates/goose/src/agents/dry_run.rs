@@ -0,0 +1,184 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rmcp::model::JsonObject;
+use serde::{Deserialize, Serialize};
+
+use crate::agents::extension_manager::{ExtensionManager, ToolEffect};
+use crate::agents::platform_extensions::developer::edit::{
+    preview_edit, preview_write, FileEditParams, FileWriteParams,
+};
+use crate::config::GooseMode;
+use crate::conversation::message::{Message, ToolRequest};
+use crate::session::extension_data::{ExtensionData, ExtensionState};
+use crate::session::SessionManager;
+use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
+
+/// Name of the tool set saved when a dry run starts. It's restored when the dry run ends,
+/// so extensions enabled during it don't outlive it.
+pub const DRY_RUN_TOOL_SET: &str = "dry_run";
+
+/// Per-session dry-run switch, stored in the session's extension data.
+/// While enabled, mutating tools are previewed and held for approval instead of running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunState {
+    pub enabled: bool,
+}
+
+impl ExtensionState for DryRunState {
+    const EXTENSION_NAME: &'static str = "dry_run";
+    const VERSION: &'static str = "v0";
+}
+
+impl DryRunState {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    pub fn is_enabled(extension_data: &ExtensionData) -> bool {
+        Self::from_extension_data(extension_data).is_some_and(|state| state.enabled)
+    }
+}
+
+/// Describe what a tool call would do, without running it
+pub fn preview_tool_call(
+    tool_name: &str,
+    arguments: Option<&JsonObject>,
+    working_dir: &Path,
+) -> String {
+    let args = arguments
+        .map(|a| serde_json::Value::Object(a.clone()))
+        .unwrap_or_default();
+    let developer_tool = tool_name.strip_prefix("developer__").unwrap_or(tool_name);
+
+    let preview = match developer_tool {
+        "write" => serde_json::from_value::<FileWriteParams>(args.clone())
+            .ok()
+            .map(|params| preview_write(&params, Some(working_dir))),
+        "edit" => serde_json::from_value::<FileEditParams>(args.clone())
+            .ok()
+            .map(|params| preview_edit(&params, Some(working_dir))),
+        "shell" => args.get("command").and_then(|c| c.as_str()).map(|command| {
+            format!(
+                "Would execute in {}:\n```sh\n{}\n```",
+                working_dir.display(),
+                command
+            )
+        }),
+        _ => None,
+    };
+
+    preview.unwrap_or_else(|| {
+        format!(
+            "Would call {} with:\n```json\n{}\n```",
+            tool_name,
+            serde_json::to_string_pretty(&args).unwrap_or_default()
+        )
+    })
+}
+
+/// Holds mutating tool calls for approval in dry-run sessions, showing a diff or
+/// execution plan in place of running them
+pub struct DryRunInspector {
+    session_manager: Arc<SessionManager>,
+    extension_manager: Arc<ExtensionManager>,
+}
+
+impl DryRunInspector {
+    pub fn new(
+        session_manager: Arc<SessionManager>,
+        extension_manager: Arc<ExtensionManager>,
+    ) -> Self {
+        Self {
+            session_manager,
+            extension_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolInspector for DryRunInspector {
+    fn name(&self) -> &'static str {
+        "dry_run"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn inspect(
+        &self,
+        session_id: &str,
+        tool_requests: &[ToolRequest],
+        _messages: &[Message],
+        _goose_mode: GooseMode,
+    ) -> Result<Vec<InspectionResult>> {
+        let session = self.session_manager.get_session(session_id, false).await?;
+        if !DryRunState::is_enabled(&session.extension_data) {
+            return Ok(vec![]);
+        }
+
+        let mut results = Vec::new();
+        for request in tool_requests {
+            let Ok(tool_call) = &request.tool_call else {
+                continue;
+            };
+            if self
+                .extension_manager
+                .classify_tool(session_id, &tool_call.name)
+                .await
+                == ToolEffect::ReadOnly
+            {
+                continue;
+            }
+
+            let preview = preview_tool_call(
+                &tool_call.name,
+                tool_call.arguments.as_ref(),
+                &session.working_dir,
+            );
+            results.push(InspectionResult {
+                tool_request_id: request.id.clone(),
+                action: InspectionAction::RequireApproval(Some(format!(
+                    "Dry run: this call has not been executed.\n\n{}",
+                    preview
+                ))),
+                reason: "Session is in dry-run mode".to_string(),
+                confidence: 1.0,
+                inspector_name: self.name().to_string(),
+                finding_id: None,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+
+    #[test]
+    fn previews_shell_and_unknown_tools() {
+        let dir = Path::new("/tmp/project");
+        let shell = preview_tool_call("shell", Some(&object!({"command": "rm -rf build"})), dir);
+        assert_eq!(
+            shell,
+            "Would execute in /tmp/project:\n```sh\nrm -rf build\n```"
+        );
+
+        let other = preview_tool_call("github__create_issue", Some(&object!({"title": "x"})), dir);
+        assert!(other.starts_with("Would call github__create_issue"));
+        assert!(other.contains("\"title\": \"x\""));
+    }
+
+    #[test]
+    fn state_defaults_to_disabled() {
+        let mut data = ExtensionData::new();
+        assert!(!DryRunState::is_enabled(&data));
+        DryRunState::new(true).to_extension_data(&mut data).unwrap();
+        assert!(DryRunState::is_enabled(&data));
+    }
+}
@@ -1,11 +1,22 @@
 use std::collections::HashMap;
 
 use anyhow::{anyhow, Result};
+use rmcp::model::{GetPromptResult, Prompt};
 
+use crate::agents::dry_run::{DryRunState, DRY_RUN_TOOL_SET};
+use crate::agents::lifecycle::{self, LifecycleEvent};
+use crate::agents::plan_mode::PlanModeState;
 use crate::context_mgmt::compact_messages;
 use crate::conversation::message::{Message, SystemNotificationType};
 use crate::hooks::{HookEvent, HookRuntime};
+use crate::i18n;
+use crate::permission::profiles::{
+    active_profile_name, configured_profiles, org_profile_name, PermissionProfileState,
+};
 use crate::recipe::build_recipe::build_recipe_from_template_with_positional_params;
+use crate::security::secrets::SecretScanState;
+use crate::session::extension_data::ExtensionState;
+use crate::token_counter::create_token_counter;
 use tokio_util::sync::CancellationToken;
 
 use super::Agent;
@@ -35,6 +46,30 @@ static COMMANDS: &[CommandDef] = &[
         name: "clear",
         description: "Clear the conversation history",
     },
+    CommandDef {
+        name: "context",
+        description: "Show how the context window is shared between prompt sources",
+    },
+    CommandDef {
+        name: "system",
+        description: "Show the system prompt for the next turn and what each section costs",
+    },
+    CommandDef {
+        name: "secrets",
+        description: "Turn masking of secrets in tool output on or off for this session",
+    },
+    CommandDef {
+        name: "dryrun",
+        description: "Preview mutating tool calls instead of running them, for this session",
+    },
+    CommandDef {
+        name: "plan",
+        description: "Plan before acting: on, off, stop after the current step, or show the plan",
+    },
+    CommandDef {
+        name: "profile",
+        description: "Show or switch the permission profile for this session",
+    },
 ];
 
 pub fn list_commands() -> &'static [CommandDef] {
@@ -74,8 +109,20 @@ impl Agent {
             "prompt" => self.handle_prompt_command(&params, session_id).await,
             "compact" => self.handle_compact_command(session_id).await,
             "clear" => self.handle_clear_command(session_id).await,
+            "context" => self.handle_context_command().await,
+            "system" => self.handle_system_prompt_command(session_id).await,
+            "secrets" => self.handle_secrets_command(&params, session_id).await,
+            "dryrun" => self.handle_dry_run_command(&params, session_id).await,
+            "plan" => self.handle_plan_command(&params, session_id).await,
+            "profile" => self.handle_profile_command(&params, session_id).await,
             _ => {
-                self.handle_recipe_command(command, params_str, session_id)
+                if let Some(message) = self
+                    .handle_recipe_command(command, params_str, session_id)
+                    .await?
+                {
+                    return Ok(Some(message));
+                }
+                self.handle_extension_prompt_command(command, params_str, session_id)
                     .await
             }
         }
@@ -115,6 +162,12 @@ impl Agent {
 
         self.update_session_metrics(session_id, session.schedule_id, &usage, true)
             .await?;
+        lifecycle::publish(LifecycleEvent::CompactionOccurred {
+            session_id: session_id.to_string(),
+            before_count: pre_compact_len,
+            after_count: compacted_conversation.messages().len(),
+            manual: true,
+        });
 
         // Fire PostCompact hook
         hooks.emit(
@@ -131,7 +184,7 @@ impl Agent {
 
         Ok(Some(Message::assistant().with_system_notification(
             SystemNotificationType::InlineMessage,
-            "Compaction complete",
+            i18n::text("compaction.complete"),
         )))
     }
 
@@ -153,7 +206,249 @@ impl Agent {
 
         Ok(Some(Message::assistant().with_system_notification(
             SystemNotificationType::InlineMessage,
-            "Conversation cleared",
+            i18n::text("command.cleared"),
+        )))
+    }
+
+    async fn handle_context_command(&self) -> Result<Option<Message>> {
+        let report = match self.context_report().await {
+            Some(report) => report.to_string(),
+            None => "No system prompt has been built yet in this session".to_string(),
+        };
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            report,
+        )))
+    }
+
+    async fn handle_system_prompt_command(&self, session_id: &str) -> Result<Option<Message>> {
+        let session = self
+            .config
+            .session_manager
+            .get_session(session_id, false)
+            .await?;
+        let code_execution_active = self.code_execution_active().await;
+        let rendered = self
+            .render_system_prompt(session_id, &session.working_dir, code_execution_active)
+            .await?;
+        let counter = create_token_counter().await.map_err(|e| anyhow!(e))?;
+
+        let text = rendered.text();
+        let mut output = format!("System prompt: {} tokens\n", counter.count_tokens(&text));
+        for rendered_section in &rendered.sections {
+            output.push_str(&format!(
+                "- {}: {} tokens\n",
+                rendered_section.section,
+                counter.count_tokens(&rendered_section.content)
+            ));
+        }
+        output.push_str(&format!("\n{}", text));
+
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            output,
+        )))
+    }
+
+    async fn handle_secrets_command(
+        &self,
+        params: &[&str],
+        session_id: &str,
+    ) -> Result<Option<Message>> {
+        let enabled = match params.first() {
+            Some(&"on") => true,
+            Some(&"off") => false,
+            _ => {
+                return Ok(Some(
+                    Message::assistant().with_text("Usage: /secrets on|off"),
+                ));
+            }
+        };
+
+        let manager = self.config.session_manager.clone();
+        let mut session = manager.get_session(session_id, false).await?;
+        SecretScanState::new(enabled).to_extension_data(&mut session.extension_data)?;
+        manager
+            .update(session_id)
+            .extension_data(session.extension_data)
+            .apply()
+            .await?;
+
+        let notice = if enabled {
+            i18n::text("command.secrets_on")
+        } else {
+            i18n::text("command.secrets_off")
+        };
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            notice,
+        )))
+    }
+
+    async fn handle_dry_run_command(
+        &self,
+        params: &[&str],
+        session_id: &str,
+    ) -> Result<Option<Message>> {
+        let enabled = match params.first() {
+            Some(&"on") => true,
+            Some(&"off") => false,
+            _ => {
+                return Ok(Some(
+                    Message::assistant().with_text("Usage: /dryrun on|off"),
+                ));
+            }
+        };
+
+        let manager = self.config.session_manager.clone();
+        let mut session = manager.get_session(session_id, false).await?;
+        DryRunState::new(enabled).to_extension_data(&mut session.extension_data)?;
+        manager
+            .update(session_id)
+            .extension_data(session.extension_data)
+            .apply()
+            .await?;
+
+        if enabled {
+            if let Err(e) = self.snapshot_tool_set(session_id, DRY_RUN_TOOL_SET).await {
+                tracing::warn!("Failed to save extensions before dry run: {}", e);
+            }
+        } else if let Some(snapshot) = self
+            .extension_manager
+            .remove_tool_set(DRY_RUN_TOOL_SET)
+            .await
+        {
+            if let Err(e) = self.apply_tool_set(&snapshot, session_id).await {
+                tracing::warn!("Failed to restore extensions after dry run: {}", e);
+            }
+        }
+
+        let notice = if enabled {
+            i18n::text("command.dry_run_on")
+        } else {
+            i18n::text("command.dry_run_off")
+        };
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            notice,
+        )))
+    }
+
+    async fn handle_plan_command(
+        &self,
+        params: &[&str],
+        session_id: &str,
+    ) -> Result<Option<Message>> {
+        let manager = self.config.session_manager.clone();
+        let mut session = manager.get_session(session_id, false).await?;
+        let mut state = PlanModeState::load(&session.extension_data);
+
+        let notice = match params.first() {
+            None => {
+                if !state.enabled {
+                    return Ok(Some(
+                        Message::assistant()
+                            .with_text("Plan mode is off. Usage: /plan on|off|stop"),
+                    ));
+                }
+                return Ok(Some(Message::assistant().with_text(state.summary())));
+            }
+            Some(&"on") => {
+                state = PlanModeState::new(true);
+                i18n::text("command.plan_on")
+            }
+            Some(&"off") => {
+                state = PlanModeState::new(false);
+                i18n::text("command.plan_off")
+            }
+            Some(&"stop") => {
+                let Some(step) = state.current_step().filter(|_| state.approved) else {
+                    return Ok(Some(
+                        Message::assistant().with_text("There is no approved plan in progress"),
+                    ));
+                };
+                state.stop_after = Some(step);
+                i18n::text_with("command.plan_stop", &[("step", &step)])
+            }
+            _ => {
+                return Ok(Some(
+                    Message::assistant().with_text("Usage: /plan on|off|stop"),
+                ));
+            }
+        };
+
+        state.to_extension_data(&mut session.extension_data)?;
+        manager
+            .update(session_id)
+            .extension_data(session.extension_data)
+            .apply()
+            .await?;
+
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            notice,
+        )))
+    }
+
+    async fn handle_profile_command(
+        &self,
+        params: &[&str],
+        session_id: &str,
+    ) -> Result<Option<Message>> {
+        let manager = self.config.session_manager.clone();
+        let mut session = manager.get_session(session_id, false).await?;
+        let profiles = configured_profiles();
+
+        let Some(selection) = params.first() else {
+            let mut names: Vec<&String> = profiles.keys().collect();
+            names.sort();
+            let active = active_profile_name(&session).unwrap_or_else(|| "none".to_string());
+            let available = if names.is_empty() {
+                "none configured in GOOSE_PERMISSION_PROFILES".to_string()
+            } else {
+                names
+                    .iter()
+                    .map(|name| match &profiles[*name].description {
+                        Some(description) => format!("{} ({})", name, description),
+                        None => name.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            return Ok(Some(Message::assistant().with_text(format!(
+                "Active permission profile: {}\nAvailable profiles: {}\nUsage: /profile <name>|off",
+                active, available
+            ))));
+        };
+
+        let name = match *selection {
+            "off" => None,
+            name if profiles.contains_key(name) => Some(name.to_string()),
+            name => {
+                return Ok(Some(
+                    Message::assistant()
+                        .with_text(format!("Unknown permission profile '{}'", name)),
+                ));
+            }
+        };
+        PermissionProfileState::new(name.clone()).to_extension_data(&mut session.extension_data)?;
+        manager
+            .update(session_id)
+            .extension_data(session.extension_data)
+            .apply()
+            .await?;
+
+        let notice = match (name, org_profile_name()) {
+            (Some(name), _) => format!("Permission profile '{}' active for this session", name),
+            (None, Some(org)) => format!(
+                "Your organization's config requires permission profile '{}', so it stays active",
+                org
+            ),
+            (None, None) => "No permission profile active for this session".to_string(),
+        };
+        Ok(Some(Message::assistant().with_system_notification(
+            SystemNotificationType::InlineMessage,
+            notice,
         )))
     }
 
@@ -194,6 +489,7 @@ impl Agent {
                 }
                 output.push('\n');
             }
+            output.push_str("Run a prompt with /<extension>:<prompt> [arguments]\n");
         }
 
         Ok(Some(Message::assistant().with_text(output)))
@@ -261,45 +557,141 @@ impl Agent {
             .get_prompt(session_id, &prompt_name, arguments_value)
             .await
         {
-            Ok(prompt_result) => {
-                for (i, prompt_message) in prompt_result.messages.into_iter().enumerate() {
-                    let msg = Message::from(prompt_message);
-
-                    let expected_role = if i % 2 == 0 {
-                        rmcp::model::Role::User
-                    } else {
-                        rmcp::model::Role::Assistant
-                    };
-
-                    if msg.role != expected_role {
-                        let error_msg = format!(
-                            "Expected {:?} message at position {}, but found {:?}",
-                            expected_role, i, msg.role
-                        );
-                        return Ok(Some(Message::assistant().with_text(error_msg)));
-                    }
+            Ok(prompt_result) => self.insert_prompt_messages(session_id, prompt_result).await,
+            Err(e) => Ok(Some(
+                Message::assistant().with_text(format!("Error getting prompt: {}", e)),
+            )),
+        }
+    }
 
-                    self.config
-                        .session_manager
-                        .clone()
-                        .add_message(session_id, &msg)
-                        .await?;
-                }
+    /// Add the rendered messages of a prompt to the conversation, returning the last one
+    async fn insert_prompt_messages(
+        &self,
+        session_id: &str,
+        prompt_result: GetPromptResult,
+    ) -> Result<Option<Message>> {
+        for (i, prompt_message) in prompt_result.messages.into_iter().enumerate() {
+            let msg = Message::from(prompt_message);
 
-                let last_message = self
-                    .config
-                    .session_manager
-                    .get_session(session_id, true)
-                    .await?
-                    .conversation
-                    .ok_or_else(|| anyhow!("No conversation found"))?
-                    .messages()
-                    .last()
-                    .cloned()
-                    .ok_or_else(|| anyhow!("No messages in conversation"))?;
-
-                Ok(Some(last_message))
+            let expected_role = if i % 2 == 0 {
+                rmcp::model::Role::User
+            } else {
+                rmcp::model::Role::Assistant
+            };
+
+            if msg.role != expected_role {
+                let error_msg = format!(
+                    "Expected {:?} message at position {}, but found {:?}",
+                    expected_role, i, msg.role
+                );
+                return Ok(Some(Message::assistant().with_text(error_msg)));
+            }
+
+            self.config
+                .session_manager
+                .clone()
+                .add_message(session_id, &msg)
+                .await?;
+        }
+
+        let last_message = self
+            .config
+            .session_manager
+            .get_session(session_id, true)
+            .await?
+            .conversation
+            .ok_or_else(|| anyhow!("No conversation found"))?
+            .messages()
+            .last()
+            .cloned()
+            .ok_or_else(|| anyhow!("No messages in conversation"))?;
+
+        Ok(Some(last_message))
+    }
+
+    /// Run an extension prompt invoked as `/<prompt>` or `/<extension>:<prompt>`.
+    /// Returns None when no extension offers a prompt by that name.
+    async fn handle_extension_prompt_command(
+        &self,
+        command: &str,
+        params_str: &str,
+        session_id: &str,
+    ) -> Result<Option<Message>> {
+        if command.is_empty()
+            || !command
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+        {
+            return Ok(None);
+        }
+
+        let prompts = match self
+            .extension_manager
+            .list_prompts(session_id, CancellationToken::default())
+            .await
+        {
+            Ok(prompts) => prompts,
+            Err(e) => {
+                tracing::debug!("Could not list prompts for /{}: {}", command, e.message);
+                return Ok(None);
+            }
+        };
+
+        let (extension_filter, prompt_name) = match command.split_once(':') {
+            Some((extension, name)) => (Some(extension), name),
+            None => (None, command),
+        };
+        let matches: Vec<(&String, &Prompt)> = prompts
+            .iter()
+            .filter(|(extension, _)| extension_filter.is_none_or(|f| f == extension.as_str()))
+            .flat_map(|(extension, list)| {
+                list.iter()
+                    .filter(|p| p.name == prompt_name)
+                    .map(move |p| (extension, p))
+            })
+            .collect();
+
+        let (extension, prompt) = match matches.as_slice() {
+            [] => return Ok(None),
+            [single] => *single,
+            several => {
+                let choices = several
+                    .iter()
+                    .map(|(extension, _)| format!("/{}:{}", extension, prompt_name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Ok(Some(Message::assistant().with_system_notification(
+                    SystemNotificationType::InlineMessage,
+                    format!(
+                        "Several extensions provide a '{}' prompt, use one of: {}",
+                        prompt_name, choices
+                    ),
+                )));
             }
+        };
+
+        let (arguments, missing) = parse_prompt_arguments(prompt, params_str);
+        if !missing.is_empty() {
+            return Ok(Some(Message::assistant().with_system_notification(
+                SystemNotificationType::InlineMessage,
+                prompt_usage(extension, prompt, &missing),
+            )));
+        }
+
+        let arguments_value = serde_json::to_value(arguments)
+            .map_err(|e| anyhow!("Failed to serialize arguments: {}", e))?;
+        match self
+            .extension_manager
+            .get_prompt(
+                session_id,
+                extension,
+                &prompt.name,
+                arguments_value,
+                CancellationToken::default(),
+            )
+            .await
+        {
+            Ok(prompt_result) => self.insert_prompt_messages(session_id, prompt_result).await,
             Err(e) => Ok(Some(
                 Message::assistant().with_text(format!("Error getting prompt: {}", e)),
             )),
@@ -409,7 +801,8 @@ impl Agent {
         self.apply_recipe_components(recipe.response.clone(), true)
             .await;
 
-        let prompt = [recipe.instructions.as_deref(), recipe.prompt.as_deref()]
+        let instructions = recipe.system_instructions();
+        let prompt = [instructions.as_deref(), recipe.prompt.as_deref()]
             .into_iter()
             .flatten()
             .collect::<Vec<_>>()
@@ -418,3 +811,155 @@ impl Agent {
         Ok(Some(Message::user().with_text(prompt)))
     }
 }
+
+/// Split command parameters on whitespace, keeping double-quoted values together
+fn split_params(params_str: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in params_str.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    params.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        params.push(current);
+    }
+    params
+}
+
+/// Map command parameters onto a prompt's arguments. `name=value` pairs are matched by
+/// name, other values fill the remaining arguments in order. A prompt with a single
+/// argument takes the whole parameter string. Returns the values and the names of
+/// required arguments that were not given.
+fn parse_prompt_arguments(
+    prompt: &Prompt,
+    params_str: &str,
+) -> (HashMap<String, String>, Vec<String>) {
+    let declared = prompt.arguments.as_deref().unwrap_or_default();
+    let mut values = HashMap::new();
+
+    if let [only] = declared {
+        let is_named = params_str
+            .split_once('=')
+            .is_some_and(|(key, _)| key.trim() == only.name);
+        if !params_str.is_empty() && !is_named {
+            values.insert(only.name.clone(), params_str.trim_matches('"').to_string());
+        }
+    }
+
+    if values.is_empty() {
+        let mut positional = Vec::new();
+        for param in split_params(params_str) {
+            match param.split_once('=') {
+                Some((key, value)) if declared.iter().any(|arg| arg.name == key) => {
+                    values.insert(key.to_string(), value.to_string());
+                }
+                _ => positional.push(param),
+            }
+        }
+        let mut positional = positional.into_iter();
+        for arg in declared {
+            if values.contains_key(&arg.name) {
+                continue;
+            }
+            match positional.next() {
+                Some(value) => {
+                    values.insert(arg.name.clone(), value);
+                }
+                None => break,
+            }
+        }
+    }
+
+    let missing = declared
+        .iter()
+        .filter(|arg| arg.required.unwrap_or(false) && !values.contains_key(&arg.name))
+        .map(|arg| arg.name.clone())
+        .collect();
+    (values, missing)
+}
+
+fn prompt_usage(extension: &str, prompt: &Prompt, missing: &[String]) -> String {
+    let declared = prompt.arguments.as_deref().unwrap_or_default();
+    let mut output = format!(
+        "/{}:{} needs a value for: {}\n",
+        extension,
+        prompt.name,
+        missing.join(", ")
+    );
+    for arg in declared {
+        output.push_str(&format!("  - {}", arg.name));
+        if arg.required.unwrap_or(false) {
+            output.push_str(" (required)");
+        }
+        if let Some(desc) = &arg.description {
+            output.push_str(&format!(": {}", desc));
+        }
+        output.push('\n');
+    }
+    let usage = declared
+        .iter()
+        .map(|arg| format!("{}=<value>", arg.name))
+        .collect::<Vec<_>>()
+        .join(" ");
+    output.push_str(&format!(
+        "\nUsage: /{}:{} {}",
+        extension, prompt.name, usage
+    ));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(arguments: &[(&str, bool)]) -> Prompt {
+        let arguments: Vec<_> = arguments
+            .iter()
+            .map(|(name, required)| serde_json::json!({"name": name, "required": required}))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "name": "review",
+            "description": "Review a change",
+            "arguments": arguments,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn single_argument_takes_whole_input() {
+        let (values, missing) =
+            parse_prompt_arguments(&prompt(&[("topic", true)]), "error handling in the cli");
+        assert_eq!(values["topic"], "error handling in the cli");
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn named_and_positional_arguments() {
+        let prompt = prompt(&[("pr", true), ("focus", false), ("tone", false)]);
+        let (values, missing) =
+            parse_prompt_arguments(&prompt, r#"tone=strict 42 "perf and memory""#);
+        assert_eq!(values["pr"], "42");
+        assert_eq!(values["focus"], "perf and memory");
+        assert_eq!(values["tone"], "strict");
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn reports_missing_required_arguments() {
+        let prompt = prompt(&[("pr", true), ("focus", false)]);
+        let (values, missing) = parse_prompt_arguments(&prompt, "");
+        assert!(values.is_empty());
+        assert_eq!(missing, vec!["pr".to_string()]);
+        let usage = prompt_usage("github", &prompt, &missing);
+        assert!(usage.starts_with("/github:review needs a value for: pr"));
+        assert!(usage.ends_with("Usage: /github:review pr=<value> focus=<value>"));
+    }
+}
@@ -7,6 +7,7 @@ use crate::config::Config;
 use rmcp::model::Tool;
 use rmcp::service::ClientInitializeError;
 use rmcp::ServiceError as ClientError;
+use schemars::JsonSchema;
 use serde::Deserializer;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -58,15 +59,39 @@ pub enum ExtensionError {
 
 pub type ExtensionResult<T> = Result<T, ExtensionError>;
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default, ToSchema, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, Default, ToSchema, JsonSchema, PartialEq)]
 pub struct Envs {
     /// A map of environment variables to set, e.g. API_KEY -> some_secret, HOST -> host
     #[serde(default)]
     #[serde(flatten)]
     map: HashMap<String, String>,
+    /// Host environment variables the extension inherits. When unset the extension sees the
+    /// whole host environment; when set only these (plus a minimal base such as PATH and HOME)
+    /// are passed through.
+    #[serde(
+        default,
+        rename = "inherit_env",
+        skip_serializing_if = "Option::is_none"
+    )]
+    inherit: Option<Vec<String>>,
 }
 
 impl Envs {
+    /// Prefix for values that name a secret in the keyring, e.g. `keyring:github_token`.
+    /// The secret is looked up when the extension starts and never stored in the config.
+    pub const KEYRING_PREFIX: &'static str = "keyring:";
+
+    /// Host variables always inherited when an allowlist is set, so commands can still be found
+    const BASE_INHERITED_KEYS: [&'static str; 7] = [
+        "PATH",
+        "HOME",
+        "USERPROFILE",
+        "SystemRoot",
+        "TEMP",
+        "TMP",
+        "TMPDIR",
+    ];
+
     /// List of sensitive env vars that should not be overridden
     const DISALLOWED_KEYS: [&'static str; 31] = [
         // 🔧 Binary path manipulation
@@ -119,7 +144,16 @@ impl Envs {
             validated.insert(key, value);
         }
 
-        Self { map: validated }
+        Self {
+            map: validated,
+            inherit: None,
+        }
+    }
+
+    /// Restrict the host variables the extension inherits to `keys`
+    pub fn with_inherit_env(mut self, keys: Option<Vec<String>>) -> Self {
+        self.inherit = keys;
+        self
     }
 
     /// Returns a copy of the validated env vars
@@ -127,6 +161,34 @@ impl Envs {
         self.map.clone()
     }
 
+    pub fn inherit_env(&self) -> Option<&[String]> {
+        self.inherit.as_deref()
+    }
+
+    /// Host variables to pass through to the extension process, or None to inherit everything
+    pub fn inherited_host_env(&self) -> Option<HashMap<String, String>> {
+        let allowed = self.inherit.as_ref()?;
+        Some(
+            std::env::vars()
+                .filter(|(key, _)| {
+                    Self::BASE_INHERITED_KEYS
+                        .iter()
+                        .copied()
+                        .chain(allowed.iter().map(String::as_str))
+                        .any(|k| k.eq_ignore_ascii_case(key))
+                })
+                .collect(),
+        )
+    }
+
+    /// The keyring secret a value refers to, if it is a `keyring:` reference
+    pub fn keyring_reference(value: &str) -> Option<&str> {
+        value
+            .strip_prefix(Self::KEYRING_PREFIX)
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+
     /// Returns an error if any disallowed env var is present
     pub fn validate(&self) -> Result<(), Box<ExtensionError>> {
         for key in self.map.keys() {
@@ -148,7 +210,7 @@ impl Envs {
 }
 
 /// Represents the different types of MCP extensions that can be added to the manager
-#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, JsonSchema, PartialEq)]
 #[serde(tag = "type")]
 pub enum ExtensionConfig {
     /// SSE transport is no longer supported - kept only for config file compatibility
@@ -378,6 +440,32 @@ impl ExtensionConfig {
         }
     }
 
+    /// Limit the tools exposed from this extension to the given names
+    pub fn with_available_tools(mut self, tools: Vec<String>) -> Self {
+        match &mut self {
+            Self::Sse { .. } => {}
+            Self::StreamableHttp {
+                available_tools, ..
+            }
+            | Self::Stdio {
+                available_tools, ..
+            }
+            | Self::Builtin {
+                available_tools, ..
+            }
+            | Self::Platform {
+                available_tools, ..
+            }
+            | Self::InlinePython {
+                available_tools, ..
+            }
+            | Self::Frontend {
+                available_tools, ..
+            } => *available_tools = tools,
+        }
+        self
+    }
+
     pub fn key(&self) -> String {
         name_to_key(&self.name())
     }
@@ -440,12 +528,13 @@ impl ExtensionConfig {
                 available_tools,
             } => {
                 let merged = merge_environments(&envs, &env_keys, &name, config).await?;
+                let inherit = envs.inherit_env().map(<[String]>::to_vec);
                 Ok(Self::Stdio {
                     name,
                     description,
                     cmd,
                     args,
-                    envs: Envs::new(merged),
+                    envs: Envs::new(merged).with_inherit_env(inherit),
                     env_keys: vec![],
                     timeout,
                     bundled,
@@ -591,6 +680,50 @@ available_tools: []",
         }
     }
 
+    #[test]
+    fn test_deserialize_inherit_env() {
+        let config: ExtensionConfig = serde_yaml::from_str(
+            "type: stdio
+name: github
+cmd: github-mcp-server
+args: []
+envs:
+  GITHUB_TOKEN: keyring:github_token
+  inherit_env: [LANG, SSH_AUTH_SOCK]",
+        )
+        .unwrap();
+        let ExtensionConfig::Stdio { envs, .. } = config else {
+            panic!("unexpected result of deserialization: {}", config)
+        };
+        assert_eq!(
+            envs.inherit_env(),
+            Some(&["LANG".to_string(), "SSH_AUTH_SOCK".to_string()][..])
+        );
+        assert_eq!(envs.get_env().len(), 1);
+        assert_eq!(
+            extension::Envs::keyring_reference(&envs.get_env()["GITHUB_TOKEN"]),
+            Some("github_token")
+        );
+        assert_eq!(extension::Envs::keyring_reference("plain"), None);
+
+        let inherited = envs.inherited_host_env().unwrap();
+        assert!(inherited.keys().all(|k| {
+            [
+                "PATH",
+                "HOME",
+                "USERPROFILE",
+                "SystemRoot",
+                "TEMP",
+                "TMP",
+                "TMPDIR",
+                "LANG",
+                "SSH_AUTH_SOCK",
+            ]
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(k))
+        }));
+    }
+
     #[test]
     fn test_deserialize_null_description() {
         let config: ExtensionConfig = serde_yaml::from_str(
@@ -818,6 +951,37 @@ available_tools: []
         }
         ; "env_key_skipped_when_already_in_envs"
     )]
+    #[test_case(
+        ExtensionConfig::Stdio {
+            name: "test".into(),
+            description: String::new(),
+            cmd: "echo".into(),
+            args: vec![],
+            envs: extension::Envs::new(
+                [("TOKEN".to_string(), "keyring:MY_SECRET".to_string())].into(),
+            )
+            .with_inherit_env(Some(vec!["LANG".into()])),
+            env_keys: vec![],
+            timeout: None,
+            bundled: None,
+            available_tools: vec![],
+        },
+        ExtensionConfig::Stdio {
+            name: "test".into(),
+            description: String::new(),
+            cmd: "echo".into(),
+            args: vec![],
+            envs: extension::Envs::new(
+                [("TOKEN".to_string(), "secret_value".to_string())].into(),
+            )
+            .with_inherit_env(Some(vec!["LANG".into()])),
+            env_keys: vec![],
+            timeout: None,
+            bundled: None,
+            available_tools: vec![],
+        }
+        ; "keyring_reference_resolved"
+    )]
     #[tokio::test]
     async fn test_resolve(config: ExtensionConfig, expected: ExtensionConfig) {
         let dir = tempfile::tempdir().unwrap();
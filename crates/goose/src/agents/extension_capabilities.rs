@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rmcp::model::{JsonObject, ServerInfo};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::agents::extension_manager::{ExtensionManager, ToolEffect};
+use crate::agents::tool_cache::path_arguments;
+use crate::config::extensions::name_to_key;
+use crate::config::{Config, GooseMode};
+use crate::conversation::message::{Message, ToolRequest};
+use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
+
+/// Experimental server capability under which an extension can declare its own manifest
+pub const MANIFEST_CAPABILITY: &str = "goose/capabilities";
+
+/// What an extension needs access to. Servers can declare this under the `goose/capabilities`
+/// experimental capability; users can declare it for third-party servers in
+/// `GOOSE_EXTENSION_CAPABILITIES`, keyed by extension, which takes precedence.
+/// Extensions without a manifest are trusted with everything, as before.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CapabilityManifest {
+    /// Reaches out to hosts on the network
+    #[serde(default)]
+    pub network: bool,
+    /// Creates, changes or deletes files
+    #[serde(default)]
+    pub filesystem_write: bool,
+    /// Environment variables or keyring secrets it is given
+    #[serde(default)]
+    pub credentials: Vec<String>,
+}
+
+impl CapabilityManifest {
+    pub fn configured() -> HashMap<String, CapabilityManifest> {
+        Config::global()
+            .get_param::<HashMap<String, CapabilityManifest>>("GOOSE_EXTENSION_CAPABILITIES")
+            .unwrap_or_default()
+    }
+
+    /// The manifest the user declared for an extension
+    pub fn for_extension(name: &str) -> Option<Self> {
+        Self::configured().remove(&name_to_key(name))
+    }
+
+    pub fn save_for_extension(name: &str, manifest: Self) -> Result<()> {
+        let mut configured = Self::configured();
+        configured.insert(name_to_key(name), manifest);
+        Config::global().set_param("GOOSE_EXTENSION_CAPABILITIES", configured)?;
+        Ok(())
+    }
+
+    /// The manifest a server declared in its initialize result
+    pub fn from_server_info(info: &ServerInfo) -> Option<Self> {
+        let capabilities = serde_json::to_value(&info.capabilities).ok()?;
+        let declared = capabilities.get("experimental")?.get(MANIFEST_CAPABILITY)?;
+        serde_json::from_value(declared.clone()).ok()
+    }
+
+    pub fn allows_credential(&self, key: &str) -> bool {
+        self.credentials
+            .iter()
+            .any(|declared| declared.eq_ignore_ascii_case(key))
+    }
+
+    /// One line per capability, for showing before an extension is trusted
+    pub fn summary(&self) -> Vec<String> {
+        let yes_no = |allowed: bool| if allowed { "yes" } else { "no" };
+        let credentials = if self.credentials.is_empty() {
+            "none".to_string()
+        } else {
+            self.credentials.join(", ")
+        };
+        vec![
+            format!("network access: {}", yes_no(self.network)),
+            format!("filesystem writes: {}", yes_no(self.filesystem_write)),
+            format!("credentials: {}", credentials),
+        ]
+    }
+
+    /// Capabilities a tool call appears to use that the manifest does not declare
+    pub fn undeclared_uses(
+        &self,
+        arguments: Option<&JsonObject>,
+        effect: ToolEffect,
+    ) -> Vec<&'static str> {
+        let mut undeclared = Vec::new();
+        let Some(arguments) = arguments else {
+            return undeclared;
+        };
+        if !self.network && arguments.values().any(contains_url) {
+            undeclared.push("network access");
+        }
+        if !self.filesystem_write
+            && effect == ToolEffect::Mutating
+            && !path_arguments(arguments).is_empty()
+        {
+            undeclared.push("filesystem writes");
+        }
+        undeclared
+    }
+}
+
+fn contains_url(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::String(s) => {
+            let s = s.trim_start();
+            s.starts_with("http://") || s.starts_with("https://")
+        }
+        serde_json::Value::Array(items) => items.iter().any(contains_url),
+        serde_json::Value::Object(map) => map.values().any(contains_url),
+        _ => false,
+    }
+}
+
+/// Holds tool calls for approval when they look like they use a capability the
+/// extension's manifest does not declare
+pub struct CapabilityInspector {
+    extension_manager: Arc<ExtensionManager>,
+}
+
+impl CapabilityInspector {
+    pub fn new(extension_manager: Arc<ExtensionManager>) -> Self {
+        Self { extension_manager }
+    }
+}
+
+#[async_trait]
+impl ToolInspector for CapabilityInspector {
+    fn name(&self) -> &'static str {
+        "capabilities"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn inspect(
+        &self,
+        session_id: &str,
+        tool_requests: &[ToolRequest],
+        _messages: &[Message],
+        _goose_mode: GooseMode,
+    ) -> Result<Vec<InspectionResult>> {
+        let mut results = Vec::new();
+        for request in tool_requests {
+            let Ok(tool_call) = &request.tool_call else {
+                continue;
+            };
+            let Some(extension) = self
+                .extension_manager
+                .tool_owner(session_id, &tool_call.name)
+                .await
+            else {
+                continue;
+            };
+            let Some(manifest) = self.extension_manager.capability_manifest(&extension).await
+            else {
+                continue;
+            };
+            let effect = self
+                .extension_manager
+                .classify_tool(session_id, &tool_call.name)
+                .await;
+            let undeclared = manifest.undeclared_uses(tool_call.arguments.as_ref(), effect);
+            if undeclared.is_empty() {
+                continue;
+            }
+
+            let reason = format!(
+                "{} did not declare {} in its capability manifest",
+                extension,
+                undeclared.join(" or ")
+            );
+            results.push(InspectionResult {
+                tool_request_id: request.id.clone(),
+                action: InspectionAction::RequireApproval(Some(reason.clone())),
+                reason,
+                confidence: 1.0,
+                inspector_name: self.name().to_string(),
+                finding_id: None,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ServerCapabilities;
+    use rmcp::object;
+
+    #[test]
+    fn reads_manifest_declared_by_server() {
+        let capabilities: ServerCapabilities = serde_json::from_value(serde_json::json!({
+            "tools": {},
+            "experimental": {
+                MANIFEST_CAPABILITY: {"network": true, "credentials": ["GITHUB_TOKEN"]}
+            }
+        }))
+        .unwrap();
+        let info = ServerInfo::new(capabilities);
+        let manifest = CapabilityManifest::from_server_info(&info).unwrap();
+        assert!(manifest.network);
+        assert!(!manifest.filesystem_write);
+        assert!(manifest.allows_credential("github_token"));
+
+        let undeclared = ServerInfo::new(ServerCapabilities::builder().enable_tools().build());
+        assert_eq!(CapabilityManifest::from_server_info(&undeclared), None);
+    }
+
+    #[test]
+    fn flags_undeclared_network_and_writes() {
+        let manifest = CapabilityManifest::default();
+        let fetch = object!({"request": {"url": "https://example.com"}});
+        assert_eq!(
+            manifest.undeclared_uses(Some(&fetch), ToolEffect::ReadOnly),
+            vec!["network access"]
+        );
+
+        let write = object!({"path": "notes.txt", "content": "hi"});
+        assert_eq!(
+            manifest.undeclared_uses(Some(&write), ToolEffect::Mutating),
+            vec!["filesystem writes"]
+        );
+        assert!(manifest
+            .undeclared_uses(Some(&write), ToolEffect::ReadOnly)
+            .is_empty());
+
+        let trusted = CapabilityManifest {
+            network: true,
+            filesystem_write: true,
+            credentials: vec![],
+        };
+        assert!(trusted
+            .undeclared_uses(Some(&fetch), ToolEffect::Mutating)
+            .is_empty());
+    }
+}
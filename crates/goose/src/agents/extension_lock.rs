@@ -0,0 +1,542 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::agents::extension::ExtensionConfig;
+use crate::config::extensions::name_to_key;
+use crate::config::paths::Paths;
+use crate::config::Config;
+
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+const PYPI_REGISTRY: &str = "https://pypi.org/pypi";
+
+/// Package ecosystems whose launchers accept an exact version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    Npm,
+    Pypi,
+}
+
+impl Ecosystem {
+    fn from_cmd(cmd: &str) -> Option<Self> {
+        if cmd.ends_with("npx") {
+            Some(Self::Npm)
+        } else if cmd.ends_with("uvx") {
+            Some(Self::Pypi)
+        } else {
+            None
+        }
+    }
+
+    /// Flags of the launcher that take the package as their value
+    fn package_flags(self) -> &'static [&'static str] {
+        match self {
+            Self::Npm => &["-p", "--package"],
+            Self::Pypi => &["--from"],
+        }
+    }
+
+    /// Flags of the launcher that take a value other than the package
+    fn value_flags(self) -> &'static [&'static str] {
+        match self {
+            Self::Npm => &[
+                "-c",
+                "--call",
+                "--cache",
+                "--loglevel",
+                "--prefix",
+                "--registry",
+                "--userconfig",
+                "-w",
+                "--workspace",
+            ],
+            Self::Pypi => &[
+                "-b",
+                "--build-constraints",
+                "-c",
+                "--constraints",
+                "--cache-dir",
+                "--config-file",
+                "--default-index",
+                "--directory",
+                "--env-file",
+                "--exclude-newer",
+                "--extra-index-url",
+                "-f",
+                "--find-links",
+                "--index",
+                "--index-strategy",
+                "--index-url",
+                "--keyring-provider",
+                "--overrides",
+                "-p",
+                "--python",
+                "--prerelease",
+                "--project",
+                "--resolution",
+                "--with",
+                "--with-editable",
+                "--with-requirements",
+            ],
+        }
+    }
+}
+
+/// A package reference found in the args of an `npx`/`uvx` launch
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageRef {
+    pub ecosystem: Ecosystem,
+    /// Index of the package token in the args
+    pub index: usize,
+    pub name: String,
+    /// Version given explicitly in the args, if any
+    pub version: Option<String>,
+}
+
+impl PackageRef {
+    pub fn from_args(cmd: &str, args: &[String]) -> Option<Self> {
+        let ecosystem = Ecosystem::from_cmd(cmd)?;
+        let mut index = 0;
+        while index < args.len() {
+            let arg = args[index].trim();
+            if ecosystem.package_flags().contains(&arg) || arg == "--" {
+                index += 1;
+                break;
+            }
+            if !arg.starts_with('-') {
+                break;
+            }
+            // `--flag=value` carries its value; `--flag value` takes the next arg
+            if ecosystem.value_flags().contains(&arg) {
+                index += 1;
+            }
+            index += 1;
+        }
+        let token = args.get(index)?.trim();
+        if token.is_empty() {
+            return None;
+        }
+        let (name, version) = split_version(ecosystem, token);
+        Some(Self {
+            ecosystem,
+            index,
+            name,
+            version,
+        })
+    }
+
+    /// The args with this package pinned to `version`
+    pub fn pinned_args(&self, args: &[String], version: &str) -> Vec<String> {
+        let mut args = args.to_vec();
+        let from_flag = self.index > 0 && args[self.index - 1] == "--from";
+        args[self.index] = match self.ecosystem {
+            // `uvx --from` takes a requirement, a bare `uvx` tool name takes `name@version`
+            Ecosystem::Pypi if from_flag => format!("{}=={}", self.name, version),
+            _ => format!("{}@{}", self.name, version),
+        };
+        args
+    }
+}
+
+fn split_version(ecosystem: Ecosystem, token: &str) -> (String, Option<String>) {
+    let split = match ecosystem {
+        // Scoped packages start with '@', so the version follows the last one
+        Ecosystem::Npm => token
+            .rfind('@')
+            .filter(|idx| *idx > 0)
+            .map(|idx| (&token[..idx], &token[idx + 1..])),
+        Ecosystem::Pypi => token.split_once("==").or_else(|| token.split_once('@')),
+    };
+    match split {
+        Some((name, version)) if !version.is_empty() && version != "latest" => {
+            (name.trim().to_string(), Some(version.trim().to_string()))
+        }
+        Some((name, _)) => (name.trim().to_string(), None),
+        None => (token.to_string(), None),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub ecosystem: Ecosystem,
+    pub name: String,
+    pub version: String,
+    pub locked_at: DateTime<Utc>,
+}
+
+/// Versions resolved for `npx`/`uvx` extensions, so later launches run the same release.
+/// Stored as `extensions.lock` in the config directory and keyed by extension.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionLockfile {
+    #[serde(default)]
+    pub extensions: BTreeMap<String, LockedPackage>,
+}
+
+impl ExtensionLockfile {
+    pub fn path() -> PathBuf {
+        Paths::in_config_dir("extensions.lock")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_yaml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Change the lockfile on disk. Launches run concurrently, so changes go through here
+    /// to not overwrite each other.
+    fn update(change: impl FnOnce(&mut Self)) -> Result<()> {
+        static WRITE: Mutex<()> = Mutex::new(());
+        let _guard = WRITE.lock().unwrap_or_else(|e| e.into_inner());
+        let mut lockfile = Self::load();
+        change(&mut lockfile);
+        lockfile.save()
+    }
+
+    fn lock(&mut self, key: &str, package: &PackageRef, version: &str) {
+        self.extensions.insert(
+            key.to_string(),
+            LockedPackage {
+                ecosystem: package.ecosystem,
+                name: package.name.clone(),
+                version: version.to_string(),
+                locked_at: Utc::now(),
+            },
+        );
+    }
+
+    fn locked_version(&self, key: &str, package: &PackageRef) -> Option<&str> {
+        self.extensions
+            .get(key)
+            .filter(|locked| locked.ecosystem == package.ecosystem && locked.name == package.name)
+            .map(|locked| locked.version.as_str())
+    }
+}
+
+/// Whether launches are pinned; `GOOSE_EXTENSION_LOCK: false` turns the lockfile off
+pub fn locking_enabled() -> bool {
+    Config::global()
+        .get_param::<bool>("GOOSE_EXTENSION_LOCK")
+        .unwrap_or(true)
+}
+
+/// Look up the newest published version of a package
+pub async fn latest_version(ecosystem: Ecosystem, name: &str) -> Result<String> {
+    #[derive(Deserialize)]
+    struct Versioned {
+        version: String,
+    }
+    #[derive(Deserialize)]
+    struct PypiInfo {
+        info: Versioned,
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let version = match ecosystem {
+        Ecosystem::Npm => {
+            client
+                .get(format!("{}/{}/latest", NPM_REGISTRY, name))
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Versioned>()
+                .await?
+                .version
+        }
+        Ecosystem::Pypi => {
+            client
+                .get(format!("{}/{}/json", PYPI_REGISTRY, name))
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<PypiInfo>()
+                .await?
+                .info
+                .version
+        }
+    };
+    Ok(version)
+}
+
+type ResolvedVersion = Arc<tokio::sync::Mutex<Option<String>>>;
+
+/// The version resolved for each package in this process, behind a lock per package so
+/// extensions launched together that share a package resolve it once
+static RESOLVED: LazyLock<Mutex<HashMap<(Ecosystem, String), ResolvedVersion>>> =
+    LazyLock::new(Default::default);
+
+fn resolved_slot(package: &PackageRef) -> ResolvedVersion {
+    RESOLVED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry((package.ecosystem, package.name.clone()))
+        .or_default()
+        .clone()
+}
+
+/// The version resolved for the package earlier in this process, or a new one from `resolve`
+async fn cached_version<F, Fut>(package: &PackageRef, resolve: F) -> Result<String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    let slot = resolved_slot(package);
+    let mut resolved = slot.lock().await;
+    if let Some(version) = resolved.as_ref() {
+        return Ok(version.clone());
+    }
+    let version = resolve().await?;
+    *resolved = Some(version.clone());
+    Ok(version)
+}
+
+/// Args to launch an extension with, pinned to its locked version. The first launch of an
+/// unpinned package resolves and records the current version. Packages the user pinned in
+/// the config are left alone, and resolution failures fall back to the unpinned args.
+pub async fn pin_launch_args(key: &str, cmd: &str, args: &[String]) -> Vec<String> {
+    let Some(package) = PackageRef::from_args(cmd, args) else {
+        return args.to_vec();
+    };
+    if package.version.is_some() || !locking_enabled() {
+        return args.to_vec();
+    }
+
+    if let Some(version) = ExtensionLockfile::load().locked_version(key, &package) {
+        return package.pinned_args(args, version);
+    }
+
+    let resolved = cached_version(&package, || {
+        latest_version(package.ecosystem, &package.name)
+    })
+    .await;
+    match resolved {
+        Ok(version) => {
+            let recorded = ExtensionLockfile::update(|lockfile| {
+                // Another launch may have locked it meanwhile
+                if lockfile.locked_version(key, &package).is_none() {
+                    lockfile.lock(key, &package, &version);
+                }
+            });
+            if let Err(e) = recorded {
+                tracing::warn!("Failed to write extension lockfile: {}", e);
+            }
+            package.pinned_args(args, &version)
+        }
+        Err(e) => {
+            tracing::warn!(
+                "Could not resolve a version for {}, launching unpinned: {}",
+                package.name,
+                e
+            );
+            args.to_vec()
+        }
+    }
+}
+
+/// Result of re-resolving one locked extension
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockUpdate {
+    pub extension: String,
+    pub package: String,
+    pub previous: Option<String>,
+    pub current: String,
+}
+
+/// Move locked extensions to the newest published versions. With `names` empty every
+/// configured `npx`/`uvx` extension is updated, including ones not locked yet.
+pub async fn update_locked_extensions(
+    extensions: &[ExtensionConfig],
+    names: &[String],
+) -> Result<Vec<LockUpdate>> {
+    let lockfile = ExtensionLockfile::load();
+    let mut locked = Vec::new();
+    let mut updates = Vec::new();
+
+    for config in extensions {
+        let ExtensionConfig::Stdio {
+            name, cmd, args, ..
+        } = config
+        else {
+            continue;
+        };
+        if !names.is_empty() && !names.iter().any(|n| name_to_key(n) == config.key()) {
+            continue;
+        }
+        let Some(package) = PackageRef::from_args(cmd, args) else {
+            continue;
+        };
+        if package.version.is_some() {
+            continue;
+        }
+
+        let key = config.key();
+        let version = latest_version(package.ecosystem, &package.name)
+            .await
+            .map_err(|e| anyhow!("Failed to resolve {} for {}: {}", package.name, name, e))?;
+        let previous = lockfile.locked_version(&key, &package).map(str::to_string);
+        *resolved_slot(&package).lock().await = Some(version.clone());
+        updates.push(LockUpdate {
+            extension: name.clone(),
+            package: package.name.clone(),
+            previous,
+            current: version.clone(),
+        });
+        locked.push((key, package, version));
+    }
+
+    ExtensionLockfile::update(|lockfile| {
+        for (key, package, version) in &locked {
+            lockfile.lock(key, package, version);
+        }
+    })?;
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_package_tokens() {
+        let npx = args(&["-y", "@modelcontextprotocol/server-github", "--verbose"]);
+        let package = PackageRef::from_args("npx", &npx).unwrap();
+        assert_eq!(package.name, "@modelcontextprotocol/server-github");
+        assert_eq!(package.version, None);
+        assert_eq!(
+            package.pinned_args(&npx, "2025.4.8"),
+            args(&[
+                "-y",
+                "@modelcontextprotocol/server-github@2025.4.8",
+                "--verbose"
+            ])
+        );
+
+        let uvx = args(&["mcp-server-fetch"]);
+        let package = PackageRef::from_args("uvx", &uvx).unwrap();
+        assert_eq!(
+            package.pinned_args(&uvx, "2025.1.17"),
+            args(&["mcp-server-fetch@2025.1.17"])
+        );
+
+        let uvx = args(&["--from", "mcp-server-git==0.6.2", "mcp-server-git"]);
+        let package = PackageRef::from_args("/usr/local/bin/uvx", &uvx).unwrap();
+        assert_eq!(package.index, 1);
+        assert_eq!(package.name, "mcp-server-git");
+        assert_eq!(package.version.as_deref(), Some("0.6.2"));
+
+        assert!(PackageRef::from_args("python", &args(&["server.py"])).is_none());
+    }
+
+    #[test]
+    fn skips_launcher_flags_and_their_values() {
+        let uvx = args(&["--python", "3.12", "--with", "httpx", "mcp-server-fetch"]);
+        let package = PackageRef::from_args("uvx", &uvx).unwrap();
+        assert_eq!(package.index, 4);
+        assert_eq!(package.name, "mcp-server-fetch");
+
+        // `-p` picks the interpreter for uvx but the package for npx
+        let uvx = args(&["-p", "3.11", "mcp-server-git"]);
+        assert_eq!(
+            PackageRef::from_args("uvx", &uvx).unwrap().name,
+            "mcp-server-git"
+        );
+        let npx = args(&["-p", "@scope/server@1.0.0", "server"]);
+        let package = PackageRef::from_args("npx", &npx).unwrap();
+        assert_eq!(package.name, "@scope/server");
+        assert_eq!(package.version.as_deref(), Some("1.0.0"));
+
+        let npx = args(&[
+            "--registry",
+            "https://npm.example.com",
+            "--yes",
+            "--",
+            "mcp-remote",
+        ]);
+        let package = PackageRef::from_args("npx", &npx).unwrap();
+        assert_eq!(package.index, 4);
+        assert_eq!(package.name, "mcp-remote");
+
+        let npx = args(&["--registry=https://npm.example.com", "-y", "mcp-remote"]);
+        assert_eq!(PackageRef::from_args("npx", &npx).unwrap().index, 2);
+
+        assert!(PackageRef::from_args("uvx", &args(&["--python", "3.12"])).is_none());
+    }
+
+    #[tokio::test]
+    async fn resolves_each_package_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let package = PackageRef::from_args("npx", &args(&["resolves-once-server"])).unwrap();
+        let calls = &AtomicUsize::new(0);
+        let resolve = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::task::yield_now().await;
+            Ok("1.2.3".to_string())
+        };
+
+        let (first, second) = tokio::join!(
+            cached_version(&package, resolve),
+            cached_version(&package, resolve)
+        );
+        assert_eq!(first.unwrap(), "1.2.3");
+        assert_eq!(second.unwrap(), "1.2.3");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let failing = PackageRef::from_args("npx", &args(&["resolves-later-server"])).unwrap();
+        assert!(
+            cached_version(&failing, || async { Err(anyhow!("offline")) })
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            cached_version(&failing, || async { Ok("2.0.0".to_string()) })
+                .await
+                .unwrap(),
+            "2.0.0"
+        );
+    }
+
+    #[test]
+    fn lock_entries_only_apply_to_the_same_package() {
+        let package = PackageRef::from_args("uvx", &args(&["mcp-server-fetch"])).unwrap();
+        let mut lockfile = ExtensionLockfile::default();
+        lockfile.extensions.insert(
+            "fetch".to_string(),
+            LockedPackage {
+                ecosystem: Ecosystem::Pypi,
+                name: "mcp-server-fetch".to_string(),
+                version: "2025.1.17".to_string(),
+                locked_at: Utc::now(),
+            },
+        );
+        assert_eq!(
+            lockfile.locked_version("fetch", &package),
+            Some("2025.1.17")
+        );
+
+        let other = PackageRef::from_args("uvx", &args(&["other-server"])).unwrap();
+        assert_eq!(lockfile.locked_version("fetch", &other), None);
+    }
+}
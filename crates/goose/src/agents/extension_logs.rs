@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{LazyLock, OnceLock};
+
+use rmcp::model::LoggingMessageNotificationParam;
+
+use crate::config::extensions::name_to_key;
+use crate::config::paths::Paths;
+
+/// A log file is rotated once it grows past this size
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept per extension, as `<name>.log.1` (newest) to `<name>.log.3`
+const ROTATED_FILES: usize = 3;
+/// Entries waiting for the writer thread; more are dropped rather than blocking the caller
+const PENDING_ENTRIES: usize = 4096;
+
+static EXTENSION_LOGS: LazyLock<ExtensionLogs> =
+    LazyLock::new(|| ExtensionLogs::new(Paths::in_state_dir("logs").join("extensions")));
+
+/// Where a captured line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSource {
+    /// The stderr of a stdio extension process
+    Stderr,
+    /// An MCP `notifications/message` sent by the server
+    Server,
+}
+
+impl LogSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            LogSource::Stderr => "stderr",
+            LogSource::Server => "server",
+        }
+    }
+}
+
+struct LogEntry {
+    path: PathBuf,
+    timestamp: String,
+    source: LogSource,
+    text: String,
+}
+
+enum WriterCommand {
+    Write(LogEntry),
+    Flush(mpsc::Sender<()>),
+}
+
+/// Owns the log files on a thread of its own so callers on the async runtime never wait on
+/// file I/O
+struct LogWriter {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl LogWriter {
+    fn spawn(self) -> SyncSender<WriterCommand> {
+        let (sender, receiver) = mpsc::sync_channel(PENDING_ENTRIES);
+        std::thread::Builder::new()
+            .name("extension-logs".to_string())
+            .spawn(move || {
+                for command in receiver {
+                    match command {
+                        WriterCommand::Write(entry) => {
+                            if let Err(e) = self.write(&entry) {
+                                tracing::debug!(
+                                    "Failed to write extension log {}: {}",
+                                    entry.path.display(),
+                                    e
+                                );
+                            }
+                        }
+                        WriterCommand::Flush(done) => {
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn the extension log writer");
+        sender
+    }
+
+    fn write(&self, entry: &LogEntry) -> std::io::Result<()> {
+        let path = &entry.path;
+        fs::create_dir_all(&self.dir)?;
+        if fs::metadata(path).is_ok_and(|m| m.len() >= self.max_bytes) {
+            for index in (1..ROTATED_FILES).rev() {
+                let from = ExtensionLogs::rotated_path(path, index);
+                if from.exists() {
+                    fs::rename(&from, ExtensionLogs::rotated_path(path, index + 1))?;
+                }
+            }
+            fs::rename(path, ExtensionLogs::rotated_path(path, 1))?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        for line in entry.text.lines().filter(|line| !line.trim().is_empty()) {
+            writeln!(
+                file,
+                "{} [{}] {}",
+                entry.timestamp,
+                entry.source.as_str(),
+                line
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-extension rotating log files holding everything an extension wrote to stderr or
+/// sent as MCP logging notifications
+pub struct ExtensionLogs {
+    dir: PathBuf,
+    max_bytes: u64,
+    writer: OnceLock<SyncSender<WriterCommand>>,
+}
+
+impl ExtensionLogs {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            max_bytes: MAX_LOG_BYTES,
+            writer: OnceLock::new(),
+        }
+    }
+
+    pub fn global() -> &'static ExtensionLogs {
+        &EXTENSION_LOGS
+    }
+
+    pub fn path(&self, extension: &str) -> PathBuf {
+        self.dir.join(format!("{}.log", name_to_key(extension)))
+    }
+
+    fn rotated_path(path: &Path, index: usize) -> PathBuf {
+        PathBuf::from(format!("{}.{}", path.display(), index))
+    }
+
+    fn writer(&self) -> &SyncSender<WriterCommand> {
+        self.writer.get_or_init(|| {
+            LogWriter {
+                dir: self.dir.clone(),
+                max_bytes: self.max_bytes,
+            }
+            .spawn()
+        })
+    }
+
+    /// Queue text for an extension's log, one timestamped entry per line. Never blocks: when
+    /// the writer falls too far behind the text is dropped.
+    pub fn append(&self, extension: &str, source: LogSource, text: &str) {
+        let entry = LogEntry {
+            path: self.path(extension),
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            source,
+            text: text.to_string(),
+        };
+        if let Err(TrySendError::Full(_)) = self.writer().try_send(WriterCommand::Write(entry)) {
+            tracing::debug!("Extension log writer is behind, dropping a log entry");
+        }
+    }
+
+    /// Wait until everything queued so far is on disk
+    fn flush(&self) {
+        let Some(writer) = self.writer.get() else {
+            return;
+        };
+        let (done, wait) = mpsc::channel();
+        if writer.send(WriterCommand::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+
+    /// Record an MCP logging notification
+    pub fn append_notification(&self, extension: &str, params: &LoggingMessageNotificationParam) {
+        let level = format!("{:?}", params.level).to_lowercase();
+        let data = match &params.data {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let text = match &params.logger {
+            Some(logger) => format!("{} {}: {}", level, logger, data),
+            None => format!("{} {}", level, data),
+        };
+        self.append(extension, LogSource::Server, &text);
+    }
+
+    /// The last `lines` entries logged for an extension, oldest first
+    pub fn tail(&self, extension: &str, lines: usize) -> Vec<String> {
+        if lines == 0 {
+            return Vec::new();
+        }
+        self.flush();
+        let path = self.path(extension);
+        let files = std::iter::once(path.clone())
+            .chain((1..=ROTATED_FILES).map(|index| Self::rotated_path(&path, index)));
+
+        let mut tail = VecDeque::with_capacity(lines);
+        for file in files {
+            if tail.len() >= lines {
+                break;
+            }
+            let Ok(file) = fs::File::open(&file) else {
+                continue;
+            };
+            let mut file_lines: VecDeque<String> = VecDeque::new();
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                if file_lines.len() == lines - tail.len() {
+                    file_lines.pop_front();
+                }
+                file_lines.push_back(line);
+            }
+            // Older files come later, so their lines go in front
+            while let Some(line) = file_lines.pop_back() {
+                tail.push_front(line);
+            }
+        }
+        tail.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tails_across_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut logs = ExtensionLogs::new(dir.path().to_path_buf());
+        logs.max_bytes = 200;
+
+        for i in 0..20 {
+            logs.append("GitHub", LogSource::Stderr, &format!("line {}", i));
+        }
+        assert!(ExtensionLogs::rotated_path(&logs.path("github"), 1).exists());
+
+        let tail = logs.tail("github", 5);
+        assert_eq!(tail.len(), 5);
+        assert!(tail[0].ends_with("[stderr] line 15"));
+        assert!(tail[4].ends_with("[stderr] line 19"));
+        assert!(logs.tail("unknown", 5).is_empty());
+    }
+
+    #[test]
+    fn records_server_log_notifications() {
+        let dir = tempfile::tempdir().unwrap();
+        let logs = ExtensionLogs::new(dir.path().to_path_buf());
+        let params: LoggingMessageNotificationParam = serde_json::from_value(serde_json::json!({
+            "level": "error",
+            "logger": "auth",
+            "data": "token expired"
+        }))
+        .unwrap();
+        logs.append_notification("github", &params);
+
+        let tail = logs.tail("github", 50);
+        assert_eq!(tail.len(), 1);
+        assert!(tail[0].ends_with("[server] error auth: token expired"));
+    }
+}
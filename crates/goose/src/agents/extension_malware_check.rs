@@ -148,6 +148,17 @@ fn parse_pypi_token(token: &str) -> Option<(String, Option<String>)> {
         }
         return Some((name.to_string(), Some(ver.to_string())));
     }
+    // uvx also accepts `package@1.2.3`
+    if let Some((name, ver)) = token.split_once('@') {
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        if ver.is_empty() || ver.eq_ignore_ascii_case("latest") {
+            return Some((name.to_string(), None));
+        }
+        return Some((name.to_string(), Some(ver.to_string())));
+    }
     Some((token.to_string(), None))
 }
 
@@ -11,14 +11,14 @@ use rmcp::transport::streamable_http_client::{
 use rmcp::transport::{
     ConfigureCommandExt, DynamicTransportError, StreamableHttpClientTransport, TokioChildProcess,
 };
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tempfile::{tempdir, TempDir};
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio_stream::wrappers::ReceiverStream;
@@ -30,13 +30,21 @@ use super::extension::{
     ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, PlatformExtensionContext,
     ToolInfo, PLATFORM_EXTENSIONS,
 };
+use super::lazy_extension::{
+    supports_lazy_startup, ExtensionStartup, LazyClient, StartedClient, Starter, ToolManifest,
+    PREWARM_AFTER_ACTIVATIONS,
+};
+use super::tool_cache::ToolResultCache;
 use super::tool_execution::ToolCallResult;
 use super::types::SharedProvider;
 use crate::agents::extension::{Envs, ProcessExit};
+use crate::agents::extension_capabilities::CapabilityManifest;
+use crate::agents::extension_lock;
+use crate::agents::extension_logs::{ExtensionLogs, LogSource};
 use crate::agents::extension_malware_check;
 use crate::agents::mcp_client::{GooseMcpClientCapabilities, McpClient, McpClientTrait};
 use crate::builtin_extension::get_builtin_extension;
-use crate::config::extensions::name_to_key;
+use crate::config::extensions::{name_to_key, resolve_for_launch};
 use crate::config::search_path::SearchPaths;
 use crate::config::{get_all_extensions, Config};
 use crate::oauth::oauth_flow;
@@ -44,7 +52,7 @@ use crate::prompt_template;
 use crate::subprocess::configure_subprocess;
 use rmcp::model::{
     CallToolRequestParams, Content, ErrorCode, ErrorData, GetPromptResult, Prompt, Resource,
-    ResourceContents, ServerInfo, Tool,
+    ResourceContents, ServerInfo, ServerNotification, Tool,
 };
 use rmcp::transport::auth::AuthClient;
 use schemars::_private::NoSerialize;
@@ -64,6 +72,15 @@ struct Extension {
     client: McpClientBox,
     server_info: Option<ServerInfo>,
     _temp_dir: Option<tempfile::TempDir>,
+    launch: LaunchContext,
+}
+
+/// What an extension was started with, so it can be started again after a crash
+#[derive(Clone, Default)]
+struct LaunchContext {
+    working_dir: Option<PathBuf>,
+    container: Option<Container>,
+    session_id: Option<String>,
 }
 
 impl Extension {
@@ -78,6 +95,23 @@ impl Extension {
             config,
             server_info,
             _temp_dir: temp_dir,
+            launch: LaunchContext::default(),
+        }
+    }
+
+    fn with_launch(mut self, launch: LaunchContext) -> Self {
+        self.launch = launch;
+        self
+    }
+
+    /// In-process platform extensions share the agent's lifetime and cannot crash separately
+    fn is_restartable(&self) -> bool {
+        match &self.config {
+            ExtensionConfig::Platform { .. } | ExtensionConfig::Frontend { .. } => false,
+            ExtensionConfig::Builtin { name, .. } => {
+                !PLATFORM_EXTENSIONS.contains_key(name_to_key(name).as_str())
+            }
+            _ => true,
         }
     }
 
@@ -88,6 +122,23 @@ impl Extension {
             .is_some()
     }
 
+    fn supports_resource_subscriptions(&self) -> bool {
+        self.server_info
+            .as_ref()
+            .and_then(|info| info.capabilities.resources.as_ref())
+            .and_then(|resources| resources.subscribe)
+            .unwrap_or(false)
+    }
+
+    /// The manifest the user declared for this extension, or else the one its server declared
+    fn capability_manifest(&self) -> Option<CapabilityManifest> {
+        CapabilityManifest::for_extension(&self.config.key()).or_else(|| {
+            self.server_info
+                .as_ref()
+                .and_then(CapabilityManifest::from_server_info)
+        })
+    }
+
     fn get_instructions(&self) -> Option<String> {
         self.server_info
             .as_ref()
@@ -112,8 +163,112 @@ pub struct ExtensionManager {
     tools_cache_version: AtomicU64,
     client_name: String,
     capabilities: ExtensionManagerCapabilities,
+    /// Extension name -> subscribed resource URIs
+    resource_subscriptions: Mutex<HashMap<String, HashSet<String>>>,
+    /// (extension name, uri) of subscribed resources that changed since they were last read
+    resource_updates: Arc<Mutex<BTreeSet<(String, String)>>>,
+    /// Extensions that reported a changed tool list the model has not been told about yet
+    tool_list_changes: Mutex<BTreeSet<String>>,
+    /// Extensions whose transport failed during a tool call, awaiting a health check
+    suspect_extensions: Arc<std::sync::Mutex<HashSet<String>>>,
+    restart_counts: Mutex<HashMap<String, u32>>,
+    tool_results: Arc<ToolResultCache>,
+    /// Unprefixed tool names claimed by more than one extension, from the last tool listing
+    tool_conflicts: std::sync::Mutex<Vec<ToolConflict>>,
+    /// Tool sets saved with `snapshot_tool_set`, by name
+    tool_sets: Mutex<HashMap<String, ToolSetSnapshot>>,
+}
+
+/// Several extensions offered a tool under the same unprefixed name. The winner keeps the
+/// name; the others are only reachable as `<extension>__<tool>`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, utoipa::ToSchema)]
+pub struct ToolConflict {
+    pub tool: String,
+    pub extensions: Vec<String>,
+    pub winner: String,
+    /// Whether the winner was chosen in `GOOSE_TOOL_ALIASES`
+    pub aliased: bool,
+}
+
+impl std::fmt::Display for ToolConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Tool '{}' is provided by {}; '{}' is used{}",
+            self.tool,
+            self.extensions.join(", "),
+            self.winner,
+            if self.aliased {
+                ""
+            } else {
+                " (set GOOSE_TOOL_ALIASES to choose)"
+            }
+        )
+    }
 }
 
+/// The extensions a manager runs and the tools they offer, to switch back to later
+#[derive(Debug, Clone, Default)]
+pub struct ToolSetSnapshot {
+    extensions: Vec<ExtensionConfig>,
+    tools: Vec<String>,
+}
+
+impl ToolSetSnapshot {
+    /// A tool set of the given extensions, for one that was never running
+    pub fn new(extensions: Vec<ExtensionConfig>) -> Self {
+        Self {
+            extensions,
+            tools: Vec::new(),
+        }
+    }
+
+    pub fn extensions(&self) -> &[ExtensionConfig] {
+        &self.extensions
+    }
+
+    /// Prefixed names of the tools offered when the snapshot was taken. Empty for tool sets
+    /// made with `new`.
+    pub fn tools(&self) -> &[String] {
+        &self.tools
+    }
+}
+
+/// Outcome of checking an extension that failed during a tool call
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExtensionHealthEvent {
+    Restarted { name: String },
+    RestartFailed { name: String, error: String },
+    RestartLimitReached { name: String, restarts: u32 },
+}
+
+impl std::fmt::Display for ExtensionHealthEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Restarted { name } => {
+                write!(
+                    f,
+                    "Extension '{}' stopped responding and was restarted",
+                    name
+                )
+            }
+            Self::RestartFailed { name, error } => write!(
+                f,
+                "Extension '{}' stopped responding and could not be restarted: {}",
+                name, error
+            ),
+            Self::RestartLimitReached { name, restarts } => write!(
+                f,
+                "Extension '{}' stopped responding again after {} restarts and was disabled",
+                name, restarts
+            ),
+        }
+    }
+}
+
+const HEALTH_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_EXTENSION_RESTARTS: u32 = 3;
+
 /// A flattened representation of a resource used by the agent to prepare inference
 #[derive(Debug, Clone)]
 pub struct ResourceItem {
@@ -147,6 +302,26 @@ impl ResourceItem {
     }
 }
 
+/// Roots offered to MCP servers: the working directory, then `GOOSE_MCP_ROOTS`
+fn workspace_roots(working_dir: Option<&Path>) -> Vec<PathBuf> {
+    let working_dir = working_dir
+        .map(Path::to_path_buf)
+        .or_else(|| std::env::current_dir().ok());
+    let additional = Config::global()
+        .get_param::<Vec<String>>("GOOSE_MCP_ROOTS")
+        .unwrap_or_default()
+        .into_iter()
+        .map(|root| PathBuf::from(shellexpand::tilde(&root).into_owned()));
+
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for root in working_dir.into_iter().chain(additional) {
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
+
 fn resolve_command(cmd: &str) -> PathBuf {
     SearchPaths::builder()
         .with_npm()
@@ -196,6 +371,23 @@ pub fn get_tool_owner(tool: &Tool) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Whether calling a tool can change state outside the conversation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolEffect {
+    ReadOnly,
+    Mutating,
+}
+
+impl ToolEffect {
+    /// Only tools annotated with `readOnlyHint: true` are read-only; anything else may mutate.
+    pub fn of(tool: &Tool) -> Self {
+        match tool.annotations.as_ref().and_then(|a| a.read_only_hint) {
+            Some(true) => ToolEffect::ReadOnly,
+            _ => ToolEffect::Mutating,
+        }
+    }
+}
+
 fn is_unprefixed_extension(config: &ExtensionConfig) -> bool {
     match config {
         ExtensionConfig::Platform { name, .. } | ExtensionConfig::Builtin { name, .. } => {
@@ -215,6 +407,71 @@ pub fn is_first_class_extension(name: &str) -> bool {
         .is_some_and(|def| def.unprefixed_tools)
 }
 
+/// Give every tool its public name. Tools are prefixed with their extension name unless the
+/// extension exposes them unprefixed or `aliases` (unprefixed name -> extension) selects them.
+/// When several extensions claim the same unprefixed name, the aliased extension wins, then
+/// the first by name; the others fall back to their prefixed name.
+fn assign_tool_names(
+    mut results: Vec<(String, Vec<(Tool, bool)>)>,
+    aliases: &HashMap<String, String>,
+) -> (Vec<Tool>, Vec<ToolConflict>) {
+    // Listing order comes from a HashMap; sort so names and conflict winners are stable
+    results.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut claims: HashMap<String, Vec<String>> = HashMap::new();
+    for (extension, tools) in &results {
+        for (tool, unprefixed) in tools {
+            let aliased = aliases.get(tool.name.as_ref()) == Some(extension);
+            if *unprefixed || aliased {
+                claims
+                    .entry(tool.name.to_string())
+                    .or_default()
+                    .push(extension.clone());
+            }
+        }
+    }
+
+    let mut conflicts = Vec::new();
+    let mut owners: HashMap<String, String> = HashMap::new();
+    for (name, extensions) in claims {
+        let aliased = aliases.get(&name).filter(|ext| extensions.contains(ext));
+        let winner = aliased.unwrap_or(&extensions[0]).clone();
+        if extensions.len() > 1 {
+            conflicts.push(ToolConflict {
+                tool: name.clone(),
+                extensions: extensions.clone(),
+                winner: winner.clone(),
+                aliased: aliased.is_some(),
+            });
+        }
+        owners.insert(name, winner);
+    }
+    conflicts.sort_by(|a, b| a.tool.cmp(&b.tool));
+
+    let mut seen_names = HashSet::new();
+    let mut assigned = Vec::new();
+    for (extension, tools) in results {
+        for (mut tool, _) in tools {
+            let public_name = if owners.get(tool.name.as_ref()) == Some(&extension) {
+                tool.name.to_string()
+            } else {
+                format!("{}__{}", extension, tool.name)
+            };
+            if !seen_names.insert(public_name.clone()) {
+                warn!(
+                    tool = %public_name,
+                    extension = %extension,
+                    "Duplicate tool name - skipping"
+                );
+                continue;
+            }
+            tool.name = public_name.into();
+            assigned.push(tool);
+        }
+    }
+    (assigned, conflicts)
+}
+
 /// Result of resolving a tool call to its owning extension
 struct ResolvedTool {
     extension_name: String,
@@ -222,7 +479,40 @@ struct ResolvedTool {
     client: McpClientBox,
 }
 
+/// How much of a process's stderr is kept to explain a failed start
+const STDERR_TAIL_BYTES: usize = 64 * 1024;
+
+/// The most recent stderr lines of a process, holding at most `STDERR_TAIL_BYTES`
+#[derive(Default)]
+struct StderrTail {
+    lines: VecDeque<String>,
+    bytes: usize,
+}
+
+impl StderrTail {
+    fn push(&mut self, line: String) {
+        self.bytes += line.len() + 1;
+        self.lines.push_back(line);
+        while self.bytes > STDERR_TAIL_BYTES {
+            let Some(dropped) = self.lines.pop_front() else {
+                break;
+            };
+            self.bytes -= dropped.len() + 1;
+        }
+    }
+
+    fn into_string(self) -> String {
+        self.lines.into_iter().fold(String::new(), |mut out, line| {
+            out.push_str(&line);
+            out.push('\n');
+            out
+        })
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn child_process_client(
+    extension_name: String,
     mut command: Command,
     timeout: &Option<u64>,
     provider: SharedProvider,
@@ -257,14 +547,19 @@ async fn child_process_client(
     let (transport, mut stderr) = TokioChildProcess::builder(command)
         .stderr(Stdio::piped())
         .spawn()?;
-    let mut stderr = stderr.take().ok_or_else(|| {
+    let stderr = stderr.take().ok_or_else(|| {
         ExtensionError::SetupError("failed to attach child process stderr".to_owned())
     })?;
 
+    // Everything the process writes to stderr goes to its extension log
     let stderr_task = tokio::spawn(async move {
-        let mut all_stderr = Vec::new();
-        stderr.read_to_end(&mut all_stderr).await?;
-        Ok::<String, std::io::Error>(String::from_utf8_lossy(&all_stderr).into())
+        let mut tail = StderrTail::default();
+        let mut lines = BufReader::new(stderr).lines();
+        while let Some(line) = lines.next_line().await? {
+            ExtensionLogs::global().append(&extension_name, LogSource::Stderr, &line);
+            tail.push(line);
+        }
+        Ok::<String, std::io::Error>(tail.into_string())
     });
 
     let client_result = McpClient::connect_with_container(
@@ -319,11 +614,26 @@ pub(crate) async fn merge_environments(
     config: &Config,
 ) -> Result<HashMap<String, String>, ExtensionError> {
     let mut all_envs = envs.get_env();
+    // A user-declared manifest limits which credentials the extension is handed
+    let manifest = CapabilityManifest::for_extension(ext_name);
+    let undeclared = |key: &str| {
+        manifest
+            .as_ref()
+            .is_some_and(|manifest| !manifest.allows_credential(key))
+    };
 
     for key in env_keys {
         if all_envs.contains_key(key) {
             continue;
         }
+        if undeclared(key) {
+            warn!(
+                key = %key,
+                ext_name = %ext_name,
+                "Credential not declared in the extension's capability manifest; withholding it."
+            );
+            continue;
+        }
 
         match config.get(key, true) {
             Ok(value) => {
@@ -362,6 +672,46 @@ pub(crate) async fn merge_environments(
         }
     }
 
+    let withheld: Vec<String> = all_envs
+        .iter()
+        .filter(|(key, value)| {
+            Envs::keyring_reference(value)
+                .is_some_and(|secret| undeclared(key) && undeclared(secret))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in withheld {
+        warn!(
+            key = %key,
+            ext_name = %ext_name,
+            "Credential not declared in the extension's capability manifest; withholding it."
+        );
+        all_envs.remove(&key);
+    }
+
+    // `keyring:` references are resolved here, so secrets only live in the process env
+    for (key, value) in all_envs.iter_mut() {
+        let Some(secret) = Envs::keyring_reference(value) else {
+            continue;
+        };
+        match config.get_secret::<String>(secret) {
+            Ok(resolved) => *value = resolved,
+            Err(e) => {
+                error!(
+                    key = %key,
+                    secret = %secret,
+                    ext_name = %ext_name,
+                    error = %e,
+                    "Failed to resolve keyring secret."
+                );
+                return Err(ExtensionError::ConfigError(format!(
+                    "Failed to resolve keyring secret '{}' for {}: {}",
+                    secret, key, e
+                )));
+            }
+        }
+    }
+
     Ok(all_envs)
 }
 
@@ -496,6 +846,14 @@ impl ExtensionManager {
             tools_cache_version: AtomicU64::new(0),
             client_name,
             capabilities,
+            resource_subscriptions: Mutex::new(HashMap::new()),
+            resource_updates: Arc::new(Mutex::new(BTreeSet::new())),
+            tool_list_changes: Mutex::new(BTreeSet::new()),
+            suspect_extensions: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            restart_counts: Mutex::new(HashMap::new()),
+            tool_results: Arc::new(ToolResultCache::from_config()),
+            tool_conflicts: std::sync::Mutex::new(Vec::new()),
+            tool_sets: Mutex::new(HashMap::new()),
         }
     }
 
@@ -510,6 +868,32 @@ impl ExtensionManager {
         )
     }
 
+    fn client_capabilities(&self, working_dir: Option<&Path>) -> GooseMcpClientCapabilities {
+        GooseMcpClientCapabilities {
+            mcpui: self.capabilities.mcpui,
+            roots: workspace_roots(working_dir),
+        }
+    }
+
+    /// Point extensions at a new working directory. Servers are sent `roots/list_changed`
+    /// and restarts use the new directory; running processes keep their current directory.
+    pub async fn update_workspace_roots(&self, working_dir: &Path) {
+        let mut changed = Vec::new();
+        for extension in self.extensions.lock().await.values_mut() {
+            if extension.launch.working_dir.as_deref() != Some(working_dir) {
+                extension.launch.working_dir = Some(working_dir.to_path_buf());
+                changed.push(extension.get_client());
+            }
+        }
+
+        let roots = workspace_roots(Some(working_dir));
+        for client in changed {
+            if let Err(e) = client.update_roots(roots.clone()).await {
+                warn!("Failed to update extension roots: {}", e);
+            }
+        }
+    }
+
     pub fn get_context(&self) -> &PlatformExtensionContext {
         &self.context
     }
@@ -526,6 +910,14 @@ impl ExtensionManager {
             .any(|ext| ext.supports_resources())
     }
 
+    pub async fn supports_resource_subscriptions(&self) -> bool {
+        self.extensions
+            .lock()
+            .await
+            .values()
+            .any(|ext| ext.supports_resource_subscriptions())
+    }
+
     /// Add an extension with an optional working directory.
     /// If working_dir is None, falls back to current_dir.
     #[allow(clippy::too_many_lines)]
@@ -548,9 +940,109 @@ impl ExtensionManager {
             );
         }
 
+        let launch = LaunchContext {
+            working_dir,
+            container: container.cloned(),
+            session_id: session_id.map(str::to_string),
+        };
+
+        let (client, temp_dir) = match self.lazy_client(&config, &launch) {
+            Some(client) => (client, None),
+            None => {
+                let (client, temp_dir) = self.start_client(&config, &launch).await?;
+                if ExtensionStartup::from_config() == ExtensionStartup::Lazy
+                    && supports_lazy_startup(&config)
+                {
+                    // Remember the tools so the next session can start this extension lazily
+                    let config = config.clone();
+                    let client = Arc::clone(&client);
+                    let session_id = launch.session_id.clone().unwrap_or_default();
+                    tokio::spawn(async move {
+                        if let Err(e) =
+                            ToolManifest::record(&config, client.as_ref(), &session_id).await
+                        {
+                            warn!("Failed to record tools of {}: {}", config.name(), e);
+                        }
+                    });
+                }
+                (client, temp_dir)
+            }
+        };
+        let server_info = client.get_info().cloned();
+
+        let mut extensions = self.extensions.lock().await;
+        extensions.insert(
+            sanitized_name,
+            Extension::new(config, client, server_info, temp_dir).with_launch(launch),
+        );
+        drop(extensions);
+        self.invalidate_tools_cache_and_bump_version().await;
+
+        Ok(())
+    }
+
+    /// In lazy startup mode, stand in for an extension with the tools it advertised last time
+    /// and start its server on first use. Returns None when the extension must start now.
+    fn lazy_client(
+        self: &Arc<Self>,
+        config: &ExtensionConfig,
+        launch: &LaunchContext,
+    ) -> Option<McpClientBox> {
+        if ExtensionStartup::from_config() != ExtensionStartup::Lazy
+            || !supports_lazy_startup(config)
+        {
+            return None;
+        }
+        let manifest = ToolManifest::load(config)?;
+
+        let manager = Arc::downgrade(self);
+        let starter_config = config.clone();
+        let starter_launch = launch.clone();
+        let starter: Starter = Box::new(move || {
+            let manager = manager.clone();
+            let config = starter_config.clone();
+            let launch = starter_launch.clone();
+            Box::pin(async move {
+                let manager = manager.upgrade().ok_or_else(|| {
+                    ExtensionError::SetupError("Extension manager is gone".to_string())
+                })?;
+                tracing::info!("Starting extension {} on first use", config.name());
+                let started = manager.start_client(&config, &launch).await?;
+                let session_id = launch.session_id.clone().unwrap_or_default();
+                if let Err(e) = ToolManifest::record(&config, started.0.as_ref(), &session_id).await
+                {
+                    warn!("Failed to record tools of {}: {}", config.name(), e);
+                }
+                Ok(started)
+            })
+        });
+
+        let client = Arc::new(LazyClient::new(config.name(), manifest, starter));
+        if client.activations() >= PREWARM_AFTER_ACTIVATIONS {
+            let prewarm = Arc::clone(&client);
+            tokio::spawn(async move {
+                let _ = prewarm.start().await;
+            });
+        }
+        Some(client)
+    }
+
+    /// Start the server or in-process client for an extension
+    async fn start_client(
+        self: &Arc<Self>,
+        config: &ExtensionConfig,
+        launch: &LaunchContext,
+    ) -> ExtensionResult<StartedClient> {
+        let config =
+            &resolve_for_launch(config).map_err(|e| ExtensionError::ConfigError(e.to_string()))?;
+        let sanitized_name = config.key();
+        let working_dir = launch.working_dir.clone();
+        let container = launch.container.as_ref();
+        let session_id = launch.session_id.as_deref();
+
         let mut temp_dir = None;
 
-        let client: Box<dyn McpClientTrait> = match &config {
+        let client: Box<dyn McpClientTrait> = match config {
             ExtensionConfig::Sse { .. } => {
                 return Err(ExtensionError::ConfigError(
                     "SSE is unsupported, migrate to streamable_http".to_string(),
@@ -571,9 +1063,7 @@ impl ExtensionManager {
                     .iter()
                     .map(|(k, v)| (k.clone(), substitute_env_vars(v, &all_envs)))
                     .collect();
-                let capability = GooseMcpClientCapabilities {
-                    mcpui: self.capabilities.mcpui,
-                };
+                let capability = self.client_capabilities(working_dir.as_deref());
 
                 create_streamable_http_client(
                     uri,
@@ -588,7 +1078,7 @@ impl ExtensionManager {
             }
             ExtensionConfig::Builtin { ref name, .. }
             | ExtensionConfig::Platform { ref name, .. } => {
-                let timeout = if let ExtensionConfig::Builtin { timeout, .. } = &config {
+                let timeout = if let ExtensionConfig::Builtin { timeout, .. } = config {
                     *timeout
                 } else {
                     None
@@ -636,11 +1126,10 @@ impl ExtensionManager {
                             .clone()
                             .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-                        let capabilities = GooseMcpClientCapabilities {
-                            mcpui: self.capabilities.mcpui,
-                        };
+                        let capabilities = self.client_capabilities(working_dir.as_deref());
 
                         let client = child_process_client(
+                            sanitized_name.clone(),
                             command,
                             &Some(timeout_secs),
                             self.provider.clone(),
@@ -656,9 +1145,7 @@ impl ExtensionManager {
                         let (client_read, server_write) = tokio::io::duplex(65536);
                         extension_fn(server_read, server_write);
 
-                        let capabilities = GooseMcpClientCapabilities {
-                            mcpui: self.capabilities.mcpui,
-                        };
+                        let capabilities = self.client_capabilities(working_dir.as_deref());
 
                         Box::new(
                             McpClient::connect(
@@ -689,6 +1176,9 @@ impl ExtensionManager {
                     all_envs.insert("AGENT_SESSION_ID".to_string(), sid.to_string());
                 }
 
+                // Run the locked package version so launches are reproducible
+                let args = &extension_lock::pin_launch_args(&sanitized_name, cmd, args).await;
+
                 // Check for malicious packages before launching the process
                 extension_malware_check::deny_if_malicious_cmd_args(cmd, args).await?;
 
@@ -710,7 +1200,11 @@ impl ExtensionManager {
                     })
                 } else {
                     let cmd = resolve_command(cmd);
+                    let inherited = envs.inherited_host_env();
                     Command::new(cmd).configure(|command| {
+                        if let Some(inherited) = inherited {
+                            command.env_clear().envs(inherited);
+                        }
                         command.args(args).envs(all_envs);
                     })
                 };
@@ -718,10 +1212,9 @@ impl ExtensionManager {
                 let effective_working_dir = working_dir
                     .clone()
                     .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
-                let capabilities = GooseMcpClientCapabilities {
-                    mcpui: self.capabilities.mcpui,
-                };
+                let capabilities = self.client_capabilities(working_dir.as_deref());
                 let client = child_process_client(
+                    sanitized_name.clone(),
                     command,
                     timeout,
                     self.provider.clone(),
@@ -758,11 +1251,10 @@ impl ExtensionManager {
                     .clone()
                     .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
 
-                let capabilities = GooseMcpClientCapabilities {
-                    mcpui: self.capabilities.mcpui,
-                };
+                let capabilities = self.client_capabilities(working_dir.as_deref());
 
                 let client = child_process_client(
+                    sanitized_name.clone(),
                     command,
                     timeout,
                     self.provider.clone(),
@@ -782,17 +1274,10 @@ impl ExtensionManager {
             }
         };
 
-        let server_info = client.get_info().cloned();
-
-        let mut extensions = self.extensions.lock().await;
-        extensions.insert(
-            sanitized_name,
-            Extension::new(config, Arc::from(client), server_info, temp_dir),
-        );
-        drop(extensions);
-        self.invalidate_tools_cache_and_bump_version().await;
-
-        Ok(())
+        let client: McpClientBox = Arc::from(client);
+        self.listen_for_server_notifications(config.key(), &client)
+            .await;
+        Ok((client, temp_dir))
     }
 
     pub async fn add_client(
@@ -830,10 +1315,128 @@ impl ExtensionManager {
     pub async fn remove_extension(&self, name: &str) -> ExtensionResult<()> {
         let sanitized_name = name_to_key(name);
         self.extensions.lock().await.remove(&sanitized_name);
+        self.resource_subscriptions
+            .lock()
+            .await
+            .remove(&sanitized_name);
+        self.resource_updates
+            .lock()
+            .await
+            .retain(|(extension, _)| extension != &sanitized_name);
+        self.tool_list_changes.lock().await.remove(&sanitized_name);
+        self.tool_results.clear();
         self.invalidate_tools_cache_and_bump_version().await;
         Ok(())
     }
 
+    /// Stop every extension. Dropping the clients closes their transports, which ends the
+    /// servers' child processes.
+    pub async fn shutdown(&self) {
+        let extensions = std::mem::take(&mut *self.extensions.lock().await);
+        if extensions.is_empty() {
+            return;
+        }
+        tracing::debug!("Stopping {} extensions", extensions.len());
+        drop(extensions);
+        self.resource_subscriptions.lock().await.clear();
+        self.invalidate_tools_cache_and_bump_version().await;
+    }
+
+    /// Probe an extension by listing its tools
+    async fn is_responsive(&self, session_id: &str, name: &str) -> bool {
+        let Some(client) = self.get_server_client(name).await else {
+            return false;
+        };
+        matches!(
+            tokio::time::timeout(
+                HEALTH_PROBE_TIMEOUT,
+                client.list_tools(session_id, None, CancellationToken::default()),
+            )
+            .await,
+            Ok(Ok(_))
+        )
+    }
+
+    /// Stop and start an extension again with the configuration it was launched with
+    pub async fn restart_extension(self: &Arc<Self>, name: &str) -> ExtensionResult<()> {
+        let key = name_to_key(name);
+        let (config, launch) = {
+            let mut extensions = self.extensions.lock().await;
+            let extension = extensions.remove(&key).ok_or_else(|| {
+                ExtensionError::ConfigError(format!("Unknown extension: {}", name))
+            })?;
+            (extension.config.clone(), extension.launch.clone())
+        };
+        self.invalidate_tools_cache_and_bump_version().await;
+        self.resource_subscriptions.lock().await.remove(&key);
+
+        self.add_extension(
+            config,
+            launch.working_dir,
+            launch.container.as_ref(),
+            launch.session_id.as_deref(),
+        )
+        .await
+    }
+
+    /// Check extensions whose transport failed during a tool call and restart the ones that
+    /// are no longer responding, up to `GOOSE_EXTENSION_MAX_RESTARTS` times each.
+    pub async fn recover_unresponsive_extensions(
+        self: &Arc<Self>,
+        session_id: &str,
+    ) -> Vec<ExtensionHealthEvent> {
+        let suspects: Vec<String> = self
+            .suspect_extensions
+            .lock()
+            .map(|mut suspects| suspects.drain().collect())
+            .unwrap_or_default();
+        let max_restarts = Config::global()
+            .get_param::<u32>("GOOSE_EXTENSION_MAX_RESTARTS")
+            .unwrap_or(DEFAULT_MAX_EXTENSION_RESTARTS);
+
+        let mut events = Vec::new();
+        for name in suspects {
+            let restartable = self
+                .extensions
+                .lock()
+                .await
+                .get(&name)
+                .is_some_and(|ext| ext.is_restartable());
+            if !restartable || self.is_responsive(session_id, &name).await {
+                continue;
+            }
+
+            let restarts = {
+                let mut counts = self.restart_counts.lock().await;
+                let count = counts.entry(name.clone()).or_insert(0);
+                *count += 1;
+                *count
+            };
+            if restarts > max_restarts {
+                warn!("Extension {} exceeded {} restarts", name, max_restarts);
+                let _ = self.remove_extension(&name).await;
+                events.push(ExtensionHealthEvent::RestartLimitReached {
+                    name,
+                    restarts: max_restarts,
+                });
+                continue;
+            }
+
+            tracing::info!("Restarting unresponsive extension {} ({})", name, restarts);
+            match self.restart_extension(&name).await {
+                Ok(()) => events.push(ExtensionHealthEvent::Restarted { name }),
+                Err(e) => {
+                    error!("Failed to restart extension {}: {}", name, e);
+                    events.push(ExtensionHealthEvent::RestartFailed {
+                        name,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+        events
+    }
+
     pub async fn get_extension_and_tool_counts(&self, session_id: &str) -> (usize, usize) {
         let enabled_extensions_count = self.extensions.lock().await.len();
 
@@ -864,6 +1467,89 @@ impl ExtensionManager {
             .collect()
     }
 
+    /// Capture the running extensions and the tools they offer, and save them as `name`
+    pub async fn snapshot_tool_set(
+        &self,
+        session_id: &str,
+        name: &str,
+    ) -> ExtensionResult<ToolSetSnapshot> {
+        let mut extensions = self.get_extension_configs().await;
+        extensions.sort_by_key(|config| config.key());
+        let tools = self
+            .get_prefixed_tools(session_id, None)
+            .await?
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        let snapshot = ToolSetSnapshot { extensions, tools };
+        self.tool_sets
+            .lock()
+            .await
+            .insert(name.to_string(), snapshot.clone());
+        Ok(snapshot)
+    }
+
+    pub async fn tool_set(&self, name: &str) -> Option<ToolSetSnapshot> {
+        self.tool_sets.lock().await.get(name).cloned()
+    }
+
+    pub async fn remove_tool_set(&self, name: &str) -> Option<ToolSetSnapshot> {
+        self.tool_sets.lock().await.remove(name)
+    }
+
+    /// Run exactly the extensions of `snapshot`: the others are stopped, and missing or
+    /// reconfigured ones are started. Extensions that fail to start don't stop the rest and
+    /// are reported together.
+    pub async fn apply_tool_set(
+        self: &Arc<Self>,
+        snapshot: &ToolSetSnapshot,
+        working_dir: Option<PathBuf>,
+        container: Option<&Container>,
+        session_id: Option<&str>,
+    ) -> ExtensionResult<()> {
+        let wanted: HashSet<String> = snapshot.extensions.iter().map(|c| c.key()).collect();
+        for name in self.list_extensions().await? {
+            if !wanted.contains(&name) {
+                self.remove_extension(&name).await?;
+            }
+        }
+
+        let mut failed = Vec::new();
+        for config in &snapshot.extensions {
+            if let Err(e) = self
+                .add_extension(config.clone(), working_dir.clone(), container, session_id)
+                .await
+            {
+                warn!(extension = config.key(), "Failed to start extension: {}", e);
+                failed.push(format!("{}: {}", config.name(), e));
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(ExtensionError::SetupError(format!(
+                "Failed to start extensions: {}",
+                failed.join("; ")
+            )))
+        }
+    }
+
+    /// Switch back to the tool set saved as `name`
+    pub async fn restore_tool_set(
+        self: &Arc<Self>,
+        name: &str,
+        working_dir: Option<PathBuf>,
+        container: Option<&Container>,
+        session_id: Option<&str>,
+    ) -> ExtensionResult<()> {
+        let snapshot = self
+            .tool_set(name)
+            .await
+            .ok_or_else(|| ExtensionError::ConfigError(format!("Unknown tool set: {}", name)))?;
+        self.apply_tool_set(&snapshot, working_dir, container, session_id)
+            .await
+    }
+
     /// Get all tools from all clients with proper prefixing
     pub async fn get_prefixed_tools(
         &self,
@@ -874,6 +1560,34 @@ impl ExtensionManager {
         Ok(self.filter_tools(&all_tools, extension_name.as_deref(), None))
     }
 
+    /// Classify a tool as read-only or mutating. Unknown tools are treated as mutating.
+    pub async fn classify_tool(&self, session_id: &str, tool_name: &str) -> ToolEffect {
+        match self.get_all_tools_cached(session_id).await {
+            Ok(tools) => tools
+                .iter()
+                .find(|tool| tool.name == tool_name)
+                .map(ToolEffect::of)
+                .unwrap_or(ToolEffect::Mutating),
+            Err(_) => ToolEffect::Mutating,
+        }
+    }
+
+    pub async fn capability_manifest(&self, name: &str) -> Option<CapabilityManifest> {
+        self.extensions
+            .lock()
+            .await
+            .get(&name_to_key(name))
+            .and_then(Extension::capability_manifest)
+    }
+
+    /// Name of the extension that provides a tool
+    pub async fn tool_owner(&self, session_id: &str, tool_name: &str) -> Option<String> {
+        self.resolve_tool(session_id, tool_name)
+            .await
+            .ok()
+            .map(|resolved| resolved.extension_name)
+    }
+
     pub async fn get_prefixed_tools_excluding(
         &self,
         session_id: &str,
@@ -937,6 +1651,11 @@ impl ExtensionManager {
         Ok(tools)
     }
 
+    /// Changes whenever extensions are added or removed
+    pub fn tools_version(&self) -> u64 {
+        self.tools_cache_version.load(Ordering::SeqCst)
+    }
+
     async fn invalidate_tools_cache_and_bump_version(&self) {
         self.tools_cache_version.fetch_add(1, Ordering::SeqCst);
         *self.tools_cache.lock().await = None;
@@ -973,12 +1692,6 @@ impl ExtensionManager {
                 loop {
                     for mut tool in client_tools.tools {
                         if config.is_tool_available(&tool.name) {
-                            let public_name = if expose_unprefixed {
-                                tool.name.to_string()
-                            } else {
-                                format!("{}__{}", name, tool.name)
-                            };
-
                             let mut meta_map = tool
                                 .meta
                                 .as_ref()
@@ -989,10 +1702,9 @@ impl ExtensionManager {
                                 serde_json::Value::String(name.clone()),
                             );
 
-                            tool.name = public_name.into();
                             tool.meta = Some(rmcp::model::Meta(meta_map));
 
-                            tools.push(tool);
+                            tools.push((tool, expose_unprefixed));
                         }
                     }
 
@@ -1017,28 +1729,31 @@ impl ExtensionManager {
         });
 
         let results = future::join_all(client_futures).await;
-
-        let mut seen_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-        let mut tools = Vec::new();
-        for (ext_name, client_tools) in results {
-            for tool in client_tools {
-                let tool_name = tool.name.to_string();
-                if seen_names.contains(&tool_name) {
-                    warn!(
-                        tool = %tool_name,
-                        extension = %ext_name,
-                        "Duplicate tool name - skipping"
-                    );
-                    continue;
-                }
-                seen_names.insert(tool_name);
-                tools.push(tool);
-            }
+        let aliases = Config::global()
+            .get_param::<HashMap<String, String>>("GOOSE_TOOL_ALIASES")
+            .unwrap_or_default();
+        let (tools, conflicts) = assign_tool_names(results, &aliases);
+        for conflict in &conflicts {
+            warn!("{}", conflict);
+        }
+        if let Ok(mut stored) = self.tool_conflicts.lock() {
+            *stored = conflicts;
         }
 
         Ok(tools)
     }
 
+    /// Tool names that more than one extension wanted to expose unprefixed
+    pub async fn tool_conflicts(&self, session_id: &str) -> Vec<ToolConflict> {
+        if let Err(e) = self.get_all_tools_cached(session_id).await {
+            warn!("Failed to list tools: {}", e);
+        }
+        self.tool_conflicts
+            .lock()
+            .map(|conflicts| conflicts.clone())
+            .unwrap_or_default()
+    }
+
     /// Get the extension prompt including client instructions
     pub async fn get_planning_prompt(&self, tools_info: Vec<ToolInfo>) -> String {
         let mut context: HashMap<&str, Value> = HashMap::new();
@@ -1152,16 +1867,179 @@ impl ExtensionManager {
             .await
             .ok_or(ErrorData::new(ErrorCode::INVALID_PARAMS, error_msg, None))?;
 
+        let result = client
+            .read_resource(session_id, uri, cancellation_token)
+            .await
+            .map_err(|_| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Could not read resource with uri: {}", uri),
+                    None,
+                )
+            })?;
+
+        self.resource_updates
+            .lock()
+            .await
+            .remove(&(name_to_key(extension_name), uri.to_string()));
+        Ok(result)
+    }
+
+    /// Subscribe to updates of a resource. Changes are reported to the model through
+    /// the MOIM until the resource is read again.
+    pub async fn subscribe_resource(
+        &self,
+        session_id: &str,
+        extension_name: &str,
+        uri: &str,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), ErrorData> {
+        let key = name_to_key(extension_name);
+        let client = {
+            let extensions = self.extensions.lock().await;
+            let extension = extensions.get(&key).ok_or_else(|| {
+                ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!("Extension {} is not valid", extension_name),
+                    None,
+                )
+            })?;
+            if !extension.supports_resource_subscriptions() {
+                return Err(ErrorData::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "Extension {} does not support resource subscriptions",
+                        extension_name
+                    ),
+                    None,
+                ));
+            }
+            extension.get_client()
+        };
+
+        client
+            .subscribe_resource(session_id, uri, cancellation_token)
+            .await
+            .map_err(|e| {
+                ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Could not subscribe to resource {}: {:?}", uri, e),
+                    None,
+                )
+            })?;
+
+        let mut subscriptions = self.resource_subscriptions.lock().await;
+        if !subscriptions.contains_key(&key) {
+            self.listen_for_resource_updates(key.clone(), &client).await;
+        }
+        subscriptions
+            .entry(key)
+            .or_default()
+            .insert(uri.to_string());
+        Ok(())
+    }
+
+    pub async fn unsubscribe_resource(
+        &self,
+        session_id: &str,
+        extension_name: &str,
+        uri: &str,
+        cancellation_token: CancellationToken,
+    ) -> Result<(), ErrorData> {
+        let key = name_to_key(extension_name);
+        let client = self.get_server_client(&key).await.ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Extension {} is not valid", extension_name),
+                None,
+            )
+        })?;
+
         client
-            .read_resource(session_id, uri, cancellation_token)
+            .unsubscribe_resource(session_id, uri, cancellation_token)
             .await
-            .map_err(|_| {
+            .map_err(|e| {
                 ErrorData::new(
                     ErrorCode::INTERNAL_ERROR,
-                    format!("Could not read resource with uri: {}", uri),
+                    format!("Could not unsubscribe from resource {}: {:?}", uri, e),
                     None,
                 )
-            })
+            })?;
+
+        if let Some(uris) = self.resource_subscriptions.lock().await.get_mut(&key) {
+            uris.remove(uri);
+        }
+        self.resource_updates
+            .lock()
+            .await
+            .remove(&(key, uri.to_string()));
+        Ok(())
+    }
+
+    /// Forward resource-updated notifications from an extension into the pending updates.
+    /// The listener ends when the extension's client is dropped.
+    async fn listen_for_resource_updates(&self, extension_name: String, client: &McpClientBox) {
+        let mut receiver = client.subscribe().await;
+        let updates = Arc::clone(&self.resource_updates);
+        tokio::spawn(async move {
+            while let Some(notification) = receiver.recv().await {
+                if let ServerNotification::ResourceUpdatedNotification(notification) = notification
+                {
+                    updates
+                        .lock()
+                        .await
+                        .insert((extension_name.clone(), notification.params.uri));
+                }
+            }
+        });
+    }
+
+    /// Write an extension's logging notifications to its log, and refresh the tools when it
+    /// sends `notifications/tools/list_changed`, remembering the change so the model can be
+    /// told. The listener ends with the client.
+    async fn listen_for_server_notifications(
+        self: &Arc<Self>,
+        extension_name: String,
+        client: &McpClientBox,
+    ) {
+        let mut receiver = client.subscribe().await;
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(notification) = receiver.recv().await {
+                match notification {
+                    ServerNotification::LoggingMessageNotification(logged) => {
+                        ExtensionLogs::global()
+                            .append_notification(&extension_name, &logged.params);
+                    }
+                    ServerNotification::ToolListChangedNotification(_) => {
+                        let Some(manager) = manager.upgrade() else {
+                            break;
+                        };
+                        tracing::info!("Tools of extension {} changed", extension_name);
+                        manager
+                            .tool_list_changes
+                            .lock()
+                            .await
+                            .insert(extension_name.clone());
+                        manager.tool_results.clear();
+                        manager.invalidate_tools_cache_and_bump_version().await;
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Extensions whose tools changed since this was last called
+    pub async fn take_tool_list_changes(&self) -> Vec<String> {
+        std::mem::take(&mut *self.tool_list_changes.lock().await)
+            .into_iter()
+            .collect()
+    }
+
+    /// Subscribed resources that changed since they were last read, as (extension, uri)
+    pub async fn pending_resource_updates(&self) -> Vec<(String, String)> {
+        self.resource_updates.lock().await.iter().cloned().collect()
     }
 
     pub async fn get_ui_resources(
@@ -1244,7 +2122,10 @@ impl ExtensionManager {
         params: Value,
         cancellation_token: CancellationToken,
     ) -> Result<Vec<Content>, ErrorData> {
-        let extension = params.get("extension").and_then(|v| v.as_str());
+        let extension = params
+            .get("extension_name")
+            .or_else(|| params.get("extension"))
+            .and_then(|v| v.as_str());
 
         match extension {
             Some(extension_name) => {
@@ -1388,12 +2269,29 @@ impl ExtensionManager {
             }
         }
 
+        // Opted-in idempotent tools are answered from the cache while the result is fresh
+        let cache_entry = self.tool_results.policy(&tool_name_str).map(|policy| {
+            (
+                ToolResultCache::key(&tool_name_str, tool_call.arguments.as_ref(), working_dir),
+                Duration::from_secs(policy.ttl_secs),
+            )
+        });
+        if let Some((key, _)) = &cache_entry {
+            if let Some(result) = self.tool_results.get(key) {
+                tracing::debug!("dispatch_tool_call: cache hit for tool={}", tool_name_str);
+                return Ok(ToolCallResult::from(Ok(result)));
+            }
+        }
+
         let arguments = tool_call.arguments.clone();
         let client = resolved.client.clone();
         let notifications_receiver = client.subscribe().await;
         let session_id = session_id.to_string();
         let actual_tool_name = resolved.actual_tool_name;
         let working_dir_str = working_dir.map(|p| p.to_string_lossy().to_string());
+        let extension_name = resolved.extension_name;
+        let suspect_extensions = Arc::clone(&self.suspect_extensions);
+        let tool_results = Arc::clone(&self.tool_results);
 
         let fut = async move {
             tracing::debug!(
@@ -1413,10 +2311,30 @@ impl ExtensionManager {
                 .await
                 .map_err(|e| match e {
                     ServiceError::McpError(error_data) => error_data,
+                    ServiceError::TransportClosed | ServiceError::TransportSend(_) => {
+                        // The server process most likely exited; check on it after this turn
+                        if let Ok(mut suspects) = suspect_extensions.lock() {
+                            suspects.insert(extension_name.clone());
+                        }
+                        ErrorData::new(
+                            ErrorCode::INTERNAL_ERROR,
+                            format!(
+                                "Extension '{}' is not responding ({}). It will be restarted \
+                                 if possible; retry the call afterwards.",
+                                extension_name, e
+                            ),
+                            None,
+                        )
+                    }
                     _ => {
                         ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), e.maybe_to_value())
                     }
                 })
+                .inspect(|result| {
+                    if let Some((key, ttl)) = cache_entry {
+                        tool_results.insert(key, result, ttl);
+                    }
+                })
         };
 
         Ok(ToolCallResult {
@@ -1666,6 +2584,16 @@ impl ExtensionManager {
             }
         }
 
+        let resource_updates = self.pending_resource_updates().await;
+        if !resource_updates.is_empty() {
+            content.push_str(
+                "\nSubscribed resources changed since they were last read (use read_resource to refresh):\n",
+            );
+            for (extension_name, uri) in resource_updates {
+                content.push_str(&format!("- {} ({})\n", uri, extension_name));
+            }
+        }
+
         content.push_str("\n</info-msg>");
 
         Some(content)
@@ -2155,6 +3083,75 @@ mod tests {
         assert!(!tool_names.iter().any(|n| n.starts_with("ext_b__")));
     }
 
+    #[tokio::test]
+    async fn test_tools_version_changes_when_extensions_change() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let extension_manager =
+            ExtensionManager::new_without_provider(temp_dir.path().to_path_buf());
+        let initial = extension_manager.tools_version();
+
+        extension_manager
+            .add_client(
+                "ext_a".to_string(),
+                ExtensionConfig::Builtin {
+                    name: "ext_a".to_string(),
+                    display_name: None,
+                    description: "built-in".to_string(),
+                    timeout: None,
+                    bundled: None,
+                    available_tools: vec![],
+                },
+                Arc::new(MockClient {}),
+                None,
+                None,
+            )
+            .await;
+        let added = extension_manager.tools_version();
+        assert_ne!(added, initial);
+
+        extension_manager.remove_extension("ext_a").await.unwrap();
+        assert_ne!(extension_manager.tools_version(), added);
+    }
+
+    #[tokio::test]
+    async fn test_restore_tool_set_stops_extensions_added_since() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let extension_manager = Arc::new(ExtensionManager::new_without_provider(
+            temp_dir.path().to_path_buf(),
+        ));
+        extension_manager
+            .add_mock_extension("ext_a".to_string(), Arc::new(MockClient {}))
+            .await;
+
+        let snapshot = extension_manager
+            .snapshot_tool_set("test-session-id", "pinned")
+            .await
+            .unwrap();
+        assert_eq!(snapshot.extensions().len(), 1);
+        assert!(snapshot.tools().iter().all(|n| n.starts_with("ext_a__")));
+
+        extension_manager
+            .add_mock_extension("ext_b".to_string(), Arc::new(MockClient {}))
+            .await;
+        extension_manager
+            .restore_tool_set("pinned", None, None, Some("test-session-id"))
+            .await
+            .unwrap();
+
+        let tool_names: Vec<String> = extension_manager
+            .get_prefixed_tools("test-session-id", None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert_eq!(tool_names, snapshot.tools());
+        assert!(extension_manager
+            .restore_tool_set("missing", None, None, None)
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_get_prefixed_tools_excluding() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -2286,4 +3283,312 @@ mod tests {
             "old extension must be preserved when replacement client creation fails"
         );
     }
+
+    struct SubscribableClient {
+        info: InitializeResult,
+        sender: std::sync::Mutex<Option<mpsc::Sender<ServerNotification>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for SubscribableClient {
+        fn get_info(&self) -> Option<&InitializeResult> {
+            Some(&self.info)
+        }
+
+        async fn list_tools(
+            &self,
+            _session_id: &str,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Ok(ListToolsResult {
+                tools: vec![],
+                next_cursor: None,
+                meta: None,
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            _session_id: &str,
+            _name: &str,
+            _arguments: Option<JsonObject>,
+            _working_dir: Option<&str>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn subscribe_resource(
+            &self,
+            _session_id: &str,
+            _uri: &str,
+            _cancellation_token: CancellationToken,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn read_resource(
+            &self,
+            _session_id: &str,
+            _uri: &str,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ReadResourceResult, Error> {
+            Ok(ReadResourceResult::new(vec![]))
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+            let (tx, rx) = mpsc::channel(16);
+            *self.sender.lock().unwrap() = Some(tx);
+            rx
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resource_updates_are_pending_until_read() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let em = ExtensionManager::new_without_provider(temp_dir.path().to_path_buf());
+        let info = InitializeResult::new(
+            rmcp::model::ServerCapabilities::builder()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+        );
+        let client = Arc::new(SubscribableClient {
+            info: info.clone(),
+            sender: std::sync::Mutex::new(None),
+        });
+        em.add_client(
+            "tickets".to_string(),
+            ExtensionConfig::Builtin {
+                name: "tickets".to_string(),
+                display_name: None,
+                description: "tickets".to_string(),
+                timeout: None,
+                bundled: None,
+                available_tools: vec![],
+            },
+            client.clone(),
+            Some(info),
+            None,
+        )
+        .await;
+
+        em.subscribe_resource("s", "tickets", "queue://open", CancellationToken::default())
+            .await
+            .unwrap();
+        let sender = client.sender.lock().unwrap().clone().unwrap();
+        sender
+            .send(ServerNotification::ResourceUpdatedNotification(
+                rmcp::model::ResourceUpdatedNotification::new(
+                    rmcp::model::ResourceUpdatedNotificationParam::new("queue://open"),
+                ),
+            ))
+            .await
+            .unwrap();
+
+        let mut updates = Vec::new();
+        for _ in 0..50 {
+            updates = em.pending_resource_updates().await;
+            if !updates.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            updates,
+            vec![("tickets".to_string(), "queue://open".to_string())]
+        );
+
+        em.read_resource("s", "queue://open", "tickets", CancellationToken::default())
+            .await
+            .unwrap();
+        assert!(em.pending_resource_updates().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tool_list_changes_refresh_tools() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let em = Arc::new(ExtensionManager::new_without_provider(
+            temp_dir.path().to_path_buf(),
+        ));
+        let client = Arc::new(SubscribableClient {
+            info: InitializeResult::new(
+                rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_tool_list_changed()
+                    .build(),
+            ),
+            sender: std::sync::Mutex::new(None),
+        });
+        let client_box: McpClientBox = client.clone();
+        em.listen_for_server_notifications("tickets".to_string(), &client_box)
+            .await;
+        let version = em.tools_version();
+
+        let sender = client.sender.lock().unwrap().clone().unwrap();
+        sender
+            .send(
+                serde_json::from_value(serde_json::json!({
+                    "method": "notifications/tools/list_changed"
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut changes = Vec::new();
+        for _ in 0..50 {
+            changes = em.take_tool_list_changes().await;
+            if !changes.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(changes, vec!["tickets".to_string()]);
+        assert!(em.tools_version() > version);
+        assert!(em.take_tool_list_changes().await.is_empty());
+    }
+
+    struct DeadClient {}
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for DeadClient {
+        fn get_info(&self) -> Option<&InitializeResult> {
+            None
+        }
+
+        async fn list_tools(
+            &self,
+            _session_id: &str,
+            _next_cursor: Option<String>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Err(Error::TransportClosed)
+        }
+
+        async fn call_tool(
+            &self,
+            _session_id: &str,
+            _name: &str,
+            _arguments: Option<JsonObject>,
+            _working_dir: Option<&str>,
+            _cancellation_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Err(Error::TransportClosed)
+        }
+    }
+
+    #[test]
+    fn test_stderr_tail_keeps_only_the_latest_lines() {
+        let mut tail = StderrTail::default();
+        let line = "x".repeat(1023);
+        for _ in 0..(STDERR_TAIL_BYTES / 1024) {
+            tail.push(line.clone());
+        }
+        tail.push("last".to_string());
+
+        assert!(tail.bytes <= STDERR_TAIL_BYTES);
+        assert_eq!(tail.lines.len(), STDERR_TAIL_BYTES / 1024);
+        assert!(tail.into_string().ends_with("x\nlast\n"));
+    }
+
+    #[test]
+    fn test_assign_tool_names_resolves_conflicts() {
+        use serde_json::json;
+        let tool = |name: &str| {
+            Tool::new(
+                name.to_string(),
+                String::new(),
+                Arc::new(json!({}).as_object().unwrap().clone()),
+            )
+        };
+        let listing = || {
+            vec![
+                (
+                    "todo".to_string(),
+                    vec![(tool("search"), true), (tool("write"), true)],
+                ),
+                ("github".to_string(), vec![(tool("search"), false)]),
+                ("developer".to_string(), vec![(tool("search"), true)]),
+            ]
+        };
+        let names = |tools: &[Tool]| {
+            let mut names: Vec<_> = tools.iter().map(|t| t.name.to_string()).collect();
+            names.sort();
+            names
+        };
+
+        let (tools, conflicts) = assign_tool_names(listing(), &HashMap::new());
+        assert_eq!(
+            names(&tools),
+            vec!["github__search", "search", "todo__search", "write"]
+        );
+        assert_eq!(
+            conflicts,
+            vec![ToolConflict {
+                tool: "search".to_string(),
+                extensions: vec!["developer".to_string(), "todo".to_string()],
+                winner: "developer".to_string(),
+                aliased: false,
+            }]
+        );
+
+        let aliases = HashMap::from([("search".to_string(), "github".to_string())]);
+        let (tools, conflicts) = assign_tool_names(listing(), &aliases);
+        assert_eq!(
+            names(&tools),
+            vec!["developer__search", "search", "todo__search", "write"]
+        );
+        assert_eq!(conflicts[0].winner, "github");
+        assert!(conflicts[0].aliased);
+    }
+
+    #[tokio::test]
+    async fn test_unresponsive_extensions_are_restarted_after_transport_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let em = Arc::new(ExtensionManager::new_without_provider(
+            temp_dir.path().to_path_buf(),
+        ));
+        em.add_mock_extension("alive".to_string(), Arc::new(MockClient {}))
+            .await;
+        em.add_mock_extension("crashed".to_string(), Arc::new(DeadClient {}))
+            .await;
+
+        // A transport error from a server that still answers probes is left alone
+        let result = em
+            .dispatch_tool_call(
+                "s",
+                CallToolRequestParams::new("alive__missing"),
+                None,
+                CancellationToken::default(),
+            )
+            .await
+            .unwrap();
+        assert!(result.result.await.is_err());
+        assert!(em.recover_unresponsive_extensions("s").await.is_empty());
+
+        let result = em
+            .dispatch_tool_call(
+                "s",
+                CallToolRequestParams::new("crashed__tool"),
+                None,
+                CancellationToken::default(),
+            )
+            .await
+            .unwrap();
+        let error = result.result.await.unwrap_err();
+        assert!(error
+            .message
+            .contains("Extension 'crashed' is not responding"));
+
+        // "crashed" is not a real builtin, so starting it again fails and it is dropped
+        let events = em.recover_unresponsive_extensions("s").await;
+        assert!(matches!(
+            events.as_slice(),
+            [ExtensionHealthEvent::RestartFailed { name, .. }] if name == "crashed"
+        ));
+        assert!(!em.is_extension_enabled("crashed").await);
+        assert!(em.is_extension_enabled("alive").await);
+    }
 }
@@ -1,31 +1,91 @@
 use chrono::Utc;
 use rmcp::model::{CallToolResult, Content, ErrorData};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
 
+use crate::config::Config;
+
 const LARGE_TEXT_THRESHOLD: usize = 200_000;
 
+/// Most characters of an oversized output shown inline, next to the reference to the full file
+const MAX_PREVIEW_CHARS: usize = 8_000;
+
+/// Longest string kept whole when pruning JSON for a preview
+const MAX_JSON_STRING_CHARS: usize = 200;
+
+/// Character limit for a tool's text output. `GOOSE_TOOL_OUTPUT_LIMITS` maps tool name
+/// globs to limits, e.g. `{"developer__shell": 20000, "github__*": 50000}`.
+pub fn output_limit(tool_name: &str) -> usize {
+    let limits = Config::global()
+        .get_param::<HashMap<String, usize>>("GOOSE_TOOL_OUTPUT_LIMITS")
+        .unwrap_or_default();
+    limit_for(&limits, tool_name)
+}
+
+fn limit_for(limits: &HashMap<String, usize>, tool_name: &str) -> usize {
+    if let Some(limit) = limits.get(tool_name) {
+        return *limit;
+    }
+    // Most specific pattern wins when several globs match
+    limits
+        .iter()
+        .filter(|(pattern, _)| {
+            glob::Pattern::new(pattern)
+                .map(|p| p.matches(tool_name))
+                .unwrap_or(false)
+        })
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map_or(LARGE_TEXT_THRESHOLD, |(_, limit)| *limit)
+}
+
 /// Process tool response and handle large text content
 pub fn process_tool_response(
+    tool_name: &str,
     response: Result<CallToolResult, ErrorData>,
+) -> Result<CallToolResult, ErrorData> {
+    let limit = output_limit(tool_name);
+    process_tool_response_with_limit(response, limit)
+}
+
+fn process_tool_response_with_limit(
+    response: Result<CallToolResult, ErrorData>,
+    limit: usize,
 ) -> Result<CallToolResult, ErrorData> {
     match response {
         Ok(mut result) => {
+            // Oversized structured content is dropped so providers read the text, which is
+            // redirected to a file below; a result without text gets the JSON as its text
+            let structured_len = result
+                .structured_content
+                .as_ref()
+                .map_or(0, |structured| structured.to_string().chars().count());
+            if structured_len > limit {
+                if let Some(text) = crate::mcp_utils::structured_content_as_text(&result) {
+                    result.content.push(Content::text(text));
+                }
+                result.structured_content = None;
+            }
+
             let mut processed_contents = Vec::new();
 
             for content in result.content {
                 match content.as_text() {
                     Some(text_content) => {
                         // Check if text exceeds threshold
-                        if text_content.text.chars().count() > LARGE_TEXT_THRESHOLD {
+                        let char_count = text_content.text.chars().count();
+                        if char_count > limit {
                             // Write to temp file
                             match write_large_text_to_file(&text_content.text) {
                                 Ok(file_path) => {
-                                    // Create a new text content with reference to the file
+                                    // Reference the full output and show a preview that fits the limit
                                     let message = format!(
-                                        "The response returned from the tool call was larger ({} characters) and is stored in the file which you can use other tools to examine or search in: {}",
-                                        text_content.text.chars().count(),
-                                        file_path
+                                        "The response returned from the tool call was larger ({} characters, {} lines) and is stored in the file which you can use other tools to examine or search in: {}\nRead it in slices (e.g. a range of lines) rather than all at once.\n\nPreview:\n{}",
+                                        char_count,
+                                        text_content.text.lines().count(),
+                                        file_path,
+                                        preview(&text_content.text, limit.min(MAX_PREVIEW_CHARS))
                                     );
                                     processed_contents.push(Content::text(message));
                                 }
@@ -58,6 +118,98 @@ pub fn process_tool_response(
     }
 }
 
+/// Shorten text to about `budget` characters, keeping its structure: JSON is pruned,
+/// anything else keeps its first and last lines
+fn preview(text: &str, budget: usize) -> String {
+    if let Ok(value) = serde_json::from_str::<Value>(text) {
+        if value.is_object() || value.is_array() {
+            if let Some(pruned) = prune_json_to_fit(&value, budget) {
+                return pruned;
+            }
+        }
+    }
+    head_and_tail(text, budget)
+}
+
+fn head_and_tail(text: &str, budget: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let half = budget / 2;
+
+    let mut head = Vec::new();
+    let mut used = 0;
+    for line in &lines {
+        let len = line.chars().count() + 1;
+        if used + len > half {
+            break;
+        }
+        used += len;
+        head.push(*line);
+    }
+
+    let mut tail = Vec::new();
+    used = 0;
+    for line in lines[head.len()..].iter().rev() {
+        let len = line.chars().count() + 1;
+        if used + len > half {
+            break;
+        }
+        used += len;
+        tail.push(*line);
+    }
+    tail.reverse();
+
+    if head.is_empty() && tail.is_empty() {
+        // A single enormous line: cut by characters instead
+        let start: String = text.chars().take(half).collect();
+        return format!("{}\n[... output truncated ...]", start);
+    }
+
+    let omitted = lines.len() - head.len() - tail.len();
+    format!(
+        "{}\n[... {} lines omitted ...]\n{}",
+        head.join("\n"),
+        omitted,
+        tail.join("\n")
+    )
+}
+
+/// Keep fewer array items per level until the JSON fits the budget
+fn prune_json_to_fit(value: &Value, budget: usize) -> Option<String> {
+    [50, 20, 10, 5, 3, 1].into_iter().find_map(|max_items| {
+        let pruned = serde_json::to_string_pretty(&prune_json(value, max_items)).ok()?;
+        (pruned.chars().count() <= budget).then_some(pruned)
+    })
+}
+
+fn prune_json(value: &Value, max_items: usize) -> Value {
+    match value {
+        Value::Array(items) => {
+            let mut pruned: Vec<Value> = items
+                .iter()
+                .take(max_items)
+                .map(|item| prune_json(item, max_items))
+                .collect();
+            if items.len() > max_items {
+                pruned.push(Value::String(format!(
+                    "[... {} more items]",
+                    items.len() - max_items
+                )));
+            }
+            Value::Array(pruned)
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, item)| (key.clone(), prune_json(item, max_items)))
+                .collect(),
+        ),
+        Value::String(s) if s.chars().count() > MAX_JSON_STRING_CHARS => {
+            let kept: String = s.chars().take(MAX_JSON_STRING_CHARS).collect();
+            Value::String(format!("{}[... truncated]", kept))
+        }
+        other => other.clone(),
+    }
+}
+
 /// Write large text content to a temporary file
 fn write_large_text_to_file(content: &str) -> Result<String, std::io::Error> {
     // Create temp directory if it doesn't exist
@@ -93,7 +245,7 @@ mod tests {
         let response = Ok(CallToolResult::success(vec![content]));
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let processed = process_tool_response_with_limit(response, LARGE_TEXT_THRESHOLD).unwrap();
 
         // Verify the response is unchanged
         assert_eq!(processed.content.len(), 1);
@@ -113,7 +265,7 @@ mod tests {
         let response = Ok(CallToolResult::success(vec![content]));
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let processed = process_tool_response_with_limit(response, LARGE_TEXT_THRESHOLD).unwrap();
 
         // Verify the response contains a message about the file
         assert_eq!(processed.content.len(), 1);
@@ -142,6 +294,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_large_structured_content_redirected_as_text() {
+        let mut small = CallToolResult::success(vec![]);
+        small.structured_content = Some(serde_json::json!({"rows": [1, 2, 3]}));
+        let processed = process_tool_response_with_limit(Ok(small), 100).unwrap();
+        assert!(processed.structured_content.is_some());
+        assert!(processed.content.is_empty());
+
+        let mut large = CallToolResult::success(vec![]);
+        large.structured_content = Some(serde_json::json!({"rows": vec![1; 100]}));
+        let processed = process_tool_response_with_limit(Ok(large), 100).unwrap();
+        assert!(processed.structured_content.is_none());
+        let text = &processed.content[0].as_text().unwrap().text;
+        assert!(text.contains("The response returned from the tool call was larger"));
+        if let Some(file_path) = text.lines().next().and_then(|l| l.split(": ").last()) {
+            let _ = fs::remove_file(file_path.trim());
+        }
+    }
+
     #[test]
     fn test_image_content_passes_through() {
         // Create an image content
@@ -150,7 +321,7 @@ mod tests {
         let response = Ok(CallToolResult::success(vec![image_content]));
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let processed = process_tool_response_with_limit(response, LARGE_TEXT_THRESHOLD).unwrap();
 
         // Verify the response is unchanged
         assert_eq!(processed.content.len(), 1);
@@ -172,7 +343,7 @@ mod tests {
         let response = Ok(CallToolResult::success(vec![small_text, large_text, image]));
 
         // Process the response
-        let processed = process_tool_response(response).unwrap();
+        let processed = process_tool_response_with_limit(response, LARGE_TEXT_THRESHOLD).unwrap();
 
         // Verify each item is handled correctly
         assert_eq!(processed.content.len(), 3);
@@ -210,6 +381,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_per_tool_limits_prefer_specific_patterns() {
+        let limits = HashMap::from([
+            ("github__*".to_string(), 50_000),
+            ("github__get_logs".to_string(), 5_000),
+        ]);
+        assert_eq!(limit_for(&limits, "github__get_logs"), 5_000);
+        assert_eq!(limit_for(&limits, "github__search"), 50_000);
+        assert_eq!(limit_for(&limits, "shell"), LARGE_TEXT_THRESHOLD);
+    }
+
+    #[test]
+    fn test_log_preview_keeps_head_and_tail() {
+        let log: String = (1..=40_000).map(|i| format!("line {}\n", i)).collect();
+        let response = Ok(CallToolResult::success(vec![Content::text(log)]));
+
+        let processed = process_tool_response_with_limit(response, 2_000).unwrap();
+        let text = &processed.content[0].as_text().unwrap().text;
+        assert!(text.contains("40000 lines"));
+        assert!(text.contains("line 1\n"));
+        assert!(text.contains("line 40000"));
+        assert!(text.contains("lines omitted"));
+        assert!(text.len() < 3_000);
+
+        if let Some(path) = text
+            .split("search in: ")
+            .nth(1)
+            .and_then(|t| t.lines().next())
+        {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_json_preview_prunes_long_arrays() {
+        let items: Vec<Value> = (0..1_000)
+            .map(|i| serde_json::json!({"id": i, "title": "x".repeat(500)}))
+            .collect();
+        let preview = preview(&serde_json::json!({"items": items}).to_string(), 4_000);
+
+        let pruned: Value = serde_json::from_str(&preview).unwrap();
+        let kept = pruned["items"].as_array().unwrap();
+        assert!(kept.len() < 1_000);
+        assert!(kept
+            .last()
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .contains("more items"));
+    }
+
     #[test]
     fn test_error_response_passes_through() {
         // Create an error response
@@ -221,7 +443,7 @@ mod tests {
         let response: Result<CallToolResult, ErrorData> = Err(error);
 
         // Process the response
-        let processed = process_tool_response(response);
+        let processed = process_tool_response_with_limit(response, LARGE_TEXT_THRESHOLD);
 
         // Verify the error is passed through unchanged
         assert!(processed.is_err());
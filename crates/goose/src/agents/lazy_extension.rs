@@ -0,0 +1,439 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use futures::future::BoxFuture;
+use rmcp::model::{
+    CallToolResult, ErrorCode, ErrorData, GetPromptResult, InitializeResult, JsonObject,
+    ListPromptsResult, ListResourcesResult, ListToolsResult, ReadResourceResult,
+    ServerNotification, Tool,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tempfile::TempDir;
+use tokio::sync::{mpsc, OnceCell};
+use tokio_util::sync::CancellationToken;
+
+use crate::agents::extension::{ExtensionConfig, ExtensionResult, PLATFORM_EXTENSIONS};
+use crate::agents::mcp_client::{Error, McpClientTrait};
+use crate::config::extensions::name_to_key;
+use crate::config::paths::Paths;
+use crate::config::Config;
+
+/// Lazily started extensions that were activated at least this often are started in the
+/// background when the session begins
+pub const PREWARM_AFTER_ACTIVATIONS: u32 = 3;
+
+/// `GOOSE_EXTENSION_STARTUP`: whether extension servers start with the session or on first use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtensionStartup {
+    #[default]
+    Eager,
+    Lazy,
+}
+
+impl ExtensionStartup {
+    pub fn from_config() -> Self {
+        Config::global()
+            .get_param::<ExtensionStartup>("GOOSE_EXTENSION_STARTUP")
+            .unwrap_or_default()
+    }
+}
+
+/// Whether an extension can be started on demand. Platform extensions run in-process
+/// and are cheap to create, so they always start eagerly.
+pub fn supports_lazy_startup(config: &ExtensionConfig) -> bool {
+    match config {
+        ExtensionConfig::Stdio { .. }
+        | ExtensionConfig::StreamableHttp { .. }
+        | ExtensionConfig::InlinePython { .. } => true,
+        ExtensionConfig::Builtin { name, .. } => {
+            !PLATFORM_EXTENSIONS.contains_key(name_to_key(name).as_str())
+        }
+        _ => false,
+    }
+}
+
+/// What an extension server advertised the last time it ran, so its tools can be offered
+/// before it is started again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolManifest {
+    /// Hash of the extension config the manifest was recorded with
+    pub fingerprint: String,
+    pub server_info: InitializeResult,
+    pub tools: Vec<Tool>,
+    /// How many times the server has been started
+    #[serde(default)]
+    pub activations: u32,
+}
+
+impl ToolManifest {
+    fn path(key: &str) -> PathBuf {
+        Paths::in_state_dir("extension_manifests").join(format!("{}.json", key))
+    }
+
+    pub fn fingerprint(config: &ExtensionConfig) -> String {
+        let serialized = serde_json::to_string(config).unwrap_or_default();
+        format!("{:x}", Sha256::digest(serialized.as_bytes()))
+    }
+
+    /// Load the manifest for an extension, ignoring it if the config changed since
+    pub fn load(config: &ExtensionConfig) -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path(&config.key())).ok()?;
+        let manifest: Self = serde_json::from_str(&content).ok()?;
+        (manifest.fingerprint == Self::fingerprint(config)).then_some(manifest)
+    }
+
+    fn save(&self, key: &str) -> Result<()> {
+        let path = Self::path(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Record the tools of a freshly started server and count the activation
+    pub async fn record(
+        config: &ExtensionConfig,
+        client: &dyn McpClientTrait,
+        session_id: &str,
+    ) -> Result<()> {
+        let Some(server_info) = client.get_info().cloned() else {
+            return Ok(());
+        };
+        let mut tools = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = client
+                .list_tools(session_id, cursor, CancellationToken::default())
+                .await?;
+            tools.extend(page.tools);
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let activations = Self::load(config).map_or(0, |m| m.activations) + 1;
+        Self {
+            fingerprint: Self::fingerprint(config),
+            server_info,
+            tools,
+            activations,
+        }
+        .save(&config.key())
+    }
+}
+
+pub type StartedClient = (Arc<dyn McpClientTrait>, Option<TempDir>);
+pub type Starter =
+    Box<dyn Fn() -> BoxFuture<'static, ExtensionResult<StartedClient>> + Send + Sync>;
+
+/// Stands in for an extension server that has not been started yet. Tools come from the
+/// cached manifest; the server is started on the first tool call or resource/prompt read.
+/// Until then resource and prompt listings are empty.
+pub struct LazyClient {
+    name: String,
+    manifest: ToolManifest,
+    starter: Starter,
+    started: OnceCell<StartedClient>,
+}
+
+impl LazyClient {
+    pub fn new(name: String, manifest: ToolManifest, starter: Starter) -> Self {
+        Self {
+            name,
+            manifest,
+            starter,
+            started: OnceCell::new(),
+        }
+    }
+
+    pub fn activations(&self) -> u32 {
+        self.manifest.activations
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started.initialized()
+    }
+
+    /// Start the server if it is not running yet
+    pub async fn start(&self) -> Result<&Arc<dyn McpClientTrait>, Error> {
+        self.started
+            .get_or_try_init(|| (self.starter)())
+            .await
+            .map(|(client, _)| client)
+            .map_err(|e| {
+                tracing::warn!("Failed to start extension {}: {}", self.name, e);
+                Error::McpError(ErrorData::new(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Failed to start extension {}: {}", self.name, e),
+                    None,
+                ))
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl McpClientTrait for LazyClient {
+    fn get_info(&self) -> Option<&InitializeResult> {
+        Some(&self.manifest.server_info)
+    }
+
+    async fn list_tools(
+        &self,
+        session_id: &str,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListToolsResult, Error> {
+        match self.started.get() {
+            Some((client, _)) => {
+                client
+                    .list_tools(session_id, next_cursor, cancel_token)
+                    .await
+            }
+            None => Ok(ListToolsResult {
+                tools: self.manifest.tools.clone(),
+                next_cursor: None,
+                meta: None,
+            }),
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        session_id: &str,
+        name: &str,
+        arguments: Option<JsonObject>,
+        working_dir: Option<&str>,
+        cancel_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        self.start()
+            .await?
+            .call_tool(session_id, name, arguments, working_dir, cancel_token)
+            .await
+    }
+
+    async fn list_resources(
+        &self,
+        session_id: &str,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListResourcesResult, Error> {
+        match self.started.get() {
+            Some((client, _)) => {
+                client
+                    .list_resources(session_id, next_cursor, cancel_token)
+                    .await
+            }
+            None => Ok(ListResourcesResult {
+                resources: vec![],
+                next_cursor: None,
+                meta: None,
+            }),
+        }
+    }
+
+    async fn read_resource(
+        &self,
+        session_id: &str,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<ReadResourceResult, Error> {
+        self.start()
+            .await?
+            .read_resource(session_id, uri, cancel_token)
+            .await
+    }
+
+    async fn subscribe_resource(
+        &self,
+        session_id: &str,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        self.start()
+            .await?
+            .subscribe_resource(session_id, uri, cancel_token)
+            .await
+    }
+
+    async fn unsubscribe_resource(
+        &self,
+        session_id: &str,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        match self.started.get() {
+            Some((client, _)) => {
+                client
+                    .unsubscribe_resource(session_id, uri, cancel_token)
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn list_prompts(
+        &self,
+        session_id: &str,
+        next_cursor: Option<String>,
+        cancel_token: CancellationToken,
+    ) -> Result<ListPromptsResult, Error> {
+        match self.started.get() {
+            Some((client, _)) => {
+                client
+                    .list_prompts(session_id, next_cursor, cancel_token)
+                    .await
+            }
+            None => Ok(ListPromptsResult {
+                prompts: vec![],
+                next_cursor: None,
+                meta: None,
+            }),
+        }
+    }
+
+    async fn get_prompt(
+        &self,
+        session_id: &str,
+        name: &str,
+        arguments: Value,
+        cancel_token: CancellationToken,
+    ) -> Result<GetPromptResult, Error> {
+        self.start()
+            .await?
+            .get_prompt(session_id, name, arguments, cancel_token)
+            .await
+    }
+
+    async fn update_roots(&self, roots: Vec<PathBuf>) -> Result<(), Error> {
+        match self.started.get() {
+            Some((client, _)) => client.update_roots(roots).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Called right before a tool call is dispatched, so this starts the server too
+    async fn subscribe(&self) -> mpsc::Receiver<ServerNotification> {
+        match self.start().await {
+            Ok(client) => client.subscribe().await,
+            Err(_) => mpsc::channel(1).1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::ServerCapabilities;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct EchoClient {
+        info: InitializeResult,
+    }
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for EchoClient {
+        fn get_info(&self) -> Option<&InitializeResult> {
+            Some(&self.info)
+        }
+
+        async fn list_tools(
+            &self,
+            _session_id: &str,
+            _next_cursor: Option<String>,
+            _cancel_token: CancellationToken,
+        ) -> Result<ListToolsResult, Error> {
+            Ok(ListToolsResult {
+                tools: vec![],
+                next_cursor: None,
+                meta: None,
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            _session_id: &str,
+            name: &str,
+            _arguments: Option<JsonObject>,
+            _working_dir: Option<&str>,
+            _cancel_token: CancellationToken,
+        ) -> Result<CallToolResult, Error> {
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                name,
+            )]))
+        }
+    }
+
+    fn manifest() -> ToolManifest {
+        ToolManifest {
+            fingerprint: String::new(),
+            server_info: InitializeResult::new(
+                ServerCapabilities::builder().enable_tools().build(),
+            ),
+            tools: vec![Tool::new(
+                "search".to_string(),
+                "Search".to_string(),
+                Arc::new(JsonObject::new()),
+            )],
+            activations: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn starts_once_on_first_tool_call() {
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counter = starts.clone();
+        let client = LazyClient::new(
+            "search".to_string(),
+            manifest(),
+            Box::new(move || {
+                let counter = counter.clone();
+                Box::pin(async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let client: Arc<dyn McpClientTrait> = Arc::new(EchoClient {
+                        info: manifest().server_info,
+                    });
+                    Ok((client, None))
+                })
+            }),
+        );
+
+        let tools = client
+            .list_tools("s", None, CancellationToken::default())
+            .await
+            .unwrap();
+        assert_eq!(tools.tools.len(), 1);
+        assert!(!client.is_started());
+
+        for _ in 0..2 {
+            client
+                .call_tool("s", "search", None, None, CancellationToken::default())
+                .await
+                .unwrap();
+        }
+        assert!(client.is_started());
+        assert_eq!(starts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn only_process_backed_extensions_start_lazily() {
+        assert!(supports_lazy_startup(&ExtensionConfig::stdio(
+            "kube",
+            "kubectl-mcp",
+            "",
+            300u64
+        )));
+        assert!(!supports_lazy_startup(&ExtensionConfig::Platform {
+            name: "todo".to_string(),
+            description: String::new(),
+            display_name: None,
+            bundled: None,
+            available_tools: vec![],
+        }));
+    }
+}
@@ -0,0 +1,174 @@
+//! Process-wide broadcast of agent lifecycle events.
+//!
+//! Frontends, the scheduler and integrations subscribe here to follow what agents are doing
+//! without inspecting the message stream. Publishing never blocks: events are dropped when
+//! nobody is listening, and slow subscribers see `RecvError::Lagged` instead of holding up
+//! the agent.
+
+use serde::Serialize;
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+static BUS: LazyLock<broadcast::Sender<LifecycleEvent>> =
+    LazyLock::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LifecycleEvent {
+    TurnStarted {
+        session_id: String,
+    },
+    TurnFinished {
+        session_id: String,
+    },
+    ToolDispatched {
+        session_id: String,
+        request_id: String,
+        tool_name: String,
+    },
+    ApprovalRequested {
+        session_id: String,
+        request_id: String,
+        tool_name: String,
+    },
+    CompactionOccurred {
+        session_id: String,
+        before_count: usize,
+        after_count: usize,
+        manual: bool,
+    },
+    UsageUpdated {
+        session_id: String,
+        input_tokens: Option<i32>,
+        output_tokens: Option<i32>,
+        total_tokens: Option<i32>,
+        accumulated_total_tokens: Option<i32>,
+    },
+    /// The session switched to another provider or model.
+    ModelChanged {
+        session_id: String,
+        provider: String,
+        model: String,
+    },
+    /// Progress downloading a model to a local provider. `completed` and `total` are bytes
+    /// of the layer being downloaded, when the step has any.
+    ModelPullProgress {
+        session_id: String,
+        provider: String,
+        model: String,
+        status: String,
+        completed: Option<u64>,
+        total: Option<u64>,
+    },
+    /// A provider's sign-in expired and couldn't be refreshed, so the user has to sign in
+    /// again. Sent to every subscriber, whatever session they follow.
+    ReauthRequired {
+        provider: String,
+        reason: String,
+    },
+}
+
+impl LifecycleEvent {
+    /// Empty for events that aren't about one session
+    pub fn session_id(&self) -> &str {
+        match self {
+            LifecycleEvent::TurnStarted { session_id }
+            | LifecycleEvent::TurnFinished { session_id }
+            | LifecycleEvent::ToolDispatched { session_id, .. }
+            | LifecycleEvent::ApprovalRequested { session_id, .. }
+            | LifecycleEvent::CompactionOccurred { session_id, .. }
+            | LifecycleEvent::UsageUpdated { session_id, .. }
+            | LifecycleEvent::ModelChanged { session_id, .. }
+            | LifecycleEvent::ModelPullProgress { session_id, .. } => session_id,
+            LifecycleEvent::ReauthRequired { .. } => "",
+        }
+    }
+}
+
+pub fn publish(event: LifecycleEvent) {
+    // An error only means there are no subscribers right now
+    let _ = BUS.send(event);
+}
+
+/// Receive every event published from now on, for all sessions
+pub fn subscribe() -> broadcast::Receiver<LifecycleEvent> {
+    BUS.subscribe()
+}
+
+/// Publishes `TurnStarted`, and `TurnFinished` once dropped, so turns that fail or whose
+/// stream is dropped part way still finish.
+pub(crate) struct TurnGuard {
+    session_id: String,
+}
+
+impl TurnGuard {
+    pub(crate) fn start(session_id: String) -> Self {
+        publish(LifecycleEvent::TurnStarted {
+            session_id: session_id.clone(),
+        });
+        Self { session_id }
+    }
+}
+
+impl Drop for TurnGuard {
+    fn drop(&mut self) {
+        publish(LifecycleEvent::TurnFinished {
+            session_id: std::mem::take(&mut self.session_id),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_receive_published_events() {
+        let mut rx = subscribe();
+        let session_id = "lifecycle-test-session";
+        publish(LifecycleEvent::TurnStarted {
+            session_id: session_id.to_string(),
+        });
+
+        // Other tests publish on the same bus, so skip anything from other sessions
+        loop {
+            let event = rx.recv().await.unwrap();
+            if event.session_id() == session_id {
+                assert_eq!(
+                    serde_json::to_value(&event).unwrap(),
+                    serde_json::json!({"type": "turn_started", "session_id": session_id})
+                );
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn dropped_turns_finish() {
+        let mut rx = subscribe();
+        let session_id = "lifecycle-dropped-turn-session";
+        drop(TurnGuard::start(session_id.to_string()));
+
+        let mut events = Vec::new();
+        while events.len() < 2 {
+            let event = rx.recv().await.unwrap();
+            if event.session_id() == session_id {
+                events.push(event);
+            }
+        }
+        assert_eq!(
+            events,
+            vec![
+                LifecycleEvent::TurnStarted {
+                    session_id: session_id.to_string()
+                },
+                LifecycleEvent::TurnFinished {
+                    session_id: session_id.to_string()
+                },
+            ]
+        );
+    }
+}
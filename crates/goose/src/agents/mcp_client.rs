@@ -4,7 +4,8 @@ use crate::session_context::{SESSION_ID_HEADER, WORKING_DIR_HEADER};
 use rmcp::model::{
     CreateElicitationRequestParams, CreateElicitationResult, ElicitationAction, ErrorCode,
     ExtensionCapabilities, Extensions, JsonObject, LoggingMessageNotification, Meta,
-    SamplingMessageContent,
+    ResourceUpdatedNotification, SamplingMessageContent, SubscribeRequestParams,
+    UnsubscribeRequestParams,
 };
 /// MCP client implementation for Goose
 use rmcp::{
@@ -12,10 +13,10 @@ use rmcp::{
         CallToolRequestParams, CallToolResult, CancelledNotificationParam, ClientCapabilities,
         ClientInfo, ClientRequest, CreateMessageRequestParams, CreateMessageResult,
         GetPromptRequestParams, GetPromptResult, Implementation, InitializeRequestParams,
-        InitializeResult, ListPromptsResult, ListResourcesResult, ListToolsResult, Notification,
-        PaginatedRequestParams, ProtocolVersion, ReadResourceRequestParams, ReadResourceResult,
-        Request, RequestId, RequestOptionalParam, Role, SamplingMessage, ServerNotification,
-        ServerResult,
+        InitializeResult, ListPromptsResult, ListResourcesResult, ListRootsResult, ListToolsResult,
+        Notification, PaginatedRequestParams, ProtocolVersion, ReadResourceRequestParams,
+        ReadResourceResult, Request, RequestId, RequestOptionalParam, Role, SamplingMessage,
+        ServerNotification, ServerResult,
     },
     service::{
         ClientInitializeError, PeerRequestOptions, RequestContext, RequestHandle, RunningService,
@@ -25,7 +26,7 @@ use rmcp::{
     ClientHandler, ErrorData, Peer, RoleClient, ServiceError, ServiceExt,
 };
 use serde_json::Value;
-use std::{sync::Arc, time::Duration};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::sync::{
     mpsc::{self, Sender},
     Mutex,
@@ -74,6 +75,24 @@ pub trait McpClientTrait: Send + Sync {
         Err(Error::TransportClosed)
     }
 
+    async fn subscribe_resource(
+        &self,
+        _session_id: &str,
+        _uri: &str,
+        _cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        Err(Error::TransportClosed)
+    }
+
+    async fn unsubscribe_resource(
+        &self,
+        _session_id: &str,
+        _uri: &str,
+        _cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        Err(Error::TransportClosed)
+    }
+
     async fn list_prompts(
         &self,
         _session_id: &str,
@@ -100,6 +119,11 @@ pub trait McpClientTrait: Send + Sync {
     async fn get_moim(&self, _session_id: &str) -> Option<String> {
         None
     }
+
+    /// Replace the filesystem roots offered to the server and tell it they changed
+    async fn update_roots(&self, _roots: Vec<PathBuf>) -> Result<(), Error> {
+        Ok(())
+    }
 }
 
 pub struct GooseClient {
@@ -111,6 +135,7 @@ pub struct GooseClient {
     session_id: Mutex<Option<String>>,
     client_name: String,
     capabilities: GooseMcpClientCapabilities,
+    roots: std::sync::RwLock<Vec<PathBuf>>,
 }
 
 impl GooseClient {
@@ -120,12 +145,20 @@ impl GooseClient {
         client_name: String,
         capabilities: GooseMcpClientCapabilities,
     ) -> Self {
+        let roots = std::sync::RwLock::new(capabilities.roots.clone());
         GooseClient {
             notification_handlers: handlers,
             provider,
             session_id: Mutex::new(None),
             client_name,
             capabilities,
+            roots,
+        }
+    }
+
+    fn set_roots(&self, roots: Vec<PathBuf>) {
+        if let Ok(mut current) = self.roots.write() {
+            *current = roots;
         }
     }
 
@@ -192,6 +225,45 @@ impl ClientHandler for GooseClient {
             });
     }
 
+    async fn on_tool_list_changed(
+        &self,
+        context: rmcp::service::NotificationContext<rmcp::RoleClient>,
+    ) {
+        let Ok(mut notification) = serde_json::from_value::<ServerNotification>(
+            serde_json::json!({ "method": "notifications/tools/list_changed" }),
+        ) else {
+            return;
+        };
+        if let ServerNotification::ToolListChangedNotification(changed) = &mut notification {
+            changed.extensions = context.extensions.clone();
+        }
+        self.notification_handlers
+            .lock()
+            .await
+            .iter()
+            .for_each(|handler| {
+                let _ = handler.try_send(notification.clone());
+            });
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: rmcp::model::ResourceUpdatedNotificationParam,
+        context: rmcp::service::NotificationContext<rmcp::RoleClient>,
+    ) {
+        self.notification_handlers
+            .lock()
+            .await
+            .iter()
+            .for_each(|handler| {
+                let mut notification = ResourceUpdatedNotification::new(params.clone());
+                notification.extensions = context.extensions.clone();
+                let _ = handler.try_send(ServerNotification::ResourceUpdatedNotification(
+                    notification,
+                ));
+            });
+    }
+
     async fn create_message(
         &self,
         params: CreateMessageRequestParams,
@@ -318,6 +390,18 @@ impl ClientHandler for GooseClient {
             })
     }
 
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, ErrorData> {
+        let roots = self
+            .roots
+            .read()
+            .map(|roots| roots.clone())
+            .unwrap_or_default();
+        roots_result(&roots)
+    }
+
     fn get_info(&self) -> ClientInfo {
         let mut extensions = ExtensionCapabilities::new();
 
@@ -338,6 +422,8 @@ impl ClientHandler for GooseClient {
         InitializeRequestParams::new(
             ClientCapabilities::builder()
                 .enable_extensions_with(extensions)
+                .enable_roots()
+                .enable_roots_list_changed()
                 .enable_sampling()
                 .enable_elicitation()
                 .build(),
@@ -354,6 +440,23 @@ impl ClientHandler for GooseClient {
 #[derive(Debug, Clone)]
 pub struct GooseMcpClientCapabilities {
     pub mcpui: bool,
+    /// Directories the server may operate on, offered through `roots/list`
+    pub roots: Vec<PathBuf>,
+}
+
+fn roots_result(roots: &[PathBuf]) -> Result<ListRootsResult, ErrorData> {
+    let roots: Vec<Value> = roots
+        .iter()
+        .filter_map(|path| {
+            let uri = url::Url::from_directory_path(path).ok()?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+            Some(serde_json::json!({ "uri": uri.to_string(), "name": name }))
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({ "roots": roots }))
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))
 }
 
 /// The MCP client is the interface for MCP operations.
@@ -534,6 +637,52 @@ impl McpClientTrait for McpClient {
         }
     }
 
+    async fn subscribe_resource(
+        &self,
+        session_id: &str,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        let res = self
+            .send_request_with_context(
+                session_id,
+                None,
+                ClientRequest::SubscribeRequest(Request::new(SubscribeRequestParams::new(
+                    uri.to_string(),
+                ))),
+                cancel_token,
+            )
+            .await?;
+
+        match res {
+            ServerResult::EmptyResult(_) => Ok(()),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
+    async fn unsubscribe_resource(
+        &self,
+        session_id: &str,
+        uri: &str,
+        cancel_token: CancellationToken,
+    ) -> Result<(), Error> {
+        let res = self
+            .send_request_with_context(
+                session_id,
+                None,
+                ClientRequest::UnsubscribeRequest(Request::new(UnsubscribeRequestParams::new(
+                    uri.to_string(),
+                ))),
+                cancel_token,
+            )
+            .await?;
+
+        match res {
+            ServerResult::EmptyResult(_) => Ok(()),
+            _ => Err(ServiceError::UnexpectedResponse),
+        }
+    }
+
     async fn list_tools(
         &self,
         session_id: &str,
@@ -569,7 +718,13 @@ impl McpClientTrait for McpClient {
         if let Some(args) = arguments {
             params = params.with_arguments(args);
         }
-        let request = ClientRequest::CallToolRequest(Request::new(params));
+        let mut request = Request::new(params);
+        // Servers only report progress for requests that carry a progress token
+        request.extensions.insert(Meta(JsonObject::from_iter([(
+            "progressToken".to_string(),
+            Value::String(uuid::Uuid::new_v4().to_string()),
+        )])));
+        let request = ClientRequest::CallToolRequest(request);
 
         let result = self
             .send_request_with_context(session_id, working_dir, request, cancel_token)
@@ -639,6 +794,12 @@ impl McpClientTrait for McpClient {
         self.notification_subscribers.lock().await.push(tx);
         rx
     }
+
+    async fn update_roots(&self, roots: Vec<PathBuf>) -> Result<(), Error> {
+        let client = self.client.lock().await;
+        client.service().set_roots(roots);
+        client.notify_roots_list_changed().await
+    }
 }
 
 /// Injects the given session_id and working_dir into Extensions._meta.
@@ -727,8 +888,14 @@ mod tests {
 
     fn new_client(platform: GoosePlatform) -> GooseClient {
         let capabilities = match platform {
-            GoosePlatform::GooseDesktop => GooseMcpClientCapabilities { mcpui: true },
-            GoosePlatform::GooseCli => GooseMcpClientCapabilities { mcpui: false },
+            GoosePlatform::GooseDesktop => GooseMcpClientCapabilities {
+                mcpui: true,
+                roots: vec![],
+            },
+            GoosePlatform::GooseCli => GooseMcpClientCapabilities {
+                mcpui: false,
+                roots: vec![],
+            },
         };
 
         GooseClient::new(
@@ -925,6 +1092,18 @@ mod tests {
         assert_eq!(&mcp_meta.0, expected_meta.as_object().unwrap());
     }
 
+    #[test]
+    fn test_roots_are_advertised_as_file_uris() {
+        let client = new_client(GoosePlatform::GooseCli);
+        let info = ClientHandler::get_info(&client);
+        assert!(info.capabilities.roots.is_some());
+
+        let roots = roots_result(&[PathBuf::from("/home/dev/project")]).unwrap();
+        let roots = serde_json::to_value(roots).unwrap();
+        assert_eq!(roots["roots"][0]["uri"], "file:///home/dev/project/");
+        assert_eq!(roots["roots"][0]["name"], "project");
+    }
+
     #[test]
     fn test_client_info_advertises_mcp_apps_ui_extension() {
         let client = new_client(GoosePlatform::GooseDesktop);
@@ -1,16 +1,24 @@
 mod agent;
 pub(crate) mod builtin_skills;
 pub mod container;
+pub mod dry_run;
 pub mod execute_commands;
 pub mod extension;
+pub mod extension_capabilities;
+pub mod extension_lock;
+pub mod extension_logs;
 pub mod extension_malware_check;
 pub mod extension_manager;
 pub mod final_output_tool;
 mod large_response_handler;
+pub mod lazy_extension;
+pub mod lifecycle;
 pub mod mcp_client;
 pub mod moim;
+pub mod plan_mode;
 pub mod platform_extensions;
 pub mod platform_tools;
+pub mod prompt_layout;
 pub mod prompt_manager;
 mod reply_parts;
 pub mod retry;
@@ -18,7 +26,12 @@ mod schedule_tool;
 pub mod subagent_execution_tool;
 pub(crate) mod subagent_handler;
 pub(crate) mod subagent_task_config;
+pub mod tool_bridge;
+pub mod tool_cache;
 mod tool_execution;
+mod tool_scheduler;
+pub mod tool_selection;
+pub mod turn_validation;
 pub mod types;
 pub mod validate_extensions;
 
@@ -30,4 +43,4 @@ pub use extension_manager::ExtensionManager;
 pub use prompt_manager::PromptManager;
 pub use subagent_handler::SUBAGENT_TOOL_REQUEST_TYPE;
 pub use subagent_task_config::TaskConfig;
-pub use types::{FrontendTool, RetryConfig, SessionConfig, SuccessCheck};
+pub use types::{FrontendTool, RetryConfig, SessionConfig, SteeringMessage, SuccessCheck};
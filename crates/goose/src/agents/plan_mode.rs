@@ -0,0 +1,588 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use indoc::indoc;
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, Content, ErrorCode, ErrorData, Tool, ToolAnnotations,
+};
+use rmcp::object;
+use serde::{Deserialize, Serialize};
+
+use crate::action_required_manager::ActionRequiredManager;
+use crate::agents::extension_manager::{ExtensionManager, ToolEffect};
+use crate::agents::Agent;
+use crate::config::GooseMode;
+use crate::conversation::message::{Message, ToolRequest};
+use crate::session::extension_data::{ExtensionData, ExtensionState};
+use crate::session::SessionManager;
+use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
+
+pub const PLAN_SUBMIT_TOOL_NAME: &str = "plan__submit";
+pub const PLAN_UPDATE_STEP_TOOL_NAME: &str = "plan__update_step";
+
+/// How long a submitted plan waits for the user to review it
+const PLAN_REVIEW_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Done,
+    Skipped,
+}
+
+impl StepStatus {
+    fn is_finished(&self) -> bool {
+        matches!(self, StepStatus::Done | StepStatus::Skipped)
+    }
+
+    fn marker(&self) -> &'static str {
+        match self {
+            StepStatus::Pending => "[ ]",
+            StepStatus::InProgress => "[~]",
+            StepStatus::Done => "[x]",
+            StepStatus::Skipped => "[-]",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub description: String,
+    /// Files the step expects to create or change
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Commands the step expects to run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub status: StepStatus,
+}
+
+impl PlanStep {
+    fn render(&self) -> String {
+        let mut line = self.description.clone();
+        if !self.files.is_empty() {
+            line.push_str(&format!(" (files: {})", self.files.join(", ")));
+        }
+        if !self.commands.is_empty() {
+            line.push_str(&format!(" (run: {})", self.commands.join("; ")));
+        }
+        line
+    }
+}
+
+/// Per-session plan mode, stored in the session's extension data. While enabled, the
+/// model has to submit a plan and get it approved before any mutating tool runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlanModeState {
+    pub enabled: bool,
+    #[serde(default)]
+    pub steps: Vec<PlanStep>,
+    #[serde(default)]
+    pub approved: bool,
+    /// 1-based step after which execution stops and the turn ends
+    #[serde(default)]
+    pub stop_after: Option<usize>,
+}
+
+impl ExtensionState for PlanModeState {
+    const EXTENSION_NAME: &'static str = "plan_mode";
+    const VERSION: &'static str = "v0";
+}
+
+impl PlanModeState {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    pub fn load(extension_data: &ExtensionData) -> Self {
+        Self::from_extension_data(extension_data).unwrap_or_default()
+    }
+
+    /// Whether mutating tools are held back: no plan is approved yet, or the user asked
+    /// to stop and that step is finished
+    pub fn blocks_changes(&self) -> bool {
+        self.enabled && (!self.approved || self.stopped())
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop_after
+            .and_then(|step| self.steps.get(step.saturating_sub(1)))
+            .is_some_and(|step| step.status.is_finished())
+    }
+
+    /// 1-based index of the first step that is not finished
+    pub fn current_step(&self) -> Option<usize> {
+        self.steps
+            .iter()
+            .position(|step| !step.status.is_finished())
+            .map(|index| index + 1)
+    }
+
+    pub fn summary(&self) -> String {
+        if self.steps.is_empty() {
+            return "No plan submitted yet".to_string();
+        }
+        let heading = if self.approved {
+            "Approved plan"
+        } else {
+            "Proposed plan"
+        };
+        let mut summary = format!("{}:", heading);
+        for (index, step) in self.steps.iter().enumerate() {
+            summary.push_str(&format!(
+                "\n{} {}. {}",
+                step.status.marker(),
+                index + 1,
+                step.render()
+            ));
+        }
+        if let Some(step) = self.stop_after {
+            summary.push_str(&format!("\nStopping after step {}", step));
+        }
+        summary
+    }
+
+    fn editable_text(&self) -> String {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| format!("{}. {}", index + 1, step.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Replace the steps with the user's edited text, one step per line. Steps whose
+    /// description is unchanged keep their files and commands.
+    fn apply_edits(&mut self, text: &str) {
+        let previous = std::mem::take(&mut self.steps);
+        self.steps = text
+            .lines()
+            .map(strip_step_number)
+            .filter(|line| !line.is_empty())
+            .map(|description| {
+                previous
+                    .iter()
+                    .find(|step| step.description == description)
+                    .cloned()
+                    .unwrap_or_else(|| PlanStep {
+                        description: description.to_string(),
+                        files: Vec::new(),
+                        commands: Vec::new(),
+                        status: StepStatus::Pending,
+                    })
+            })
+            .collect();
+    }
+
+    /// Record progress on a step and tell the model what to do next
+    fn record_step(&mut self, step: usize, status: StepStatus) -> Result<String, String> {
+        if !self.approved {
+            return Err("No approved plan. Submit one with plan__submit first".to_string());
+        }
+        let total = self.steps.len();
+        let Some(entry) = step.checked_sub(1).and_then(|i| self.steps.get_mut(i)) else {
+            return Err(format!("Step must be between 1 and {}", total));
+        };
+        entry.status = status;
+
+        if !status.is_finished() {
+            return Ok(format!("Step {} is in progress", step));
+        }
+        if self.stopped() {
+            return Ok(format!(
+                "The user asked to stop after step {}. Do not start the next step: summarize what was done and end your turn.",
+                step
+            ));
+        }
+        match self.current_step() {
+            Some(next) => Ok(format!(
+                "Step {} recorded. Next is step {}: {}",
+                step,
+                next,
+                self.steps[next - 1].description
+            )),
+            None => {
+                let summary = self.summary();
+                self.steps.clear();
+                self.approved = false;
+                self.stop_after = None;
+                Ok(format!(
+                    "All steps are finished.\n\n{}\n\nThe next change will need a new plan.",
+                    summary
+                ))
+            }
+        }
+    }
+}
+
+fn strip_step_number(line: &str) -> &str {
+    let line = line.trim();
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(['.', ')']) {
+            return rest.trim();
+        }
+    }
+    line
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitPlanParams {
+    steps: Vec<PlanStep>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateStepParams {
+    step: usize,
+    status: StepStatus,
+}
+
+pub fn plan_tools() -> Vec<Tool> {
+    vec![
+        Tool::new(
+            PLAN_SUBMIT_TOOL_NAME.to_string(),
+            indoc! {r#"
+                Submit a plan for the user to review. This session is in plan mode: tools
+                that change files or run commands are blocked until the user approves a plan.
+
+                Explore with read-only tools first, then submit the steps you intend to take,
+                with the files each step touches and the commands it runs. The user may edit
+                the steps, reject the plan with feedback, or approve it. Once approved, carry
+                out the returned steps in order.
+            "#}
+            .to_string(),
+            object!({
+                "type": "object",
+                "required": ["steps"],
+                "properties": {
+                    "steps": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["description"],
+                            "properties": {
+                                "description": {"type": "string"},
+                                "files": {"type": "array", "items": {"type": "string"}},
+                                "commands": {"type": "array", "items": {"type": "string"}}
+                            }
+                        }
+                    }
+                }
+            }),
+        )
+        .annotate(
+            ToolAnnotations::with_title("Submit plan".to_string())
+                .read_only(true)
+                .open_world(false),
+        ),
+        Tool::new(
+            PLAN_UPDATE_STEP_TOOL_NAME.to_string(),
+            indoc! {r#"
+                Record progress on the approved plan. Mark a step in_progress when you start it
+                and done (or skipped) when it is finished. Follow the returned instructions; the
+                user may have asked you to stop after a step.
+            "#}
+            .to_string(),
+            object!({
+                "type": "object",
+                "required": ["step", "status"],
+                "properties": {
+                    "step": {"type": "integer", "minimum": 1, "description": "1-based step number"},
+                    "status": {"type": "string", "enum": ["in_progress", "done", "skipped"]}
+                }
+            }),
+        )
+        .annotate(
+            ToolAnnotations::with_title("Update plan step".to_string())
+                .read_only(true)
+                .open_world(false),
+        ),
+    ]
+}
+
+pub fn is_plan_tool(tool_name: &str) -> bool {
+    tool_name == PLAN_SUBMIT_TOOL_NAME || tool_name == PLAN_UPDATE_STEP_TOOL_NAME
+}
+
+fn invalid_params(message: impl Into<String>) -> ErrorData {
+    ErrorData::new(ErrorCode::INVALID_PARAMS, message.into(), None)
+}
+
+impl Agent {
+    pub(super) async fn handle_plan_tool(
+        &self,
+        tool_call: &CallToolRequestParams,
+        session_id: &str,
+    ) -> Result<CallToolResult, ErrorData> {
+        let internal =
+            |e: anyhow::Error| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None);
+        let manager = self.config.session_manager.clone();
+        let session = manager
+            .get_session(session_id, false)
+            .await
+            .map_err(internal)?;
+        let mut state = PlanModeState::load(&session.extension_data);
+        if !state.enabled {
+            return Err(invalid_params("Plan mode is not enabled for this session"));
+        }
+
+        let arguments = serde_json::Value::Object(tool_call.arguments.clone().unwrap_or_default());
+        let text = if tool_call.name == PLAN_SUBMIT_TOOL_NAME {
+            let params: SubmitPlanParams =
+                serde_json::from_value(arguments).map_err(|e| invalid_params(e.to_string()))?;
+            if params.steps.is_empty() {
+                return Err(invalid_params("A plan needs at least one step"));
+            }
+            state.steps = params.steps;
+            state.approved = false;
+            state.stop_after = None;
+            self.review_plan(&mut state).await
+        } else {
+            let params: UpdateStepParams =
+                serde_json::from_value(arguments).map_err(|e| invalid_params(e.to_string()))?;
+            state
+                .record_step(params.step, params.status)
+                .map_err(invalid_params)?
+        };
+
+        // The review waits on the user, so save onto the extension data as it is now rather
+        // than the copy loaded before it
+        let mut extension_data = manager
+            .get_session(session_id, false)
+            .await
+            .map_err(internal)?
+            .extension_data;
+        state
+            .to_extension_data(&mut extension_data)
+            .map_err(internal)?;
+        manager
+            .update(session_id)
+            .extension_data(extension_data)
+            .apply()
+            .await
+            .map_err(internal)?;
+
+        Ok(CallToolResult::success(vec![Content::text(text)]))
+    }
+
+    /// Show the proposed plan to the user as an editable action and apply their answer
+    async fn review_plan(&self, state: &mut PlanModeState) -> String {
+        let message = format!(
+            "Review the plan before any changes are made. Edit the steps (one per line), then approve it or leave feedback.\n\n{}",
+            state.summary()
+        );
+        let schema = serde_json::json!({
+            "type": "object",
+            "required": ["approved"],
+            "properties": {
+                "approved": {"type": "boolean", "title": "Approve and start", "default": false},
+                "plan": {
+                    "type": "string",
+                    "title": "Steps",
+                    "description": "One step per line",
+                    "default": state.editable_text()
+                },
+                "stop_after": {
+                    "type": "integer",
+                    "title": "Stop after step",
+                    "description": "Leave empty to run every step",
+                    "minimum": 1
+                },
+                "feedback": {"type": "string", "title": "Feedback"}
+            }
+        });
+
+        let response = match ActionRequiredManager::global()
+            .request_and_wait(message, schema, PLAN_REVIEW_TIMEOUT)
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                return format!(
+                    "The plan was not reviewed ({e}). Do not make changes; ask the user how to \
+                     proceed."
+                )
+            }
+        };
+
+        if let Some(text) = response.get("plan").and_then(|p| p.as_str()) {
+            state.apply_edits(text);
+        }
+        let feedback = response
+            .get("feedback")
+            .and_then(|f| f.as_str())
+            .filter(|f| !f.trim().is_empty());
+        let approved = response
+            .get("approved")
+            .and_then(|a| a.as_bool())
+            .unwrap_or(false);
+
+        if !approved || state.steps.is_empty() {
+            return format!(
+                "The user did not approve the plan.{}\n\nRevise it and submit it again with plan__submit.",
+                feedback.map_or_else(String::new, |f| format!(" Feedback: {}", f))
+            );
+        }
+
+        state.approved = true;
+        state.stop_after = response
+            .get("stop_after")
+            .and_then(|s| s.as_u64())
+            .map(|s| s as usize)
+            .filter(|s| (1..=state.steps.len()).contains(s));
+        let mut text = format!(
+            "The user approved the plan.\n\n{}\n\nCarry out the steps in order. Call plan__update_step with in_progress when you start a step and done when it is finished.",
+            state.summary()
+        );
+        if let Some(feedback) = feedback {
+            text.push_str(&format!("\n\nUser notes: {}", feedback));
+        }
+        text
+    }
+}
+
+/// Blocks mutating tool calls in plan mode until a plan is approved, and again once
+/// execution reaches the step the user asked to stop after
+pub struct PlanModeInspector {
+    session_manager: Arc<SessionManager>,
+    extension_manager: Arc<ExtensionManager>,
+}
+
+impl PlanModeInspector {
+    pub fn new(
+        session_manager: Arc<SessionManager>,
+        extension_manager: Arc<ExtensionManager>,
+    ) -> Self {
+        Self {
+            session_manager,
+            extension_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolInspector for PlanModeInspector {
+    fn name(&self) -> &'static str {
+        "plan_mode"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn inspect(
+        &self,
+        session_id: &str,
+        tool_requests: &[ToolRequest],
+        _messages: &[Message],
+        _goose_mode: GooseMode,
+    ) -> Result<Vec<InspectionResult>> {
+        let session = self.session_manager.get_session(session_id, false).await?;
+        let state = PlanModeState::load(&session.extension_data);
+        if !state.blocks_changes() {
+            return Ok(vec![]);
+        }
+
+        let reason = if state.approved {
+            "Plan mode: the user asked to stop at this step".to_string()
+        } else {
+            "Plan mode: submit a plan with plan__submit and wait for approval".to_string()
+        };
+        let mut results = Vec::new();
+        for request in tool_requests {
+            let Ok(tool_call) = &request.tool_call else {
+                continue;
+            };
+            if is_plan_tool(&tool_call.name)
+                || self
+                    .extension_manager
+                    .classify_tool(session_id, &tool_call.name)
+                    .await
+                    == ToolEffect::ReadOnly
+            {
+                continue;
+            }
+            results.push(InspectionResult {
+                tool_request_id: request.id.clone(),
+                action: InspectionAction::Deny,
+                reason: reason.clone(),
+                confidence: 1.0,
+                inspector_name: self.name().to_string(),
+                finding_id: None,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(description: &str) -> PlanStep {
+        PlanStep {
+            description: description.to_string(),
+            files: vec![],
+            commands: vec![],
+            status: StepStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn edits_keep_details_of_unchanged_steps() {
+        let mut state = PlanModeState::new(true);
+        state.steps = vec![
+            PlanStep {
+                files: vec!["src/lib.rs".to_string()],
+                ..step("Add the parser")
+            },
+            step("Write tests"),
+        ];
+        state.apply_edits("1. Add the parser\n2) Update the docs\n\n3. Write tests\n");
+
+        assert_eq!(state.steps.len(), 3);
+        assert_eq!(state.steps[0].files, vec!["src/lib.rs"]);
+        assert_eq!(state.steps[1].description, "Update the docs");
+        assert_eq!(state.steps[2].description, "Write tests");
+    }
+
+    #[test]
+    fn blocks_changes_until_approved_and_after_stop() {
+        let mut state = PlanModeState::new(true);
+        state.steps = vec![step("one"), step("two"), step("three")];
+        assert!(state.blocks_changes());
+        assert!(state.record_step(1, StepStatus::Done).is_err());
+
+        state.approved = true;
+        state.stop_after = Some(2);
+        assert!(!state.blocks_changes());
+
+        state.record_step(1, StepStatus::Done).unwrap();
+        assert_eq!(state.current_step(), Some(2));
+        let reply = state.record_step(2, StepStatus::Done).unwrap();
+        assert!(reply.contains("stop after step 2"));
+        assert!(state.blocks_changes());
+        assert!(state.record_step(4, StepStatus::Done).is_err());
+    }
+
+    #[test]
+    fn finishing_all_steps_clears_the_plan() {
+        let mut state = PlanModeState::new(true);
+        state.steps = vec![step("one")];
+        state.approved = true;
+
+        let reply = state.record_step(1, StepStatus::Skipped).unwrap();
+        assert!(reply.starts_with("All steps are finished"));
+        assert!(state.steps.is_empty());
+        assert!(state.blocks_changes());
+    }
+}
@@ -0,0 +1,510 @@
+//! Detection of files that changed on disk after the developer tools last wrote them.
+//!
+//! Every successful write and edit records the file's size, modification time and content
+//! hash. Before the next write to the same file, a changed file is a conflict: the user is
+//! shown what changed on disk and what goose wants to change, as a three-way diff, and picks
+//! whether to merge both, overwrite with goose's version, or abort.
+//!
+//! Only files goose has written are tracked; a file goose has only read through the shell has
+//! no baseline to compare against. Goose's own shell commands re-record the tracked files when
+//! they finish, so a conflict means the file was changed outside goose. Each session keeps a
+//! bounded number of files and the least recently recorded ones are forgotten first.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use similar::{capture_diff_slices, Algorithm, DiffOp};
+
+use super::edit::unified_diff;
+use crate::action_required_manager::ActionRequiredManager;
+
+const CONFLICT_REVIEW_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Larger files are tracked by hash only, so they can be overwritten or left alone but not merged
+const MAX_TRACKED_CONTENT_BYTES: u64 = 1024 * 1024;
+const MAX_TRACKED_FILES_PER_SESSION: usize = 256;
+const MAX_RETAINED_CONTENT_BYTES_PER_SESSION: u64 = 32 * 1024 * 1024;
+const MAX_TRACKED_SESSIONS: usize = 16;
+
+struct TrackedFile {
+    len: u64,
+    modified: Option<SystemTime>,
+    hash: blake3::Hash,
+    content: Option<String>,
+    recorded: u64,
+}
+
+impl TrackedFile {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let content = fs::read_to_string(path).ok()?;
+        Some(TrackedFile {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            hash: blake3::hash(content.as_bytes()),
+            content: (metadata.len() <= MAX_TRACKED_CONTENT_BYTES).then_some(content),
+            recorded: 0,
+        })
+    }
+
+    fn retained_bytes(&self) -> u64 {
+        self.content.as_ref().map_or(0, |c| c.len() as u64)
+    }
+}
+
+#[derive(Default)]
+struct SessionFiles {
+    files: HashMap<PathBuf, TrackedFile>,
+    retained_bytes: u64,
+    last_used: u64,
+}
+
+impl SessionFiles {
+    fn remove(&mut self, path: &Path) {
+        if let Some(old) = self.files.remove(path) {
+            self.retained_bytes -= old.retained_bytes();
+        }
+    }
+
+    fn insert(&mut self, path: PathBuf, tracked: TrackedFile) {
+        self.remove(&path);
+        self.retained_bytes += tracked.retained_bytes();
+        self.files.insert(path, tracked);
+        while self.files.len() > MAX_TRACKED_FILES_PER_SESSION
+            || self.retained_bytes > MAX_RETAINED_CONTENT_BYTES_PER_SESSION
+        {
+            let Some(oldest) = self
+                .files
+                .iter()
+                .min_by_key(|(_, f)| f.recorded)
+                .map(|(p, _)| p.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+}
+
+#[derive(Default)]
+struct TrackerState {
+    sessions: HashMap<String, SessionFiles>,
+    tick: u64,
+}
+
+impl TrackerState {
+    fn session(&mut self, session_id: &str) -> &mut SessionFiles {
+        self.tick += 1;
+        if !self.sessions.contains_key(session_id) {
+            if self.sessions.len() >= MAX_TRACKED_SESSIONS {
+                let oldest = self
+                    .sessions
+                    .iter()
+                    .min_by_key(|(_, s)| s.last_used)
+                    .map(|(id, _)| id.clone());
+                if let Some(oldest) = oldest {
+                    self.sessions.remove(&oldest);
+                }
+            }
+            self.sessions
+                .insert(session_id.to_string(), SessionFiles::default());
+        }
+        let session = self.sessions.get_mut(session_id).expect("inserted above");
+        session.last_used = self.tick;
+        session
+    }
+}
+
+/// A tracked file whose content on disk is no longer what goose last wrote
+pub struct ChangedFile {
+    /// What goose last wrote, when the file was small enough to keep
+    pub base: Option<String>,
+    pub current: String,
+}
+
+#[derive(Default)]
+pub struct FileTracker {
+    state: Mutex<TrackerState>,
+}
+
+impl FileTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember the file as it is on disk now
+    pub fn record(&self, session_id: &str, path: &Path) {
+        let tracked = TrackedFile::read(path);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let tick = state.tick + 1;
+        let session = state.session(session_id);
+        match tracked {
+            Some(tracked) => session.insert(
+                path.to_path_buf(),
+                TrackedFile {
+                    recorded: tick,
+                    ..tracked
+                },
+            ),
+            None => session.remove(path),
+        }
+    }
+
+    /// Re-record every file tracked for the session, accepting changes goose made itself
+    pub fn refresh(&self, session_id: &str) {
+        let tracked: Vec<(PathBuf, u64, Option<SystemTime>)> = {
+            let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            match state.sessions.get(session_id) {
+                Some(session) => session
+                    .files
+                    .iter()
+                    .map(|(path, f)| (path.clone(), f.len, f.modified))
+                    .collect(),
+                None => return,
+            }
+        };
+        for (path, len, modified) in tracked {
+            let unchanged =
+                fs::metadata(&path).is_ok_and(|m| m.len() == len && m.modified().ok() == modified);
+            if !unchanged {
+                self.record(session_id, &path);
+            }
+        }
+    }
+
+    /// The file's base and current content if it changed since it was recorded
+    pub fn changed(&self, session_id: &str, path: &Path) -> Option<ChangedFile> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let tracked = state.sessions.get(session_id)?.files.get(path)?;
+        let metadata = fs::metadata(path).ok()?;
+        if metadata.len() == tracked.len && metadata.modified().ok() == tracked.modified {
+            return None;
+        }
+        let current = fs::read_to_string(path).ok()?;
+        if blake3::hash(current.as_bytes()) == tracked.hash {
+            return None;
+        }
+        Some(ChangedFile {
+            base: tracked.content.clone(),
+            current,
+        })
+    }
+}
+
+/// Ask the user how to resolve a conflict between the file on disk and `ours`. Returns the
+/// content to write, or the message to give the model when nothing should be written.
+pub async fn resolve(
+    display_path: &str,
+    changed: &ChangedFile,
+    ours: &str,
+) -> Result<String, String> {
+    let mut message = format!(
+        "{} changed on disk since goose last wrote it.\n\n",
+        display_path
+    );
+    let mut options = vec!["overwrite", "abort"];
+    match &changed.base {
+        Some(base) => {
+            message.push_str(&format!(
+                "Changes made on disk:\n```diff\n{}```\n\nChanges goose wants to make:\n```diff\n{}```\n\n",
+                unified_diff(base, &changed.current, display_path),
+                unified_diff(base, ours, display_path)
+            ));
+            message.push_str(
+                "Merge to keep both sets of changes, overwrite to replace the file with goose's version, or abort.",
+            );
+            options.insert(0, "merge");
+        }
+        None => {
+            message.push_str(&format!(
+                "Writing goose's version would make these changes to the file on disk:\n```diff\n{}```\n\n",
+                unified_diff(&changed.current, ours, display_path)
+            ));
+            message.push_str("Overwrite to replace the file with goose's version, or abort.");
+        }
+    }
+    let schema = serde_json::json!({
+        "type": "object",
+        "required": ["resolution"],
+        "properties": {
+            "resolution": {
+                "type": "string",
+                "title": "Resolution",
+                "enum": options,
+                "default": "abort"
+            }
+        }
+    });
+
+    let resolution = match ActionRequiredManager::global()
+        .request_and_wait(message, schema, CONFLICT_REVIEW_TIMEOUT)
+        .await
+    {
+        Ok(response) => response
+            .get("resolution")
+            .and_then(|r| r.as_str())
+            .unwrap_or("abort")
+            .to_string(),
+        Err(e) => {
+            return Err(format!(
+                "{} changed on disk since you last wrote it and the conflict was not resolved ({}). Read the file again before changing it.",
+                display_path, e
+            ))
+        }
+    };
+
+    match (resolution.as_str(), &changed.base) {
+        ("overwrite", _) => Ok(ours.to_string()),
+        ("merge", Some(base)) => merge3(base, ours, &changed.current).map_err(|conflicted| {
+            format!(
+                "{} changed on disk since you last wrote it, and your changes overlap with the changes made there. Nothing was written. The conflicting regions:\n```\n{}```\nRead the file again and redo your change on top of the current content.",
+                display_path,
+                conflict_regions(&conflicted)
+            )
+        }),
+        _ => Err(format!(
+            "The user aborted the change because {} changed on disk since you last wrote it. Read the file again before changing it.",
+            display_path
+        )),
+    }
+}
+
+/// A run of base lines `start..end` replaced by `lines`
+struct Change<'a> {
+    start: usize,
+    end: usize,
+    lines: Vec<&'a str>,
+}
+
+fn changes<'a>(base: &[&'a str], other: &[&'a str]) -> Vec<Change<'a>> {
+    let mut changes: Vec<Change<'a>> = Vec::new();
+    for op in capture_diff_slices(Algorithm::Myers, base, other) {
+        let (start, end, new) = match op {
+            DiffOp::Equal { .. } => continue,
+            DiffOp::Delete {
+                old_index, old_len, ..
+            } => (old_index, old_index + old_len, 0..0),
+            DiffOp::Insert {
+                old_index,
+                new_index,
+                new_len,
+            } => (old_index, old_index, new_index..new_index + new_len),
+            DiffOp::Replace {
+                old_index,
+                old_len,
+                new_index,
+                new_len,
+            } => (
+                old_index,
+                old_index + old_len,
+                new_index..new_index + new_len,
+            ),
+        };
+        match changes.last_mut() {
+            Some(last) if last.end == start => {
+                last.end = end;
+                last.lines.extend_from_slice(&other[new]);
+            }
+            _ => changes.push(Change {
+                start,
+                end,
+                lines: other[new].to_vec(),
+            }),
+        }
+    }
+    changes
+}
+
+/// Base lines `start..end` with `changes` applied
+fn apply(base: &[&str], changes: &[Change], start: usize, end: usize) -> String {
+    let mut out = String::new();
+    let mut pos = start;
+    for change in changes {
+        out.push_str(&base[pos..change.start].concat());
+        out.push_str(&change.lines.concat());
+        pos = change.end;
+    }
+    out.push_str(&base[pos..end].concat());
+    out
+}
+
+fn push_block(out: &mut String, text: &str) {
+    out.push_str(text);
+    if !text.is_empty() && !text.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Line-based three-way merge. On overlapping changes the error holds the merged text with
+/// conflict markers around each overlap.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Result<String, String> {
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let our_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let their_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let our_changes = changes(&base_lines, &our_lines);
+    let their_changes = changes(&base_lines, &their_lines);
+
+    let mut merged = String::new();
+    let mut conflicted = false;
+    let mut pos = 0;
+    let (mut i, mut j) = (0, 0);
+    while let Some(first) = [our_changes.get(i), their_changes.get(j)]
+        .into_iter()
+        .flatten()
+        .min_by_key(|change| change.start)
+    {
+        // Grow the region from the earliest change while changes from either side touch it
+        let (start, mut end) = (first.start, first.end);
+        let (first_ours, first_theirs) = (i, j);
+        let overlaps = |change: &Change, end: usize| change.start < end || change.start == start;
+        loop {
+            let mut grew = false;
+            while let Some(change) = our_changes.get(i).filter(|c| overlaps(c, end)) {
+                end = end.max(change.end);
+                i += 1;
+                grew = true;
+            }
+            while let Some(change) = their_changes.get(j).filter(|c| overlaps(c, end)) {
+                end = end.max(change.end);
+                j += 1;
+                grew = true;
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        merged.push_str(&base_lines[pos..start].concat());
+        let ours_in = &our_changes[first_ours..i];
+        let theirs_in = &their_changes[first_theirs..j];
+        let our_version = apply(&base_lines, ours_in, start, end);
+        let their_version = apply(&base_lines, theirs_in, start, end);
+        if theirs_in.is_empty() || our_version == their_version {
+            merged.push_str(&our_version);
+        } else if ours_in.is_empty() {
+            merged.push_str(&their_version);
+        } else {
+            conflicted = true;
+            merged.push_str("<<<<<<< goose\n");
+            push_block(&mut merged, &our_version);
+            merged.push_str("=======\n");
+            push_block(&mut merged, &their_version);
+            merged.push_str(">>>>>>> on disk\n");
+        }
+        pos = end;
+    }
+    merged.push_str(&base_lines[pos..].concat());
+
+    if conflicted {
+        Err(merged)
+    } else {
+        Ok(merged)
+    }
+}
+
+/// Just the conflict-marked parts of a failed merge
+fn conflict_regions(conflicted: &str) -> String {
+    let mut regions = String::new();
+    let mut inside = false;
+    for line in conflicted.split_inclusive('\n') {
+        if line.starts_with("<<<<<<< ") {
+            inside = true;
+        }
+        if inside {
+            regions.push_str(line);
+        }
+        if line.starts_with(">>>>>>> ") {
+            inside = false;
+        }
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge3_combines_changes_to_different_lines() {
+        let base = "one\ntwo\nthree\nfour\n";
+        let ours = "one\n2\nthree\nfour\n";
+        let theirs = "one\ntwo\nthree\nfour\nfive\n";
+
+        assert_eq!(
+            merge3(base, ours, theirs).unwrap(),
+            "one\n2\nthree\nfour\nfive\n"
+        );
+    }
+
+    #[test]
+    fn merge3_marks_overlapping_changes() {
+        let base = "one\ntwo\nthree\n";
+        let ours = "one\nTWO\nthree\n";
+        let theirs = "one\n2\nthree\n";
+
+        let conflicted = merge3(base, ours, theirs).unwrap_err();
+
+        assert_eq!(
+            conflicted,
+            "one\n<<<<<<< goose\nTWO\n=======\n2\n>>>>>>> on disk\nthree\n"
+        );
+        assert_eq!(
+            conflict_regions(&conflicted),
+            "<<<<<<< goose\nTWO\n=======\n2\n>>>>>>> on disk\n"
+        );
+    }
+
+    #[test]
+    fn tracker_reports_only_real_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "goose wrote this\n").unwrap();
+        let tracker = FileTracker::new();
+        tracker.record("session", &path);
+
+        fs::write(&path, "goose wrote this\n").unwrap();
+        assert!(tracker.changed("session", &path).is_none());
+
+        fs::write(&path, "the user changed this\n").unwrap();
+        let changed = tracker.changed("session", &path).unwrap();
+        assert_eq!(changed.base.as_deref(), Some("goose wrote this\n"));
+        assert_eq!(changed.current, "the user changed this\n");
+        assert!(tracker.changed("other-session", &path).is_none());
+    }
+
+    #[test]
+    fn tracker_accepts_changes_from_goose_shell_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        fs::write(&path, "goose wrote this\n").unwrap();
+        let tracker = FileTracker::new();
+        tracker.record("session", &path);
+
+        fs::write(&path, "a goose shell command changed this\n").unwrap();
+        tracker.refresh("session");
+        assert!(tracker.changed("session", &path).is_none());
+    }
+
+    #[test]
+    fn tracker_forgets_the_oldest_files_of_a_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = FileTracker::new();
+        let paths: Vec<PathBuf> = (0..=MAX_TRACKED_FILES_PER_SESSION)
+            .map(|i| {
+                let path = dir.path().join(format!("{}.txt", i));
+                fs::write(&path, "goose wrote this\n").unwrap();
+                tracker.record("session", &path);
+                path
+            })
+            .collect();
+        for path in &paths {
+            fs::write(path, "the user changed this\n").unwrap();
+        }
+
+        assert!(tracker.changed("session", &paths[0]).is_none());
+        assert!(tracker.changed("session", &paths[1]).is_some());
+        assert!(tracker.changed("session", paths.last().unwrap()).is_some());
+    }
+}
@@ -5,6 +5,8 @@ use rmcp::model::{CallToolResult, Content};
 use schemars::JsonSchema;
 use serde::Deserialize;
 
+use super::sandbox::{self, ExecutionBackend};
+
 const NO_MATCH_PREVIEW_LINES: usize = 20;
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -54,20 +56,31 @@ impl EditTools {
         let is_new = !path.exists();
 
         match fs::write(path, &params.content) {
-            Ok(()) => {
-                let line_count = params.content.lines().count();
-                let action = if is_new { "Created" } else { "Wrote" };
-                CallToolResult::success(vec![Content::text(format!(
-                    "{} {} ({} lines)",
-                    action, params.path, line_count
-                ))
-                .with_priority(0.0)])
-            }
-            Err(error) => CallToolResult::error(vec![Content::text(format!(
-                "Failed to write {}: {}",
-                params.path, error
-            ))
-            .with_priority(0.0)]),
+            Ok(()) => write_succeeded(&params, is_new),
+            Err(error) => failed("write", &params.path, error),
+        }
+    }
+
+    /// Write on the host, or from inside the container when the session is sandboxed
+    pub async fn file_write_with_backend(
+        &self,
+        params: FileWriteParams,
+        working_dir: Option<&Path>,
+        backend: &ExecutionBackend,
+    ) -> CallToolResult {
+        let ExecutionBackend::Container(config) = backend else {
+            return self.file_write_with_cwd(params, working_dir);
+        };
+        match sandbox::write_file(
+            config,
+            working_dir,
+            Path::new(&params.path),
+            &params.content,
+        )
+        .await
+        {
+            Ok(existed) => write_succeeded(&params, !existed),
+            Err(error) => failed("write", &params.path, error),
         }
     }
 
@@ -84,82 +97,180 @@ impl EditTools {
 
         let content = match fs::read_to_string(&path) {
             Ok(c) => c,
-            Err(error) => {
-                return CallToolResult::error(vec![Content::text(format!(
-                    "Failed to read {}: {}",
-                    params.path, error
-                ))
-                .with_priority(0.0)]);
-            }
+            Err(error) => return failed("read", &params.path, error),
+        };
+        let new_content = match apply_edit(&params, &content) {
+            Ok(new_content) => new_content,
+            Err(result) => return result,
         };
+        match fs::write(&path, &new_content) {
+            Ok(()) => edit_succeeded(&params),
+            Err(error) => failed("write", &params.path, error),
+        }
+    }
 
-        let matches: Vec<_> = content.match_indices(&params.before).collect();
+    /// Edit on the host, or from inside the container when the session is sandboxed
+    pub async fn file_edit_with_backend(
+        &self,
+        params: FileEditParams,
+        working_dir: Option<&Path>,
+        backend: &ExecutionBackend,
+    ) -> CallToolResult {
+        let ExecutionBackend::Container(config) = backend else {
+            return self.file_edit_with_cwd(params, working_dir);
+        };
+        let path = Path::new(&params.path);
+        let content = match sandbox::read_file(config, working_dir, path).await {
+            Ok(c) => c,
+            Err(error) => return failed("read", &params.path, error),
+        };
+        let new_content = match apply_edit(&params, &content) {
+            Ok(new_content) => new_content,
+            Err(result) => return result,
+        };
+        match sandbox::write_file(config, working_dir, path, &new_content).await {
+            Ok(_) => edit_succeeded(&params),
+            Err(error) => failed("write", &params.path, error),
+        }
+    }
+}
 
-        match matches.len() {
-            0 => {
-                let suggestion = find_similar_context(&content, &params.before);
-                let mut msg = "No match found for the specified text.".to_string();
-                if let Some(hint) = suggestion {
-                    msg.push_str(&format!("\n\nDid you mean:\n```\n{}\n```", hint));
-                }
-                let preview = build_file_preview(&content, NO_MATCH_PREVIEW_LINES);
-                msg.push_str(&format!("\n\nFile preview:\n```\n{}\n```", preview));
-                CallToolResult::error(vec![Content::text(msg).with_priority(0.0)])
+fn failed(action: &str, path: &str, error: impl std::fmt::Display) -> CallToolResult {
+    CallToolResult::error(vec![Content::text(format!(
+        "Failed to {} {}: {}",
+        action, path, error
+    ))
+    .with_priority(0.0)])
+}
+
+fn write_succeeded(params: &FileWriteParams, is_new: bool) -> CallToolResult {
+    let line_count = params.content.lines().count();
+    let action = if is_new { "Created" } else { "Wrote" };
+    CallToolResult::success(vec![Content::text(format!(
+        "{} {} ({} lines)",
+        action, params.path, line_count
+    ))
+    .with_priority(0.0)])
+}
+
+fn edit_succeeded(params: &FileEditParams) -> CallToolResult {
+    let old_lines = params.before.lines().count();
+    let new_lines = params.after.lines().count();
+    CallToolResult::success(vec![Content::text(format!(
+        "Edited {} ({} lines -> {} lines)",
+        params.path, old_lines, new_lines
+    ))
+    .with_priority(0.0)])
+}
+
+/// The content with the edit applied, or the error to return when `before` does not match
+/// exactly once
+fn apply_edit(params: &FileEditParams, content: &str) -> Result<String, CallToolResult> {
+    let matches: Vec<_> = content.match_indices(&params.before).collect();
+
+    match matches.len() {
+        0 => {
+            let suggestion = find_similar_context(content, &params.before);
+            let mut msg = "No match found for the specified text.".to_string();
+            if let Some(hint) = suggestion {
+                msg.push_str(&format!("\n\nDid you mean:\n```\n{}\n```", hint));
             }
-            1 => {
-                let new_content = content.replacen(&params.before, &params.after, 1);
-
-                match fs::write(&path, &new_content) {
-                    Ok(()) => {
-                        let old_lines = params.before.lines().count();
-                        let new_lines = params.after.lines().count();
-                        CallToolResult::success(vec![Content::text(format!(
-                            "Edited {} ({} lines -> {} lines)",
-                            params.path, old_lines, new_lines
-                        ))
-                        .with_priority(0.0)])
-                    }
-                    Err(error) => CallToolResult::error(vec![Content::text(format!(
-                        "Failed to write {}: {}",
-                        params.path, error
-                    ))
-                    .with_priority(0.0)]),
-                }
+            let preview = build_file_preview(content, NO_MATCH_PREVIEW_LINES);
+            msg.push_str(&format!("\n\nFile preview:\n```\n{}\n```", preview));
+            Err(CallToolResult::error(vec![
+                Content::text(msg).with_priority(0.0)
+            ]))
+        }
+        1 => Ok(content.replacen(&params.before, &params.after, 1)),
+        n => {
+            let mut msg = format!(
+                "Found {} matches. Please provide more context to identify a unique match:\n",
+                n
+            );
+
+            for (i, (pos, _)) in matches.iter().enumerate().take(2) {
+                let line_num = count_lines_before(content, *pos);
+                let context = get_line_context(content, line_num, 1);
+                msg.push_str(&format!(
+                    "\nMatch {} (line {}):\n```\n{}\n```",
+                    i + 1,
+                    line_num,
+                    context
+                ));
             }
-            n => {
-                let mut msg = format!(
-                    "Found {} matches. Please provide more context to identify a unique match:\n",
-                    n
-                );
-
-                for (i, (pos, _)) in matches.iter().enumerate().take(2) {
-                    let line_num = count_lines_before(&content, *pos);
-                    let context = get_line_context(&content, line_num, 1);
-                    msg.push_str(&format!(
-                        "\nMatch {} (line {}):\n```\n{}\n```",
-                        i + 1,
-                        line_num,
-                        context
-                    ));
-                }
-
-                if n > 2 {
-                    msg.push_str(&format!("\n\n...and {} more", n - 2));
-                }
 
-                CallToolResult::error(vec![Content::text(msg).with_priority(0.0)])
+            if n > 2 {
+                msg.push_str(&format!("\n\n...and {} more", n - 2));
             }
+
+            Err(CallToolResult::error(vec![
+                Content::text(msg).with_priority(0.0)
+            ]))
         }
     }
 }
 
+/// Describe what a write would change without touching the file.
+pub fn preview_write(params: &FileWriteParams, working_dir: Option<&Path>) -> String {
+    let path = resolve_path(&params.path, working_dir);
+    match fs::read_to_string(&path) {
+        Ok(existing) => format!(
+            "Would overwrite {}:\n```diff\n{}```",
+            params.path,
+            unified_diff(&existing, &params.content, &params.path)
+        ),
+        Err(_) => format!(
+            "Would create {} ({} lines):\n```diff\n{}```",
+            params.path,
+            params.content.lines().count(),
+            unified_diff("", &params.content, &params.path)
+        ),
+    }
+}
+
+/// Describe what an edit would change without touching the file.
+pub fn preview_edit(params: &FileEditParams, working_dir: Option<&Path>) -> String {
+    let path = resolve_path(&params.path, working_dir);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) => return format!("Would fail to edit {}: {}", params.path, error),
+    };
+    match content.matches(&params.before).count() {
+        1 => format!(
+            "Would edit {}:\n```diff\n{}```",
+            params.path,
+            unified_diff(
+                &content,
+                &content.replacen(&params.before, &params.after, 1),
+                &params.path
+            )
+        ),
+        0 => format!(
+            "Would fail to edit {}: no match for the specified text",
+            params.path
+        ),
+        n => format!(
+            "Would fail to edit {}: found {} matches for the specified text",
+            params.path, n
+        ),
+    }
+}
+
+pub(super) fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", path), &format!("b/{}", path))
+        .to_string()
+}
+
 impl Default for EditTools {
     fn default() -> Self {
         Self::new()
     }
 }
 
-fn resolve_path(path: &str, working_dir: Option<&Path>) -> PathBuf {
+pub(super) fn resolve_path(path: &str, working_dir: Option<&Path>) -> PathBuf {
     let path = PathBuf::from(path);
     if path.is_absolute() {
         path
@@ -404,4 +515,37 @@ mod tests {
             "after"
         );
     }
+
+    #[test]
+    fn test_previews_do_not_modify_files() {
+        let dir = setup();
+        fs::write(dir.path().join("preview.txt"), "one\ntwo\n").unwrap();
+
+        let edit = preview_edit(
+            &FileEditParams {
+                path: "preview.txt".to_string(),
+                before: "two".to_string(),
+                after: "three".to_string(),
+            },
+            Some(dir.path()),
+        );
+        assert!(edit.contains("-two"));
+        assert!(edit.contains("+three"));
+
+        let write = preview_write(
+            &FileWriteParams {
+                path: "created.txt".to_string(),
+                content: "hello\n".to_string(),
+            },
+            Some(dir.path()),
+        );
+        assert!(write.starts_with("Would create created.txt"));
+        assert!(write.contains("+hello"));
+
+        assert_eq!(
+            fs::read_to_string(dir.path().join("preview.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+        assert!(!dir.path().join("created.txt").exists());
+    }
 }
@@ -1,4 +1,6 @@
+pub mod conflicts;
 pub mod edit;
+pub mod sandbox;
 pub mod shell;
 pub mod tree;
 
@@ -6,12 +8,14 @@ use crate::agents::extension::PlatformExtensionContext;
 use crate::agents::mcp_client::{Error, McpClientTrait};
 use anyhow::Result;
 use async_trait::async_trait;
+use conflicts::FileTracker;
 use edit::{EditTools, FileEditParams, FileWriteParams};
 use indoc::indoc;
 use rmcp::model::{
     CallToolResult, Content, Implementation, InitializeResult, JsonObject, ListToolsResult,
     ServerCapabilities, Tool, ToolAnnotations,
 };
+use sandbox::{ensure_within_workspace, ExecutionBackend};
 use schemars::{schema_for, JsonSchema};
 use serde_json::Value;
 use shell::{ShellOutput, ShellParams, ShellTool};
@@ -24,13 +28,15 @@ pub static EXTENSION_NAME: &str = "developer";
 
 pub struct DeveloperClient {
     info: InitializeResult,
+    context: PlatformExtensionContext,
     shell_tool: Arc<ShellTool>,
     edit_tools: Arc<EditTools>,
     tree_tool: Arc<TreeTool>,
+    file_tracker: FileTracker,
 }
 
 impl DeveloperClient {
-    pub fn new(_context: PlatformExtensionContext) -> Result<Self> {
+    pub fn new(context: PlatformExtensionContext) -> Result<Self> {
         let info = InitializeResult::new(
             ServerCapabilities::builder().enable_tools().build(),
         )
@@ -51,12 +57,117 @@ impl DeveloperClient {
 
         Ok(Self {
             info,
+            context,
             shell_tool: Arc::new(ShellTool::new()?),
             edit_tools: Arc::new(EditTools::new()),
             tree_tool: Arc::new(TreeTool::new()),
+            file_tracker: FileTracker::new(),
         })
     }
 
+    fn conflict_error(message: String) -> CallToolResult {
+        CallToolResult::error(vec![Content::text(message).with_priority(0.0)])
+    }
+
+    fn track(&self, session_id: &str, path: &Path, result: &CallToolResult) {
+        if result.is_error != Some(true) {
+            self.file_tracker.record(session_id, path);
+        }
+    }
+
+    async fn write(
+        &self,
+        session_id: &str,
+        mut params: FileWriteParams,
+        working_dir: Option<&Path>,
+        backend: &ExecutionBackend,
+    ) -> CallToolResult {
+        let path = edit::resolve_path(&params.path, working_dir);
+        if let Some(changed) = self.file_tracker.changed(session_id, &path) {
+            match conflicts::resolve(&params.path, &changed, &params.content).await {
+                Ok(content) => params.content = content,
+                Err(message) => return Self::conflict_error(message),
+            }
+        }
+        let result = self
+            .edit_tools
+            .file_write_with_backend(params, working_dir, backend)
+            .await;
+        self.track(session_id, &path, &result);
+        result
+    }
+
+    async fn edit(
+        &self,
+        session_id: &str,
+        params: FileEditParams,
+        working_dir: Option<&Path>,
+        backend: &ExecutionBackend,
+    ) -> CallToolResult {
+        let path = edit::resolve_path(&params.path, working_dir);
+        if let Some(changed) = self.file_tracker.changed(session_id, &path) {
+            // An edit that still applies to the current content keeps the changes made on disk
+            let applies_to_current = changed.current.matches(&params.before).count() == 1;
+            let base_edit = changed
+                .base
+                .as_deref()
+                .filter(|base| base.matches(&params.before).count() == 1)
+                .map(|base| base.replacen(&params.before, &params.after, 1));
+            if let (false, Some(ours)) = (applies_to_current, base_edit) {
+                let result = match conflicts::resolve(&params.path, &changed, &ours).await {
+                    Ok(content) => {
+                        self.edit_tools
+                            .file_write_with_backend(
+                                FileWriteParams {
+                                    path: params.path,
+                                    content,
+                                },
+                                working_dir,
+                                backend,
+                            )
+                            .await
+                    }
+                    Err(message) => return Self::conflict_error(message),
+                };
+                self.track(session_id, &path, &result);
+                return result;
+            }
+        }
+        let result = self
+            .edit_tools
+            .file_edit_with_backend(params, working_dir, backend)
+            .await;
+        self.track(session_id, &path, &result);
+        result
+    }
+
+    async fn execution_backend(&self, session_id: &str) -> ExecutionBackend {
+        let session = self
+            .context
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .ok();
+        ExecutionBackend::for_session(session.as_ref())
+    }
+
+    fn sandbox_path_error(
+        backend: &ExecutionBackend,
+        path: &str,
+        working_dir: Option<&Path>,
+    ) -> Option<CallToolResult> {
+        if !backend.is_sandboxed() {
+            return None;
+        }
+        ensure_within_workspace(Path::new(path), working_dir)
+            .err()
+            .map(|error| {
+                CallToolResult::error(vec![
+                    Content::text(format!("Error: {error}")).with_priority(0.0)
+                ])
+            })
+    }
+
     fn schema<T: JsonSchema>() -> JsonObject {
         serde_json::to_value(schema_for!(T))
             .expect("schema serialization should succeed")
@@ -146,27 +257,41 @@ impl McpClientTrait for DeveloperClient {
 
     async fn call_tool(
         &self,
-        _session_id: &str,
+        session_id: &str,
         name: &str,
         arguments: Option<JsonObject>,
         working_dir: Option<&str>,
         _cancellation_token: CancellationToken,
     ) -> Result<CallToolResult, Error> {
         let working_dir = working_dir.map(Path::new);
+        let backend = self.execution_backend(session_id).await;
         match name {
             "shell" => match Self::parse_args::<ShellParams>(arguments) {
-                Ok(params) => Ok(self.shell_tool.shell_with_cwd(params, working_dir).await),
+                Ok(params) => {
+                    let result = self
+                        .shell_tool
+                        .shell_with_backend(params, working_dir, &backend)
+                        .await;
+                    self.file_tracker.refresh(session_id);
+                    Ok(result)
+                }
                 Err(error) => Ok(ShellTool::error_result(&format!("Error: {error}"), None)),
             },
             "write" => match Self::parse_args::<FileWriteParams>(arguments) {
-                Ok(params) => Ok(self.edit_tools.file_write_with_cwd(params, working_dir)),
+                Ok(params) => match Self::sandbox_path_error(&backend, &params.path, working_dir) {
+                    Some(error) => Ok(error),
+                    None => Ok(self.write(session_id, params, working_dir, &backend).await),
+                },
                 Err(error) => Ok(CallToolResult::error(vec![Content::text(format!(
                     "Error: {error}"
                 ))
                 .with_priority(0.0)])),
             },
             "edit" => match Self::parse_args::<FileEditParams>(arguments) {
-                Ok(params) => Ok(self.edit_tools.file_edit_with_cwd(params, working_dir)),
+                Ok(params) => match Self::sandbox_path_error(&backend, &params.path, working_dir) {
+                    Some(error) => Ok(error),
+                    None => Ok(self.edit(session_id, params, working_dir, &backend).await),
+                },
                 Err(error) => Ok(CallToolResult::error(vec![Content::text(format!(
                     "Error: {error}"
                 ))
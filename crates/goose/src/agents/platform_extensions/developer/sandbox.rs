@@ -0,0 +1,469 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::Config;
+use crate::session::extension_data::{ExtensionData, ExtensionState};
+use crate::session::Session;
+use crate::subprocess::SubprocessExt;
+
+/// Mount point of the session working directory inside the sandbox container.
+pub const SANDBOX_WORKSPACE: &str = "/workspace";
+
+const DEFAULT_SANDBOX_IMAGE: &str = "ubuntu:24.04";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxRuntime {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl SandboxRuntime {
+    pub fn binary(&self) -> &'static str {
+        match self {
+            SandboxRuntime::Docker => "docker",
+            SandboxRuntime::Podman => "podman",
+        }
+    }
+}
+
+/// Settings for running developer tools inside an ephemeral container.
+///
+/// Only the session working directory is mounted. No host environment variables,
+/// home directory, or credential stores are passed through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct SandboxConfig {
+    #[serde(default)]
+    pub runtime: SandboxRuntime,
+    #[serde(default = "default_image")]
+    pub image: String,
+    /// CPU limit passed to `--cpus`, e.g. 1.5
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+    /// Memory limit passed to `--memory`, e.g. "2g"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    /// Allow network access from inside the container. Disabled by default.
+    #[serde(default)]
+    pub network: bool,
+}
+
+fn default_image() -> String {
+    DEFAULT_SANDBOX_IMAGE.to_string()
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            runtime: SandboxRuntime::default(),
+            image: default_image(),
+            cpus: None,
+            memory: None,
+            network: false,
+        }
+    }
+}
+
+/// Per-session sandbox selection, stored in the session's extension data.
+/// `config: None` explicitly selects host execution, overriding recipe and global settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxState {
+    pub config: Option<SandboxConfig>,
+}
+
+impl ExtensionState for SandboxState {
+    const EXTENSION_NAME: &'static str = "sandbox";
+    const VERSION: &'static str = "v0";
+}
+
+impl SandboxState {
+    pub fn new(config: Option<SandboxConfig>) -> Self {
+        Self { config }
+    }
+}
+
+/// Where the developer extension runs shell commands and reads and writes files for edits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ExecutionBackend {
+    #[default]
+    Host,
+    Container(SandboxConfig),
+}
+
+impl ExecutionBackend {
+    /// Resolve the backend for a session, in order of precedence:
+    /// 1. Explicit per-session selection (`sandbox.v0` extension data)
+    /// 2. The `sandbox` setting of the recipe the session was started from
+    /// 3. The global `GOOSE_SANDBOX` config value
+    pub fn for_session(session: Option<&Session>) -> Self {
+        if let Some(session) = session {
+            if let Some(state) = SandboxState::from_extension_data(&session.extension_data) {
+                return Self::from_config(state.config);
+            }
+            if let Some(config) = session
+                .recipe
+                .as_ref()
+                .and_then(|r| r.settings.as_ref())
+                .and_then(|s| s.sandbox.clone())
+            {
+                return Self::Container(config);
+            }
+        }
+        Self::from_config(
+            Config::global()
+                .get_param::<SandboxConfig>("GOOSE_SANDBOX")
+                .ok(),
+        )
+    }
+
+    fn from_config(config: Option<SandboxConfig>) -> Self {
+        config.map(Self::Container).unwrap_or_default()
+    }
+
+    pub fn is_sandboxed(&self) -> bool {
+        matches!(self, Self::Container(_))
+    }
+}
+
+/// Persist an explicit sandbox selection for a session.
+pub fn set_session_sandbox(
+    extension_data: &mut ExtensionData,
+    config: Option<SandboxConfig>,
+) -> anyhow::Result<()> {
+    SandboxState::new(config).to_extension_data(extension_data)
+}
+
+/// A prepared `run` invocation for the container runtime.
+pub struct ContainerCommand {
+    pub command: tokio::process::Command,
+    /// Name given to the container, used to kill it when the command times out.
+    pub container_name: String,
+}
+
+fn workspace_dir(working_dir: Option<&Path>) -> Result<PathBuf, String> {
+    match working_dir {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => std::env::current_dir()
+            .map_err(|e| format!("Failed to determine working directory: {e}")),
+    }
+}
+
+pub fn build_container_command(
+    config: &SandboxConfig,
+    command_line: &str,
+    working_dir: Option<&Path>,
+) -> Result<ContainerCommand, String> {
+    container_command(config, working_dir, false, &["sh", "-c", command_line])
+}
+
+fn container_command(
+    config: &SandboxConfig,
+    working_dir: Option<&Path>,
+    interactive: bool,
+    command_args: &[&str],
+) -> Result<ContainerCommand, String> {
+    let workspace = workspace_dir(working_dir)?;
+    let container_name = format!("goose-sandbox-{}", uuid::Uuid::new_v4().simple());
+
+    let mut command = tokio::process::Command::new(config.runtime.binary());
+    command.args(container_run_args(config, &workspace, &container_name));
+    if interactive {
+        command.arg("--interactive");
+    }
+    command.arg(&config.image).args(command_args);
+    command.env_clear();
+    if let Ok(path) = std::env::var("PATH") {
+        // The runtime client itself still needs to be found on the host.
+        command.env("PATH", path);
+    }
+    command.stdin(Stdio::null());
+    command.set_no_window();
+
+    Ok(ContainerCommand {
+        command,
+        container_name,
+    })
+}
+
+fn container_run_args(config: &SandboxConfig, workspace: &Path, name: &str) -> Vec<String> {
+    let mut args = vec![
+        "run".to_string(),
+        "--rm".to_string(),
+        "--init".to_string(),
+        "--name".to_string(),
+        name.to_string(),
+        "--volume".to_string(),
+        format!("{}:{}", workspace.display(), SANDBOX_WORKSPACE),
+        "--workdir".to_string(),
+        SANDBOX_WORKSPACE.to_string(),
+        "--security-opt".to_string(),
+        "no-new-privileges".to_string(),
+        "--cap-drop".to_string(),
+        "ALL".to_string(),
+    ];
+    if !config.network {
+        args.push("--network".to_string());
+        args.push("none".to_string());
+    }
+    if let Some(cpus) = config.cpus {
+        args.push("--cpus".to_string());
+        args.push(cpus.to_string());
+    }
+    if let Some(memory) = &config.memory {
+        args.push("--memory".to_string());
+        args.push(memory.clone());
+    }
+    args
+}
+
+/// Best-effort removal of a container whose command timed out.
+pub async fn kill_container(runtime: SandboxRuntime, container_name: &str) {
+    let mut command = tokio::process::Command::new(runtime.binary());
+    command
+        .arg("kill")
+        .arg(container_name)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null());
+    command.set_no_window();
+    if let Err(e) = command.status().await {
+        tracing::warn!("Failed to kill sandbox container {}: {}", container_name, e);
+    }
+}
+
+/// Run a small `sh` script in a fresh container with `args` as `$1..`, feeding `input` on stdin.
+/// Returns stdout, or stderr as the error when the script fails.
+async fn run_script(
+    config: &SandboxConfig,
+    working_dir: Option<&Path>,
+    script: &str,
+    args: &[&str],
+    input: Option<&[u8]>,
+) -> Result<Vec<u8>, String> {
+    let mut command_args = vec!["sh", "-c", script, "sh"];
+    command_args.extend_from_slice(args);
+    let mut command =
+        container_command(config, working_dir, input.is_some(), &command_args)?.command;
+    command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start sandbox container: {e}"))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        use tokio::io::AsyncWriteExt;
+        stdin
+            .write_all(input)
+            .await
+            .map_err(|e| format!("Failed to send file content to the sandbox: {e}"))?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed waiting on sandbox container: {e}"))?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Where a workspace path is mounted inside the container
+fn container_path(path: &Path, working_dir: Option<&Path>) -> Result<String, String> {
+    let root = normalize(&workspace_dir(working_dir)?);
+    let target = normalize(&if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    });
+    let relative = target.strip_prefix(&root).map_err(|_| {
+        format!(
+            "Path {} is outside the sandboxed workspace {}",
+            path.display(),
+            root.display()
+        )
+    })?;
+    Ok(Path::new(SANDBOX_WORKSPACE)
+        .join(relative)
+        .to_string_lossy()
+        .into_owned())
+}
+
+/// Read a workspace file from inside the container
+pub async fn read_file(
+    config: &SandboxConfig,
+    working_dir: Option<&Path>,
+    path: &Path,
+) -> Result<String, String> {
+    let target = container_path(path, working_dir)?;
+    let content = run_script(config, working_dir, r#"cat -- "$1""#, &[&target], None).await?;
+    String::from_utf8(content).map_err(|_| "stream did not contain valid UTF-8".to_string())
+}
+
+/// Write a workspace file from inside the container, creating missing parent directories.
+/// Returns whether the file already existed.
+pub async fn write_file(
+    config: &SandboxConfig,
+    working_dir: Option<&Path>,
+    path: &Path,
+    content: &str,
+) -> Result<bool, String> {
+    let target = container_path(path, working_dir)?;
+    let script = r#"existed=0; [ -e "$1" ] && existed=1; mkdir -p -- "$(dirname -- "$1")" && cat > "$1" && echo "$existed""#;
+    let output = run_script(
+        config,
+        working_dir,
+        script,
+        &[&target],
+        Some(content.as_bytes()),
+    )
+    .await?;
+    Ok(String::from_utf8_lossy(&output).trim() == "1")
+}
+
+/// File tools only see the mounted workspace when sandboxed, so reject paths that escape it.
+pub fn ensure_within_workspace(path: &Path, working_dir: Option<&Path>) -> Result<(), String> {
+    let Some(root) = working_dir else {
+        return Err("Sandboxed file access requires a working directory".to_string());
+    };
+    let root = normalize(root);
+    let target = normalize(&if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    });
+    if target.starts_with(&root) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Path {} is outside the sandboxed workspace {}",
+            path.display(),
+            root.display()
+        ))
+    }
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    if let Ok(canonical) = std::fs::canonicalize(path) {
+        return canonical;
+    }
+    // The target may not exist yet (e.g. a new file); canonicalize its closest existing parent.
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    let mut existing = normalized.clone();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match existing.file_name() {
+            Some(name) => rest.push(name.to_os_string()),
+            None => break,
+        }
+        if !existing.pop() {
+            break;
+        }
+    }
+    let mut base = std::fs::canonicalize(&existing).unwrap_or(existing);
+    for name in rest.into_iter().rev() {
+        base.push(name);
+    }
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_args_apply_limits_and_isolate_network() {
+        let config = SandboxConfig {
+            runtime: SandboxRuntime::Podman,
+            image: "alpine:3".to_string(),
+            cpus: Some(1.5),
+            memory: Some("512m".to_string()),
+            network: false,
+        };
+        let args = container_run_args(&config, Path::new("/tmp/project"), "goose-sandbox-test");
+
+        assert_eq!(args[0], "run");
+        assert!(args.contains(&"--rm".to_string()));
+        assert!(args.windows(2).any(|w| w == ["--network", "none"]));
+        assert!(args.windows(2).any(|w| w == ["--cpus", "1.5"]));
+        assert!(args.windows(2).any(|w| w == ["--memory", "512m"]));
+        assert!(args.contains(&"/tmp/project:/workspace".to_string()));
+        assert!(!args
+            .iter()
+            .any(|a| a.contains(".ssh") || a.contains("HOME")));
+    }
+
+    #[test]
+    fn run_args_allow_network_when_enabled() {
+        let config = SandboxConfig {
+            network: true,
+            ..Default::default()
+        };
+        let args = container_run_args(&config, Path::new("/tmp/project"), "name");
+        assert!(!args.contains(&"--network".to_string()));
+    }
+
+    #[test]
+    fn sandbox_config_deserializes_with_defaults() {
+        let config: SandboxConfig = serde_yaml::from_str("runtime: podman").unwrap();
+        assert_eq!(config.runtime, SandboxRuntime::Podman);
+        assert_eq!(config.image, DEFAULT_SANDBOX_IMAGE);
+        assert!(!config.network);
+    }
+
+    #[test]
+    fn session_state_overrides_default() {
+        let mut data = ExtensionData::new();
+        set_session_sandbox(&mut data, Some(SandboxConfig::default())).unwrap();
+        let state = SandboxState::from_extension_data(&data).unwrap();
+        assert_eq!(
+            ExecutionBackend::from_config(state.config),
+            ExecutionBackend::Container(SandboxConfig::default())
+        );
+    }
+
+    #[test]
+    fn container_paths_are_under_the_mount() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(
+            container_path(Path::new("src/new.rs"), Some(root)).unwrap(),
+            "/workspace/src/new.rs"
+        );
+        assert_eq!(
+            container_path(&root.join("a.txt"), Some(root)).unwrap(),
+            "/workspace/a.txt"
+        );
+        assert!(container_path(Path::new("../escape.txt"), Some(root)).is_err());
+    }
+
+    #[test]
+    fn workspace_confinement() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert!(ensure_within_workspace(Path::new("src/new.rs"), Some(root)).is_ok());
+        assert!(ensure_within_workspace(&root.join("a.txt"), Some(root)).is_ok());
+        assert!(ensure_within_workspace(Path::new("../escape.txt"), Some(root)).is_err());
+        assert!(ensure_within_workspace(Path::new("/etc/passwd"), Some(root)).is_err());
+        assert!(ensure_within_workspace(Path::new("a.txt"), None).is_err());
+    }
+}
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio_stream::{wrappers::SplitStream, StreamExt};
 
+use super::sandbox::{build_container_command, kill_container, ExecutionBackend};
 use crate::subprocess::SubprocessExt;
 
 const OUTPUT_LIMIT_LINES: usize = 2000;
@@ -101,15 +102,26 @@ impl ShellTool {
         &self,
         params: ShellParams,
         working_dir: Option<&std::path::Path>,
+    ) -> CallToolResult {
+        self.shell_with_backend(params, working_dir, &ExecutionBackend::Host)
+            .await
+    }
+
+    pub async fn shell_with_backend(
+        &self,
+        params: ShellParams,
+        working_dir: Option<&std::path::Path>,
+        backend: &ExecutionBackend,
     ) -> CallToolResult {
         if params.command.trim().is_empty() {
             return Self::error_result("Command cannot be empty.", None);
         }
 
-        let execution = match run_command(&params.command, params.timeout_secs, working_dir).await {
-            Ok(execution) => execution,
-            Err(error) => return Self::error_result(&error, None),
-        };
+        let execution =
+            match run_command(&params.command, params.timeout_secs, working_dir, backend).await {
+                Ok(execution) => execution,
+                Err(error) => return Self::error_result(&error, None),
+            };
 
         // Derive stdout, stderr, and interleaved display from the single tagged-line buffer
         let (raw_stdout, raw_stderr, interleaved) = split_lines(&execution.lines);
@@ -199,16 +211,29 @@ async fn run_command(
     command_line: &str,
     timeout_secs: Option<u64>,
     working_dir: Option<&std::path::Path>,
+    backend: &ExecutionBackend,
 ) -> Result<ExecutionOutput, String> {
-    let mut command = build_shell_command(command_line);
-    if let Some(path) = working_dir {
-        command.current_dir(path);
-    }
+    let (mut command, container) = match backend {
+        ExecutionBackend::Host => {
+            let mut command = build_shell_command(command_line);
+            if let Some(path) = working_dir {
+                command.current_dir(path);
+            }
 
-    #[cfg(not(windows))]
-    if let Some(path) = user_login_path() {
-        command.env("PATH", path);
-    }
+            #[cfg(not(windows))]
+            if let Some(path) = user_login_path() {
+                command.env("PATH", path);
+            }
+            (command, None)
+        }
+        ExecutionBackend::Container(config) => {
+            let prepared = build_container_command(config, command_line, working_dir)?;
+            (
+                prepared.command,
+                Some((config.runtime, prepared.container_name)),
+            )
+        }
+    };
 
     command.stdout(Stdio::piped());
     command.stderr(Stdio::piped());
@@ -237,6 +262,9 @@ async fn run_command(
                 .code(),
             Err(_) => {
                 timed_out = true;
+                if let Some((runtime, name)) = &container {
+                    kill_container(*runtime, name).await;
+                }
                 let _ = child.start_kill();
                 let _ = child.wait().await;
                 None
@@ -62,7 +62,22 @@ pub struct ListResourcesParams {
     pub extension_name: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceSubscriptionAction {
+    Subscribe,
+    Unsubscribe,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceSubscriptionParams {
+    pub action: ResourceSubscriptionAction,
+    pub uri: String,
+    pub extension_name: String,
+}
+
 pub const READ_RESOURCE_TOOL_NAME: &str = "read_resource";
+pub const SUBSCRIBE_RESOURCE_TOOL_NAME: &str = "subscribe_resource";
 pub const LIST_RESOURCES_TOOL_NAME: &str = "list_resources";
 pub const SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME: &str = "search_available_extensions";
 pub const MANAGE_EXTENSIONS_TOOL_NAME: &str = "manage_extensions";
@@ -90,12 +105,15 @@ impl ExtensionManagerClient {
             - manage_extensions: Enable or disable extensions
             - list_resources: List resources from extensions
             - read_resource: Read specific resources from extensions
+            - subscribe_resource: Get notified when a resource changes
 
             When you lack the tools needed to complete a task, use search_available_extensions first
             to discover what extensions can help.
 
             Use manage_extensions to enable or disable specific extensions by name.
             Use list_resources and read_resource to work with extension data and resources.
+            Subscribe to resources you need to keep up to date with; changed resources are
+            listed in your context until you read them again.
         "#});
 
         Ok(Self { info, context })
@@ -122,6 +140,7 @@ impl ExtensionManagerClient {
 
     async fn handle_manage_extensions(
         &self,
+        session_id: &str,
         arguments: Option<JsonObject>,
     ) -> Result<Vec<Content>, ExtensionManagerToolError> {
         let arguments = arguments.ok_or(ExtensionManagerToolError::MissingParameter {
@@ -132,7 +151,7 @@ impl ExtensionManagerClient {
             serde_json::from_value(serde_json::Value::Object(arguments))?;
 
         match self
-            .manage_extensions_impl(params.action, params.extension_name)
+            .manage_extensions_impl(session_id, params.action, params.extension_name)
             .await
         {
             Ok(content) => Ok(content),
@@ -144,6 +163,7 @@ impl ExtensionManagerClient {
 
     async fn manage_extensions_impl(
         &self,
+        session_id: &str,
         action: ManageExtensionAction,
         extension_name: String,
     ) -> Result<Vec<Content>, ErrorData> {
@@ -187,8 +207,18 @@ impl ExtensionManagerClient {
             }
         };
 
+        // Start the extension in the session's working directory, like extensions loaded
+        // when the session was created
+        let working_dir = self
+            .context
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .ok()
+            .map(|session| session.working_dir);
+
         extension_manager
-            .add_extension(config, None, None, None)
+            .add_extension(config, working_dir, None, Some(session_id))
             .await
             .map(|_| {
                 vec![Content::text(format!(
@@ -263,6 +293,62 @@ impl ExtensionManagerClient {
         }
     }
 
+    async fn handle_subscribe_resource(
+        &self,
+        session_id: &str,
+        arguments: Option<JsonObject>,
+    ) -> Result<Vec<Content>, ExtensionManagerToolError> {
+        let arguments = arguments.ok_or(ExtensionManagerToolError::MissingParameter {
+            param_name: "arguments".to_string(),
+        })?;
+        let params: ResourceSubscriptionParams =
+            serde_json::from_value(serde_json::Value::Object(arguments))?;
+
+        let extension_manager = self
+            .context
+            .extension_manager
+            .as_ref()
+            .and_then(|weak_ref| weak_ref.upgrade())
+            .ok_or(ExtensionManagerToolError::ManagerUnavailable)?;
+
+        let result = match params.action {
+            ResourceSubscriptionAction::Subscribe => {
+                extension_manager
+                    .subscribe_resource(
+                        session_id,
+                        &params.extension_name,
+                        &params.uri,
+                        CancellationToken::default(),
+                    )
+                    .await
+            }
+            ResourceSubscriptionAction::Unsubscribe => {
+                extension_manager
+                    .unsubscribe_resource(
+                        session_id,
+                        &params.extension_name,
+                        &params.uri,
+                        CancellationToken::default(),
+                    )
+                    .await
+            }
+        };
+
+        match result {
+            Ok(()) => Ok(vec![Content::text(match params.action {
+                ResourceSubscriptionAction::Subscribe => {
+                    format!("Subscribed to {}", params.uri)
+                }
+                ResourceSubscriptionAction::Unsubscribe => {
+                    format!("Unsubscribed from {}", params.uri)
+                }
+            })]),
+            Err(e) => Err(ExtensionManagerToolError::OperationFailed {
+                message: e.message.to_string(),
+            }),
+        }
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn get_tools(&self) -> Vec<Tool> {
         let mut tools = vec![
@@ -365,6 +451,34 @@ impl ExtensionManagerClient {
                         )),
                     ]);
                 }
+                if extension_manager.supports_resource_subscriptions().await {
+                    tools.push(
+                        Tool::new(
+                            SUBSCRIBE_RESOURCE_TOOL_NAME.to_string(),
+                            indoc! {r#"
+            Subscribe to or unsubscribe from changes to a resource.
+
+            While subscribed, resources that changed since you last read them are listed in
+            your context. Use read_resource to fetch the new content.
+        "#}
+                            .to_string(),
+                            Arc::new(
+                                serde_json::to_value(schema_for!(ResourceSubscriptionParams))
+                                    .expect("Failed to serialize schema")
+                                    .as_object()
+                                    .expect("Schema must be an object")
+                                    .clone(),
+                            ),
+                        )
+                        .annotate(ToolAnnotations::from_raw(
+                            Some("Subscribe to a resource".to_string()),
+                            Some(true),
+                            Some(false),
+                            Some(true),
+                            Some(false),
+                        )),
+                    );
+                }
             }
         }
 
@@ -418,9 +532,14 @@ impl McpClientTrait for ExtensionManagerClient {
             SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME => {
                 self.handle_search_available_extensions().await
             }
-            MANAGE_EXTENSIONS_TOOL_NAME => self.handle_manage_extensions(arguments).await,
+            MANAGE_EXTENSIONS_TOOL_NAME => {
+                self.handle_manage_extensions(session_id, arguments).await
+            }
             LIST_RESOURCES_TOOL_NAME => self.handle_list_resources(session_id, arguments).await,
             READ_RESOURCE_TOOL_NAME => self.handle_read_resource(session_id, arguments).await,
+            SUBSCRIBE_RESOURCE_TOOL_NAME => {
+                self.handle_subscribe_resource(session_id, arguments).await
+            }
             _ => Err(ExtensionManagerToolError::UnknownTool {
                 tool_name: name.to_string(),
             }),
@@ -0,0 +1,670 @@
+//! Index Extension - symbol and semantic search over the workspace
+//!
+//! Each project root gets an index of its source files: the symbols the analyze parser
+//! finds, and the file contents split into chunks. The index refreshes incrementally before
+//! a search, off the async runtime, re-reading only files whose size or modification time
+//! changed, so it follows edits made by the agent or the user. Chunk embeddings come from the
+//! session's provider when it supports them and are kept by content hash, so unchanged code
+//! is embedded once.
+
+use crate::agents::extension::PlatformExtensionContext;
+use crate::agents::mcp_client::{Error, McpClientTrait};
+use crate::agents::platform_extensions::analyze::languages::lang_for_ext;
+use crate::agents::platform_extensions::analyze::parser::Parser;
+use crate::providers;
+use crate::providers::base::Provider;
+use anyhow::Result;
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use indoc::indoc;
+use rmcp::model::{
+    CallToolResult, Content, Implementation, InitializeResult, JsonObject, ListToolsResult,
+    ServerCapabilities, Tool, ToolAnnotations,
+};
+use schemars::{schema_for, JsonSchema};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tokio_util::sync::CancellationToken;
+
+pub static EXTENSION_NAME: &str = "index";
+
+const MAX_FILES: usize = 20_000;
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+const CHUNK_LINES: usize = 40;
+const EMBEDDING_BATCH: usize = 64;
+/// An index refreshed this recently is searched as it is
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+static INDEXES: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<WorkspaceIndex>>>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedSymbol {
+    pub name: String,
+    pub kind: &'static str,
+    pub line: usize,
+    pub parent: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Chunk {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+    hash: blake3::Hash,
+}
+
+#[derive(Debug)]
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    symbols: Vec<IndexedSymbol>,
+    chunks: Vec<Chunk>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub files: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub score: f32,
+    pub text: String,
+}
+
+/// Symbols and chunks for the source files under one project root
+pub struct WorkspaceIndex {
+    root: PathBuf,
+    files: HashMap<PathBuf, IndexedFile>,
+    embeddings: HashMap<blake3::Hash, Vec<f32>>,
+    refreshed_at: Option<Instant>,
+}
+
+impl WorkspaceIndex {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: HashMap::new(),
+            embeddings: HashMap::new(),
+            refreshed_at: None,
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.refreshed_at
+            .is_none_or(|refreshed_at| refreshed_at.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// Bring the index up to date with the files on disk
+    pub fn refresh(&mut self) -> RefreshStats {
+        let mut stats = RefreshStats::default();
+        let mut seen = HashSet::new();
+        let parser = Parser::new();
+
+        let entries = WalkBuilder::new(&self.root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| lang_for_ext(ext).is_some())
+            })
+            .take(MAX_FILES);
+        for entry in entries {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.len() > MAX_FILE_BYTES {
+                continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+            seen.insert(relative.clone());
+
+            let modified = metadata.modified().ok();
+            if self
+                .files
+                .get(&relative)
+                .is_some_and(|file| file.modified == modified && file.len == metadata.len())
+            {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let symbols = parser
+                .analyze_file(path, &source)
+                .map(|analysis| {
+                    let functions = analysis.functions.into_iter().map(|s| (s, "function"));
+                    let classes = analysis.classes.into_iter().map(|s| (s, "class"));
+                    functions
+                        .chain(classes)
+                        .map(|(symbol, kind)| IndexedSymbol {
+                            name: symbol.name,
+                            kind,
+                            line: symbol.line,
+                            parent: symbol.parent,
+                            detail: symbol.detail,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.files.insert(
+                relative,
+                IndexedFile {
+                    modified,
+                    len: metadata.len(),
+                    symbols,
+                    chunks: chunk_source(&source),
+                },
+            );
+            stats.updated += 1;
+        }
+
+        let before = self.files.len();
+        self.files.retain(|path, _| seen.contains(path));
+        stats.removed = before - self.files.len();
+        stats.files = self.files.len();
+
+        let live: HashSet<blake3::Hash> = self
+            .files
+            .values()
+            .flat_map(|file| file.chunks.iter().map(|chunk| chunk.hash))
+            .collect();
+        self.embeddings.retain(|hash, _| live.contains(hash));
+        self.refreshed_at = Some(Instant::now());
+        stats
+    }
+
+    /// Definitions named `name`, or containing it when nothing matches exactly
+    pub fn find_symbol(&self, name: &str, limit: usize) -> Vec<(PathBuf, IndexedSymbol)> {
+        let symbols = || {
+            self.files.iter().flat_map(|(path, file)| {
+                file.symbols
+                    .iter()
+                    .map(move |symbol| (path.clone(), symbol.clone()))
+            })
+        };
+        let mut found: Vec<_> = symbols().filter(|(_, s)| s.name == name).collect();
+        if found.is_empty() {
+            let needle = name.to_lowercase();
+            found = symbols()
+                .filter(|(_, s)| s.name.to_lowercase().contains(&needle))
+                .collect();
+        }
+        found.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.line.cmp(&b.1.line)));
+        found.truncate(limit);
+        found
+    }
+
+    /// Embed the chunks that do not have an embedding yet
+    pub async fn embed_pending(&mut self, provider: &dyn Provider, session_id: &str) -> Result<()> {
+        let mut pending: Vec<(blake3::Hash, String)> = Vec::new();
+        let mut queued = HashSet::new();
+        for chunk in self.files.values().flat_map(|file| &file.chunks) {
+            if !self.embeddings.contains_key(&chunk.hash) && queued.insert(chunk.hash) {
+                pending.push((chunk.hash, chunk.text.clone()));
+            }
+        }
+        for batch in pending.chunks(EMBEDDING_BATCH) {
+            let texts = batch.iter().map(|(_, text)| text.clone()).collect();
+            let vectors = provider.create_embeddings(session_id, texts).await?;
+            for ((hash, _), vector) in batch.iter().zip(vectors) {
+                self.embeddings.insert(*hash, vector);
+            }
+        }
+        Ok(())
+    }
+
+    /// Chunks closest to the query embedding
+    pub fn semantic_search(&self, query: &[f32], limit: usize) -> Vec<SearchHit> {
+        self.rank(limit, |chunk| {
+            self.embeddings
+                .get(&chunk.hash)
+                .map(|embedding| cosine_similarity(query, embedding))
+        })
+    }
+
+    /// Chunks sharing the most terms with the query, for providers without embeddings
+    pub fn keyword_search(&self, query: &str, limit: usize) -> Vec<SearchHit> {
+        let terms: Vec<String> = query
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|term| term.len() > 1)
+            .map(str::to_lowercase)
+            .collect();
+        self.rank(limit, |chunk| {
+            let text = chunk.text.to_lowercase();
+            let matched = terms
+                .iter()
+                .filter(|term| text.contains(term.as_str()))
+                .count();
+            (matched > 0).then(|| matched as f32 / terms.len() as f32)
+        })
+    }
+
+    fn rank(&self, limit: usize, score: impl Fn(&Chunk) -> Option<f32>) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| file.chunks.iter().map(move |chunk| (path, chunk)))
+            .filter_map(|(path, chunk)| {
+                score(chunk).map(|score| SearchHit {
+                    path: path.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score,
+                    text: chunk.text.clone(),
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.cmp(&b.path))
+                .then(a.start_line.cmp(&b.start_line))
+        });
+        hits.truncate(limit);
+        hits
+    }
+}
+
+fn chunk_source(source: &str) -> Vec<Chunk> {
+    let lines: Vec<&str> = source.lines().collect();
+    lines
+        .chunks(CHUNK_LINES)
+        .enumerate()
+        .filter_map(|(index, lines)| {
+            let text = lines.join("\n");
+            (!text.trim().is_empty()).then(|| Chunk {
+                start_line: index * CHUNK_LINES + 1,
+                end_line: index * CHUNK_LINES + lines.len(),
+                hash: blake3::hash(text.as_bytes()),
+                text,
+            })
+        })
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+fn project_root(working_dir: &Path) -> PathBuf {
+    working_dir
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(working_dir)
+        .to_path_buf()
+}
+
+fn index_for(root: &Path) -> Arc<tokio::sync::Mutex<WorkspaceIndex>> {
+    let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    indexes
+        .entry(root.to_path_buf())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(WorkspaceIndex::new(root))))
+        .clone()
+}
+
+/// The index for `root`, refreshed unless that happened moments ago
+async fn refreshed_index(
+    root: &Path,
+) -> Result<tokio::sync::OwnedMutexGuard<WorkspaceIndex>, String> {
+    let index = index_for(root).lock_owned().await;
+    if !index.is_stale() {
+        return Ok(index);
+    }
+    // Walking and reading the tree blocks
+    tokio::task::spawn_blocking(move || {
+        let mut index = index;
+        index.refresh();
+        index
+    })
+    .await
+    .map_err(|e| format!("Failed to refresh the workspace index: {e}"))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FindSymbolParams {
+    /// Name of the function, method, class or type to find
+    pub name: String,
+    /// Maximum number of definitions to return (default 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SemanticSearchParams {
+    /// What the code you are looking for does, in natural language
+    pub query: String,
+    /// Maximum number of snippets to return (default 8)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+pub struct IndexClient {
+    info: InitializeResult,
+    context: PlatformExtensionContext,
+}
+
+impl IndexClient {
+    pub fn new(context: PlatformExtensionContext) -> Result<Self> {
+        let info = InitializeResult::new(ServerCapabilities::builder().enable_tools().build())
+            .with_server_info(
+                Implementation::new(EXTENSION_NAME, "1.0.0").with_title("Workspace Index"),
+            )
+            .with_instructions(indoc! {"
+            The workspace index tracks the source files of the current project and refreshes
+            itself before a search.
+            - find_symbol: where a function, method, class or type is defined
+            - semantic_search: code snippets that match a description of what they do
+
+            Prefer these over grepping the whole tree, then read the files they point to.
+        "});
+
+        Ok(Self { info, context })
+    }
+
+    fn schema<T: JsonSchema>() -> JsonObject {
+        serde_json::to_value(schema_for!(T))
+            .expect("schema serialization should succeed")
+            .as_object()
+            .expect("schema should serialize to an object")
+            .clone()
+    }
+
+    fn parse_args<T: serde::de::DeserializeOwned>(
+        arguments: Option<JsonObject>,
+    ) -> Result<T, String> {
+        let value = arguments
+            .map(serde_json::Value::Object)
+            .ok_or_else(|| "Missing arguments".to_string())?;
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse arguments: {e}"))
+    }
+
+    async fn working_dir(&self, session_id: &str, working_dir: Option<&str>) -> Option<PathBuf> {
+        if let Some(dir) = working_dir {
+            return Some(PathBuf::from(dir));
+        }
+        self.context
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .ok()
+            .map(|session| session.working_dir)
+    }
+
+    async fn embedding_provider(&self, session_id: &str) -> Option<Arc<dyn Provider>> {
+        let session = self
+            .context
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .ok()?;
+        let provider = providers::create(
+            session.provider_name.as_deref()?,
+            session.model_config?,
+            Vec::new(),
+        )
+        .await
+        .ok()?;
+        provider.supports_embeddings().then_some(provider)
+    }
+
+    async fn find_symbol(&self, root: &Path, params: FindSymbolParams) -> CallToolResult {
+        let index = match refreshed_index(root).await {
+            Ok(index) => index,
+            Err(error) => {
+                return CallToolResult::error(vec![Content::text(format!("Error: {error}"))])
+            }
+        };
+        let found = index.find_symbol(&params.name, params.limit.unwrap_or(20));
+        if found.is_empty() {
+            return CallToolResult::success(vec![Content::text(format!(
+                "No definition of '{}' found",
+                params.name
+            ))]);
+        }
+        let lines: Vec<String> = found
+            .into_iter()
+            .map(|(path, symbol)| {
+                let mut line = format!(
+                    "{}:{} {} {}",
+                    path.display(),
+                    symbol.line,
+                    symbol.kind,
+                    symbol.name
+                );
+                if let Some(parent) = symbol.parent {
+                    line.push_str(&format!(" (in {})", parent));
+                }
+                if let Some(detail) = symbol.detail {
+                    line.push_str(&format!(" {}", detail));
+                }
+                line
+            })
+            .collect();
+        CallToolResult::success(vec![Content::text(lines.join("\n"))])
+    }
+
+    async fn semantic_search(
+        &self,
+        session_id: &str,
+        root: &Path,
+        params: SemanticSearchParams,
+    ) -> CallToolResult {
+        let limit = params.limit.unwrap_or(8);
+        let mut index = match refreshed_index(root).await {
+            Ok(index) => index,
+            Err(error) => {
+                return CallToolResult::error(vec![Content::text(format!("Error: {error}"))])
+            }
+        };
+
+        let mut note = None;
+        let hits = match self.embedding_provider(session_id).await {
+            Some(provider) => {
+                let query = provider
+                    .create_embeddings(session_id, vec![params.query.clone()])
+                    .await
+                    .ok()
+                    .and_then(|mut vectors| vectors.pop());
+                match (
+                    index.embed_pending(provider.as_ref(), session_id).await,
+                    query,
+                ) {
+                    (Ok(()), Some(query)) => index.semantic_search(&query, limit),
+                    (result, _) => {
+                        if let Err(e) = result {
+                            tracing::warn!("Failed to embed the workspace index: {}", e);
+                        }
+                        note = Some("Embeddings failed, so results are ranked by keyword match");
+                        index.keyword_search(&params.query, limit)
+                    }
+                }
+            }
+            None => {
+                note = Some(
+                    "The provider does not support embeddings, so results are ranked by keyword match",
+                );
+                index.keyword_search(&params.query, limit)
+            }
+        };
+
+        if hits.is_empty() {
+            return CallToolResult::success(vec![Content::text(format!(
+                "No code matching '{}' found",
+                params.query
+            ))]);
+        }
+        let mut output: Vec<String> = note.map(str::to_string).into_iter().collect();
+        for hit in hits {
+            output.push(format!(
+                "{}:{}-{} (score {:.2})\n```\n{}\n```",
+                hit.path.display(),
+                hit.start_line,
+                hit.end_line,
+                hit.score,
+                hit.text
+            ));
+        }
+        CallToolResult::success(vec![Content::text(output.join("\n\n"))])
+    }
+}
+
+#[async_trait]
+impl McpClientTrait for IndexClient {
+    async fn list_tools(
+        &self,
+        _session_id: &str,
+        _next_cursor: Option<String>,
+        _cancellation_token: CancellationToken,
+    ) -> Result<ListToolsResult, Error> {
+        let read_only = |title: &str| {
+            ToolAnnotations::from_raw(
+                Some(title.to_string()),
+                Some(true),
+                Some(false),
+                Some(true),
+                Some(false),
+            )
+        };
+        Ok(ListToolsResult {
+            tools: vec![
+                Tool::new(
+                    "find_symbol".to_string(),
+                    "Find where a function, method, class or type is defined in the project. Matches the exact name first, then names containing it.".to_string(),
+                    Self::schema::<FindSymbolParams>(),
+                )
+                .annotate(read_only("Find symbol")),
+                Tool::new(
+                    "semantic_search".to_string(),
+                    "Search the project's source code by meaning. Describe what the code does; returns the best matching snippets with file and line ranges.".to_string(),
+                    Self::schema::<SemanticSearchParams>(),
+                )
+                .annotate(read_only("Semantic search")),
+            ],
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        session_id: &str,
+        name: &str,
+        arguments: Option<JsonObject>,
+        working_dir: Option<&str>,
+        _cancellation_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        let Some(working_dir) = self.working_dir(session_id, working_dir).await else {
+            return Ok(CallToolResult::error(vec![Content::text(
+                "Error: no working directory for this session",
+            )]));
+        };
+        let root = project_root(&working_dir);
+        let result = match name {
+            "find_symbol" => match Self::parse_args::<FindSymbolParams>(arguments) {
+                Ok(params) => self.find_symbol(&root, params).await,
+                Err(error) => CallToolResult::error(vec![Content::text(format!("Error: {error}"))]),
+            },
+            "semantic_search" => match Self::parse_args::<SemanticSearchParams>(arguments) {
+                Ok(params) => self.semantic_search(session_id, &root, params).await,
+                Err(error) => CallToolResult::error(vec![Content::text(format!("Error: {error}"))]),
+            },
+            _ => CallToolResult::error(vec![Content::text(format!("Error: Unknown tool: {name}"))]),
+        };
+        Ok(result)
+    }
+
+    fn get_info(&self) -> Option<&InitializeResult> {
+        Some(&self.info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn refresh_is_incremental_and_finds_symbols() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("lib.rs"),
+            "struct Parser;\nfn parse_config() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("main.rs"),
+            "fn main() { parse_config(); }\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes.txt"), "not source\n").unwrap();
+
+        let mut index = WorkspaceIndex::new(dir.path());
+        assert_eq!(
+            index.refresh(),
+            RefreshStats {
+                files: 2,
+                updated: 2,
+                removed: 0,
+            }
+        );
+        assert_eq!(index.refresh().updated, 0);
+
+        let found = index.find_symbol("parse_config", 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, PathBuf::from("lib.rs"));
+        assert_eq!(found[0].1.kind, "function");
+        assert_eq!(index.find_symbol("pars", 10).len(), 2);
+
+        fs::remove_file(dir.path().join("main.rs")).unwrap();
+        let stats = index.refresh();
+        assert_eq!((stats.files, stats.removed), (1, 1));
+    }
+
+    #[test]
+    fn keyword_search_ranks_by_matched_terms() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.rs"),
+            "fn load_config() { read_file(); }\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("b.rs"), "fn load_plugins() {}\n").unwrap();
+        let mut index = WorkspaceIndex::new(dir.path());
+        index.refresh();
+
+        let hits = index.keyword_search("load config", 5);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].path, PathBuf::from("a.rs"));
+        assert_eq!(hits[0].score, 1.0);
+    }
+
+    #[test]
+    fn cosine_similarity_of_parallel_vectors_is_one() {
+        assert!((cosine_similarity(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+}
@@ -5,9 +5,12 @@ pub mod chatrecall;
 pub mod code_execution;
 pub mod developer;
 pub mod ext_manager;
+pub mod index;
 pub mod summon;
+pub mod team;
 pub mod todo;
 pub mod tom;
+pub mod web;
 
 use std::collections::HashMap;
 
@@ -40,6 +43,19 @@ pub static PLATFORM_EXTENSIONS: Lazy<HashMap<&'static str, PlatformExtensionDef>
             },
         );
 
+        map.insert(
+            index::EXTENSION_NAME,
+            PlatformExtensionDef {
+                name: index::EXTENSION_NAME,
+                display_name: "Workspace Index",
+                description:
+                    "Incremental index of the project's symbols and code, searchable by name or meaning",
+                default_enabled: false,
+                unprefixed_tools: true,
+                client_factory: |ctx| Box::new(index::IndexClient::new(ctx).unwrap()),
+            },
+        );
+
         map.insert(
             todo::EXTENSION_NAME,
             PlatformExtensionDef {
@@ -104,6 +120,19 @@ pub static PLATFORM_EXTENSIONS: Lazy<HashMap<&'static str, PlatformExtensionDef>
             },
         );
 
+        map.insert(
+            team::EXTENSION_NAME,
+            PlatformExtensionDef {
+                name: team::EXTENSION_NAME,
+                display_name: "Team",
+                description:
+                    "Run several agents, possibly on different providers, against a shared task board",
+                default_enabled: false,
+                unprefixed_tools: false,
+                client_factory: |ctx| Box::new(team::TeamClient::new(ctx).unwrap()),
+            },
+        );
+
         #[cfg(feature = "code-mode")]
         map.insert(
             code_execution::EXTENSION_NAME,
@@ -132,6 +161,19 @@ pub static PLATFORM_EXTENSIONS: Lazy<HashMap<&'static str, PlatformExtensionDef>
             },
         );
 
+        map.insert(
+            web::EXTENSION_NAME,
+            PlatformExtensionDef {
+                name: web::EXTENSION_NAME,
+                display_name: "Web",
+                description:
+                    "Read web pages and search the web, respecting robots.txt and caching recent pages",
+                default_enabled: false,
+                unprefixed_tools: true,
+                client_factory: |ctx| Box::new(web::WebClient::new(ctx).unwrap()),
+            },
+        );
+
         map.insert(
             tom::EXTENSION_NAME,
             PlatformExtensionDef {
@@ -7,7 +7,9 @@
 use crate::agents::builtin_skills;
 use crate::agents::extension::PlatformExtensionContext;
 use crate::agents::mcp_client::{Error, McpClientTrait};
-use crate::agents::subagent_handler::{run_subagent_task, OnMessageCallback, SubagentRunParams};
+use crate::agents::subagent_handler::{
+    run_subagent, run_subagent_task, OnMessageCallback, SubagentRunParams,
+};
 use crate::agents::subagent_task_config::{TaskConfig, DEFAULT_SUBAGENT_MAX_TURNS};
 use crate::agents::AgentConfig;
 use crate::config::paths::Paths;
@@ -106,9 +108,11 @@ pub struct DelegateParams {
     pub source: Option<String>,
     pub parameters: Option<HashMap<String, serde_json::Value>>,
     pub extensions: Option<Vec<String>>,
+    pub tools: Option<Vec<String>>,
     pub provider: Option<String>,
     pub model: Option<String>,
     pub temperature: Option<f32>,
+    pub max_tokens: Option<i32>,
     #[serde(default)]
     pub r#async: bool,
 }
@@ -136,6 +140,9 @@ pub struct CompletedTask {
 struct SkillMetadata {
     name: String,
     description: String,
+    /// When the agent should reach for the skill, shown alongside the description
+    #[serde(default, alias = "when-to-use")]
+    when_to_use: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -177,10 +184,19 @@ fn parse_skill_content(content: &str, path: PathBuf) -> Option<Source> {
         return None;
     }
 
+    let description = match metadata.when_to_use.as_deref().map(str::trim) {
+        Some(when) if !when.is_empty() => format!(
+            "{}. Use when: {}",
+            metadata.description.trim_end_matches('.'),
+            when
+        ),
+        _ => metadata.description,
+    };
+
     Some(Source {
         name: metadata.name,
         kind: SourceKind::Skill,
-        description: metadata.description,
+        description,
         path,
         content: body,
         supporting_files: Vec::new(),
@@ -615,6 +631,11 @@ impl SummonClient {
                     "items": {"type": "string"},
                     "description": "Extensions to enable. Omit to inherit all, empty array for none."
                 },
+                "tools": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Restrict the delegate to these tools (e.g. \"developer__shell\"). Omit to allow all tools of its extensions."
+                },
                 "provider": {
                     "type": "string",
                     "description": "Override LLM provider."
@@ -627,6 +648,10 @@ impl SummonClient {
                     "type": "number",
                     "description": "Override temperature."
                 },
+                "max_tokens": {
+                    "type": "integer",
+                    "description": "Token budget. The delegate is stopped once it has used this many tokens."
+                },
                 "async": {
                     "type": "boolean",
                     "default": false,
@@ -1197,23 +1222,14 @@ impl SummonClient {
         session_id: &str,
         arguments: Option<JsonObject>,
         cancellation_token: CancellationToken,
-    ) -> Result<Vec<Content>, String> {
+    ) -> Result<CallToolResult, String> {
         self.cleanup_completed_tasks().await;
 
         let params: DelegateParams = arguments
             .map(|args| serde_json::from_value(serde_json::Value::Object(args)))
             .transpose()
             .map_err(|e| format!("Invalid parameters: {}", e))?
-            .unwrap_or(DelegateParams {
-                instructions: None,
-                source: None,
-                parameters: None,
-                extensions: None,
-                provider: None,
-                model: None,
-                temperature: None,
-                r#async: false,
-            });
+            .unwrap_or_default();
 
         self.validate_delegate_params(&params)?;
 
@@ -1229,7 +1245,10 @@ impl SummonClient {
         }
 
         if params.r#async {
-            return self.handle_async_delegate(session_id, params).await;
+            return self
+                .handle_async_delegate(session_id, params)
+                .await
+                .map(CallToolResult::success);
         }
 
         let working_dir = session.working_dir.clone();
@@ -1269,7 +1288,7 @@ impl SummonClient {
             Arc::new(Mutex::new(Vec::new())),
         );
 
-        let result = run_subagent_task(SubagentRunParams {
+        let result = run_subagent(SubagentRunParams {
             config: agent_config,
             recipe,
             task_config,
@@ -1279,10 +1298,16 @@ impl SummonClient {
             on_message: None,
             notification_tx: Some(notif_tx),
         })
-        .await
-        .map_err(|e| format!("Delegation failed: {}", e))?;
+        .await;
+        let text = result
+            .to_text()
+            .map_err(|e| format!("Delegation failed: {}", e))?;
 
-        Ok(vec![Content::text(result)])
+        // The structured result lets callers read the status, usage and changed files
+        // without parsing the text
+        let mut tool_result = CallToolResult::success(vec![Content::text(text)]);
+        tool_result.structured_content = serde_json::to_value(&result).ok();
+        Ok(tool_result)
     }
 
     fn validate_delegate_params(&self, params: &DelegateParams) -> Result<(), String> {
@@ -1490,6 +1515,10 @@ impl SummonClient {
             goose_provider: params.provider.clone(),
             temperature: params.temperature,
             max_turns: None,
+            sandbox: None,
+            permission_profile: None,
+            prompt_sections: None,
+            context_recovery: None,
         });
 
         let mut builder = Recipe::builder()
@@ -1535,7 +1564,9 @@ impl SummonClient {
         let max_turns = self.resolve_max_turns(session);
 
         let task_config = TaskConfig::new(provider, &session.id, &session.working_dir, extensions)
-            .with_max_turns(Some(max_turns));
+            .with_max_turns(Some(max_turns))
+            .with_allowed_tools(params.tools.clone())
+            .with_token_budget(params.max_tokens);
 
         Ok(task_config)
     }
@@ -1821,8 +1852,11 @@ impl McpClientTrait for SummonClient {
         _working_dir: Option<&str>,
         cancellation_token: CancellationToken,
     ) -> Result<CallToolResult, Error> {
-        let content = match name {
-            "load" => self.handle_load(session_id, arguments).await,
+        let result = match name {
+            "load" => self
+                .handle_load(session_id, arguments)
+                .await
+                .map(CallToolResult::success),
             "delegate" => {
                 self.handle_delegate(session_id, arguments, cancellation_token)
                     .await
@@ -1830,8 +1864,8 @@ impl McpClientTrait for SummonClient {
             _ => Err(format!("Unknown tool: {}", name)),
         };
 
-        match content {
-            Ok(content) => Ok(CallToolResult::success(content)),
+        match result {
+            Ok(result) => Ok(result),
             Err(error) => Ok(CallToolResult::error(vec![Content::text(format!(
                 "Error: {}",
                 error
@@ -1936,6 +1970,19 @@ Skill body here."#;
         assert_eq!(source.name, "test-skill");
         assert_eq!(source.kind, SourceKind::Skill);
         assert!(source.content.contains("Skill body"));
+        assert_eq!(source.description, "A test skill");
+
+        let skill = r#"---
+name: release-notes
+description: Drafts release notes
+when-to-use: the user asks for a changelog
+---
+Read scripts/collect.sh first."#;
+        let source = parse_skill_content(skill, PathBuf::new()).unwrap();
+        assert_eq!(
+            source.description,
+            "Drafts release notes. Use when: the user asks for a changelog"
+        );
 
         let agent = r#"---
 name: reviewer
@@ -2277,9 +2324,11 @@ You review code."#;
             instructions: instructions.map(String::from),
             parameters: None,
             extensions: None,
+            tools: None,
             provider: None,
             model: None,
             temperature: None,
+            max_tokens: None,
             r#async: false,
         };
 
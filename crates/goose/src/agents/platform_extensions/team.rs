@@ -0,0 +1,774 @@
+//! Team Extension - several agents working through a shared task board
+//!
+//! The lead session spawns teammates, each running as its own subagent with an optional
+//! provider and model. Every member can add, claim and complete tasks on the board and
+//! send messages to the others. A teammate that finds no open task to claim fires the
+//! `TeammateIdle` hook.
+//!
+//! Tasks a teammate still holds when it stops go back to open. The board lives as long as
+//! the lead's extension: it is dropped when the lead session's agent is.
+
+use crate::agents::extension::PlatformExtensionContext;
+use crate::agents::mcp_client::{Error, McpClientTrait};
+use crate::agents::subagent_handler::{run_subagent, SubagentRunParams};
+use crate::agents::subagent_task_config::TaskConfig;
+use crate::agents::AgentConfig;
+use crate::config::Config;
+use crate::hooks::{HookEvent, HookRuntime};
+use crate::providers;
+use crate::recipe::Recipe;
+use crate::session::extension_data::EnabledExtensionsState;
+use crate::session::{Session, SessionType};
+use anyhow::Result;
+use async_trait::async_trait;
+use indoc::indoc;
+use rmcp::model::{
+    CallToolResult, Content, Implementation, InitializeResult, JsonObject, ListToolsResult,
+    ServerCapabilities, Tool, ToolAnnotations,
+};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{LazyLock, Mutex};
+use tokio_util::sync::CancellationToken;
+
+pub static EXTENSION_NAME: &str = "team";
+
+/// The member name of the session that created the team
+pub const LEAD_NAME: &str = "lead";
+
+static TEAMS: LazyLock<Mutex<HashMap<String, Team>>> = LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Open,
+    Claimed,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamTask {
+    pub id: usize,
+    pub description: String,
+    pub status: TaskStatus,
+    pub owner: Option<String>,
+    pub result: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TeamMessage {
+    pub from: String,
+    /// `None` for a message to the whole team
+    pub to: Option<String>,
+    pub text: String,
+}
+
+/// The tasks and messages shared by the members of a team
+#[derive(Debug, Default)]
+pub struct TaskBoard {
+    tasks: Vec<TeamTask>,
+    messages: Vec<TeamMessage>,
+    /// How far into `messages` each member has read
+    read: HashMap<String, usize>,
+}
+
+impl TaskBoard {
+    pub fn add_task(&mut self, description: &str) -> usize {
+        let id = self.tasks.len() + 1;
+        self.tasks.push(TeamTask {
+            id,
+            description: description.to_string(),
+            status: TaskStatus::Open,
+            owner: None,
+            result: None,
+        });
+        id
+    }
+
+    /// Claim a task for a member: the given one, or else the first open task. Returns
+    /// `None` when no open task is left.
+    pub fn claim(&mut self, member: &str, id: Option<usize>) -> Result<Option<TeamTask>, String> {
+        let task = match id {
+            Some(id) => {
+                let task = self.task_mut(id)?;
+                if task.status != TaskStatus::Open {
+                    return Err(format!(
+                        "Task {} is not open ({:?} by {})",
+                        id,
+                        task.status,
+                        task.owner.as_deref().unwrap_or("unknown")
+                    ));
+                }
+                task
+            }
+            None => match self
+                .tasks
+                .iter_mut()
+                .find(|task| task.status == TaskStatus::Open)
+            {
+                Some(task) => task,
+                None => return Ok(None),
+            },
+        };
+        task.status = TaskStatus::Claimed;
+        task.owner = Some(member.to_string());
+        Ok(Some(task.clone()))
+    }
+
+    pub fn complete(&mut self, member: &str, id: usize, result: &str) -> Result<(), String> {
+        let task = self.task_mut(id)?;
+        match &task.owner {
+            Some(owner) if owner == member && task.status == TaskStatus::Claimed => {
+                task.status = TaskStatus::Done;
+                task.result = Some(result.to_string());
+                Ok(())
+            }
+            Some(owner) if owner != member => {
+                Err(format!("Task {} is claimed by {}, not you", id, owner))
+            }
+            _ => Err(format!("Claim task {} before completing it", id)),
+        }
+    }
+
+    /// Reopen the tasks a member claimed but did not complete. Returns their ids.
+    pub fn release(&mut self, member: &str) -> Vec<usize> {
+        self.tasks
+            .iter_mut()
+            .filter(|task| {
+                task.status == TaskStatus::Claimed && task.owner.as_deref() == Some(member)
+            })
+            .map(|task| {
+                task.status = TaskStatus::Open;
+                task.owner = None;
+                task.id
+            })
+            .collect()
+    }
+
+    pub fn send(&mut self, from: &str, to: Option<&str>, text: &str) {
+        self.messages.push(TeamMessage {
+            from: from.to_string(),
+            to: to.map(str::to_string),
+            text: text.to_string(),
+        });
+    }
+
+    /// Messages sent to a member or the whole team since the member last read them
+    pub fn read_messages(&mut self, member: &str) -> Vec<TeamMessage> {
+        let start = self.read.insert(member.to_string(), self.messages.len());
+        self.messages[start.unwrap_or(0)..]
+            .iter()
+            .filter(|message| Self::is_for(message, member))
+            .cloned()
+            .collect()
+    }
+
+    pub fn unread_count(&self, member: &str) -> usize {
+        let start = self.read.get(member).copied().unwrap_or(0);
+        self.messages[start..]
+            .iter()
+            .filter(|message| Self::is_for(message, member))
+            .count()
+    }
+
+    pub fn has_open_tasks(&self) -> bool {
+        self.tasks
+            .iter()
+            .any(|task| task.status == TaskStatus::Open)
+    }
+
+    pub fn render(&self) -> String {
+        if self.tasks.is_empty() {
+            return "The task board is empty.".to_string();
+        }
+        self.tasks
+            .iter()
+            .map(|task| {
+                let mut line = format!("#{} [{:?}] {}", task.id, task.status, task.description);
+                if let Some(owner) = &task.owner {
+                    line.push_str(&format!(" (owner: {})", owner));
+                }
+                if let Some(result) = &task.result {
+                    line.push_str(&format!("\n    result: {}", result));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn is_for(message: &TeamMessage, member: &str) -> bool {
+        message.from != member && message.to.as_deref().is_none_or(|to| to == member)
+    }
+
+    fn task_mut(&mut self, id: usize) -> Result<&mut TeamTask, String> {
+        self.tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .ok_or_else(|| format!("No task {} on the board", id))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Team {
+    board: TaskBoard,
+    /// Teammate names by their session id
+    members: HashMap<String, String>,
+}
+
+/// The team a session belongs to and its member name there. A session that is not a
+/// teammate leads its own team.
+fn membership(teams: &HashMap<String, Team>, session_id: &str) -> (String, String) {
+    teams
+        .iter()
+        .find_map(|(team_id, team)| {
+            team.members
+                .get(session_id)
+                .map(|name| (team_id.clone(), name.clone()))
+        })
+        .unwrap_or_else(|| (session_id.to_string(), LEAD_NAME.to_string()))
+}
+
+/// Run `f` against the board of the session's team, with the session's member name
+fn with_board<T>(session_id: &str, f: impl FnOnce(&mut TaskBoard, &str) -> T) -> T {
+    let mut teams = TEAMS.lock().unwrap_or_else(|e| e.into_inner());
+    let (team_id, member) = membership(&teams, session_id);
+    let team = teams.entry(team_id).or_default();
+    f(&mut team.board, &member)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AddTaskParams {
+    /// What needs to be done, specific enough for a teammate to pick up
+    description: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ClaimTaskParams {
+    /// The task to claim. Omit to claim the first open task.
+    #[serde(default)]
+    id: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CompleteTaskParams {
+    id: usize,
+    /// A short summary of what was done
+    result: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SendMessageParams {
+    /// The member to send to. Omit to send to the whole team.
+    #[serde(default)]
+    to: Option<String>,
+    text: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct SpawnParams {
+    /// A short unique name for the teammate
+    name: String,
+    /// Extra guidance for this teammate, such as the kind of tasks to focus on
+    #[serde(default)]
+    instructions: Option<String>,
+    /// Override the LLM provider for this teammate
+    #[serde(default)]
+    provider: Option<String>,
+    /// Override the model for this teammate
+    #[serde(default)]
+    model: Option<String>,
+}
+
+pub struct TeamClient {
+    info: InitializeResult,
+    context: PlatformExtensionContext,
+    teammates: Mutex<Vec<CancellationToken>>,
+    /// Sessions that used this extension, whose boards go when it does
+    sessions: Mutex<HashSet<String>>,
+}
+
+impl Drop for TeamClient {
+    fn drop(&mut self) {
+        if let Ok(teammates) = self.teammates.lock() {
+            for token in teammates.iter() {
+                token.cancel();
+            }
+        }
+        if let Ok(sessions) = self.sessions.lock() {
+            let mut teams = TEAMS.lock().unwrap_or_else(|e| e.into_inner());
+            for session_id in sessions.iter() {
+                teams.remove(session_id);
+            }
+        }
+    }
+}
+
+impl TeamClient {
+    pub fn new(context: PlatformExtensionContext) -> Result<Self> {
+        let info = InitializeResult::new(ServerCapabilities::builder().enable_tools().build())
+            .with_server_info(
+                Implementation::new(EXTENSION_NAME.to_string(), "1.0.0".to_string())
+                    .with_title("Team"),
+            )
+            .with_instructions(
+                indoc! {r#"
+                Work with a team of agents on a shared task board.
+
+                As the lead:
+                - Break the work into independent tasks with add_task
+                - Start teammates with spawn; each claims and completes tasks on its own
+                - Follow progress with list_tasks and read_messages
+
+                As a teammate:
+                - claim_task, do the work, then complete_task with a short result
+                - Repeat until claim_task reports no open work
+                - Use send_message to ask or tell other members something
+            "#}
+                .to_string(),
+            );
+
+        Ok(Self {
+            info,
+            context,
+            teammates: Mutex::new(Vec::new()),
+            sessions: Mutex::new(HashSet::new()),
+        })
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<Session, String> {
+        self.context
+            .session_manager
+            .get_session(session_id, false)
+            .await
+            .map_err(|e| format!("Failed to get session: {}", e))
+    }
+
+    async fn handle_claim_task(
+        &self,
+        session_id: &str,
+        params: ClaimTaskParams,
+        cancellation_token: CancellationToken,
+    ) -> Result<String, String> {
+        let (claimed, member) = with_board(session_id, |board, member| {
+            board
+                .claim(member, params.id)
+                .map(|task| (task, member.to_string()))
+        })?;
+        if let Some(task) = claimed {
+            return Ok(format!("Claimed task #{}: {}", task.id, task.description));
+        }
+        if member == LEAD_NAME {
+            return Ok("No open tasks on the board.".to_string());
+        }
+
+        let session = self.get_session(session_id).await?;
+        let lead_session_id = {
+            let teams = TEAMS.lock().unwrap_or_else(|e| e.into_inner());
+            membership(&teams, session_id).0
+        };
+        let hooks = HookRuntime::load(&session.working_dir);
+        let outcome = hooks
+            .emit(
+                HookEvent::TeammateIdle {
+                    session_id: lead_session_id,
+                    teammate_id: session_id.to_string(),
+                    teammate_name: member,
+                    cwd: session.working_dir.clone(),
+                },
+                &session.working_dir,
+                cancellation_token,
+            )
+            .await;
+
+        let mut text = if outcome.blocked {
+            "No open tasks, but a TeammateIdle hook asked you to keep working. Check \
+             read_messages and list_tasks before you stop."
+                .to_string()
+        } else {
+            "No open tasks left. Finish by summarizing what you completed.".to_string()
+        };
+        if let Some(context) = outcome.context {
+            text.push_str(&format!("\n\n{}", context));
+        }
+        Ok(text)
+    }
+
+    async fn handle_spawn(&self, session_id: &str, params: SpawnParams) -> Result<String, String> {
+        let name = params.name.trim().to_string();
+        if name.is_empty() || name == LEAD_NAME {
+            return Err(format!("'{}' is not a valid teammate name", params.name));
+        }
+
+        let session = self.get_session(session_id).await?;
+        if session.session_type == SessionType::SubAgent {
+            return Err("Only the lead can spawn teammates".to_string());
+        }
+        {
+            let teams = TEAMS.lock().unwrap_or_else(|e| e.into_inner());
+            if teams
+                .get(session_id)
+                .is_some_and(|team| team.members.values().any(|member| *member == name))
+            {
+                return Err(format!("A teammate named '{}' already exists", name));
+            }
+        }
+
+        let provider_name = params
+            .provider
+            .clone()
+            .or_else(|| {
+                Config::global()
+                    .get_param::<String>("GOOSE_SUBAGENT_PROVIDER")
+                    .ok()
+            })
+            .or_else(|| session.provider_name.clone())
+            .ok_or("No provider configured")?;
+        let mut model_config = match session.model_config.clone() {
+            Some(config) => config,
+            None => crate::model::ModelConfig::new("default")
+                .map(|c| c.with_canonical_limits(&provider_name))
+                .map_err(|e| format!("Failed to build model config: {}", e))?,
+        };
+        if let Some(model) = &params.model {
+            model_config.model_name = model.clone();
+        }
+        let model_name = model_config.model_name.clone();
+        let provider = providers::create(&provider_name, model_config, Vec::new())
+            .await
+            .map_err(|e| format!("Failed to create provider: {}", e))?;
+
+        let extensions = EnabledExtensionsState::extensions_or_default(
+            Some(&session.extension_data),
+            Config::global(),
+        );
+        let task_config = TaskConfig::new(provider, &session.id, &session.working_dir, extensions);
+        let recipe = Recipe::builder()
+            .version("1.0.0")
+            .title(format!("Teammate {}", name))
+            .description("A team member working through the shared task board")
+            .prompt(teammate_prompt(&name, params.instructions.as_deref()))
+            .build()
+            .map_err(|e| format!("Failed to build recipe: {}", e))?;
+
+        let teammate_session = self
+            .context
+            .session_manager
+            .create_session(
+                session.working_dir.clone(),
+                format!("Teammate {}", name),
+                SessionType::SubAgent,
+            )
+            .await
+            .map_err(|e| format!("Failed to create teammate session: {}", e))?;
+        TEAMS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(session_id.to_string())
+            .or_default()
+            .members
+            .insert(teammate_session.id.clone(), name.clone());
+
+        let agent_config = AgentConfig::new(
+            self.context.session_manager.clone(),
+            crate::config::permission::PermissionManager::instance(),
+            None,
+            crate::config::GooseMode::Auto,
+            true, // disable session naming for subagents
+            crate::agents::GoosePlatform::GooseCli,
+        );
+        let token = CancellationToken::new();
+        if let Ok(mut teammates) = self.teammates.lock() {
+            teammates.push(token.clone());
+        }
+
+        let team_id = session_id.to_string();
+        let teammate = name.clone();
+        tokio::spawn(async move {
+            let result = run_subagent(SubagentRunParams {
+                config: agent_config,
+                recipe,
+                task_config,
+                return_last_only: true,
+                session_id: teammate_session.id,
+                cancellation_token: Some(token),
+                on_message: None,
+                notification_tx: None,
+            })
+            .await;
+            let mut report = match &result.error {
+                Some(error) => format!("Stopped ({}): {}", result.status.as_str(), error),
+                None => format!("Stopped ({}): {}", result.status.as_str(), result.output),
+            };
+            if let Some(follow_up) = &result.follow_up {
+                report.push_str(&format!("\nNeeds follow-up: {}", follow_up));
+            }
+            if let Some(team) = TEAMS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get_mut(&team_id)
+            {
+                let released = team.board.release(&teammate);
+                if !released.is_empty() {
+                    let ids = released
+                        .iter()
+                        .map(|id| format!("#{}", id))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    report.push_str(&format!("\nReopened unfinished tasks: {}", ids));
+                }
+                team.board.send(&teammate, Some(LEAD_NAME), &report);
+            }
+        });
+
+        Ok(format!(
+            "Spawned teammate '{}' ({}/{}). It will work through open tasks and message you when it stops.",
+            name, provider_name, model_name
+        ))
+    }
+
+    fn get_tools() -> Vec<Tool> {
+        fn tool<T: JsonSchema>(name: &str, description: &str, read_only: bool) -> Tool {
+            let schema =
+                serde_json::to_value(schema_for!(T)).expect("Failed to serialize team tool schema");
+            Tool::new(
+                name.to_string(),
+                description.to_string(),
+                schema.as_object().unwrap().clone(),
+            )
+            .annotate(ToolAnnotations::from_raw(
+                None,
+                Some(read_only),
+                Some(false),
+                Some(false),
+                Some(false),
+            ))
+        }
+
+        #[derive(JsonSchema)]
+        struct NoParams {}
+
+        vec![
+            tool::<AddTaskParams>(
+                "add_task",
+                "Add a task to the team's shared board.",
+                false,
+            ),
+            tool::<NoParams>(
+                "list_tasks",
+                "Show every task on the board with its status, owner and result.",
+                true,
+            ),
+            tool::<ClaimTaskParams>(
+                "claim_task",
+                "Claim an open task so no other member works on it. Reports when no open work is left.",
+                false,
+            ),
+            tool::<CompleteTaskParams>(
+                "complete_task",
+                "Mark a task you claimed as done, with a short summary of the result.",
+                false,
+            ),
+            tool::<SendMessageParams>(
+                "send_message",
+                "Send a message to one team member, or to the whole team.",
+                false,
+            ),
+            tool::<NoParams>(
+                "read_messages",
+                "Read the messages sent to you or the whole team since you last checked.",
+                false,
+            ),
+            tool::<SpawnParams>(
+                "spawn",
+                "Start a teammate that works through the board on its own. Lead only.",
+                false,
+            ),
+        ]
+    }
+}
+
+fn teammate_prompt(name: &str, instructions: Option<&str>) -> String {
+    let mut prompt = format!(
+        "You are '{}', a member of a team of agents sharing a task board. The team lead is \
+         '{}'.\n\nRepeat until claim_task reports no open work: claim a task with claim_task, \
+         do it, then record the outcome with complete_task. Check read_messages between \
+         tasks and use send_message when you need something from another member. When \
+         you stop, summarize the tasks you completed.",
+        name, LEAD_NAME
+    );
+    if let Some(instructions) = instructions.filter(|i| !i.trim().is_empty()) {
+        prompt.push_str(&format!(
+            "\n\nInstructions from the lead:\n{}",
+            instructions
+        ));
+    }
+    prompt
+}
+
+fn parse_params<T: for<'de> Deserialize<'de>>(arguments: Option<JsonObject>) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::Object(arguments.unwrap_or_default()))
+        .map_err(|e| format!("Invalid parameters: {}", e))
+}
+
+#[async_trait]
+impl McpClientTrait for TeamClient {
+    async fn list_tools(
+        &self,
+        _session_id: &str,
+        _next_cursor: Option<String>,
+        _cancellation_token: CancellationToken,
+    ) -> Result<ListToolsResult, Error> {
+        Ok(ListToolsResult {
+            tools: Self::get_tools(),
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        session_id: &str,
+        name: &str,
+        arguments: Option<JsonObject>,
+        _working_dir: Option<&str>,
+        cancellation_token: CancellationToken,
+    ) -> Result<CallToolResult, Error> {
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(session_id.to_string());
+        }
+        let result = match name {
+            "add_task" => parse_params::<AddTaskParams>(arguments).map(|params| {
+                let id = with_board(session_id, |board, _| board.add_task(&params.description));
+                format!("Added task #{}", id)
+            }),
+            "list_tasks" => Ok(with_board(session_id, |board, _| board.render())),
+            "claim_task" => match parse_params(arguments) {
+                Ok(params) => {
+                    self.handle_claim_task(session_id, params, cancellation_token)
+                        .await
+                }
+                Err(e) => Err(e),
+            },
+            "complete_task" => parse_params::<CompleteTaskParams>(arguments).and_then(|params| {
+                with_board(session_id, |board, member| {
+                    board.complete(member, params.id, &params.result)
+                })
+                .map(|_| format!("Completed task #{}", params.id))
+            }),
+            "send_message" => parse_params::<SendMessageParams>(arguments).map(|params| {
+                with_board(session_id, |board, member| {
+                    board.send(member, params.to.as_deref(), &params.text)
+                });
+                "Message sent".to_string()
+            }),
+            "read_messages" => Ok(with_board(session_id, |board, member| {
+                let messages = board.read_messages(member);
+                if messages.is_empty() {
+                    return "No new messages.".to_string();
+                }
+                messages
+                    .iter()
+                    .map(|message| match &message.to {
+                        Some(_) => format!("{}: {}", message.from, message.text),
+                        None => format!("{} (to everyone): {}", message.from, message.text),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })),
+            "spawn" => match parse_params(arguments) {
+                Ok(params) => self.handle_spawn(session_id, params).await,
+                Err(e) => Err(e),
+            },
+            _ => Err(format!("Unknown tool: {}", name)),
+        };
+
+        match result {
+            Ok(text) => Ok(CallToolResult::success(vec![Content::text(text)])),
+            Err(error) => Ok(CallToolResult::error(vec![Content::text(format!(
+                "Error: {}",
+                error
+            ))])),
+        }
+    }
+
+    fn get_info(&self) -> Option<&InitializeResult> {
+        Some(&self.info)
+    }
+
+    async fn get_moim(&self, session_id: &str) -> Option<String> {
+        let teams = TEAMS.lock().unwrap_or_else(|e| e.into_inner());
+        let (team_id, member) = membership(&teams, session_id);
+        let board = &teams.get(&team_id)?.board;
+        let count = |status| {
+            board
+                .tasks
+                .iter()
+                .filter(|task| task.status == status)
+                .count()
+        };
+        Some(format!(
+            "Team board: {} open, {} claimed, {} done. {} unread message(s) for you ({}).\n",
+            count(TaskStatus::Open),
+            count(TaskStatus::Claimed),
+            count(TaskStatus::Done),
+            board.unread_count(&member),
+            member
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_and_completes_tasks() {
+        let mut board = TaskBoard::default();
+        let first = board.add_task("write the parser");
+        let second = board.add_task("write the tests");
+
+        let claimed = board.claim("alice", None).unwrap().unwrap();
+        assert_eq!(claimed.id, first);
+        assert!(board.claim("bob", Some(first)).is_err());
+        assert_eq!(board.claim("bob", None).unwrap().unwrap().id, second);
+        assert!(board.claim("carol", None).unwrap().is_none());
+        assert!(!board.has_open_tasks());
+
+        assert!(board.complete("bob", first, "done").is_err());
+        board
+            .complete("alice", first, "parser in src/parse.rs")
+            .unwrap();
+        assert!(board.render().contains("result: parser in src/parse.rs"));
+    }
+
+    #[test]
+    fn delivers_messages_once_to_their_recipients() {
+        let mut board = TaskBoard::default();
+        board.send(LEAD_NAME, None, "standup in five");
+        board.send("alice", Some("bob"), "can you review #2?");
+
+        assert_eq!(board.unread_count("bob"), 2);
+        assert_eq!(board.read_messages("bob").len(), 2);
+        assert!(board.read_messages("bob").is_empty());
+
+        let for_alice = board.read_messages("alice");
+        assert_eq!(for_alice.len(), 1);
+        assert_eq!(for_alice[0].from, LEAD_NAME);
+    }
+
+    #[test]
+    fn releases_unfinished_claims() {
+        let mut board = TaskBoard::default();
+        let first = board.add_task("write the parser");
+        let second = board.add_task("write the tests");
+        board.claim("alice", Some(first)).unwrap();
+        board.claim("alice", Some(second)).unwrap();
+        board.complete("alice", first, "done").unwrap();
+
+        assert_eq!(board.release("alice"), vec![second]);
+        assert!(board.has_open_tasks());
+        assert_eq!(board.claim("bob", None).unwrap().unwrap().id, second);
+    }
+}
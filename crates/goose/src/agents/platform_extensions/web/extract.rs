@@ -0,0 +1,182 @@
+//! Pulling the readable text out of an HTML page.
+//!
+//! Scripts, styles, navigation and other page furniture are dropped, the article or main
+//! element is kept when the page has one, and what remains becomes plain text with markdown
+//! headings, list items and links.
+
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+use url::Url;
+
+/// Elements that never hold the content of a page
+const NOISE_TAGS: [&str; 11] = [
+    "script", "style", "noscript", "svg", "nav", "footer", "aside", "form", "iframe", "template",
+    "button",
+];
+
+static COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static NOISE: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    NOISE_TAGS
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b.*?</{tag}\s*>")).unwrap())
+        .collect()
+});
+static TITLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title\s*>").unwrap());
+static ARTICLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*?)</article\s*>").unwrap());
+static MAIN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*?)</main\s*>").unwrap());
+static BODY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").unwrap());
+static LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)<a\b[^>]*?\bhref\s*=\s*["']([^"']*)["'][^>]*>(.*?)</a\s*>"#).unwrap()
+});
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").unwrap());
+static LIST_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+static LINE_BREAK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)<br\s*/?>").unwrap());
+static BLOCK_END: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)</(p|div|section|header|tr|ul|ol|table|blockquote|pre|figure)\s*>").unwrap()
+});
+static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+static ENTITY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"&(#[xX][0-9a-fA-F]+|#[0-9]+|[a-zA-Z]+);").unwrap());
+static SPACES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[ \t\r\f\v]+").unwrap());
+static BLANK_LINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n{3,}").unwrap());
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Extracted {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// The readable text of `html`, with links resolved against `base`
+pub fn extract(html: &str, base: &Url) -> Extracted {
+    let title = TITLE
+        .captures(html)
+        .map(|captures| to_text(&captures[1], base))
+        .filter(|title| !title.is_empty());
+
+    let mut html = COMMENT.replace_all(html, "").into_owned();
+    for noise in NOISE.iter() {
+        html = noise.replace_all(&html, "").into_owned();
+    }
+    // The longest article is most likely the content, not a teaser next to it
+    let content = ARTICLE
+        .captures_iter(&html)
+        .map(|captures| captures.get(1).unwrap().as_str())
+        .max_by_key(|article| article.len())
+        .or_else(|| MAIN.captures(&html).map(|c| c.get(1).unwrap().as_str()))
+        .or_else(|| BODY.captures(&html).map(|c| c.get(1).unwrap().as_str()))
+        .unwrap_or(&html);
+
+    Extracted {
+        title,
+        text: to_text(content, base),
+    }
+}
+
+/// `html` as plain text with markdown headings, list items and links
+pub fn to_text(html: &str, base: &Url) -> String {
+    let text = LINK.replace_all(html, |captures: &Captures| {
+        let label = inline_text(&captures[2]);
+        let href = decode_entities(captures[1].trim());
+        match base.join(&href) {
+            Ok(url) if !label.is_empty() && matches!(url.scheme(), "http" | "https") => {
+                format!("[{}]({})", label, url)
+            }
+            _ => label,
+        }
+    });
+    let text = HEADING.replace_all(&text, |captures: &Captures| {
+        let level: usize = captures[1].parse().unwrap_or(1);
+        format!(
+            "\n\n{} {}\n\n",
+            "#".repeat(level),
+            inline_text(&captures[2])
+        )
+    });
+    let text = LIST_ITEM.replace_all(&text, "\n- ");
+    let text = LINE_BREAK.replace_all(&text, "\n");
+    let text = BLOCK_END.replace_all(&text, "\n\n");
+    let text = TAG.replace_all(&text, "");
+    let text = decode_entities(&text);
+
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| SPACES.replace_all(line, " ").trim().to_string())
+        .collect();
+    BLANK_LINES
+        .replace_all(&lines.join("\n"), "\n\n")
+        .trim()
+        .to_string()
+}
+
+/// Text of an inline fragment, on one line
+pub fn inline_text(html: &str) -> String {
+    let text = decode_entities(&TAG.replace_all(html, ""));
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITY
+        .replace_all(text, |captures: &Captures| {
+            let entity = &captures[1];
+            let decoded = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "ndash" => Some('–'),
+                    "mdash" => Some('—'),
+                    "hellip" => Some('…'),
+                    "lsquo" => Some('‘'),
+                    "rsquo" => Some('’'),
+                    "ldquo" => Some('“'),
+                    "rdquo" => Some('”'),
+                    "copy" => Some('©'),
+                    _ => None,
+                }
+            };
+            match decoded {
+                Some(c) => c.to_string(),
+                None => captures[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_article_and_drops_page_furniture() {
+        let html = r#"<html><head><title>Release notes &amp; more</title>
+            <style>body { color: red }</style><script>track()</script></head>
+            <body><nav><a href="/">Home</a></nav>
+            <article><h1>goose 2.0</h1>
+            <p>Adds <b>web</b> tools.<br>See <a href="/docs/web">the docs</a> &mdash; or
+            <a href="javascript:void(0)">nothing</a>.</p>
+            <ul><li>web_fetch</li><li>web_search</li></ul></article>
+            <footer>&copy; 2026</footer></body></html>"#;
+        let page = extract(html, &Url::parse("https://example.com/blog/").unwrap());
+
+        assert_eq!(page.title.as_deref(), Some("Release notes & more"));
+        assert_eq!(
+            page.text,
+            "# goose 2.0\n\nAdds web tools.\nSee [the docs](https://example.com/docs/web) — or\nnothing.\n\n- web_fetch\n- web_search"
+        );
+    }
+}
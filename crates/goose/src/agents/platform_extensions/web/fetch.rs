@@ -0,0 +1,456 @@
+//! Fetching pages for web_fetch.
+//!
+//! Pages are fetched once per origin's robots.txt allowing it, read up to [`MAX_BYTES`] and
+//! kept for [`CACHE_TTL`], so paging through a long document or reading it again later in the
+//! session doesn't hit the site again. A robots.txt that is missing or can't be read allows
+//! everything.
+//!
+//! Only public addresses are fetched: a host that resolves to a loopback, private,
+//! link-local or otherwise internal address is refused, so a page can't point goose at the
+//! local network or a cloud metadata service. Redirects are followed by hand, checking the
+//! address and robots.txt of every hop before requesting it.
+
+use super::extract::extract;
+use anyhow::{anyhow, bail, Result};
+use lru::LruCache;
+use reqwest::header::{HeaderValue, CONTENT_TYPE, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::{Client, Response, StatusCode};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use url::{Host, Url};
+
+pub(super) const GOOSE_USER_AGENT: HeaderValue =
+    HeaderValue::from_static(concat!("goose/", env!("CARGO_PKG_VERSION")));
+/// The name goose looks for in robots.txt user-agent lines
+const ROBOTS_AGENT: &str = "goose";
+
+/// Bodies are cut off after this many bytes
+pub const MAX_BYTES: usize = 5 * 1024 * 1024;
+const CACHE_ENTRIES: usize = 64;
+const CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+const ROBOTS_TTL: Duration = Duration::from_secs(60 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 10;
+
+pub static FETCHER: LazyLock<Fetcher> = LazyLock::new(Fetcher::new);
+
+/// A fetched page, as text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// Where the page was fetched from, after redirects
+    pub url: Url,
+    pub title: Option<String>,
+    pub text: String,
+    /// The body was longer than [`MAX_BYTES`]
+    pub truncated: bool,
+}
+
+pub struct Fetcher {
+    client: Client,
+    pages: Mutex<LruCache<Url, (Instant, Arc<Page>)>>,
+    robots: Mutex<HashMap<String, (Instant, Arc<Robots>)>>,
+}
+
+impl Fetcher {
+    fn new() -> Self {
+        let client = Client::builder()
+            .user_agent(GOOSE_USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(Policy::none())
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            pages: Mutex::new(LruCache::new(NonZeroUsize::new(CACHE_ENTRIES).unwrap())),
+            robots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The page at `url`, from the cache when it was fetched recently
+    pub async fn fetch(&self, url: &Url) -> Result<Arc<Page>> {
+        if let Some(page) = self.cached(url) {
+            return Ok(page);
+        }
+
+        let mut response = self.get(url, true).await?;
+        let status = response.status();
+        if !status.is_success() {
+            bail!("{} returned {}", url, status);
+        }
+        let final_url = response.url().clone();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_ascii_lowercase());
+
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await? {
+            let room = MAX_BYTES - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let body = String::from_utf8_lossy(&body);
+
+        let page = match content_type.as_deref() {
+            Some(kind) if kind.contains("html") => html_page(final_url, &body, truncated),
+            None if body.trim_start().starts_with('<') => html_page(final_url, &body, truncated),
+            Some(kind)
+                if kind.starts_with("text/") || kind.contains("json") || kind.contains("xml") =>
+            {
+                text_page(final_url, &body, truncated)
+            }
+            None => text_page(final_url, &body, truncated),
+            Some(kind) => bail!("Can't read {} content from {}", kind, url),
+        };
+        let page = Arc::new(page);
+        self.pages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .put(url.clone(), (Instant::now(), page.clone()));
+        Ok(page)
+    }
+
+    /// GET `url`, following redirects by hand so that every hop is checked before it is
+    /// requested: it must be a public http(s) address and, for pages, allowed by robots.txt
+    async fn get(&self, url: &Url, obey_robots: bool) -> Result<Response> {
+        let mut current = url.clone();
+        for _ in 0..=MAX_REDIRECTS {
+            check_public(&current).await?;
+            if obey_robots {
+                let robots = self.robots(&current).await;
+                if !robots.allows(&path_and_query(&current)) {
+                    bail!(
+                        "The robots.txt of {} doesn't allow fetching {}",
+                        current.host_str().unwrap_or_default(),
+                        current
+                    );
+                }
+            }
+
+            let response = self.client.get(current.clone()).send().await?;
+            if !response.status().is_redirection() {
+                return Ok(response);
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| anyhow!("{} redirected without a location", current))?;
+            current = current.join(location)?;
+        }
+        bail!("{} redirected too many times", url)
+    }
+
+    fn cached(&self, url: &Url) -> Option<Arc<Page>> {
+        let mut pages = self.pages.lock().unwrap_or_else(|e| e.into_inner());
+        match pages.get(url) {
+            Some((fetched, page)) if fetched.elapsed() < CACHE_TTL => Some(page.clone()),
+            Some(_) => {
+                pages.pop(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn robots(&self, url: &Url) -> Arc<Robots> {
+        let origin = url.origin().ascii_serialization();
+        let cached = self
+            .robots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&origin)
+            .filter(|(fetched, _)| fetched.elapsed() < ROBOTS_TTL)
+            .map(|(_, robots)| robots.clone());
+        if let Some(robots) = cached {
+            return robots;
+        }
+
+        let robots = Arc::new(match self.fetch_robots(&origin).await {
+            Ok(Some(content)) => Robots::parse(&content, ROBOTS_AGENT),
+            Ok(None) => Robots::default(),
+            Err(e) => {
+                tracing::debug!("Couldn't read robots.txt of {}: {}", origin, e);
+                Robots::default()
+            }
+        });
+        self.robots
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(origin, (Instant::now(), robots.clone()));
+        robots
+    }
+
+    async fn fetch_robots(&self, origin: &str) -> Result<Option<String>> {
+        let url = Url::parse(origin)?.join("/robots.txt")?;
+        let response = self.get(&url, false).await?;
+        match response.status() {
+            status if status.is_success() => Ok(Some(response.text().await?)),
+            status if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS => {
+                Ok(None)
+            }
+            status => Err(anyhow!("robots.txt returned {}", status)),
+        }
+    }
+}
+
+/// Refuse URLs that aren't http(s) or whose host is, or resolves to, an address that isn't
+/// public
+async fn check_public(url: &Url) -> Result<()> {
+    if !matches!(url.scheme(), "http" | "https") {
+        bail!("Only http and https URLs can be fetched");
+    }
+    let addresses: Vec<IpAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![ip.into()],
+        Some(Host::Ipv6(ip)) => vec![ip.into()],
+        Some(Host::Domain(domain)) => {
+            let port = url.port_or_known_default().unwrap_or(443);
+            tokio::net::lookup_host((domain, port))
+                .await?
+                .map(|address| address.ip())
+                .collect()
+        }
+        None => bail!("{} has no host", url),
+    };
+    if addresses.is_empty() {
+        bail!("{} did not resolve", url.host_str().unwrap_or_default());
+    }
+    if let Some(ip) = addresses.iter().find(|ip| !is_public(**ip)) {
+        bail!("Refusing to fetch {}: {} is not a public address", url, ip);
+    }
+    Ok(())
+}
+
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "this network", carrier-grade NAT, benchmarking and reserved ranges
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 198 && (18..20).contains(&b))
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // unique local, link-local and documentation ranges
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+}
+
+fn html_page(url: Url, body: &str, truncated: bool) -> Page {
+    let extracted = extract(body, &url);
+    Page {
+        url,
+        title: extracted.title,
+        text: extracted.text,
+        truncated,
+    }
+}
+
+fn text_page(url: Url, body: &str, truncated: bool) -> Page {
+    Page {
+        url,
+        title: None,
+        text: body.to_string(),
+        truncated,
+    }
+}
+
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// The rules of a robots.txt that apply to goose
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Robots {
+    /// Path pattern and whether it is allowed
+    rules: Vec<(String, bool)>,
+}
+
+impl Robots {
+    /// The rules of the group naming `agent`, or of the `*` group when none does
+    pub fn parse(content: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut named = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut any_named = false;
+        // Agents of the group being read, and whether its rules have started
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((field, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match field.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if in_rules {
+                        agents.clear();
+                        in_rules = false;
+                    }
+                    agents.push(value.to_ascii_lowercase());
+                }
+                field @ ("allow" | "disallow") => {
+                    in_rules = true;
+                    let rules = if agents.contains(&agent) {
+                        any_named = true;
+                        &mut named
+                    } else if agents.iter().any(|a| a == "*") {
+                        &mut wildcard
+                    } else {
+                        continue;
+                    };
+                    // An empty disallow allows everything
+                    if !value.is_empty() {
+                        rules.push((value.to_string(), field == "allow"));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if any_named { named } else { wildcard },
+        }
+    }
+
+    /// Whether `path` may be fetched: the longest matching rule decides, allow winning ties
+    pub fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| rule_matches(pattern, path))
+            .max_by_key(|(pattern, allow)| (pattern.len(), *allow))
+            .is_none_or(|(_, allow)| *allow)
+    }
+}
+
+/// Whether a robots.txt path pattern, with `*` wildcards and a `$` end anchor, matches `path`
+fn rule_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn robots_rules_for_goose_or_everyone() {
+        let content = "
+User-agent: *
+Disallow: /private/
+Allow: /private/press/
+
+User-agent: otherbot
+User-agent: goose
+Disallow: /search
+Disallow: /*.pdf$ # no documents
+Allow: /search/help
+";
+        let goose = Robots::parse(content, ROBOTS_AGENT);
+        assert!(goose.allows("/private/notes"));
+        assert!(!goose.allows("/search?q=x"));
+        assert!(goose.allows("/search/help"));
+        assert!(!goose.allows("/files/report.pdf"));
+        assert!(goose.allows("/files/report.pdf?download=1"));
+
+        let others = Robots::parse(content, "somebot");
+        assert!(!others.allows("/private/notes"));
+        assert!(others.allows("/private/press/release"));
+        assert!(others.allows("/search"));
+
+        assert!(Robots::parse("User-agent: *\nDisallow:\n", ROBOTS_AGENT).allows("/"));
+    }
+
+    #[test]
+    fn only_public_addresses_are_fetched() {
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::ffff:127.0.0.1",
+            "fd00::1",
+            "fe80::1",
+        ] {
+            assert!(!is_public(internal.parse().unwrap()), "{}", internal);
+        }
+        for public in ["93.184.216.34", "8.8.8.8", "2606:4700::1111"] {
+            assert!(is_public(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn internal_hosts_are_refused() {
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://[::1]:8080/",
+            "http://localhost:3000/",
+            "file:///etc/passwd",
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert!(FETCHER.fetch(&url).await.is_err(), "{}", url);
+        }
+    }
+}
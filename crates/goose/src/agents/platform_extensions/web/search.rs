@@ -0,0 +1,188 @@
+//! Search backends for web_search.
+//!
+//! `GOOSE_WEB_SEARCH` picks the backend:
+//!
+//! ```yaml
+//! GOOSE_WEB_SEARCH:
+//!   backend: searxng
+//!   url: https://searx.example.com
+//! ```
+//!
+//! `brave` queries the Brave Search API with the `BRAVE_API_KEY` secret, and is the default
+//! when that secret is set. `searxng` queries a SearXNG instance, which needs its JSON format
+//! enabled. `provider` leaves searching to the model provider's own web search, so goose
+//! doesn't offer a web_search tool of its own.
+
+use super::extract::inline_text;
+use super::fetch::GOOSE_USER_AGENT;
+use crate::config::{Config, ConfigError};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::Duration;
+
+pub const WEB_SEARCH_CONFIG_KEY: &str = "GOOSE_WEB_SEARCH";
+pub const BRAVE_API_KEY_SECRET: &str = "BRAVE_API_KEY";
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, PartialEq, Deserialize, JsonSchema)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SearchConfig {
+    Brave,
+    Searxng { url: String },
+    Provider,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>>;
+}
+
+/// The configured search, or None when there is none
+pub fn configured() -> Option<SearchConfig> {
+    match Config::global().get_param::<SearchConfig>(WEB_SEARCH_CONFIG_KEY) {
+        Ok(config) => Some(config),
+        Err(ConfigError::NotFound(_)) => Config::global()
+            .get_secret::<String>(BRAVE_API_KEY_SECRET)
+            .ok()
+            .map(|_| SearchConfig::Brave),
+        Err(e) => {
+            tracing::warn!("Ignoring malformed {}: {}", WEB_SEARCH_CONFIG_KEY, e);
+            None
+        }
+    }
+}
+
+/// The backend goose searches with itself, None when the provider searches instead
+pub fn backend(config: &SearchConfig) -> Result<Option<Box<dyn SearchBackend>>> {
+    let client = Client::builder()
+        .user_agent(GOOSE_USER_AGENT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()?;
+    let backend: Box<dyn SearchBackend> = match config {
+        SearchConfig::Brave => {
+            let api_key = Config::global()
+                .get_secret::<String>(BRAVE_API_KEY_SECRET)
+                .map_err(|_| anyhow!("Brave search needs the {} secret", BRAVE_API_KEY_SECRET))?;
+            Box::new(BraveSearch { client, api_key })
+        }
+        SearchConfig::Searxng { url } => Box::new(SearxngSearch {
+            client,
+            url: url.trim_end_matches('/').to_string(),
+        }),
+        SearchConfig::Provider => return Ok(None),
+    };
+    Ok(Some(backend))
+}
+
+pub struct BraveSearch {
+    client: Client,
+    api_key: String,
+}
+
+#[async_trait]
+impl SearchBackend for BraveSearch {
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let response: Value = self
+            .client
+            .get(BRAVE_ENDPOINT)
+            .query(&[("q", query), ("count", &count.to_string())])
+            .header("X-Subscription-Token", &self.api_key)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(results(&response["web"]["results"], "description", count))
+    }
+}
+
+pub struct SearxngSearch {
+    client: Client,
+    url: String,
+}
+
+#[async_trait]
+impl SearchBackend for SearxngSearch {
+    async fn search(&self, query: &str, count: usize) -> Result<Vec<SearchResult>> {
+        let response: Value = self
+            .client
+            .get(format!("{}/search", self.url))
+            .query(&[("q", query), ("format", "json")])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(results(&response["results"], "content", count))
+    }
+}
+
+/// The results in a JSON array of `{title, url, <snippet_field>}` objects
+fn results(items: &Value, snippet_field: &str, count: usize) -> Vec<SearchResult> {
+    let text = |item: &Value, field: &str| inline_text(item[field].as_str().unwrap_or_default());
+    items
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["url"].is_string())
+        .take(count)
+        .map(|item| SearchResult {
+            title: text(item, "title"),
+            url: text(item, "url"),
+            snippet: text(item, snippet_field),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn backend_responses_become_results() {
+        let brave = json!({"web": {"results": [
+            {"title": "goose", "url": "https://example.com/goose",
+             "description": "An <strong>open source</strong> agent &amp; more"},
+            {"title": "no url"},
+        ]}});
+        assert_eq!(
+            results(&brave["web"]["results"], "description", 5),
+            [SearchResult {
+                title: "goose".to_string(),
+                url: "https://example.com/goose".to_string(),
+                snippet: "An open source agent & more".to_string(),
+            }]
+        );
+
+        let searxng = json!({"results": [
+            {"title": "a", "url": "https://a.example", "content": "first"},
+            {"title": "b", "url": "https://b.example", "content": "second"},
+        ]});
+        let found = results(&searxng["results"], "content", 1);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].snippet, "first");
+
+        let config: SearchConfig =
+            serde_yaml::from_str("backend: searxng\nurl: https://searx.example.com\n").unwrap();
+        assert_eq!(
+            config,
+            SearchConfig::Searxng {
+                url: "https://searx.example.com".to_string()
+            }
+        );
+    }
+}
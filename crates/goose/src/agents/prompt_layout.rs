@@ -0,0 +1,199 @@
+//! Which sections make up the system prompt, and in what order.
+//!
+//! The prompt is assembled from named sections. `GOOSE_PROMPT_SECTIONS` arranges them for
+//! every session, and a recipe's `prompt_sections` setting adjusts that for the sessions it
+//! starts, e.g.
+//!
+//! ```yaml
+//! GOOSE_PROMPT_SECTIONS:
+//!   order: [identity, hints, tool_guidance]
+//!   disabled: [response_guidelines]
+//!   overrides:
+//!     identity: You are a release engineer. It is now {{ current_date_time }}.
+//! ```
+//!
+//! Sections left out of `order` follow in their default order. Instructions, hints and mode
+//! rules always come last, together under one "Additional Instructions" heading, so `order`
+//! arranges them among themselves. Overrides are templates rendered with the same context as
+//! the built-in sections.
+
+use crate::config::Config;
+use crate::session::Session;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptSection {
+    /// Who goose is (the `system.md` template)
+    Identity,
+    /// The active extensions and their instructions
+    ToolGuidance,
+    /// How to format responses
+    ResponseGuidelines,
+    /// Instructions added by recipes, extensions and frontends
+    Instructions,
+    /// Project hint files such as .goosehints and AGENTS.md
+    Hints,
+    /// Rules for the current goose mode
+    ModeRules,
+}
+
+impl PromptSection {
+    pub const DEFAULT_ORDER: [PromptSection; 6] = [
+        PromptSection::Identity,
+        PromptSection::ToolGuidance,
+        PromptSection::ResponseGuidelines,
+        PromptSection::Instructions,
+        PromptSection::Hints,
+        PromptSection::ModeRules,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PromptSection::Identity => "identity",
+            PromptSection::ToolGuidance => "tool_guidance",
+            PromptSection::ResponseGuidelines => "response_guidelines",
+            PromptSection::Instructions => "instructions",
+            PromptSection::Hints => "hints",
+            PromptSection::ModeRules => "mode_rules",
+        }
+    }
+
+    /// The built-in template for the section, if it has one
+    pub fn template(self) -> Option<&'static str> {
+        match self {
+            PromptSection::Identity => Some("system.md"),
+            PromptSection::ToolGuidance => Some("tool_guidance.md"),
+            PromptSection::ResponseGuidelines => Some("response_guidelines.md"),
+            _ => None,
+        }
+    }
+
+    /// Sections rendered under the "Additional Instructions" heading
+    pub fn is_additional(self) -> bool {
+        matches!(
+            self,
+            PromptSection::Instructions | PromptSection::Hints | PromptSection::ModeRules
+        )
+    }
+}
+
+impl fmt::Display for PromptSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema, JsonSchema)]
+pub struct PromptLayout {
+    /// Sections to render first, in this order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order: Vec<PromptSection>,
+    /// Sections to leave out of the prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<PromptSection>,
+    /// Templates that replace the built-in content of a section
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub overrides: HashMap<PromptSection, String>,
+}
+
+impl PromptLayout {
+    pub fn from_config() -> Self {
+        Config::global()
+            .get_param::<PromptLayout>("GOOSE_PROMPT_SECTIONS")
+            .unwrap_or_default()
+    }
+
+    /// The configured layout, adjusted by the recipe the session was started from
+    pub fn for_session(session: &Session) -> Self {
+        let layout = Self::from_config();
+        match session
+            .recipe
+            .as_ref()
+            .and_then(|recipe| recipe.settings.as_ref())
+            .and_then(|settings| settings.prompt_sections.as_ref())
+        {
+            Some(recipe_layout) => layout.merged_with(recipe_layout),
+            None => layout,
+        }
+    }
+
+    /// Apply `other` on top of this layout: its order wins when set, disabled sections add up
+    /// and its overrides replace ours
+    pub fn merged_with(mut self, other: &PromptLayout) -> Self {
+        if !other.order.is_empty() {
+            self.order = other.order.clone();
+        }
+        for section in &other.disabled {
+            if !self.disabled.contains(section) {
+                self.disabled.push(*section);
+            }
+        }
+        self.overrides
+            .extend(other.overrides.iter().map(|(k, v)| (*k, v.clone())));
+        self
+    }
+
+    /// The enabled sections in the order they are rendered, the additional ones last
+    pub fn sections(&self) -> Vec<PromptSection> {
+        let mut sections: Vec<PromptSection> = Vec::new();
+        for section in self.order.iter().chain(PromptSection::DEFAULT_ORDER.iter()) {
+            if !sections.contains(section) && !self.disabled.contains(section) {
+                sections.push(*section);
+            }
+        }
+        // Stable, so each group keeps its order
+        sections.sort_by_key(|section| section.is_additional());
+        sections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlisted_sections_follow_in_default_order() {
+        let layout: PromptLayout = serde_yaml::from_str(
+            "order: [hints, identity]\ndisabled: [response_guidelines, mode_rules]",
+        )
+        .unwrap();
+        assert_eq!(
+            layout.sections(),
+            vec![
+                PromptSection::Identity,
+                PromptSection::ToolGuidance,
+                PromptSection::Hints,
+                PromptSection::Instructions,
+            ]
+        );
+    }
+
+    #[test]
+    fn recipe_layout_is_applied_on_top() {
+        let global: PromptLayout =
+            serde_yaml::from_str("disabled: [hints]\noverrides:\n  identity: You are a reviewer.")
+                .unwrap();
+        let recipe: PromptLayout =
+            serde_yaml::from_str("order: [instructions]\ndisabled: [response_guidelines]").unwrap();
+
+        let layout = global.merged_with(&recipe);
+
+        assert_eq!(
+            layout.sections().iter().find(|s| s.is_additional()),
+            Some(&PromptSection::Instructions)
+        );
+        assert!(!layout.sections().contains(&PromptSection::Hints));
+        assert!(!layout
+            .sections()
+            .contains(&PromptSection::ResponseGuidelines));
+        assert_eq!(
+            layout.overrides[&PromptSection::Identity],
+            "You are a reviewer."
+        );
+    }
+}
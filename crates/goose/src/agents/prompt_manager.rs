@@ -4,19 +4,25 @@ use chrono::Utc;
 use indexmap::IndexMap;
 use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use crate::agents::extension::ExtensionInfo;
-use crate::hints::load_hints::{load_hint_files, AGENTS_MD_FILENAME, GOOSE_HINTS_FILENAME};
+use crate::agents::prompt_layout::{PromptLayout, PromptSection};
+use crate::context_mgmt::budget::{
+    fit_sections, ContextBudget, ContextReport, ContextSource, SourceUsage,
+};
+use crate::hints::load_hints::{configured_hint_files, render_hints, HintFile};
+use crate::token_counter::TokenCounter;
 use crate::{
     config::{Config, GooseMode},
     prompt_template,
     utils::sanitize_unicode_tags,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 const MAX_EXTENSIONS: usize = 5;
 const MAX_TOOLS: usize = 50;
+const DEFAULT_IDENTITY: &str = "You are a general-purpose AI agent called goose, created by Block";
 
 pub struct PromptManager {
     system_prompt_override: Option<String>,
@@ -51,8 +57,12 @@ pub struct SystemPromptBuilder<'a, M> {
     frontend_instructions: Option<String>,
     extension_tool_count: Option<(usize, usize)>,
     subagents_enabled: bool,
-    hints: Option<String>,
+    hint_files: Vec<HintFile>,
     code_execution_mode: bool,
+    context_budget: Option<(usize, TokenCounter)>,
+    layout: Option<PromptLayout>,
+    /// The user's customized system.md, which holds the whole prompt
+    custom_system_prompt: Option<String>,
 }
 
 impl<'a> SystemPromptBuilder<'a, PromptManager> {
@@ -87,30 +97,18 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
         self
     }
 
-    pub fn with_hints(mut self, working_dir: &Path) -> Self {
-        let config = Config::global();
-        let hints_filenames = config
-            .get_param::<Vec<String>>("CONTEXT_FILE_NAMES")
-            .unwrap_or_else(|_| {
-                vec![
-                    GOOSE_HINTS_FILENAME.to_string(),
-                    AGENTS_MD_FILENAME.to_string(),
-                ]
-            });
-        let ignore_patterns = {
-            let builder = ignore::gitignore::GitignoreBuilder::new(working_dir);
-            builder.build().unwrap_or_else(|_| {
-                ignore::gitignore::GitignoreBuilder::new(working_dir)
-                    .build()
-                    .expect("Failed to build default gitignore")
-            })
-        };
-
-        let hints = load_hint_files(working_dir, &hints_filenames, &ignore_patterns);
+    /// Add the hint files for `working_dir`, except the `skipped` ones
+    pub fn with_hints(mut self, working_dir: &Path, skipped: &BTreeSet<PathBuf>) -> Self {
+        self.hint_files = configured_hint_files(working_dir)
+            .into_iter()
+            .filter(|file| !skipped.contains(&file.path))
+            .collect();
+        self
+    }
 
-        if !hints.is_empty() {
-            self.hints = Some(hints);
-        }
+    /// Trim the extension and added instructions to their share of the context limit
+    pub fn with_context_budget(mut self, context_limit: usize, counter: TokenCounter) -> Self {
+        self.context_budget = Some((context_limit, counter));
         self
     }
 
@@ -119,7 +117,19 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
         self
     }
 
+    /// Arrange the prompt sections, instead of the configured layout
+    pub fn with_layout(mut self, layout: PromptLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
     pub fn build(self) -> String {
+        self.render().text()
+    }
+
+    /// Render the prompt section by section, along with how it used the context window when
+    /// a budget was set
+    pub fn render(self) -> RenderedPrompt {
         let mut extensions_info = self.extensions_info;
 
         // Add frontend instructions to extensions_info to simplify json rendering
@@ -133,7 +143,7 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
         // Stable tool ordering is important for multi session prompt caching.
         extensions_info.sort_by(|a, b| a.name.cmp(&b.name));
 
-        let sanitized_extensions_info: Vec<ExtensionInfo> = extensions_info
+        let mut sanitized_extensions_info: Vec<ExtensionInfo> = extensions_info
             .into_iter()
             .map(|mut ext_info| {
                 ext_info.instructions = sanitize_unicode_tags(&ext_info.instructions);
@@ -141,6 +151,25 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
             })
             .collect();
 
+        let context_budget = self
+            .context_budget
+            .map(|(context_limit, counter)| (context_limit, counter, ContextBudget::from_config()));
+        // Extensions keep their instructions in the order they appear in the prompt
+        let extensions_usage = context_budget
+            .as_ref()
+            .map(|(context_limit, counter, budget)| {
+                fit_sections(
+                    ContextSource::Extensions,
+                    sanitized_extensions_info
+                        .iter_mut()
+                        .map(|info| (info.name.clone(), &mut info.instructions)),
+                    budget
+                        .tokens_for(ContextSource::Extensions, *context_limit)
+                        .unwrap_or_default(),
+                    counter,
+                )
+            });
+
         let config = Config::global();
         let goose_mode = config.get_goose_mode().unwrap_or(GooseMode::Auto);
 
@@ -160,45 +189,155 @@ impl<'a> SystemPromptBuilder<'a, PromptManager> {
             code_execution_mode: self.code_execution_mode,
         };
 
-        let base_prompt = if let Some(override_prompt) = &self.manager.system_prompt_override {
-            let sanitized_override_prompt = sanitize_unicode_tags(override_prompt);
-            prompt_template::render_string(&sanitized_override_prompt, &context)
-        } else {
-            prompt_template::render_template("system.md", &context)
+        let layout = self.layout.unwrap_or_else(PromptLayout::from_config);
+        let sections = layout.sections();
+
+        // An override set on the manager, or a customized system.md, stands in for all of the
+        // built-in template sections
+        let stand_in = self
+            .manager
+            .system_prompt_override
+            .as_ref()
+            .or(self.custom_system_prompt.as_ref());
+        let mut base: HashMap<PromptSection, String> = HashMap::new();
+        for section in sections.iter().filter(|section| !section.is_additional()) {
+            let content = match (stand_in, section) {
+                (Some(override_prompt), PromptSection::Identity) => {
+                    let sanitized_override_prompt = sanitize_unicode_tags(override_prompt);
+                    prompt_template::render_string(&sanitized_override_prompt, &context)
+                        .unwrap_or_else(|_| DEFAULT_IDENTITY.to_string())
+                }
+                (Some(_), _) => String::new(),
+                (None, section) => match layout.overrides.get(section) {
+                    Some(template) => {
+                        render_override(*section, template, &context).unwrap_or_default()
+                    }
+                    None => section
+                        .template()
+                        .and_then(|name| prompt_template::render_template(name, &context).ok())
+                        .unwrap_or_else(|| match section {
+                            PromptSection::Identity => DEFAULT_IDENTITY.to_string(),
+                            _ => String::new(),
+                        }),
+                },
+            };
+            base.insert(*section, content);
         }
-        .unwrap_or_else(|_| {
-            "You are a general-purpose AI agent called goose, created by Block".to_string()
-        });
 
         let mut system_prompt_extras = self.manager.system_prompt_extras.clone();
+        let hint_files = self.hint_files;
+
+        let report = context_budget.zip(extensions_usage).map(
+            |((context_limit, counter, budget), extensions_usage)| {
+                // The base sections render the extension instructions, which count separately
+                let base_tokens: usize = base
+                    .values()
+                    .map(|content| counter.count_tokens(content))
+                    .sum();
+                let mut sources = vec![
+                    SourceUsage::untrimmed(
+                        ContextSource::SystemPrompt,
+                        base_tokens.saturating_sub(extensions_usage.used),
+                    ),
+                    extensions_usage,
+                    // Already held to GOOSE_HINTS_MAX_BYTES when the files were read
+                    SourceUsage::untrimmed(
+                        ContextSource::Hints,
+                        hint_files
+                            .iter()
+                            .map(|file| counter.count_tokens(&file.content))
+                            .sum(),
+                    ),
+                ];
+                // The earliest added instructions are kept first
+                sources.push(fit_sections(
+                    ContextSource::Instructions,
+                    system_prompt_extras
+                        .iter_mut()
+                        .map(|(key, extra)| (key.clone(), extra)),
+                    budget
+                        .tokens_for(ContextSource::Instructions, context_limit)
+                        .unwrap_or_default(),
+                    &counter,
+                ));
+                system_prompt_extras.retain(|_, extra| !extra.is_empty());
+                ContextReport {
+                    context_limit,
+                    sources,
+                }
+            },
+        );
 
-        // Add hints if provided
-        if let Some(hints) = self.hints {
-            system_prompt_extras.insert("hints".to_string(), hints);
-        }
+        let rendered_sections = sections
+            .into_iter()
+            .filter_map(|section| {
+                let content = if !section.is_additional() {
+                    base.remove(&section).unwrap_or_default()
+                } else if let Some(template) = layout.overrides.get(&section) {
+                    render_override(section, template, &context).unwrap_or_default()
+                } else {
+                    let content = match section {
+                        PromptSection::Instructions => system_prompt_extras
+                            .values()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                            .join("\n\n"),
+                        PromptSection::Hints => render_hints(&hint_files),
+                        PromptSection::ModeRules if goose_mode == GooseMode::Chat => {
+                            "Right now you are in the chat only mode, no access to any tool use and system."
+                                .to_string()
+                        }
+                        _ => String::new(),
+                    };
+                    sanitize_unicode_tags(&content)
+                };
+                (!content.is_empty()).then_some(RenderedSection { section, content })
+            })
+            .collect();
 
-        if goose_mode == GooseMode::Chat {
-            system_prompt_extras.insert(
-                "chat_mode".to_string(),
-                "Right now you are in the chat only mode, no access to any tool use and system."
-                    .to_string(),
-            );
+        RenderedPrompt {
+            sections: rendered_sections,
+            report,
         }
+    }
+}
 
-        if system_prompt_extras.is_empty() {
-            base_prompt
-        } else {
-            let sanitized_system_prompt_extras: Vec<String> = system_prompt_extras
-                .into_values()
-                .map(|extra| sanitize_unicode_tags(&extra))
-                .collect();
-
-            format!(
-                "{}\n\n# Additional Instructions:\n\n{}",
-                base_prompt,
-                sanitized_system_prompt_extras.join("\n\n")
-            )
+fn render_override(
+    section: PromptSection,
+    template: &str,
+    context: &SystemPromptContext,
+) -> Option<String> {
+    prompt_template::render_string(&sanitize_unicode_tags(template), context)
+        .map_err(|e| tracing::warn!("Ignoring the override of the {} section: {}", section, e))
+        .ok()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedSection {
+    pub section: PromptSection,
+    pub content: String,
+}
+
+/// A system prompt split into the sections it was built from
+#[derive(Debug, Clone)]
+pub struct RenderedPrompt {
+    pub sections: Vec<RenderedSection>,
+    /// How the context window was shared out, when the prompt was built with a budget
+    pub report: Option<ContextReport>,
+}
+
+impl RenderedPrompt {
+    pub fn text(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        let mut in_additional = false;
+        for rendered in &self.sections {
+            if rendered.section.is_additional() && !in_additional {
+                parts.push("# Additional Instructions:");
+                in_additional = true;
+            }
+            parts.push(&rendered.content);
         }
+        parts.join("\n\n")
     }
 }
 
@@ -241,8 +380,12 @@ impl PromptManager {
             frontend_instructions: None,
             extension_tool_count: None,
             subagents_enabled: false,
-            hints: None,
+            hint_files: Vec::new(),
             code_execution_mode: false,
+            context_budget: None,
+            layout: None,
+            custom_system_prompt: prompt_template::get_template("system.md")
+                .and_then(|template| template.user_content),
         }
     }
 
@@ -346,6 +489,78 @@ mod tests {
         assert!(result.contains("hidden instructions"));
     }
 
+    #[tokio::test]
+    async fn test_context_budget_trims_extension_instructions() {
+        let counter = crate::token_counter::create_token_counter().await.unwrap();
+        let manager = PromptManager::new();
+        let verbose = ExtensionInfo::new("verbose", &"Use the tools. ".repeat(2_000), false);
+
+        // The default extensions share of 10k tokens is far less than the instructions
+        let rendered = manager
+            .builder()
+            .with_extension(verbose)
+            .with_context_budget(10_000, counter)
+            .render();
+
+        let report = rendered.report.unwrap();
+        let extensions = report
+            .sources
+            .iter()
+            .find(|usage| usage.source == ContextSource::Extensions)
+            .unwrap();
+        assert_eq!(extensions.trimmed, vec!["verbose"]);
+        assert!(extensions.used <= extensions.budget.unwrap());
+        assert!(rendered
+            .text()
+            .contains("omitted to fit the context budget"));
+    }
+
+    #[test]
+    fn test_layout_reorders_disables_and_overrides_sections() {
+        let mut manager = PromptManager::new();
+        manager.add_system_prompt_extra("recipe".to_string(), "Follow the recipe".to_string());
+        let layout: PromptLayout = serde_yaml::from_str(
+            "order: [instructions, response_guidelines]\ndisabled: [tool_guidance]\noverrides:\n  response_guidelines: Answer in plain text.",
+        )
+        .unwrap();
+
+        let mut builder = manager.builder().with_layout(layout);
+        builder.custom_system_prompt = None;
+        let rendered = builder.render();
+
+        let sections: Vec<PromptSection> = rendered.sections.iter().map(|s| s.section).collect();
+        assert_eq!(
+            sections,
+            vec![
+                PromptSection::ResponseGuidelines,
+                PromptSection::Identity,
+                PromptSection::Instructions,
+            ]
+        );
+        let text = rendered.text();
+        assert!(text.starts_with("Answer in plain text.\n\nYou are"));
+        assert!(text.ends_with("\n\n# Additional Instructions:\n\nFollow the recipe"));
+        assert!(!text.contains("# Extensions"));
+    }
+
+    #[test]
+    fn test_custom_system_md_is_the_whole_base_prompt() {
+        let mut manager = PromptManager::new();
+        manager.add_system_prompt_extra("recipe".to_string(), "Follow the recipe".to_string());
+
+        let mut builder = manager
+            .builder()
+            .with_layout(PromptLayout::default())
+            .with_extension(ExtensionInfo::new("test", "instructions", false));
+        builder.custom_system_prompt = Some("You are my own agent.".to_string());
+        let text = builder.render().text();
+
+        assert_eq!(
+            text,
+            "You are my own agent.\n\n# Additional Instructions:\n\nFollow the recipe"
+        );
+    }
+
     #[test]
     fn test_basic() {
         let manager = PromptManager::with_timestamp(DateTime::<Utc>::from_timestamp(0, 0).unwrap());
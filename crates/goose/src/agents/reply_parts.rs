@@ -8,18 +8,27 @@ use serde_json::{json, Value};
 use tracing::debug;
 
 use super::super::agents::Agent;
+use crate::agents::lifecycle::{self, LifecycleEvent};
 #[cfg(feature = "code-mode")]
 use crate::agents::platform_extensions::code_execution;
+use crate::agents::prompt_layout::PromptLayout;
+use crate::agents::prompt_manager::RenderedPrompt;
+use crate::conversation::attachments::AttachmentStore;
 use crate::conversation::message::{Message, MessageContent, ToolRequest};
 use crate::conversation::Conversation;
 #[cfg(test)]
 use crate::providers::base::stream_from_single_message;
 use crate::providers::base::{MessageStream, Provider, ProviderUsage};
+use crate::providers::canonical::{maybe_get_canonical_model, Modality};
 use crate::providers::errors::ProviderError;
+use crate::providers::markdown_chunks;
+use crate::providers::server_tools;
 use crate::providers::toolshim::{
-    augment_message_with_tool_calls, convert_tool_messages_to_text,
-    modify_system_prompt_for_tool_json, OllamaInterpreter,
+    augment_message_with_configured_interpreter, convert_tool_messages_to_text,
+    modify_system_prompt_for_tool_json,
 };
+use crate::security::pii::{self, PiiRedactor, PiiVault};
+use crate::token_counter::create_token_counter;
 use rmcp::model::Tool;
 
 async fn enhance_model_error(error: ProviderError, provider: &Arc<dyn Provider>) -> ProviderError {
@@ -123,11 +132,7 @@ async fn toolshim_postprocess(
     response: Message,
     toolshim_tools: &[Tool],
 ) -> Result<Message, ProviderError> {
-    let interpreter = OllamaInterpreter::new().map_err(|e| {
-        ProviderError::ExecutionError(format!("Failed to create OllamaInterpreter: {}", e))
-    })?;
-
-    augment_message_with_tool_calls(&interpreter, response, toolshim_tools)
+    augment_message_with_configured_interpreter(response, toolshim_tools)
         .await
         .map_err(|e| ProviderError::ExecutionError(format!("Failed to augment message: {}", e)))
 }
@@ -147,13 +152,7 @@ impl Agent {
             tools.push(frontend_tool.tool.clone());
         }
 
-        #[cfg(feature = "code-mode")]
-        let code_execution_active = self
-            .extension_manager
-            .is_extension_enabled(code_execution::EXTENSION_NAME)
-            .await;
-        #[cfg(not(feature = "code-mode"))]
-        let code_execution_active = false;
+        let code_execution_active = self.code_execution_active().await;
         if code_execution_active {
             tools.retain(|tool| {
                 if let Some(owner) = crate::agents::extension_manager::get_tool_owner(tool) {
@@ -168,6 +167,44 @@ impl Agent {
         tools.sort_by(|a, b| a.name.cmp(&b.name));
 
         // Prepare system prompt
+        let mut system_prompt = self
+            .render_system_prompt(session_id, working_dir, code_execution_active)
+            .await?
+            .text();
+        let model_config = self.provider().await?.get_model_config();
+
+        // Handle toolshim if enabled
+        let mut toolshim_tools = vec![];
+        if model_config.toolshim {
+            // If tool interpretation is enabled, modify the system prompt
+            system_prompt = modify_system_prompt_for_tool_json(&system_prompt, &tools);
+            // Make a copy of tools before emptying
+            toolshim_tools = tools.clone();
+            // Empty the tools vector for provider completion
+            tools = vec![];
+        }
+
+        Ok((tools, toolshim_tools, system_prompt))
+    }
+
+    pub(crate) async fn code_execution_active(&self) -> bool {
+        #[cfg(feature = "code-mode")]
+        let active = self
+            .extension_manager
+            .is_extension_enabled(code_execution::EXTENSION_NAME)
+            .await;
+        #[cfg(not(feature = "code-mode"))]
+        let active = false;
+        active
+    }
+
+    /// Render the system prompt for the next turn of a session, section by section
+    pub async fn render_system_prompt(
+        &self,
+        session_id: &str,
+        working_dir: &std::path::Path,
+        code_execution_active: bool,
+    ) -> Result<RenderedPrompt> {
         let extensions_info = self
             .extension_manager
             .get_extensions_info(working_dir)
@@ -176,33 +213,50 @@ impl Agent {
             .extension_manager
             .get_extension_and_tool_counts(session_id)
             .await;
+        let layout = match self
+            .config
+            .session_manager
+            .get_session(session_id, false)
+            .await
+        {
+            Ok(session) => PromptLayout::for_session(&session),
+            Err(_) => PromptLayout::from_config(),
+        };
 
         // Get model name from provider
         let provider = self.provider().await?;
         let model_config = provider.get_model_config();
 
+        let token_counter = create_token_counter().await;
         let prompt_manager = self.prompt_manager.lock().await;
-        let mut system_prompt = prompt_manager
+        let mut builder = prompt_manager
             .builder()
             .with_extensions(extensions_info.into_iter())
             .with_frontend_instructions(self.frontend_instructions.lock().await.clone())
             .with_extension_and_tool_counts(extension_count, tool_count)
             .with_code_execution_mode(code_execution_active)
-            .with_hints(working_dir)
-            .build();
-
-        // Handle toolshim if enabled
-        let mut toolshim_tools = vec![];
-        if model_config.toolshim {
-            // If tool interpretation is enabled, modify the system prompt
-            system_prompt = modify_system_prompt_for_tool_json(&system_prompt, &tools);
-            // Make a copy of tools before emptying
-            toolshim_tools = tools.clone();
-            // Empty the tools vector for provider completion
-            tools = vec![];
+            .with_hints(
+                working_dir,
+                &crate::hooks::config_change::skipped_instructions(session_id),
+            )
+            .with_layout(layout);
+        match token_counter {
+            Ok(counter) => {
+                builder = builder.with_context_budget(model_config.context_limit(), counter)
+            }
+            Err(e) => tracing::warn!("Building the system prompt without a context budget: {}", e),
         }
-
-        Ok((tools, toolshim_tools, system_prompt))
+        let rendered = builder.render();
+        if let Some(report) = &rendered.report {
+            if report.dropped() > 0 {
+                tracing::warn!(
+                    "System prompt trimmed to fit the context budget\n{}",
+                    report
+                );
+            }
+            *self.context_report.lock().await = Some(report.clone());
+        }
+        Ok(rendered)
     }
 
     /// Stream a response from the LLM provider.
@@ -223,6 +277,29 @@ impl Agent {
             .map(|m| m.agent_visible_content())
             .collect();
 
+        // Attachments are read from the session's store only now, and sent as images only to
+        // models known to take them
+        let images = maybe_get_canonical_model(provider.get_name(), &config.model_name)
+            .is_none_or(|model| model.modalities.input.contains(&Modality::Image));
+        let filtered_messages = AttachmentStore::for_session(session_id)
+            .render_messages(filtered_messages, images)
+            .await;
+        let filtered_messages = server_tools::calls_as_text(filtered_messages);
+
+        // Pseudonymize PII before anything leaves the machine; originals stay in memory
+        let (system_prompt, filtered_messages, pii_vault) = match PiiRedactor::from_config() {
+            Some(redactor) => {
+                let mut vault = PiiVault::default();
+                let system_prompt = redactor.scrub(system_prompt, &mut vault);
+                let messages = filtered_messages
+                    .iter()
+                    .map(|m| redactor.scrub_message(m, &mut vault))
+                    .collect();
+                (system_prompt, messages, Some(vault))
+            }
+            None => (system_prompt.to_owned(), filtered_messages, None),
+        };
+
         // Convert tool messages to text if toolshim is enabled
         let messages_for_provider = if config.toolshim {
             convert_tool_messages_to_text(&filtered_messages)
@@ -231,7 +308,6 @@ impl Agent {
         };
 
         // Clone owned data to move into the async stream
-        let system_prompt = system_prompt.to_owned();
         let tools = tools.to_owned();
         let toolshim_tools = toolshim_tools.to_owned();
         let provider = provider.clone();
@@ -264,7 +340,7 @@ impl Agent {
             }
         };
 
-        Ok(Box::pin(try_stream! {
+        let stream: MessageStream = Box::pin(try_stream! {
             while let Some(result) = stream.next().await {
                 let (mut message, usage) = result?;
 
@@ -280,7 +356,15 @@ impl Agent {
 
                 yield (message, usage);
             }
-        }))
+        });
+        let stream = match pii_vault {
+            Some(vault) => pii::restore_stream(stream, vault),
+            None => stream,
+        };
+        if markdown_chunks::enabled() {
+            return Ok(markdown_chunks::chunk_markdown(stream));
+        }
+        Ok(stream)
     }
 
     /// Categorize tool requests from the response into different types
@@ -298,7 +382,11 @@ impl Agent {
             .content
             .iter()
             .filter_map(|content| {
+                // Calls the provider already ran stay in the message but aren't for goose to run
                 if let MessageContent::ToolRequest(req) = content {
+                    if req.is_server_tool() {
+                        return None;
+                    }
                     let mut coerced_req = req.clone();
 
                     if let Ok(ref mut tool_call) = coerced_req.tool_call {
@@ -326,7 +414,7 @@ impl Agent {
 
         for content in &response.content {
             match content {
-                MessageContent::ToolRequest(_) => {
+                MessageContent::ToolRequest(req) if !req.is_server_tool() => {
                     if tool_request_index < tool_requests.len() {
                         let coerced_req = &tool_requests[tool_request_index];
                         tool_request_index += 1;
@@ -424,6 +512,13 @@ impl Agent {
             .apply()
             .await?;
 
+        lifecycle::publish(LifecycleEvent::UsageUpdated {
+            session_id: session_id.to_string(),
+            input_tokens: current_input,
+            output_tokens: current_output,
+            total_tokens: current_total,
+            accumulated_total_tokens: accumulated_total,
+        });
         Ok(())
     }
 }
@@ -159,6 +159,8 @@ impl Agent {
             paused: false,
             current_session_id: None,
             process_start_time: None,
+            settings: crate::scheduler::JobRunSettings::default(),
+            last_run_status: None,
         };
 
         match scheduler.add_scheduled_job(job, true).await {
@@ -1,16 +1,24 @@
 use crate::{
-    agents::{subagent_task_config::TaskConfig, Agent, AgentConfig, AgentEvent, SessionConfig},
+    agents::{
+        extension_manager::ToolSetSnapshot, subagent_task_config::TaskConfig, Agent, AgentConfig,
+        AgentEvent, SessionConfig,
+    },
     conversation::{
         message::{Message, MessageContent},
         Conversation,
     },
+    headless::{files_changed, TaskUsage},
+    hooks::{HookEvent, HookRuntime},
+    i18n,
+    notifications::{self, NotificationKind},
     prompt_template::render_template,
     recipe::Recipe,
+    session::SessionManager,
 };
 use anyhow::{anyhow, Result};
 use futures::StreamExt;
 use rmcp::model::{
-    ErrorCode, ErrorData, LoggingLevel, LoggingMessageNotificationParam, Notification,
+    ErrorCode, ErrorData, LoggingLevel, LoggingMessageNotificationParam, Notification, Role,
     ServerNotification,
 };
 use serde::Serialize;
@@ -31,8 +39,55 @@ pub struct SubagentPromptContext {
     pub available_tools: String,
 }
 
-type AgentMessagesFuture =
-    Pin<Box<dyn Future<Output = Result<(Conversation, Option<String>)>> + Send>>;
+/// How a subagent run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubagentStatus {
+    Completed,
+    /// Stopped after using up its token budget
+    BudgetExhausted,
+    Cancelled,
+    Failed,
+}
+
+impl SubagentStatus {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            SubagentStatus::Completed => "completed",
+            SubagentStatus::BudgetExhausted => "budget_exhausted",
+            SubagentStatus::Cancelled => "cancelled",
+            SubagentStatus::Failed => "failed",
+        }
+    }
+}
+
+/// The structured outcome of a subagent run
+#[derive(Debug, Clone, Serialize)]
+pub struct SubagentResult {
+    pub subagent_id: String,
+    pub status: SubagentStatus,
+    /// The final output, or the response text when the task has no response schema
+    pub output: String,
+    pub tool_calls: usize,
+    pub total_tokens: Option<i32>,
+    /// Files the subagent wrote or edited, relative to the parent's working directory
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files_changed: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why a TaskCompleted hook flagged the run for follow-up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_up: Option<String>,
+}
+
+struct SubagentRun {
+    conversation: Conversation,
+    final_output: Option<String>,
+    budget_exhausted: bool,
+    total_tokens: Option<i32>,
+}
+
+type AgentMessagesFuture = Pin<Box<dyn Future<Output = Result<SubagentRun>> + Send>>;
 
 pub struct SubagentRunParams {
     pub config: AgentConfig,
@@ -45,21 +100,176 @@ pub struct SubagentRunParams {
     pub notification_tx: Option<tokio::sync::mpsc::UnboundedSender<ServerNotification>>,
 }
 
+impl SubagentResult {
+    /// The output as the parent model reads it, or an error when the run failed
+    pub fn to_text(&self) -> Result<String, anyhow::Error> {
+        let output = match &self.follow_up {
+            Some(follow_up) => format!("{}\n\n[Needs follow-up: {}]", self.output, follow_up),
+            None => self.output.clone(),
+        };
+        match self.status {
+            SubagentStatus::Failed => Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                format!(
+                    "Failed to execute task: {}",
+                    self.error.as_deref().unwrap_or_default()
+                ),
+                None,
+            )
+            .into()),
+            SubagentStatus::BudgetExhausted => Ok(format!(
+                "{}\n\n[The subagent used up its token budget before finishing]",
+                output
+            )),
+            SubagentStatus::Completed | SubagentStatus::Cancelled => Ok(output),
+        }
+    }
+}
+
 pub async fn run_subagent_task(params: SubagentRunParams) -> Result<String, anyhow::Error> {
+    run_subagent(params).await.to_text()
+}
+
+/// Run a subagent in its own session and context, reporting the run to the parent
+/// session's SubagentStart, SubagentStop and TaskCompleted hooks
+pub async fn run_subagent(params: SubagentRunParams) -> SubagentResult {
+    let subagent_id = params.session_id.clone();
+    let parent_session_id = params.task_config.parent_session_id.clone();
+    let cwd = params.task_config.parent_working_dir.clone();
     let return_last_only = params.return_last_only;
-    let (messages, final_output) = get_agent_messages(params).await.map_err(|e| {
-        ErrorData::new(
-            ErrorCode::INTERNAL_ERROR,
-            format!("Failed to execute task: {}", e),
-            None,
+    let cancellation_token = params.cancellation_token.clone().unwrap_or_default();
+    let session_manager = Arc::clone(&params.config.session_manager);
+    let token_budget = params.task_config.token_budget;
+    let hooks = HookRuntime::load(&cwd);
+
+    let task = params
+        .recipe
+        .prompt
+        .clone()
+        .or_else(|| params.recipe.instructions.clone())
+        .unwrap_or_default();
+    let start = hooks
+        .emit(
+            HookEvent::SubagentStart {
+                session_id: parent_session_id.clone(),
+                subagent_id: subagent_id.clone(),
+                task,
+                cwd: cwd.clone(),
+            },
+            &cwd,
+            cancellation_token.clone(),
         )
-    })?;
+        .await;
 
-    if let Some(output) = final_output {
-        return Ok(output);
+    let mut result = if start.blocked {
+        SubagentResult {
+            subagent_id: subagent_id.clone(),
+            status: SubagentStatus::Failed,
+            output: String::new(),
+            tool_calls: 0,
+            total_tokens: None,
+            files_changed: Vec::new(),
+            error: Some("Blocked by a SubagentStart hook".to_string()),
+            follow_up: None,
+        }
+    } else {
+        match get_agent_messages(params).await {
+            Ok(run) => {
+                let status = if run.budget_exhausted {
+                    SubagentStatus::BudgetExhausted
+                } else if cancellation_token.is_cancelled() {
+                    SubagentStatus::Cancelled
+                } else {
+                    SubagentStatus::Completed
+                };
+                let tool_calls = run
+                    .conversation
+                    .iter()
+                    .flat_map(|message| message.content.iter())
+                    .filter(|content| matches!(content, MessageContent::ToolRequest(_)))
+                    .count();
+                SubagentResult {
+                    subagent_id: subagent_id.clone(),
+                    status,
+                    output: run.final_output.unwrap_or_else(|| {
+                        extract_response_text(&run.conversation, return_last_only)
+                    }),
+                    tool_calls,
+                    total_tokens: run.total_tokens,
+                    files_changed: files_changed(&run.conversation, &cwd),
+                    error: None,
+                    follow_up: None,
+                }
+            }
+            Err(e) => SubagentResult {
+                subagent_id: subagent_id.clone(),
+                status: SubagentStatus::Failed,
+                output: String::new(),
+                tool_calls: 0,
+                total_tokens: None,
+                files_changed: Vec::new(),
+                error: Some(e.to_string()),
+                follow_up: None,
+            },
+        }
+    };
+
+    if result.status == SubagentStatus::BudgetExhausted {
+        let parent_type = session_manager
+            .get_session(&parent_session_id, false)
+            .await
+            .ok()
+            .map(|session| session.session_type);
+        notifications::notify(notifications::Notification::new(
+            NotificationKind::BudgetWarning,
+            parent_session_id.clone(),
+            parent_type,
+            i18n::text_with(
+                "notify.subagent_budget",
+                &[
+                    ("subagent", &subagent_id),
+                    ("budget", &token_budget.unwrap_or_default()),
+                ],
+            ),
+        ));
     }
 
-    Ok(extract_response_text(&messages, return_last_only))
+    hooks
+        .emit(
+            HookEvent::SubagentStop {
+                session_id: parent_session_id.clone(),
+                subagent_id: subagent_id.clone(),
+                status: result.status.as_str().to_string(),
+                output: result.output.clone(),
+                cwd: cwd.clone(),
+            },
+            &cwd,
+            CancellationToken::new(),
+        )
+        .await;
+    result.follow_up = hooks
+        .emit(
+            HookEvent::TaskCompleted {
+                session_id: parent_session_id,
+                task_id: subagent_id,
+                task_type: "subagent".to_string(),
+                status: result.status.as_str().to_string(),
+                output: result.output.clone(),
+                error: result.error.clone(),
+                files_changed: result.files_changed.clone(),
+                usage: TaskUsage {
+                    total_tokens: result.total_tokens,
+                    ..Default::default()
+                },
+                cwd: cwd.clone(),
+            },
+            &cwd,
+            CancellationToken::new(),
+        )
+        .await
+        .follow_up();
+
+    result
 }
 
 fn extract_response_text(messages: &Conversation, return_last_only: bool) -> String {
@@ -132,12 +342,13 @@ fn get_agent_messages(params: SubagentRunParams) -> AgentMessagesFuture {
             ..
         } = params;
 
-        let system_instructions = recipe.instructions.clone().unwrap_or_default();
+        let system_instructions = recipe.system_instructions().unwrap_or_default();
         let user_task = recipe
             .prompt
             .clone()
             .unwrap_or_else(|| "Begin.".to_string());
 
+        let session_manager = Arc::clone(&config.session_manager);
         let agent = Arc::new(Agent::with_config(config));
 
         agent
@@ -145,14 +356,9 @@ fn get_agent_messages(params: SubagentRunParams) -> AgentMessagesFuture {
             .await
             .map_err(|e| anyhow!("Failed to set provider on sub agent: {}", e))?;
 
-        for extension in &task_config.extensions {
-            if let Err(e) = agent.add_extension(extension.clone(), &session_id).await {
-                debug!(
-                    "Failed to add extension '{}' to subagent: {}",
-                    extension.name(),
-                    e
-                );
-            }
+        let tool_set = ToolSetSnapshot::new(task_config.restricted_extensions());
+        if let Err(e) = agent.apply_tool_set(&tool_set, &session_id).await {
+            debug!("Failed to set up the subagent's extensions: {}", e);
         }
 
         let has_response_schema = recipe.response.is_some();
@@ -179,15 +385,21 @@ fn get_agent_messages(params: SubagentRunParams) -> AgentMessagesFuture {
             retry_config: recipe.retry,
         };
 
+        // A child token, so running out of budget stops only this subagent
+        let run_token = cancellation_token
+            .map(|token| token.child_token())
+            .unwrap_or_default();
         let mut stream =
             crate::session_context::with_session_id(Some(session_id.to_string()), async {
                 agent
-                    .reply(user_message, session_config, cancellation_token)
+                    .reply(user_message, session_config, Some(run_token.clone()))
                     .await
             })
             .await
             .map_err(|e| anyhow!("Failed to get reply from agent: {}", e))?;
 
+        let mut budget_exhausted = false;
+
         while let Some(message_result) = stream.next().await {
             match message_result {
                 Ok(AgentEvent::Message(msg)) => {
@@ -206,7 +418,21 @@ fn get_agent_messages(params: SubagentRunParams) -> AgentMessagesFuture {
                             }
                         }
                     }
+                    let from_assistant = msg.role == Role::Assistant;
                     conversation.push(msg);
+
+                    if let Some(budget) = task_config.token_budget.filter(|_| from_assistant) {
+                        let used = tokens_used(&session_manager, &session_id).await;
+                        if used.is_some_and(|used| used >= budget) {
+                            info!(
+                                "Subagent {} used up its budget of {} tokens",
+                                session_id, budget
+                            );
+                            budget_exhausted = true;
+                            run_token.cancel();
+                            break;
+                        }
+                    }
                 }
                 Ok(AgentEvent::McpNotification(_)) | Ok(AgentEvent::ModelChange { .. }) => {}
                 Ok(AgentEvent::HistoryReplaced(updated_conversation)) => {
@@ -221,10 +447,20 @@ fn get_agent_messages(params: SubagentRunParams) -> AgentMessagesFuture {
 
         let final_output = get_final_output(&agent, has_response_schema).await;
 
-        Ok((conversation, final_output))
+        Ok(SubagentRun {
+            conversation,
+            final_output,
+            budget_exhausted,
+            total_tokens: tokens_used(&session_manager, &session_id).await,
+        })
     })
 }
 
+async fn tokens_used(session_manager: &SessionManager, session_id: &str) -> Option<i32> {
+    let session = session_manager.get_session(session_id, false).await.ok()?;
+    session.accumulated_total_tokens.or(session.total_tokens)
+}
+
 async fn build_subagent_prompt(
     agent: &Agent,
     task_config: &TaskConfig,
@@ -294,7 +530,9 @@ pub fn create_tool_notification(
 
 #[cfg(test)]
 mod tests {
-    use super::{create_tool_notification, SUBAGENT_TOOL_REQUEST_TYPE};
+    use super::{
+        create_tool_notification, SubagentResult, SubagentStatus, SUBAGENT_TOOL_REQUEST_TYPE,
+    };
     use crate::conversation::message::MessageContent;
     use rmcp::model::{CallToolRequestParams, ServerNotification};
     use serde_json::json;
@@ -338,4 +576,37 @@ mod tests {
         let content = MessageContent::text("hello");
         assert!(create_tool_notification(&content, "session_1").is_none());
     }
+
+    #[test]
+    fn subagent_result_serializes_for_the_parent() {
+        let mut result = SubagentResult {
+            subagent_id: "sub_1".to_string(),
+            status: SubagentStatus::BudgetExhausted,
+            output: "partial".to_string(),
+            tool_calls: 2,
+            total_tokens: Some(1000),
+            files_changed: vec!["src/lib.rs".to_string()],
+            error: None,
+            follow_up: None,
+        };
+        assert!(result
+            .to_text()
+            .unwrap()
+            .starts_with("partial\n\n[The subagent"));
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            json!({
+                "subagent_id": "sub_1",
+                "status": "budget_exhausted",
+                "output": "partial",
+                "tool_calls": 2,
+                "total_tokens": 1000,
+                "files_changed": ["src/lib.rs"],
+            })
+        );
+
+        result.status = SubagentStatus::Failed;
+        result.error = Some("boom".to_string());
+        assert!(result.to_text().unwrap_err().to_string().contains("boom"));
+    }
 }
@@ -16,6 +16,10 @@ pub struct TaskConfig {
     pub parent_working_dir: PathBuf,
     pub extensions: Vec<ExtensionConfig>,
     pub max_turns: Option<usize>,
+    /// Tools the subagent may use, as `extension__tool` or bare tool names. `None` allows all.
+    pub allowed_tools: Option<Vec<String>>,
+    /// Total tokens the subagent may use before it is stopped
+    pub token_budget: Option<i32>,
 }
 
 impl fmt::Debug for TaskConfig {
@@ -26,6 +30,8 @@ impl fmt::Debug for TaskConfig {
             .field("parent_working_dir", &self.parent_working_dir)
             .field("max_turns", &self.max_turns)
             .field("extensions", &self.extensions)
+            .field("allowed_tools", &self.allowed_tools)
+            .field("token_budget", &self.token_budget)
             .finish()
     }
 }
@@ -47,6 +53,10 @@ impl TaskConfig {
                     .get_param::<usize>("GOOSE_SUBAGENT_MAX_TURNS")
                     .unwrap_or(DEFAULT_SUBAGENT_MAX_TURNS),
             ),
+            allowed_tools: None,
+            token_budget: Config::global()
+                .get_param::<i32>("GOOSE_SUBAGENT_TOKEN_BUDGET")
+                .ok(),
         }
     }
 
@@ -56,4 +66,82 @@ impl TaskConfig {
         }
         self
     }
+
+    pub fn with_allowed_tools(mut self, allowed_tools: Option<Vec<String>>) -> Self {
+        if allowed_tools.is_some() {
+            self.allowed_tools = allowed_tools;
+        }
+        self
+    }
+
+    pub fn with_token_budget(mut self, token_budget: Option<i32>) -> Self {
+        if let Some(budget) = token_budget {
+            self.token_budget = Some(budget);
+        }
+        self
+    }
+
+    /// The extensions to start for the subagent, narrowed to the allowed tools
+    pub fn restricted_extensions(&self) -> Vec<ExtensionConfig> {
+        match &self.allowed_tools {
+            Some(allowed) => restrict_extensions(&self.extensions, allowed),
+            None => self.extensions.clone(),
+        }
+    }
+}
+
+/// Narrow each extension to the allowed tools, given as `extension__tool` or bare tool
+/// names. Extensions left without any allowed tool are dropped.
+fn restrict_extensions(extensions: &[ExtensionConfig], allowed: &[String]) -> Vec<ExtensionConfig> {
+    extensions
+        .iter()
+        .filter_map(|extension| {
+            let prefix = format!("{}__", extension.key());
+            let tools: Vec<String> = allowed
+                .iter()
+                .filter_map(|tool| match tool.strip_prefix(&prefix) {
+                    Some(bare) => Some(bare.to_string()),
+                    None if !tool.contains("__") => Some(tool.clone()),
+                    None => None,
+                })
+                .filter(|tool| extension.is_tool_available(tool))
+                .collect();
+            (!tools.is_empty()).then(|| extension.clone().with_available_tools(tools))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extension_tools(config: &ExtensionConfig) -> Vec<&str> {
+        ["shell", "text_editor", "search"]
+            .into_iter()
+            .filter(|tool| config.is_tool_available(tool))
+            .collect()
+    }
+
+    #[test]
+    fn restricts_extensions_to_allowed_tools() {
+        let extensions = vec![
+            ExtensionConfig::stdio("developer", "dev-mcp", "", 300u64),
+            ExtensionConfig::stdio("github", "gh-mcp", "", 300u64),
+        ];
+        let allowed = |tools: &[&str]| tools.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        let restricted = restrict_extensions(
+            &extensions,
+            &allowed(&["developer__shell", "developer__search"]),
+        );
+        assert_eq!(restricted.len(), 1);
+        assert_eq!(restricted[0].name(), "developer");
+        assert_eq!(extension_tools(&restricted[0]), vec!["shell", "search"]);
+
+        let bare = restrict_extensions(&extensions, &allowed(&["search"]));
+        assert_eq!(bare.len(), 2);
+        assert_eq!(extension_tools(&bare[1]), vec!["search"]);
+
+        assert!(restrict_extensions(&extensions, &allowed(&["slack__post"])).is_empty());
+    }
 }
@@ -0,0 +1,275 @@
+//! Offers goose's own tools to agent CLIs that run the tool loop themselves.
+//!
+//! Providers like claude-code take goose's extensions as MCP server configs, which works
+//! for stdio and streamable HTTP extensions but not for builtin and platform extensions:
+//! those run inside goose. The bridge serves their tools from an MCP server on localhost
+//! and is handed to the provider as one more streamable HTTP extension. Calls are passed to
+//! the agent's reply loop, so they get the same inspection, approvals and hooks as the
+//! agent's own tool calls.
+
+use crate::agents::extension::ExtensionConfig;
+use crate::agents::extension_manager::ExtensionManager;
+use crate::config::extensions::name_to_key;
+use anyhow::Result;
+use axum::extract::{Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use rmcp::model::{
+    CallToolRequestParams, CallToolResult, ErrorCode, Implementation, InitializeResult,
+    ListToolsResult, PaginatedRequestParams, ServerCapabilities, ServerInfo, Tool,
+};
+use rmcp::service::RequestContext;
+use rmcp::transport::streamable_http_server::{
+    session::local::LocalSessionManager, StreamableHttpServerConfig, StreamableHttpService,
+};
+use rmcp::{ErrorData, RoleServer, ServerHandler};
+use std::collections::HashMap;
+use std::sync::{Arc, Weak};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the extension the bridge is registered as with the provider.
+pub const TOOL_BRIDGE_EXTENSION_NAME: &str = "goose";
+
+/// Providers that hand goose's extensions to a CLI as MCP servers.
+const MCP_PASSTHROUGH_PROVIDERS: &[&str] = &["claude-code"];
+
+/// Whether an extension only runs inside goose and has to be bridged.
+pub fn needs_bridge(config: &ExtensionConfig) -> bool {
+    matches!(
+        config,
+        ExtensionConfig::Builtin { .. } | ExtensionConfig::Platform { .. }
+    )
+}
+
+/// Whether the provider passes extensions on as MCP servers, so the bridge is useful.
+pub fn provider_uses_bridge(provider_name: &str) -> bool {
+    MCP_PASSTHROUGH_PROVIDERS.contains(&provider_name)
+}
+
+/// A tool call made through the bridge, for the agent's reply loop to run and answer
+pub struct BridgedCall {
+    pub tool_call: CallToolRequestParams,
+    pub respond: oneshot::Sender<Result<CallToolResult, ErrorData>>,
+}
+
+#[derive(Clone)]
+struct ToolBridge {
+    extension_manager: Weak<ExtensionManager>,
+    session_id: String,
+    calls: mpsc::Sender<BridgedCall>,
+}
+
+fn session_ended() -> ErrorData {
+    ErrorData::new(
+        ErrorCode::INTERNAL_ERROR,
+        "The goose session has ended".to_string(),
+        None,
+    )
+}
+
+impl ToolBridge {
+    async fn bridged_tools(&self) -> Result<Vec<Tool>, ErrorData> {
+        let extension_manager = self.extension_manager.upgrade().ok_or_else(session_ended)?;
+        let mut tools = Vec::new();
+        for config in extension_manager.get_extension_configs().await {
+            if !needs_bridge(&config) {
+                continue;
+            }
+            let extension_tools = extension_manager
+                .get_prefixed_tools(&self.session_id, Some(name_to_key(&config.name())))
+                .await
+                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            tools.extend(extension_tools);
+        }
+        Ok(tools)
+    }
+}
+
+impl ServerHandler for ToolBridge {
+    fn get_info(&self) -> ServerInfo {
+        InitializeResult::new(ServerCapabilities::builder().enable_tools().build())
+            .with_server_info(Implementation::new(
+                "goose-tool-bridge",
+                env!("CARGO_PKG_VERSION"),
+            ))
+            .with_instructions("Tools from goose's builtin and platform extensions.")
+    }
+
+    async fn list_tools(
+        &self,
+        _pagination: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, ErrorData> {
+        Ok(ListToolsResult {
+            tools: self.bridged_tools().await?,
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, ErrorData> {
+        // Only the bridged tools; the others are already the provider's own MCP servers
+        let bridged = self.bridged_tools().await?;
+        if !bridged.iter().any(|tool| tool.name == request.name) {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Unknown tool: {}", request.name),
+                None,
+            ));
+        }
+
+        let (respond, response) = oneshot::channel();
+        self.calls
+            .send(BridgedCall {
+                tool_call: request,
+                respond,
+            })
+            .await
+            .map_err(|_| session_ended())?;
+        tokio::select! {
+            result = response => result.map_err(|_| session_ended())?,
+            _ = context.ct.cancelled() => Err(ErrorData::new(
+                ErrorCode::INTERNAL_ERROR,
+                "Cancelled".to_string(),
+                None,
+            )),
+        }
+    }
+}
+
+async fn check_token(
+    State(token): State<Arc<str>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let authorization = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(presented) if presented == &*token => Ok(next.run(request).await),
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// A running bridge. The server and its open connections stop when this is dropped.
+pub struct ToolBridgeServer {
+    config: ExtensionConfig,
+    session_id: String,
+    shutdown: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+impl ToolBridgeServer {
+    /// Serve the bridged tools of `session_id` on a localhost port, behind a random token.
+    /// Calls are sent to `calls` for the agent to run.
+    pub async fn start(
+        extension_manager: &Arc<ExtensionManager>,
+        session_id: &str,
+        calls: mpsc::Sender<BridgedCall>,
+    ) -> Result<Self> {
+        let bridge = ToolBridge {
+            extension_manager: Arc::downgrade(extension_manager),
+            session_id: session_id.to_string(),
+            calls,
+        };
+        let service = StreamableHttpService::new(
+            move || Ok(bridge.clone()),
+            LocalSessionManager::default().into(),
+            StreamableHttpServerConfig::default(),
+        );
+
+        let token: Arc<str> = uuid::Uuid::new_v4().simple().to_string().into();
+        let router = axum::Router::new()
+            .nest_service("/mcp", service)
+            .layer(middleware::from_fn_with_state(token.clone(), check_token));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let shutdown = CancellationToken::new();
+        let stopped = shutdown.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router)
+                .with_graceful_shutdown(stopped.cancelled_owned())
+                .await
+            {
+                tracing::warn!("Tool bridge stopped: {}", e);
+            }
+        });
+        tracing::info!(%addr, session_id, "Started the tool bridge");
+
+        let config = ExtensionConfig::StreamableHttp {
+            name: TOOL_BRIDGE_EXTENSION_NAME.to_string(),
+            description: "goose's builtin and platform tools".to_string(),
+            uri: format!("http://{addr}/mcp"),
+            envs: Default::default(),
+            env_keys: Vec::new(),
+            headers: HashMap::from([(
+                header::AUTHORIZATION.to_string(),
+                format!("Bearer {token}"),
+            )]),
+            timeout: Some(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+            bundled: None,
+            available_tools: Vec::new(),
+        };
+        Ok(Self {
+            config,
+            session_id: session_id.to_string(),
+            shutdown,
+            handle,
+        })
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// The extension config to give the provider
+    pub fn extension_config(&self) -> &ExtensionConfig {
+        &self.config
+    }
+}
+
+impl Drop for ToolBridgeServer {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+        self.handle.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn bridge_rejects_requests_without_the_token() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let extension_manager = Arc::new(ExtensionManager::new_without_provider(
+            temp_dir.path().to_path_buf(),
+        ));
+        let (calls, _calls_rx) = mpsc::channel(1);
+        let bridge = ToolBridgeServer::start(&extension_manager, "s1", calls)
+            .await
+            .unwrap();
+        let config = bridge.extension_config();
+        let ExtensionConfig::StreamableHttp { uri, headers, .. } = config else {
+            panic!("the bridge is a streamable HTTP extension");
+        };
+        assert!(headers["authorization"].starts_with("Bearer "));
+
+        let response = reqwest::Client::new()
+            .post(uri)
+            .header("Accept", "application/json, text/event-stream")
+            .json(&serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    }
+}
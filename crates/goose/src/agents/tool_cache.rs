@@ -0,0 +1,253 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use rmcp::model::{CallToolResult, JsonObject};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+
+/// Argument names treated as file paths; the file's modification time becomes part of
+/// the cache key so edited files are read again
+const PATH_ARGUMENTS: &[&str] = &["path", "file", "file_path", "paths"];
+
+/// Upper bound on cached results, oldest entries are dropped first
+const MAX_ENTRIES: usize = 256;
+
+/// Caching settings for tools whose name matches a `GOOSE_TOOL_CACHE` key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCachePolicy {
+    /// How long a result stays valid, in seconds
+    #[serde(default = "default_ttl")]
+    pub ttl_secs: u64,
+}
+
+fn default_ttl() -> u64 {
+    300
+}
+
+struct CachedResult {
+    result: CallToolResult,
+    expires_at: Instant,
+}
+
+/// Results of idempotent tool calls, keyed by tool name, arguments and the modification
+/// times of files they refer to. Caching is opt-in per tool: `GOOSE_TOOL_CACHE` maps tool
+/// name globs (e.g. `read_file` or `fetch__*`) to a policy. A tool's exact name takes
+/// precedence over globs, and among globs the first in sorted order applies.
+pub struct ToolResultCache {
+    policies: Vec<(glob::Pattern, ToolCachePolicy)>,
+    entries: std::sync::Mutex<HashMap<String, CachedResult>>,
+}
+
+impl ToolResultCache {
+    pub fn new(policies: BTreeMap<String, ToolCachePolicy>) -> Self {
+        let mut policies: Vec<_> = policies
+            .into_iter()
+            .filter_map(|(pattern, policy)| match glob::Pattern::new(&pattern) {
+                Ok(pattern) => Some((pattern, policy)),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid tool cache pattern '{}': {}", pattern, e);
+                    None
+                }
+            })
+            .collect();
+        policies.sort_by_key(|(pattern, _)| pattern.as_str().contains(['*', '?', '[']));
+        Self {
+            policies,
+            entries: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config() -> Self {
+        Self::new(
+            Config::global()
+                .get_param::<BTreeMap<String, ToolCachePolicy>>("GOOSE_TOOL_CACHE")
+                .unwrap_or_default(),
+        )
+    }
+
+    pub fn policy(&self, tool_name: &str) -> Option<&ToolCachePolicy> {
+        self.policies
+            .iter()
+            .find(|(pattern, _)| pattern.matches(tool_name))
+            .map(|(_, policy)| policy)
+    }
+
+    /// Content-addressed key for a call; relative paths are resolved against `working_dir`
+    pub fn key(
+        tool_name: &str,
+        arguments: Option<&JsonObject>,
+        working_dir: Option<&Path>,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(tool_name.as_bytes());
+        hasher.update([0]);
+        if let Some(arguments) = arguments {
+            // serde_json maps are sorted, so equal arguments serialize identically
+            hasher.update(serde_json::to_vec(arguments).unwrap_or_default());
+            for path in path_arguments(arguments) {
+                hasher.update([0]);
+                hasher.update(modified_nanos(path, working_dir).to_le_bytes());
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn get(&self, key: &str) -> Option<CallToolResult> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a successful result; errors are never cached
+    pub fn insert(&self, key: String, result: &CallToolResult, ttl: Duration) {
+        if result.is_error == Some(true) {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        entries.retain(|_, entry| entry.expires_at > now);
+        if entries.len() >= MAX_ENTRIES {
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(key, _)| key.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            CachedResult {
+                result: result.clone(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+/// File paths passed to a tool under one of the conventional argument names
+pub fn path_arguments(arguments: &JsonObject) -> Vec<&str> {
+    PATH_ARGUMENTS
+        .iter()
+        .filter_map(|name| arguments.get(*name))
+        .flat_map(|value| match value {
+            serde_json::Value::String(path) => vec![path.as_str()],
+            serde_json::Value::Array(paths) => paths.iter().filter_map(|p| p.as_str()).collect(),
+            _ => vec![],
+        })
+        .collect()
+}
+
+fn modified_nanos(path: &str, working_dir: Option<&Path>) -> u128 {
+    let path = Path::new(path);
+    let path = match working_dir {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_nanos())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+    use rmcp::object;
+    use std::time::SystemTime;
+
+    fn cache() -> ToolResultCache {
+        ToolResultCache::new(BTreeMap::from([(
+            "read_*".to_string(),
+            ToolCachePolicy { ttl_secs: 60 },
+        )]))
+    }
+
+    #[test]
+    fn only_configured_tools_are_cached() {
+        let cache = cache();
+        assert_eq!(cache.policy("read_file").map(|p| p.ttl_secs), Some(60));
+        assert!(cache.policy("shell").is_none());
+    }
+
+    #[test]
+    fn exact_names_win_over_globs_then_sorted_order() {
+        let cache = ToolResultCache::new(BTreeMap::from([
+            ("read_file".to_string(), ToolCachePolicy { ttl_secs: 10 }),
+            ("read_*".to_string(), ToolCachePolicy { ttl_secs: 60 }),
+            ("*".to_string(), ToolCachePolicy { ttl_secs: 5 }),
+        ]));
+        assert_eq!(cache.policy("read_file").map(|p| p.ttl_secs), Some(10));
+        assert_eq!(cache.policy("read_dir").map(|p| p.ttl_secs), Some(5));
+        assert_eq!(cache.policy("shell").map(|p| p.ttl_secs), Some(5));
+    }
+
+    #[test]
+    fn key_changes_when_file_is_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, "one").unwrap();
+        let args = object!({"path": "notes.txt"});
+
+        let before = ToolResultCache::key("read_file", Some(&args), Some(dir.path()));
+        assert_eq!(
+            before,
+            ToolResultCache::key("read_file", Some(&args), Some(dir.path()))
+        );
+
+        let later = SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_ne!(
+            before,
+            ToolResultCache::key("read_file", Some(&args), Some(dir.path()))
+        );
+    }
+
+    #[test]
+    fn expired_and_failed_results_are_not_served() {
+        let cache = cache();
+        cache.insert(
+            "ok".to_string(),
+            &CallToolResult::success(vec![Content::text("contents")]),
+            Duration::from_secs(60),
+        );
+        cache.insert(
+            "stale".to_string(),
+            &CallToolResult::success(vec![Content::text("old")]),
+            Duration::ZERO,
+        );
+        cache.insert(
+            "failed".to_string(),
+            &CallToolResult::error(vec![Content::text("boom")]),
+            Duration::from_secs(60),
+        );
+
+        assert!(cache.get("ok").is_some());
+        assert!(cache.get("stale").is_none());
+        assert!(cache.get("failed").is_none());
+    }
+}
@@ -4,15 +4,24 @@ use std::sync::Arc;
 
 use async_stream::try_stream;
 use futures::stream::{self, BoxStream};
-use futures::{Stream, StreamExt};
+use futures::{Stream, StreamExt, TryStreamExt};
 use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
-use crate::config::permission::PermissionLevel;
-use crate::hooks::{HookEvent, HookRuntime};
+use crate::agents::lifecycle::{self, LifecycleEvent};
+use crate::config::permission::{GrantScope, PermissionLevel};
+use crate::config::GooseMode;
+use crate::hooks::types::NotificationSeverity;
+use crate::hooks::{HookEvent, HookRuntime, PermissionFinding};
+use crate::i18n;
 use crate::mcp_utils::ToolResult;
-use crate::permission::Permission;
-use rmcp::model::{Content, ServerNotification};
+use crate::notifications::{self, Notification, NotificationKind};
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::permission_judge::PermissionCheckResult;
+use crate::permission::remote_approval::RemoteApproval;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::security::audit;
+use rmcp::model::{CallToolResult, Content, ErrorCode, ErrorData, ServerNotification};
 
 // ToolCallResult combines the result of a tool call with an optional notification stream that
 // can be used to receive notifications from the tool.
@@ -30,7 +39,8 @@ impl From<ToolResult<rmcp::model::CallToolResult>> for ToolCallResult {
     }
 }
 
-use super::agent::{tool_stream, ToolStream};
+use super::agent::{tool_stream, ToolStream, ToolStreamItem};
+use super::tool_bridge::BridgedCall;
 use crate::agents::Agent;
 use crate::conversation::message::{Message, ToolRequest};
 use crate::session::Session;
@@ -40,6 +50,9 @@ pub const DECLINED_RESPONSE: &str = "The user has declined to run this tool. \
     DO NOT attempt to call this tool again. \
     If there are no alternative methods to proceed, clearly explain the situation and STOP.";
 
+pub const STEERING_TOOL_SKIPPED_RESPONSE: &str = "The user sent new instructions before this tool \
+    call ran, so it was skipped. Follow the user's latest message.";
+
 pub const CHAT_MODE_TOOL_SKIPPED_RESPONSE: &str = "Let the user know the tool call was skipped in goose chat mode. \
                                         DO NOT apologize for skipping the tool call. DO NOT say sorry. \
                                         Provide an explanation of what the tool call would do, structured as a \
@@ -49,6 +62,29 @@ pub const CHAT_MODE_TOOL_SKIPPED_RESPONSE: &str = "Let the user know the tool ca
                                         2. **Outline Steps** - Break down the steps.\n \
                                         If needed, adjust the explanation based on user preferences or questions.";
 
+/// Scope and lifetime of the grant recorded for a time-limited or scoped approval
+fn grant_for_permission(
+    permission: &Permission,
+    session: &Session,
+) -> Option<(GrantScope, Option<chrono::Duration>)> {
+    match permission {
+        Permission::AllowForHour => Some((GrantScope::Global, Some(chrono::Duration::hours(1)))),
+        Permission::AllowForSession => Some((
+            GrantScope::Session {
+                session_id: session.id.clone(),
+            },
+            None,
+        )),
+        Permission::AllowForProject => Some((
+            GrantScope::Project {
+                path: session.working_dir.clone(),
+            },
+            None,
+        )),
+        _ => None,
+    }
+}
+
 impl Agent {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn handle_approval_tool_requests<'a>(
@@ -64,30 +100,132 @@ impl Agent {
         try_stream! {
         for request in tool_requests.iter() {
             if let Ok(tool_call) = request.tool_call.clone() {
-                // Find the corresponding inspection result for this tool request
-                let security_message = inspection_results.iter()
-                    .find(|result| result.tool_request_id == request.id)
-                    .and_then(|result| {
+                // Collect the approval messages inspectors attached to this tool request
+                let messages: Vec<&str> = inspection_results.iter()
+                    .filter(|result| result.tool_request_id == request.id)
+                    .filter_map(|result| {
                         if let crate::tool_inspection::InspectionAction::RequireApproval(Some(message)) = &result.action {
-                            Some(message.clone())
+                            Some(message.as_str())
                         } else {
                             None
                         }
+                    })
+                    .collect();
+                let security_message = (!messages.is_empty()).then(|| messages.join("\n\n"));
+
+                // PermissionRequest hooks can deny or approve the call without asking
+                let findings: Vec<PermissionFinding> = inspection_results.iter()
+                    .filter(|result| result.tool_request_id == request.id)
+                    .filter_map(|result| match &result.action {
+                        crate::tool_inspection::InspectionAction::RequireApproval(message) => Some(PermissionFinding {
+                            inspector: result.inspector_name.clone(),
+                            reason: result.reason.clone(),
+                            confidence: result.confidence,
+                            message: message.clone(),
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+                let outcome = hooks.emit(
+                    HookEvent::PermissionRequest {
+                        session_id: session.id.clone(),
+                        tool_name: tool_call.name.to_string(),
+                        tool_input: serde_json::to_value(&tool_call.arguments).unwrap_or_default(),
+                        findings,
+                        cwd: session.working_dir.clone(),
+                    },
+                    &session.working_dir,
+                    cancellation_token.clone().unwrap_or_default(),
+                ).await;
+                let hook_permission = if outcome.blocked {
+                    Some(Permission::DenyOnce)
+                } else if outcome.allowed {
+                    Some(Permission::AllowOnce)
+                } else {
+                    None
+                };
+
+                let remote_approval = if let Some(permission) = hook_permission {
+                    tracing::info!(
+                        "PermissionRequest hook decided {:?} for {}{}",
+                        permission,
+                        tool_call.name,
+                        outcome.reason.as_deref().map(|reason| format!(": {}", reason)).unwrap_or_default()
+                    );
+                    // Answered like a prompt the user never saw
+                    let confirmation = PermissionConfirmation {
+                        principal_type: PrincipalType::Tool,
+                        permission,
+                    };
+                    let _ = self.confirmation_tx.send((request.id.clone(), confirmation)).await;
+                    None
+                } else {
+                    // Headless sessions can route the approval to a remote approver
+                    let remote_approval = RemoteApproval::for_session(session).map(|remote| {
+                        let approval_request = remote.request(
+                            session,
+                            &request.id,
+                            &tool_call.name,
+                            serde_json::to_value(&tool_call.arguments).unwrap_or_default(),
+                            security_message.clone(),
+                        );
+                        let confirmation_tx = self.confirmation_tx.clone();
+                        let request_id = request.id.clone();
+                        let cancel_token = cancellation_token.clone().unwrap_or_default().child_token();
+                        let guard = cancel_token.clone().drop_guard();
+                        tokio::spawn(async move {
+                            if let Some(permission) = remote.await_decision(approval_request, cancel_token).await {
+                                let confirmation = PermissionConfirmation {
+                                    principal_type: PrincipalType::Tool,
+                                    permission,
+                                };
+                                let _ = confirmation_tx.send((request_id, confirmation)).await;
+                            }
+                        });
+                        guard
                     });
 
-                let confirmation = Message::assistant()
-                    .with_action_required(
-                        request.id.clone(),
-                        tool_call.name.to_string().clone(),
-                        tool_call.arguments.clone().unwrap_or_default(),
-                        security_message,
-                    )
-                    .user_only();
-                yield confirmation;
+                    let confirmation = Message::assistant()
+                        .with_action_required(
+                            request.id.clone(),
+                            tool_call.name.to_string().clone(),
+                            tool_call.arguments.clone().unwrap_or_default(),
+                            security_message.clone(),
+                        )
+                        .user_only();
+                    lifecycle::publish(LifecycleEvent::ApprovalRequested {
+                        session_id: session.id.clone(),
+                        request_id: request.id.clone(),
+                        tool_name: tool_call.name.to_string(),
+                    });
+                    notifications::notify(Notification::new(
+                        NotificationKind::ApprovalNeeded,
+                        session.id.clone(),
+                        Some(session.session_type),
+                        i18n::text_with("approval.needed", &[("tool", &tool_call.name)]),
+                    ));
+                    hooks.emit(
+                        HookEvent::Notification {
+                            session_id: session.id.clone(),
+                            notification_type: "permission_prompt".to_string(),
+                            message: i18n::text_with("approval.needed", &[("tool", &tool_call.name)]),
+                            severity: NotificationSeverity::Warning,
+                            tool_name: Some(tool_call.name.to_string()),
+                            detail: security_message.clone(),
+                            cwd: session.working_dir.clone(),
+                        },
+                        &session.working_dir,
+                        cancellation_token.clone().unwrap_or_default(),
+                    ).await;
+                    yield confirmation;
+                    remote_approval
+                };
 
                 let mut rx = self.confirmation_rx.lock().await;
                 while let Some((req_id, confirmation)) = rx.recv().await {
                     if req_id == request.id {
+                        audit::record_confirmation(&session.id, request, &confirmation.permission);
+
                         // Log user decision if this was a security alert
                         if let Some(finding_id) = get_security_finding_id_from_results(&request.id, inspection_results) {
                             tracing::info!(
@@ -99,7 +237,7 @@ impl Agent {
                             );
                         }
 
-                        if confirmation.permission == Permission::AllowOnce || confirmation.permission == Permission::AlwaysAllow {
+                        if confirmation.permission.is_allow() {
                             // Fire PreToolUse hook — if blocked, treat as declined
                             let outcome = hooks.emit(
                                 HookEvent::PreToolUse {
@@ -146,6 +284,9 @@ impl Agent {
                                 self.tool_inspection_manager
                                     .update_permission_manager(&tool_call.name, PermissionLevel::AlwaysAllow)
                                     .await;
+                            } else if let Some((scope, ttl)) = grant_for_permission(&confirmation.permission, session) {
+                                self.tool_inspection_manager
+                                    .add_permission_grant(&tool_call.name, scope, ttl);
                             }
                         } else {
                             // User declined - update the specific response message for this request
@@ -167,11 +308,124 @@ impl Agent {
                         break; // Exit the loop once the matching `req_id` is found
                     }
                 }
+                // Stop waiting on the remote approver once a decision has been made
+                drop(remote_approval);
             }
         }
     }.boxed()
     }
 
+    /// Run a tool call the provider's CLI made through the tool bridge, with the same
+    /// inspection, approvals and hooks as the agent's own calls, and answer it. Yields the
+    /// approval prompts to show the user.
+    pub(crate) fn handle_bridged_call<'a>(
+        &'a self,
+        call: BridgedCall,
+        messages: &'a [Message],
+        goose_mode: GooseMode,
+        cancellation_token: Option<CancellationToken>,
+        session: &'a Session,
+        hooks: &'a HookRuntime,
+    ) -> BoxStream<'a, anyhow::Result<Message>> {
+        try_stream! {
+            let BridgedCall { tool_call, respond } = call;
+            let request = ToolRequest {
+                id: format!("bridge_{}", uuid::Uuid::new_v4().simple()),
+                tool_call: Ok(tool_call.clone()),
+                metadata: None,
+                tool_meta: None,
+            };
+            let requests = [request.clone()];
+            let inspection_results = self.tool_inspection_manager
+                .inspect_tools(&session.id, &requests, messages, goose_mode)
+                .await?;
+            let permission_check_result = self.tool_inspection_manager
+                .process_inspection_results_with_permission_inspector(&requests, &inspection_results)
+                .unwrap_or_else(|| PermissionCheckResult {
+                    approved: vec![],
+                    needs_approval: requests.to_vec(),
+                    denied: vec![],
+                });
+            audit::record_policy_decisions(&session.id, &permission_check_result);
+
+            let response = Arc::new(Mutex::new(Message::user()));
+            let request_to_response_map = HashMap::from([(request.id.clone(), response.clone())]);
+            let tool_futures = self.handle_approved_and_denied_tools(
+                &permission_check_result,
+                &request_to_response_map,
+                cancellation_token.clone(),
+                session,
+                hooks,
+            ).await?;
+            let tool_futures = Arc::new(Mutex::new(tool_futures));
+            let mut approvals = self.handle_approval_tool_requests(
+                &permission_check_result.needs_approval,
+                tool_futures.clone(),
+                &request_to_response_map,
+                cancellation_token.clone(),
+                session,
+                &inspection_results,
+                hooks,
+            );
+            while let Some(msg) = approvals.try_next().await? {
+                yield msg;
+            }
+            drop(approvals);
+
+            let started = tool_futures.lock().await.pop();
+            let output = match started {
+                Some((_, mut tool_stream)) => {
+                    let mut output = Err(ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        "The tool returned no result".to_string(),
+                        None,
+                    ));
+                    while let Some(item) = tool_stream.next().await {
+                        if let ToolStreamItem::Result(result) = item {
+                            output = result;
+                        }
+                    }
+
+                    let tool_input = serde_json::to_value(&tool_call.arguments).unwrap_or_default();
+                    let event = match &output {
+                        Ok(result) => HookEvent::PostToolUse {
+                            session_id: session.id.clone(),
+                            tool_name: tool_call.name.to_string(),
+                            tool_input,
+                            tool_output: serde_json::to_value(&result.content)
+                                .map(|v| v.to_string())
+                                .unwrap_or_default(),
+                            cwd: session.working_dir.clone(),
+                        },
+                        Err(error) => HookEvent::PostToolUseFailure {
+                            session_id: session.id.clone(),
+                            tool_name: tool_call.name.to_string(),
+                            tool_input,
+                            tool_error: error.message.to_string(),
+                            cwd: session.working_dir.clone(),
+                        },
+                    };
+                    let outcome = hooks.emit(
+                        event,
+                        &session.working_dir,
+                        cancellation_token.clone().unwrap_or_default(),
+                    ).await;
+                    // The CLI keeps its own conversation, so hook context goes back with the result
+                    if let (Some(context), Ok(result)) = (outcome.context, &mut output) {
+                        result.content.push(Content::text(context));
+                    }
+                    output
+                }
+                // Declined or blocked by a hook; the response says which
+                None => response.lock().await.content.iter()
+                    .find_map(|content| content.as_tool_response())
+                    .map(|response| response.tool_result.clone())
+                    .unwrap_or_else(|| Ok(CallToolResult::error(vec![Content::text(DECLINED_RESPONSE)]))),
+            };
+            let _ = respond.send(output);
+        }.boxed()
+    }
+
     pub(crate) fn handle_frontend_tool_request<'a>(
         &'a self,
         tool_request: &'a ToolRequest,
@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use futures::future::{FutureExt, Shared};
+use futures::stream::{self, BoxStream, StreamExt};
+use rmcp::model::CallToolResult;
+use tokio::sync::Semaphore;
+
+use super::agent::{ToolStream, ToolStreamItem};
+use super::extension_manager::{ExtensionManager, ToolEffect};
+use super::tool_cache::path_arguments;
+use crate::config::Config;
+use crate::conversation::message::ToolRequest;
+use crate::mcp_utils::ToolResult;
+
+/// Default for `GOOSE_MAX_PARALLEL_TOOL_CALLS`
+pub const DEFAULT_MAX_PARALLEL_TOOL_CALLS: usize = 8;
+
+pub fn max_parallel_tool_calls() -> usize {
+    Config::global()
+        .get_param::<usize>("GOOSE_MAX_PARALLEL_TOOL_CALLS")
+        .unwrap_or(DEFAULT_MAX_PARALLEL_TOOL_CALLS)
+        .max(1)
+}
+
+/// What a tool call may touch, used to decide which calls of a turn can run together
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolFootprint {
+    pub extension: Option<String>,
+    pub paths: HashSet<PathBuf>,
+    pub mutating: bool,
+}
+
+impl ToolFootprint {
+    pub async fn of(
+        extension_manager: &ExtensionManager,
+        session_id: &str,
+        request: &ToolRequest,
+        working_dir: &Path,
+    ) -> Self {
+        let Ok(tool_call) = &request.tool_call else {
+            return Self::default();
+        };
+        let paths = tool_call
+            .arguments
+            .as_ref()
+            .map(|arguments| {
+                path_arguments(arguments)
+                    .into_iter()
+                    .map(|path| working_dir.join(path))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            extension: extension_manager
+                .tool_owner(session_id, &tool_call.name)
+                .await,
+            paths,
+            mutating: extension_manager
+                .classify_tool(session_id, &tool_call.name)
+                .await
+                == ToolEffect::Mutating,
+        }
+    }
+
+    /// Calls that share a file, or share an extension while either may change its state,
+    /// have to run in the order the model requested them
+    pub fn conflicts_with(&self, other: &ToolFootprint) -> bool {
+        if !self.paths.is_disjoint(&other.paths) {
+            return true;
+        }
+        self.extension.is_some()
+            && self.extension == other.extension
+            && (self.mutating || other.mutating)
+    }
+}
+
+type Done = Shared<oneshot::Receiver<()>>;
+
+/// Run the tool streams of one turn, at most `limit` at a time. A call starts only after
+/// every earlier call it conflicts with has finished; independent calls run concurrently.
+pub fn schedule_tool_streams(
+    calls: Vec<(String, ToolStream, ToolFootprint)>,
+    limit: usize,
+) -> BoxStream<'static, (String, ToolStreamItem<ToolResult<CallToolResult>>)> {
+    let semaphore = Arc::new(Semaphore::new(limit.max(1)));
+    let mut started: Vec<(ToolFootprint, Done)> = Vec::new();
+    let mut scheduled = Vec::new();
+
+    for (request_id, tool_stream, footprint) in calls {
+        let dependencies: Vec<Done> = started
+            .iter()
+            .filter(|(earlier, _)| earlier.conflicts_with(&footprint))
+            .map(|(_, done)| done.clone())
+            .collect();
+        let (done_tx, done_rx) = oneshot::channel::<()>();
+        started.push((footprint, done_rx.shared()));
+
+        let semaphore = semaphore.clone();
+        scheduled.push(
+            async_stream::stream! {
+                for dependency in dependencies {
+                    // Resolves once the earlier call's stream is finished or dropped
+                    let _ = dependency.await;
+                }
+                let _permit = semaphore.acquire_owned().await;
+                let mut tool_stream = tool_stream;
+                while let Some(item) = tool_stream.next().await {
+                    yield (request_id.clone(), item);
+                }
+                drop(done_tx);
+            }
+            .boxed(),
+        );
+    }
+
+    stream::select_all(scheduled).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::agent::tool_stream;
+    use rmcp::model::Content;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn footprint(extension: &str, paths: &[&str], mutating: bool) -> ToolFootprint {
+        ToolFootprint {
+            extension: Some(extension.to_string()),
+            paths: paths.iter().map(PathBuf::from).collect(),
+            mutating,
+        }
+    }
+
+    #[test]
+    fn conflicts_on_shared_files_and_extension_writes() {
+        let read_a = footprint("developer", &["/p/a.rs"], false);
+        let read_b = footprint("developer", &["/p/b.rs"], false);
+        let write_a = footprint("developer", &["/p/a.rs"], true);
+        let search = footprint("github", &[], false);
+
+        assert!(!read_a.conflicts_with(&read_b));
+        assert!(read_a.conflicts_with(&write_a));
+        assert!(read_b.conflicts_with(&write_a));
+        assert!(!search.conflicts_with(&write_a));
+    }
+
+    fn timed_stream(running: Arc<AtomicUsize>, peak: Arc<AtomicUsize>) -> ToolStream {
+        tool_stream(Box::new(stream::empty()), async move {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok(CallToolResult::success(vec![Content::text("done")]))
+        })
+    }
+
+    #[tokio::test]
+    async fn independent_calls_run_concurrently_up_to_the_limit() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let calls = (0..4)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    timed_stream(running.clone(), peak.clone()),
+                    footprint(&format!("ext{}", i), &[], false),
+                )
+            })
+            .collect();
+
+        let results: Vec<_> = schedule_tool_streams(calls, 2).collect().await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn conflicting_calls_finish_in_request_order() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let calls = (0..3)
+            .map(|i| {
+                (
+                    i.to_string(),
+                    timed_stream(running.clone(), peak.clone()),
+                    footprint("developer", &["/p/main.rs"], true),
+                )
+            })
+            .collect();
+
+        let order: Vec<_> = schedule_tool_streams(calls, 8)
+            .map(|(id, _)| id)
+            .collect()
+            .await;
+        assert_eq!(order, vec!["0", "1", "2"]);
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,411 @@
+//! Sends only the most relevant tool schemas when the full set would crowd the context.
+//!
+//! Large extension sets add tens of thousands of tokens of schemas to every request.
+//! `GOOSE_TOOL_BUDGET` caps them:
+//!
+//! ```yaml
+//! GOOSE_TOOL_BUDGET:
+//!   tokens: 8000
+//!   max_tools: 40
+//! ```
+//!
+//! Tools are ranked by how recently the conversation used them and by how close their
+//! description is to the latest user messages (embedding similarity when the provider has
+//! embeddings, shared terms otherwise). The best ones that fit are sent, together with
+//! `platform__list_all_tools`, which the model calls to get the full set back for the rest
+//! of the reply. The selection is made once at the start of a reply and kept for all of its
+//! requests, unless the available tools change.
+
+use crate::agents::final_output_tool::FINAL_OUTPUT_TOOL_NAME;
+use crate::agents::platform_extensions::MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE;
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::Conversation;
+use crate::providers::base::Provider;
+use crate::token_counter::{create_token_counter, TokenCounter};
+use indoc::indoc;
+use once_cell::sync::Lazy;
+use rmcp::model::{Role, Tool, ToolAnnotations};
+use rmcp::object;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub const LIST_ALL_TOOLS_TOOL_NAME: &str = "platform__list_all_tools";
+
+/// How many of the latest tool calls count as recent use
+const RECENT_TOOL_CALLS: usize = 20;
+/// How many of the latest user messages the tools are matched against
+const QUERY_USER_MESSAGES: usize = 3;
+
+/// Tools that are always sent, since the agent loop depends on them
+const PINNED_TOOLS: &[&str] = &[FINAL_OUTPUT_TOOL_NAME, MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE];
+
+static TOOL_EMBEDDINGS: Lazy<Mutex<HashMap<blake3::Hash, Vec<f32>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// `GOOSE_TOOL_BUDGET`: limits on the tool schemas sent with each request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolBudget {
+    /// Tokens the schemas may take
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<usize>,
+    /// Number of tools to send
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tools: Option<usize>,
+}
+
+impl ToolBudget {
+    pub fn from_config() -> Self {
+        Config::global()
+            .get_param::<ToolBudget>("GOOSE_TOOL_BUDGET")
+            .unwrap_or_default()
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.tokens.is_some() || self.max_tools.is_some()
+    }
+}
+
+pub fn list_all_tools_tool() -> Tool {
+    Tool::new(
+        LIST_ALL_TOOLS_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Only the tools most relevant to the conversation were included to save context.
+            Call this when none of them fits the task: it lists every available tool, and all
+            of them can be called from the next step on.
+        "#}
+        .to_string(),
+        object!({ "type": "object", "properties": {} }),
+    )
+    .annotate(
+        ToolAnnotations::with_title("List all tools".to_string())
+            .read_only(true)
+            .open_world(false),
+    )
+}
+
+/// The listing returned by the escape hatch tool
+pub fn describe_tools(tools: &[Tool]) -> String {
+    let mut lines: Vec<String> = tools
+        .iter()
+        .filter(|tool| tool.name != LIST_ALL_TOOLS_TOOL_NAME)
+        .map(|tool| {
+            let description = tool.description.as_deref().unwrap_or_default();
+            let summary = description.trim().lines().next().unwrap_or_default();
+            format!("- {}: {}", tool.name, summary)
+        })
+        .collect();
+    lines.sort();
+    format!("All available tools:\n{}", lines.join("\n"))
+}
+
+/// Recency score per tool name: 1.0 for the latest call, falling off linearly over the last
+/// `RECENT_TOOL_CALLS` calls
+fn recent_use(messages: &[Message]) -> HashMap<String, f32> {
+    let mut scores = HashMap::new();
+    let recent_calls = messages
+        .iter()
+        .rev()
+        .flat_map(|message| message.content.iter().rev())
+        .filter_map(|content| match content {
+            MessageContent::ToolRequest(request) => request.tool_call.as_ref().ok(),
+            _ => None,
+        })
+        .take(RECENT_TOOL_CALLS)
+        .enumerate();
+    for (position, call) in recent_calls {
+        let score = 1.0 - position as f32 / RECENT_TOOL_CALLS as f32;
+        scores.entry(call.name.to_string()).or_insert(score);
+    }
+    scores
+}
+
+fn query_text(messages: &[Message]) -> String {
+    let mut texts: Vec<String> = messages
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::User && message.is_user_visible())
+        .map(Message::as_concat_text)
+        .filter(|text| !text.trim().is_empty())
+        .take(QUERY_USER_MESSAGES)
+        .collect();
+    texts.reverse();
+    texts.join("\n")
+}
+
+fn tool_text(tool: &Tool) -> String {
+    format!(
+        "{}: {}",
+        tool.name,
+        tool.description.as_deref().unwrap_or_default()
+    )
+}
+
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Share of the query terms found in each tool's name and description
+fn keyword_similarity(query: &str, tools: &[Tool]) -> HashMap<String, f32> {
+    let mut query_terms = terms(query);
+    query_terms.sort();
+    query_terms.dedup();
+    if query_terms.is_empty() {
+        return HashMap::new();
+    }
+    tools
+        .iter()
+        .map(|tool| {
+            let text = tool_text(tool).to_lowercase();
+            let matched = query_terms
+                .iter()
+                .filter(|term| text.contains(term.as_str()))
+                .count();
+            (
+                tool.name.to_string(),
+                matched as f32 / query_terms.len() as f32,
+            )
+        })
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Cosine similarity of each tool to the query. Tool embeddings are kept by content hash,
+/// so each description is embedded once per process.
+async fn embedding_similarity(
+    provider: &dyn Provider,
+    session_id: &str,
+    query: &str,
+    tools: &[Tool],
+) -> anyhow::Result<HashMap<String, f32>> {
+    let hashes: Vec<blake3::Hash> = tools
+        .iter()
+        .map(|tool| blake3::hash(tool_text(tool).as_bytes()))
+        .collect();
+    let missing: Vec<(blake3::Hash, String)> = {
+        let cache = TOOL_EMBEDDINGS.lock().unwrap();
+        tools
+            .iter()
+            .zip(&hashes)
+            .filter(|(_, hash)| !cache.contains_key(*hash))
+            .map(|(tool, hash)| (*hash, tool_text(tool)))
+            .collect()
+    };
+
+    let mut texts: Vec<String> = missing.iter().map(|(_, text)| text.clone()).collect();
+    texts.push(query.to_string());
+    let mut vectors = provider.create_embeddings(session_id, texts).await?;
+    let query_vector = vectors
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("The provider returned no embeddings"))?;
+
+    let mut cache = TOOL_EMBEDDINGS.lock().unwrap();
+    for ((hash, _), vector) in missing.into_iter().zip(vectors) {
+        cache.insert(hash, vector);
+    }
+    Ok(tools
+        .iter()
+        .zip(&hashes)
+        .filter_map(|(tool, hash)| {
+            cache.get(hash).map(|vector| {
+                (
+                    tool.name.to_string(),
+                    cosine_similarity(&query_vector, vector),
+                )
+            })
+        })
+        .collect())
+}
+
+/// Pick the tools to send: pinned tools first, then by score, skipping any that would go
+/// over the budget. Returns None when every tool fits.
+fn select(
+    tools: &[Tool],
+    scores: &HashMap<String, f32>,
+    budget: &ToolBudget,
+    counter: &TokenCounter,
+) -> Option<Vec<Tool>> {
+    let max_tools = budget.max_tools.unwrap_or(usize::MAX);
+    let max_tokens = budget.tokens.unwrap_or(usize::MAX);
+    if tools.len() <= max_tools && counter.count_tokens_for_tools(tools) <= max_tokens {
+        return None;
+    }
+
+    let hatch = list_all_tools_tool();
+    let mut ranked: Vec<&Tool> = tools.iter().collect();
+    let rank = |tool: &Tool| {
+        let pinned = PINNED_TOOLS.contains(&tool.name.as_ref());
+        let score = scores.get(tool.name.as_ref()).copied().unwrap_or(0.0);
+        (pinned, score)
+    };
+    ranked.sort_by(|a, b| {
+        let (a_pinned, a_score) = rank(a);
+        let (b_pinned, b_score) = rank(b);
+        b_pinned
+            .cmp(&a_pinned)
+            .then(b_score.total_cmp(&a_score))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let mut selected = Vec::new();
+    let mut used_tokens = counter.count_tokens_for_tools(std::slice::from_ref(&hatch));
+    for tool in ranked {
+        if selected.len() + 1 >= max_tools {
+            break;
+        }
+        let tokens = counter.count_tokens_for_tools(std::slice::from_ref(tool));
+        if used_tokens + tokens > max_tokens {
+            continue;
+        }
+        used_tokens += tokens;
+        selected.push(tool.clone());
+    }
+    selected.push(hatch);
+    // Stable tool ordering is important for multi session prompt caching.
+    selected.sort_by(|a, b| a.name.cmp(&b.name));
+    Some(selected)
+}
+
+/// The tools to send with the next request, within `GOOSE_TOOL_BUDGET`
+pub async fn select_tools(
+    provider: &dyn Provider,
+    session_id: &str,
+    conversation: &Conversation,
+    tools: &[Tool],
+) -> Vec<Tool> {
+    let budget = ToolBudget::from_config();
+    if !budget.is_limited() || tools.is_empty() {
+        return tools.to_vec();
+    }
+    let counter = match create_token_counter().await {
+        Ok(counter) => counter,
+        Err(e) => {
+            tracing::warn!(
+                "Sending every tool, the token counter is unavailable: {}",
+                e
+            );
+            return tools.to_vec();
+        }
+    };
+
+    let messages = conversation.messages();
+    let query = query_text(messages);
+    let mut similarity = HashMap::new();
+    if !query.is_empty() {
+        if provider.supports_embeddings() {
+            match embedding_similarity(provider, session_id, &query, tools).await {
+                Ok(scores) => similarity = scores,
+                Err(e) => tracing::debug!("Ranking tools by keywords instead: {}", e),
+            }
+        }
+        if similarity.is_empty() {
+            similarity = keyword_similarity(&query, tools);
+        }
+    }
+
+    let recent = recent_use(messages);
+    let scores: HashMap<String, f32> = tools
+        .iter()
+        .map(|tool| {
+            let name = tool.name.to_string();
+            let score = recent.get(&name).copied().unwrap_or(0.0)
+                + similarity.get(&name).copied().unwrap_or(0.0);
+            (name, score)
+        })
+        .collect();
+
+    match select(tools, &scores, &budget, &counter) {
+        Some(selected) => {
+            tracing::debug!(
+                sent = selected.len() - 1,
+                available = tools.len(),
+                "Trimmed the tool list to the tool budget"
+            );
+            selected
+        }
+        None => tools.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::CallToolRequestParams;
+
+    fn tool(name: &str, description: &str) -> Tool {
+        Tool::new(
+            name.to_string(),
+            description.to_string(),
+            object!({ "type": "object", "properties": {} }),
+        )
+    }
+
+    fn names(tools: &[Tool]) -> Vec<String> {
+        tools.iter().map(|tool| tool.name.to_string()).collect()
+    }
+
+    #[test]
+    fn recent_calls_score_higher() {
+        let messages = vec![
+            Message::assistant().with_tool_request("1", Ok(CallToolRequestParams::new("a"))),
+            Message::assistant().with_tool_request("2", Ok(CallToolRequestParams::new("b"))),
+        ];
+        let scores = recent_use(&messages);
+        assert_eq!(scores["b"], 1.0);
+        assert!(scores["a"] < scores["b"]);
+        assert!(!scores.contains_key("c"));
+    }
+
+    #[test]
+    fn keyword_similarity_matches_descriptions() {
+        let tools = vec![
+            tool("github__list_issues", "List issues in a GitHub repository"),
+            tool("slack__post", "Post a message to a Slack channel"),
+        ];
+        let scores = keyword_similarity("which github issues are open?", &tools);
+        assert!(scores["github__list_issues"] > scores["slack__post"]);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_best_tools_within_the_budget() {
+        let counter = create_token_counter().await.unwrap();
+        let tools = vec![
+            tool("a__one", "first"),
+            tool("b__two", "second"),
+            tool("c__three", "third"),
+            tool(FINAL_OUTPUT_TOOL_NAME, "final output"),
+        ];
+        let scores = HashMap::from([("c__three".to_string(), 1.0), ("a__one".to_string(), 0.5)]);
+        let budget = ToolBudget {
+            tokens: None,
+            max_tools: Some(3),
+        };
+
+        let selected = select(&tools, &scores, &budget, &counter).unwrap();
+        assert_eq!(
+            names(&selected),
+            ["c__three", LIST_ALL_TOOLS_TOOL_NAME, FINAL_OUTPUT_TOOL_NAME]
+        );
+
+        let roomy = ToolBudget {
+            tokens: Some(100_000),
+            max_tools: None,
+        };
+        assert!(select(&tools, &scores, &roomy, &counter).is_none());
+    }
+}
@@ -0,0 +1,139 @@
+//! Validation of model turns, so malformed tool calls can be retried with a correction
+//! instead of ending the turn with a raw provider error.
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent, ToolRequest};
+use crate::providers::errors::ProviderError;
+use rmcp::model::Tool;
+use std::collections::HashSet;
+
+/// How many times a turn is retried after failing validation, unless
+/// GOOSE_TURN_VALIDATION_RETRIES says otherwise
+pub const DEFAULT_TURN_VALIDATION_RETRIES: u32 = 2;
+
+/// Provider error text that means the model produced an invalid tool call, rather than a
+/// problem with the request or the service
+const TOOL_VALIDATION_MARKERS: &[&str] = &[
+    "tool_use_failed",
+    "failed to call a function",
+    "invalid tool call",
+    "tool call validation failed",
+    "failed to parse tool call",
+];
+
+pub fn max_retries() -> u32 {
+    Config::global()
+        .get_param::<u32>("GOOSE_TURN_VALIDATION_RETRIES")
+        .unwrap_or(DEFAULT_TURN_VALIDATION_RETRIES)
+}
+
+/// Problems with the tool calls in a response, one line per call
+pub fn validate_tool_requests<'a>(
+    requests: impl IntoIterator<Item = &'a ToolRequest>,
+    tools: &[Tool],
+) -> Vec<String> {
+    let known: HashSet<&str> = tools.iter().map(|tool| tool.name.as_ref()).collect();
+    requests
+        .into_iter()
+        .filter_map(|request| match &request.tool_call {
+            Err(e) => Some(format!("A tool call could not be parsed: {}", e.message)),
+            Ok(call) if !known.contains(call.name.as_ref()) => {
+                Some(format!("There is no tool named '{}'", call.name))
+            }
+            Ok(_) => None,
+        })
+        .collect()
+}
+
+pub fn is_tool_validation_error(error: &ProviderError) -> bool {
+    let message = match error {
+        ProviderError::RequestFailed(message) | ProviderError::ExecutionError(message) => {
+            message.to_lowercase()
+        }
+        _ => return false,
+    };
+    TOOL_VALIDATION_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
+
+/// The response without its tool calls, if anything else is left
+pub fn without_tool_requests(response: &Message) -> Option<Message> {
+    let mut message = response.clone();
+    message
+        .content
+        .retain(|content| !matches!(content, MessageContent::ToolRequest(_)));
+    (!message.content.is_empty()).then_some(message)
+}
+
+/// A message for the model explaining what was wrong with its last turn
+pub fn correction_message(problems: &[String]) -> Message {
+    Message::user()
+        .with_text(format!(
+            "Your last response could not be used:\n- {}\n\nTry again, calling only the tools \
+             you were given, with arguments that match their schemas.",
+            problems.join("\n- ")
+        ))
+        .agent_only()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolRequestParams, ErrorCode, ErrorData};
+    use rmcp::object;
+
+    fn request(id: &str, name: &str) -> ToolRequest {
+        ToolRequest {
+            id: id.to_string(),
+            tool_call: Ok(CallToolRequestParams::new(name.to_string())),
+            metadata: None,
+            tool_meta: None,
+        }
+    }
+
+    #[test]
+    fn flags_unknown_and_malformed_calls() {
+        let tools = vec![Tool::new(
+            "developer__shell".to_string(),
+            "Run a command".to_string(),
+            object!({ "type": "object", "properties": { } }),
+        )];
+        let malformed = ToolRequest {
+            tool_call: Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "expected value at line 1",
+                None,
+            )),
+            ..request("3", "")
+        };
+        let requests = [
+            request("1", "developer__shell"),
+            request("2", "developer__bash"),
+            malformed,
+        ];
+
+        let problems = validate_tool_requests(&requests, &tools);
+
+        assert_eq!(
+            problems,
+            vec![
+                "There is no tool named 'developer__bash'",
+                "A tool call could not be parsed: expected value at line 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_tool_validation_errors() {
+        assert!(is_tool_validation_error(&ProviderError::RequestFailed(
+            "400: {\"error\": {\"code\": \"tool_use_failed\"}}".to_string()
+        )));
+        assert!(!is_tool_validation_error(&ProviderError::RequestFailed(
+            "400: model not found".to_string()
+        )));
+        assert!(!is_tool_validation_error(&ProviderError::ServerError(
+            "invalid tool call".to_string()
+        )));
+    }
+}
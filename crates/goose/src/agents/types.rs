@@ -80,6 +80,15 @@ pub struct FrontendTool {
     pub tool: Tool,
 }
 
+/// A message the user sent while a turn was in progress
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SteeringMessage {
+    pub text: String,
+    /// Skip the tool calls the model requested that have not started yet
+    #[serde(default)]
+    pub cancel_pending_tools: bool,
+}
+
 /// Session configuration for an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionConfig {
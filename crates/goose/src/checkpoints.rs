@@ -0,0 +1,485 @@
+//! Git-backed checkpoints of the files an agent changes.
+//!
+//! Each session records its checkpoints as commits on its own ref,
+//! `refs/goose/checkpoints/<session_id>`, built from a private index so the user's
+//! branch, index and stash are never touched. A checkpoint is only created when the
+//! workspace differs from the previous one. Only the newest [`MAX_CHECKPOINTS`] are
+//! kept, and the ref is dropped with the session.
+
+use crate::config::Config;
+use crate::subprocess::SubprocessExt;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+use utoipa::ToSchema;
+
+const REF_PREFIX: &str = "refs/goose/checkpoints";
+const FIELD_SEPARATOR: char = '\u{1f}';
+pub const MAX_CHECKPOINTS: usize = 100;
+
+/// Whether replies are checkpointed automatically. Off unless `GOOSE_CHECKPOINTS` is set.
+pub fn enabled() -> bool {
+    Config::global()
+        .get_param::<bool>("GOOSE_CHECKPOINTS")
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// The commit id of the checkpoint
+    pub id: String,
+    /// The previous checkpoint, if any
+    pub parent: Option<String>,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The checkpoints of one session in one git repository
+#[derive(Debug, Clone)]
+pub struct CheckpointStore {
+    repo_root: PathBuf,
+    reference: String,
+}
+
+impl CheckpointStore {
+    /// Open the store for the repository containing `working_dir`, or `None` when it is
+    /// not inside a git work tree
+    pub async fn open(working_dir: &Path, session_id: &str) -> Option<Self> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(working_dir)
+            .args(["rev-parse", "--show-toplevel"])
+            .set_no_window()
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let repo_root = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        Some(Self {
+            repo_root,
+            reference: format!("{}/{}", REF_PREFIX, session_id),
+        })
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    /// Record the current state of the work tree. Returns `None` when nothing changed
+    /// since the last checkpoint.
+    pub async fn create(&self, message: &str) -> Result<Option<Checkpoint>> {
+        let tree = self.snapshot_tree().await?;
+        let parent = self.tip().await?;
+        if let Some(parent) = &parent {
+            if self.tree_of(parent).await? == tree {
+                return Ok(None);
+            }
+        }
+        let id = self.commit(&tree, parent.as_deref(), message).await?;
+        self.move_ref(&id, parent.as_deref()).await?;
+        let id = self.prune(MAX_CHECKPOINTS).await?.unwrap_or(id);
+        self.get(&id).await.map(Some)
+    }
+
+    /// Whether any checkpoint has been recorded
+    pub async fn exists(&self) -> Result<bool> {
+        Ok(self.tip().await?.is_some())
+    }
+
+    /// Drop every checkpoint of the session
+    pub async fn delete(&self) -> Result<()> {
+        if let Some(tip) = self.tip().await? {
+            self.git(&["update-ref", "-d", &self.reference, &tip])
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Keep only the newest `max` checkpoints. The kept ones are rewritten onto a new
+    /// root, so their ids change; returns the new tip when anything was dropped.
+    async fn prune(&self, max: usize) -> Result<Option<String>> {
+        let Some(tip) = self.tip().await? else {
+            return Ok(None);
+        };
+        let ids = self
+            .git(&["rev-list", &format!("--max-count={}", max + 1), &tip])
+            .await?;
+        let ids: Vec<&str> = ids.lines().collect();
+        if ids.len() <= max {
+            return Ok(None);
+        }
+
+        let mut parent: Option<String> = None;
+        for id in ids[..max].iter().rev() {
+            let checkpoint = self.get(id).await?;
+            let tree = self.tree_of(id).await?;
+            let mut args = vec![
+                "commit-tree",
+                "--no-gpg-sign",
+                tree.as_str(),
+                "-m",
+                checkpoint.message.as_str(),
+            ];
+            if let Some(parent) = &parent {
+                args.extend(["-p", parent.as_str()]);
+            }
+            let date = format!("{} +0000", checkpoint.created_at.timestamp());
+            let mut command = self.command();
+            command
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date);
+            parent = Some(Self::run(command, &args).await?.trim().to_string());
+        }
+        let new_tip = parent.unwrap_or(tip.clone());
+        self.move_ref(&new_tip, Some(&tip)).await?;
+        Ok(Some(new_tip))
+    }
+
+    /// All checkpoints, newest first
+    pub async fn list(&self) -> Result<Vec<Checkpoint>> {
+        if self.tip().await?.is_none() {
+            return Ok(Vec::new());
+        }
+        let log = self
+            .git(&["log", "--format=%H%x1f%P%x1f%ct%x1f%s", &self.reference])
+            .await?;
+        log.lines().map(parse_checkpoint).collect()
+    }
+
+    /// The diff from a checkpoint to another one, or to the current work tree
+    pub async fn diff(&self, from: &str, to: Option<&str>) -> Result<String> {
+        let from = self.resolve(from).await?;
+        let to = match to {
+            Some(to) => self.resolve(to).await?,
+            None => self.snapshot_tree().await?,
+        };
+        self.git(&["diff", "--no-renames", "--no-color", &from, &to])
+            .await
+    }
+
+    /// Restore the work tree to a checkpoint. The state being replaced is checkpointed
+    /// first, so a revert can itself be reverted.
+    pub async fn revert(&self, id: &str) -> Result<Checkpoint> {
+        let target = self.resolve(id).await?;
+        let short = &target[..target.len().min(12)];
+        self.create(&format!("Before reverting to {}", short))
+            .await?;
+        let current = self
+            .tip()
+            .await?
+            .ok_or_else(|| anyhow!("No checkpoints recorded"))?;
+
+        let added = self.changed_files(&target, &current, "A").await?;
+        for file in &added {
+            let path = self.repo_root.join(file);
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+
+        let restored = self.changed_files(&target, &current, "DMT").await?;
+        if !restored.is_empty() {
+            let index = tempfile::tempdir()?;
+            let index_file = index.path().join("index");
+            self.git_with_index(&index_file, &["read-tree", &target])
+                .await?;
+            let mut args = vec!["checkout-index", "-f", "--"];
+            args.extend(restored.iter().map(String::as_str));
+            self.git_with_index(&index_file, &args).await?;
+        }
+
+        match self.create(&format!("Reverted to {}", short)).await? {
+            Some(checkpoint) => Ok(checkpoint),
+            None => self.get(&current).await,
+        }
+    }
+
+    /// Collapse every checkpoint after `since` into a single checkpoint
+    pub async fn squash(&self, since: &str, message: Option<&str>) -> Result<Checkpoint> {
+        let base = self.resolve(since).await?;
+        let tip = self
+            .tip()
+            .await?
+            .ok_or_else(|| anyhow!("No checkpoints recorded"))?;
+        if base == tip {
+            bail!("Nothing to squash after {}", since);
+        }
+        let count = self
+            .git(&["rev-list", "--count", &format!("{}..{}", base, tip)])
+            .await?;
+        let message = message
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Squashed {} checkpoints", count.trim()));
+        let tree = self.tree_of(&tip).await?;
+        let id = self.commit(&tree, Some(&base), &message).await?;
+        self.move_ref(&id, Some(&tip)).await?;
+        self.get(&id).await
+    }
+
+    async fn get(&self, id: &str) -> Result<Checkpoint> {
+        let line = self
+            .git(&["log", "-1", "--format=%H%x1f%P%x1f%ct%x1f%s", id])
+            .await?;
+        parse_checkpoint(line.trim_end())
+    }
+
+    /// The full id of a checkpoint belonging to this session
+    async fn resolve(&self, id: &str) -> Result<String> {
+        let commit = self
+            .git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", id),
+            ])
+            .await
+            .map_err(|_| anyhow!("Unknown checkpoint {}", id))?;
+        let commit = commit.trim().to_string();
+        let is_checkpoint = self
+            .command()
+            .args(["merge-base", "--is-ancestor", &commit, &self.reference])
+            .status()
+            .await?
+            .success();
+        if !is_checkpoint {
+            bail!("{} is not a checkpoint of this session", id);
+        }
+        Ok(commit)
+    }
+
+    async fn tip(&self) -> Result<Option<String>> {
+        let output = self
+            .command()
+            .args(["rev-parse", "--verify", "--quiet", &self.reference])
+            .output()
+            .await?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    async fn tree_of(&self, commit: &str) -> Result<String> {
+        let tree = self
+            .git(&["rev-parse", &format!("{}^{{tree}}", commit)])
+            .await?;
+        Ok(tree.trim().to_string())
+    }
+
+    /// Write the work tree, including untracked but not ignored files, as a tree object.
+    /// The repository's index seeds a private copy, so unchanged files are not rehashed.
+    async fn snapshot_tree(&self) -> Result<String> {
+        let index = tempfile::tempdir()?;
+        let index_file = index.path().join("index");
+        let real_index = self.git(&["rev-parse", "--git-path", "index"]).await?;
+        let real_index = self.repo_root.join(real_index.trim());
+        if real_index.exists() {
+            std::fs::copy(&real_index, &index_file).context("Failed to copy the git index")?;
+        }
+        self.git_with_index(&index_file, &["add", "-A", "--", "."])
+            .await?;
+        let tree = self.git_with_index(&index_file, &["write-tree"]).await?;
+        Ok(tree.trim().to_string())
+    }
+
+    async fn changed_files(&self, from: &str, to: &str, filter: &str) -> Result<Vec<String>> {
+        let output = self
+            .git(&[
+                "diff",
+                "--no-renames",
+                "--name-only",
+                "-z",
+                &format!("--diff-filter={}", filter),
+                from,
+                to,
+            ])
+            .await?;
+        Ok(output
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn commit(&self, tree: &str, parent: Option<&str>, message: &str) -> Result<String> {
+        let mut args = vec!["commit-tree", "--no-gpg-sign", tree, "-m", message];
+        if let Some(parent) = parent {
+            args.extend(["-p", parent]);
+        }
+        let id = self.git(&args).await?;
+        Ok(id.trim().to_string())
+    }
+
+    /// Point the ref at `id`, failing if another writer moved it away from `expected`
+    async fn move_ref(&self, id: &str, expected: Option<&str>) -> Result<()> {
+        let null = "0".repeat(40);
+        let expected = expected.unwrap_or(&null);
+        self.git(&["update-ref", &self.reference, id, expected])
+            .await
+            .map(|_| ())
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("git");
+        command
+            .arg("-C")
+            .arg(&self.repo_root)
+            .env("GIT_AUTHOR_NAME", "goose")
+            .env("GIT_AUTHOR_EMAIL", "goose@localhost")
+            .env("GIT_COMMITTER_NAME", "goose")
+            .env("GIT_COMMITTER_EMAIL", "goose@localhost")
+            .set_no_window();
+        command
+    }
+
+    async fn git(&self, args: &[&str]) -> Result<String> {
+        Self::run(self.command(), args).await
+    }
+
+    async fn git_with_index(&self, index_file: &Path, args: &[&str]) -> Result<String> {
+        let mut command = self.command();
+        command.env("GIT_INDEX_FILE", index_file);
+        Self::run(command, args).await
+    }
+
+    async fn run(mut command: Command, args: &[&str]) -> Result<String> {
+        let output = command
+            .args(args)
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args.first().unwrap_or(&""),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+fn parse_checkpoint(line: &str) -> Result<Checkpoint> {
+    let fields: Vec<&str> = line.splitn(4, FIELD_SEPARATOR).collect();
+    let [id, parent, timestamp, message] = fields[..] else {
+        bail!("Unexpected git log output: {}", line);
+    };
+    let created_at = Utc
+        .timestamp_opt(timestamp.parse()?, 0)
+        .single()
+        .ok_or_else(|| anyhow!("Invalid checkpoint timestamp {}", timestamp))?;
+    Ok(Checkpoint {
+        id: id.to_string(),
+        parent: parent.split_whitespace().next().map(str::to_string),
+        message: message.to_string(),
+        created_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    async fn init_repo() -> (tempfile::TempDir, CheckpointStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let status = std::process::Command::new("git")
+            .arg("init")
+            .arg("-q")
+            .arg(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join("main.rs"), "fn main() {}\n").unwrap();
+        let store = CheckpointStore::open(dir.path(), "s1").await.unwrap();
+        (dir, store)
+    }
+
+    #[tokio::test]
+    async fn checkpoints_only_when_files_change() {
+        let (dir, store) = init_repo().await;
+        let first = store.create("baseline").await.unwrap().unwrap();
+        assert!(store.create("no changes").await.unwrap().is_none());
+
+        fs::write(dir.path().join("main.rs"), "fn main() { run() }\n").unwrap();
+        fs::create_dir(dir.path().join("target")).unwrap();
+        fs::write(dir.path().join("target/out"), "ignored").unwrap();
+        let second = store.create("edit main").await.unwrap().unwrap();
+        assert_eq!(second.parent.as_deref(), Some(first.id.as_str()));
+
+        let diff = store.diff(&first.id, Some(&second.id)).await.unwrap();
+        assert!(diff.contains("+fn main() { run() }"));
+        assert!(!diff.contains("target/out"));
+
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed, vec![second, first]);
+
+        let head = std::process::Command::new("git")
+            .arg("-C")
+            .arg(dir.path())
+            .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+            .output()
+            .unwrap();
+        assert!(
+            !head.status.success(),
+            "the user's branch must stay untouched"
+        );
+    }
+
+    #[tokio::test]
+    async fn reverts_and_squashes_checkpoints() {
+        let (dir, store) = init_repo().await;
+        let baseline = store.create("baseline").await.unwrap().unwrap();
+        fs::write(dir.path().join("main.rs"), "broken").unwrap();
+        fs::write(dir.path().join("new.rs"), "added").unwrap();
+        store.create("turn 1").await.unwrap().unwrap();
+        fs::write(dir.path().join("lib.rs"), "uncheckpointed").unwrap();
+
+        store.revert(&baseline.id).await.unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+            "fn main() {}\n"
+        );
+        assert!(!dir.path().join("new.rs").exists());
+        assert!(!dir.path().join("lib.rs").exists());
+        assert!(store.diff(&baseline.id, None).await.unwrap().is_empty());
+
+        let squashed = store.squash(&baseline.id, None).await.unwrap();
+        assert_eq!(squashed.message, "Squashed 3 checkpoints");
+        assert_eq!(store.list().await.unwrap().len(), 2);
+        assert!(store.squash(&squashed.id, None).await.is_err());
+        assert!(store.diff("not-a-checkpoint", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn prunes_old_checkpoints_and_deletes_the_ref() {
+        let (dir, store) = init_repo().await;
+        for turn in 0..4 {
+            fs::write(dir.path().join("main.rs"), format!("// turn {}\n", turn)).unwrap();
+            store
+                .create(&format!("turn {}", turn))
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let tip = store.prune(2).await.unwrap().unwrap();
+        let listed = store.list().await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, tip);
+        assert_eq!(listed[0].message, "turn 3");
+        assert_eq!(listed[1].message, "turn 2");
+        assert_eq!(listed[1].parent, None);
+        assert!(store.prune(2).await.unwrap().is_none());
+
+        store.delete().await.unwrap();
+        assert!(!store.exists().await.unwrap());
+        assert!(store.list().await.unwrap().is_empty());
+    }
+}
@@ -1,4 +1,15 @@
+use crate::config::interpolation::{interpolate, Reference};
+use crate::config::org_config;
 use crate::config::paths::Paths;
+use crate::config::profiles::{
+    project_profile_name, ConfigProfile, PROFILES_CONFIG_KEY, PROFILE_ENV_VAR,
+};
+use crate::config::project_config::{
+    find_project_config, ActiveProjectConfig, ProjectConfig, TRUSTED_PROJECT_CONFIGS_KEY,
+};
+use crate::config::secret_backends::{
+    MisconfiguredBackend, SecretBackend, SecretBackendConfig, SECRET_BACKEND_CONFIG_KEY,
+};
 use crate::config::GooseMode;
 use fs2::FileExt;
 use keyring::Entry;
@@ -34,6 +45,10 @@ pub enum ConfigError {
     LockError(String),
     #[error("Secret stored using file-based fallback")]
     FallbackToFileStorage,
+    #[error("Failed to access secret backend: {0}")]
+    SecretBackendError(String),
+    #[error("{key} refers to {reference}, which is not set")]
+    UnresolvedReference { key: String, reference: String },
 }
 
 impl From<serde_json::Error> for ConfigError {
@@ -103,14 +118,20 @@ impl From<keyring::Error> for ConfigError {
 pub struct Config {
     config_path: PathBuf,
     defaults_path: Option<PathBuf>,
+    org_layer_path: Option<PathBuf>,
     secrets: SecretStorage,
     guard: Mutex<()>,
     secrets_cache: Arc<Mutex<Option<HashMap<String, Value>>>>,
+    active_profile: Mutex<Option<String>>,
+    project: Mutex<Option<ActiveProjectConfig>>,
+    /// Working directory of the current session, set by [`Self::set_project_dir`]
+    project_dir: Mutex<Option<PathBuf>>,
 }
 
 enum SecretStorage {
     Keyring { service: String },
     File { path: PathBuf },
+    Backend(Box<dyn SecretBackend>),
 }
 
 // Global instance
@@ -140,13 +161,30 @@ impl Default for Config {
                 service: KEYRING_SERVICE.to_string(),
             },
         };
-        Config {
+        let mut config = Config {
             config_path,
             defaults_path,
+            org_layer_path: Some(org_config::cache_path()),
             secrets,
             guard: Mutex::new(()),
             secrets_cache: Arc::new(Mutex::new(None)),
+            active_profile: Mutex::new(None),
+            project: Mutex::new(None),
+            project_dir: Mutex::new(None),
+        };
+        // A configured backend replaces local storage entirely. When it is misconfigured,
+        // secrets fail to load rather than silently landing in the keyring.
+        match config.get_param::<SecretBackendConfig>(SECRET_BACKEND_CONFIG_KEY) {
+            Ok(backend) => config.secrets = SecretStorage::Backend(backend.build()),
+            Err(ConfigError::NotFound(_)) => {}
+            Err(e) => {
+                config.secrets = SecretStorage::Backend(Box::new(MisconfiguredBackend(format!(
+                    "{} is invalid: {}",
+                    SECRET_BACKEND_CONFIG_KEY, e
+                ))))
+            }
         }
+        config
     }
 }
 
@@ -246,11 +284,15 @@ impl Config {
         Ok(Config {
             config_path: config_path.as_ref().to_path_buf(),
             defaults_path: None,
+            org_layer_path: None,
             secrets: SecretStorage::Keyring {
                 service: service.to_string(),
             },
             guard: Mutex::new(()),
             secrets_cache: Arc::new(Mutex::new(None)),
+            active_profile: Mutex::new(None),
+            project: Mutex::new(None),
+            project_dir: Mutex::new(None),
         })
     }
 
@@ -265,11 +307,15 @@ impl Config {
         Ok(Config {
             config_path: config_path.as_ref().to_path_buf(),
             defaults_path: None,
+            org_layer_path: None,
             secrets: SecretStorage::File {
                 path: secrets_path.as_ref().to_path_buf(),
             },
             guard: Mutex::new(()),
             secrets_cache: Arc::new(Mutex::new(None)),
+            active_profile: Mutex::new(None),
+            project: Mutex::new(None),
+            project_dir: Mutex::new(None),
         })
     }
 
@@ -281,11 +327,15 @@ impl Config {
         Ok(Config {
             config_path: config_path.as_ref().to_path_buf(),
             defaults_path: Some(defaults_path.as_ref().to_path_buf()),
+            org_layer_path: None,
             secrets: SecretStorage::File {
                 path: secrets_path.as_ref().to_path_buf(),
             },
             guard: Mutex::new(()),
             secrets_cache: Arc::new(Mutex::new(None)),
+            active_profile: Mutex::new(None),
+            project: Mutex::new(None),
+            project_dir: Mutex::new(None),
         })
     }
 
@@ -331,10 +381,119 @@ impl Config {
 
     fn load(&self) -> Result<Mapping, ConfigError> {
         let mut values = self.load_raw()?;
+        if let Some(org) = self
+            .org_layer_path
+            .as_deref()
+            .and_then(org_config::load_cached)
+        {
+            org_config::merge_below(&mut values, org);
+        }
         self.merge_missing_defaults(&mut values);
+        if let Some(project) = self.project.lock().unwrap().as_ref() {
+            if project.trusted {
+                project.config.apply(&mut values);
+            }
+        }
+        self.apply_active_profile(&mut values);
         Ok(values)
     }
 
+    /// Resolve the `.goose/config.yaml` for a session working in `dir` and apply it from now
+    /// on if the user trusts it. The file is read and its trust checked once, here, rather
+    /// than on every read of the config. Returns the file found, trusted or not.
+    pub fn set_project_dir(&self, dir: &Path) -> Option<Result<ProjectConfig, ConfigError>> {
+        let project = find_project_config(dir).map(|path| ProjectConfig::load(&path));
+        let active = match &project {
+            Some(Ok(config)) => Some(ActiveProjectConfig {
+                trusted: self.is_project_config_trusted(config),
+                config: config.clone(),
+            }),
+            Some(Err(e)) => {
+                tracing::warn!("Ignoring unreadable project config: {}", e);
+                None
+            }
+            None => None,
+        };
+        *self.project.lock().unwrap() = active;
+        *self.project_dir.lock().unwrap() = Some(dir.to_path_buf());
+        project
+    }
+
+    /// The project config resolved by [`Self::set_project_dir`]
+    pub fn project_config(&self) -> Option<ActiveProjectConfig> {
+        self.project.lock().unwrap().clone()
+    }
+
+    /// Whether the user's own config file trusts `project` with its current contents. Only
+    /// the user's file can trust a project.
+    pub fn is_project_config_trusted(&self, project: &ProjectConfig) -> bool {
+        self.load_raw()
+            .map(|values| project.is_trusted(&values))
+            .unwrap_or(false)
+    }
+
+    /// Apply `project` from now on, until its contents change
+    pub fn trust_project_config(&self, project: &ProjectConfig) -> Result<(), ConfigError> {
+        let mut trusted = self
+            .get_saved_param::<Mapping>(TRUSTED_PROJECT_CONFIGS_KEY)
+            .unwrap_or_default();
+        trusted.insert(
+            serde_yaml::Value::from(project.path.to_string_lossy().as_ref()),
+            serde_yaml::Value::from(project.digest.as_str()),
+        );
+        self.set_param(TRUSTED_PROJECT_CONFIGS_KEY, trusted)?;
+        if let Some(active) = self.project.lock().unwrap().as_mut() {
+            if active.config == *project {
+                active.trusted = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Select a profile from GOOSE_PROFILES for this process. This takes precedence over the
+    /// GOOSE_PROFILE environment variable and `.goose/profile` files.
+    pub fn set_active_profile(&self, name: Option<String>) {
+        *self.active_profile.lock().unwrap() = name;
+    }
+
+    /// The active profile: the one selected with `set_active_profile`, then the one named by
+    /// GOOSE_PROFILE, then the one in a `.goose/profile` file in the session's working
+    /// directory or one of its parents. Without a session the process directory is used.
+    pub fn active_profile_name(&self) -> Option<String> {
+        if let Some(name) = self.active_profile.lock().unwrap().clone() {
+            return Some(name);
+        }
+        if let Ok(name) = env::var(PROFILE_ENV_VAR) {
+            return Some(name);
+        }
+        self.project_dir
+            .lock()
+            .unwrap()
+            .clone()
+            .or_else(|| env::current_dir().ok())
+            .and_then(|dir| project_profile_name(&dir))
+    }
+
+    fn apply_active_profile(&self, values: &mut Mapping) {
+        let Some(name) = self.active_profile_name() else {
+            return;
+        };
+        let profile = values
+            .get(PROFILES_CONFIG_KEY)
+            .and_then(|profiles| profiles.get(name.as_str()))
+            .cloned()
+            .map(serde_yaml::from_value::<ConfigProfile>);
+        match profile {
+            Some(Ok(profile)) => profile.apply(values),
+            Some(Err(e)) => tracing::warn!("Ignoring malformed profile '{}': {}", name, e),
+            None => tracing::debug!(
+                "Profile '{}' is not defined in {}",
+                name,
+                PROFILES_CONFIG_KEY
+            ),
+        }
+    }
+
     pub fn all_values(&self) -> Result<HashMap<String, Value>, ConfigError> {
         let config_values = self.load()?;
         Ok(HashMap::from_iter(config_values.into_iter().filter_map(
@@ -605,6 +764,7 @@ impl Config {
                     }
                 }
                 SecretStorage::File { path } => self.read_secrets_from_file(path)?,
+                SecretStorage::Backend(backend) => backend.load()?,
             };
 
             *cache = Some(loaded.clone());
@@ -692,6 +852,37 @@ impl Config {
             return Ok(serde_json::from_value(value)?);
         }
 
+        let mut value = self
+            .load()?
+            .get(key)
+            .cloned()
+            .ok_or_else(|| ConfigError::NotFound(key.to_string()))?;
+        self.resolve_references(key, &mut value)?;
+        Ok(serde_yaml::from_value(value)?)
+    }
+
+    /// A value set by the organization's config layer, for settings the org requires whatever
+    /// the user or the session picks
+    pub fn get_org_param<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Option<T> {
+        let org = self
+            .org_layer_path
+            .as_deref()
+            .and_then(org_config::load_cached)?;
+        serde_yaml::from_value(org.get(key)?.clone()).ok()
+    }
+
+    /// Like `get_param`, but leaves `${...}` references in place, for values that are
+    /// resolved piece by piece with `resolve_references`
+    pub fn get_unresolved_param<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, ConfigError> {
+        let env_key = key.to_uppercase();
+        if let Ok(val) = env::var(&env_key) {
+            let value = Self::parse_env_value(&val)?;
+            return Ok(serde_json::from_value(value)?);
+        }
+
         let values = self.load()?;
         values
             .get(key)
@@ -699,6 +890,39 @@ impl Config {
             .and_then(|v| Ok(serde_yaml::from_value(v.clone())?))
     }
 
+    /// Replace `${ENV_VAR}` and `${keyring:name}` references in `value` with the environment
+    /// variable or secret they name. `key` identifies the value in errors.
+    pub fn resolve_references(
+        &self,
+        key: &str,
+        value: &mut serde_yaml::Value,
+    ) -> Result<(), ConfigError> {
+        interpolate(key, value, &mut |reference| match reference {
+            Reference::Env(name) => Ok(env::var(name).ok()),
+            Reference::Secret(name) => match self.get_secret::<Value>(name) {
+                Ok(Value::String(secret)) => Ok(Some(secret)),
+                Ok(secret) => Ok(Some(secret.to_string())),
+                Err(ConfigError::NotFound(_)) => Ok(None),
+                Err(e) => Err(e),
+            },
+        })
+    }
+
+    /// Get a value as saved in the config file or bundled defaults, ignoring environment
+    /// variables and the active profile and leaving `${...}` references in place. Use this
+    /// for values that are read, modified and saved back with `set_param`.
+    pub fn get_saved_param<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, ConfigError> {
+        let mut values = self.load_raw()?;
+        self.merge_missing_defaults(&mut values);
+        values
+            .get(key)
+            .ok_or_else(|| ConfigError::NotFound(key.to_string()))
+            .and_then(|v| Ok(serde_yaml::from_value(v.clone())?))
+    }
+
     fn load_defaults(&self) -> Option<Mapping> {
         let path = self.defaults_path.as_ref()?;
         let content = std::fs::read_to_string(path).ok()?;
@@ -792,6 +1016,18 @@ impl Config {
             .and_then(|v| Ok(serde_json::from_value(v.clone())?))
     }
 
+    /// Get a secret value, reading the secret storage rather than the cache.
+    ///
+    /// Use this for values other goose processes may change while this one runs,
+    /// such as OAuth tokens that are refreshed in place.
+    pub fn get_stored_secret<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, ConfigError> {
+        self.invalidate_secrets_cache();
+        self.get_secret(key)
+    }
+
     /// Get secrets. If primary is in env, use env for all keys. Otherwise, use secret storage.
     pub fn get_secrets(
         &self,
@@ -835,8 +1071,10 @@ impl Config {
     where
         V: Serialize,
     {
-        // Lock before reading to prevent race condition.
+        // Lock before reading to prevent race condition, and start from storage
+        // so a write from another process is not clobbered by a stale cache.
         let _guard = self.guard.lock().unwrap();
+        self.invalidate_secrets_cache();
 
         let mut values = self.all_secrets()?;
         values.insert(key.to_string(), serde_json::to_value(value)?);
@@ -858,6 +1096,7 @@ impl Config {
                 let yaml_value = serde_yaml::to_string(&values)?;
                 std::fs::write(path, yaml_value)?;
             }
+            SecretStorage::Backend(backend) => backend.store(&values)?,
         };
 
         self.invalidate_secrets_cache();
@@ -876,8 +1115,10 @@ impl Config {
     /// - There is an error accessing the keyring
     /// - There is an error serializing the remaining values
     pub fn delete_secret(&self, key: &str) -> Result<(), ConfigError> {
-        // Lock before reading to prevent race condition.
+        // Lock before reading to prevent race condition, and start from storage
+        // so a write from another process is not clobbered by a stale cache.
         let _guard = self.guard.lock().unwrap();
+        self.invalidate_secrets_cache();
 
         let mut values = self.all_secrets()?;
         values.remove(key);
@@ -899,6 +1140,7 @@ impl Config {
                 let yaml_value = serde_yaml::to_string(&values)?;
                 std::fs::write(path, yaml_value)?;
             }
+            SecretStorage::Backend(backend) => backend.store(&values)?,
         };
 
         self.invalidate_secrets_cache();
@@ -1004,7 +1246,10 @@ impl Config {
 }
 
 config_value!(CLAUDE_CODE_COMMAND, String, "claude");
+config_value!(CLAUDE_CODE_HISTORY, String, "last-message");
 config_value!(GEMINI_CLI_COMMAND, String, "gemini");
+config_value!(QWEN_CODE_COMMAND, String, "qwen");
+config_value!(IFLOW_COMMAND, String, "iflow");
 config_value!(CURSOR_AGENT_COMMAND, String, "cursor-agent");
 config_value!(CODEX_COMMAND, String, "codex");
 config_value!(CODEX_REASONING_EFFORT, String, "high");
@@ -1086,6 +1331,108 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_active_profile_overrides_saved_values() -> Result<(), ConfigError> {
+        let config = new_test_config();
+        config.set_param("GOOSE_MODEL", "claude-sonnet-4-5")?;
+        config.set_param(
+            PROFILES_CONFIG_KEY,
+            serde_json::json!({"fast": {"model": "gpt-4o-mini"}}),
+        )?;
+
+        config.set_active_profile(Some("fast".to_string()));
+        assert_eq!(config.get_param::<String>("GOOSE_MODEL")?, "gpt-4o-mini");
+        assert_eq!(
+            config.get_saved_param::<String>("GOOSE_MODEL")?,
+            "claude-sonnet-4-5"
+        );
+
+        config.set_active_profile(Some("missing".to_string()));
+        assert_eq!(
+            config.get_param::<String>("GOOSE_MODEL")?,
+            "claude-sonnet-4-5"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_config_applies_once_trusted() -> Result<(), ConfigError> {
+        let config = new_test_config();
+        config.set_param("GOOSE_MODEL", "claude-sonnet-4-5")?;
+        let project_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(project_dir.path().join(".goose")).unwrap();
+        std::fs::write(
+            project_dir.path().join(".goose").join("config.yaml"),
+            "GOOSE_MODEL: gpt-4o\n",
+        )
+        .unwrap();
+
+        let project = config.set_project_dir(project_dir.path()).unwrap()?;
+        assert!(!config.project_config().unwrap().trusted);
+        assert_eq!(
+            config.get_param::<String>("GOOSE_MODEL")?,
+            "claude-sonnet-4-5"
+        );
+
+        config.trust_project_config(&project)?;
+        assert!(config.project_config().unwrap().trusted);
+        assert_eq!(config.get_param::<String>("GOOSE_MODEL")?, "gpt-4o");
+        assert_eq!(
+            config.get_saved_param::<String>("GOOSE_MODEL")?,
+            "claude-sonnet-4-5"
+        );
+
+        let elsewhere = tempfile::tempdir().unwrap();
+        assert!(config.set_project_dir(elsewhere.path()).is_none());
+        assert_eq!(
+            config.get_param::<String>("GOOSE_MODEL")?,
+            "claude-sonnet-4-5"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_file_is_found_from_the_session_dir() {
+        if env::var(PROFILE_ENV_VAR).is_ok() {
+            return;
+        }
+        let config = new_test_config();
+        let session_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(session_dir.path().join(".goose")).unwrap();
+        std::fs::write(session_dir.path().join(".goose").join("profile"), "work\n").unwrap();
+
+        config.set_project_dir(&session_dir.path().join("src"));
+        assert_eq!(config.active_profile_name().as_deref(), Some("work"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_references_resolved_on_read_and_kept_on_save() -> Result<(), ConfigError> {
+        let config = new_test_config();
+        std::env::set_var("GOOSE_TEST_INTERPOLATED_HOST", "https://example.com");
+        config.set_param("test_host", "${GOOSE_TEST_INTERPOLATED_HOST}/v1")?;
+        config.set_param("test_missing", "${GOOSE_TEST_INTERPOLATED_MISSING}")?;
+
+        assert_eq!(
+            config.get_param::<String>("test_host")?,
+            "https://example.com/v1"
+        );
+        assert!(matches!(
+            config.get_param::<String>("test_missing"),
+            Err(ConfigError::UnresolvedReference { .. })
+        ));
+
+        config.set_param("other", "value")?;
+        assert_eq!(
+            config.get_saved_param::<String>("test_host")?,
+            "${GOOSE_TEST_INTERPOLATED_HOST}/v1"
+        );
+
+        std::env::remove_var("GOOSE_TEST_INTERPOLATED_HOST");
+        Ok(())
+    }
+
     #[test]
     fn test_complex_type() -> Result<(), ConfigError> {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -1220,6 +1567,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_secrets_written_elsewhere_are_not_clobbered() -> Result<(), ConfigError> {
+        let config_file = NamedTempFile::new().unwrap();
+        let secrets_file = NamedTempFile::new().unwrap();
+        let first = Config::new_with_file_secrets(config_file.path(), secrets_file.path())?;
+        let second = Config::new_with_file_secrets(config_file.path(), secrets_file.path())?;
+
+        first.set_secret("key1", &Value::String("secret1".to_string()))?;
+        let _: String = first.get_secret("key1")?;
+        second.set_secret("key2", &Value::String("secret2".to_string()))?;
+        first.set_secret("key3", &Value::String("secret3".to_string()))?;
+
+        let value2: String = first.get_stored_secret("key2")?;
+        let value3: String = second.get_stored_secret("key3")?;
+        assert_eq!(value2, "secret2");
+        assert_eq!(value3, "secret3");
+        Ok(())
+    }
+
     #[test]
     fn test_concurrent_writes() -> Result<(), ConfigError> {
         use std::sync::{Arc, Barrier, Mutex};
@@ -1839,6 +2205,28 @@ mod tests {
         assert!(matches!(result, Err(ConfigError::NotFound(_))));
     }
 
+    #[test]
+    fn test_org_params_come_from_the_org_layer_only() {
+        let mut config = new_test_config();
+        config
+            .set_param("GOOSE_PERMISSION_PROFILE", "mine")
+            .unwrap();
+        assert_eq!(
+            config.get_org_param::<String>("GOOSE_PERMISSION_PROFILE"),
+            None
+        );
+
+        let org_layer = NamedTempFile::new().unwrap();
+        std::fs::write(org_layer.path(), "GOOSE_PERMISSION_PROFILE: reviewer\n").unwrap();
+        config.org_layer_path = Some(org_layer.path().to_path_buf());
+        assert_eq!(
+            config.get_org_param::<String>("GOOSE_PERMISSION_PROFILE"),
+            Some("reviewer".to_string())
+        );
+        let configured: String = config.get_param("GOOSE_PERMISSION_PROFILE").unwrap();
+        assert_eq!(configured, "mine");
+    }
+
     fn new_test_config() -> Config {
         let config_file = NamedTempFile::new().unwrap();
         let secrets_file = NamedTempFile::new().unwrap();
@@ -0,0 +1,377 @@
+//! Checks of the merged configuration, shared by `goose doctor` and the desktop settings.
+//!
+//! Each check reports what it found as a [`Diagnostic`] with a severity and, where there is
+//! an obvious remedy, a hint for fixing it. Nothing here changes the configuration.
+
+use crate::agents::ExtensionConfig;
+use crate::config::extensions::{get_all_extensions, get_warnings, resolve_for_launch};
+use crate::config::migrations::{CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::config::paths::Paths;
+use crate::config::project_config::{find_project_config, ProjectConfig};
+use crate::config::search_path::SearchPaths;
+use crate::config::secret_backends::SECRET_BACKEND_CONFIG_KEY;
+use crate::config::{Config, ConfigError};
+use crate::hooks::config::HooksConfig;
+use crate::providers::canonical::maybe_get_canonical_model;
+use serde::Serialize;
+use std::path::Path;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The area that was checked: config, provider, model, extension or hooks
+    pub check: String,
+    pub message: String,
+    /// The config key or file the diagnostic is about
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Diagnostic {
+    fn new(severity: Severity, check: &str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            check: check.to_string(),
+            message: message.into(),
+            subject: None,
+            fix: None,
+        }
+    }
+
+    fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    fn fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Run every check against the configuration as seen from `working_dir`, most severe first
+pub async fn diagnose(working_dir: &Path) -> Vec<Diagnostic> {
+    let config = Config::global();
+    let mut diagnostics = check_values(config);
+    diagnostics.extend(check_provider(config).await);
+    diagnostics.extend(check_extensions());
+    diagnostics.extend(check_project_config(config, working_dir));
+    diagnostics.extend(check_hooks(working_dir));
+    diagnostics.sort_by_key(|d| d.severity);
+    diagnostics
+}
+
+fn check_values(config: &Config) -> Vec<Diagnostic> {
+    let values = match config.all_values() {
+        Ok(values) => values,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("The config file could not be read: {}", e),
+            )
+            .subject(config.path())
+            .fix("Fix the YAML by hand, or restore the last backup with `goose configure`")]
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    if let Err(e) = config.all_secrets() {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("Secrets could not be loaded: {}", e),
+            )
+            .subject(SECRET_BACKEND_CONFIG_KEY)
+            .fix("Check the secret backend settings and that its CLI is installed and signed in"),
+        );
+    }
+
+    let version = values
+        .get(CONFIG_VERSION_KEY)
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    if version > CURRENT_CONFIG_VERSION {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                "config",
+                format!(
+                    "The config file was written by a newer goose (config version {}, this one understands {})",
+                    version, CURRENT_CONFIG_VERSION
+                ),
+            )
+            .subject(CONFIG_VERSION_KEY)
+            .fix("Update goose; newer settings may be ignored until then"),
+        );
+    }
+
+    let mut keys: Vec<&String> = values.keys().filter(|k| *k != "extensions").collect();
+    keys.sort();
+    diagnostics.extend(keys.into_iter().filter_map(|key| {
+        match config.get_param::<serde_yaml::Value>(key) {
+            Err(e @ ConfigError::UnresolvedReference { .. }) => Some(
+                Diagnostic::new(Severity::Error, "config", e.to_string())
+                    .subject(key.as_str())
+                    .fix("Set the environment variable or secret, or remove the reference"),
+            ),
+            _ => None,
+        }
+    }));
+    diagnostics
+}
+
+fn is_set(config: &Config, key: &str, secret: bool) -> Result<bool, ConfigError> {
+    let value = if secret {
+        config.get_secret::<serde_json::Value>(key)
+    } else {
+        config.get_param::<serde_json::Value>(key)
+    };
+    match value {
+        Ok(value) => Ok(!value.is_null()),
+        Err(ConfigError::NotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+async fn check_provider(config: &Config) -> Vec<Diagnostic> {
+    let Ok(provider) = config.get_goose_provider() else {
+        return vec![
+            Diagnostic::new(Severity::Error, "provider", "No provider is configured")
+                .subject("GOOSE_PROVIDER")
+                .fix("Run `goose configure` to choose a provider"),
+        ];
+    };
+    let Some(metadata) = crate::providers::providers()
+        .await
+        .into_iter()
+        .map(|(metadata, _)| metadata)
+        .find(|metadata| metadata.name == provider)
+    else {
+        return vec![Diagnostic::new(
+            Severity::Error,
+            "provider",
+            format!("Provider '{}' does not exist", provider),
+        )
+        .subject("GOOSE_PROVIDER")
+        .fix("Run `goose configure` to choose one of the available providers")];
+    };
+
+    let mut diagnostics = Vec::new();
+    for key in &metadata.config_keys {
+        if !key.required || key.default.is_some() {
+            continue;
+        }
+        if key.oauth_flow {
+            let marker = format!("{}_configured", metadata.name);
+            if !matches!(config.get_param::<bool>(&marker), Ok(true)) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        "provider",
+                        format!("{} has not been signed in to", metadata.display_name),
+                    )
+                    .subject(key.name.as_str())
+                    .fix("Run `goose configure` to sign in"),
+                );
+            }
+            continue;
+        }
+        match is_set(config, &key.name, key.secret) {
+            Ok(true) => {}
+            Ok(false) => diagnostics.push(
+                Diagnostic::new(
+                    Severity::Error,
+                    "provider",
+                    format!("{} requires {}", metadata.display_name, key.name),
+                )
+                .subject(key.name.as_str())
+                .fix(format!(
+                    "Run `goose configure` or set the {} environment variable",
+                    key.name
+                )),
+            ),
+            Err(e) => diagnostics.push(
+                Diagnostic::new(Severity::Error, "provider", e.to_string())
+                    .subject(key.name.as_str()),
+            ),
+        }
+    }
+
+    match config.get_goose_model() {
+        Ok(model) => {
+            let known = metadata.known_models.iter().any(|m| m.name == model)
+                || maybe_get_canonical_model(&provider, &model).is_some();
+            if !known {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Warning,
+                        "model",
+                        format!(
+                            "Model '{}' is not known for {}, so its context limit and pricing are guessed",
+                            model, metadata.display_name
+                        ),
+                    )
+                    .subject("GOOSE_MODEL")
+                    .fix("Check the model name, or set GOOSE_CONTEXT_LIMIT for it"),
+                );
+            }
+        }
+        Err(_) => diagnostics.push(
+            Diagnostic::new(
+                Severity::Info,
+                "model",
+                format!(
+                    "No model is configured, so {}'s default {} is used",
+                    metadata.display_name, metadata.default_model
+                ),
+            )
+            .subject("GOOSE_MODEL"),
+        ),
+    }
+    diagnostics
+}
+
+fn check_extensions() -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = get_warnings()
+        .into_iter()
+        .map(|warning| Diagnostic::new(Severity::Warning, "extension", warning))
+        .collect();
+
+    for entry in get_all_extensions().into_iter().filter(|e| e.enabled) {
+        let Ok(config) = resolve_for_launch(&entry.config) else {
+            continue;
+        };
+        if let ExtensionConfig::Stdio { name, cmd, .. } = &config {
+            if let Err(e) = SearchPaths::builder().with_npm().resolve(cmd) {
+                diagnostics.push(
+                    Diagnostic::new(
+                        Severity::Error,
+                        "extension",
+                        format!("Extension '{}' cannot start: {}", name, e),
+                    )
+                    .subject(entry.config.key())
+                    .fix(format!(
+                        "Install {} or add its directory to GOOSE_SEARCH_PATHS",
+                        cmd
+                    )),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+fn check_project_config(config: &Config, working_dir: &Path) -> Vec<Diagnostic> {
+    let Some(path) = find_project_config(working_dir) else {
+        return Vec::new();
+    };
+    let subject = path.display().to_string();
+    let project = match ProjectConfig::load(&path) {
+        Ok(project) => project,
+        Err(e) => {
+            return vec![Diagnostic::new(
+                Severity::Error,
+                "config",
+                format!("The project config could not be read: {}", e),
+            )
+            .subject(subject)
+            .fix("Fix the YAML; the project's settings are not applied until then")]
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    if !project.ignored_keys.is_empty() {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Warning,
+                "config",
+                format!(
+                    "The project config cannot set {}",
+                    project.ignored_keys.join(", ")
+                ),
+            )
+            .subject(subject.clone())
+            .fix("Move these settings to your own config file"),
+        );
+    }
+    if !config.is_project_config_trusted(&project) {
+        diagnostics.push(
+            Diagnostic::new(
+                Severity::Info,
+                "config",
+                "The project config is not applied because it has not been trusted since it last changed",
+            )
+            .subject(subject)
+            .fix("Start a `goose session` in the project and trust the file when asked"),
+        );
+    }
+    diagnostics
+}
+
+fn check_hooks(working_dir: &Path) -> Vec<Diagnostic> {
+    let global_path = Paths::in_config_dir("hooks.json");
+    if !global_path.exists() {
+        return Vec::new();
+    }
+    let global = match HooksConfig::load_from_file(&global_path, false) {
+        Ok(global) => global,
+        Err(e) => return vec![hooks_error(&global_path, &e)],
+    };
+    // Project hook files are only read when the global config allows them
+    if !global.allow_project_hooks {
+        return Vec::new();
+    }
+
+    [".goose", ".claude"]
+        .into_iter()
+        .map(|dir| working_dir.join(dir).join("settings.json"))
+        .filter(|path| path.exists())
+        .filter_map(|path| {
+            HooksConfig::load_from_file(&path, true)
+                .err()
+                .map(|e| hooks_error(&path, &e))
+        })
+        .collect()
+}
+
+fn hooks_error(path: &Path, error: &anyhow::Error) -> Diagnostic {
+    Diagnostic::new(
+        Severity::Error,
+        "hooks",
+        format!("Hooks are not loaded from this file: {:#}", error),
+    )
+    .subject(path.display().to_string())
+    .fix("Fix the JSON in the file; hooks from it are skipped until then")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_errors_point_at_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hooks.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let error = HooksConfig::load_from_file(&path, false).unwrap_err();
+        let diagnostic = hooks_error(&path, &error);
+
+        assert_eq!(diagnostic.severity, Severity::Error);
+        assert_eq!(diagnostic.subject, Some(path.display().to_string()));
+        assert!(diagnostic.message.contains("Failed to parse hooks config"));
+    }
+}
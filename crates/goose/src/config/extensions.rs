@@ -1,7 +1,9 @@
-use super::base::Config;
+use super::base::{Config, ConfigError};
+use super::migrations::MIGRATED_FROM_KEY;
 use crate::agents::extension::PLATFORM_EXTENSIONS;
 use crate::agents::ExtensionConfig;
 use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Mapping;
 use tracing::warn;
@@ -13,7 +15,7 @@ pub const DEFAULT_EXTENSION_DESCRIPTION: &str = "";
 pub const DEFAULT_DISPLAY_NAME: &str = "Developer";
 const EXTENSIONS_CONFIG_KEY: &str = "extensions";
 
-#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema, JsonSchema)]
 pub struct ExtensionEntry {
     pub enabled: bool,
     #[serde(flatten)]
@@ -41,16 +43,72 @@ pub(crate) fn is_extension_available(config: &ExtensionConfig) -> bool {
     }
 }
 
+/// The extensions with their `${...}` references left in place, so secrets never leave the
+/// process through the extension list. `resolve_for_launch` fills them in when one starts.
 fn get_extensions_map_with_config(config: &Config) -> IndexMap<String, ExtensionEntry> {
-    let raw: Mapping = config
-        .get_param(EXTENSIONS_CONFIG_KEY)
-        .unwrap_or_else(|err| {
-            warn!(
-                "Failed to load {}: {err}. Falling back to empty object.",
-                EXTENSIONS_CONFIG_KEY
+    // Check references per extension, so one missing variable only disables the extension
+    // that uses it
+    let mut raw: Result<Mapping, ConfigError> = config.get_unresolved_param(EXTENSIONS_CONFIG_KEY);
+    if let Ok(raw) = &mut raw {
+        raw.retain(|k, v| {
+            let key = format!(
+                "{}.{}",
+                EXTENSIONS_CONFIG_KEY,
+                k.as_str().unwrap_or_default()
             );
-            Default::default()
+            match config.resolve_references(&key, &mut v.clone()) {
+                Ok(()) => true,
+                Err(e) => {
+                    warn!("Skipping extension: {}", e);
+                    false
+                }
+            }
         });
+    }
+    parse_extensions_map(raw)
+}
+
+/// Resolve the `${...}` references in an extension's config right before it starts. Only an
+/// extension as defined in config.yaml is resolved; anything else, such as one from a recipe,
+/// starts as given so it cannot name the user's secrets.
+pub fn resolve_for_launch(extension: &ExtensionConfig) -> Result<ExtensionConfig, ConfigError> {
+    resolve_for_launch_with_config(Config::global(), extension)
+}
+
+pub fn resolve_for_launch_with_config(
+    config: &Config,
+    extension: &ExtensionConfig,
+) -> Result<ExtensionConfig, ConfigError> {
+    let key = extension.key();
+    let raw: Mapping = config
+        .get_unresolved_param(EXTENSIONS_CONFIG_KEY)
+        .unwrap_or_default();
+    let defined_in_config = raw
+        .get(key.as_str())
+        .and_then(|v| serde_yaml::from_value::<ExtensionEntry>(v.clone()).ok())
+        .is_some_and(|entry| entry.config == *extension);
+    if !defined_in_config {
+        return Ok(extension.clone());
+    }
+    let mut value = serde_yaml::to_value(extension)?;
+    config.resolve_references(&format!("{}.{}", EXTENSIONS_CONFIG_KEY, key), &mut value)?;
+    Ok(serde_yaml::from_value(value)?)
+}
+
+/// The extensions as saved in the config file, without the active profile's selection, for
+/// changes that are written back
+fn get_saved_extensions_map() -> IndexMap<String, ExtensionEntry> {
+    parse_extensions_map(Config::global().get_saved_param(EXTENSIONS_CONFIG_KEY))
+}
+
+fn parse_extensions_map(raw: Result<Mapping, ConfigError>) -> IndexMap<String, ExtensionEntry> {
+    let raw = raw.unwrap_or_else(|err| {
+        warn!(
+            "Failed to load {}: {err}. Falling back to empty object.",
+            EXTENSIONS_CONFIG_KEY
+        );
+        Default::default()
+    });
 
     let mut extensions_map = IndexMap::with_capacity(raw.len());
     for (k, v) in raw {
@@ -95,20 +153,20 @@ pub fn get_extension_by_name(name: &str) -> Option<ExtensionConfig> {
 }
 
 pub fn set_extension(entry: ExtensionEntry) {
-    let mut extensions = get_extensions_map();
+    let mut extensions = get_saved_extensions_map();
     let key = entry.config.key();
     extensions.insert(key, entry);
     save_extensions_map(extensions);
 }
 
 pub fn remove_extension(key: &str) {
-    let mut extensions = get_extensions_map();
+    let mut extensions = get_saved_extensions_map();
     extensions.shift_remove(key);
     save_extensions_map(extensions);
 }
 
 pub fn set_extension_enabled(key: &str, enabled: bool) {
-    let mut extensions = get_extensions_map();
+    let mut extensions = get_saved_extensions_map();
     if let Some(entry) = extensions.get_mut(key) {
         entry.enabled = enabled;
         save_extensions_map(extensions);
@@ -116,8 +174,13 @@ pub fn set_extension_enabled(key: &str, enabled: bool) {
 }
 
 pub fn get_all_extensions() -> Vec<ExtensionEntry> {
-    let extensions = get_extensions_map();
-    extensions.into_values().collect()
+    get_all_extensions_with_config(Config::global())
+}
+
+pub fn get_all_extensions_with_config(config: &Config) -> Vec<ExtensionEntry> {
+    get_extensions_map_with_config(config)
+        .into_values()
+        .collect()
 }
 
 pub fn get_all_extension_names() -> Vec<String> {
@@ -147,12 +210,26 @@ pub fn get_enabled_extensions_with_config(config: &Config) -> Vec<ExtensionConfi
 }
 
 pub fn get_warnings() -> Vec<String> {
-    let raw: Mapping = Config::global()
-        .get_param(EXTENSIONS_CONFIG_KEY)
+    let config = Config::global();
+    let raw: Mapping = config
+        .get_unresolved_param(EXTENSIONS_CONFIG_KEY)
         .unwrap_or_default();
 
     let mut warnings = Vec::new();
     for (k, v) in raw {
+        if let Some(key) = k.as_str() {
+            let mut resolved = v.clone();
+            let path = format!("{}.{}", EXTENSIONS_CONFIG_KEY, key);
+            if let Err(e) = config.resolve_references(&path, &mut resolved) {
+                warnings.push(format!("'{}' is disabled: {}", key, e));
+            }
+            if let Some(old_type) = v.get(MIGRATED_FROM_KEY).and_then(|t| t.as_str()) {
+                warnings.push(format!(
+                    "'{}' was converted from {} to streamable_http and disabled; enable it if the server supports streamable HTTP",
+                    key, old_type
+                ));
+            }
+        }
         if let (serde_yaml::Value::String(key), Ok(entry)) =
             (k, serde_yaml::from_value::<ExtensionEntry>(v))
         {
@@ -211,4 +288,38 @@ mod tests {
         assert!(!is_extension_available(&unknown_platform));
         assert!(is_extension_available(&builtin));
     }
+
+    #[test]
+    fn references_resolve_only_for_configured_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("secrets.yaml"),
+        )
+        .unwrap();
+        config.set_secret("TOKEN", &"s3cret").unwrap();
+        let configured = ExtensionConfig::stdio("tool", "${keyring:TOKEN}", "", 300u64);
+        let mut extensions = Mapping::new();
+        extensions.insert(
+            configured.key().into(),
+            serde_yaml::to_value(ExtensionEntry {
+                enabled: true,
+                config: configured.clone(),
+            })
+            .unwrap(),
+        );
+        config.set_param(EXTENSIONS_CONFIG_KEY, extensions).unwrap();
+
+        let listed = get_all_extensions_with_config(&config);
+        assert_eq!(listed[0].config, configured);
+
+        let launched = resolve_for_launch_with_config(&config, &configured).unwrap();
+        assert!(matches!(launched, ExtensionConfig::Stdio { ref cmd, .. } if cmd == "s3cret"));
+
+        let from_recipe = ExtensionConfig::stdio("tool", "${keyring:TOKEN} x", "", 300u64);
+        assert_eq!(
+            resolve_for_launch_with_config(&config, &from_recipe).unwrap(),
+            from_recipe
+        );
+    }
 }
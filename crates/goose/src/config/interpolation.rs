@@ -0,0 +1,169 @@
+//! `${...}` references in config values.
+//!
+//! String values in config.yaml, including those inside extension definitions, can refer to
+//! environment variables and stored secrets instead of holding the value itself:
+//!
+//! ```yaml
+//! OPENAI_HOST: ${CORP_OPENAI_HOST}
+//! extensions:
+//!   github:
+//!     envs:
+//!       GITHUB_TOKEN: ${keyring:GITHUB_TOKEN}
+//! ```
+//!
+//! References are resolved when a value is read, so the file keeps the references when it is
+//! saved again. Extension definitions are the exception: they are listed with their references
+//! in place and resolved only when the extension starts. `$${` stands for a literal `${`.
+
+use super::base::ConfigError;
+use serde_yaml::Value;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reference {
+    /// `${NAME}`
+    Env(String),
+    /// `${keyring:name}`, a secret stored with `set_secret`
+    Secret(String),
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reference::Env(name) => write!(f, "environment variable {}", name),
+            Reference::Secret(name) => write!(f, "secret '{}'", name),
+        }
+    }
+}
+
+fn parse_reference(inner: &str) -> Option<Reference> {
+    if let Some(name) = inner.strip_prefix("keyring:") {
+        return (!name.is_empty()).then(|| Reference::Secret(name.to_string()));
+    }
+    let mut chars = inner.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| Reference::Env(inner.to_string()))
+}
+
+/// Replace the references in `text`. Anything that looks like `${...}` but is not a valid
+/// reference is left alone.
+pub fn interpolate_str<F>(key: &str, text: &str, resolve: &mut F) -> Result<String, ConfigError>
+where
+    F: FnMut(&Reference) -> Result<Option<String>, ConfigError>,
+{
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos..];
+        if let Some(escaped) = after.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let reference = after
+            .strip_prefix("${")
+            .and_then(|body| body.find('}').map(|end| (&body[..end], &body[end + 1..])))
+            .and_then(|(inner, tail)| parse_reference(inner).map(|r| (r, tail)));
+        match reference {
+            Some((reference, tail)) => {
+                let value =
+                    resolve(&reference)?.ok_or_else(|| ConfigError::UnresolvedReference {
+                        key: key.to_string(),
+                        reference: reference.to_string(),
+                    })?;
+                out.push_str(&value);
+                rest = tail;
+            }
+            None => {
+                out.push('$');
+                rest = &after[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Replace the references in every string inside `value`. `key` names the value in errors;
+/// nested values are named by their path, e.g. `extensions.github.envs.GITHUB_TOKEN`.
+pub fn interpolate<F>(key: &str, value: &mut Value, resolve: &mut F) -> Result<(), ConfigError>
+where
+    F: FnMut(&Reference) -> Result<Option<String>, ConfigError>,
+{
+    match value {
+        Value::String(text) if text.contains('$') => {
+            *text = interpolate_str(key, text, resolve)?;
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                interpolate(&format!("{}[{}]", key, i), item, resolve)?;
+            }
+        }
+        Value::Mapping(entries) => {
+            for (k, v) in entries.iter_mut() {
+                let path = match k.as_str() {
+                    Some(k) => format!("{}.{}", key, k),
+                    None => key.to_string(),
+                };
+                interpolate(&path, v, resolve)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate(key, &mut tagged.value, resolve)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(reference: &Reference) -> Result<Option<String>, ConfigError> {
+        Ok(match reference {
+            Reference::Env(name) if name == "HOST" => Some("example.com".to_string()),
+            Reference::Secret(name) if name == "token" => Some("s3cret".to_string()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn resolves_env_and_secret_references() {
+        let mut value: Value = serde_yaml::from_str(
+            r#"
+uri: https://${HOST}/mcp
+headers:
+  Authorization: Bearer ${keyring:token}
+args: ["--price", "$5", "$${HOST}", "${not a reference}"]
+"#,
+        )
+        .unwrap();
+
+        interpolate("extensions.remote", &mut value, &mut resolver).unwrap();
+
+        assert_eq!(value["uri"], Value::from("https://example.com/mcp"));
+        assert_eq!(
+            value["headers"]["Authorization"],
+            Value::from("Bearer s3cret")
+        );
+        assert_eq!(
+            value["args"],
+            serde_yaml::from_str::<Value>(r#"["--price", "$5", "${HOST}", "${not a reference}"]"#)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn missing_reference_names_the_value() {
+        let mut value: Value = serde_yaml::from_str("envs:\n  TOKEN: ${keyring:missing}").unwrap();
+
+        let err = interpolate("extensions.github", &mut value, &mut resolver).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "extensions.github.envs.TOKEN refers to secret 'missing', which is not set"
+        );
+    }
+}
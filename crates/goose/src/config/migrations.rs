@@ -4,13 +4,111 @@ use crate::config::extensions::ExtensionEntry;
 use serde_yaml::Mapping;
 
 const EXTENSIONS_CONFIG_KEY: &str = "extensions";
+pub(crate) const CONFIG_VERSION_KEY: &str = "GOOSE_CONFIG_VERSION";
+/// Set on an extension entry a migration converted, naming its old type. Saving the
+/// extension again drops it, since it isn't part of the entry.
+pub(crate) const MIGRATED_FROM_KEY: &str = "migrated_from";
+
+/// Migrations that run once, in order: a config at version N has had the first N applied.
+/// Append new migrations; never reorder or remove them.
+const VERSIONED_MIGRATIONS: &[fn(&mut Mapping)] =
+    &[migrate_thinking_enabled, migrate_sse_extensions];
+
+pub(crate) const CURRENT_CONFIG_VERSION: u64 = VERSIONED_MIGRATIONS.len() as u64;
 
 pub fn run_migrations(config: &mut Mapping) -> bool {
     let mut changed = false;
+    changed |= run_versioned_migrations(config);
     changed |= migrate_platform_extensions(config);
     changed
 }
 
+pub(crate) fn config_version(config: &Mapping) -> u64 {
+    config
+        .get(CONFIG_VERSION_KEY)
+        .and_then(serde_yaml::Value::as_u64)
+        .unwrap_or(0)
+}
+
+fn run_versioned_migrations(config: &mut Mapping) -> bool {
+    let version = config_version(config);
+    if version > CURRENT_CONFIG_VERSION {
+        tracing::warn!(
+            "Config was written by a newer goose (config version {}, this build understands {}); some settings may be ignored",
+            version,
+            CURRENT_CONFIG_VERSION
+        );
+        return false;
+    }
+    if version == CURRENT_CONFIG_VERSION {
+        return false;
+    }
+    for (i, migration) in VERSIONED_MIGRATIONS
+        .iter()
+        .enumerate()
+        .skip(version as usize)
+    {
+        tracing::info!("Migrating config to version {}", i + 1);
+        migration(config);
+    }
+    config.insert(
+        serde_yaml::Value::from(CONFIG_VERSION_KEY),
+        serde_yaml::Value::from(CURRENT_CONFIG_VERSION),
+    );
+    true
+}
+
+/// Version 1: CLAUDE_THINKING_ENABLED was only ever read from the environment, so a value in
+/// the config file did nothing. It means CLAUDE_THINKING_TYPE: enabled.
+fn migrate_thinking_enabled(config: &mut Mapping) {
+    let Some(value) = config.shift_remove("CLAUDE_THINKING_ENABLED") else {
+        return;
+    };
+    let enabled = match value {
+        serde_yaml::Value::Bool(b) => b,
+        serde_yaml::Value::String(s) => matches!(s.to_lowercase().as_str(), "true" | "1" | "yes"),
+        _ => false,
+    };
+    if enabled && !config.contains_key("CLAUDE_THINKING_TYPE") {
+        config.insert(
+            serde_yaml::Value::from("CLAUDE_THINKING_TYPE"),
+            serde_yaml::Value::from("enabled"),
+        );
+    }
+}
+
+/// Version 2: SSE extensions no longer load. Those with a URI become streamable HTTP
+/// extensions at the same address, which is what most servers that offered SSE also serve.
+/// Not all do, so they are disabled and marked with [`MIGRATED_FROM_KEY`] until the user
+/// checks and enables them.
+fn migrate_sse_extensions(config: &mut Mapping) {
+    let Some(serde_yaml::Value::Mapping(extensions)) = config.get_mut(EXTENSIONS_CONFIG_KEY) else {
+        return;
+    };
+    for (key, entry) in extensions.iter_mut() {
+        let serde_yaml::Value::Mapping(entry) = entry else {
+            continue;
+        };
+        let is_sse = entry.get("type").and_then(|t| t.as_str()) == Some("sse");
+        if !is_sse || entry.get("uri").and_then(|u| u.as_str()).is_none() {
+            continue;
+        }
+        tracing::warn!(
+            "Converted SSE extension {:?} to streamable_http and disabled it; enable it once the server is confirmed to support it",
+            key.as_str().unwrap_or_default()
+        );
+        entry.insert("type".into(), "streamable_http".into());
+        entry.insert("enabled".into(), false.into());
+        entry.insert(MIGRATED_FROM_KEY.into(), "sse".into());
+        if !entry.contains_key("timeout") {
+            entry.insert(
+                "timeout".into(),
+                serde_yaml::Value::from(super::DEFAULT_EXTENSION_TIMEOUT),
+            );
+        }
+    }
+}
+
 fn migrate_platform_extensions(config: &mut Mapping) -> bool {
     let extensions_key = serde_yaml::Value::String(EXTENSIONS_CONFIG_KEY.to_string());
 
@@ -106,6 +204,46 @@ fn migrate_platform_extensions(config: &mut Mapping) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_versioned_migrations_run_once() {
+        let mut config: Mapping = serde_yaml::from_str(
+            r#"
+CLAUDE_THINKING_ENABLED: true
+extensions:
+  remote:
+    enabled: true
+    type: sse
+    name: remote
+    uri: https://example.com/mcp
+"#,
+        )
+        .unwrap();
+
+        assert!(run_migrations(&mut config));
+        assert_eq!(config_version(&config), CURRENT_CONFIG_VERSION);
+        assert!(!config.contains_key("CLAUDE_THINKING_ENABLED"));
+        assert_eq!(
+            config["CLAUDE_THINKING_TYPE"],
+            serde_yaml::Value::from("enabled")
+        );
+        let remote: ExtensionEntry =
+            serde_yaml::from_value(config["extensions"]["remote"].clone()).unwrap();
+        assert!(matches!(
+            remote.config,
+            ExtensionConfig::StreamableHttp { .. }
+        ));
+        assert!(!remote.enabled);
+        assert_eq!(
+            config["extensions"]["remote"][MIGRATED_FROM_KEY],
+            serde_yaml::Value::from("sse")
+        );
+
+        // Already migrated, and a later key of the old name is left for the user to see
+        config.insert("CLAUDE_THINKING_ENABLED".into(), true.into());
+        assert!(!run_versioned_migrations(&mut config));
+        assert!(config.contains_key("CLAUDE_THINKING_ENABLED"));
+    }
+
     #[test]
     fn test_migrate_platform_extensions_empty_config() {
         let mut config = Mapping::new();
@@ -1,12 +1,19 @@
 pub mod base;
 pub mod declarative_providers;
+pub mod doctor;
 mod experiments;
 pub mod extensions;
 pub mod goose_mode;
+pub mod interpolation;
 mod migrations;
+pub mod org_config;
 pub mod paths;
 pub mod permission;
+pub mod profiles;
+pub mod project_config;
+pub mod schema;
 pub mod search_path;
+pub mod secret_backends;
 pub mod signup_openrouter;
 pub mod signup_tetrate;
 
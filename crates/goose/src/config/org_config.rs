@@ -0,0 +1,268 @@
+//! Organization-wide config layer fetched from a URL.
+//!
+//! Platform teams publish a config.yaml with the providers, extensions and permission
+//! policies they approve, and point goose at it:
+//!
+//! ```yaml
+//! GOOSE_ORG_CONFIG_URL: https://config.example.com/goose/config.yaml
+//! GOOSE_ORG_CONFIG_KEY: RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3
+//! ```
+//!
+//! The layer sits between the user's config and the bundled defaults: values the user sets
+//! win, and for maps such as `extensions` the user's entries are merged over the org's.
+//! It is refreshed in the background with `refresh` and read from a local cache, so loading
+//! config never waits on the network. The layer can add extensions that run local commands,
+//! so it is only fetched over https and must carry a valid minisign signature at
+//! `<url>.minisig`, checked against `GOOSE_ORG_CONFIG_KEY` or, without it, the trusted keys
+//! with `GOOSE_SIGNATURE_POLICY: enforce`. Any other setup refuses the layer.
+
+use crate::config::paths::Paths;
+use crate::config::Config;
+use crate::security::signing::{
+    SignaturePolicy, SignatureVerifier, TrustedKey, SIGNATURE_EXTENSION,
+};
+use anyhow::{bail, Context, Result};
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use url::Url;
+
+pub const ORG_CONFIG_URL_KEY: &str = "GOOSE_ORG_CONFIG_URL";
+pub const ORG_CONFIG_SIGNING_KEY: &str = "GOOSE_ORG_CONFIG_KEY";
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshOutcome {
+    NotConfigured,
+    Unchanged,
+    Updated,
+}
+
+pub fn cache_path() -> PathBuf {
+    Paths::in_config_dir("org_config.yaml")
+}
+
+fn etag_path(cache: &Path) -> PathBuf {
+    cache.with_extension("etag")
+}
+
+pub fn load_cached(path: &Path) -> Option<Mapping> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match serde_yaml::from_str(&content) {
+        Ok(values) => Some(values),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable org config cache {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Fill in what `values` lacks from `org`. Maps present in both are merged one level down,
+/// so the org can add extensions or permission profiles without replacing the user's.
+pub fn merge_below(values: &mut Mapping, org: Mapping) {
+    for (key, org_value) in org {
+        // Where the layer comes from and who signs it is up to the user
+        if matches!(
+            key.as_str(),
+            Some(ORG_CONFIG_URL_KEY) | Some(ORG_CONFIG_SIGNING_KEY)
+        ) {
+            continue;
+        }
+        match (values.get_mut(&key), org_value) {
+            (None, org_value) => {
+                values.insert(key, org_value);
+            }
+            (Some(Value::Mapping(user)), Value::Mapping(org_map)) => {
+                for (k, v) in org_map {
+                    if !user.contains_key(&k) {
+                        user.insert(k, v);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The verifier for the layer, or an error when nothing would actually check its signature
+fn verifier(key: Option<String>, fallback: SignatureVerifier) -> Result<SignatureVerifier> {
+    match key {
+        Some(key) => match TrustedKey::parse(&key) {
+            Ok(key) => Ok(SignatureVerifier::new(SignaturePolicy::Enforce, vec![key])),
+            Err(e) => bail!("Invalid {}: {}", ORG_CONFIG_SIGNING_KEY, e),
+        },
+        None if fallback.policy() == SignaturePolicy::Enforce => Ok(fallback),
+        None => bail!(
+            "Refusing an unsigned org config layer: set {} or GOOSE_SIGNATURE_POLICY: enforce",
+            ORG_CONFIG_SIGNING_KEY
+        ),
+    }
+}
+
+fn require_https(url: &str) -> Result<()> {
+    let parsed = Url::parse(url).with_context(|| format!("Invalid {}", ORG_CONFIG_URL_KEY))?;
+    if parsed.scheme() != "https" {
+        bail!("Org config must be fetched over https, not {}", url);
+    }
+    Ok(())
+}
+
+/// Check the fetched layer's signature and parse it
+fn verify_layer(
+    verifier: &SignatureVerifier,
+    url: &str,
+    content: &[u8],
+    signature: Option<&str>,
+) -> Result<Mapping> {
+    verifier.check_detached(url, content, signature, "org config")?;
+    serde_yaml::from_slice(content)
+        .with_context(|| format!("Org config at {} is not a YAML mapping", url))
+}
+
+/// Fetch the org layer if it changed since the last fetch, and cache it after verifying it
+pub async fn refresh() -> Result<RefreshOutcome> {
+    let config = Config::global();
+    let cache = cache_path();
+    let Ok(url) = config.get_param::<String>(ORG_CONFIG_URL_KEY) else {
+        // Stop applying a layer the user no longer points at
+        let _ = std::fs::remove_file(&cache);
+        return Ok(RefreshOutcome::NotConfigured);
+    };
+    require_https(&url)?;
+    let verifier = verifier(
+        config.get_param::<String>(ORG_CONFIG_SIGNING_KEY).ok(),
+        SignatureVerifier::from_config(),
+    )?;
+    let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+
+    let mut request = client.get(&url);
+    if cache.exists() {
+        if let Ok(etag) = std::fs::read_to_string(etag_path(&cache)) {
+            request = request.header(IF_NONE_MATCH, etag.trim());
+        }
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch org config from {}", url))?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(RefreshOutcome::Unchanged);
+    }
+    let response = response.error_for_status()?;
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let content = response.bytes().await?;
+
+    let signature_response = client
+        .get(format!("{}.{}", url, SIGNATURE_EXTENSION))
+        .send()
+        .await?;
+    let signature = if signature_response.status().is_success() {
+        Some(signature_response.text().await?)
+    } else {
+        None
+    };
+    let values = verify_layer(&verifier, &url, &content, signature.as_deref())?;
+    if let Some(parent) = cache.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = cache.with_extension("yaml.tmp");
+    std::fs::write(&tmp, serde_yaml::to_string(&values)?)?;
+    std::fs::rename(&tmp, &cache)?;
+    match etag {
+        Some(etag) => std::fs::write(etag_path(&cache), etag)?,
+        None => {
+            let _ = std::fs::remove_file(etag_path(&cache));
+        }
+    }
+    Ok(RefreshOutcome::Updated)
+}
+
+/// Refresh the org layer, logging rather than failing when it cannot be fetched
+pub async fn refresh_in_background() {
+    match refresh().await {
+        Ok(RefreshOutcome::Updated) => tracing::info!("Updated the org config layer"),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Using the cached org config layer: {:#}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_values_win_and_maps_merge() {
+        let mut values: Mapping = serde_yaml::from_str(
+            r#"
+GOOSE_PROVIDER: anthropic
+extensions:
+  developer:
+    enabled: false
+"#,
+        )
+        .unwrap();
+        let org: Mapping = serde_yaml::from_str(
+            r#"
+GOOSE_PROVIDER: azure_openai
+GOOSE_MODE: smart_approve
+extensions:
+  developer:
+    enabled: true
+  jira:
+    enabled: true
+"#,
+        )
+        .unwrap();
+
+        merge_below(&mut values, org);
+
+        assert_eq!(values["GOOSE_PROVIDER"], Value::from("anthropic"));
+        assert_eq!(values["GOOSE_MODE"], Value::from("smart_approve"));
+        assert_eq!(
+            values["extensions"]["developer"]["enabled"],
+            Value::from(false)
+        );
+        assert_eq!(values["extensions"]["jira"]["enabled"], Value::from(true));
+    }
+
+    const TEST_KEY: &str = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+
+    #[test]
+    fn unsigned_layer_is_rejected() {
+        let verifier = verifier(
+            Some(TEST_KEY.to_string()),
+            SignatureVerifier::new(SignaturePolicy::Off, Vec::new()),
+        )
+        .unwrap();
+        let layer = b"extensions:\n  evil:\n    type: stdio\n    cmd: sh\n";
+
+        let err = verify_layer(&verifier, "https://example.com/org.yaml", layer, None).unwrap_err();
+
+        assert!(err.to_string().contains("is not signed"), "{}", err);
+    }
+
+    #[test]
+    fn layer_needs_a_key_or_enforced_policy() {
+        for policy in [SignaturePolicy::Off, SignaturePolicy::Warn] {
+            let fallback = SignatureVerifier::new(policy, Vec::new());
+            assert!(verifier(None, fallback).is_err());
+        }
+        let enforced = SignatureVerifier::new(SignaturePolicy::Enforce, Vec::new());
+        assert!(verifier(None, enforced).is_ok());
+        let bad_key = SignatureVerifier::new(SignaturePolicy::Off, Vec::new());
+        assert!(verifier(Some("not a key".to_string()), bad_key).is_err());
+    }
+
+    #[test]
+    fn layer_must_use_https() {
+        assert!(require_https("https://config.example.com/goose.yaml").is_ok());
+        assert!(require_https("http://config.example.com/goose.yaml").is_err());
+        assert!(require_https("file:///etc/goose.yaml").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::config::paths::Paths;
+use chrono::{DateTime, Duration, Utc};
 use rmcp::model::Tool;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -9,6 +10,7 @@ use tracing;
 use utoipa::ToSchema;
 
 const PERMISSION_FILE: &str = "permission.yaml";
+const PERMISSION_GRANTS_FILE: &str = "permission_grants.yaml";
 
 static PERMISSION_MANAGER: LazyLock<Arc<PermissionManager>> =
     LazyLock::new(|| Arc::new(PermissionManager::new(Paths::config_dir())));
@@ -30,11 +32,56 @@ pub struct PermissionConfig {
     pub never_allow: Vec<String>,  // List of tools that are never allowed
 }
 
+/// Where a temporary permission grant applies.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GrantScope {
+    /// Any session, until the grant expires
+    Global,
+    /// A single session
+    Session { session_id: String },
+    /// Sessions whose working directory is inside this project directory
+    Project { path: PathBuf },
+}
+
+/// A time-limited or scoped "allow" decision for a tool.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+pub struct PermissionGrant {
+    pub id: String,
+    pub tool_name: String,
+    pub scope: GrantScope,
+    pub created_at: DateTime<Utc>,
+    /// Grants without an expiry last as long as their scope
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl PermissionGrant {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    fn matches(&self, tool_name: &str, session_id: &str, working_dir: Option<&Path>) -> bool {
+        if self.tool_name != tool_name {
+            return false;
+        }
+        match &self.scope {
+            GrantScope::Global => true,
+            GrantScope::Session {
+                session_id: grant_session,
+            } => grant_session == session_id,
+            GrantScope::Project { path } => working_dir.is_some_and(|dir| dir.starts_with(path)),
+        }
+    }
+}
+
 /// PermissionManager manages permission configurations for various tools.
 #[derive(Debug)]
 pub struct PermissionManager {
     config_path: PathBuf,
     permission_map: RwLock<HashMap<String, PermissionConfig>>,
+    grants_path: PathBuf,
+    grants: RwLock<Vec<PermissionGrant>>,
 }
 
 // Constants representing specific permission categories
@@ -63,12 +110,29 @@ impl PermissionManager {
             fs::create_dir_all(&config_dir).expect("Failed to create config directory");
             HashMap::new()
         };
+        let grants_path = config_dir.join(PERMISSION_GRANTS_FILE);
+        let grants = Self::load_grants(&grants_path);
         PermissionManager {
             config_path: permission_path,
             permission_map: RwLock::new(permission_map),
+            grants_path,
+            grants: RwLock::new(grants),
         }
     }
 
+    fn load_grants(path: &Path) -> Vec<PermissionGrant> {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        // Grants are short-lived convenience state; a corrupted file only costs a re-prompt.
+        let grants: Vec<PermissionGrant> = serde_yaml::from_str(&contents).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable {}: {}", path.display(), e);
+            Vec::new()
+        });
+        let now = Utc::now();
+        grants.into_iter().filter(|g| !g.is_expired(now)).collect()
+    }
+
     pub fn instance() -> Arc<PermissionManager> {
         Arc::clone(&PERMISSION_MANAGER)
     }
@@ -213,6 +277,83 @@ impl PermissionManager {
         fs::write(&self.config_path, yaml_content).expect("Failed to write to permission.yaml");
     }
 
+    /// Records a scoped "allow" for a tool, optionally expiring after `ttl`.
+    pub fn add_grant(
+        &self,
+        tool_name: &str,
+        scope: GrantScope,
+        ttl: Option<Duration>,
+    ) -> PermissionGrant {
+        let now = Utc::now();
+        let grant = PermissionGrant {
+            id: uuid::Uuid::new_v4().to_string(),
+            tool_name: tool_name.to_string(),
+            scope,
+            created_at: now,
+            expires_at: ttl.map(|ttl| now + ttl),
+        };
+
+        let mut grants = self.grants.write().unwrap();
+        grants.retain(|g| {
+            !g.is_expired(now) && !(g.tool_name == grant.tool_name && g.scope == grant.scope)
+        });
+        grants.push(grant.clone());
+        self.save_grants(&grants);
+        grant
+    }
+
+    /// Returns the first unexpired grant that allows the tool in this session.
+    pub fn find_grant(
+        &self,
+        tool_name: &str,
+        session_id: &str,
+        working_dir: Option<&Path>,
+    ) -> Option<PermissionGrant> {
+        let now = Utc::now();
+        self.grants
+            .read()
+            .unwrap()
+            .iter()
+            .find(|g| !g.is_expired(now) && g.matches(tool_name, session_id, working_dir))
+            .cloned()
+    }
+
+    pub fn has_grants(&self) -> bool {
+        !self.grants.read().unwrap().is_empty()
+    }
+
+    /// Lists all unexpired grants, dropping expired ones from disk.
+    pub fn list_grants(&self) -> Vec<PermissionGrant> {
+        let now = Utc::now();
+        let mut grants = self.grants.write().unwrap();
+        let before = grants.len();
+        grants.retain(|g| !g.is_expired(now));
+        if grants.len() != before {
+            self.save_grants(&grants);
+        }
+        grants.clone()
+    }
+
+    /// Revokes a grant by id. Returns false if no such grant exists.
+    pub fn revoke_grant(&self, grant_id: &str) -> bool {
+        let mut grants = self.grants.write().unwrap();
+        let before = grants.len();
+        grants.retain(|g| g.id != grant_id);
+        let removed = grants.len() != before;
+        if removed {
+            self.save_grants(&grants);
+        }
+        removed
+    }
+
+    fn save_grants(&self, grants: &[PermissionGrant]) {
+        let yaml_content =
+            serde_yaml::to_string(grants).expect("Failed to serialize permission grants");
+        if let Err(e) = fs::write(&self.grants_path, yaml_content) {
+            tracing::warn!("Failed to write {}: {}", self.grants_path.display(), e);
+        }
+    }
+
     /// Removes all entries where the principal name starts with the given extension name.
     pub fn remove_extension(&self, extension_name: &str) {
         let mut map = self.permission_map.write().unwrap();
@@ -231,6 +372,14 @@ impl PermissionManager {
         let yaml_content =
             serde_yaml::to_string(&*map).expect("Failed to serialize permission config");
         fs::write(&self.config_path, yaml_content).expect("Failed to write to permission.yaml");
+        drop(map);
+
+        let mut grants = self.grants.write().unwrap();
+        let before = grants.len();
+        grants.retain(|g| !g.tool_name.starts_with(extension_name));
+        if grants.len() != before {
+            self.save_grants(&grants);
+        }
     }
 }
 
@@ -353,6 +502,66 @@ mod tests {
             .contains(&"nonprefix__tool2".to_string()));
     }
 
+    #[test]
+    fn test_session_grant_is_scoped_to_session() {
+        let (manager, _temp_dir) = create_test_permission_manager();
+        manager.add_grant(
+            "developer__shell",
+            GrantScope::Session {
+                session_id: "s1".to_string(),
+            },
+            None,
+        );
+
+        assert!(manager.find_grant("developer__shell", "s1", None).is_some());
+        assert!(manager.find_grant("developer__shell", "s2", None).is_none());
+        assert!(manager.find_grant("developer__write", "s1", None).is_none());
+    }
+
+    #[test]
+    fn test_project_grant_matches_subdirectories() {
+        let (manager, _temp_dir) = create_test_permission_manager();
+        manager.add_grant(
+            "developer__shell",
+            GrantScope::Project {
+                path: PathBuf::from("/work/project"),
+            },
+            None,
+        );
+
+        let inside = Path::new("/work/project/crates/core");
+        let outside = Path::new("/work/other");
+        assert!(manager
+            .find_grant("developer__shell", "s1", Some(inside))
+            .is_some());
+        assert!(manager
+            .find_grant("developer__shell", "s1", Some(outside))
+            .is_none());
+    }
+
+    #[test]
+    fn test_expired_grants_are_ignored_and_pruned() {
+        let (manager, temp_dir) = create_test_permission_manager();
+        manager.add_grant("tool", GrantScope::Global, Some(Duration::seconds(-1)));
+        let live = manager.add_grant("other", GrantScope::Global, Some(Duration::hours(1)));
+
+        assert!(manager.find_grant("tool", "s1", None).is_none());
+        assert_eq!(manager.list_grants(), vec![live.clone()]);
+
+        let reloaded = PermissionManager::new(temp_dir.path().to_path_buf());
+        assert_eq!(reloaded.list_grants(), vec![live]);
+    }
+
+    #[test]
+    fn test_revoke_grant() {
+        let (manager, _temp_dir) = create_test_permission_manager();
+        let grant = manager.add_grant("tool", GrantScope::Global, Some(Duration::hours(1)));
+
+        assert!(manager.revoke_grant(&grant.id));
+        assert!(!manager.revoke_grant(&grant.id));
+        assert!(manager.find_grant("tool", "s1", None).is_none());
+    }
+
     #[test]
     #[should_panic(expected = "Corrupted permission config")]
     fn test_corrupted_permission_file_panics() {
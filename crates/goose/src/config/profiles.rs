@@ -0,0 +1,170 @@
+//! Named configuration profiles.
+//!
+//! A profile groups the settings that usually change together, so switching setups is a
+//! matter of picking a name:
+//!
+//! ```yaml
+//! GOOSE_PROFILES:
+//!   work-azure:
+//!     provider: azure_openai
+//!     model: gpt-4o
+//!     mode: smart_approve
+//!     permission_profile: reviewer
+//!     extensions: [developer, github]
+//!   personal-anthropic:
+//!     provider: anthropic
+//!     model: claude-sonnet-4-5
+//! ```
+//!
+//! The active profile's values take precedence over the config file, while environment
+//! variables still override both. Values saved with `set_param` always go to the config file
+//! itself, never into the profile.
+
+use super::base::Config;
+use super::goose_mode::GooseMode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub const PROFILES_CONFIG_KEY: &str = "GOOSE_PROFILES";
+pub const PROFILE_ENV_VAR: &str = "GOOSE_PROFILE";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ConfigProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<GooseMode>,
+    /// Permission profile from `GOOSE_PERMISSION_PROFILES`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_profile: Option<String>,
+    /// Configured extensions to enable, by key; the others are disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<String>>,
+    /// Any other config values, by key
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(with = "HashMap<String, serde_json::Value>")]
+    pub settings: HashMap<String, Value>,
+}
+
+impl ConfigProfile {
+    /// Overlay the profile on loaded config values
+    pub fn apply(&self, values: &mut Mapping) {
+        for (key, value) in &self.settings {
+            values.insert(Value::from(key.as_str()), value.clone());
+        }
+        let mut set = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                values.insert(Value::from(key), value);
+            }
+        };
+        set("GOOSE_PROVIDER", self.provider.clone().map(Value::from));
+        set("GOOSE_MODEL", self.model.clone().map(Value::from));
+        set(
+            "GOOSE_MODE",
+            self.mode.and_then(|mode| serde_yaml::to_value(mode).ok()),
+        );
+        set(
+            "GOOSE_PERMISSION_PROFILE",
+            self.permission_profile.clone().map(Value::from),
+        );
+
+        let Some(enabled) = &self.extensions else {
+            return;
+        };
+        if let Some(Value::Mapping(extensions)) = values.get_mut("extensions") {
+            for (key, entry) in extensions.iter_mut() {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                if let Value::Mapping(entry) = entry {
+                    entry.insert(
+                        Value::from("enabled"),
+                        Value::from(enabled.iter().any(|name| name == key)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// The profile named in a `.goose/profile` file in `dir` or the closest parent that has one
+pub fn project_profile_name(dir: &Path) -> Option<String> {
+    dir.ancestors().find_map(|dir| {
+        let name = std::fs::read_to_string(dir.join(".goose").join("profile")).ok()?;
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+pub fn configured_profiles(config: &Config) -> HashMap<String, ConfigProfile> {
+    config
+        .get_saved_param::<HashMap<String, ConfigProfile>>(PROFILES_CONFIG_KEY)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_overlays_settings_and_extension_selection() {
+        let profile: ConfigProfile = serde_yaml::from_str(
+            r#"
+provider: azure_openai
+model: gpt-4o
+mode: smart_approve
+extensions: [developer]
+settings:
+  GOOSE_TEMPERATURE: 0.2
+"#,
+        )
+        .unwrap();
+        let mut values: Mapping = serde_yaml::from_str(
+            r#"
+GOOSE_PROVIDER: anthropic
+GOOSE_MODEL: claude-sonnet-4-5
+extensions:
+  developer:
+    enabled: false
+    type: platform
+  github:
+    enabled: true
+    type: stdio
+"#,
+        )
+        .unwrap();
+
+        profile.apply(&mut values);
+
+        assert_eq!(values["GOOSE_PROVIDER"], Value::from("azure_openai"));
+        assert_eq!(values["GOOSE_MODEL"], Value::from("gpt-4o"));
+        assert_eq!(values["GOOSE_MODE"], Value::from("smart_approve"));
+        assert_eq!(values["GOOSE_TEMPERATURE"], Value::from(0.2));
+        assert_eq!(
+            values["extensions"]["developer"]["enabled"],
+            Value::from(true)
+        );
+        assert_eq!(
+            values["extensions"]["github"]["enabled"],
+            Value::from(false)
+        );
+    }
+
+    #[test]
+    fn project_profile_is_found_in_parent_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".goose")).unwrap();
+        std::fs::write(dir.path().join(".goose/profile"), "work-azure\n").unwrap();
+        let nested = dir.path().join("src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(project_profile_name(&nested).as_deref(), Some("work-azure"));
+    }
+}
@@ -0,0 +1,201 @@
+//! Per-project overrides in `.goose/config.yaml`.
+//!
+//! A repository can pin the settings its contributors should share:
+//!
+//! ```yaml
+//! GOOSE_PROVIDER: anthropic
+//! GOOSE_MODEL: claude-sonnet-4-5
+//! GOOSE_MODE: smart_approve
+//! CONTEXT_FILE_NAMES: [AGENTS.md, .goosehints]
+//! extensions:
+//!   developer: true
+//!   computercontroller: false
+//! ```
+//!
+//! Only these keys are read; anything else in the file is ignored, so a checkout cannot
+//! change signature policies, permissions or secrets, and `extensions` can only switch
+//! extensions the user already configured on or off. The file is applied only after the
+//! user trusts it, and trust is tied to its contents: any change to the file needs to be
+//! trusted again. Environment variables and the active profile still win over it.
+//!
+//! The file is found from the session's working directory when the session starts, with
+//! [`Config::set_project_dir`](super::Config::set_project_dir), and applies to the whole
+//! process from then on.
+
+use super::base::ConfigError;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const TRUSTED_PROJECT_CONFIGS_KEY: &str = "GOOSE_TRUSTED_PROJECT_CONFIGS";
+const OVERRIDABLE_KEYS: &[&str] = &[
+    "GOOSE_PROVIDER",
+    "GOOSE_MODEL",
+    "GOOSE_MODE",
+    "CONTEXT_FILE_NAMES",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectConfig {
+    pub path: PathBuf,
+    /// Hash of the file contents, recorded when the user trusts the file
+    pub digest: String,
+    pub overrides: Mapping,
+    /// Configured extensions to switch on or off, by key
+    pub extensions: BTreeMap<String, bool>,
+    /// Keys in the file that a project is not allowed to set
+    pub ignored_keys: Vec<String>,
+}
+
+/// The project config a process applies to its sessions, with whether the user trusted it
+/// when it was resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveProjectConfig {
+    pub config: ProjectConfig,
+    pub trusted: bool,
+}
+
+/// The `.goose/config.yaml` in `dir` or the closest parent that has one
+pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(".goose").join("config.yaml"))
+        .find(|path| path.is_file())
+}
+
+impl ProjectConfig {
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)?;
+        let path = path.canonicalize()?;
+        let digest = blake3::hash(content.as_bytes()).to_hex().to_string();
+        let values: Mapping = if content.trim().is_empty() {
+            Mapping::new()
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+
+        let mut config = ProjectConfig {
+            path,
+            digest,
+            overrides: Mapping::new(),
+            extensions: BTreeMap::new(),
+            ignored_keys: Vec::new(),
+        };
+        for (key, value) in values {
+            let Some(name) = key.as_str() else {
+                continue;
+            };
+            if name == "extensions" {
+                config.extensions = serde_yaml::from_value(value).map_err(|e| {
+                    ConfigError::DeserializeError(format!(
+                        "extensions in {} must map extension keys to true or false: {}",
+                        config.path.display(),
+                        e
+                    ))
+                })?;
+            } else if OVERRIDABLE_KEYS.contains(&name) {
+                config.overrides.insert(key, value);
+            } else {
+                config.ignored_keys.push(name.to_string());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Whether the user trusted this file with its current contents, according to `values`
+    /// from their config file
+    pub fn is_trusted(&self, values: &Mapping) -> bool {
+        values
+            .get(TRUSTED_PROJECT_CONFIGS_KEY)
+            .and_then(|trusted| trusted.get(self.path.to_string_lossy().as_ref()))
+            .and_then(Value::as_str)
+            == Some(self.digest.as_str())
+    }
+
+    /// Overlay the project's settings on loaded config values
+    pub fn apply(&self, values: &mut Mapping) {
+        for (key, value) in &self.overrides {
+            values.insert(key.clone(), value.clone());
+        }
+        if let Some(Value::Mapping(extensions)) = values.get_mut("extensions") {
+            for (key, enabled) in &self.extensions {
+                if let Some(Value::Mapping(entry)) = extensions.get_mut(key.as_str()) {
+                    entry.insert(Value::from("enabled"), Value::from(*enabled));
+                }
+            }
+        }
+    }
+
+    /// One line per setting the file changes, for showing the user before they trust it
+    pub fn summary(&self) -> Vec<String> {
+        let overrides = self.overrides.iter().filter_map(|(key, value)| {
+            let value = serde_yaml::to_string(value).ok()?;
+            Some(format!("{}: {}", key.as_str()?, value.trim()))
+        });
+        let extensions = self.extensions.iter().map(|(key, enabled)| {
+            let state = if *enabled { "enabled" } else { "disabled" };
+            format!("extension {}: {}", key, state)
+        });
+        overrides.chain(extensions).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_allowed_keys_apply_once_trusted() {
+        let dir = tempfile::tempdir().unwrap();
+        let goose_dir = dir.path().join(".goose");
+        std::fs::create_dir(&goose_dir).unwrap();
+        std::fs::write(
+            goose_dir.join("config.yaml"),
+            r#"
+GOOSE_MODEL: gpt-4o
+GOOSE_SIGNATURE_POLICY: "off"
+extensions:
+  developer: false
+  unknown: true
+"#,
+        )
+        .unwrap();
+        let nested = dir.path().join("crates").join("core");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let path = find_project_config(&nested).unwrap();
+        let project = ProjectConfig::load(&path).unwrap();
+        assert_eq!(project.ignored_keys, vec!["GOOSE_SIGNATURE_POLICY"]);
+
+        let mut values: Mapping = serde_yaml::from_str(
+            r#"
+GOOSE_MODEL: claude-sonnet-4-5
+GOOSE_SIGNATURE_POLICY: enforce
+extensions:
+  developer:
+    enabled: true
+"#,
+        )
+        .unwrap();
+        assert!(!project.is_trusted(&values));
+
+        let mut trusted = Mapping::new();
+        trusted.insert(
+            Value::from(project.path.to_string_lossy().as_ref()),
+            Value::from(project.digest.as_str()),
+        );
+        values.insert(
+            Value::from(TRUSTED_PROJECT_CONFIGS_KEY),
+            Value::Mapping(trusted),
+        );
+        assert!(project.is_trusted(&values));
+
+        project.apply(&mut values);
+        assert_eq!(values["GOOSE_MODEL"], Value::from("gpt-4o"));
+        assert_eq!(values["GOOSE_SIGNATURE_POLICY"], Value::from("enforce"));
+        assert_eq!(
+            values["extensions"]["developer"]["enabled"],
+            Value::from(false)
+        );
+        assert!(values["extensions"].get("unknown").is_none());
+    }
+}
@@ -0,0 +1,242 @@
+//! JSON schema for config.yaml, for editors and CI to validate it against.
+//!
+//! Editors with the YAML language server pick it up from a modeline at the top of the file:
+//!
+//! ```yaml
+//! # yaml-language-server: $schema=/path/to/goose-config.schema.json
+//! ```
+//!
+//! The schema describes the settings goose itself reads, generated from the types they are
+//! read into where they have one. Other keys are allowed, since providers and extensions keep
+//! their own settings in the same file.
+
+use super::extensions::ExtensionEntry;
+use super::migrations::{CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use super::org_config::{ORG_CONFIG_SIGNING_KEY, ORG_CONFIG_URL_KEY};
+use super::profiles::{ConfigProfile, PROFILES_CONFIG_KEY};
+use super::project_config::TRUSTED_PROJECT_CONFIGS_KEY;
+use super::secret_backends::{SecretBackendConfig, SECRET_BACKEND_CONFIG_KEY};
+use super::GooseMode;
+use crate::agents::platform_extensions::web::search::{SearchConfig, WEB_SEARCH_CONFIG_KEY};
+use crate::agents::prompt_layout::PromptLayout;
+use crate::agents::tool_selection::ToolBudget;
+use crate::context_mgmt::recovery::{ContextRecovery, CONTEXT_RECOVERY_CONFIG_KEY};
+use crate::context_mgmt::workspace_changes::WATCH_WORKSPACE_CONFIG_KEY;
+use crate::i18n::LOCALE_CONFIG_KEY;
+use crate::notifications::{ChannelConfig, NOTIFICATIONS_CONFIG_KEY};
+use crate::providers::markdown_chunks::MARKDOWN_CHUNKING_CONFIG_KEY;
+use crate::providers::preflight::MODEL_PREFLIGHT_CONFIG_KEY;
+use crate::providers::server_tools::{ServerTool, SERVER_TOOLS_CONFIG_KEY};
+use crate::retrieval::{SourceConfig, RETRIEVAL_CONFIG_KEY};
+use crate::session::session_manager::SESSION_NAME_INTERVAL_CONFIG_KEY;
+use schemars::generate::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+fn string(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+fn string_list(description: &str) -> Value {
+    json!({ "type": "array", "items": { "type": "string" }, "description": description })
+}
+
+/// The schema of a type goose reads from the file, with its subschemas inlined so it can
+/// sit under a key
+fn schema_of<T: JsonSchema>(description: &str) -> Value {
+    let schema = SchemaSettings::draft2020_12()
+        .with(|settings| settings.inline_subschemas = true)
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let mut schema = serde_json::to_value(schema).unwrap_or_default();
+    if let Some(object) = schema.as_object_mut() {
+        object.remove("$schema");
+        object.remove("title");
+        object.insert("description".to_string(), description.into());
+    }
+    schema
+}
+
+/// The JSON schema (draft 2020-12) for config.yaml
+pub fn config_json_schema() -> Value {
+    let mut properties = Map::new();
+    let mut add = |key: &str, schema: Value| {
+        properties.insert(key.to_string(), schema);
+    };
+    add(
+        CONFIG_VERSION_KEY,
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "maximum": CURRENT_CONFIG_VERSION,
+            "description": "Set by goose when it migrates the file; do not edit"
+        }),
+    );
+    add("GOOSE_PROVIDER", string("Provider used for new sessions"));
+    add("GOOSE_MODEL", string("Model used for new sessions"));
+    add(
+        "GOOSE_MODE",
+        schema_of::<GooseMode>("How much goose asks before running tools"),
+    );
+    add(
+        "GOOSE_SEARCH_PATHS",
+        string_list("Extra directories searched for extension commands"),
+    );
+    add(
+        "GOOSE_TRUSTED_SIGNING_KEYS",
+        string_list("Minisign public keys trusted for project files"),
+    );
+    add(
+        "GOOSE_SIGNATURE_POLICY",
+        json!({ "type": "string", "enum": ["off", "warn", "enforce"] }),
+    );
+    add(
+        "extensions",
+        schema_of::<HashMap<String, ExtensionEntry>>("Configured extensions by key"),
+    );
+    add(
+        PROFILES_CONFIG_KEY,
+        schema_of::<HashMap<String, ConfigProfile>>(
+            "Named profiles, selected with --profile, GOOSE_PROFILE or .goose/profile",
+        ),
+    );
+    add(
+        TRUSTED_PROJECT_CONFIGS_KEY,
+        json!({
+            "type": "object",
+            "additionalProperties": { "type": "string" },
+            "description": "Project .goose/config.yaml files the user trusted, with the hash of their contents; set by goose"
+        }),
+    );
+    add(
+        "GOOSE_PROMPT_SECTIONS",
+        schema_of::<PromptLayout>("How the sections of the system prompt are arranged"),
+    );
+    add(
+        "GOOSE_TOOL_BUDGET",
+        schema_of::<ToolBudget>("Limits on the tool schemas sent with each request"),
+    );
+    add(
+        CONTEXT_RECOVERY_CONFIG_KEY,
+        schema_of::<ContextRecovery>("How to recover when a request exceeds the context window"),
+    );
+    add(
+        WATCH_WORKSPACE_CONFIG_KEY,
+        json!({
+            "type": "boolean",
+            "description": "Tell the model which files changed outside goose since its last reply"
+        }),
+    );
+    add(
+        SESSION_NAME_INTERVAL_CONFIG_KEY,
+        json!({
+            "type": "integer",
+            "minimum": 0,
+            "description": "Rename sessions after their conversation every this many turns; 0 keeps the first name"
+        }),
+    );
+    add(
+        MODEL_PREFLIGHT_CONFIG_KEY,
+        json!({
+            "type": "boolean",
+            "description": "Check that the provider has the configured model before the first turn"
+        }),
+    );
+    add(
+        MARKDOWN_CHUNKING_CONFIG_KEY,
+        json!({
+            "type": "boolean",
+            "description": "Send streamed text to frontends in chunks that end at markdown boundaries"
+        }),
+    );
+    add(
+        LOCALE_CONFIG_KEY,
+        string(
+            "Locale for the messages goose shows, like de or pt-BR; defaults to the system locale",
+        ),
+    );
+    add(
+        NOTIFICATIONS_CONFIG_KEY,
+        schema_of::<Vec<ChannelConfig>>(
+            "Where goose sends approval requests, finished tasks and budget warnings",
+        ),
+    );
+    add(
+        WEB_SEARCH_CONFIG_KEY,
+        schema_of::<SearchConfig>(
+            "Backend for the web extension's web_search tool; Brave when BRAVE_API_KEY is set",
+        ),
+    );
+    add(
+        SERVER_TOOLS_CONFIG_KEY,
+        schema_of::<HashMap<String, Vec<ServerTool>>>(
+            "Tools the provider runs itself, by model; a name ending in * matches every model it prefixes",
+        ),
+    );
+    add(
+        RETRIEVAL_CONFIG_KEY,
+        schema_of::<Vec<SourceConfig>>("Knowledge bases each prompt is looked up in"),
+    );
+    add(
+        SECRET_BACKEND_CONFIG_KEY,
+        schema_of::<SecretBackendConfig>("Where goose keeps secrets instead of the keyring"),
+    );
+    add(
+        ORG_CONFIG_URL_KEY,
+        json!({ "type": "string", "format": "uri", "description": "Organization config layer" }),
+    );
+    add(
+        ORG_CONFIG_SIGNING_KEY,
+        string("Minisign public key the organization config layer must be signed with"),
+    );
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "goose config.yaml",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_accepts_valid_and_rejects_invalid_config() {
+        let validator = jsonschema::validator_for(&config_json_schema()).unwrap();
+        let valid: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+GOOSE_PROVIDER: anthropic
+GOOSE_MODE: smart_approve
+OPENAI_HOST: https://api.openai.com
+extensions:
+  github:
+    type: stdio
+    name: github
+    enabled: true
+    cmd: npx
+    args: ["-y", "@modelcontextprotocol/server-github"]
+    envs:
+      GITHUB_TOKEN: ${keyring:GITHUB_TOKEN}
+    timeout: 300
+GOOSE_SECRET_BACKEND:
+  type: vault
+  path: goose/alice
+"#,
+        )
+        .unwrap();
+        assert!(validator.is_valid(&serde_json::to_value(valid).unwrap()));
+
+        for invalid in [
+            "GOOSE_MODE: yolo",
+            "GOOSE_TOOL_BUDGET:\n  tokens: lots",
+            "extensions:\n  github:\n    type: stdio\n    name: github\n    enabled: true",
+        ] {
+            let invalid: serde_yaml::Value = serde_yaml::from_str(invalid).unwrap();
+            assert!(!validator.is_valid(&serde_json::to_value(invalid).unwrap()));
+        }
+    }
+}
@@ -0,0 +1,423 @@
+//! External stores for secrets, used instead of the system keyring or secrets.yaml.
+//!
+//! Selected with `GOOSE_SECRET_BACKEND` in config.yaml or the environment:
+//!
+//! ```yaml
+//! GOOSE_SECRET_BACKEND:
+//!   type: vault          # HashiCorp Vault KV v2, via the `vault` CLI
+//!   mount: secret
+//!   path: goose/alice
+//! ```
+//!
+//! ```yaml
+//! GOOSE_SECRET_BACKEND:
+//!   type: one_password   # a 1Password item with a field per secret, via the `op` CLI
+//!   vault: Engineering
+//!   item: goose
+//! ```
+//!
+//! ```yaml
+//! GOOSE_SECRET_BACKEND:
+//!   type: aws_secrets_manager   # a JSON secret, via the `aws` CLI
+//!   secret_id: goose/alice
+//!   region: us-east-1
+//! ```
+//!
+//! Each backend talks to its service through the vendor's CLI, so it authenticates however
+//! that CLI is already set up (VAULT_TOKEN, `op signin`, AWS profiles and SSO). Secret values
+//! are passed to the CLIs on stdin, never on the command line.
+
+use super::base::ConfigError;
+use super::search_path::SearchPaths;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub const SECRET_BACKEND_CONFIG_KEY: &str = "GOOSE_SECRET_BACKEND";
+
+pub trait SecretBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// All stored secrets by key
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError>;
+
+    /// Replace the stored secrets with `values`
+    fn store(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecretBackendConfig {
+    Vault {
+        #[serde(default = "default_vault_mount")]
+        mount: String,
+        path: String,
+    },
+    OnePassword {
+        vault: String,
+        item: String,
+    },
+    AwsSecretsManager {
+        secret_id: String,
+        #[serde(default)]
+        region: Option<String>,
+        #[serde(default)]
+        profile: Option<String>,
+    },
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_string()
+}
+
+impl SecretBackendConfig {
+    pub fn build(self) -> Box<dyn SecretBackend> {
+        match self {
+            SecretBackendConfig::Vault { mount, path } => Box::new(VaultBackend { mount, path }),
+            SecretBackendConfig::OnePassword { vault, item } => {
+                Box::new(OnePasswordBackend { vault, item })
+            }
+            SecretBackendConfig::AwsSecretsManager {
+                secret_id,
+                region,
+                profile,
+            } => Box::new(AwsSecretsManagerBackend {
+                secret_id,
+                region,
+                profile,
+            }),
+        }
+    }
+}
+
+/// Stands in for a backend whose configuration could not be read
+pub struct MisconfiguredBackend(pub String);
+
+impl SecretBackend for MisconfiguredBackend {
+    fn name(&self) -> &'static str {
+        "misconfigured"
+    }
+
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        Err(ConfigError::SecretBackendError(self.0.clone()))
+    }
+
+    fn store(&self, _values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        Err(ConfigError::SecretBackendError(self.0.clone()))
+    }
+}
+
+struct CliOutput {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn run_cli(program: &str, args: &[&str], stdin: Option<&str>) -> Result<CliOutput, ConfigError> {
+    let resolved = SearchPaths::builder()
+        .resolve(program)
+        .map_err(|e| ConfigError::SecretBackendError(e.to_string()))?;
+    let mut child = Command::new(resolved)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            ConfigError::SecretBackendError(format!("failed to run {}: {}", program, e))
+        })?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    Ok(CliOutput {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+fn cli_error(backend: &str, output: &CliOutput) -> ConfigError {
+    ConfigError::SecretBackendError(format!("{}: {}", backend, output.stderr))
+}
+
+fn value_as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+struct VaultBackend {
+    mount: String,
+    path: String,
+}
+
+impl VaultBackend {
+    fn mount_arg(&self) -> String {
+        format!("-mount={}", self.mount)
+    }
+}
+
+/// The secret's key/value data from `vault kv get -format=json`
+fn parse_vault_kv(output: &str) -> Result<HashMap<String, Value>, ConfigError> {
+    let response: Value = serde_json::from_str(output)?;
+    Ok(response
+        .pointer("/data/data")
+        .and_then(Value::as_object)
+        .map(|data| data.clone().into_iter().collect())
+        .unwrap_or_default())
+}
+
+impl SecretBackend for VaultBackend {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let output = run_cli(
+            "vault",
+            &["kv", "get", "-format=json", &self.mount_arg(), &self.path],
+            None,
+        )?;
+        if output.success {
+            parse_vault_kv(&output.stdout)
+        } else if output.stderr.contains("No value found") {
+            Ok(HashMap::new())
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+
+    fn store(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        // A lone "-" makes `vault kv put` read the whole secret as JSON from stdin
+        let data = serde_json::to_string(values)?;
+        let output = run_cli(
+            "vault",
+            &["kv", "put", &self.mount_arg(), &self.path, "-"],
+            Some(&data),
+        )?;
+        if output.success {
+            Ok(())
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+}
+
+struct OnePasswordBackend {
+    vault: String,
+    item: String,
+}
+
+impl OnePasswordBackend {
+    fn get_item(&self) -> Result<Option<Value>, ConfigError> {
+        let output = run_cli(
+            "op",
+            &[
+                "item",
+                "get",
+                &self.item,
+                "--vault",
+                &self.vault,
+                "--format",
+                "json",
+            ],
+            None,
+        )?;
+        if output.success {
+            Ok(Some(serde_json::from_str(&output.stdout)?))
+        } else if output.stderr.contains("isn't an item") {
+            Ok(None)
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+}
+
+/// Fields of the item outside any section and without a built-in purpose hold the secrets
+fn is_secret_field(field: &Value) -> bool {
+    field.get("section").is_none() && field.get("purpose").is_none()
+}
+
+/// The secrets held in an item from `op item get --format json`
+fn parse_op_item(item: &Value) -> HashMap<String, Value> {
+    item.get("fields")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|field| is_secret_field(field))
+        .filter_map(|field| {
+            let label = field.get("label")?.as_str()?;
+            let value = field.get("value")?.as_str()?;
+            Some((label.to_string(), Value::String(value.to_string())))
+        })
+        .collect()
+}
+
+impl SecretBackend for OnePasswordBackend {
+    fn name(&self) -> &'static str {
+        "1password"
+    }
+
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        Ok(self
+            .get_item()?
+            .map(|item| parse_op_item(&item))
+            .unwrap_or_default())
+    }
+
+    fn store(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let Some(mut item) = self.get_item()? else {
+            return Err(ConfigError::SecretBackendError(format!(
+                "1password: create the item '{}' in the '{}' vault first",
+                self.item, self.vault
+            )));
+        };
+        // `op item edit` takes the whole item as JSON on stdin, so keep the fields goose
+        // does not manage and replace the rest
+        let mut fields: Vec<Value> = item
+            .get("fields")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter(|field| !is_secret_field(field))
+            .cloned()
+            .collect();
+        fields.extend(values.iter().map(|(key, value)| {
+            serde_json::json!({
+                "id": key,
+                "label": key,
+                "type": "CONCEALED",
+                "value": value_as_text(value),
+            })
+        }));
+        item["fields"] = Value::Array(fields);
+        let template = item.to_string();
+        let output = run_cli(
+            "op",
+            &["item", "edit", &self.item, "--vault", &self.vault],
+            Some(&template),
+        )?;
+        if output.success {
+            Ok(())
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+}
+
+struct AwsSecretsManagerBackend {
+    secret_id: String,
+    region: Option<String>,
+    profile: Option<String>,
+}
+
+impl AwsSecretsManagerBackend {
+    fn args<'a>(&'a self, command: &'a str) -> Vec<&'a str> {
+        let mut args = vec![
+            "secretsmanager",
+            command,
+            "--secret-id",
+            self.secret_id.as_str(),
+        ];
+        if let Some(region) = &self.region {
+            args.extend(["--region", region.as_str()]);
+        }
+        if let Some(profile) = &self.profile {
+            args.extend(["--profile", profile.as_str()]);
+        }
+        args
+    }
+}
+
+impl SecretBackend for AwsSecretsManagerBackend {
+    fn name(&self) -> &'static str {
+        "aws secrets manager"
+    }
+
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let mut args = self.args("get-secret-value");
+        args.extend(["--query", "SecretString", "--output", "text"]);
+        let output = run_cli("aws", &args, None)?;
+        if output.success {
+            Ok(serde_json::from_str(output.stdout.trim())?)
+        } else if output.stderr.contains("ResourceNotFoundException") {
+            Ok(HashMap::new())
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+
+    fn store(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        if cfg!(windows) {
+            return Err(ConfigError::SecretBackendError(
+                "storing secrets in AWS Secrets Manager is not supported on Windows; use the AWS console or CLI".to_string(),
+            ));
+        }
+        let data = serde_json::to_string(values)?;
+        let mut args = self.args("put-secret-value");
+        args.extend(["--secret-string", "file:///dev/stdin"]);
+        let output = run_cli("aws", &args, Some(&data))?;
+        if output.success {
+            Ok(())
+        } else {
+            Err(cli_error(self.name(), &output))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vault_kv_data_is_read_from_the_response() {
+        let output = r#"{"request_id":"1","data":{"data":{"OPENAI_API_KEY":"sk-1"},"metadata":{"version":3}}}"#;
+
+        let secrets = parse_vault_kv(output).unwrap();
+
+        assert_eq!(secrets.len(), 1);
+        assert_eq!(secrets["OPENAI_API_KEY"], Value::from("sk-1"));
+    }
+
+    #[test]
+    fn one_password_fields_become_secrets() {
+        let item: Value = serde_json::from_str(
+            r#"{"id":"x","title":"goose","fields":[
+            {"id":"notesPlain","label":"notesPlain","type":"STRING","purpose":"NOTES","value":"notes"},
+            {"id":"ANTHROPIC_API_KEY","label":"ANTHROPIC_API_KEY","type":"CONCEALED","value":"sk-ant"},
+            {"id":"x1","label":"other","section":{"id":"s"},"type":"STRING","value":"ignored"}
+        ]}"#,
+        )
+        .unwrap();
+
+        let secrets = parse_op_item(&item);
+
+        assert_eq!(
+            secrets,
+            HashMap::from([("ANTHROPIC_API_KEY".to_string(), Value::from("sk-ant"))])
+        );
+    }
+
+    #[test]
+    fn backend_config_is_tagged_by_type() {
+        let config: SecretBackendConfig =
+            serde_yaml::from_str("type: vault\npath: goose/alice").unwrap();
+
+        assert_eq!(
+            config,
+            SecretBackendConfig::Vault {
+                mount: "secret".to_string(),
+                path: "goose/alice".to_string(),
+            }
+        );
+    }
+}
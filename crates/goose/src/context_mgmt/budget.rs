@@ -0,0 +1,284 @@
+//! Token budgets for the sources that make up the system prompt.
+//!
+//! Extension instructions and added instructions each get a share of the model's context
+//! limit and are trimmed to it, so one oversized source cannot crowd out the conversation.
+//! Hint files are held to GOOSE_HINTS_MAX_BYTES when they are read. The resulting
+//! [`ContextReport`] records what every source used and what was cut. Documents retrieved
+//! for a prompt get a share of their own.
+
+use crate::config::Config;
+use crate::token_counter::TokenCounter;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Default share of the context limit for the instructions of extensions and the frontend
+pub const DEFAULT_EXTENSIONS_SHARE: f64 = 0.15;
+/// Default share of the context limit for instructions added by recipes and extensions
+pub const DEFAULT_INSTRUCTIONS_SHARE: f64 = 0.1;
+/// Default share of the context limit for documents retrieved from knowledge bases
+pub const DEFAULT_RETRIEVAL_SHARE: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    /// The base prompt, which is never trimmed
+    SystemPrompt,
+    /// Instructions of the enabled extensions and the frontend
+    Extensions,
+    /// Hint files such as .goosehints and AGENTS.md, held to GOOSE_HINTS_MAX_BYTES
+    Hints,
+    /// Instructions added to the system prompt by recipes and extensions
+    Instructions,
+    /// Documents retrieved from knowledge bases for the latest prompt
+    Retrieval,
+}
+
+impl fmt::Display for ContextSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextSource::SystemPrompt => write!(f, "system prompt"),
+            ContextSource::Extensions => write!(f, "extension instructions"),
+            ContextSource::Hints => write!(f, "hints"),
+            ContextSource::Instructions => write!(f, "instructions"),
+            ContextSource::Retrieval => write!(f, "retrieved documents"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContextBudget {
+    pub extensions: f64,
+    pub instructions: f64,
+    pub retrieval: f64,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self {
+            extensions: DEFAULT_EXTENSIONS_SHARE,
+            instructions: DEFAULT_INSTRUCTIONS_SHARE,
+            retrieval: DEFAULT_RETRIEVAL_SHARE,
+        }
+    }
+}
+
+impl ContextBudget {
+    /// Shares from GOOSE_CONTEXT_BUDGET, e.g. `{extensions: 0.05, instructions: 0.2}`, with
+    /// the defaults for sources it leaves out
+    pub fn from_config() -> Self {
+        let mut budget = Self::default();
+        let Ok(shares) = Config::global().get_param::<HashMap<String, f64>>("GOOSE_CONTEXT_BUDGET")
+        else {
+            return budget;
+        };
+        for (source, share) in shares {
+            let share = share.clamp(0.0, 1.0);
+            match source.as_str() {
+                "extensions" => budget.extensions = share,
+                "hints" => tracing::warn!(
+                    "Ignoring the hints share of GOOSE_CONTEXT_BUDGET; set GOOSE_HINTS_MAX_BYTES"
+                ),
+                "instructions" => budget.instructions = share,
+                "retrieval" => budget.retrieval = share,
+                other => tracing::warn!("Ignoring unknown GOOSE_CONTEXT_BUDGET source '{}'", other),
+            }
+        }
+        budget
+    }
+
+    /// Token budget for a source, or None if the source is not trimmed
+    pub fn tokens_for(&self, source: ContextSource, context_limit: usize) -> Option<usize> {
+        let share = match source {
+            ContextSource::SystemPrompt | ContextSource::Hints => return None,
+            ContextSource::Extensions => self.extensions,
+            ContextSource::Instructions => self.instructions,
+            ContextSource::Retrieval => self.retrieval,
+        };
+        Some((context_limit as f64 * share) as usize)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceUsage {
+    pub source: ContextSource,
+    pub budget: Option<usize>,
+    pub used: usize,
+    pub dropped: usize,
+    /// Sections that were cut, partly or entirely
+    pub trimmed: Vec<String>,
+}
+
+impl SourceUsage {
+    pub fn untrimmed(source: ContextSource, used: usize) -> Self {
+        Self {
+            source,
+            budget: None,
+            used,
+            dropped: 0,
+            trimmed: Vec::new(),
+        }
+    }
+}
+
+/// How the context window was shared out when the system prompt was last built
+#[derive(Debug, Clone, Serialize)]
+pub struct ContextReport {
+    pub context_limit: usize,
+    pub sources: Vec<SourceUsage>,
+}
+
+impl ContextReport {
+    /// Tokens left for the conversation once the system prompt is in place
+    pub fn history_budget(&self) -> usize {
+        let used: usize = self.sources.iter().map(|usage| usage.used).sum();
+        self.context_limit.saturating_sub(used)
+    }
+
+    pub fn dropped(&self) -> usize {
+        self.sources.iter().map(|usage| usage.dropped).sum()
+    }
+}
+
+impl fmt::Display for ContextReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Context window: {} tokens", self.context_limit)?;
+        for usage in &self.sources {
+            write!(f, "- {}: {} tokens", usage.source, usage.used)?;
+            if let Some(budget) = usage.budget {
+                write!(f, " of {}", budget)?;
+            }
+            if usage.dropped > 0 {
+                write!(
+                    f,
+                    ", {} dropped from {}",
+                    usage.dropped,
+                    usage.trimmed.join(", ")
+                )?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "- history: up to {} tokens", self.history_budget())
+    }
+}
+
+/// Fit named sections, given in priority order, into `budget` tokens. The first section
+/// that does not fit is truncated and the ones after it are cleared; callers drop the
+/// sections left empty.
+pub fn fit_sections<'s>(
+    source: ContextSource,
+    sections: impl IntoIterator<Item = (String, &'s mut String)>,
+    budget: usize,
+    counter: &TokenCounter,
+) -> SourceUsage {
+    let mut usage = SourceUsage {
+        source,
+        budget: Some(budget),
+        used: 0,
+        dropped: 0,
+        trimmed: Vec::new(),
+    };
+    for (name, content) in sections {
+        let tokens = counter.count_tokens(content);
+        let remaining = budget - usage.used;
+        if tokens <= remaining {
+            usage.used += tokens;
+            continue;
+        }
+
+        let kept = truncate_to_tokens(content, remaining, counter);
+        tracing::warn!(
+            "Trimming {} from the {} to fit its budget of {} tokens",
+            name,
+            source,
+            budget
+        );
+        usage.used += kept;
+        usage.dropped += tokens - kept;
+        usage.trimmed.push(name);
+        if kept > 0 {
+            content.push_str(&format!(
+                "\n[... {} tokens omitted to fit the context budget]",
+                tokens - kept
+            ));
+        }
+    }
+    usage
+}
+
+/// Cut `content` to at most `max_tokens`, returning the tokens kept
+fn truncate_to_tokens(content: &mut String, max_tokens: usize, counter: &TokenCounter) -> usize {
+    let mut cut = content.len();
+    loop {
+        if cut == 0 || max_tokens == 0 {
+            content.clear();
+            return 0;
+        }
+        let tokens = counter.count_tokens(&content[..cut]);
+        if tokens <= max_tokens {
+            content.truncate(cut);
+            return tokens;
+        }
+        // Shrink in proportion to the overshoot, and by at least one byte
+        cut = (cut * max_tokens / tokens).min(cut - 1);
+        while !content.is_char_boundary(cut) {
+            cut -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token_counter::create_token_counter;
+
+    #[tokio::test]
+    async fn fit_sections_keeps_priority_order() {
+        let counter = create_token_counter().await.unwrap();
+        let mut first = "word ".repeat(20);
+        let mut second = "word ".repeat(20);
+        let mut third = "word ".repeat(20);
+        let first_tokens = counter.count_tokens(&first);
+        let budget = first_tokens + 5;
+
+        let usage = fit_sections(
+            ContextSource::Hints,
+            [
+                ("first".to_string(), &mut first),
+                ("second".to_string(), &mut second),
+                ("third".to_string(), &mut third),
+            ],
+            budget,
+            &counter,
+        );
+
+        assert_eq!(first, "word ".repeat(20));
+        assert!(second.contains("omitted to fit the context budget"));
+        assert!(third.is_empty());
+        assert!(usage.used <= budget);
+        assert_eq!(usage.trimmed, vec!["second", "third"]);
+        assert!(usage.dropped > 0);
+    }
+
+    #[test]
+    fn report_leaves_the_rest_to_history() {
+        let report = ContextReport {
+            context_limit: 1000,
+            sources: vec![
+                SourceUsage::untrimmed(ContextSource::SystemPrompt, 300),
+                SourceUsage {
+                    source: ContextSource::Extensions,
+                    budget: Some(100),
+                    used: 100,
+                    dropped: 50,
+                    trimmed: vec!["developer".to_string()],
+                },
+            ],
+        };
+        assert_eq!(report.history_budget(), 600);
+        assert_eq!(report.dropped(), 50);
+        assert!(report
+            .to_string()
+            .contains("- extension instructions: 100 tokens of 100, 50 dropped from developer"));
+    }
+}
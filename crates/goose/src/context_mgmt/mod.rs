@@ -1,3 +1,7 @@
+pub mod budget;
+pub mod recovery;
+pub mod workspace_changes;
+
 use crate::conversation::message::{ActionRequiredData, MessageMetadata};
 use crate::conversation::message::{Message, MessageContent};
 use crate::conversation::{merge_consecutive_messages, Conversation};
@@ -247,23 +251,30 @@ fn filter_tool_responses(messages: &[Message], remove_percent: u32) -> Vec<&Mess
     let num_to_remove = ((tool_indices.len() * remove_percent as usize) / 100).max(1);
 
     let middle = tool_indices.len() / 2;
-    let mut indices_to_remove = Vec::new();
+    let mut removal_order = Vec::new();
 
     // Middle out
-    for i in 0..num_to_remove {
+    for i in 0..tool_indices.len() * 2 {
         if i % 2 == 0 {
             let offset = i / 2;
             if middle > offset {
-                indices_to_remove.push(tool_indices[middle - offset - 1]);
+                removal_order.push(tool_indices[middle - offset - 1]);
             }
         } else {
             let offset = i / 2;
             if middle + offset < tool_indices.len() {
-                indices_to_remove.push(tool_indices[middle + offset]);
+                removal_order.push(tool_indices[middle + offset]);
             }
         }
     }
 
+    // With their sizes recorded, the largest responses go first since they free the most
+    let recorded_tokens = |i: usize| messages[i].metadata.usage.and_then(|u| u.input_tokens);
+    if tool_indices.iter().any(|&i| recorded_tokens(i).is_some()) {
+        removal_order.sort_by_key(|&i| std::cmp::Reverse(recorded_tokens(i).unwrap_or(0)));
+    }
+    let indices_to_remove = &removal_order[..num_to_remove.min(removal_order.len())];
+
     messages
         .iter()
         .enumerate()
@@ -404,6 +415,10 @@ fn format_message_for_compacting(msg: &Message) -> String {
                 Some(format!("system_notification: {}", notification.msg))
             }
             MessageContent::Reasoning(_) => None,
+            MessageContent::Attachment(attachment) => {
+                Some(format!("attachment: {}", attachment.name))
+            }
+            MessageContent::Annotation(_) => None,
         })
         .collect();
 
@@ -678,6 +693,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_largest_recorded_tool_responses_are_removed_first() {
+        let mut messages = vec![Message::user().with_text("start")];
+        for (i, tokens) in [10, 5000, 20, 30].into_iter().enumerate() {
+            messages.push(Message::assistant().with_tool_request(
+                format!("tool_{}", i),
+                Ok(CallToolRequestParams::new("read_file")),
+            ));
+            messages.push(
+                Message::user()
+                    .with_tool_response(
+                        format!("tool_{}", i),
+                        Ok(rmcp::model::CallToolResult::success(vec![])),
+                    )
+                    .with_usage(crate::conversation::message::MessageUsage {
+                        input_tokens: Some(tokens),
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        let kept = filter_tool_responses(&messages, 25);
+        assert_eq!(kept.len(), messages.len() - 1);
+        assert!(!kept.iter().any(|m| std::ptr::eq(*m, &messages[4])));
+    }
+
     #[tokio::test]
     async fn test_tool_pair_summarization_workflow() {
         fn create_tool_pair(
@@ -0,0 +1,168 @@
+//! What the agent does when the provider rejects a request as too long for the context
+//! window.
+//!
+//! `GOOSE_CONTEXT_RECOVERY` picks the strategy for every session, and a recipe's
+//! `context_recovery` setting overrides it for the sessions it starts:
+//!
+//! - `compact` (default): summarize the conversation and retry
+//! - `drop_tool_outputs`: blank out the older half of the tool outputs in the copy of the
+//!   conversation sent to the model and retry, compacting once there are none left to drop.
+//!   The session keeps the full outputs.
+//! - `off`: stop the turn and leave it to the user
+//!
+//! Compaction is retried once, with PreCompact and PostCompact hooks around it.
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent, ToolResponse};
+use crate::conversation::Conversation;
+use crate::session::Session;
+use rmcp::model::{CallToolResult, Content};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+pub const CONTEXT_RECOVERY_CONFIG_KEY: &str = "GOOSE_CONTEXT_RECOVERY";
+
+/// Text that replaces a tool output dropped to fit the context window.
+pub const DROPPED_TOOL_OUTPUT_TEXT: &str =
+    "[Tool output removed to fit the context window. Run the tool again if it is still needed.]";
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextRecovery {
+    /// Summarize the conversation and retry
+    #[default]
+    Compact,
+    /// Drop the oldest tool outputs and retry
+    DropToolOutputs,
+    /// Don't recover; end the turn with an error
+    Off,
+}
+
+impl ContextRecovery {
+    pub fn from_config() -> Self {
+        Config::global()
+            .get_param::<ContextRecovery>(CONTEXT_RECOVERY_CONFIG_KEY)
+            .unwrap_or_default()
+    }
+
+    /// The strategy for a session: its recipe's setting, else the configured one
+    pub fn for_session(session: &Session) -> Self {
+        session
+            .recipe
+            .as_ref()
+            .and_then(|recipe| recipe.settings.as_ref())
+            .and_then(|settings| settings.context_recovery)
+            .unwrap_or_else(Self::from_config)
+    }
+}
+
+fn is_dropped(response: &ToolResponse) -> bool {
+    matches!(&response.tool_result, Ok(result) if result.content.len() == 1
+        && result.content[0].as_text().is_some_and(|t| t.text == DROPPED_TOOL_OUTPUT_TEXT))
+}
+
+fn droppable_outputs(message: &Message) -> bool {
+    message.is_agent_visible()
+        && message.content.iter().any(|content| {
+            matches!(content, MessageContent::ToolResponse(response) if !is_dropped(response))
+        })
+}
+
+/// Replace the older half of the tool outputs the model still sees with a placeholder,
+/// always keeping the most recent one. The tool requests stay, so every call still has a
+/// response. Returns None when there is nothing to drop.
+pub fn drop_oldest_tool_outputs(conversation: &Conversation) -> Option<Conversation> {
+    let candidates: Vec<usize> = conversation
+        .messages()
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| droppable_outputs(message))
+        .map(|(idx, _)| idx)
+        .collect();
+    if candidates.len() < 2 {
+        return None;
+    }
+    let to_drop = &candidates[..candidates.len() / 2];
+
+    let messages = conversation
+        .messages()
+        .iter()
+        .enumerate()
+        .map(|(idx, message)| {
+            if !to_drop.contains(&idx) {
+                return message.clone();
+            }
+            let mut message = message.clone();
+            for content in message.content.iter_mut() {
+                if let MessageContent::ToolResponse(response) = content {
+                    *response = Arc::new(ToolResponse {
+                        id: response.id.clone(),
+                        tool_result: Ok(CallToolResult::success(vec![Content::text(
+                            DROPPED_TOOL_OUTPUT_TEXT,
+                        )])),
+                        metadata: response.metadata.clone(),
+                    });
+                }
+            }
+            message
+        });
+    Some(Conversation::new_unvalidated(messages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::CallToolRequestParams;
+
+    fn tool_round(id: &str, output: &str) -> Vec<Message> {
+        vec![
+            Message::assistant().with_tool_request(id, Ok(CallToolRequestParams::new("shell"))),
+            Message::user()
+                .with_tool_response(id, Ok(CallToolResult::success(vec![Content::text(output)]))),
+        ]
+    }
+
+    fn outputs(conversation: &Conversation) -> Vec<String> {
+        conversation
+            .messages()
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|c| match c {
+                MessageContent::ToolResponse(r) => r.tool_result.as_ref().ok(),
+                _ => None,
+            })
+            .map(|r| r.content[0].as_text().unwrap().text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn drops_the_older_half_of_tool_outputs() {
+        let mut messages = vec![Message::user().with_text("go")];
+        for (id, output) in [("1", "a"), ("2", "b"), ("3", "c"), ("4", "d")] {
+            messages.extend(tool_round(id, output));
+        }
+        let conversation = Conversation::new_unvalidated(messages);
+
+        let dropped = drop_oldest_tool_outputs(&conversation).unwrap();
+        assert_eq!(
+            outputs(&dropped),
+            [DROPPED_TOOL_OUTPUT_TEXT, DROPPED_TOOL_OUTPUT_TEXT, "c", "d"]
+        );
+        assert_eq!(dropped.messages().len(), conversation.messages().len());
+
+        let dropped = drop_oldest_tool_outputs(&dropped).unwrap();
+        assert_eq!(outputs(&dropped)[2..], [DROPPED_TOOL_OUTPUT_TEXT, "d"]);
+        assert!(drop_oldest_tool_outputs(&dropped).is_none());
+    }
+
+    #[test]
+    fn strategy_names() {
+        let parsed: ContextRecovery = serde_json::from_str("\"drop_tool_outputs\"").unwrap();
+        assert_eq!(parsed, ContextRecovery::DropToolOutputs);
+        assert!(serde_json::from_str::<ContextRecovery>("\"truncate\"").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Telling the model about files changed outside the agent.
+//!
+//! With `GOOSE_WATCH_WORKSPACE` on, the agent snapshots the working directory when a reply
+//! ends: the size and modification time of every file git doesn't ignore. The next reply
+//! compares the snapshot with the files on disk, and when someone edited files in between,
+//! say in their IDE, it starts with a short note listing them, so the model reads them again
+//! instead of acting on what it read turns ago. Changes the agent makes itself happen during
+//! a reply and are never reported.
+
+use crate::config::Config;
+use crate::conversation::message::Message;
+use crate::conversation::{fix_conversation, Conversation};
+use ignore::WalkBuilder;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+pub const WATCH_WORKSPACE_CONFIG_KEY: &str = "GOOSE_WATCH_WORKSPACE";
+
+/// Workspaces with more files than this aren't watched
+const MAX_FILES: usize = 10_000;
+/// Files named in the note; the rest are counted
+const MAX_LISTED: usize = 20;
+
+static RECORDED: LazyLock<Mutex<HashMap<String, WorkspaceSnapshot>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The files of a working directory, at one point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceSnapshot {
+    root: PathBuf,
+    /// Relative path -> modification time and size
+    files: BTreeMap<PathBuf, (Option<SystemTime>, u64)>,
+}
+
+impl WorkspaceSnapshot {
+    /// Snapshot the files under `root`, skipping what git ignores. None when there are too
+    /// many files to watch.
+    pub fn capture(root: &Path) -> Option<Self> {
+        let mut files = BTreeMap::new();
+        let entries = WalkBuilder::new(root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()));
+        for entry in entries {
+            if files.len() >= MAX_FILES {
+                tracing::debug!(
+                    "Not watching {}: more than {} files",
+                    root.display(),
+                    MAX_FILES
+                );
+                return None;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            let relative = path.strip_prefix(root).unwrap_or(path).to_path_buf();
+            files.insert(relative, (metadata.modified().ok(), metadata.len()));
+        }
+        Some(Self {
+            root: root.to_path_buf(),
+            files,
+        })
+    }
+
+    /// What changed from `self` to `newer`
+    pub fn diff(&self, newer: &Self) -> WorkspaceChanges {
+        let mut changes = WorkspaceChanges::default();
+        for (path, file) in &newer.files {
+            match self.files.get(path) {
+                None => changes.added.push(path.clone()),
+                Some(previous) if previous != file => changes.modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        changes.removed = self
+            .files
+            .keys()
+            .filter(|path| !newer.files.contains_key(*path))
+            .cloned()
+            .collect();
+        changes
+    }
+}
+
+/// Files added, modified and removed, relative to the working directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceChanges {
+    pub added: Vec<PathBuf>,
+    pub modified: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+}
+
+impl WorkspaceChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.added.len() + self.modified.len() + self.removed.len()
+    }
+
+    /// The note for the model
+    pub fn note(&self) -> String {
+        let mut note = String::from(
+            "These files in the working directory changed outside of goose since your last \
+             reply. Read them again before relying on what you read earlier:",
+        );
+        let listed = [
+            ("modified", &self.modified),
+            ("added", &self.added),
+            ("removed", &self.removed),
+        ]
+        .into_iter()
+        .flat_map(|(change, paths)| paths.iter().map(move |path| (change, path)))
+        .take(MAX_LISTED);
+        for (change, path) in listed {
+            note.push_str(&format!("\n- {} ({})", path.display(), change));
+        }
+        if self.len() > MAX_LISTED {
+            note.push_str(&format!("\n- and {} more", self.len() - MAX_LISTED));
+        }
+        note
+    }
+}
+
+pub fn enabled() -> bool {
+    Config::global()
+        .get_param::<bool>(WATCH_WORKSPACE_CONFIG_KEY)
+        .unwrap_or(false)
+}
+
+/// Remember the working directory as the agent left it at the end of a reply
+pub async fn record(session_id: &str, working_dir: PathBuf) {
+    let snapshot = tokio::task::spawn_blocking(move || WorkspaceSnapshot::capture(&working_dir))
+        .await
+        .ok()
+        .flatten();
+    let mut recorded = RECORDED.lock().unwrap_or_else(|e| e.into_inner());
+    match snapshot {
+        Some(snapshot) => recorded.insert(session_id.to_string(), snapshot),
+        None => recorded.remove(session_id),
+    };
+}
+
+/// Forget the snapshot of a session that ended
+pub fn forget(session_id: &str) {
+    RECORDED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(session_id);
+}
+
+/// `conversation` with `note` added for the model after the user's latest message. Only the
+/// copy sent to the provider carries it.
+pub fn with_note(conversation: Conversation, note: String) -> Conversation {
+    let mut messages = conversation.messages().clone();
+    messages.push(Message::user().with_text(note).with_visibility(false, true));
+    fix_conversation(Conversation::new_unvalidated(messages)).0
+}
+
+/// What changed in the working directory since the last reply of the session ended. The
+/// snapshot is used up, so a reply that never finishes can't report the agent's own edits
+/// on the next one.
+pub async fn changes_since_recorded(
+    session_id: &str,
+    working_dir: &Path,
+) -> Option<WorkspaceChanges> {
+    let previous = RECORDED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(session_id)?;
+    // The session moved to another directory, so the files aren't comparable
+    if previous.root != working_dir {
+        return None;
+    }
+    let root = working_dir.to_path_buf();
+    let current = tokio::task::spawn_blocking(move || WorkspaceSnapshot::capture(&root))
+        .await
+        .ok()
+        .flatten()?;
+    let changes = previous.diff(&current);
+    (!changes.is_empty()).then_some(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reports_files_changed_between_replies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("old.rs"), "").unwrap();
+        let session = "workspace-changes-test";
+
+        record(session, dir.path().to_path_buf()).await;
+        std::fs::write(dir.path().join("main.rs"), "fn main() { run(); }").unwrap();
+        std::fs::write(dir.path().join("new.rs"), "").unwrap();
+        std::fs::remove_file(dir.path().join("old.rs")).unwrap();
+
+        let changes = changes_since_recorded(session, dir.path()).await.unwrap();
+        assert_eq!(changes.modified, [PathBuf::from("main.rs")]);
+        assert_eq!(changes.added, [PathBuf::from("new.rs")]);
+        assert_eq!(changes.removed, [PathBuf::from("old.rs")]);
+        assert!(changes.note().contains("\n- main.rs (modified)"));
+        // Used up until the next reply records again
+        assert!(changes_since_recorded(session, dir.path()).await.is_none());
+
+        record(session, dir.path().to_path_buf()).await;
+        forget(session);
+        std::fs::write(dir.path().join("new.rs"), "fn new() {}").unwrap();
+        assert!(changes_since_recorded(session, dir.path()).await.is_none());
+    }
+
+    #[test]
+    fn long_lists_are_cut_short() {
+        let changes = WorkspaceChanges {
+            modified: (0..25)
+                .map(|i| PathBuf::from(format!("{}.rs", i)))
+                .collect(),
+            ..Default::default()
+        };
+        let note = changes.note();
+        assert_eq!(note.matches("(modified)").count(), MAX_LISTED);
+        assert!(note.ends_with("- and 5 more"));
+    }
+}
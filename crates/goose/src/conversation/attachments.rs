@@ -0,0 +1,311 @@
+//! Files attached to messages.
+//!
+//! Attached files are copied into the session directory, named by the SHA-256 of their
+//! contents, so a file attached twice is stored once and messages stay small: they carry an
+//! [`AttachmentContent`] reference and the contents are only read when a request is built.
+//! What the provider then sees depends on the file:
+//!
+//! - text up to [`INLINE_TEXT_LIMIT`] is inlined
+//! - longer text is previewed, with the path of the stored copy for the model to read
+//! - images are sent as images when the model takes them
+//! - anything else is described and linked by path
+
+use crate::config::paths::Paths;
+use crate::conversation::message::{AttachmentContent, Message, MessageContent};
+use crate::session::session_manager::SESSIONS_FOLDER;
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+pub const ATTACHMENTS_FOLDER: &str = "attachments";
+
+/// Largest text file sent to the provider in full
+pub const INLINE_TEXT_LIMIT: u64 = 32 * 1024;
+/// Lines of a longer text file shown as its preview
+const PREVIEW_LINES: usize = 40;
+/// Largest image sent to the provider as an image
+const INLINE_IMAGE_LIMIT: u64 = 5 * 1024 * 1024;
+
+const MIME_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("pdf", "application/pdf"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("zip", "application/zip"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("md", "text/markdown"),
+];
+
+/// MIME type for a file name, by extension. Unknown extensions are sniffed by the store.
+pub fn mime_type_for(name: &str) -> Option<&'static str> {
+    let extension = Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+    MIME_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime_type)| *mime_type)
+}
+
+fn is_text(mime_type: &str) -> bool {
+    mime_type.starts_with("text/")
+        || matches!(
+            mime_type,
+            "application/json" | "application/xml" | "application/yaml" | "application/toml"
+        )
+}
+
+/// Whether `hash` is a SHA-256 as the store names files, so it cannot leave the store's directory
+fn is_valid_hash(hash: &str) -> bool {
+    hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Content-addressed storage for a session's attachments
+#[derive(Debug, Clone)]
+pub struct AttachmentStore {
+    dir: PathBuf,
+}
+
+impl AttachmentStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn for_session(session_id: &str) -> Self {
+        Self::new(
+            Paths::data_dir()
+                .join(SESSIONS_FOLDER)
+                .join(session_id)
+                .join(ATTACHMENTS_FOLDER),
+        )
+    }
+
+    /// Where the contents of an attachment are stored. Attachments come from clients, so
+    /// anything but a SHA-256 hex digest is refused.
+    pub fn path(&self, attachment: &AttachmentContent) -> Result<PathBuf> {
+        if !is_valid_hash(&attachment.hash) {
+            bail!("Attachment {} has an invalid hash", attachment.name);
+        }
+        Ok(self.dir.join(&attachment.hash))
+    }
+
+    /// Store `data` under its hash. Without a MIME type it is guessed from the name, then
+    /// from whether the data is text.
+    pub fn add_bytes(
+        &self,
+        name: &str,
+        mime_type: Option<&str>,
+        data: &[u8],
+    ) -> Result<AttachmentContent> {
+        let hash = format!("{:x}", Sha256::digest(data));
+        let mime_type = mime_type
+            .or_else(|| mime_type_for(name))
+            .unwrap_or_else(|| {
+                if std::str::from_utf8(data).is_ok() && !data.contains(&0) {
+                    "text/plain"
+                } else {
+                    "application/octet-stream"
+                }
+            });
+        let attachment = AttachmentContent {
+            hash,
+            name: name.to_string(),
+            mime_type: mime_type.to_string(),
+            size: data.len() as u64,
+        };
+
+        let path = self.path(&attachment)?;
+        if !path.exists() {
+            std::fs::create_dir_all(&self.dir)
+                .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+            let partial = path.with_extension("partial");
+            std::fs::write(&partial, data)
+                .with_context(|| format!("Failed to write {}", partial.display()))?;
+            std::fs::rename(&partial, &path)?;
+        }
+        Ok(attachment)
+    }
+
+    /// Copy a file into the store
+    pub fn add_file(&self, path: &Path) -> Result<AttachmentContent> {
+        let data =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        self.add_bytes(&name, None, &data)
+    }
+
+    pub async fn read(&self, attachment: &AttachmentContent) -> Result<Vec<u8>> {
+        let path = self.path(attachment)?;
+        tokio::fs::read(&path).await.with_context(|| {
+            format!(
+                "Attachment {} is missing from {}",
+                attachment.name,
+                path.display()
+            )
+        })
+    }
+
+    /// What the provider is sent in place of an attachment
+    pub async fn render(&self, attachment: &AttachmentContent, images: bool) -> MessageContent {
+        let path = match self.path(attachment) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::warn!("{:#}", e);
+                return MessageContent::text(format!(
+                    "[Attachment {} could not be read]",
+                    attachment.name
+                ));
+            }
+        };
+        let describe = |note: &str| {
+            MessageContent::text(format!(
+                "[Attachment {} ({}, {} bytes) {}. The file is at {}]",
+                attachment.name,
+                attachment.mime_type,
+                attachment.size,
+                note,
+                path.display()
+            ))
+        };
+
+        let inline_image = images
+            && attachment.mime_type.starts_with("image/")
+            && attachment.size <= INLINE_IMAGE_LIMIT;
+        let inline_text = is_text(&attachment.mime_type);
+        if !inline_image && !inline_text {
+            return describe("is not shown inline");
+        }
+
+        let data = match self.read(attachment).await {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("{:#}", e);
+                return describe("could not be read");
+            }
+        };
+        if inline_image {
+            let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+            return MessageContent::image(encoded, attachment.mime_type.clone());
+        }
+
+        let text = String::from_utf8_lossy(&data);
+        if attachment.size <= INLINE_TEXT_LIMIT {
+            return MessageContent::text(format!(
+                "<attachment name=\"{}\">\n{}\n</attachment>",
+                attachment.name, text
+            ));
+        }
+        let total_lines = text.lines().count();
+        let preview: Vec<&str> = text.lines().take(PREVIEW_LINES).collect();
+        MessageContent::text(format!(
+            "<attachment name=\"{}\" preview=\"first {} of {} lines\" path=\"{}\">\n{}\n</attachment>",
+            attachment.name,
+            preview.len(),
+            total_lines,
+            path.display(),
+            preview.join("\n")
+        ))
+    }
+
+    /// Replace the attachments in `messages` with what the provider is sent
+    pub async fn render_messages(&self, messages: Vec<Message>, images: bool) -> Vec<Message> {
+        let mut rendered = Vec::with_capacity(messages.len());
+        for mut message in messages {
+            if message.content.iter().any(|c| c.as_attachment().is_some()) {
+                let mut content = Vec::with_capacity(message.content.len());
+                for item in &message.content {
+                    content.push(match item {
+                        MessageContent::Attachment(attachment) => {
+                            self.render(attachment, images).await
+                        }
+                        other => other.clone(),
+                    });
+                }
+                message.content = content;
+            }
+            rendered.push(message);
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn identical_files_are_stored_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().to_path_buf());
+        let first = store.add_bytes("a.txt", None, b"hello").unwrap();
+        let second = store.add_bytes("b.txt", None, b"hello").unwrap();
+        assert_eq!(first.hash, second.hash);
+        assert_eq!(first.mime_type, "text/plain");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(store.read(&second).await.unwrap(), b"hello");
+    }
+
+    #[tokio::test]
+    async fn hashes_that_are_not_digests_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().join("attachments"));
+        std::fs::write(dir.path().join("secret"), "key").unwrap();
+        let upper = "A".repeat(64);
+        let short = "a".repeat(63);
+        for hash in ["../secret", "/etc/passwd", upper.as_str(), short.as_str()] {
+            let attachment = AttachmentContent {
+                hash: hash.to_string(),
+                name: "x".to_string(),
+                mime_type: "text/plain".to_string(),
+                size: 3,
+            };
+            assert!(store.path(&attachment).is_err(), "{}", hash);
+            assert!(store.read(&attachment).await.is_err(), "{}", hash);
+            assert!(!store
+                .render(&attachment, true)
+                .await
+                .as_text()
+                .unwrap()
+                .contains("key"));
+        }
+    }
+
+    #[tokio::test]
+    async fn renders_by_size_and_type() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AttachmentStore::new(dir.path().to_path_buf());
+
+        let small = store.add_bytes("notes.md", None, b"# Notes").unwrap();
+        let text = store.render(&small, true).await;
+        assert!(text.as_text().unwrap().contains("# Notes"));
+
+        let long: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        let long = store.add_bytes("log.txt", None, long.as_bytes()).unwrap();
+        let preview = store.render(&long, true).await;
+        let preview = preview.as_text().unwrap();
+        assert!(preview.contains("first 40 of 5000 lines"));
+        assert!(!preview.contains("line 40\n"));
+
+        let image = store.add_bytes("shot.png", None, &[0x89, 0x50]).unwrap();
+        assert!(matches!(
+            store.render(&image, true).await,
+            MessageContent::Image(_)
+        ));
+        assert!(store
+            .render(&image, false)
+            .await
+            .as_text()
+            .unwrap()
+            .contains("is not shown inline"));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::conversation::tool_result_serde;
 use crate::mcp_utils::{extract_text_from_resource, ToolResult};
+use crate::providers::base::Usage;
+use crate::providers::canonical::Pricing;
+use crate::providers::server_tools::SERVER_TOOL_METADATA_KEY;
 use crate::utils::sanitize_unicode_tags;
 use chrono::Utc;
 use rmcp::model::{
@@ -10,6 +13,7 @@ use rmcp::model::{
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashSet;
 use std::fmt;
+use std::sync::Arc;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -87,6 +91,11 @@ impl ToolRequest {
             Err(e) => format!("Invalid tool call: {}", e),
         }
     }
+
+    /// Whether the provider ran this call itself
+    pub fn is_server_tool(&self) -> bool {
+        is_server_tool(self.metadata.as_ref())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -102,6 +111,19 @@ pub struct ToolResponse {
     pub metadata: Option<ProviderMetadata>,
 }
 
+impl ToolResponse {
+    /// Whether this is the result of a call the provider ran itself
+    pub fn is_server_tool(&self) -> bool {
+        is_server_tool(self.metadata.as_ref())
+    }
+}
+
+fn is_server_tool(metadata: Option<&ProviderMetadata>) -> bool {
+    metadata.is_some_and(|metadata| {
+        metadata.get(SERVER_TOOL_METADATA_KEY) == Some(&serde_json::Value::Bool(true))
+    })
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[derive(ToSchema)]
@@ -165,6 +187,7 @@ pub enum SystemNotificationType {
     ThinkingMessage,
     InlineMessage,
     CreditsExhausted,
+    ToolProgress,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
@@ -181,6 +204,87 @@ pub struct ReasoningContent {
     pub text: String,
 }
 
+/// A file attached to a message. The contents live in the session's attachment store, see
+/// [`crate::conversation::attachments`]; the message only refers to them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentContent {
+    /// SHA-256 of the contents, which names the stored file
+    pub hash: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+}
+
+/// What an annotation cites
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CitationSource {
+    #[serde(rename_all = "camelCase")]
+    Url { url: String, title: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    File {
+        path: String,
+        start_line: Option<u32>,
+        end_line: Option<u32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    ToolResult { tool_call_id: String },
+}
+
+/// Where part of a message's text came from. Offsets count characters into the text the
+/// model produced for the message, that is its text content joined without separators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotationContent {
+    /// First character of the supported span; the whole text when there is no span
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    /// One past the last character of the span
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+    pub source: CitationSource,
+    /// The passage of the source that was cited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cited_text: Option<String>,
+}
+
+impl AnnotationContent {
+    pub fn new(source: CitationSource) -> Self {
+        Self {
+            start: None,
+            end: None,
+            source,
+            cited_text: None,
+        }
+    }
+
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.start = Some(start);
+        self.end = Some(end);
+        self
+    }
+
+    pub fn with_cited_text(mut self, cited_text: impl Into<String>) -> Self {
+        self.cited_text = Some(cited_text.into());
+        self
+    }
+
+    /// The part of `text` this annotation supports
+    pub fn span<'a>(&self, text: &'a str) -> Option<&'a str> {
+        let (Some(start), Some(end)) = (self.start, self.end) else {
+            return Some(text);
+        };
+        let byte_offset = |chars: usize| {
+            text.char_indices()
+                .map(|(idx, _)| idx)
+                .chain(std::iter::once(text.len()))
+                .nth(chars)
+        };
+        text.get(byte_offset(start)?..byte_offset(end)?)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 /// Content passed inside a message, which can be both simple content and tool content
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -188,7 +292,9 @@ pub enum MessageContent {
     Text(TextContent),
     Image(ImageContent),
     ToolRequest(ToolRequest),
-    ToolResponse(ToolResponse),
+    /// Shared, since tool output can be megabytes and messages are cloned as they move from
+    /// the agent to the provider, the session store and the client
+    ToolResponse(Arc<ToolResponse>),
     ToolConfirmationRequest(ToolConfirmationRequest),
     ActionRequired(ActionRequired),
     FrontendToolRequest(FrontendToolRequest),
@@ -196,6 +302,10 @@ pub enum MessageContent {
     RedactedThinking(RedactedThinkingContent),
     SystemNotification(SystemNotificationContent),
     Reasoning(ReasoningContent),
+    Attachment(AttachmentContent),
+    /// Shown to the user but never sent to providers, since the cited text is already in
+    /// the message
+    Annotation(AnnotationContent),
 }
 
 impl fmt::Display for MessageContent {
@@ -238,6 +348,14 @@ impl fmt::Display for MessageContent {
                 write!(f, "[SystemNotification: {}]", r.msg)
             }
             MessageContent::Reasoning(r) => write!(f, "[Reasoning: {}]", r.text),
+            MessageContent::Attachment(a) => write!(f, "[Attachment: {}]", a.name),
+            MessageContent::Annotation(a) => match &a.source {
+                CitationSource::Url { url, .. } => write!(f, "[Citation: {}]", url),
+                CitationSource::File { path, .. } => write!(f, "[Citation: {}]", path),
+                CitationSource::ToolResult { tool_call_id } => {
+                    write!(f, "[Citation: tool result {}]", tool_call_id)
+                }
+            },
         }
     }
 }
@@ -282,26 +400,25 @@ impl MessageContent {
                     return Some(self.clone());
                 };
 
-                let filtered_content: Vec<Content> = result
-                    .content
-                    .iter()
-                    .filter(|c| {
-                        c.audience()
-                            .map(|roles| roles.contains(&audience))
-                            .unwrap_or(true)
-                    })
-                    .cloned()
-                    .collect();
+                let visible = |c: &Content| {
+                    c.audience()
+                        .map(|roles| roles.contains(&audience))
+                        .unwrap_or(true)
+                };
+                // Most responses are visible to everyone, so share them rather than copy
+                if result.content.iter().all(visible) {
+                    return Some(self.clone());
+                }
 
                 // Preserve ToolResponse even when content is empty - some providers
                 // (like Google) need to handle empty tool responses specially
                 let mut tool_result = result.clone();
-                tool_result.content = filtered_content;
-                Some(MessageContent::ToolResponse(ToolResponse {
+                tool_result.content.retain(visible);
+                Some(MessageContent::ToolResponse(Arc::new(ToolResponse {
                     id: res.id.clone(),
                     tool_result: Ok(tool_result),
                     metadata: res.metadata.clone(),
-                }))
+                })))
             }
             MessageContent::Thinking(_) | MessageContent::RedactedThinking(_) => None,
             _ => Some(self.clone()),
@@ -345,11 +462,11 @@ impl MessageContent {
     }
 
     pub fn tool_response<S: Into<String>>(id: S, tool_result: ToolResult<CallToolResult>) -> Self {
-        MessageContent::ToolResponse(ToolResponse {
+        MessageContent::ToolResponse(Arc::new(ToolResponse {
             id: id.into(),
             tool_result,
             metadata: None,
-        })
+        }))
     }
 
     pub fn tool_response_with_metadata<S: Into<String>>(
@@ -357,11 +474,11 @@ impl MessageContent {
         tool_result: ToolResult<CallToolResult>,
         metadata: Option<&ProviderMetadata>,
     ) -> Self {
-        MessageContent::ToolResponse(ToolResponse {
+        MessageContent::ToolResponse(Arc::new(ToolResponse {
             id: id.into(),
             tool_result,
             metadata: metadata.cloned(),
-        })
+        }))
     }
 
     pub fn action_required<S: Into<String>>(
@@ -454,6 +571,22 @@ impl MessageContent {
         MessageContent::Reasoning(ReasoningContent { text: text.into() })
     }
 
+    pub fn as_attachment(&self) -> Option<&AttachmentContent> {
+        if let MessageContent::Attachment(ref attachment) = self {
+            Some(attachment)
+        } else {
+            None
+        }
+    }
+
+    pub fn as_annotation(&self) -> Option<&AnnotationContent> {
+        if let MessageContent::Annotation(ref annotation) = self {
+            Some(annotation)
+        } else {
+            None
+        }
+    }
+
     pub fn as_system_notification(&self) -> Option<&SystemNotificationContent> {
         if let MessageContent::SystemNotification(ref notification) = self {
             Some(notification)
@@ -472,7 +605,7 @@ impl MessageContent {
 
     pub fn as_tool_response(&self) -> Option<&ToolResponse> {
         if let MessageContent::ToolResponse(ref tool_response) = self {
-            Some(tool_response)
+            Some(tool_response.as_ref())
         } else {
             None
         }
@@ -579,6 +712,60 @@ impl From<PromptMessage> for Message {
     }
 }
 
+/// Tokens attributed to a message. On an assistant message, what the provider reported for
+/// the call that produced it; on a tool response, the tokens it adds to the context.
+#[derive(ToSchema, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageUsage {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_tokens: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_tokens: Option<i32>,
+    /// Cost of the tokens in USD, when the model's pricing is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+impl From<&Usage> for MessageUsage {
+    fn from(usage: &Usage) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost: None,
+        }
+    }
+}
+
+impl MessageUsage {
+    /// The usage with its cost, from prices per million tokens
+    pub fn priced(mut self, pricing: &Pricing) -> Self {
+        let price = |tokens: Option<i32>, per_million: Option<f64>| match (tokens, per_million) {
+            (Some(0) | None, _) => Some(0.0),
+            (Some(tokens), Some(price)) => Some(tokens as f64 * price / 1_000_000.0),
+            (Some(_), None) => None,
+        };
+        self.cost = price(self.input_tokens, pricing.input)
+            .zip(price(self.output_tokens, pricing.output))
+            .map(|(input, output)| input + output);
+        self
+    }
+}
+
+impl std::ops::AddAssign for MessageUsage {
+    fn add_assign(&mut self, other: Self) {
+        let sum = |a: Option<i32>, b: Option<i32>| match (a, b) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+        self.input_tokens = sum(self.input_tokens, other.input_tokens);
+        self.output_tokens = sum(self.output_tokens, other.output_tokens);
+        self.cost = match (self.cost, other.cost) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+    }
+}
+
 #[derive(ToSchema, Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
 /// Metadata for message visibility
 #[serde(rename_all = "camelCase")]
@@ -587,6 +774,13 @@ pub struct MessageMetadata {
     pub user_visible: bool,
     /// Whether the message should be included in the agent's context window
     pub agent_visible: bool,
+    /// The turn of the conversation the message belongs to, see
+    /// [`crate::conversation::turns`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn: Option<u32>,
+    /// Usage of the provider call that produced the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<MessageUsage>,
 }
 
 impl Default for MessageMetadata {
@@ -594,6 +788,8 @@ impl Default for MessageMetadata {
         MessageMetadata {
             user_visible: true,
             agent_visible: true,
+            turn: None,
+            usage: None,
         }
     }
 }
@@ -603,15 +799,15 @@ impl MessageMetadata {
     pub fn agent_only() -> Self {
         MessageMetadata {
             user_visible: false,
-            agent_visible: true,
+            ..Default::default()
         }
     }
 
     /// Create metadata for messages visible only to the user
     pub fn user_only() -> Self {
         MessageMetadata {
-            user_visible: true,
             agent_visible: false,
+            ..Default::default()
         }
     }
 
@@ -620,6 +816,7 @@ impl MessageMetadata {
         MessageMetadata {
             user_visible: false,
             agent_visible: false,
+            ..Default::default()
         }
     }
 
@@ -683,15 +880,16 @@ impl Message {
     }
 
     pub fn agent_visible_content(&self) -> Message {
-        let filtered_content = self
-            .content
-            .iter()
-            .filter_map(|c| c.filter_for_audience(Role::Assistant))
-            .collect();
-
         Message {
-            content: filtered_content,
-            ..self.clone()
+            id: self.id.clone(),
+            role: self.role.clone(),
+            created: self.created,
+            content: self
+                .content
+                .iter()
+                .filter_map(|c| c.filter_for_audience(Role::Assistant))
+                .collect(),
+            metadata: self.metadata.clone(),
         }
     }
 
@@ -751,6 +949,21 @@ impl Message {
         self.with_content(MessageContent::image(data, mime_type))
     }
 
+    /// Attach a file stored in the session's attachment store
+    pub fn with_attachment(self, attachment: AttachmentContent) -> Self {
+        self.with_content(MessageContent::Attachment(attachment))
+    }
+
+    /// Cite the source of some of the message's text
+    pub fn with_annotation(self, annotation: AnnotationContent) -> Self {
+        self.with_content(MessageContent::Annotation(annotation))
+    }
+
+    /// The message's citations, in the order they were added
+    pub fn annotations(&self) -> impl Iterator<Item = &AnnotationContent> {
+        self.content.iter().filter_map(|c| c.as_annotation())
+    }
+
     /// Add a tool request to the message
     pub fn with_tool_request<S: Into<String>>(
         self,
@@ -923,6 +1136,38 @@ impl Message {
         .with_metadata(MessageMetadata::user_only())
     }
 
+    /// Progress reported by a running tool. The message id is derived from the tool request,
+    /// so each update replaces the previous one in UIs that merge messages by id.
+    pub fn tool_progress(
+        tool_request_id: &str,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<&str>,
+    ) -> Self {
+        let percentage = total
+            .filter(|total| *total > 0.0)
+            .map(|total| (progress / total * 100.0).clamp(0.0, 100.0).round());
+        let msg = match (message, percentage) {
+            (Some(message), Some(percentage)) => format!("{}… {}%", message, percentage),
+            (Some(message), None) => message.to_string(),
+            (None, Some(percentage)) => format!("{}%", percentage),
+            (None, None) => format!("{}", progress),
+        };
+        Message::assistant()
+            .with_id(format!("progress_{}", tool_request_id))
+            .with_system_notification_with_data(
+                SystemNotificationType::ToolProgress,
+                msg,
+                serde_json::json!({
+                    "toolRequestId": tool_request_id,
+                    "progress": progress,
+                    "total": total,
+                    "percentage": percentage,
+                    "message": message,
+                }),
+            )
+    }
+
     pub fn with_visibility(mut self, user_visible: bool, agent_visible: bool) -> Self {
         self.metadata.user_visible = user_visible;
         self.metadata.agent_visible = agent_visible;
@@ -934,6 +1179,16 @@ impl Message {
         self
     }
 
+    pub fn with_turn(mut self, turn: u32) -> Self {
+        self.metadata.turn = Some(turn);
+        self
+    }
+
+    pub fn with_usage(mut self, usage: MessageUsage) -> Self {
+        self.metadata.usage = Some(usage);
+        self
+    }
+
     pub fn user_only(mut self) -> Self {
         self.metadata.user_visible = true;
         self.metadata.agent_visible = false;
@@ -974,9 +1229,10 @@ mod tests {
         AnnotateAble, CallToolRequestParams, PromptMessage, PromptMessageContent,
         PromptMessageRole, RawEmbeddedResource, RawImageContent, ResourceContents,
     };
-    use rmcp::model::{ErrorCode, ErrorData};
+    use rmcp::model::{CallToolResult, Content, ErrorCode, ErrorData};
     use rmcp::object;
     use serde_json::Value;
+    use std::sync::Arc;
 
     #[test]
     fn test_sanitize_with_text() {
@@ -1452,6 +1708,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cloned_messages_share_tool_output() {
+        let output = "x".repeat(1 << 20);
+        let message = Message::user().with_tool_response(
+            "tool789",
+            Ok(CallToolResult::success(vec![Content::text(output)])),
+        );
+        let copy = message.clone();
+
+        match (&message.content[0], &copy.content[0]) {
+            (MessageContent::ToolResponse(a), MessageContent::ToolResponse(b)) => {
+                assert!(Arc::ptr_eq(a, b));
+            }
+            _ => panic!("Expected ToolResponse content"),
+        }
+        assert_eq!(message, copy);
+    }
+
+    #[test]
+    fn test_audience_filter_copies_only_annotated_tool_output() {
+        let shared = Message::user().with_tool_response(
+            "tool1",
+            Ok(CallToolResult::success(vec![Content::text("visible")])),
+        );
+        match (
+            &shared.content[0],
+            &shared.agent_visible_content().content[0],
+        ) {
+            (MessageContent::ToolResponse(a), MessageContent::ToolResponse(b)) => {
+                assert!(Arc::ptr_eq(a, b));
+            }
+            _ => panic!("Expected ToolResponse content"),
+        }
+
+        let annotated = Message::user().with_tool_response(
+            "tool2",
+            Ok(CallToolResult::success(vec![
+                Content::text("for the model"),
+                Content::text("for the user").with_audience(vec![Role::User]),
+            ])),
+        );
+        let filtered = annotated.agent_visible_content();
+        let MessageContent::ToolResponse(response) = &filtered.content[0] else {
+            panic!("Expected ToolResponse content");
+        };
+        let result = response.tool_result.as_ref().unwrap();
+        assert_eq!(result.content.len(), 1);
+        assert_eq!(result.content[0].as_text().unwrap().text, "for the model");
+    }
+
     #[test]
     fn test_new_tool_response_deserialization() {
         let new_json = r#"{
@@ -1577,4 +1883,50 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tool_progress_updates_share_an_id() {
+        let first = Message::tool_progress("call_1", 45.0, Some(100.0), Some("cloning repository"));
+        let second =
+            Message::tool_progress("call_1", 90.0, Some(100.0), Some("cloning repository"));
+        assert_eq!(first.id, second.id);
+        assert!(!first.is_agent_visible());
+
+        let notification = first.content[0].as_system_notification().unwrap();
+        assert_eq!(notification.msg, "cloning repository… 45%");
+        assert_eq!(notification.data.as_ref().unwrap()["percentage"], 45.0);
+
+        let indeterminate = Message::tool_progress("call_2", 3.0, None, Some("indexing"));
+        let notification = indeterminate.content[0].as_system_notification().unwrap();
+        assert_eq!(notification.msg, "indexing");
+        assert!(notification.data.as_ref().unwrap()["percentage"].is_null());
+    }
+
+    #[test]
+    fn test_annotation_spans_and_serialization() {
+        let message = Message::assistant()
+            .with_text("Café prices rose 5% in 2024.")
+            .with_annotation(
+                AnnotationContent::new(CitationSource::Url {
+                    url: "https://example.com/report".to_string(),
+                    title: Some("Report".to_string()),
+                })
+                .with_span(12, 27)
+                .with_cited_text("prices rose 5%"),
+            );
+
+        let annotation = message.annotations().next().unwrap();
+        assert_eq!(
+            annotation.span(&message.as_concat_text()),
+            Some("rose 5% in 2024")
+        );
+        assert_eq!(annotation.clone().with_span(3, 99).span("Café"), None);
+
+        let value = serde_json::to_value(&message.content[1]).unwrap();
+        assert_eq!(value["type"], "annotation");
+        assert_eq!(value["source"]["type"], "url");
+        assert_eq!(value["citedText"], "prices rose 5%");
+        let parsed: MessageContent = serde_json::from_value(value).unwrap();
+        assert_eq!(&parsed, &message.content[1]);
+    }
 }
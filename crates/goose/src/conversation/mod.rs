@@ -5,8 +5,10 @@ use std::collections::HashSet;
 use thiserror::Error;
 use utoipa::ToSchema;
 
+pub mod attachments;
 pub mod message;
 mod tool_result_serde;
+pub mod turns;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq)]
 pub struct Conversation(Vec<Message>);
@@ -349,6 +351,9 @@ fn fix_tool_calling(mut messages: Vec<Message>) -> (Vec<Message>, Vec<String>) {
             Role::Assistant => {
                 for (idx, content) in message.content.iter().enumerate() {
                     match content {
+                        // Calls the provider ran carry their response on the assistant message
+                        MessageContent::ToolRequest(req) if req.is_server_tool() => {}
+                        MessageContent::ToolResponse(resp) if resp.is_server_tool() => {}
                         MessageContent::ToolResponse(resp) => {
                             content_to_remove.push(idx);
                             issues.push(format!(
@@ -503,6 +508,7 @@ pub fn debug_conversation_fix(
 mod tests {
     use crate::conversation::message::Message;
     use crate::conversation::{debug_conversation_fix, fix_conversation, Conversation};
+    use crate::providers::server_tools::{self, ServerTool};
     use rmcp::model::{CallToolRequestParams, Role};
     use rmcp::object;
 
@@ -715,6 +721,29 @@ mod tests {
         assert!(issues.is_empty());
     }
 
+    #[test]
+    fn test_server_tool_calls_stay_on_the_assistant_message() {
+        let [request, response] = server_tools::call(
+            "srvtoolu_1",
+            ServerTool::WebSearch,
+            serde_json::json!({"query": "goose"}),
+            "- goose (https://example.com)".to_string(),
+            false,
+        );
+        let messages = vec![
+            Message::user().with_text("Search for goose"),
+            Message::assistant()
+                .with_content(request)
+                .with_content(response)
+                .with_text("Found it"),
+            Message::user().with_text("Thanks!"),
+        ];
+
+        let (fixed, issues) = run_verify(messages.clone());
+        assert!(issues.is_empty());
+        assert_eq!(fixed, messages);
+    }
+
     #[test]
     fn test_merge_text_content_items() {
         use crate::conversation::message::MessageContent;
@@ -0,0 +1,370 @@
+//! Turns of a conversation: a prompt from the user and everything the agent did about it,
+//! from tool calls to the final answer.
+//!
+//! The agent numbers each turn when the prompt arrives and the session stamps every message
+//! saved after it with that number, so grouping doesn't depend on guessing where one turn
+//! ends. Messages saved before turns were numbered are grouped by the user prompts instead.
+
+use crate::conversation::message::{Message, MessageContent, MessageUsage};
+use crate::conversation::Conversation;
+use crate::providers::base::Usage;
+use crate::providers::canonical::Pricing;
+use crate::token_counter::TokenCounter;
+use rmcp::model::Role;
+use serde::Serialize;
+use std::ops::Range;
+use utoipa::ToSchema;
+
+/// The messages of one turn, in order
+#[derive(Debug, Clone, Copy)]
+pub struct Turn<'a> {
+    pub id: u32,
+    pub messages: &'a [Message],
+}
+
+/// Whether a message opens a turn when the turn isn't recorded on it
+fn starts_turn(message: &Message) -> bool {
+    message.role == Role::User
+        && !message.is_tool_response()
+        && message
+            .content
+            .iter()
+            .any(|c| matches!(c, MessageContent::Text(_) | MessageContent::Attachment(_)))
+}
+
+impl<'a> Turn<'a> {
+    /// The user message the turn answers
+    pub fn prompt(&self) -> Option<&'a Message> {
+        self.messages.iter().find(|m| starts_turn(m))
+    }
+
+    /// The last thing the agent said in the turn
+    pub fn answer(&self) -> Option<&'a Message> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == Role::Assistant && !m.as_concat_text().trim().is_empty())
+    }
+
+    pub fn tool_calls(&self) -> usize {
+        self.messages
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter(|c| matches!(c, MessageContent::ToolRequest(_)))
+            .count()
+    }
+
+    /// Tokens and cost of the provider calls made during the turn. Tool responses aren't
+    /// counted again: what they add is in the input of the calls after them.
+    pub fn usage(&self) -> MessageUsage {
+        let mut total = MessageUsage::default();
+        for usage in self
+            .messages
+            .iter()
+            .filter(|m| m.role == Role::Assistant)
+            .filter_map(|m| m.metadata.usage)
+        {
+            total += usage;
+        }
+        total
+    }
+
+    pub fn summary(&self) -> TurnSummary {
+        TurnSummary {
+            turn: self.id,
+            created: self.messages.first().map(|m| m.created).unwrap_or_default(),
+            prompt: self.prompt().map(|m| m.as_concat_text()),
+            answer: self.answer().map(|m| m.as_concat_text()),
+            message_count: self.messages.len(),
+            tool_calls: self.tool_calls(),
+            usage: self.usage(),
+        }
+    }
+}
+
+/// A turn without its messages, for listing a session's turns
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnSummary {
+    pub turn: u32,
+    pub created: i64,
+    pub prompt: Option<String>,
+    pub answer: Option<String>,
+    pub message_count: usize,
+    pub tool_calls: usize,
+    pub usage: MessageUsage,
+}
+
+impl Conversation {
+    /// Each turn's number and where its messages are
+    fn turn_ranges(&self) -> Vec<(u32, Range<usize>)> {
+        let messages = self.messages();
+        let mut turns = Vec::new();
+        let mut current: Option<(u32, usize)> = None;
+        // Whether the current turn was numbered by the agent, or guessed from the prompts
+        let mut recorded = false;
+
+        for (idx, message) in messages.iter().enumerate() {
+            let last_id = current.map(|(id, _)| id);
+            let next_id = match (message.metadata.turn, last_id) {
+                (Some(turn), Some(id)) if turn == id => None,
+                (Some(turn), _) => Some((turn, true)),
+                (None, None) => Some((1, false)),
+                (None, Some(id)) if !recorded && starts_turn(message) => Some((id + 1, false)),
+                (None, Some(_)) => None,
+            };
+            if let Some((id, is_recorded)) = next_id {
+                if let Some((last_id, start)) = current {
+                    turns.push((last_id, start..idx));
+                }
+                current = Some((id, idx));
+                recorded = is_recorded;
+            }
+        }
+        if let Some((id, start)) = current {
+            turns.push((id, start..messages.len()));
+        }
+        turns
+    }
+
+    fn turn_range(&self, id: u32) -> Option<Range<usize>> {
+        self.turn_ranges()
+            .into_iter()
+            .find_map(|(turn, range)| (turn == id).then_some(range))
+    }
+
+    /// The conversation's messages grouped by turn
+    pub fn turns(&self) -> Vec<Turn<'_>> {
+        self.turn_ranges()
+            .into_iter()
+            .map(|(id, range)| Turn {
+                id,
+                messages: &self.messages()[range],
+            })
+            .collect()
+    }
+
+    pub fn turn(&self, id: u32) -> Option<Turn<'_>> {
+        let range = self.turn_range(id)?;
+        Some(Turn {
+            id,
+            messages: &self.messages()[range],
+        })
+    }
+
+    /// The number for a turn started now
+    pub fn next_turn(&self) -> u32 {
+        self.turn_ranges().last().map_or(1, |(id, _)| id + 1)
+    }
+
+    /// The conversation without the messages of a turn
+    pub fn without_turn(&self, id: u32) -> Option<Conversation> {
+        let range = self.turn_range(id)?;
+        let messages = self.messages();
+        Some(Conversation::new_unvalidated(
+            messages[..range.start]
+                .iter()
+                .chain(&messages[range.end..])
+                .cloned(),
+        ))
+    }
+
+    /// The conversation up to a turn, and the prompt that started it
+    pub fn before_turn(&self, id: u32) -> Option<(Conversation, Option<Message>)> {
+        let turn = self.turn(id)?;
+        let start = self.turn_range(id)?.start;
+        Some((
+            Conversation::new_unvalidated(self.messages()[..start].iter().cloned()),
+            turn.prompt().cloned(),
+        ))
+    }
+}
+
+/// Text of the tool responses in a message, as the model will read it
+fn tool_response_text(message: &Message) -> String {
+    let mut text = String::new();
+    for content in &message.content {
+        let MessageContent::ToolResponse(response) = content else {
+            continue;
+        };
+        match &response.tool_result {
+            Ok(result) => {
+                for item in &result.content {
+                    match item.as_text() {
+                        Some(item) => text.push_str(&item.text),
+                        None => text.push_str(&serde_json::to_string(item).unwrap_or_default()),
+                    }
+                }
+            }
+            Err(error) => text.push_str(&error.to_string()),
+        }
+    }
+    text
+}
+
+/// Record the usage of a provider call on the last assistant message it produced, and on
+/// each tool response the tokens it adds to the context, priced as input. The price of the
+/// server tool calls the provider ran is added to the call's cost when that is known.
+pub(crate) fn record_usage(
+    messages: Conversation,
+    usage: &Usage,
+    pricing: Option<&Pricing>,
+    server_tool_cost: f64,
+    counter: Option<&TokenCounter>,
+) -> Conversation {
+    let price = |usage: MessageUsage| match pricing {
+        Some(pricing) => usage.priced(pricing),
+        None => usage,
+    };
+    let mut messages: Vec<Message> = messages.into_iter().collect();
+    if let Some(message) = messages
+        .iter_mut()
+        .rev()
+        .find(|m| m.role == Role::Assistant)
+    {
+        let mut call = price(MessageUsage::from(usage));
+        call.cost = call.cost.map(|cost| cost + server_tool_cost);
+        message.metadata.usage = Some(call);
+    }
+    if let Some(counter) = counter {
+        for message in messages
+            .iter_mut()
+            .filter(|m| m.role == Role::User && m.is_tool_response())
+        {
+            let tokens = counter.count_tokens(&tool_response_text(message));
+            message.metadata.usage = Some(price(MessageUsage {
+                input_tokens: Some(tokens as i32),
+                ..Default::default()
+            }));
+        }
+    }
+    Conversation::new_unvalidated(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolRequestParams, CallToolResult};
+
+    fn tool_turn(turn: Option<u32>, prompt: &str) -> Vec<Message> {
+        let stamp = |message: Message| match turn {
+            Some(turn) => message.with_turn(turn),
+            None => message,
+        };
+        vec![
+            stamp(Message::user().with_text(prompt)),
+            stamp(
+                Message::assistant().with_tool_request("1", Ok(CallToolRequestParams::new("ls"))),
+            ),
+            stamp(Message::user().with_tool_response("1", Ok(CallToolResult::success(vec![])))),
+            stamp(
+                Message::assistant()
+                    .with_text(format!("done with {}", prompt))
+                    .with_usage(MessageUsage {
+                        input_tokens: Some(100),
+                        output_tokens: Some(10),
+                        cost: Some(0.01),
+                    }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn groups_recorded_and_unrecorded_turns() {
+        let mut messages = tool_turn(None, "first");
+        messages.extend(tool_turn(None, "second"));
+        messages.extend(tool_turn(Some(3), "third"));
+        // A message the user sent mid-turn stays in the turn it was recorded in
+        messages.insert(
+            messages.len() - 1,
+            Message::user().with_text("also").with_turn(3),
+        );
+        let conversation = Conversation::new_unvalidated(messages);
+
+        let turns = conversation.turns();
+        assert_eq!(turns.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(turns[2].messages.len(), 5);
+        let summary = turns[1].summary();
+        assert_eq!(summary.prompt.as_deref(), Some("second"));
+        assert_eq!(summary.answer.as_deref(), Some("done with second"));
+        assert_eq!(summary.tool_calls, 1);
+        assert_eq!(summary.usage.input_tokens, Some(100));
+        assert_eq!(summary.usage.cost, Some(0.01));
+        assert_eq!(conversation.next_turn(), 4);
+    }
+
+    #[test]
+    fn removes_and_rewinds_turns() {
+        let mut messages = tool_turn(Some(1), "first");
+        messages.extend(tool_turn(Some(2), "second"));
+        messages.extend(tool_turn(Some(3), "third"));
+        let conversation = Conversation::new_unvalidated(messages);
+
+        let without = conversation.without_turn(2).unwrap();
+        assert_eq!(
+            without.turns().iter().map(|t| t.id).collect::<Vec<_>>(),
+            [1, 3]
+        );
+        assert!(conversation.without_turn(7).is_none());
+
+        let (before, prompt) = conversation.before_turn(3).unwrap();
+        assert_eq!(before.len(), 8);
+        assert_eq!(prompt.unwrap().as_concat_text(), "third");
+    }
+
+    #[test]
+    fn usage_goes_on_the_last_assistant_message() {
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::assistant().with_text("thinking"),
+            Message::assistant().with_tool_request("1", Ok(CallToolRequestParams::new("ls"))),
+            Message::user().with_tool_response("1", Ok(CallToolResult::success(vec![]))),
+        ]);
+        let recorded = record_usage(
+            conversation,
+            &Usage::new(Some(5), Some(7), Some(12)),
+            None,
+            0.0,
+            None,
+        );
+        let usage: Vec<_> = recorded.iter().map(|m| m.metadata.usage).collect();
+        assert_eq!(usage[0], None);
+        assert_eq!(usage[1].unwrap().output_tokens, Some(7));
+        assert_eq!(usage[2], None);
+    }
+
+    #[tokio::test]
+    async fn tool_responses_are_counted_and_priced() {
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::assistant().with_tool_request("1", Ok(CallToolRequestParams::new("ls"))),
+            Message::user().with_tool_response(
+                "1",
+                Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                    "a.txt b.txt c.txt",
+                )])),
+            ),
+        ]);
+        let pricing = Pricing {
+            input: Some(3.0),
+            output: Some(15.0),
+            ..Default::default()
+        };
+        let counter = crate::token_counter::create_token_counter().await.unwrap();
+        let recorded = record_usage(
+            conversation,
+            &Usage::new(Some(1_000_000), Some(1_000_000), None),
+            Some(&pricing),
+            0.5,
+            Some(&counter),
+        );
+
+        let call = recorded.messages()[0].metadata.usage.unwrap();
+        assert_eq!(call.cost, Some(18.5));
+        let response = recorded.messages()[1].metadata.usage.unwrap();
+        assert!(response.input_tokens.unwrap() > 0);
+        assert!(response.output_tokens.is_none());
+        assert!(response.cost.unwrap() > 0.0);
+        // The turn is billed for the call alone
+        let turn = recorded.turns()[0].usage();
+        assert_eq!(turn.cost, Some(18.5));
+    }
+}
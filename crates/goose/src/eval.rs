@@ -0,0 +1,693 @@
+//! Evaluation harness for prompts, providers and recipes.
+//!
+//! A suite file lists cases: a prompt or recipe, fixture files to seed the workspace with,
+//! and assertions on the result. Each case runs against every provider/model target, with
+//! provider traffic recorded on the first run and replayed afterwards, so changes to the
+//! system prompt or a provider can be regression tested without live model calls.
+
+use crate::agents::{Agent, AgentConfig, AgentEvent, GoosePlatform, SessionConfig};
+use crate::config::extensions::resolve_extensions_for_new_session;
+use crate::config::permission::PermissionManager;
+use crate::config::GooseMode;
+use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::providers::base::Provider;
+use crate::providers::testprovider::TestProvider;
+use crate::recipe::Recipe;
+use crate::session::session_manager::SessionType;
+use crate::session::SessionManager;
+use anyhow::{anyhow, bail, Context, Result};
+use futures::StreamExt;
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+const DEFAULT_MIN_RUBRIC_SCORE: f32 = 7.0;
+
+const GRADER_SYSTEM_PROMPT: &str = "You grade the work of an AI agent against a rubric. \
+Reply with only a JSON object: {\"score\": <0-10>, \"reason\": \"<one sentence>\"}.";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalSuite {
+    pub name: String,
+    /// Targets to run when none are given on the command line
+    #[serde(default)]
+    pub targets: Vec<EvalTarget>,
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalSuite {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read eval suite {}", path.display()))?;
+        let suite: Self = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content)?,
+            _ => serde_yaml::from_str(&content)?,
+        };
+        let mut names = std::collections::HashSet::new();
+        for case in &suite.cases {
+            if !names.insert(&case.name) {
+                bail!("Duplicate eval case name '{}'", case.name);
+            }
+            if case.prompt.is_none() && case.recipe.is_none() {
+                bail!("Eval case '{}' needs a prompt or a recipe", case.name);
+            }
+        }
+        Ok(suite)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalTarget {
+    pub provider: String,
+    pub model: String,
+}
+
+impl EvalTarget {
+    /// Parse a `provider/model` pair. The model may itself contain slashes.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (provider, model) = spec
+            .split_once('/')
+            .filter(|(provider, model)| !provider.is_empty() && !model.is_empty())
+            .ok_or_else(|| anyhow!("Expected provider/model, got '{}'", spec))?;
+        Ok(Self {
+            provider: provider.to_string(),
+            model: model.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for EvalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.provider, self.model)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalCase {
+    pub name: String,
+    /// The user message. Defaults to the recipe's prompt.
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// A recipe whose instructions, prompt and extensions the case uses, relative to the
+    /// suite file
+    #[serde(default)]
+    pub recipe: Option<PathBuf>,
+    /// Extra system prompt instructions
+    #[serde(default)]
+    pub instructions: Option<String>,
+    /// Files to create in the workspace before the run, by relative path
+    #[serde(default)]
+    pub fixtures: BTreeMap<String, String>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    FileExists {
+        path: String,
+    },
+    FileContains {
+        path: String,
+        text: String,
+    },
+    ResponseContains {
+        text: String,
+    },
+    ToolCalled {
+        name: String,
+    },
+    /// Graded by the target model on a 0-10 scale
+    Rubric {
+        rubric: String,
+        #[serde(default = "default_min_score")]
+        min_score: f32,
+    },
+}
+
+fn default_min_score() -> f32 {
+    DEFAULT_MIN_RUBRIC_SCORE
+}
+
+impl fmt::Display for Assertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Assertion::FileExists { path } => write!(f, "{} exists", path),
+            Assertion::FileContains { path, text } => write!(f, "{} contains {:?}", path, text),
+            Assertion::ResponseContains { text } => write!(f, "response contains {:?}", text),
+            Assertion::ToolCalled { name } => write!(f, "{} was called", name),
+            Assertion::Rubric { rubric, min_score } => {
+                write!(f, "rubric scores at least {}: {}", min_score, rubric)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub case: String,
+    pub target: EvalTarget,
+    /// Whether provider responses came from a recording
+    pub replayed: bool,
+    pub assertions: Vec<AssertionResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl CaseResult {
+    /// The share of assertions that passed, or 0 when the run failed
+    pub fn score(&self) -> f64 {
+        if self.error.is_some() {
+            return 0.0;
+        }
+        if self.assertions.is_empty() {
+            return 1.0;
+        }
+        let passed = self.assertions.iter().filter(|a| a.passed).count();
+        passed as f64 / self.assertions.len() as f64
+    }
+
+    pub fn passed(&self) -> bool {
+        self.error.is_none() && self.assertions.iter().all(|a| a.passed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub suite: String,
+    pub results: Vec<CaseResult>,
+}
+
+impl EvalReport {
+    pub fn score(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(CaseResult::score).sum::<f64>() / self.results.len() as f64
+    }
+
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(CaseResult::passed)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Eval report: {}\n\nScore: {:.0}% ({} of {} runs passed)\n\n",
+            self.suite,
+            self.score() * 100.0,
+            self.results.iter().filter(|r| r.passed()).count(),
+            self.results.len()
+        );
+        out.push_str("| Case | Target | Score | Result |\n|---|---|---|---|\n");
+        for result in &self.results {
+            out.push_str(&format!(
+                "| {} | {}{} | {:.0}% | {} |\n",
+                result.case,
+                result.target,
+                if result.replayed { " (replay)" } else { "" },
+                result.score() * 100.0,
+                if result.passed() { "pass" } else { "FAIL" }
+            ));
+        }
+        for result in self.results.iter().filter(|r| !r.passed()) {
+            out.push_str(&format!("\n## {} on {}\n\n", result.case, result.target));
+            if let Some(error) = &result.error {
+                out.push_str(&format!("- error: {}\n", error));
+            }
+            for assertion in result.assertions.iter().filter(|a| !a.passed) {
+                out.push_str(&format!("- failed: {}", assertion.assertion));
+                if let Some(detail) = &assertion.detail {
+                    out.push_str(&format!(" ({})", detail));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EvalOptions {
+    /// Where provider traffic is recorded, as `<provider>/<model>/<case>.json`. Without
+    /// it every run calls the providers live.
+    pub recordings_dir: Option<PathBuf>,
+    /// Fail cases without a recording instead of recording them
+    pub replay_only: bool,
+    /// Parent of the per-run workspaces. Paths must be stable across runs for
+    /// recordings to replay.
+    pub workspace_root: PathBuf,
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self {
+            recordings_dir: None,
+            replay_only: false,
+            workspace_root: std::env::temp_dir().join("goose-eval"),
+        }
+    }
+}
+
+/// Run every case of a suite against every target, one after another
+pub async fn run_suite(
+    suite: &EvalSuite,
+    suite_dir: &Path,
+    targets: &[EvalTarget],
+    options: &EvalOptions,
+) -> EvalReport {
+    let mut results = Vec::new();
+    for target in targets {
+        for case in &suite.cases {
+            let start = Instant::now();
+            let workspace = options
+                .workspace_root
+                .join(file_name(&suite.name))
+                .join(file_name(&target.to_string()))
+                .join(file_name(&case.name));
+            let mut result = match run_case(case, target, suite_dir, &workspace, options).await {
+                Ok(result) => result,
+                Err(e) => CaseResult {
+                    case: case.name.clone(),
+                    target: target.clone(),
+                    replayed: false,
+                    assertions: Vec::new(),
+                    error: Some(format!("{:#}", e)),
+                    duration_ms: 0,
+                },
+            };
+            result.duration_ms = start.elapsed().as_millis() as u64;
+            results.push(result);
+        }
+    }
+    EvalReport {
+        suite: suite.name.clone(),
+        results,
+    }
+}
+
+struct CaseRun {
+    response: String,
+    tools_called: Vec<String>,
+    transcript: String,
+}
+
+async fn run_case(
+    case: &EvalCase,
+    target: &EvalTarget,
+    suite_dir: &Path,
+    workspace: &Path,
+    options: &EvalOptions,
+) -> Result<CaseResult> {
+    let recipe = case
+        .recipe
+        .as_ref()
+        .map(|path| Recipe::from_file_path(&suite_dir.join(path)))
+        .transpose()?;
+    let prompt = case
+        .prompt
+        .clone()
+        .or_else(|| recipe.as_ref().and_then(|r| r.prompt.clone()))
+        .ok_or_else(|| anyhow!("Case '{}' has no prompt", case.name))?;
+
+    if workspace.exists() {
+        std::fs::remove_dir_all(workspace)?;
+    }
+    std::fs::create_dir_all(workspace)?;
+    for (path, content) in &case.fixtures {
+        let path = workspace_path(workspace, path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+    }
+
+    let extensions = resolve_extensions_for_new_session(
+        recipe.as_ref().and_then(|r| r.extensions.as_deref()),
+        None,
+    );
+    let recording = options.recordings_dir.as_ref().map(|dir| {
+        dir.join(file_name(&target.provider))
+            .join(file_name(&target.model))
+            .join(format!("{}.json", file_name(&case.name)))
+    });
+    let replayed = recording.as_ref().is_some_and(|path| path.exists());
+    let (provider, recorder): (Arc<dyn Provider>, Option<Arc<TestProvider>>) = match &recording {
+        Some(path) if replayed => (
+            Arc::new(TestProvider::new_replaying(path.to_string_lossy())?),
+            None,
+        ),
+        _ if options.replay_only => bail!("No recording for this case"),
+        recording => {
+            let model_config =
+                ModelConfig::new(&target.model)?.with_canonical_limits(&target.provider);
+            let inner =
+                crate::providers::create(&target.provider, model_config, extensions.clone())
+                    .await?;
+            match recording {
+                Some(path) => {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    let recorder =
+                        Arc::new(TestProvider::new_recording(inner, path.to_string_lossy()));
+                    (recorder.clone(), Some(recorder))
+                }
+                None => (inner, None),
+            }
+        }
+    };
+
+    let data_dir = tempfile::tempdir()?;
+    let agent = Agent::with_config(AgentConfig::new(
+        Arc::new(SessionManager::new(data_dir.path().to_path_buf())),
+        Arc::new(PermissionManager::new(data_dir.path().to_path_buf())),
+        None,
+        GooseMode::Auto,
+        true,
+        GoosePlatform::GooseCli,
+    ));
+    let session = agent
+        .config
+        .session_manager
+        .create_session(
+            workspace.to_path_buf(),
+            format!("eval: {}", case.name),
+            SessionType::Hidden,
+        )
+        .await?;
+    for extension in &extensions {
+        agent.add_extension(extension.clone(), &session.id).await?;
+    }
+    let instructions = [
+        recipe.as_ref().and_then(|r| r.instructions.clone()),
+        case.instructions.clone(),
+    ];
+    for (i, instructions) in instructions.into_iter().flatten().enumerate() {
+        agent
+            .extend_system_prompt(format!("eval_{}", i), instructions)
+            .await;
+    }
+    agent.update_provider(provider.clone(), &session.id).await?;
+
+    let run = drive(&agent, &session.id, &prompt, case.max_turns).await;
+    if let Some(recorder) = &recorder {
+        recorder.save_records()?;
+    }
+    let run = run?;
+
+    let mut assertions = Vec::new();
+    for assertion in &case.assertions {
+        let (passed, detail) = check(assertion, workspace, &run, provider.as_ref(), &session.id)
+            .await
+            .unwrap_or_else(|e| (false, Some(format!("{:#}", e))));
+        assertions.push(AssertionResult {
+            assertion: assertion.to_string(),
+            passed,
+            detail,
+        });
+    }
+    if let Some(recorder) = &recorder {
+        // Rubric grading goes through the provider too, so it replays with the run
+        recorder.save_records()?;
+    }
+
+    Ok(CaseResult {
+        case: case.name.clone(),
+        target: target.clone(),
+        replayed,
+        assertions,
+        error: None,
+        duration_ms: 0,
+    })
+}
+
+async fn drive(
+    agent: &Agent,
+    session_id: &str,
+    prompt: &str,
+    max_turns: Option<u32>,
+) -> Result<CaseRun> {
+    let session_config = SessionConfig {
+        id: session_id.to_string(),
+        schedule_id: None,
+        max_turns,
+        retry_config: None,
+    };
+    let mut stream = agent
+        .reply(Message::user().with_text(prompt), session_config, None)
+        .await?;
+
+    let mut run = CaseRun {
+        response: String::new(),
+        tools_called: Vec::new(),
+        transcript: format!("User: {}\n", prompt),
+    };
+    while let Some(event) = stream.next().await {
+        let AgentEvent::Message(message) = event? else {
+            continue;
+        };
+        for content in &message.content {
+            if let MessageContent::ToolRequest(request) = content {
+                if let Ok(call) = &request.tool_call {
+                    run.tools_called.push(call.name.to_string());
+                    run.transcript
+                        .push_str(&format!("Tool call: {}\n", call.name));
+                }
+            }
+        }
+        let text = message.as_concat_text();
+        if message.role == Role::Assistant && !text.trim().is_empty() {
+            run.transcript.push_str(&format!("Assistant: {}\n", text));
+            run.response = text;
+        }
+    }
+    Ok(run)
+}
+
+async fn check(
+    assertion: &Assertion,
+    workspace: &Path,
+    run: &CaseRun,
+    grader: &dyn Provider,
+    session_id: &str,
+) -> Result<(bool, Option<String>)> {
+    Ok(match assertion {
+        Assertion::FileExists { path } => (workspace_path(workspace, path)?.exists(), None),
+        Assertion::FileContains { path, text } => {
+            match std::fs::read_to_string(workspace_path(workspace, path)?) {
+                Ok(content) => (content.contains(text.as_str()), None),
+                Err(e) => (false, Some(e.to_string())),
+            }
+        }
+        Assertion::ResponseContains { text } => (run.response.contains(text.as_str()), None),
+        Assertion::ToolCalled { name } => (
+            run.tools_called
+                .iter()
+                .any(|called| called == name || called.ends_with(&format!("__{}", name))),
+            Some(format!("called: {}", run.tools_called.join(", "))),
+        ),
+        Assertion::Rubric { rubric, min_score } => {
+            let (score, reason) = grade(grader, session_id, rubric, &run.transcript).await?;
+            (
+                score >= *min_score,
+                Some(format!("scored {}: {}", score, reason)),
+            )
+        }
+    })
+}
+
+async fn grade(
+    grader: &dyn Provider,
+    session_id: &str,
+    rubric: &str,
+    transcript: &str,
+) -> Result<(f32, String)> {
+    let request = Message::user().with_text(format!(
+        "Rubric:\n{}\n\nTranscript:\n{}",
+        rubric, transcript
+    ));
+    let (reply, _) = grader
+        .complete(
+            &grader.get_model_config(),
+            session_id,
+            GRADER_SYSTEM_PROMPT,
+            &[request],
+            &[],
+        )
+        .await?;
+    parse_grade(&reply.as_concat_text())
+}
+
+fn parse_grade(reply: &str) -> Result<(f32, String)> {
+    #[derive(Deserialize)]
+    struct Grade {
+        score: f32,
+        #[serde(default)]
+        reason: String,
+    }
+
+    let json = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| &reply[start..=end])
+        .ok_or_else(|| anyhow!("Grader did not reply with JSON: {}", reply))?;
+    let grade: Grade = serde_json::from_str(json)?;
+    Ok((grade.score, grade.reason))
+}
+
+/// Resolve a fixture or assertion path, which must stay inside the workspace
+fn workspace_path(workspace: &Path, relative: &str) -> Result<PathBuf> {
+    let path = Path::new(relative);
+    if path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        bail!(
+            "Path '{}' must be relative and stay inside the workspace",
+            relative
+        );
+    }
+    Ok(workspace.join(path))
+}
+
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_suites_and_targets() {
+        let suite: EvalSuite = serde_yaml::from_str(
+            r##"
+name: smoke
+targets:
+  - provider: openai
+    model: gpt-4o
+cases:
+  - name: hello file
+    prompt: Create hello.txt containing hi
+    fixtures:
+      README.md: "# demo"
+    assertions:
+      - type: file_contains
+        path: hello.txt
+        text: hi
+      - type: rubric
+        rubric: The agent explains what it did
+"##,
+        )
+        .unwrap();
+        assert_eq!(suite.cases[0].fixtures["README.md"], "# demo");
+        assert!(matches!(
+            suite.cases[0].assertions[1],
+            Assertion::Rubric { min_score, .. } if min_score == DEFAULT_MIN_RUBRIC_SCORE
+        ));
+
+        let target = EvalTarget::parse("openrouter/anthropic/claude-sonnet-4").unwrap();
+        assert_eq!(target.provider, "openrouter");
+        assert_eq!(target.model, "anthropic/claude-sonnet-4");
+        assert!(EvalTarget::parse("openai").is_err());
+    }
+
+    #[tokio::test]
+    async fn checks_assertions_and_scores_reports() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(workspace.path().join("hello.txt"), "hi there").unwrap();
+        let run = CaseRun {
+            response: "Created hello.txt".to_string(),
+            tools_called: vec!["developer__write".to_string()],
+            transcript: String::new(),
+        };
+        let grader = TestProvider::new_replaying("/nonexistent/recording.json").unwrap();
+
+        let passing = [
+            Assertion::FileContains {
+                path: "hello.txt".into(),
+                text: "hi".into(),
+            },
+            Assertion::ToolCalled {
+                name: "write".into(),
+            },
+            Assertion::ResponseContains {
+                text: "hello.txt".into(),
+            },
+        ];
+        for assertion in &passing {
+            let (passed, _) = check(assertion, workspace.path(), &run, &grader, "s")
+                .await
+                .unwrap();
+            assert!(passed, "{}", assertion);
+        }
+        let escaping = Assertion::FileExists {
+            path: "../etc/passwd".into(),
+        };
+        assert!(check(&escaping, workspace.path(), &run, &grader, "s")
+            .await
+            .is_err());
+
+        let result = |passed: bool| CaseResult {
+            case: "c".into(),
+            target: EvalTarget::parse("openai/gpt-4o").unwrap(),
+            replayed: true,
+            assertions: vec![
+                AssertionResult {
+                    assertion: "a".into(),
+                    passed: true,
+                    detail: None,
+                },
+                AssertionResult {
+                    assertion: "b".into(),
+                    passed,
+                    detail: None,
+                },
+            ],
+            error: None,
+            duration_ms: 0,
+        };
+        let report = EvalReport {
+            suite: "smoke".into(),
+            results: vec![result(true), result(false)],
+        };
+        assert_eq!(report.score(), 0.75);
+        assert!(!report.passed());
+        assert!(report.to_markdown().contains("- failed: b"));
+    }
+
+    #[test]
+    fn parses_grader_replies() {
+        let (score, reason) =
+            parse_grade("Sure. {\"score\": 8, \"reason\": \"tidy\"} Done.").unwrap();
+        assert_eq!(score, 8.0);
+        assert_eq!(reason, "tidy");
+        assert!(parse_grade("eight out of ten").is_err());
+    }
+}
@@ -2,9 +2,12 @@ use crate::agents::{Agent, AgentConfig, GoosePlatform};
 use crate::config::paths::Paths;
 use crate::config::permission::PermissionManager;
 use crate::config::{Config, GooseMode};
+use crate::context_mgmt::workspace_changes;
+use crate::hooks::config_change;
 use crate::scheduler::Scheduler;
 use crate::scheduler_trait::SchedulerTrait;
 use crate::session::SessionManager;
+use crate::shutdown::ShutdownCoordinator;
 use anyhow::Result;
 use lru::LruCache;
 use std::num::NonZeroUsize;
@@ -139,6 +142,16 @@ impl AgentManager {
         Ok(())
     }
 
+    /// Remove the session's agent and what goose keeps about the session while it's open.
+    /// Unlike [`Self::remove_session`], the session isn't expected to come back.
+    pub async fn end_session(&self, session_id: &str) -> Result<()> {
+        self.remove_session(session_id).await?;
+        workspace_changes::forget(session_id);
+        config_change::forget(session_id);
+        ShutdownCoordinator::global().untrack_session(session_id);
+        Ok(())
+    }
+
     pub async fn has_session(&self, session_id: &str) -> bool {
         self.sessions.read().await.contains(session_id)
     }
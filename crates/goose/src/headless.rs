@@ -0,0 +1,450 @@
+//! Non-interactive execution of a single task.
+//!
+//! `run_task` runs one prompt to completion with no user in the loop and returns a
+//! [`TaskReport`] that serializes to JSON, for CI pipelines and scripts that need to act on
+//! the outcome rather than read a transcript.
+
+use crate::agents::extension::ExtensionConfig;
+use crate::agents::{Agent, AgentConfig, AgentEvent, GoosePlatform, SessionConfig};
+use crate::config::extensions::resolve_extensions_for_new_session;
+use crate::config::permission::PermissionManager;
+use crate::config::{Config, GooseMode};
+use crate::conversation::message::{Message, MessageContent};
+use crate::hooks::{HookEvent, HookRuntime};
+use crate::i18n;
+use crate::model::ModelConfig;
+use crate::notifications::{self, Notification, NotificationKind};
+use crate::session::session_manager::SessionType;
+use crate::session::SessionManager;
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone)]
+pub struct TaskOptions {
+    pub working_dir: PathBuf,
+    /// Provider and model to use instead of the configured GOOSE_PROVIDER and GOOSE_MODEL
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Extensions to load instead of the ones enabled in the config
+    pub extensions: Option<Vec<ExtensionConfig>>,
+    /// Extra instructions appended to the system prompt
+    pub instructions: Option<String>,
+    pub max_turns: Option<u32>,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for TaskOptions {
+    fn default() -> Self {
+        Self {
+            working_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            provider: None,
+            model: None,
+            extensions: None,
+            instructions: None,
+            max_turns: None,
+            timeout: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Completed,
+    Failed,
+    TimedOut,
+}
+
+impl TaskStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskStatus::Completed => "completed",
+            TaskStatus::Failed => "failed",
+            TaskStatus::TimedOut => "timed_out",
+        }
+    }
+
+    /// Process exit code for the status, following the conventions of `timeout(1)`
+    pub fn exit_code(self) -> i32 {
+        match self {
+            TaskStatus::Completed => 0,
+            TaskStatus::Failed => 1,
+            TaskStatus::TimedOut => 124,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandRun {
+    pub command: String,
+    /// None when the command never returned a result, e.g. because the task timed out
+    pub succeeded: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaskUsage {
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskReport {
+    pub session_id: String,
+    pub status: TaskStatus,
+    /// The last text the assistant produced
+    pub final_message: Option<String>,
+    /// Files written or edited through the developer tools, relative to the working directory
+    pub files_changed: Vec<String>,
+    /// Shell commands in the order they were requested
+    pub commands_run: Vec<CommandRun>,
+    pub usage: TaskUsage,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why a TaskCompleted hook flagged the task for follow-up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up: Option<String>,
+}
+
+impl TaskReport {
+    pub fn exit_code(&self) -> i32 {
+        self.status.exit_code()
+    }
+}
+
+/// Run a single task without user interaction. Errors setting up the provider or session are
+/// returned as `Err`; failures while the task runs are reported in the `TaskReport`.
+pub async fn run_task(prompt: &str, options: TaskOptions) -> Result<TaskReport> {
+    let started = Instant::now();
+    let config = Config::global();
+    let provider_name = match options.provider {
+        Some(provider) => provider,
+        None => config
+            .get_goose_provider()
+            .context("No provider configured. Run 'goose configure' first")?,
+    };
+    let model_name = match options.model {
+        Some(model) => model,
+        None => config
+            .get_goose_model()
+            .context("No model configured. Run 'goose configure' first")?,
+    };
+    let extensions = options
+        .extensions
+        .unwrap_or_else(|| resolve_extensions_for_new_session(None, None));
+    let model_config = ModelConfig::new(&model_name)?.with_canonical_limits(&provider_name);
+    let provider =
+        crate::providers::create(&provider_name, model_config, extensions.clone()).await?;
+
+    let agent = Agent::with_config(AgentConfig::new(
+        Arc::new(SessionManager::instance()),
+        PermissionManager::instance(),
+        None,
+        GooseMode::Auto,
+        true,
+        GoosePlatform::GooseCli,
+    ));
+    let session = agent
+        .config
+        .session_manager
+        .create_session(
+            options.working_dir.clone(),
+            "Headless task".to_string(),
+            SessionType::Hidden,
+        )
+        .await?;
+    for extension in extensions {
+        agent.add_extension(extension, &session.id).await?;
+    }
+    if let Some(instructions) = options.instructions {
+        agent
+            .extend_system_prompt("headless_task".to_string(), instructions)
+            .await;
+    }
+    agent.update_provider(provider, &session.id).await?;
+
+    let cancel_token = CancellationToken::new();
+    let mut trace = TaskTrace::new(&options.working_dir);
+    let run = drive(
+        &agent,
+        &session.id,
+        prompt,
+        options.max_turns,
+        cancel_token.clone(),
+        &mut trace,
+    );
+    let (status, error) = match options.timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, run).await {
+            Ok(result) => outcome(result),
+            Err(_) => {
+                cancel_token.cancel();
+                (
+                    TaskStatus::TimedOut,
+                    Some(format!("Timed out after {}s", timeout.as_secs())),
+                )
+            }
+        },
+        None => outcome(run.await),
+    };
+
+    let usage = agent
+        .config
+        .session_manager
+        .get_session(&session.id, false)
+        .await
+        .map(|session| TaskUsage {
+            input_tokens: session.accumulated_input_tokens,
+            output_tokens: session.accumulated_output_tokens,
+            total_tokens: session.accumulated_total_tokens,
+        })
+        .unwrap_or_default();
+    let files_changed: Vec<String> = trace.files_changed.into_iter().collect();
+
+    notifications::notify(Notification::new(
+        NotificationKind::TaskFinished,
+        session.id.clone(),
+        Some(SessionType::Hidden),
+        i18n::text_with(
+            "notify.task",
+            &[("task_type", &"headless"), ("status", &status.as_str())],
+        ),
+    ));
+
+    let follow_up = HookRuntime::load(&options.working_dir)
+        .emit(
+            HookEvent::TaskCompleted {
+                session_id: session.id.clone(),
+                task_id: session.id.clone(),
+                task_type: "headless".to_string(),
+                status: status.as_str().to_string(),
+                output: trace.final_message.clone().unwrap_or_default(),
+                error: error.clone(),
+                files_changed: files_changed.clone(),
+                usage: usage.clone(),
+                cwd: options.working_dir.clone(),
+            },
+            &options.working_dir,
+            CancellationToken::new(),
+        )
+        .await
+        .follow_up();
+
+    Ok(TaskReport {
+        session_id: session.id,
+        status,
+        final_message: trace.final_message,
+        files_changed,
+        commands_run: trace.commands_run,
+        usage,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error,
+        follow_up,
+    })
+}
+
+/// Files written or edited through the developer tools in `messages`, relative to
+/// `working_dir`
+pub(crate) fn files_changed<'a>(
+    messages: impl IntoIterator<Item = &'a Message>,
+    working_dir: &Path,
+) -> Vec<String> {
+    let mut trace = TaskTrace::new(working_dir);
+    for message in messages {
+        trace.observe(message);
+    }
+    trace.files_changed.into_iter().collect()
+}
+
+fn outcome(result: Result<()>) -> (TaskStatus, Option<String>) {
+    match result {
+        Ok(()) => (TaskStatus::Completed, None),
+        Err(e) => (TaskStatus::Failed, Some(format!("{:#}", e))),
+    }
+}
+
+async fn drive(
+    agent: &Agent,
+    session_id: &str,
+    prompt: &str,
+    max_turns: Option<u32>,
+    cancel_token: CancellationToken,
+    trace: &mut TaskTrace,
+) -> Result<()> {
+    let session_config = SessionConfig {
+        id: session_id.to_string(),
+        schedule_id: None,
+        max_turns,
+        retry_config: None,
+    };
+    let mut stream = agent
+        .reply(
+            Message::user().with_text(prompt),
+            session_config,
+            Some(cancel_token),
+        )
+        .await?;
+    while let Some(event) = stream.next().await {
+        if let AgentEvent::Message(message) = event? {
+            trace.observe(&message);
+        }
+    }
+    Ok(())
+}
+
+/// What the task did, collected from the messages the agent streams
+struct TaskTrace {
+    working_dir: PathBuf,
+    final_message: Option<String>,
+    files_changed: BTreeSet<String>,
+    commands_run: Vec<CommandRun>,
+    pending_commands: HashMap<String, usize>,
+}
+
+impl TaskTrace {
+    fn new(working_dir: &Path) -> Self {
+        Self {
+            working_dir: working_dir.to_path_buf(),
+            final_message: None,
+            files_changed: BTreeSet::new(),
+            commands_run: Vec::new(),
+            pending_commands: HashMap::new(),
+        }
+    }
+
+    fn observe(&mut self, message: &Message) {
+        for content in &message.content {
+            match content {
+                MessageContent::ToolRequest(request) => {
+                    let Ok(call) = &request.tool_call else {
+                        continue;
+                    };
+                    let argument = |name: &str| {
+                        call.arguments
+                            .as_ref()
+                            .and_then(|args| args.get(name))
+                            .and_then(|value| value.as_str())
+                    };
+                    match call.name.as_ref() {
+                        "write" | "edit" => {
+                            if let Some(path) = argument("path") {
+                                self.files_changed.insert(self.relative(path));
+                            }
+                        }
+                        "shell" => {
+                            if let Some(command) = argument("command") {
+                                self.pending_commands
+                                    .insert(request.id.clone(), self.commands_run.len());
+                                self.commands_run.push(CommandRun {
+                                    command: command.to_string(),
+                                    succeeded: None,
+                                });
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                MessageContent::ToolResponse(response) => {
+                    if let Some(index) = self.pending_commands.remove(&response.id) {
+                        self.commands_run[index].succeeded = Some(matches!(
+                            &response.tool_result,
+                            Ok(result) if result.is_error != Some(true)
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+        let text = message.as_concat_text();
+        if message.role == Role::Assistant && !text.trim().is_empty() {
+            self.final_message = Some(text);
+        }
+    }
+
+    fn relative(&self, path: &str) -> String {
+        Path::new(path)
+            .strip_prefix(&self.working_dir)
+            .map(|relative| relative.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolRequestParams, CallToolResult, Content};
+    use serde_json::json;
+
+    fn call(name: &str, arguments: serde_json::Value) -> CallToolRequestParams {
+        CallToolRequestParams::new(name.to_string())
+            .with_arguments(arguments.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn trace_collects_files_commands_and_final_message() {
+        let mut trace = TaskTrace::new(Path::new("/work"));
+        trace.observe(
+            &Message::assistant()
+                .with_text("Fixing the test")
+                .with_tool_request(
+                    "1",
+                    Ok(call(
+                        "write",
+                        json!({"path": "/work/src/lib.rs", "content": ""}),
+                    )),
+                )
+                .with_tool_request("2", Ok(call("shell", json!({"command": "cargo test"}))))
+                .with_tool_request("3", Ok(call("shell", json!({"command": "false"})))),
+        );
+        trace.observe(
+            &Message::user()
+                .with_tool_response("2", Ok(CallToolResult::success(vec![Content::text("ok")])))
+                .with_tool_response("3", Ok(CallToolResult::error(vec![Content::text("1")]))),
+        );
+        trace.observe(&Message::assistant().with_text("Done"));
+
+        assert_eq!(trace.final_message.as_deref(), Some("Done"));
+        assert_eq!(
+            trace.files_changed.into_iter().collect::<Vec<_>>(),
+            vec!["src/lib.rs"]
+        );
+        assert_eq!(
+            trace.commands_run,
+            vec![
+                CommandRun {
+                    command: "cargo test".to_string(),
+                    succeeded: Some(true),
+                },
+                CommandRun {
+                    command: "false".to_string(),
+                    succeeded: Some(false),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn report_serializes_status_and_exit_code() {
+        let report = TaskReport {
+            session_id: "20260101_1".to_string(),
+            status: TaskStatus::TimedOut,
+            final_message: None,
+            files_changed: Vec::new(),
+            commands_run: Vec::new(),
+            usage: TaskUsage::default(),
+            duration_ms: 10,
+            error: Some("Timed out after 1s".to_string()),
+            follow_up: None,
+        };
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["status"], "timed_out");
+        assert_eq!(report.exit_code(), 124);
+    }
+}
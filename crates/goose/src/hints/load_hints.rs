@@ -5,10 +5,13 @@ use std::{
 };
 
 use crate::config::paths::Paths;
+use crate::config::Config;
 use crate::hints::import_files::read_referenced_files;
 
 pub const GOOSE_HINTS_FILENAME: &str = ".goosehints";
 pub const AGENTS_MD_FILENAME: &str = "AGENTS.md";
+/// Default upper bound on the hints added to the system prompt
+pub const DEFAULT_HINTS_MAX_BYTES: usize = 64 * 1024;
 
 fn find_git_root(start_dir: &Path) -> Option<&Path> {
     let mut check_dir = start_dir;
@@ -51,74 +54,195 @@ fn get_local_directories(git_root: Option<&Path>, cwd: &Path) -> Vec<PathBuf> {
     }
 }
 
-pub fn load_hint_files(
+/// Where a hint file was found, from least to most specific
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLayer {
+    /// The user's config directory
+    Global,
+    /// The repository root, or the working directory outside a repository
+    ProjectRoot,
+    /// A directory between the repository root and the working directory
+    Subdirectory,
+}
+
+/// A discovered instruction file with its includes expanded
+#[derive(Debug, Clone)]
+pub struct HintFile {
+    pub path: PathBuf,
+    pub layer: HintLayer,
+    pub content: String,
+}
+
+fn read_hint_file(
+    path: PathBuf,
+    layer: HintLayer,
+    import_boundary: &Path,
+    ignore_patterns: &Gitignore,
+) -> Option<HintFile> {
+    if !path.is_file() {
+        return None;
+    }
+    let mut visited = HashSet::new();
+    let content = read_referenced_files(&path, import_boundary, &mut visited, 0, ignore_patterns);
+    (!content.is_empty()).then_some(HintFile {
+        path,
+        layer,
+        content,
+    })
+}
+
+/// Find instruction files from the global config directory down through the repository
+/// root to the working directory, in that order
+pub fn discover_hint_files(
     cwd: &Path,
     hints_filenames: &[String],
     ignore_patterns: &Gitignore,
-) -> String {
-    let mut global_hints_contents = Vec::with_capacity(hints_filenames.len());
-    let mut local_hints_contents = Vec::with_capacity(hints_filenames.len());
-
+) -> Vec<HintFile> {
+    let mut files = Vec::new();
     for hints_filename in hints_filenames {
         let global_hints_path = Paths::in_config_dir(hints_filename);
-        if global_hints_path.is_file() {
-            let mut visited = HashSet::new();
-            let hints_dir = global_hints_path.parent().unwrap();
-            let expanded_content = read_referenced_files(
-                &global_hints_path,
-                hints_dir,
-                &mut visited,
-                0,
-                ignore_patterns,
-            );
-            if !expanded_content.is_empty() {
-                global_hints_contents.push(expanded_content);
-            }
-        }
+        let hints_dir = global_hints_path.parent().unwrap_or(cwd).to_path_buf();
+        files.extend(read_hint_file(
+            global_hints_path,
+            HintLayer::Global,
+            &hints_dir,
+            ignore_patterns,
+        ));
     }
+
     let git_root = find_git_root(cwd);
     let local_directories = get_local_directories(git_root, cwd);
-
     let import_boundary = git_root.unwrap_or(cwd);
 
-    for directory in &local_directories {
+    for (index, directory) in local_directories.iter().enumerate() {
+        let layer = if index == 0 {
+            HintLayer::ProjectRoot
+        } else {
+            HintLayer::Subdirectory
+        };
         for hints_filename in hints_filenames {
-            let hints_path = directory.join(hints_filename);
-            if hints_path.is_file() {
-                let mut visited = HashSet::new();
-                let expanded_content = read_referenced_files(
-                    &hints_path,
-                    import_boundary,
-                    &mut visited,
-                    0,
-                    ignore_patterns,
-                );
-                if !expanded_content.is_empty() {
-                    local_hints_contents.push(expanded_content);
-                }
-            }
+            files.extend(read_hint_file(
+                directory.join(hints_filename),
+                layer,
+                import_boundary,
+                ignore_patterns,
+            ));
         }
     }
+    files
+}
+
+/// Trim hint files to fit in `max_bytes`. More specific files are kept first, so a
+/// large global file is cut before the instructions for the directory being worked in.
+pub fn apply_hints_budget(files: &mut Vec<HintFile>, max_bytes: usize) {
+    let mut remaining = max_bytes;
+    let mut dropped = Vec::new();
+    for (index, file) in files.iter_mut().enumerate().rev() {
+        if file.content.len() <= remaining {
+            remaining -= file.content.len();
+            continue;
+        }
+        if remaining == 0 {
+            dropped.push(index);
+            continue;
+        }
+        let mut cut = remaining;
+        while !file.content.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        tracing::warn!(
+            "Truncating {} to {} of {} bytes to fit the hints budget",
+            file.path.display(),
+            cut,
+            file.content.len()
+        );
+        let omitted = file.content.len() - cut;
+        file.content.truncate(cut);
+        file.content.push_str(&format!(
+            "\n[... {} bytes omitted to fit the hints budget]",
+            omitted
+        ));
+        remaining = 0;
+    }
+    for index in dropped {
+        tracing::warn!(
+            "Skipping {}: the hints budget of {} bytes is used up",
+            files[index].path.display(),
+            max_bytes
+        );
+        files.remove(index);
+    }
+}
+
+pub fn render_hints(files: &[HintFile]) -> String {
+    let (global, local): (Vec<&HintFile>, Vec<&HintFile>) = files
+        .iter()
+        .partition(|file| file.layer == HintLayer::Global);
+    let join = |files: Vec<&HintFile>| {
+        files
+            .into_iter()
+            .map(|file| file.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
 
     let mut hints = String::new();
-    if !global_hints_contents.is_empty() {
+    if !global.is_empty() {
         hints.push_str("\n### Global Hints\nThese are my global goose hints.\n");
-        hints.push_str(&global_hints_contents.join("\n"));
+        hints.push_str(&join(global));
     }
 
-    if !local_hints_contents.is_empty() {
+    if !local.is_empty() {
         if !hints.is_empty() {
             hints.push_str("\n\n");
         }
         hints.push_str(
             "### Project Hints\nThese are hints for working on the project in this directory.\n",
         );
-        hints.push_str(&local_hints_contents.join("\n"));
+        hints.push_str(&join(local));
     }
 
     hints
 }
 
+pub fn load_hint_files(
+    cwd: &Path,
+    hints_filenames: &[String],
+    ignore_patterns: &Gitignore,
+) -> String {
+    let mut files = discover_hint_files(cwd, hints_filenames, ignore_patterns);
+    apply_hints_budget(&mut files, DEFAULT_HINTS_MAX_BYTES);
+    render_hints(&files)
+}
+
+/// The hint files for a working directory, using the configured file names and budget
+pub fn configured_hint_files(working_dir: &Path) -> Vec<HintFile> {
+    let config = Config::global();
+    let hints_filenames = config
+        .get_param::<Vec<String>>("CONTEXT_FILE_NAMES")
+        .unwrap_or_else(|_| {
+            vec![
+                GOOSE_HINTS_FILENAME.to_string(),
+                AGENTS_MD_FILENAME.to_string(),
+            ]
+        });
+    let max_bytes = config
+        .get_param::<usize>("GOOSE_HINTS_MAX_BYTES")
+        .unwrap_or(DEFAULT_HINTS_MAX_BYTES);
+    let ignore_patterns = {
+        let builder = ignore::gitignore::GitignoreBuilder::new(working_dir);
+        builder.build().unwrap_or_else(|_| {
+            ignore::gitignore::GitignoreBuilder::new(working_dir)
+                .build()
+                .expect("Failed to build default gitignore")
+        })
+    };
+
+    let mut files = discover_hint_files(working_dir, &hints_filenames, &ignore_patterns);
+    apply_hints_budget(&mut files, max_bytes);
+    files
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -466,4 +590,51 @@ End of hints"#;
         assert!(hints.contains("Root file content"));
         assert!(hints.contains("--- Content from ../root_file.md ---"));
     }
+
+    #[test]
+    fn test_discovers_layers_from_root_to_subdirectory() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path();
+        fs::create_dir(project_root.join(".git")).unwrap();
+        fs::write(project_root.join(AGENTS_MD_FILENAME), "Root rules").unwrap();
+        let subdir = project_root.join("crates").join("api");
+        fs::create_dir_all(&subdir).unwrap();
+        fs::write(subdir.join(AGENTS_MD_FILENAME), "API rules").unwrap();
+        let gitignore = create_dummy_gitignore();
+
+        let files = discover_hint_files(&subdir, &[AGENTS_MD_FILENAME.to_string()], &gitignore);
+        let local: Vec<(HintLayer, &str)> = files
+            .iter()
+            .filter(|file| file.layer != HintLayer::Global)
+            .map(|file| (file.layer, file.content.as_str()))
+            .collect();
+        assert_eq!(
+            local,
+            vec![
+                (HintLayer::ProjectRoot, "Root rules"),
+                (HintLayer::Subdirectory, "API rules")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_budget_keeps_most_specific_hints() {
+        let file = |layer, content: &str| HintFile {
+            path: PathBuf::from(format!("{:?}.md", layer)),
+            layer,
+            content: content.to_string(),
+        };
+        let mut files = vec![
+            file(HintLayer::Global, "global instructions"),
+            file(HintLayer::ProjectRoot, "root instructions"),
+            file(HintLayer::Subdirectory, "subdirectory"),
+        ];
+
+        apply_hints_budget(&mut files, 20);
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].content, "subdirectory");
+        assert!(files[0].content.starts_with("root ins"));
+        assert!(files[0].content.contains("bytes omitted"));
+    }
 }
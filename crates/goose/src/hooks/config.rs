@@ -1,7 +1,9 @@
+use super::notify::NotifyMethod;
+use super::templates;
 use anyhow::{Context, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Merged hook settings from global + project config.
 #[derive(Debug, Clone, Default)]
@@ -18,11 +20,35 @@ impl<'de> serde::Deserialize<'de> for HooksConfig {
             hooks: HashMap<String, Vec<HookEventConfig>>,
             #[serde(default)]
             allow_project_hooks: bool,
+            #[serde(default, rename = "use")]
+            templates: Templates,
+        }
+
+        #[derive(Default, Deserialize)]
+        #[serde(untagged)]
+        enum Templates {
+            #[default]
+            None,
+            One(String),
+            Many(Vec<String>),
         }
 
         let raw = Raw::deserialize(deserializer)?;
+        let templates = match raw.templates {
+            Templates::None => Vec::new(),
+            Templates::One(name) => vec![name],
+            Templates::Many(names) => names,
+        };
+        let mut hooks = raw.hooks;
+        for (event, configs) in templates
+            .iter()
+            .filter_map(|name| templates::expand(name))
+            .flatten()
+        {
+            hooks.entry(event).or_default().extend(configs);
+        }
         Ok(Self {
-            hooks: raw.hooks,
+            hooks,
             allow_project_hooks: raw.allow_project_hooks,
         })
     }
@@ -46,12 +72,14 @@ where
     let mut actions = Vec::new();
     for value in raw {
         match value.get("type").and_then(|t| t.as_str()) {
-            Some("command") => match serde_json::from_value(value) {
-                Ok(action) => actions.push(action),
-                Err(e) => {
-                    tracing::warn!("Invalid hook action config: {}", e);
+            Some("command") | Some("notify") | Some("builtin") => {
+                match serde_json::from_value(value) {
+                    Ok(action) => actions.push(action),
+                    Err(e) => {
+                        tracing::warn!("Invalid hook action config: {}", e);
+                    }
                 }
-            },
+            }
             Some(other) => {
                 tracing::debug!("Unsupported hook action type '{}', skipping", other);
             }
@@ -63,7 +91,7 @@ where
     Ok(actions)
 }
 
-/// Only command actions are supported. MCP tool routing was removed
+/// Command, notify and builtin actions are supported. MCP tool routing was removed
 /// in the HookRuntime re-architecture.
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -74,6 +102,16 @@ pub enum HookAction {
         #[serde(default = "default_timeout")]
         timeout: u64,
     },
+    /// Get the user's attention without a script.
+    Notify {
+        #[serde(default)]
+        title: Option<String>,
+
+        #[serde(default)]
+        method: NotifyMethod,
+    },
+    /// A check goose runs itself, so templates work the same under every shell.
+    Builtin { name: String },
 }
 
 fn default_timeout() -> u64 {
@@ -84,11 +122,9 @@ impl HooksConfig {
     /// Load merged config from global (~/.config/goose/hooks.json) and
     /// project (.goose/settings.json or .claude/settings.json).
     pub fn load_merged(working_dir: &Path) -> Result<Self> {
-        let global_path = crate::config::paths::Paths::in_config_dir("hooks.json");
-        let goose_project_path = working_dir.join(".goose").join("settings.json");
-        let claude_project_path = working_dir.join(".claude").join("settings.json");
+        let [global_path, goose_project_path, claude_project_path] = Self::paths(working_dir);
 
-        let global = Self::load_from_file(&global_path).unwrap_or_else(|e| {
+        let global = Self::load_from_file(&global_path, false).unwrap_or_else(|e| {
             tracing::debug!("No global hooks config at {:?}: {}", global_path, e);
             Self::default()
         });
@@ -119,19 +155,19 @@ impl HooksConfig {
             if claude_project_path.exists() {
                 tracing::warn!("Found hooks config in both .goose/ and .claude/; using .goose/");
             }
-            Self::load_from_file(&goose_project_path).unwrap_or_else(|e| {
+            Self::load_from_file(&goose_project_path, true).unwrap_or_else(|e| {
                 tracing::warn!(
-                    "Failed to parse hooks config {:?}: {}",
+                    "Failed to load hooks config {:?}: {}",
                     goose_project_path,
                     e
                 );
                 Self::default()
             })
         } else {
-            Self::load_from_file(&claude_project_path).unwrap_or_else(|e| {
+            Self::load_from_file(&claude_project_path, true).unwrap_or_else(|e| {
                 if claude_project_path.exists() {
                     tracing::warn!(
-                        "Failed to parse hooks config {:?}: {}",
+                        "Failed to load hooks config {:?}: {}",
                         claude_project_path,
                         e
                     );
@@ -143,7 +179,17 @@ impl HooksConfig {
         Ok(Self::merge(global, project))
     }
 
-    fn load_from_file(path: &Path) -> Result<Self> {
+    /// Files hooks are loaded from: global, then the two project locations.
+    pub fn paths(working_dir: &Path) -> [PathBuf; 3] {
+        [
+            crate::config::paths::Paths::in_config_dir("hooks.json"),
+            working_dir.join(".goose").join("settings.json"),
+            working_dir.join(".claude").join("settings.json"),
+        ]
+    }
+
+    /// Project hooks run arbitrary commands, so they are subject to signature verification.
+    pub(crate) fn load_from_file(path: &Path, is_project: bool) -> Result<Self> {
         if !path.exists() {
             anyhow::bail!("Config file does not exist: {:?}", path);
         }
@@ -151,6 +197,10 @@ impl HooksConfig {
         let content = std::fs::read_to_string(path)
             .with_context(|| format!("Failed to read hooks config from {:?}", path))?;
 
+        if is_project {
+            crate::security::signing::check_project_file(path, content.as_bytes(), "hooks config")?;
+        }
+
         let config: Self = serde_json::from_str(&content)
             .with_context(|| format!("Failed to parse hooks config from {:?}", path))?;
 
@@ -0,0 +1,246 @@
+//! Noticing what changed in a session's configuration between replies.
+//!
+//! Each reply takes a snapshot of the goose config values, the hooks files and the hint
+//! files. When it differs from the snapshot the session last accepted, the differences are
+//! sent to ConfigChange hooks: config keys one by one, files as a whole. The new snapshot is
+//! only accepted when no hook blocks, so a blocked change keeps being reported until it is
+//! undone. While a change to hint files alone is blocked, those files are left out of the
+//! system prompt instead of stopping the reply.
+
+use super::config::HooksConfig;
+use super::types::{ChangeKind, ConfigChangeEntry, ConfigSource};
+use crate::config::Config;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+static ACCEPTED: LazyLock<Mutex<HashMap<String, ConfigSnapshot>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Session id -> hint files left out of the prompt because a hook blocked their change
+static SKIPPED: LazyLock<Mutex<HashMap<String, BTreeSet<PathBuf>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn content_hash(content: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The configuration a session runs with, at one point in time
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigSnapshot {
+    config_path: PathBuf,
+    values: BTreeMap<String, Value>,
+    files: BTreeMap<PathBuf, (ConfigSource, u64)>,
+}
+
+impl ConfigSnapshot {
+    /// Snapshot the goose config, the hooks files for `working_dir` and the given hint files
+    pub fn capture<'a>(
+        working_dir: &Path,
+        hint_files: impl IntoIterator<Item = (&'a Path, &'a str)>,
+    ) -> Self {
+        let config = Config::global();
+        let mut files = BTreeMap::new();
+        for path in HooksConfig::paths(working_dir) {
+            if let Ok(content) = std::fs::read(&path) {
+                files.insert(path, (ConfigSource::Hooks, content_hash(&content)));
+            }
+        }
+        for (path, content) in hint_files {
+            files.insert(
+                path.to_path_buf(),
+                (ConfigSource::Instructions, content_hash(content.as_bytes())),
+            );
+        }
+        Self {
+            config_path: PathBuf::from(config.path()),
+            values: config
+                .all_values()
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            files,
+        }
+    }
+
+    /// What changed from `self` to `newer`
+    pub fn diff(&self, newer: &Self) -> Vec<ConfigChangeEntry> {
+        let mut changes = Vec::new();
+        let keys = diff_maps(&self.values, &newer.values);
+        changes.extend(keys.into_iter().map(|(key, change)| ConfigChangeEntry {
+            source: ConfigSource::Config,
+            path: newer.config_path.clone(),
+            key: Some(key.clone()),
+            change,
+        }));
+        let files = diff_maps(&self.files, &newer.files);
+        changes.extend(files.into_iter().map(|(path, change)| {
+            let (source, _) = newer.files.get(path).or(self.files.get(path)).unwrap();
+            ConfigChangeEntry {
+                source: *source,
+                path: path.clone(),
+                key: None,
+                change,
+            }
+        }));
+        changes
+    }
+}
+
+fn diff_maps<'a, K: Ord, V: PartialEq>(
+    old: &'a BTreeMap<K, V>,
+    new: &'a BTreeMap<K, V>,
+) -> Vec<(&'a K, ChangeKind)> {
+    let mut changes: Vec<(&K, ChangeKind)> = new
+        .iter()
+        .filter_map(|(key, value)| match old.get(key) {
+            None => Some((key, ChangeKind::Added)),
+            Some(previous) if previous != value => Some((key, ChangeKind::Modified)),
+            Some(_) => None,
+        })
+        .chain(
+            old.keys()
+                .filter(|key| !new.contains_key(*key))
+                .map(|key| (key, ChangeKind::Removed)),
+        )
+        .collect();
+    changes.sort_by(|a, b| a.0.cmp(b.0));
+    changes
+}
+
+/// What changed since the snapshot the session last accepted. A session's first snapshot is
+/// accepted as it is.
+pub fn changes_since_accepted(
+    session_id: &str,
+    snapshot: &ConfigSnapshot,
+) -> Option<Vec<ConfigChangeEntry>> {
+    let mut accepted = ACCEPTED.lock().ok()?;
+    let Some(previous) = accepted.get(session_id) else {
+        accepted.insert(session_id.to_string(), snapshot.clone());
+        return None;
+    };
+    let changes = previous.diff(snapshot);
+    (!changes.is_empty()).then_some(changes)
+}
+
+/// Run the session with `snapshot` from now on
+pub fn accept(session_id: &str, snapshot: ConfigSnapshot) {
+    if let Ok(mut accepted) = ACCEPTED.lock() {
+        accepted.insert(session_id.to_string(), snapshot);
+    }
+}
+
+/// The hint files among `changes`
+pub fn instruction_files(changes: &[ConfigChangeEntry]) -> Vec<PathBuf> {
+    changes
+        .iter()
+        .filter(|change| change.source == ConfigSource::Instructions)
+        .map(|change| change.path.clone())
+        .collect()
+}
+
+/// Leave `files` out of the session's system prompt, replacing the files skipped before
+pub fn skip_instructions(session_id: &str, files: BTreeSet<PathBuf>) {
+    if let Ok(mut skipped) = SKIPPED.lock() {
+        if files.is_empty() {
+            skipped.remove(session_id);
+        } else {
+            skipped.insert(session_id.to_string(), files);
+        }
+    }
+}
+
+/// The hint files left out of the session's system prompt
+pub fn skipped_instructions(session_id: &str) -> BTreeSet<PathBuf> {
+    SKIPPED
+        .lock()
+        .ok()
+        .and_then(|skipped| skipped.get(session_id).cloned())
+        .unwrap_or_default()
+}
+
+/// Forget what was accepted and skipped for a session that ended
+pub fn forget(session_id: &str) {
+    if let Ok(mut accepted) = ACCEPTED.lock() {
+        accepted.remove(session_id);
+    }
+    if let Ok(mut skipped) = SKIPPED.lock() {
+        skipped.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_changed_keys_and_files_until_accepted() {
+        let hints = PathBuf::from("/repo/AGENTS.md");
+        let before = ConfigSnapshot {
+            config_path: "/home/me/.config/goose/config.yaml".into(),
+            values: BTreeMap::from([
+                ("GOOSE_MODE".to_string(), json!("approve")),
+                ("GOOSE_MODEL".to_string(), json!("gpt-4o")),
+            ]),
+            files: BTreeMap::from([(hints.clone(), (ConfigSource::Instructions, 1))]),
+        };
+        let after = ConfigSnapshot {
+            values: BTreeMap::from([
+                ("GOOSE_MODE".to_string(), json!("auto")),
+                ("GOOSE_TEMPERATURE".to_string(), json!(0.2)),
+            ]),
+            files: BTreeMap::from([(hints.clone(), (ConfigSource::Instructions, 2))]),
+            ..before.clone()
+        };
+
+        let changes = before.diff(&after);
+        let summary: Vec<_> = changes
+            .iter()
+            .map(|c| (c.source, c.key.as_deref(), c.change))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    ConfigSource::Config,
+                    Some("GOOSE_MODE"),
+                    ChangeKind::Modified
+                ),
+                (
+                    ConfigSource::Config,
+                    Some("GOOSE_MODEL"),
+                    ChangeKind::Removed
+                ),
+                (
+                    ConfigSource::Config,
+                    Some("GOOSE_TEMPERATURE"),
+                    ChangeKind::Added
+                ),
+                (ConfigSource::Instructions, None, ChangeKind::Modified),
+            ]
+        );
+        assert_eq!(changes[3].path, hints);
+
+        let session = "config-change-test";
+        assert!(changes_since_accepted(session, &before).is_none());
+        assert_eq!(changes_since_accepted(session, &after).unwrap().len(), 4);
+        // Still reported while the change isn't accepted
+        assert!(changes_since_accepted(session, &after).is_some());
+        accept(session, after.clone());
+        assert!(changes_since_accepted(session, &after).is_none());
+
+        assert_eq!(instruction_files(&changes), [hints.clone()]);
+        skip_instructions(session, BTreeSet::from([hints.clone()]));
+        assert!(skipped_instructions(session).contains(&hints));
+        forget(session);
+        assert!(skipped_instructions(session).is_empty());
+        // A forgotten session starts over from its next snapshot
+        assert!(changes_since_accepted(session, &before).is_none());
+    }
+}
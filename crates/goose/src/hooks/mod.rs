@@ -1,13 +1,17 @@
-mod config;
+pub(crate) mod config;
+pub mod config_change;
+pub(crate) mod notify;
 mod subprocess;
+pub mod templates;
 pub mod types;
 
-pub use types::{HookEvent, HookOutcome};
+pub use types::{HookEvent, HookOutcome, PermissionFinding};
 
+use crate::security::audit::{AuditEvent, AuditLog};
 use config::{HookAction, HooksConfig};
 use std::path::Path;
 use tokio_util::sync::CancellationToken;
-use types::{HookDecision, HookResult};
+use types::{HookCommandOutput, HookDecision, HookResult};
 
 const MAX_CONTEXT_LEN: usize = 32_768;
 
@@ -28,10 +32,25 @@ impl HookRuntime {
         Self { config }
     }
 
+    /// Build a runtime from settings in the hooks.json format.
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            config: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Whether any configured hook would run for the event.
+    pub fn has_hooks_for(&self, event: &HookEvent) -> bool {
+        self.config
+            .get_hooks_for_event(event.kind())
+            .iter()
+            .any(|config| Self::matches_config(config, event))
+    }
+
     /// Emit a lifecycle event. Runs all matching hooks, returns aggregated outcome.
     pub async fn emit(
         &self,
-        event: HookEvent,
+        mut event: HookEvent,
         working_dir: &Path,
         cancel_token: CancellationToken,
     ) -> HookOutcome {
@@ -46,7 +65,7 @@ impl HookRuntime {
             event.kind()
         );
 
-        let stdin_json = match serde_json::to_string(&event) {
+        let mut stdin_json = match serde_json::to_string(&event) {
             Ok(json) => json,
             Err(e) => {
                 tracing::warn!("Failed to serialize hook event: {}", e);
@@ -83,6 +102,7 @@ impl HookRuntime {
                                     output.stdout.len()
                                 );
                                 if output.timed_out {
+                                    Self::audit(&event, command, Some(&output), false, None);
                                     tracing::warn!(
                                         "Hook timed out after {}s, failing open",
                                         timeout
@@ -101,19 +121,62 @@ impl HookRuntime {
                                                 && event.is_blockable()
                                             {
                                                 outcome.blocked = true;
+                                                outcome.reason = hook_result
+                                                    .reason
+                                                    .filter(|reason| !reason.trim().is_empty());
+                                                Self::audit(
+                                                    &event,
+                                                    command,
+                                                    Some(&output),
+                                                    true,
+                                                    None,
+                                                );
                                                 tracing::info!(
                                                     "Hook blocked event {} (JSON decision)",
                                                     event.kind()
                                                 );
                                                 return outcome;
                                             }
+                                            if hook_result.decision == Some(HookDecision::Allow)
+                                                && matches!(
+                                                    event,
+                                                    HookEvent::PermissionRequest { .. }
+                                                )
+                                            {
+                                                outcome.allowed = true;
+                                            }
                                             if let Some(ctx) = hook_result.additional_context {
                                                 contexts.push(ctx);
                                             }
+                                            if let Some(prompt) = hook_result.modified_prompt {
+                                                // Later hooks see the rewritten prompt
+                                                if let HookEvent::UserPromptSubmit {
+                                                    user_prompt,
+                                                    ..
+                                                } = &mut event
+                                                {
+                                                    *user_prompt = prompt.clone();
+                                                    if let Ok(json) = serde_json::to_string(&event) {
+                                                        stdin_json = json;
+                                                    }
+                                                    outcome.modified_prompt = Some(prompt);
+                                                } else {
+                                                    tracing::debug!(
+                                                        "Ignoring modified_prompt from a {} hook",
+                                                        event.kind()
+                                                    );
+                                                }
+                                            }
                                         }
+                                        Self::audit(&event, command, Some(&output), false, None);
                                     }
                                     Some(2) if event.is_blockable() => {
                                         outcome.blocked = true;
+                                        let stderr = output.stderr.trim();
+                                        if !stderr.is_empty() {
+                                            outcome.reason = Some(stderr.to_string());
+                                        }
+                                        Self::audit(&event, command, Some(&output), true, None);
                                         tracing::info!(
                                             "Hook blocked event {} (exit 2)",
                                             event.kind()
@@ -121,21 +184,44 @@ impl HookRuntime {
                                         return outcome;
                                     }
                                     Some(code) => {
+                                        Self::audit(&event, command, Some(&output), false, None);
                                         tracing::debug!(
                                             "Hook exited with code {}, failing open",
                                             code
                                         );
                                     }
                                     None => {
+                                        Self::audit(&event, command, Some(&output), false, None);
                                         tracing::debug!("Hook killed (no exit code), failing open");
                                     }
                                 }
                             }
                             Err(e) => {
+                                Self::audit(&event, command, None, false, Some(e.clone()));
                                 tracing::warn!("Hook execution failed: {}, failing open", e);
                             }
                         }
                     }
+                    HookAction::Notify { title, method } => {
+                        notify::notify(
+                            title.as_deref().unwrap_or("goose"),
+                            &event.notification_text(),
+                            *method,
+                        );
+                    }
+                    HookAction::Builtin { name } => {
+                        let Some(reason) = templates::run_builtin(name, &event) else {
+                            continue;
+                        };
+                        if event.is_blockable() {
+                            outcome.blocked = true;
+                            outcome.reason = Some(reason);
+                            let command = format!("{}{}", templates::BUILTIN_PREFIX, name);
+                            Self::audit(&event, &command, None, true, None);
+                            tracing::info!("Hook blocked event {} ({})", event.kind(), command);
+                            return outcome;
+                        }
+                    }
                 }
             }
         }
@@ -162,6 +248,28 @@ impl HookRuntime {
         outcome
     }
 
+    /// Record a hook command execution in the security audit trail.
+    fn audit(
+        event: &HookEvent,
+        command: &str,
+        output: Option<&HookCommandOutput>,
+        blocked: bool,
+        error: Option<String>,
+    ) {
+        AuditLog::record(
+            Some(event.session_id()),
+            AuditEvent::HookExecution {
+                event: event.kind().to_string(),
+                tool_name: event.tool_name().map(str::to_string),
+                command: command.to_string(),
+                exit_code: output.and_then(|o| o.exit_code),
+                timed_out: output.is_some_and(|o| o.timed_out),
+                blocked,
+                error,
+            },
+        );
+    }
+
     /// Parse stdout from a hook that exited 0.
     fn parse_stdout(stdout: &str, is_blockable: bool) -> Option<HookResult> {
         let trimmed = stdout.trim();
@@ -204,11 +312,27 @@ impl HookRuntime {
         match event {
             HookEvent::PreToolUse { .. }
             | HookEvent::PostToolUse { .. }
-            | HookEvent::PostToolUseFailure { .. } => Self::matches_tool(pattern, event),
+            | HookEvent::PostToolUseFailure { .. }
+            | HookEvent::PermissionRequest { .. } => Self::matches_tool(pattern, event),
             HookEvent::PreCompact { .. } | HookEvent::PostCompact { .. } => {
                 (event.is_manual_compact() && pattern == "manual")
                     || (!event.is_manual_compact() && pattern == "auto")
             }
+            HookEvent::Notification {
+                notification_type, ..
+            } => pattern == "*" || pattern.split('|').any(|p| p == notification_type),
+            HookEvent::TaskCompleted { task_type, .. } => {
+                pattern == "*" || pattern.split('|').any(|p| p == task_type)
+            }
+            HookEvent::SessionEnd { reason, .. } => {
+                pattern == "*" || pattern.split('|').any(|p| p == reason)
+            }
+            HookEvent::ConfigChange { changes, .. } => {
+                pattern == "*"
+                    || pattern
+                        .split('|')
+                        .any(|p| changes.iter().any(|change| change.source.as_str() == p))
+            }
             _ => true,
         }
     }
@@ -388,5 +512,132 @@ mod tests {
             .emit(event, dir.path(), CancellationToken::new())
             .await;
         assert!(outcome.blocked, "exit-0 JSON block decision must set outcome.blocked");
+        assert_eq!(outcome.reason.as_deref(), Some("test"));
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn emit_chains_prompt_rewrites() {
+        let dir = tempfile::tempdir().unwrap();
+        let rewrite = |command: &str| {
+            serde_json::json!({
+                "hooks": [{ "type": "command", "command": command, "timeout": 5 }]
+            })
+        };
+        let runtime = HookRuntime::from_json(
+            &serde_json::json!({
+                "hooks": {
+                    "UserPromptSubmit": [
+                        rewrite(r#"echo '{"modified_prompt": "fix GOOSE-42"}'"#),
+                        // Sees the first rewrite on stdin
+                        rewrite(r#"grep -q GOOSE-42 && echo '{"modifiedPrompt": "fix GOOSE-42 (login fails)"}'"#),
+                    ]
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let event = HookEvent::UserPromptSubmit {
+            session_id: "test".into(),
+            user_prompt: "fix #42".into(),
+            cwd: dir.path().to_path_buf(),
+        };
+        let outcome = runtime
+            .emit(event, dir.path(), CancellationToken::new())
+            .await;
+        assert!(!outcome.blocked);
+        assert_eq!(
+            outcome.modified_prompt.as_deref(),
+            Some("fix GOOSE-42 (login fails)")
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn emit_honors_allow_for_permission_requests_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let allow = serde_json::json!({
+            "matcher": "Bash(cargo*)",
+            "hooks": [{
+                "type": "command",
+                "command": r#"echo '{"decision": "allow"}'"#,
+                "timeout": 5
+            }]
+        });
+        let runtime = HookRuntime::from_json(
+            &serde_json::json!({
+                "hooks": { "PermissionRequest": [allow.clone()], "PreToolUse": [allow] }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let request = |command: &str| HookEvent::PermissionRequest {
+            session_id: "test".into(),
+            tool_name: "developer__shell".into(),
+            tool_input: json!({ "command": command }),
+            findings: vec![],
+            cwd: dir.path().to_path_buf(),
+        };
+        let outcome = runtime
+            .emit(request("cargo test"), dir.path(), CancellationToken::new())
+            .await;
+        assert!(outcome.allowed);
+        let outcome = runtime
+            .emit(
+                request("rm -rf target"),
+                dir.path(),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(!outcome.allowed);
+
+        let pre_tool_use = HookEvent::PreToolUse {
+            session_id: "test".into(),
+            tool_name: "developer__shell".into(),
+            tool_input: json!({"command": "cargo test"}),
+            cwd: dir.path().to_path_buf(),
+        };
+        let outcome = runtime
+            .emit(pre_tool_use, dir.path(), CancellationToken::new())
+            .await;
+        assert!(!outcome.allowed);
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn emit_reports_stderr_as_reason_at_exit_2() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = HookRuntime::from_json(
+            &serde_json::json!({
+                "hooks": {
+                    "Stop": [{
+                        "hooks": [{
+                            "type": "command",
+                            "command": "echo 'Run the tests before stopping' >&2; exit 2",
+                            "timeout": 5
+                        }]
+                    }]
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let event = HookEvent::Stop {
+            session_id: "test".into(),
+            last_assistant_text: "Done.".into(),
+            stop_hook_active: false,
+            cwd: dir.path().to_path_buf(),
+        };
+        let outcome = runtime
+            .emit(event, dir.path(), CancellationToken::new())
+            .await;
+        assert!(outcome.blocked);
+        assert_eq!(
+            outcome.reason.as_deref(),
+            Some("Run the tests before stopping")
+        );
     }
 }
@@ -0,0 +1,110 @@
+use serde::Deserialize;
+use std::io::Write;
+use std::time::Duration;
+use tokio::process::Command;
+
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How the built-in `notify` action gets the user's attention.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMethod {
+    /// A native desktop notification, falling back to the terminal bell where none is
+    /// available.
+    #[default]
+    Desktop,
+    /// Only ring the terminal bell.
+    Bell,
+}
+
+fn desktop_command(title: &str, body: &str) -> Option<Command> {
+    if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        Some(command)
+    } else if cfg!(target_os = "windows") {
+        let script = format!(
+            "[reflection.assembly]::loadwithpartialname('System.Windows.Forms') | Out-Null; \
+             $n = New-Object System.Windows.Forms.NotifyIcon; \
+             $n.Icon = [System.Drawing.SystemIcons]::Information; $n.Visible = $true; \
+             $n.ShowBalloonTip(5000, {}, {}, 'Info'); Start-Sleep -Seconds 5; $n.Dispose()",
+            powershell_string(title),
+            powershell_string(body)
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-Command", &script]);
+        Some(command)
+    } else if which::which("notify-send").is_ok() {
+        let mut command = Command::new("notify-send");
+        command.args(["--app-name=goose", title, body]);
+        Some(command)
+    } else {
+        None
+    }
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn powershell_string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+pub(crate) fn ring_bell() {
+    let mut stderr = std::io::stderr();
+    let _ = stderr.write_all(b"\x07");
+    let _ = stderr.flush();
+}
+
+/// Start the notifier without waiting for it, so a slow notifier never holds up the prompt
+/// it announces.
+pub(crate) fn show_desktop_notification(title: &str, body: &str) -> bool {
+    let Some(mut command) = desktop_command(title, body) else {
+        return false;
+    };
+    command.kill_on_drop(true);
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::debug!("Desktop notification failed: {}", e);
+            return false;
+        }
+    };
+    tokio::spawn(async move {
+        // Windows keeps the process alive while the balloon shows
+        if let Ok(Ok(status)) = tokio::time::timeout(NOTIFY_TIMEOUT, child.wait()).await {
+            if !status.success() {
+                tracing::debug!("Desktop notification exited with {}", status);
+            }
+        }
+    });
+    true
+}
+
+/// Run the built-in `notify` action.
+pub fn notify(title: &str, body: &str, method: NotifyMethod) {
+    if method == NotifyMethod::Desktop && show_desktop_notification(title, body) {
+        return;
+    }
+    ring_bell();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notification_text_is_quoted_for_the_shell_it_runs_in() {
+        assert_eq!(
+            applescript_string(r#"run "rm" \ now"#),
+            r#""run \"rm\" \\ now""#
+        );
+        assert_eq!(powershell_string("it's done"), "'it''s done'");
+    }
+}
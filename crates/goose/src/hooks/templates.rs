@@ -0,0 +1,293 @@
+//! Ready-made hook configurations, selected by name in hooks.json:
+//!
+//! ```json
+//! { "use": ["builtin:block-git-push", "builtin:notify-on-idle"] }
+//! ```
+//!
+//! Each template expands into ordinary hook groups when the config is loaded, alongside
+//! the file's own `hooks`. Checks that would need a particular shell are `builtin` actions
+//! goose runs itself; lint-on-edit has a bash and a cmd version of its command.
+
+use super::config::HookEventConfig;
+use super::HookEvent;
+use crate::config::paths::Paths;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+/// Name and description of each built-in template.
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "block-git-push",
+        "Stop shell commands that run git push; the model is told to ask the user instead",
+    ),
+    (
+        "lint-on-edit",
+        "Check the project after goose writes or edits a file and show the model any errors",
+    ),
+    (
+        "notify-on-idle",
+        "Desktop notification when goose finishes a turn or needs approval",
+    ),
+    (
+        "audit-shell-commands",
+        "Append every shell command goose runs to shell-commands.jsonl in the goose state directory",
+    ),
+];
+
+const BLOCK_GIT_PUSH_REASON: &str =
+    "git push is blocked by the builtin:block-git-push hook; ask the user to push";
+
+#[cfg(not(windows))]
+const LINT_COMMAND: &str = r#"if [ -f Cargo.toml ]; then
+  out=$(cargo check --quiet --message-format short 2>&1) || echo "$out" | tail -n 40
+elif [ -f package.json ] && grep -q '"lint"' package.json; then
+  out=$(npm run --silent lint 2>&1) || echo "$out" | tail -n 40
+elif [ -f pyproject.toml ] && command -v ruff >/dev/null; then
+  ruff check --quiet . 2>&1 | tail -n 40
+fi
+exit 0"#;
+
+#[cfg(windows)]
+const LINT_COMMAND: &str = r#"(if exist Cargo.toml (cargo check --quiet --message-format short 2>&1) else if exist package.json (findstr /c:"\"lint\"" package.json >nul && npm run --silent lint 2>&1) else if exist pyproject.toml (where ruff >nul 2>&1 && ruff check --quiet . 2>&1)) & exit /b 0"#;
+
+fn template_json(name: &str) -> Option<Value> {
+    let template = match name {
+        "block-git-push" => json!({
+            "PreToolUse": [{
+                "matcher": "Bash",
+                "hooks": [{ "type": "builtin", "name": "block-git-push" }]
+            }]
+        }),
+        "lint-on-edit" => {
+            let lint = json!([{ "type": "command", "command": LINT_COMMAND, "timeout": 120 }]);
+            json!({
+                "PostToolUse": [
+                    { "matcher": "write", "hooks": lint },
+                    { "matcher": "edit", "hooks": lint }
+                ]
+            })
+        }
+        "notify-on-idle" => json!({
+            "Stop": [{ "hooks": [{ "type": "notify" }] }],
+            "Notification": [{ "matcher": "permission_prompt", "hooks": [{ "type": "notify" }] }]
+        }),
+        "audit-shell-commands" => json!({
+            "PreToolUse": [{
+                "matcher": "Bash",
+                "hooks": [{ "type": "builtin", "name": "audit-shell-commands" }]
+            }]
+        }),
+        _ => return None,
+    };
+    Some(template)
+}
+
+/// The hook groups a `use` entry expands to, by event.
+pub fn expand(reference: &str) -> Option<HashMap<String, Vec<HookEventConfig>>> {
+    let Some(name) = reference.strip_prefix(BUILTIN_PREFIX) else {
+        tracing::warn!(
+            "Unsupported hook template '{}', expected {}<name>",
+            reference,
+            BUILTIN_PREFIX
+        );
+        return None;
+    };
+    let Some(template) = template_json(name) else {
+        let names: Vec<&str> = BUILTIN_TEMPLATES.iter().map(|(name, _)| *name).collect();
+        tracing::warn!(
+            "Unknown hook template '{}', available: {}",
+            reference,
+            names.join(", ")
+        );
+        return None;
+    };
+    match serde_json::from_value(template) {
+        Ok(hooks) => Some(hooks),
+        Err(e) => {
+            tracing::warn!("Invalid hook template '{}': {}", reference, e);
+            None
+        }
+    }
+}
+
+/// Runs a `builtin` hook action, returning the reason when it blocks the event.
+pub fn run_builtin(name: &str, event: &HookEvent) -> Option<String> {
+    match name {
+        "block-git-push" => {
+            let command = event
+                .tool_input()
+                .and_then(|input| input.get("command"))
+                .and_then(|command| command.as_str())?;
+            runs_git_push(command).then(|| BLOCK_GIT_PUSH_REASON.to_string())
+        }
+        "audit-shell-commands" => {
+            let log = Paths::in_state_dir("logs/shell-commands.jsonl");
+            if let Err(e) = append_json_line(&log, event) {
+                tracing::warn!("Failed to record shell command in {:?}: {}", log, e);
+            }
+            None
+        }
+        _ => {
+            tracing::warn!("Unknown builtin hook '{}', skipping", name);
+            None
+        }
+    }
+}
+
+fn append_json_line(path: &std::path::Path, event: &HookEvent) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Whether a shell command line runs `git push`, however the git global options
+/// before the subcommand are written (`git -C repo push`, `git -c k=v push`).
+fn runs_git_push(command_line: &str) -> bool {
+    shell_commands(command_line).iter().any(|words| {
+        words.iter().enumerate().any(|(i, word)| {
+            let program = word.rsplit(['/', '\\']).next().unwrap_or(word);
+            (program.eq_ignore_ascii_case("git") || program.eq_ignore_ascii_case("git.exe"))
+                && git_subcommand(&words[i + 1..]) == Some("push")
+        })
+    })
+}
+
+/// The first argument after git's global options.
+fn git_subcommand(args: &[String]) -> Option<&str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace" => {
+                args.next();
+            }
+            option if option.starts_with('-') => {}
+            subcommand => return Some(subcommand),
+        }
+    }
+    None
+}
+
+/// Splits a command line into the words of each command in it, at `;`, `&`, `|`,
+/// parentheses, backticks and newlines outside quotes, with the quotes removed.
+fn shell_commands(command_line: &str) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = command_line.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') | (None, '\\') => {
+                if let Some(next) = chars.next().filter(|next| *next != '\n') {
+                    word.push(next);
+                    in_word = true;
+                }
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() || ";&|()`".contains(c) => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+                if (c == '\n' || !c.is_whitespace()) && !words.is_empty() {
+                    commands.push(std::mem::take(&mut words));
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    if !words.is_empty() {
+        commands.push(words);
+    }
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::{HookEvent, HookRuntime};
+    use tokio_util::sync::CancellationToken;
+
+    #[test]
+    fn every_template_expands() {
+        for (name, _) in BUILTIN_TEMPLATES {
+            let hooks = expand(&format!("{}{}", BUILTIN_PREFIX, name)).unwrap();
+            assert!(hooks
+                .values()
+                .flatten()
+                .all(|group| !group.hooks.is_empty()));
+        }
+        assert!(expand("builtin:missing").is_none());
+        assert!(expand("block-git-push").is_none());
+    }
+
+    #[test]
+    fn git_push_is_found_past_global_options_and_quotes() {
+        for command in [
+            "git push origin main",
+            "git -C other/repo push",
+            "git -C \"my repo\" push",
+            "git -c push.default=current --no-pager push",
+            "cd repo && /usr/bin/git push --force",
+            "echo done; GIT_TRACE=1 git push",
+            "(git push)",
+        ] {
+            assert!(runs_git_push(command), "{}", command);
+        }
+        for command in [
+            "git status",
+            "git log --grep push",
+            "git -C push status",
+            "echo 'git push'",
+            "git commit -m \"then git push\"",
+        ] {
+            assert!(!runs_git_push(command), "{}", command);
+        }
+    }
+
+    #[tokio::test]
+    async fn block_git_push_blocks_only_pushes() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = HookRuntime::from_json(r#"{"use": "builtin:block-git-push"}"#).unwrap();
+        let shell = |command: &str| HookEvent::PreToolUse {
+            session_id: "test".into(),
+            tool_name: "developer__shell".into(),
+            tool_input: json!({ "command": command }),
+            cwd: dir.path().to_path_buf(),
+        };
+
+        let outcome = runtime
+            .emit(
+                shell("git push origin main"),
+                dir.path(),
+                CancellationToken::new(),
+            )
+            .await;
+        assert!(outcome.blocked);
+        assert!(outcome.reason.unwrap().contains("ask the user to push"));
+
+        let outcome = runtime
+            .emit(shell("git status"), dir.path(), CancellationToken::new())
+            .await;
+        assert!(!outcome.blocked);
+    }
+}
@@ -0,0 +1,282 @@
+//! Localizing the messages goose shows users.
+//!
+//! Every user-facing string of the core crate has a key and an English text in [`ENGLISH`].
+//! `GOOSE_LOCALE` picks the locale, falling back to the system's (`LC_ALL`, `LC_MESSAGES`,
+//! `LANG`). The translations for a locale are read from `locales/<locale>.yaml` in the goose
+//! config directory, a flat map from key to text:
+//!
+//! ```yaml
+//! compaction.complete: Compactación completa
+//! tools.changed: "Herramientas cambiadas: {tools}"
+//! ```
+//!
+//! A regional locale like `pt-BR` falls back to `pt`, and keys a catalog leaves out fall back
+//! to English. Texts take arguments as `{name}`. The locale and its catalog are read once,
+//! the first time a message is shown.
+//!
+//! Text meant for the model, like tool responses and prompts, stays in English.
+
+use crate::config::paths::Paths;
+use crate::config::Config;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::LazyLock;
+
+pub const LOCALE_CONFIG_KEY: &str = "GOOSE_LOCALE";
+
+const DEFAULT_LOCALE: &str = "en";
+
+/// The English text of every key
+pub const ENGLISH: &[(&str, &str)] = &[
+    // Tool approval
+    ("approval.needed", "goose needs your approval to run {tool}"),
+    // Compaction and the context window
+    (
+        "compaction.auto_threshold",
+        "Exceeded auto-compact threshold of {threshold}%. Performing auto-compaction...",
+    ),
+    ("compaction.thinking", "goose is compacting the conversation..."),
+    ("compaction.complete", "Compaction complete"),
+    (
+        "context.recovery_off",
+        "Unable to continue: Context limit exceeded and automatic recovery is off. Compact the conversation, use a model with a larger context window, or start a new session.",
+    ),
+    (
+        "context.still_exceeded",
+        "Unable to continue: Context limit still exceeded after compaction. Try using a shorter message, a model with a larger context window, or start a new session.",
+    ),
+    (
+        "context.dropping_tool_outputs",
+        "Context limit reached. Dropping older tool outputs to continue conversation...",
+    ),
+    (
+        "context.compacting",
+        "Context limit reached. Compacting to continue conversation...",
+    ),
+    // Provider errors
+    ("error.provider", "Ran into this error: {error}."),
+    (
+        "error.advice.auth",
+        "Check your provider credentials with `goose configure`, then resend your message.",
+    ),
+    (
+        "error.advice.model",
+        "Pick a model the provider has with `goose configure`, then resend your message.",
+    ),
+    (
+        "error.advice.content_filter",
+        "Rephrase your message, then resend it to continue.",
+    ),
+    (
+        "error.advice.busy",
+        "The provider is busy. Wait a moment, then resend your message.",
+    ),
+    (
+        "error.advice.retry",
+        "Please retry if you think this is a transient or recoverable error.",
+    ),
+    (
+        "error.advice.network",
+        "Please resend your message to try again.",
+    ),
+    (
+        "error.credits.top_up",
+        "Please add credits to your account, then resend your message to continue.",
+    ),
+    (
+        "error.credits.provider",
+        "Please check your account with your provider to add more credits, then resend your message to continue.",
+    ),
+    // Slash commands
+    ("command.cleared", "Conversation cleared"),
+    ("command.secrets_on", "Secret masking enabled for this session"),
+    (
+        "command.secrets_off",
+        "Secret masking disabled for this session. Tool output will enter the conversation unmasked",
+    ),
+    (
+        "command.dry_run_on",
+        "Dry run enabled. Mutating tools will show a preview and wait for approval instead of running",
+    ),
+    (
+        "command.dry_run_off",
+        "Dry run disabled. Tools will execute under the normal permission mode",
+    ),
+    (
+        "command.plan_on",
+        "Plan mode enabled. goose will propose a plan for your approval before making changes",
+    ),
+    (
+        "command.plan_off",
+        "Plan mode disabled. Tools will execute under the normal permission mode",
+    ),
+    ("command.plan_stop", "goose will stop after finishing step {step}"),
+    // Session notifications
+    ("tools.changed", "Tools changed: {tools}"),
+    ("session.paused", "goose paused: {reason}"),
+    ("notify.finished", "goose finished"),
+    ("notify.finished_with", "goose finished: {summary}"),
+    ("notify.subagent", "Subagent {status}"),
+    ("notify.task", "{task_type} task {status}"),
+    ("notify.scheduled_job", "Scheduled job {job} {status}"),
+    (
+        "notify.subagent_budget",
+        "Subagent {subagent} used up its budget of {budget} tokens",
+    ),
+    ("notify.teammate_idle", "{name} is idle"),
+    ("notify.settings_changed", "{count} settings changed"),
+];
+
+/// Translations for one locale, over English
+struct Catalog {
+    texts: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Read the catalog for `locale` from `dir`, then the one for its language
+    fn load(dir: &Path, locale: &str) -> Self {
+        let mut texts = HashMap::new();
+        let language = locale.split('-').next().unwrap_or(locale);
+        for name in [locale, language] {
+            let path = dir.join(format!("{}.yaml", name));
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            match serde_yaml::from_str::<HashMap<String, String>>(&content) {
+                Ok(catalog) => {
+                    for (key, text) in catalog {
+                        texts.entry(key).or_insert(text);
+                    }
+                }
+                Err(e) => tracing::warn!("Ignoring locale file {}: {}", path.display(), e),
+            }
+        }
+        Self { texts }
+    }
+
+    fn text(&self, key: &str) -> &str {
+        if let Some(text) = self.texts.get(key) {
+            return text;
+        }
+        ENGLISH
+            .iter()
+            .find_map(|(k, text)| (*k == key).then_some(*text))
+            .unwrap_or(key)
+    }
+}
+
+static CATALOG: LazyLock<Catalog> =
+    LazyLock::new(|| Catalog::load(&Paths::in_config_dir("locales"), &locale()));
+
+/// A locale like `de_DE.UTF-8` as `de-DE`; None for the C locale
+fn normalize(locale: &str) -> Option<String> {
+    let locale = locale.split(['.', '@']).next()?.trim().replace('_', "-");
+    match locale.as_str() {
+        "" | "C" | "POSIX" => None,
+        _ => Some(locale),
+    }
+}
+
+/// The locale goose shows messages in
+pub fn locale() -> String {
+    Config::global()
+        .get_param::<String>(LOCALE_CONFIG_KEY)
+        .ok()
+        .into_iter()
+        .chain(
+            ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .filter_map(|var| std::env::var(var).ok()),
+        )
+        .find_map(|locale| normalize(&locale))
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// `text` with each `{name}` replaced by its argument, in one pass so braces in the
+/// arguments are left alone. Unknown names stay as they are.
+fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        filled.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find('}').and_then(|end| {
+            let name = &placeholder[1..end];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| (value.to_string(), end))
+        });
+        match value {
+            Some((value, end)) => {
+                filled.push_str(&value);
+                rest = &placeholder[end + 1..];
+            }
+            None => {
+                filled.push('{');
+                rest = &placeholder[1..];
+            }
+        }
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// The text for `key` in the user's locale
+pub fn text(key: &str) -> String {
+    CATALOG.text(key).to_string()
+}
+
+/// The text for `key` in the user's locale, with its `{name}` arguments filled in
+pub fn text_with(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(CATALOG.text(key), args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translations_fall_back_to_the_language_then_english() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pt-BR.yaml"),
+            "compaction.complete: Compactação concluída\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("pt.yaml"),
+            "compaction.complete: Compactação completa\ntools.changed: \"Ferramentas alteradas: {tools}\"\n",
+        )
+        .unwrap();
+
+        let catalog = Catalog::load(dir.path(), "pt-BR");
+        assert_eq!(catalog.text("compaction.complete"), "Compactação concluída");
+        assert_eq!(
+            fill(catalog.text("tools.changed"), &[("tools", &"developer")]),
+            "Ferramentas alteradas: developer"
+        );
+        assert_eq!(catalog.text("session.paused"), "goose paused: {reason}");
+        assert_eq!(catalog.text("no.such.key"), "no.such.key");
+    }
+
+    #[test]
+    fn arguments_are_filled_in_once() {
+        assert_eq!(
+            fill(
+                "{task_type} task {status}",
+                &[("task_type", &"{status}"), ("status", &"failed")]
+            ),
+            "{status} task failed"
+        );
+        assert_eq!(fill("{unknown} {", &[("status", &"ok")]), "{unknown} {");
+    }
+
+    #[test]
+    fn system_locales_are_normalized() {
+        assert_eq!(normalize("de_DE.UTF-8").as_deref(), Some("de-DE"));
+        assert_eq!(normalize("fr").as_deref(), Some("fr"));
+        assert_eq!(normalize("C.UTF-8"), None);
+        assert_eq!(normalize("POSIX"), None);
+    }
+}
@@ -1,19 +1,25 @@
 pub mod action_required_manager;
 pub mod agents;
 pub mod builtin_extension;
+pub mod checkpoints;
 pub mod config;
 pub mod context_mgmt;
 pub mod conversation;
 pub mod dictation;
 pub mod download_manager;
+pub mod eval;
 pub mod execution;
 pub mod gateway;
 pub mod goose_apps;
+pub mod headless;
 pub mod hints;
 pub mod hooks;
+pub mod i18n;
 pub mod logging;
+pub mod loop_guard;
 pub mod mcp_utils;
 pub mod model;
+pub mod notifications;
 pub mod oauth;
 pub mod otel;
 pub mod permission;
@@ -22,11 +28,14 @@ pub mod prompt_template;
 pub mod providers;
 pub mod recipe;
 pub mod recipe_deeplink;
+pub mod retrieval;
 pub mod scheduler;
 pub mod scheduler_trait;
+pub mod sdk;
 pub mod security;
 pub mod session;
 pub mod session_context;
+pub mod shutdown;
 pub mod slash_commands;
 pub mod subprocess;
 pub mod token_counter;
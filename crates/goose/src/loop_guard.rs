@@ -0,0 +1,453 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rmcp::model::CallToolRequestParams;
+use serde_json::Value;
+
+use crate::agents::extension_manager::{ExtensionManager, ToolEffect};
+use crate::agents::tool_cache::path_arguments;
+use crate::config::{Config, GooseMode};
+use crate::conversation::message::{Message, MessageContent, ToolRequest};
+use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
+
+pub const LOOP_GUARD_INSPECTOR_NAME: &str = "loop_guard";
+
+const DEFAULT_MAX_IDENTICAL_CALLS: usize = 5;
+const DEFAULT_MAX_OSCILLATIONS: usize = 2;
+const DEFAULT_MAX_SAME_ERRORS: usize = 3;
+
+/// Thresholds for pausing a session that is going in circles. A limit of 0 turns
+/// that detector off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopGuardConfig {
+    /// Identical calls in a row before the next one is stopped
+    pub max_identical_calls: usize,
+    /// Times an edit to a file may be undone and reapplied
+    pub max_oscillations: usize,
+    /// Consecutive tool failures with the same error
+    pub max_same_errors: usize,
+}
+
+impl Default for LoopGuardConfig {
+    fn default() -> Self {
+        Self {
+            max_identical_calls: DEFAULT_MAX_IDENTICAL_CALLS,
+            max_oscillations: DEFAULT_MAX_OSCILLATIONS,
+            max_same_errors: DEFAULT_MAX_SAME_ERRORS,
+        }
+    }
+}
+
+impl LoopGuardConfig {
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        let defaults = Self::default();
+        Self {
+            max_identical_calls: config
+                .get_param("GOOSE_LOOP_MAX_IDENTICAL_CALLS")
+                .unwrap_or(defaults.max_identical_calls),
+            max_oscillations: config
+                .get_param("GOOSE_LOOP_MAX_OSCILLATIONS")
+                .unwrap_or(defaults.max_oscillations),
+            max_same_errors: config
+                .get_param("GOOSE_LOOP_MAX_SAME_ERRORS")
+                .unwrap_or(defaults.max_same_errors),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopKind {
+    RepeatedCall,
+    OscillatingEdit,
+    RepeatedError,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoopDetection {
+    pub kind: LoopKind,
+    pub tool_name: String,
+    pub count: usize,
+    /// The file being edited back and forth, or the error that keeps coming back
+    pub detail: Option<String>,
+}
+
+impl LoopDetection {
+    pub fn finding_id(&self) -> &'static str {
+        match self.kind {
+            LoopKind::RepeatedCall => "LOOP-001",
+            LoopKind::OscillatingEdit => "LOOP-002",
+            LoopKind::RepeatedError => "LOOP-003",
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let detail = self.detail.as_deref().unwrap_or_default();
+        match self.kind {
+            LoopKind::RepeatedCall => format!(
+                "{} was about to be called with the same arguments {} times in a row",
+                self.tool_name, self.count
+            ),
+            LoopKind::OscillatingEdit => format!(
+                "{} keeps switching {} back and forth between the same versions ({} times)",
+                self.tool_name, detail, self.count
+            ),
+            LoopKind::RepeatedError => format!(
+                "The last {} tool calls failed with the same error: {}",
+                self.count, detail
+            ),
+        }
+    }
+
+    pub fn suggestions(&self) -> &'static [&'static str] {
+        match self.kind {
+            LoopKind::RepeatedCall => &[
+                "Check whether the tool output already answers the question",
+                "Give more specific instructions or narrow the task",
+            ],
+            LoopKind::OscillatingEdit => &[
+                "Decide which version of the change you want and say so",
+                "Review the file and the failing check together before editing again",
+            ],
+            LoopKind::RepeatedError => &[
+                "Fix the underlying problem (missing dependency, permissions, wrong path) and resume",
+                "Suggest a different approach that avoids the failing command",
+            ],
+        }
+    }
+
+    /// What the user sees when the session is paused
+    pub fn pause_message(&self) -> String {
+        let mut message = format!(
+            "Paused to avoid a loop: {}.\n\nSuggested next steps:",
+            self.summary()
+        );
+        for suggestion in self.suggestions() {
+            message.push_str(&format!("\n- {}", suggestion));
+        }
+        message.push_str("\n- Reply to continue; the same pattern will be stopped again");
+        message
+    }
+}
+
+struct PastCall<'a> {
+    name: &'a str,
+    arguments: Value,
+    mutating: bool,
+    error: Option<String>,
+}
+
+fn call_arguments(tool_call: &CallToolRequestParams) -> Value {
+    tool_call
+        .arguments
+        .as_ref()
+        .map(|args| Value::Object(args.clone()))
+        .unwrap_or(Value::Null)
+}
+
+fn error_text(content: &MessageContent) -> Option<(&str, Option<String>)> {
+    let response = content.as_tool_response()?;
+    let error = match &response.tool_result {
+        Err(e) => Some(e.message.to_string()),
+        Ok(result) if result.is_error == Some(true) => Some(
+            result
+                .content
+                .iter()
+                .filter_map(|c| c.as_text())
+                .map(|t| t.text.trim())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        Ok(_) => None,
+    };
+    Some((response.id.as_str(), error))
+}
+
+/// Tool calls in the order they were made, with the error each one returned
+fn past_calls<'a>(history: &'a [Message], mutating: &HashSet<String>) -> Vec<PastCall<'a>> {
+    let mut errors: HashMap<&str, Option<String>> = HashMap::new();
+    for content in history.iter().flat_map(|m| m.content.iter()) {
+        if let Some((id, error)) = error_text(content) {
+            errors.insert(id, error);
+        }
+    }
+
+    history
+        .iter()
+        .flat_map(|m| m.content.iter())
+        .filter_map(|content| content.as_tool_request())
+        .filter_map(|request| {
+            let tool_call = request.tool_call.as_ref().ok()?;
+            Some(PastCall {
+                name: tool_call.name.as_ref(),
+                arguments: call_arguments(tool_call),
+                mutating: mutating.contains(tool_call.name.as_ref()),
+                error: errors.get(request.id.as_str()).cloned().flatten(),
+            })
+        })
+        .collect()
+}
+
+/// Check whether making `tool_call` next would continue a loop in `history`.
+/// `mutating` holds the names of tools that change files or state.
+pub fn detect_loop(
+    config: &LoopGuardConfig,
+    history: &[Message],
+    tool_call: &CallToolRequestParams,
+    mutating: &HashSet<String>,
+) -> Option<LoopDetection> {
+    let calls = past_calls(history, mutating);
+    let name = tool_call.name.to_string();
+    let arguments = call_arguments(tool_call);
+
+    if config.max_identical_calls > 0 {
+        let repeats = calls
+            .iter()
+            .rev()
+            .take_while(|call| call.name == name && call.arguments == arguments)
+            .count();
+        if repeats >= config.max_identical_calls {
+            return Some(LoopDetection {
+                kind: LoopKind::RepeatedCall,
+                tool_name: name,
+                count: repeats + 1,
+                detail: None,
+            });
+        }
+    }
+
+    if config.max_oscillations > 0 && mutating.contains(&name) {
+        if let Value::Object(args) = &arguments {
+            for path in path_arguments(args) {
+                let edits: Vec<&PastCall> = calls
+                    .iter()
+                    .filter(|call| call.mutating)
+                    .filter(|call| match &call.arguments {
+                        Value::Object(args) => path_arguments(args).contains(&path),
+                        _ => false,
+                    })
+                    .collect();
+                // Each earlier identical edit that something else later replaced is a reversal
+                let reversals = edits
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, call)| {
+                        call.name == name
+                            && call.arguments == arguments
+                            && edits[i + 1..]
+                                .iter()
+                                .any(|later| later.name != name || later.arguments != arguments)
+                    })
+                    .count();
+                if reversals >= config.max_oscillations {
+                    return Some(LoopDetection {
+                        kind: LoopKind::OscillatingEdit,
+                        tool_name: name,
+                        count: reversals,
+                        detail: Some(path.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    if config.max_same_errors > 0 {
+        let last_error = calls.last().and_then(|call| call.error.as_deref())?;
+        let failures = calls
+            .iter()
+            .rev()
+            .take_while(|call| call.error.as_deref() == Some(last_error))
+            .count();
+        if failures >= config.max_same_errors {
+            return Some(LoopDetection {
+                kind: LoopKind::RepeatedError,
+                tool_name: name,
+                count: failures,
+                detail: Some(last_error.to_string()),
+            });
+        }
+    }
+
+    None
+}
+
+/// Stops tool calls that repeat themselves, undo each other, or keep hitting the
+/// same error, so the agent can pause the session instead of spending its budget
+pub struct LoopGuardInspector {
+    config: LoopGuardConfig,
+    extension_manager: Arc<ExtensionManager>,
+}
+
+impl LoopGuardInspector {
+    pub fn new(config: LoopGuardConfig, extension_manager: Arc<ExtensionManager>) -> Self {
+        Self {
+            config,
+            extension_manager,
+        }
+    }
+}
+
+#[async_trait]
+impl ToolInspector for LoopGuardInspector {
+    fn name(&self) -> &'static str {
+        LOOP_GUARD_INSPECTOR_NAME
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    async fn inspect(
+        &self,
+        session_id: &str,
+        tool_requests: &[ToolRequest],
+        messages: &[Message],
+        _goose_mode: GooseMode,
+    ) -> Result<Vec<InspectionResult>> {
+        let mut names: HashSet<String> = messages
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|content| content.as_tool_request())
+            .filter_map(|request| request.tool_call.as_ref().ok())
+            .map(|tool_call| tool_call.name.to_string())
+            .collect();
+        names.extend(
+            tool_requests
+                .iter()
+                .filter_map(|request| request.tool_call.as_ref().ok())
+                .map(|tool_call| tool_call.name.to_string()),
+        );
+        let mut mutating = HashSet::new();
+        for name in names {
+            if self
+                .extension_manager
+                .classify_tool(session_id, &name)
+                .await
+                == ToolEffect::Mutating
+            {
+                mutating.insert(name);
+            }
+        }
+
+        let mut results = Vec::new();
+        for request in tool_requests {
+            let Ok(tool_call) = &request.tool_call else {
+                continue;
+            };
+            if let Some(detection) = detect_loop(&self.config, messages, tool_call, &mutating) {
+                results.push(InspectionResult {
+                    tool_request_id: request.id.clone(),
+                    action: InspectionAction::Deny,
+                    reason: detection.pause_message(),
+                    confidence: 1.0,
+                    inspector_name: self.name().to_string(),
+                    finding_id: Some(detection.finding_id().to_string()),
+                });
+            }
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolResult, Content, ErrorCode, ErrorData};
+    use rmcp::object;
+
+    fn call(name: &str, arguments: rmcp::model::JsonObject) -> CallToolRequestParams {
+        CallToolRequestParams::new(name.to_string()).with_arguments(arguments)
+    }
+
+    fn history(calls: &[(CallToolRequestParams, Result<&str, &str>)]) -> Vec<Message> {
+        calls
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (tool_call, result))| {
+                let id = format!("call_{}", i);
+                let result = match result {
+                    Ok(text) => Ok(CallToolResult::success(vec![Content::text(*text)])),
+                    Err(error) => Err(ErrorData::new(
+                        ErrorCode::INTERNAL_ERROR,
+                        error.to_string(),
+                        None,
+                    )),
+                };
+                [
+                    Message::assistant().with_tool_request(&id, Ok(tool_call.clone())),
+                    Message::user().with_tool_response(&id, result),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn stops_identical_calls_in_a_row() {
+        let config = LoopGuardConfig::default();
+        let ls = call("developer__shell", object!({"command": "ls"}));
+        let messages =
+            history(&std::iter::repeat_n((ls.clone(), Ok("src")), 5).collect::<Vec<_>>());
+
+        let detection = detect_loop(&config, &messages, &ls, &HashSet::new()).unwrap();
+        assert_eq!(detection.kind, LoopKind::RepeatedCall);
+        assert_eq!(detection.count, 6);
+        assert!(detect_loop(&config, &messages[..8], &ls, &HashSet::new()).is_none());
+    }
+
+    #[test]
+    fn stops_edits_that_undo_each_other() {
+        let config = LoopGuardConfig::default();
+        let mutating = HashSet::from(["developer__write".to_string()]);
+        let a = call(
+            "developer__write",
+            object!({"path": "lib.rs", "content": "a"}),
+        );
+        let b = call(
+            "developer__write",
+            object!({"path": "lib.rs", "content": "b"}),
+        );
+
+        let once = history(&[(a.clone(), Ok("ok")), (b.clone(), Ok("ok"))]);
+        assert!(detect_loop(&config, &once, &a, &mutating).is_none());
+
+        let twice = history(&[
+            (a.clone(), Ok("ok")),
+            (b.clone(), Ok("ok")),
+            (a.clone(), Ok("ok")),
+            (b, Ok("ok")),
+        ]);
+        let detection = detect_loop(&config, &twice, &a, &mutating).unwrap();
+        assert_eq!(detection.kind, LoopKind::OscillatingEdit);
+        assert_eq!(detection.detail.as_deref(), Some("lib.rs"));
+    }
+
+    #[test]
+    fn stops_after_the_same_error_repeats() {
+        let config = LoopGuardConfig::default();
+        let build = |n: u64| {
+            call(
+                "developer__shell",
+                object!({"command": format!("cargo build -j{}", n)}),
+            )
+        };
+        let messages = history(&[
+            (build(1), Err("linker not found")),
+            (build(2), Err("linker not found")),
+            (build(3), Err("linker not found")),
+        ]);
+
+        let detection = detect_loop(&config, &messages, &build(4), &HashSet::new()).unwrap();
+        assert_eq!(detection.kind, LoopKind::RepeatedError);
+        assert_eq!(detection.count, 3);
+        assert!(detection.pause_message().contains("linker not found"));
+
+        let recovered = history(&[
+            (build(1), Err("linker not found")),
+            (build(2), Err("linker not found")),
+            (build(3), Ok("done")),
+        ]);
+        assert!(detect_loop(&config, &recovered, &build(4), &HashSet::new()).is_none());
+    }
+}
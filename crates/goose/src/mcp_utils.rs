@@ -1,6 +1,6 @@
 use base64::Engine;
 pub use rmcp::model::ErrorData;
-use rmcp::model::ResourceContents;
+use rmcp::model::{CallToolResult, ResourceContents};
 
 pub type ToolResult<T> = Result<T, ErrorData>;
 
@@ -28,6 +28,17 @@ pub fn extract_text_from_resource(resource: &ResourceContents) -> String {
     }
 }
 
+/// The structured content of a tool result as JSON text, for providers that only take text.
+/// Only given when the result has no text of its own: tools that return both usually put the
+/// same data in the text.
+pub fn structured_content_as_text(result: &CallToolResult) -> Option<String> {
+    let structured = result.structured_content.as_ref()?;
+    if result.content.iter().any(|c| c.as_text().is_some()) {
+        return None;
+    }
+    serde_json::to_string(structured).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,6 +106,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_structured_content_as_text() {
+        let mut with_text = CallToolResult::success(vec![rmcp::model::Content::text("2 files")]);
+        with_text.structured_content = Some(serde_json::json!({"count": 2}));
+        assert_eq!(structured_content_as_text(&with_text), None);
+
+        let mut data_only = with_text.clone();
+        data_only.content.clear();
+        assert_eq!(
+            structured_content_as_text(&data_only).as_deref(),
+            Some(r#"{"count":2}"#)
+        );
+        assert_eq!(
+            structured_content_as_text(&CallToolResult::success(vec![])),
+            None
+        );
+    }
+
     #[test]
     fn test_extract_text_from_blob_invalid_base64() {
         let resource = ResourceContents::BlobResourceContents {
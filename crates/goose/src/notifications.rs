@@ -0,0 +1,279 @@
+//! Telling the user when goose needs them or has finished something.
+//!
+//! Approval requests, finished background tasks and budget warnings go to the channels listed
+//! in `GOOSE_NOTIFICATIONS`, so a session running without anyone watching its terminal doesn't
+//! sit waiting for input unnoticed:
+//!
+//! ```yaml
+//! GOOSE_NOTIFICATIONS:
+//!   - type: desktop
+//!     events: [approval_needed, task_finished]
+//!   - type: webhook
+//!     url: https://example.com/goose-events
+//!     session_types: [scheduled, hidden]
+//! ```
+//!
+//! A channel gets every kind of notification from every type of session unless it lists some.
+//! Profiles pick their own channels by setting `GOOSE_NOTIFICATIONS` under `settings`. Webhooks
+//! are sent the [`Notification`] as JSON, with the `GOOSE_NOTIFICATIONS_TOKEN` secret as a
+//! bearer token when it is set. Applications embedding goose can add a [`NotificationSink`] of
+//! their own with [`register_sink`].
+
+use crate::config::{Config, ConfigError};
+use crate::hooks::notify::{ring_bell, show_desktop_notification};
+use crate::session::SessionType;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+pub const NOTIFICATIONS_CONFIG_KEY: &str = "GOOSE_NOTIFICATIONS";
+const WEBHOOK_TOKEN_SECRET: &str = "GOOSE_NOTIFICATIONS_TOKEN";
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const TITLE: &str = "goose";
+
+static SINKS: LazyLock<Mutex<Vec<Arc<dyn NotificationSink>>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    /// A tool call waits for the user's approval
+    ApprovalNeeded,
+    /// A scheduled job or headless task finished
+    TaskFinished,
+    /// A session or subagent is running out of its token budget or context window
+    BudgetWarning,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub kind: NotificationKind,
+    pub session_id: String,
+    pub session_type: Option<SessionType>,
+    pub title: String,
+    pub body: String,
+}
+
+impl Notification {
+    pub fn new(
+        kind: NotificationKind,
+        session_id: impl Into<String>,
+        session_type: Option<SessionType>,
+        body: impl Into<String>,
+    ) -> Self {
+        Self {
+            kind,
+            session_id: session_id.into(),
+            session_type,
+            title: TITLE.to_string(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Somewhere notifications are delivered
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    async fn send(&self, notification: &Notification) -> Result<()>;
+}
+
+/// Rings the terminal bell and prints the notification on stderr
+pub struct TerminalSink;
+
+#[async_trait]
+impl NotificationSink for TerminalSink {
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        ring_bell();
+        writeln!(
+            std::io::stderr(),
+            "{}: {}",
+            notification.title,
+            notification.body
+        )?;
+        Ok(())
+    }
+}
+
+/// A native notification: Notification Center on macOS, notify-send on Linux and a balloon
+/// tip on Windows
+pub struct DesktopSink;
+
+#[async_trait]
+impl NotificationSink for DesktopSink {
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        if show_desktop_notification(&notification.title, &notification.body) {
+            Ok(())
+        } else {
+            Err(anyhow!("No desktop notifier is available"))
+        }
+    }
+}
+
+/// POSTs the notification as JSON
+pub struct WebhookSink {
+    client: Client,
+    url: String,
+    token: Option<String>,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, token: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    async fn send(&self, notification: &Notification) -> Result<()> {
+        let mut request = self.client.post(&self.url).json(notification);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        request
+            .timeout(SEND_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelKind {
+    Terminal,
+    Desktop,
+    Webhook { url: String },
+}
+
+/// One entry of `GOOSE_NOTIFICATIONS`
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ChannelConfig {
+    #[serde(flatten)]
+    pub kind: ChannelKind,
+    /// Only send these kinds of notification; all of them when empty
+    #[serde(default)]
+    pub events: Vec<NotificationKind>,
+    /// Only send notifications from these types of session; all of them when empty
+    #[serde(default)]
+    pub session_types: Vec<SessionType>,
+}
+
+impl ChannelConfig {
+    fn accepts(&self, notification: &Notification) -> bool {
+        (self.events.is_empty() || self.events.contains(&notification.kind))
+            && (self.session_types.is_empty()
+                || notification
+                    .session_type
+                    .is_some_and(|session_type| self.session_types.contains(&session_type)))
+    }
+
+    fn sink(&self) -> Arc<dyn NotificationSink> {
+        match &self.kind {
+            ChannelKind::Terminal => Arc::new(TerminalSink),
+            ChannelKind::Desktop => Arc::new(DesktopSink),
+            ChannelKind::Webhook { url } => Arc::new(WebhookSink::new(
+                url.clone(),
+                Config::global()
+                    .get_secret::<String>(WEBHOOK_TOKEN_SECRET)
+                    .ok(),
+            )),
+        }
+    }
+}
+
+/// Send every notification to `sink` too, for the rest of the process
+pub fn register_sink(sink: Arc<dyn NotificationSink>) {
+    SINKS.lock().unwrap_or_else(|e| e.into_inner()).push(sink);
+}
+
+fn configured_channels() -> Vec<ChannelConfig> {
+    match Config::global().get_param::<Vec<ChannelConfig>>(NOTIFICATIONS_CONFIG_KEY) {
+        Ok(channels) => channels,
+        Err(ConfigError::NotFound(_)) => Vec::new(),
+        Err(e) => {
+            tracing::warn!("Ignoring malformed {}: {}", NOTIFICATIONS_CONFIG_KEY, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Send `notification` to the configured channels and registered sinks, without waiting for
+/// them
+pub fn notify(notification: Notification) {
+    let mut sinks: Vec<Arc<dyn NotificationSink>> = configured_channels()
+        .iter()
+        .filter(|channel| channel.accepts(&notification))
+        .map(ChannelConfig::sink)
+        .collect();
+    sinks.extend(
+        SINKS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned(),
+    );
+    if sinks.is_empty() {
+        return;
+    }
+    tokio::spawn(async move {
+        for sink in sinks {
+            match tokio::time::timeout(SEND_TIMEOUT, sink.send(&notification)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to send notification: {}", e),
+                Err(_) => tracing::warn!("Sending a notification timed out"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channels_filter_by_kind_and_session_type() {
+        let channels: Vec<ChannelConfig> = serde_yaml::from_str(
+            r#"
+- type: desktop
+  events: [approval_needed]
+- type: webhook
+  url: https://example.com/hook
+  session_types: [scheduled]
+- type: terminal
+"#,
+        )
+        .unwrap();
+        let approval = Notification::new(
+            NotificationKind::ApprovalNeeded,
+            "s1",
+            Some(SessionType::User),
+            "goose needs your approval to run developer__shell",
+        );
+        let finished = Notification::new(
+            NotificationKind::TaskFinished,
+            "s2",
+            Some(SessionType::Scheduled),
+            "scheduled task completed",
+        );
+        let accepted = |notification: &Notification| -> Vec<bool> {
+            channels.iter().map(|c| c.accepts(notification)).collect()
+        };
+
+        assert_eq!(accepted(&approval), [true, false, true]);
+        assert_eq!(accepted(&finished), [false, true, true]);
+        assert!(matches!(
+            &channels[1].kind,
+            ChannelKind::Webhook { url } if url == "https://example.com/hook"
+        ));
+    }
+}
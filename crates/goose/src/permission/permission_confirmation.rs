@@ -6,11 +6,31 @@ use utoipa::ToSchema;
 pub enum Permission {
     AlwaysAllow,
     AllowOnce,
+    /// Allow this tool in any session for the next hour
+    AllowForHour,
+    /// Allow this tool for the rest of the current session
+    AllowForSession,
+    /// Allow this tool in sessions running inside the current project directory
+    AllowForProject,
     Cancel,
     DenyOnce,
     AlwaysDeny,
 }
 
+impl Permission {
+    /// Whether this decision lets the tool call run
+    pub fn is_allow(&self) -> bool {
+        matches!(
+            self,
+            Permission::AlwaysAllow
+                | Permission::AllowOnce
+                | Permission::AllowForHour
+                | Permission::AllowForSession
+                | Permission::AllowForProject
+        )
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, ToSchema)]
 pub enum PrincipalType {
     Extension,
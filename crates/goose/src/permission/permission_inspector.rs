@@ -4,18 +4,24 @@ use crate::config::permission::PermissionLevel;
 use crate::config::{GooseMode, PermissionManager};
 use crate::conversation::message::{Message, ToolRequest};
 use crate::permission::permission_judge::{detect_read_only_tools, PermissionCheckResult};
+use crate::permission::profiles::{active_profile_name, configured_profiles, PermissionProfile};
+use crate::session::{Session, SessionManager};
 use crate::tool_inspection::{InspectionAction, InspectionResult, ToolInspector};
 use anyhow::Result;
 use async_trait::async_trait;
-use rmcp::model::Tool;
+use rmcp::model::{JsonObject, Tool};
 use std::collections::HashSet;
 use std::sync::{Arc, RwLock};
 
+/// Name of the session's permission profile and its definition, if it is configured
+type ActiveProfile = Option<(String, Option<PermissionProfile>)>;
+
 /// Permission Inspector that handles tool permission checking
 pub struct PermissionInspector {
     pub permission_manager: Arc<PermissionManager>,
     provider: SharedProvider,
     readonly_tools: RwLock<HashSet<String>>,
+    session_manager: Option<Arc<SessionManager>>,
 }
 
 impl PermissionInspector {
@@ -24,9 +30,49 @@ impl PermissionInspector {
             permission_manager,
             provider,
             readonly_tools: RwLock::new(HashSet::new()),
+            session_manager: None,
         }
     }
 
+    /// Lets project-scoped grants be matched against the session's working directory
+    pub fn with_session_manager(mut self, session_manager: Arc<SessionManager>) -> Self {
+        self.session_manager = Some(session_manager);
+        self
+    }
+
+    async fn load_session(&self, session_id: &str) -> Option<Session> {
+        let session_manager = self.session_manager.as_ref()?;
+        session_manager.get_session(session_id, false).await.ok()
+    }
+
+    /// Apply the session's permission profile, if any. An unknown profile name fails closed
+    /// by requiring approval for every tool.
+    fn profile_decision(
+        &self,
+        profile: &ActiveProfile,
+        tool_name: &str,
+        arguments: Option<&JsonObject>,
+    ) -> Option<(InspectionAction, String)> {
+        let (name, profile) = profile.as_ref()?;
+        let Some(profile) = profile else {
+            return Some((
+                InspectionAction::RequireApproval(None),
+                format!("Unknown permission profile '{}'", name),
+            ));
+        };
+        let level = profile.evaluate(
+            tool_name,
+            arguments,
+            self.is_readonly_annotated_tool(tool_name),
+        )?;
+        let action = match level {
+            PermissionLevel::AlwaysAllow => InspectionAction::Allow,
+            PermissionLevel::NeverAllow => InspectionAction::Deny,
+            PermissionLevel::AskBefore => InspectionAction::RequireApproval(None),
+        };
+        Some((action, format!("Permission profile '{}'", name)))
+    }
+
     // readonly_tools is per-agent to avoid concurrent session clobbering; write-annotated
     // tools are cached globally via PermissionManager.
     pub fn apply_tool_annotations(&self, tools: &[Tool]) {
@@ -131,17 +177,60 @@ impl ToolInspector for PermissionInspector {
         let mut results = Vec::new();
         let permission_manager = &self.permission_manager;
         let mut llm_detect_candidates: Vec<&ToolRequest> = Vec::new();
+        let profiles = configured_profiles();
+        let session = if permission_manager.has_grants() || !profiles.is_empty() {
+            self.load_session(session_id).await
+        } else {
+            None
+        };
+        let working_dir = session.as_ref().map(|s| s.working_dir.clone());
+        let profile: ActiveProfile = if profiles.is_empty() {
+            None
+        } else {
+            session.as_ref().and_then(active_profile_name).map(|name| {
+                let profile = profiles.get(&name).cloned();
+                (name, profile)
+            })
+        };
 
         for request in tool_requests {
             if let Ok(tool_call) = &request.tool_call {
                 let tool_name = &tool_call.name;
 
+                // Profiles are organization policy and take precedence over every other setting
+                if goose_mode != GooseMode::Chat {
+                    if let Some((action, reason)) =
+                        self.profile_decision(&profile, tool_name, tool_call.arguments.as_ref())
+                    {
+                        results.push(InspectionResult {
+                            tool_request_id: request.id.clone(),
+                            action,
+                            reason,
+                            confidence: 1.0,
+                            inspector_name: self.name().to_string(),
+                            finding_id: None,
+                        });
+                        continue;
+                    }
+                }
+
+                let granted = permission_manager.get_user_permission(tool_name)
+                    != Some(PermissionLevel::NeverAllow)
+                    && permission_manager
+                        .find_grant(tool_name, session_id, working_dir.as_deref())
+                        .is_some();
+
                 let action = match goose_mode {
                     GooseMode::Chat => continue,
                     GooseMode::Auto => InspectionAction::Allow,
                     GooseMode::Approve | GooseMode::SmartApprove => {
-                        // 1. Check user-defined permission first
-                        if let Some(level) = permission_manager.get_user_permission(tool_name) {
+                        // 1. A live scoped grant allows unless the user set never-allow
+                        if granted {
+                            InspectionAction::Allow
+                        // 2. Check user-defined permission
+                        } else if let Some(level) =
+                            permission_manager.get_user_permission(tool_name)
+                        {
                             match level {
                                 PermissionLevel::AlwaysAllow => InspectionAction::Allow,
                                 PermissionLevel::NeverAllow => InspectionAction::Deny,
@@ -149,19 +238,19 @@ impl ToolInspector for PermissionInspector {
                                     InspectionAction::RequireApproval(None)
                                 }
                             }
-                        // 2. Check if it's a smart-approved tool (annotation or cached LLM decision)
+                        // 3. Check if it's a smart-approved tool (annotation or cached LLM decision)
                         } else if self.is_readonly_annotated_tool(tool_name)
                             || (goose_mode == GooseMode::SmartApprove
                                 && permission_manager.get_smart_approve_permission(tool_name)
                                     == Some(PermissionLevel::AlwaysAllow))
                         {
                             InspectionAction::Allow
-                        // 3. Special case for extension management
+                        // 4. Special case for extension management
                         } else if tool_name == MANAGE_EXTENSIONS_TOOL_NAME_COMPLETE {
                             InspectionAction::RequireApproval(Some(
                                 "Extension management requires approval for security".to_string(),
                             ))
-                        // 4. Defer to LLM detection (SmartApprove, not yet cached)
+                        // 5. Defer to LLM detection (SmartApprove, not yet cached)
                         } else if goose_mode == GooseMode::SmartApprove
                             && permission_manager
                                 .get_smart_approve_permission(tool_name)
@@ -169,7 +258,7 @@ impl ToolInspector for PermissionInspector {
                         {
                             llm_detect_candidates.push(request);
                             continue;
-                        // 5. Default: require approval for unknown tools
+                        // 6. Default: require approval for unknown tools
                         } else {
                             InspectionAction::RequireApproval(None)
                         }
@@ -180,6 +269,8 @@ impl ToolInspector for PermissionInspector {
                     InspectionAction::Allow => {
                         if goose_mode == GooseMode::Auto {
                             "Auto mode - all tools approved".to_string()
+                        } else if granted {
+                            "Scoped permission grant allows this tool".to_string()
                         } else if self.is_readonly_annotated_tool(tool_name) {
                             "Tool annotated as read-only".to_string()
                         } else if goose_mode == GooseMode::SmartApprove {
@@ -264,6 +355,7 @@ impl ToolInspector for PermissionInspector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::permission::GrantScope;
     use rmcp::model::CallToolRequestParams;
     use rmcp::object;
     use std::sync::Arc;
@@ -304,4 +396,74 @@ mod tests {
             .unwrap();
         assert_eq!(results[0].action, expected);
     }
+
+    #[tokio::test]
+    async fn test_session_grant_allows_in_approve_mode() {
+        let pm = Arc::new(PermissionManager::new(tempfile::tempdir().unwrap().keep()));
+        pm.add_grant(
+            "tool",
+            GrantScope::Session {
+                session_id: goose_test_support::TEST_SESSION_ID.to_string(),
+            },
+            None,
+        );
+        let inspector = PermissionInspector::new(pm.clone(), Arc::new(Mutex::new(None)));
+        let req = ToolRequest {
+            id: "req".into(),
+            tool_call: Ok(CallToolRequestParams::new("tool").with_arguments(object!({}))),
+            metadata: None,
+            tool_meta: None,
+        };
+
+        let results = inspector
+            .inspect(
+                goose_test_support::TEST_SESSION_ID,
+                std::slice::from_ref(&req),
+                &[],
+                GooseMode::Approve,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results[0].action, InspectionAction::Allow);
+
+        pm.update_user_permission("tool", PermissionLevel::NeverAllow);
+        let results = inspector
+            .inspect(
+                goose_test_support::TEST_SESSION_ID,
+                &[req],
+                &[],
+                GooseMode::Approve,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results[0].action, InspectionAction::Deny);
+    }
+
+    #[test]
+    fn test_profile_decision() {
+        let pm = Arc::new(PermissionManager::new(tempfile::tempdir().unwrap().keep()));
+        let inspector = PermissionInspector::new(pm, Arc::new(Mutex::new(None)));
+        *inspector.readonly_tools.write().unwrap() = ["tree".to_string()].into_iter().collect();
+        let reviewer: ActiveProfile = Some((
+            "reviewer".to_string(),
+            Some(serde_yaml::from_str(
+                "rules:\n  - read_only: true\n    permission: always_allow\ndefault: never_allow",
+            )
+            .unwrap()),
+        ));
+
+        let (action, _) = inspector.profile_decision(&reviewer, "tree", None).unwrap();
+        assert_eq!(action, InspectionAction::Allow);
+        let (action, reason) = inspector
+            .profile_decision(&reviewer, "shell", None)
+            .unwrap();
+        assert_eq!(action, InspectionAction::Deny);
+        assert_eq!(reason, "Permission profile 'reviewer'");
+
+        let unknown: ActiveProfile = Some(("missing".to_string(), None));
+        let (action, _) = inspector.profile_decision(&unknown, "tree", None).unwrap();
+        assert_eq!(action, InspectionAction::RequireApproval(None));
+
+        assert!(inspector.profile_decision(&None, "shell", None).is_none());
+    }
 }
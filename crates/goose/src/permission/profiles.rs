@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+use rmcp::model::JsonObject;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::config::permission::PermissionLevel;
+use crate::config::Config;
+use crate::session::extension_data::ExtensionState;
+use crate::session::Session;
+
+/// A single rule in a permission profile. All conditions present must match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PermissionRule {
+    /// Glob matched against the tool name, e.g. `shell` or `github__*`
+    #[serde(default = "match_any")]
+    pub tool: String,
+    /// Glob matched against the `command` argument of shell-style tools, e.g. `git push*`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Only match tools whose read-only annotation equals this value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    pub permission: PermissionLevel,
+}
+
+fn match_any() -> String {
+    "*".to_string()
+}
+
+/// Named set of rules, configured under `GOOSE_PERMISSION_PROFILES`.
+/// Rules are checked in order and the first match decides.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PermissionProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<PermissionRule>,
+    /// Applied when no rule matches. Without it, the regular permission settings decide.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<PermissionLevel>,
+}
+
+/// Per-session profile selection, stored in the session's extension data.
+/// `name: None` explicitly selects no profile, overriding recipe and global settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionProfileState {
+    pub name: Option<String>,
+}
+
+impl ExtensionState for PermissionProfileState {
+    const EXTENSION_NAME: &'static str = "permission_profile";
+    const VERSION: &'static str = "v0";
+}
+
+impl PermissionProfileState {
+    pub fn new(name: Option<String>) -> Self {
+        Self { name }
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    glob::Pattern::new(pattern)
+        .map(|p| p.matches(text))
+        .unwrap_or(false)
+}
+
+/// Split a shell command line into the individual commands it chains together
+fn command_segments(command: &str) -> Vec<&str> {
+    command
+        .split(['\n', ';', '|', '&'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl PermissionRule {
+    fn matches(&self, tool_name: &str, arguments: Option<&JsonObject>, read_only: bool) -> bool {
+        if !glob_match(&self.tool, tool_name) {
+            return false;
+        }
+        if self.read_only.is_some_and(|expected| expected != read_only) {
+            return false;
+        }
+        let Some(pattern) = &self.command else {
+            return true;
+        };
+        let Some(command) = arguments
+            .and_then(|args| args.get("command"))
+            .and_then(|c| c.as_str())
+        else {
+            return false;
+        };
+        let segments = command_segments(command);
+        if segments.is_empty() {
+            return false;
+        }
+        // Restrictive rules apply if any chained command matches; an allow rule only
+        // applies when every chained command does, so `ls && git push` is not allowed by `ls*`.
+        if self.permission == PermissionLevel::AlwaysAllow {
+            segments.iter().all(|segment| glob_match(pattern, segment))
+        } else {
+            segments.iter().any(|segment| glob_match(pattern, segment))
+        }
+    }
+}
+
+impl PermissionProfile {
+    /// Decide the permission for a tool call, or None to defer to the regular settings
+    pub fn evaluate(
+        &self,
+        tool_name: &str,
+        arguments: Option<&JsonObject>,
+        read_only: bool,
+    ) -> Option<PermissionLevel> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(tool_name, arguments, read_only))
+            .map(|rule| rule.permission.clone())
+            .or_else(|| self.default.clone())
+    }
+}
+
+pub fn configured_profiles() -> HashMap<String, PermissionProfile> {
+    Config::global()
+        .get_param::<HashMap<String, PermissionProfile>>("GOOSE_PERMISSION_PROFILES")
+        .unwrap_or_default()
+}
+
+/// The profile the organization's config layer sets, which turning profiles off for a
+/// session does not turn off
+pub fn org_profile_name() -> Option<String> {
+    Config::global().get_org_param::<String>("GOOSE_PERMISSION_PROFILE")
+}
+
+/// Name of the profile active for a session, in order of precedence:
+/// 1. Explicit per-session selection (`permission_profile.v0` extension data); selecting
+///    no profile falls back to the one the org config sets, if any
+/// 2. The `permission_profile` setting of the recipe the session was started from
+/// 3. The global `GOOSE_PERMISSION_PROFILE` config value
+pub fn active_profile_name(session: &Session) -> Option<String> {
+    if let Some(state) = PermissionProfileState::from_extension_data(&session.extension_data) {
+        return state.name.or_else(org_profile_name);
+    }
+    if let Some(name) = session
+        .recipe
+        .as_ref()
+        .and_then(|r| r.settings.as_ref())
+        .and_then(|s| s.permission_profile.clone())
+    {
+        return Some(name);
+    }
+    Config::global()
+        .get_param::<String>("GOOSE_PERMISSION_PROFILE")
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::object;
+
+    fn profiles() -> HashMap<String, PermissionProfile> {
+        serde_yaml::from_str(
+            r#"
+reviewer:
+  description: Read-only access
+  rules:
+    - read_only: true
+      permission: always_allow
+  default: never_allow
+operator:
+  rules:
+    - tool: shell
+      command: "git push*"
+      permission: never_allow
+    - tool: shell
+      permission: always_allow
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn reviewer_only_allows_read_only_tools() {
+        let reviewer = &profiles()["reviewer"];
+        assert_eq!(
+            reviewer.evaluate("tree", None, true),
+            Some(PermissionLevel::AlwaysAllow)
+        );
+        assert_eq!(
+            reviewer.evaluate("write", None, false),
+            Some(PermissionLevel::NeverAllow)
+        );
+    }
+
+    #[test]
+    fn operator_blocks_git_push_even_when_chained() {
+        let operator = &profiles()["operator"];
+        let args = object!({"command": "cargo test"});
+        assert_eq!(
+            operator.evaluate("shell", Some(&args), false),
+            Some(PermissionLevel::AlwaysAllow)
+        );
+        let args = object!({"command": "git commit -am wip && git push origin main"});
+        assert_eq!(
+            operator.evaluate("shell", Some(&args), false),
+            Some(PermissionLevel::NeverAllow)
+        );
+        assert_eq!(operator.evaluate("write", None, false), None);
+    }
+
+    #[test]
+    fn allow_rules_require_every_chained_command_to_match() {
+        let rule = PermissionRule {
+            tool: "shell".to_string(),
+            command: Some("ls*".to_string()),
+            read_only: None,
+            permission: PermissionLevel::AlwaysAllow,
+        };
+        assert!(rule.matches("shell", Some(&object!({"command": "ls -la"})), false));
+        assert!(!rule.matches("shell", Some(&object!({"command": "ls; rm -rf /"})), false));
+    }
+}
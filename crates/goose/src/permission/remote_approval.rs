@@ -0,0 +1,622 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::config::Config;
+use crate::permission::Permission;
+use crate::session::{Session, SessionType};
+
+const SLACK_API_BASE: &str = "https://slack.com/api";
+const HTTP_TIMEOUT: Duration = Duration::from_secs(15);
+const APPROVE_REACTIONS: &[&str] = &["white_check_mark", "heavy_check_mark", "+1"];
+const DENY_REACTIONS: &[&str] = &["x", "no_entry", "-1"];
+
+/// Decision applied when nobody answers before the timeout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteDecision {
+    Approve,
+    #[default]
+    Deny,
+}
+
+impl RemoteDecision {
+    pub fn as_permission(&self) -> Permission {
+        match self {
+            RemoteDecision::Approve => Permission::AllowOnce,
+            RemoteDecision::Deny => Permission::DenyOnce,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "transport", rename_all = "snake_case")]
+pub enum TransportConfig {
+    /// POST pending requests to `url`, then poll `{poll_url}/{id}` for a decision
+    Webhook {
+        url: String,
+        #[serde(default)]
+        poll_url: Option<String>,
+    },
+    /// Post to a Slack channel and read the decision from message reactions.
+    /// Requires the `SLACK_BOT_TOKEN` secret with chat:write and reactions:read scopes.
+    /// Only reactions from the Slack user IDs in `approvers` count.
+    Slack {
+        channel: String,
+        #[serde(default)]
+        approvers: Vec<String>,
+    },
+}
+
+/// `GOOSE_REMOTE_APPROVAL` config value
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteApprovalConfig {
+    #[serde(flatten)]
+    pub transport: TransportConfig,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    #[serde(default)]
+    pub default_decision: RemoteDecision,
+    /// Session types that route approvals remotely; interactive sessions keep local prompts
+    #[serde(default = "default_session_types")]
+    pub session_types: Vec<SessionType>,
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+fn default_poll_interval_secs() -> u64 {
+    5
+}
+
+fn default_session_types() -> Vec<SessionType> {
+    vec![SessionType::Scheduled]
+}
+
+/// A pending tool call sent to a remote approver
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalRequest {
+    pub id: String,
+    pub session_id: String,
+    pub session_name: String,
+    pub tool_name: String,
+    pub arguments: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub security_message: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ApprovalRequest {
+    fn summary(&self) -> String {
+        let arguments =
+            serde_json::to_string_pretty(&self.arguments).unwrap_or_else(|_| "{}".to_string());
+        let mut text = format!(
+            "goose wants to run `{}` in session \"{}\"\n```{}```",
+            self.tool_name, self.session_name, arguments
+        );
+        if let Some(message) = &self.security_message {
+            text.push_str(&format!("\n⚠️ {}", message));
+        }
+        text
+    }
+}
+
+#[async_trait]
+pub trait ApprovalTransport: Send + Sync {
+    /// Deliver the request, returning a transport-specific reference used for polling
+    async fn send(&self, request: &ApprovalRequest) -> Result<String>;
+
+    /// Check whether a decision has been made yet
+    async fn poll(
+        &self,
+        request: &ApprovalRequest,
+        reference: &str,
+    ) -> Result<Option<RemoteDecision>>;
+
+    /// Report the final outcome back to the approver, if the transport supports it
+    async fn resolve(
+        &self,
+        _request: &ApprovalRequest,
+        _reference: &str,
+        _decision: RemoteDecision,
+        _timed_out: bool,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct WebhookTransport {
+    client: Client,
+    url: String,
+    poll_url: String,
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WebhookDecision {
+    decision: Option<RemoteDecision>,
+}
+
+impl WebhookTransport {
+    pub fn new(url: String, poll_url: Option<String>, token: Option<String>) -> Self {
+        let poll_url = poll_url.unwrap_or_else(|| url.clone());
+        Self {
+            client: Client::new(),
+            url,
+            poll_url,
+            token,
+        }
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+}
+
+#[async_trait]
+impl ApprovalTransport for WebhookTransport {
+    async fn send(&self, request: &ApprovalRequest) -> Result<String> {
+        self.authorize(self.client.post(&self.url))
+            .json(request)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(format!(
+            "{}/{}",
+            self.poll_url.trim_end_matches('/'),
+            request.id
+        ))
+    }
+
+    async fn poll(
+        &self,
+        _request: &ApprovalRequest,
+        reference: &str,
+    ) -> Result<Option<RemoteDecision>> {
+        let response = self
+            .authorize(self.client.get(reference))
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?;
+        if matches!(
+            response.status(),
+            StatusCode::NOT_FOUND | StatusCode::NO_CONTENT | StatusCode::ACCEPTED
+        ) {
+            return Ok(None);
+        }
+        let body: WebhookDecision = response.error_for_status()?.json().await?;
+        Ok(body.decision)
+    }
+}
+
+pub struct SlackTransport {
+    client: Client,
+    token: String,
+    channel: String,
+    approvers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct SlackResponse {
+    ok: bool,
+    error: Option<String>,
+    ts: Option<String>,
+    channel: Option<String>,
+    message: Option<SlackMessage>,
+}
+
+#[derive(Deserialize)]
+struct SlackMessage {
+    #[serde(default)]
+    reactions: Vec<SlackReaction>,
+}
+
+#[derive(Deserialize)]
+struct SlackReaction {
+    name: String,
+    #[serde(default)]
+    users: Vec<String>,
+}
+
+impl SlackTransport {
+    pub fn new(token: String, channel: String, approvers: Vec<String>) -> Self {
+        Self {
+            client: Client::new(),
+            token,
+            channel,
+            approvers,
+        }
+    }
+
+    async fn call(&self, method: &str, body: Value) -> Result<SlackResponse> {
+        let response: SlackResponse = self
+            .client
+            .post(format!("{}/{}", SLACK_API_BASE, method))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !response.ok {
+            return Err(anyhow!(
+                "Slack {} failed: {}",
+                method,
+                response.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        Ok(response)
+    }
+}
+
+/// Slack references are `channel:ts` so replies land on the message that was posted
+fn split_slack_reference(reference: &str) -> Result<(&str, &str)> {
+    reference
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Invalid Slack message reference: {}", reference))
+}
+
+fn decision_from_reactions(
+    reactions: &[SlackReaction],
+    approvers: &[String],
+) -> Option<RemoteDecision> {
+    // Reactions from anyone outside the allowlist are ignored
+    let reacted = |names: &[&str]| {
+        reactions.iter().any(|r| {
+            names.contains(&r.name.as_str()) && r.users.iter().any(|u| approvers.contains(u))
+        })
+    };
+    // A deny reaction wins over an approve reaction if both are present
+    if reacted(DENY_REACTIONS) {
+        return Some(RemoteDecision::Deny);
+    }
+    reacted(APPROVE_REACTIONS).then_some(RemoteDecision::Approve)
+}
+
+#[async_trait]
+impl ApprovalTransport for SlackTransport {
+    async fn send(&self, request: &ApprovalRequest) -> Result<String> {
+        let text = format!(
+            "{}\nReact with :white_check_mark: to approve or :x: to deny before {}.",
+            request.summary(),
+            request.expires_at.format("%H:%M UTC")
+        );
+        let response = self
+            .call(
+                "chat.postMessage",
+                serde_json::json!({ "channel": self.channel, "text": text }),
+            )
+            .await?;
+        let channel = response.channel.unwrap_or_else(|| self.channel.clone());
+        let ts = response
+            .ts
+            .ok_or_else(|| anyhow!("Slack chat.postMessage returned no timestamp"))?;
+        Ok(format!("{}:{}", channel, ts))
+    }
+
+    async fn poll(
+        &self,
+        _request: &ApprovalRequest,
+        reference: &str,
+    ) -> Result<Option<RemoteDecision>> {
+        let (channel, ts) = split_slack_reference(reference)?;
+        let response: SlackResponse = self
+            .client
+            .get(format!("{}/reactions.get", SLACK_API_BASE))
+            .bearer_auth(&self.token)
+            .query(&[("channel", channel), ("timestamp", ts), ("full", "true")])
+            .timeout(HTTP_TIMEOUT)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if !response.ok {
+            return Err(anyhow!(
+                "Slack reactions.get failed: {}",
+                response.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        Ok(response
+            .message
+            .and_then(|m| decision_from_reactions(&m.reactions, &self.approvers)))
+    }
+
+    async fn resolve(
+        &self,
+        request: &ApprovalRequest,
+        reference: &str,
+        decision: RemoteDecision,
+        timed_out: bool,
+    ) -> Result<()> {
+        let (channel, ts) = split_slack_reference(reference)?;
+        let outcome = match (decision, timed_out) {
+            (RemoteDecision::Approve, false) => "✅ Approved",
+            (RemoteDecision::Deny, false) => "❌ Denied",
+            (RemoteDecision::Approve, true) => "⏱️ No response, approved by default",
+            (RemoteDecision::Deny, true) => "⏱️ No response, denied by default",
+        };
+        self.call(
+            "chat.update",
+            serde_json::json!({
+                "channel": channel,
+                "ts": ts,
+                "text": format!("{}\n*{}*", request.summary(), outcome),
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Routes tool approvals for headless sessions to a remote approver
+pub struct RemoteApproval {
+    transport: Arc<dyn ApprovalTransport>,
+    timeout: Duration,
+    poll_interval: Duration,
+    default_decision: RemoteDecision,
+}
+
+impl RemoteApproval {
+    pub fn new(config: &RemoteApprovalConfig, transport: Arc<dyn ApprovalTransport>) -> Self {
+        Self {
+            transport,
+            timeout: Duration::from_secs(config.timeout_secs),
+            poll_interval: Duration::from_secs(config.poll_interval_secs.max(1)),
+            default_decision: config.default_decision,
+        }
+    }
+
+    /// Returns the remote approval channel for this session, if one is configured for its type
+    pub fn for_session(session: &Session) -> Option<Self> {
+        let global = Config::global();
+        let config = global
+            .get_param::<RemoteApprovalConfig>("GOOSE_REMOTE_APPROVAL")
+            .ok()?;
+        if !config.session_types.contains(&session.session_type) {
+            return None;
+        }
+
+        let transport: Arc<dyn ApprovalTransport> = match &config.transport {
+            TransportConfig::Webhook { url, poll_url } => Arc::new(WebhookTransport::new(
+                url.clone(),
+                poll_url.clone(),
+                global
+                    .get_secret::<String>("GOOSE_REMOTE_APPROVAL_TOKEN")
+                    .ok(),
+            )),
+            TransportConfig::Slack { channel, approvers } => {
+                if approvers.is_empty() {
+                    tracing::warn!("Remote approval via Slack requires a list of approvers");
+                    return None;
+                }
+                let Ok(token) = global.get_secret::<String>("SLACK_BOT_TOKEN") else {
+                    tracing::warn!("Remote approval via Slack requires the SLACK_BOT_TOKEN secret");
+                    return None;
+                };
+                Arc::new(SlackTransport::new(
+                    token,
+                    channel.clone(),
+                    approvers.clone(),
+                ))
+            }
+        };
+        Some(Self::new(&config, transport))
+    }
+
+    pub fn request(
+        &self,
+        session: &Session,
+        request_id: &str,
+        tool_name: &str,
+        arguments: Value,
+        security_message: Option<String>,
+    ) -> ApprovalRequest {
+        ApprovalRequest {
+            id: request_id.to_string(),
+            session_id: session.id.clone(),
+            session_name: session.name.clone(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            security_message,
+            expires_at: Utc::now() + chrono::Duration::from_std(self.timeout).unwrap_or_default(),
+        }
+    }
+
+    /// Send the request and wait for a decision, falling back to the configured default
+    /// when delivery fails or the timeout elapses. Returns `None` if cancelled.
+    pub async fn await_decision(
+        &self,
+        request: ApprovalRequest,
+        cancel_token: CancellationToken,
+    ) -> Option<Permission> {
+        let default = self.default_decision;
+        let reference = match self.transport.send(&request).await {
+            Ok(reference) => reference,
+            Err(e) => {
+                tracing::error!(
+                    tool_name = %request.tool_name,
+                    error = %e,
+                    "Failed to deliver remote approval request, applying default decision"
+                );
+                return Some(default.as_permission());
+            }
+        };
+
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let (decision, timed_out) = loop {
+            tokio::select! {
+                _ = cancel_token.cancelled() => return None,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+            match self.transport.poll(&request, &reference).await {
+                Ok(Some(decision)) => break (decision, false),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to poll remote approval: {}", e),
+            }
+            if tokio::time::Instant::now() >= deadline {
+                break (default, true);
+            }
+        };
+
+        tracing::info!(
+            tool_name = %request.tool_name,
+            decision = ?decision,
+            timed_out,
+            "Remote approval resolved"
+        );
+        if let Err(e) = self
+            .transport
+            .resolve(&request, &reference, decision, timed_out)
+            .await
+        {
+            tracing::debug!("Failed to report remote approval outcome: {}", e);
+        }
+        Some(decision.as_permission())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct ScriptedTransport {
+        polls: AtomicUsize,
+        answer_after: Option<usize>,
+        decision: RemoteDecision,
+    }
+
+    #[async_trait]
+    impl ApprovalTransport for ScriptedTransport {
+        async fn send(&self, request: &ApprovalRequest) -> Result<String> {
+            Ok(request.id.clone())
+        }
+
+        async fn poll(
+            &self,
+            _request: &ApprovalRequest,
+            _reference: &str,
+        ) -> Result<Option<RemoteDecision>> {
+            let polls = self.polls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(self
+                .answer_after
+                .filter(|after| polls >= *after)
+                .map(|_| self.decision))
+        }
+    }
+
+    fn approval(
+        timeout_ms: u64,
+        default_decision: RemoteDecision,
+        answer_after: Option<usize>,
+    ) -> RemoteApproval {
+        RemoteApproval {
+            transport: Arc::new(ScriptedTransport {
+                polls: AtomicUsize::new(0),
+                answer_after,
+                decision: RemoteDecision::Approve,
+            }),
+            timeout: Duration::from_millis(timeout_ms),
+            poll_interval: Duration::from_millis(10),
+            default_decision,
+        }
+    }
+
+    fn request() -> ApprovalRequest {
+        ApprovalRequest {
+            id: "req".to_string(),
+            session_id: "session".to_string(),
+            session_name: "Scheduled job".to_string(),
+            tool_name: "developer__shell".to_string(),
+            arguments: serde_json::json!({"command": "ls"}),
+            security_message: None,
+            expires_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_remote_decision() {
+        let permission = approval(1_000, RemoteDecision::Deny, Some(2))
+            .await_decision(request(), CancellationToken::new())
+            .await;
+        assert_eq!(permission, Some(Permission::AllowOnce));
+    }
+
+    #[tokio::test]
+    async fn timeout_applies_default_decision() {
+        let permission = approval(50, RemoteDecision::Deny, None)
+            .await_decision(request(), CancellationToken::new())
+            .await;
+        assert_eq!(permission, Some(Permission::DenyOnce));
+    }
+
+    #[tokio::test]
+    async fn cancellation_stops_waiting() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let permission = approval(1_000, RemoteDecision::Approve, None)
+            .await_decision(request(), token)
+            .await;
+        assert_eq!(permission, None);
+    }
+
+    fn reaction(name: &str, user: &str) -> SlackReaction {
+        SlackReaction {
+            name: name.to_string(),
+            users: vec![user.to_string()],
+        }
+    }
+
+    #[test]
+    fn deny_reaction_wins() {
+        let approvers = vec!["U1".to_string()];
+        let reactions = vec![reaction("white_check_mark", "U1"), reaction("x", "U1")];
+        assert_eq!(
+            decision_from_reactions(&reactions, &approvers),
+            Some(RemoteDecision::Deny)
+        );
+        assert_eq!(decision_from_reactions(&[], &approvers), None);
+    }
+
+    #[test]
+    fn reactions_outside_the_allowlist_are_ignored() {
+        let approvers = vec!["U1".to_string()];
+        let reactions = vec![reaction("white_check_mark", "U2")];
+        assert_eq!(decision_from_reactions(&reactions, &approvers), None);
+
+        let reactions = vec![reaction("x", "U2"), reaction("+1", "U1")];
+        assert_eq!(
+            decision_from_reactions(&reactions, &approvers),
+            Some(RemoteDecision::Approve)
+        );
+    }
+
+    #[test]
+    fn config_parses_with_defaults() {
+        let config: RemoteApprovalConfig = serde_yaml::from_str(
+            "transport: slack\nchannel: C123\napprovers: [U1]\ndefault_decision: approve",
+        )
+        .unwrap();
+        let TransportConfig::Slack { approvers, .. } = &config.transport else {
+            panic!("expected the Slack transport");
+        };
+        assert_eq!(approvers, &["U1"]);
+        assert_eq!(config.timeout_secs, 600);
+        assert_eq!(config.default_decision, RemoteDecision::Approve);
+        assert_eq!(config.session_types, vec![SessionType::Scheduled]);
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::config::paths::Paths;
 use crate::config::{get_enabled_extensions, Config};
+use crate::providers::errors::ProviderErrorCode;
 use crate::session::session_manager::CURRENT_SCHEMA_VERSION;
 use crate::session::SessionManager;
 #[cfg(target_os = "windows")]
@@ -432,6 +433,17 @@ async fn send_session_event(installation: &InstallationData) -> Result<(), Strin
 // Error Classification
 // ============================================================================
 pub fn classify_error(error: &str) -> &'static str {
+    // Provider errors are reported with their code as the type; the categories that match
+    // the ones below keep their names, the rest keep the code
+    if let Some(code) = ProviderErrorCode::parse(error) {
+        return match code {
+            ProviderErrorCode::Auth => "auth_error",
+            ProviderErrorCode::RateLimit => "rate_limit",
+            ProviderErrorCode::Network => "network_error",
+            code => code.as_str(),
+        };
+    }
+
     let error_lower = error.to_lowercase();
 
     if error_lower.contains("network") || error_lower.contains("fetch") {
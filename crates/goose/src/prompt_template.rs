@@ -9,7 +9,15 @@ static CORE_PROMPTS_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/src/prompts");
 static TEMPLATE_REGISTRY: &[(&str, &str)] = &[
     (
         "system.md",
-        "Main system prompt that defines goose's personality and behavior",
+        "Identity section of the system prompt that defines goose's personality. A customized one is the whole prompt and replaces the tool guidance and response guidelines too",
+    ),
+    (
+        "tool_guidance.md",
+        "System prompt section describing the active extensions and their instructions",
+    ),
+    (
+        "response_guidelines.md",
+        "System prompt section with guidelines for formatting responses",
     ),
     (
         "compaction.md",
@@ -17,6 +17,7 @@ use super::formats::anthropic::{
 use super::openai_compatible::handle_status_openai_compat;
 use super::openai_compatible::map_http_error_to_provider_error;
 use super::retry::ProviderRetry;
+use super::server_tools::{self, ServerTool};
 use crate::config::declarative_providers::DeclarativeProviderConfig;
 use crate::conversation::message::Message;
 use crate::model::ModelConfig;
@@ -127,7 +128,7 @@ impl AnthropicProvider {
         })
     }
 
-    fn get_conditional_headers(&self) -> Vec<(&str, &str)> {
+    fn get_conditional_headers(&self, server_tools: &[ServerTool]) -> Vec<(&str, &str)> {
         let mut headers = Vec::new();
 
         if self.model.model_name.starts_with("claude-3-7-sonnet-") {
@@ -136,6 +137,9 @@ impl AnthropicProvider {
             }
             headers.push(("anthropic-beta", "token-efficient-tools-2025-02-19"));
         }
+        if server_tools.contains(&ServerTool::CodeExecution) {
+            headers.push(("anthropic-beta", "code-execution-2025-08-25"));
+        }
 
         headers
     }
@@ -221,13 +225,14 @@ impl Provider for AnthropicProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let mut payload = create_request(model_config, system, messages, tools)?;
+        let server_tools = server_tools::for_request(model_config, tools);
+        let mut payload = create_request(model_config, system, messages, tools, &server_tools)?;
         payload
             .as_object_mut()
             .unwrap()
             .insert("stream".to_string(), Value::Bool(true));
 
-        let conditional_headers = self.get_conditional_headers();
+        let conditional_headers = self.get_conditional_headers(&server_tools);
         let mut log = RequestLog::start(model_config, &payload)?;
 
         let response = self
@@ -253,7 +258,7 @@ impl Provider for AnthropicProvider {
             let message_stream = response_to_streaming_message(framed);
             pin!(message_stream);
             while let Some(message) = futures::StreamExt::next(&mut message_stream).await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
                 log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                 yield (message, usage);
             }
@@ -9,7 +9,7 @@ use serde_json::Value;
 use std::fmt;
 use std::fs::read_to_string;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub struct ApiClient {
     client: Client,
@@ -18,7 +18,6 @@ pub struct ApiClient {
     default_headers: HeaderMap,
     default_query: Vec<(String, String)>,
     timeout: Duration,
-    tls_config: Option<TlsConfig>,
 }
 
 pub enum AuthMethod {
@@ -200,6 +199,7 @@ pub struct ApiRequestBuilder<'a> {
     path: &'a str,
     headers: HeaderMap,
     session_id: Option<&'a str>,
+    timeout: Option<Duration>,
 }
 
 impl ApiClient {
@@ -207,65 +207,21 @@ impl ApiClient {
         Self::with_timeout(host, auth, Duration::from_secs(600))
     }
 
+    /// Requests go through the shared connection pool; the timeout and default headers are
+    /// applied to each request rather than to the pool.
     pub fn with_timeout(host: String, auth: AuthMethod, timeout: Duration) -> Result<Self> {
-        let mut client_builder = Client::builder().timeout(timeout);
-
-        // Configure TLS if needed
-        let tls_config = TlsConfig::from_config()?;
-        if let Some(ref config) = tls_config {
-            client_builder = Self::configure_tls(client_builder, config)?;
-        }
-
-        let client = client_builder.build()?;
-
         Ok(Self {
-            client,
+            client: super::http_client::shared_client()?,
             host,
             auth,
             default_headers: HeaderMap::new(),
             default_query: Vec::new(),
             timeout,
-            tls_config,
         })
     }
 
-    fn rebuild_client(&mut self) -> Result<()> {
-        let mut client_builder = Client::builder()
-            .timeout(self.timeout)
-            .default_headers(self.default_headers.clone());
-
-        // Configure TLS if needed
-        if let Some(ref tls_config) = self.tls_config {
-            client_builder = Self::configure_tls(client_builder, tls_config)?;
-        }
-
-        self.client = client_builder.build()?;
-        Ok(())
-    }
-
-    /// Configure TLS settings on a reqwest ClientBuilder
-    fn configure_tls(
-        mut client_builder: reqwest::ClientBuilder,
-        tls_config: &TlsConfig,
-    ) -> Result<reqwest::ClientBuilder> {
-        if tls_config.is_configured() {
-            // Load client identity (certificate + private key)
-            if let Some(identity) = tls_config.load_identity()? {
-                client_builder = client_builder.identity(identity);
-            }
-
-            // Load CA certificates
-            let ca_certs = tls_config.load_ca_certificates()?;
-            for ca_cert in ca_certs {
-                client_builder = client_builder.add_root_certificate(ca_cert);
-            }
-        }
-        Ok(client_builder)
-    }
-
     pub fn with_headers(mut self, headers: HeaderMap) -> Result<Self> {
         self.default_headers = headers;
-        self.rebuild_client()?;
         Ok(self)
     }
 
@@ -278,7 +234,6 @@ impl ApiClient {
         let header_name = HeaderName::from_bytes(key.as_bytes())?;
         let header_value = HeaderValue::from_str(value)?;
         self.default_headers.insert(header_name, header_value);
-        self.rebuild_client()?;
         Ok(self)
     }
 
@@ -293,6 +248,7 @@ impl ApiClient {
             session_id: session_id.filter(|id| !id.is_empty()),
             path,
             headers: HeaderMap::new(),
+            timeout: None,
         }
     }
 
@@ -368,6 +324,12 @@ impl<'a> ApiRequestBuilder<'a> {
         self
     }
 
+    /// Override the client's timeout for this request, such as for a long download
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
     pub async fn api_post(self, payload: &Value) -> Result<ApiResponse> {
         let response = self.response_post(payload).await?;
         ApiResponse::from_response(response).await
@@ -375,12 +337,12 @@ impl<'a> ApiRequestBuilder<'a> {
 
     pub async fn response_post(self, payload: &Value) -> Result<Response> {
         let request = self.send_request(|url, client| client.post(url)).await?;
-        Ok(request.json(payload).send().await?)
+        send(request.json(payload)).await
     }
 
     pub async fn multipart_post(self, form: reqwest::multipart::Form) -> Result<Response> {
         let request = self.send_request(|url, client| client.post(url)).await?;
-        Ok(request.multipart(form).send().await?)
+        send(request.multipart(form)).await
     }
 
     pub async fn api_get(self) -> Result<ApiResponse> {
@@ -390,7 +352,7 @@ impl<'a> ApiRequestBuilder<'a> {
 
     pub async fn response_get(self) -> Result<Response> {
         let request = self.send_request(|url, client| client.get(url)).await?;
-        Ok(request.send().await?)
+        send(request).await
     }
 
     async fn send_request<F>(&self, request_builder: F) -> Result<reqwest::RequestBuilder>
@@ -406,7 +368,9 @@ impl<'a> ApiRequestBuilder<'a> {
             headers.insert(header_name, header_value);
         }
 
-        let mut request = request_builder(url, &self.client.client);
+        let mut request = request_builder(url, &self.client.client)
+            .timeout(self.timeout.unwrap_or(self.client.timeout))
+            .headers(self.client.default_headers.clone());
         request = request.headers(headers);
 
         request = match &self.client.auth {
@@ -429,6 +393,13 @@ impl<'a> ApiRequestBuilder<'a> {
     }
 }
 
+async fn send(request: reqwest::RequestBuilder) -> Result<Response> {
+    let started = Instant::now();
+    let response = request.send().await?;
+    super::http_client::record_response(started, &response);
+    Ok(response)
+}
+
 impl fmt::Debug for ApiClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ApiClient")
@@ -479,4 +450,32 @@ mod tests {
             assert_eq!(actual, expected);
         });
     }
+
+    #[tokio::test]
+    async fn test_default_headers_and_timeout_apply_per_request() {
+        let client = ApiClient::with_timeout(
+            "http://localhost:8080".to_string(),
+            AuthMethod::NoAuth,
+            Duration::from_secs(5),
+        )
+        .unwrap()
+        .with_header("x-default", "client")
+        .unwrap()
+        .with_header("x-override", "client")
+        .unwrap();
+
+        let request = client
+            .request(None, "/test")
+            .header("x-override", "request")
+            .unwrap()
+            .send_request(|url, client| client.get(url))
+            .await
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(request.headers()["x-default"], "client");
+        assert_eq!(request.headers()["x-override"], "request");
+        assert_eq!(request.timeout(), Some(&Duration::from_secs(5)));
+    }
 }
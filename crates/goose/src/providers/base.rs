@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use super::canonical::{map_to_canonical_model, CanonicalModelRegistry};
 use super::errors::ProviderError;
 use super::retry::RetryConfig;
+use super::server_tools::ServerToolUsage;
 use crate::config::base::ConfigValue;
 use crate::config::ExtensionConfig;
 use crate::conversation::message::{Message, MessageContent};
@@ -361,6 +362,9 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Calls to tools the provider ran itself
+    #[serde(default, skip_serializing_if = "ServerToolUsage::is_empty")]
+    pub server_tools: ServerToolUsage,
 }
 
 fn sum_optionals<T>(a: Option<T>, b: Option<T>) -> Option<T>
@@ -379,11 +383,14 @@ impl Add for Usage {
     type Output = Self;
 
     fn add(self, other: Self) -> Self {
-        Self::new(
-            sum_optionals(self.input_tokens, other.input_tokens),
-            sum_optionals(self.output_tokens, other.output_tokens),
-            sum_optionals(self.total_tokens, other.total_tokens),
-        )
+        Self {
+            server_tools: self.server_tools + other.server_tools,
+            ..Self::new(
+                sum_optionals(self.input_tokens, other.input_tokens),
+                sum_optionals(self.output_tokens, other.output_tokens),
+                sum_optionals(self.total_tokens, other.total_tokens),
+            )
+        }
     }
 }
 
@@ -414,6 +421,7 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens: calculated_total,
+            server_tools: ServerToolUsage::default(),
         }
     }
 }
@@ -635,13 +643,25 @@ pub trait Provider: Send + Sync {
         }
     }
 
-    /// Returns the first 3 user messages as strings for session naming
-    fn get_initial_user_messages(&self, messages: &Conversation) -> Vec<String> {
-        messages
+    /// The user's first prompt and their latest ones, 3 in all, for session naming
+    fn get_user_messages_for_naming(&self, messages: &Conversation) -> Vec<String> {
+        let prompts: Vec<String> = messages
             .iter()
-            .filter(|m| m.role == rmcp::model::Role::User)
-            .take(MSG_COUNT_FOR_SESSION_NAME_GENERATION)
+            .filter(|m| {
+                m.role == rmcp::model::Role::User && m.is_user_visible() && !m.is_tool_response()
+            })
             .map(|m| m.as_concat_text())
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        let latest = prompts
+            .len()
+            .saturating_sub(MSG_COUNT_FOR_SESSION_NAME_GENERATION - 1)
+            .max(1);
+        prompts
+            .iter()
+            .take(1)
+            .chain(prompts.iter().skip(latest))
+            .cloned()
             .collect()
     }
 
@@ -652,7 +672,7 @@ pub trait Provider: Send + Sync {
         session_id: &str,
         messages: &Conversation,
     ) -> Result<String, ProviderError> {
-        let context = self.get_initial_user_messages(messages);
+        let context = self.get_user_messages_for_naming(messages);
         let system = crate::prompt_template::render_template(
             "session_name.md",
             &std::collections::HashMap::<String, String>::new(),
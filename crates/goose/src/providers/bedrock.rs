@@ -295,6 +295,9 @@ impl BedrockProvider {
                         err
                     ))
                 }
+                ConverseError::ServiceUnavailableException(err) => {
+                    ProviderError::Overloaded(format!("Failed to call Bedrock: {:?}", err))
+                }
                 ConverseError::ModelErrorException(err) => {
                     ProviderError::ExecutionError(format!("Failed to call Bedrock: {:?}", err))
                 }
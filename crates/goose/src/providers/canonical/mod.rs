@@ -6,6 +6,15 @@ pub use model::{CanonicalModel, Limit, Modalities, Modality, Pricing};
 pub use name_builder::{canonical_name, map_to_canonical_model, strip_version_suffix};
 pub use registry::CanonicalModelRegistry;
 
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Canonical ids already worked out for (provider, model) pairs. Mapping a name tries a
+/// series of normalizations, and it happens every time a model config is built.
+static CANONICAL_IDS: Lazy<Mutex<HashMap<(String, String), Option<String>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ModelMapping {
     pub provider_model: String,
@@ -26,7 +35,19 @@ pub fn maybe_get_canonical_model(provider: &str, model: &str) -> Option<Canonica
 
     // map_to_canonical_model returns the canonical ID (provider/model)
     // Parse it to get provider and model parts for registry lookup
-    let canonical_id = map_to_canonical_model(provider, model, registry)?;
+    let key = (provider.to_string(), model.to_string());
+    let cached = CANONICAL_IDS.lock().unwrap().get(&key).cloned();
+    let canonical_id = match cached {
+        Some(canonical_id) => canonical_id,
+        None => {
+            let canonical_id = map_to_canonical_model(provider, model, registry);
+            CANONICAL_IDS
+                .lock()
+                .unwrap()
+                .insert(key, canonical_id.clone());
+            canonical_id
+        }
+    }?;
     if let Some((canon_provider, canon_model)) = canonical_id.split_once('/') {
         registry.get(canon_provider, canon_model).cloned()
     } else {
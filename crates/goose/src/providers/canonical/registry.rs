@@ -11,23 +11,14 @@ static BUNDLED_REGISTRY: Lazy<Result<CanonicalModelRegistry>> = Lazy::new(|| {
     let models: Vec<CanonicalModel> = serde_json::from_str(CANONICAL_MODELS_JSON)
         .context("Failed to parse bundled canonical models JSON")?;
 
-    let mut registry = CanonicalModelRegistry::new();
-    for model in models {
-        // Extract provider and model from id (format: "provider/model")
-        if let Some((provider, model_name)) = model.id.split_once('/') {
-            let provider = provider.to_string();
-            let model_name = model_name.to_string();
-            registry.register(&provider, &model_name, model);
-        }
-    }
-
-    Ok(registry)
+    Ok(CanonicalModelRegistry::from_models(models))
 });
 
 #[derive(Debug, Clone)]
 pub struct CanonicalModelRegistry {
-    // Key: (provider, model) tuple
-    models: HashMap<(String, String), CanonicalModel>,
+    // Indexed by provider, then model, so lookups need no allocation and listing a
+    // provider's models does not scan the others
+    models: HashMap<String, HashMap<String, CanonicalModel>>,
 }
 
 impl CanonicalModelRegistry {
@@ -43,27 +34,31 @@ impl CanonicalModelRegistry {
             .map_err(|e| anyhow::anyhow!("{}", e))
     }
 
-    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
-        let content = std::fs::read_to_string(path.as_ref())
-            .context("Failed to read canonical models file")?;
-
-        let models: Vec<CanonicalModel> =
-            serde_json::from_str(&content).context("Failed to parse canonical models JSON")?;
-
+    fn from_models(models: Vec<CanonicalModel>) -> Self {
         let mut registry = Self::new();
         for model in models {
+            // Extract provider and model from id (format: "provider/model")
             if let Some((provider, model_name)) = model.id.split_once('/') {
                 let provider = provider.to_string();
                 let model_name = model_name.to_string();
                 registry.register(&provider, &model_name, model);
             }
         }
+        registry
+    }
 
-        Ok(registry)
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .context("Failed to read canonical models file")?;
+
+        let models: Vec<CanonicalModel> =
+            serde_json::from_str(&content).context("Failed to parse canonical models JSON")?;
+
+        Ok(Self::from_models(models))
     }
 
     pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
-        let mut models: Vec<&CanonicalModel> = self.models.values().collect();
+        let mut models: Vec<&CanonicalModel> = self.all_models();
         models.sort_by(|a, b| a.id.cmp(&b.id));
 
         let json = serde_json::to_string_pretty(&models)
@@ -76,27 +71,31 @@ impl CanonicalModelRegistry {
 
     pub fn register(&mut self, provider: &str, model: &str, canonical_model: CanonicalModel) {
         self.models
-            .insert((provider.to_string(), model.to_string()), canonical_model);
+            .entry(provider.to_string())
+            .or_default()
+            .insert(model.to_string(), canonical_model);
     }
 
     pub fn get(&self, provider: &str, model: &str) -> Option<&CanonicalModel> {
-        self.models.get(&(provider.to_string(), model.to_string()))
+        self.models.get(provider)?.get(model)
     }
 
     pub fn get_all_models_for_provider(&self, provider: &str) -> Vec<CanonicalModel> {
         self.models
-            .iter()
-            .filter(|((p, _), _)| p == provider)
-            .map(|(_, model)| model.clone())
-            .collect()
+            .get(provider)
+            .map(|models| models.values().cloned().collect())
+            .unwrap_or_default()
     }
 
     pub fn all_models(&self) -> Vec<&CanonicalModel> {
-        self.models.values().collect()
+        self.models
+            .values()
+            .flat_map(|models| models.values())
+            .collect()
     }
 
     pub fn count(&self) -> usize {
-        self.models.len()
+        self.models.values().map(|models| models.len()).sum()
     }
 }
 
@@ -7,20 +7,21 @@ use crate::providers::errors::ProviderError;
 use crate::providers::formats::openai_responses::responses_api_to_streaming_message;
 use crate::providers::openai_compatible::handle_status_openai_compat;
 use crate::providers::retry::ProviderRetry;
+use crate::providers::token_manager::{OAuthToken, TokenManager, TokenSource};
 use crate::session_context::SESSION_ID_HEADER;
 use anyhow::{anyhow, Result};
 use async_stream::try_stream;
 use async_trait::async_trait;
 use axum::{extract::Query, response::Html, routing::get, Router};
 use base64::Engine;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use futures::future::BoxFuture;
 use futures::{StreamExt, TryStreamExt};
 use jsonwebtoken::jwk::JwkSet;
 use jsonwebtoken::{decode, decode_header, DecodingKey, Validation};
 use reqwest::header::{HeaderName, HeaderValue};
 use rmcp::model::{RawContent, Role, Tool};
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::{json, Value};
 use sha2::Digest;
 use std::io;
@@ -214,53 +215,18 @@ fn create_codex_request(
     Ok(payload)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct TokenData {
-    access_token: String,
-    refresh_token: String,
-    id_token: Option<String>,
-    expires_at: DateTime<Utc>,
-    account_id: Option<String>,
-}
-
-#[derive(Debug, Clone)]
-struct TokenCache {
-    cache_path: PathBuf,
-}
+/// Kept with the token alongside the access and refresh tokens
+const ID_TOKEN_KEY: &str = "id_token";
+const ACCOUNT_ID_KEY: &str = "account_id";
 
 fn get_cache_path() -> PathBuf {
     Paths::in_config_dir("chatgpt_codex/tokens.json")
 }
 
-impl TokenCache {
-    fn new() -> Self {
-        let cache_path = get_cache_path();
-        if let Some(parent) = cache_path.parent() {
-            let _ = std::fs::create_dir_all(parent);
-        }
-        Self { cache_path }
-    }
-
-    fn load(&self) -> Option<TokenData> {
-        if let Ok(contents) = std::fs::read_to_string(&self.cache_path) {
-            serde_json::from_str(&contents).ok()
-        } else {
-            None
-        }
-    }
-
-    fn save(&self, token_data: &TokenData) -> Result<()> {
-        if let Some(parent) = self.cache_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let contents = serde_json::to_string(token_data)?;
-        std::fs::write(&self.cache_path, contents)?;
-        Ok(())
-    }
-
-    fn clear(&self) {
-        let _ = std::fs::remove_file(&self.cache_path);
-    }
+/// The token kept in a file before tokens moved to the secret backend
+fn read_cached_token() -> Option<OAuthToken> {
+    let contents = std::fs::read_to_string(get_cache_path()).ok()?;
+    serde_json::from_str(&contents).ok()
 }
 
 #[derive(Debug, Deserialize)]
@@ -373,10 +339,10 @@ fn account_id_from_claims(claims: &JwtClaims) -> Option<String> {
 }
 
 async fn extract_account_id(
-    token_data: &TokenData,
+    token_data: &OAuthToken,
     state: &ChatGptCodexAuthState,
 ) -> Option<String> {
-    if let Some(id_token) = token_data.id_token.as_deref() {
+    if let Some(id_token) = token_data.extra_str(ID_TOKEN_KEY) {
         if let Some(claims) = parse_jwt_claims(id_token, state).await {
             if let Some(account_id) = account_id_from_claims(&claims) {
                 return Some(account_id);
@@ -434,6 +400,20 @@ struct TokenResponse {
     expires_in: Option<i64>,
 }
 
+impl From<TokenResponse> for OAuthToken {
+    fn from(tokens: TokenResponse) -> Self {
+        let token = OAuthToken::new(
+            tokens.access_token,
+            Some(tokens.refresh_token),
+            Some(tokens.expires_in.unwrap_or(3600)),
+        );
+        match tokens.id_token {
+            Some(id_token) => token.with_extra(ID_TOKEN_KEY, id_token),
+            None => token,
+        }
+    }
+}
+
 async fn exchange_code_for_tokens_with_issuer(
     issuer: &str,
     code: &str,
@@ -673,7 +653,7 @@ async fn wait_for_oauth_code(rx: oneshot::Receiver<Result<String>>) -> Result<St
         .map_err(|e| anyhow!("OAuth callback error: {}", e))
 }
 
-async fn perform_oauth_flow(auth_state: &ChatGptCodexAuthState) -> Result<TokenData> {
+async fn perform_oauth_flow(auth_state: &ChatGptCodexAuthState) -> Result<OAuthToken> {
     let _guard = auth_state.oauth_mutex.try_lock().map_err(|_| {
         anyhow!("Another OAuth flow is already in progress; please try again later")
     })?;
@@ -698,71 +678,79 @@ async fn perform_oauth_flow(auth_state: &ChatGptCodexAuthState) -> Result<TokenD
     let code = code_result?;
 
     let tokens = exchange_code_for_tokens_with_issuer(ISSUER, &code, &redirect_uri, &pkce).await?;
+    let token_data = OAuthToken::from(tokens);
 
-    let expires_at = Utc::now() + chrono::Duration::seconds(tokens.expires_in.unwrap_or(3600));
-
-    let mut token_data = TokenData {
-        access_token: tokens.access_token,
-        refresh_token: tokens.refresh_token,
-        id_token: tokens.id_token,
-        expires_at,
-        account_id: None,
-    };
-
-    token_data.account_id = extract_account_id(&token_data, auth_state).await;
-
-    Ok(token_data)
+    Ok(match extract_account_id(&token_data, auth_state).await {
+        Some(account_id) => token_data.with_extra(ACCOUNT_ID_KEY, account_id),
+        None => token_data,
+    })
 }
 
-#[derive(Debug)]
-struct ChatGptCodexAuthProvider {
-    cache: TokenCache,
+struct ChatGptCodexTokens {
     state: Arc<ChatGptCodexAuthState>,
 }
 
-impl ChatGptCodexAuthProvider {
-    fn new(state: Arc<ChatGptCodexAuthState>) -> Self {
-        Self {
-            cache: TokenCache::new(),
-            state,
+#[async_trait]
+impl TokenSource for ChatGptCodexTokens {
+    async fn refresh(&self, token: &OAuthToken) -> Result<OAuthToken> {
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| anyhow!("the token has no refresh token"))?;
+        let mut refreshed =
+            OAuthToken::from(refresh_access_token_with_issuer(ISSUER, refresh_token).await?);
+        // Keep the id token and account id when the response leaves them out
+        let mut extra = token.extra.clone();
+        extra.extend(std::mem::take(&mut refreshed.extra));
+        refreshed.extra = extra;
+        if refreshed.extra_str(ACCOUNT_ID_KEY).is_none() {
+            if let Some(account_id) = extract_account_id(&refreshed, self.state.as_ref()).await {
+                refreshed = refreshed.with_extra(ACCOUNT_ID_KEY, account_id);
+            }
         }
+        Ok(refreshed)
     }
 
-    async fn get_valid_token(&self) -> Result<TokenData> {
-        if let Some(mut token_data) = self.cache.load() {
-            if token_data.expires_at > Utc::now() + chrono::Duration::seconds(60) {
-                return Ok(token_data);
+    async fn sign_in(&self) -> Result<OAuthToken> {
+        if let Some(token) = read_cached_token() {
+            if token
+                .expires_at
+                .is_some_and(|expires_at| expires_at > Utc::now())
+            {
+                return Ok(token);
             }
-
-            tracing::debug!("Token expired, attempting refresh");
-            match refresh_access_token_with_issuer(ISSUER, &token_data.refresh_token).await {
-                Ok(new_tokens) => {
-                    token_data.access_token = new_tokens.access_token;
-                    token_data.refresh_token = new_tokens.refresh_token;
-                    if new_tokens.id_token.is_some() {
-                        token_data.id_token = new_tokens.id_token;
-                    }
-                    token_data.expires_at = Utc::now()
-                        + chrono::Duration::seconds(new_tokens.expires_in.unwrap_or(3600));
-                    if token_data.account_id.is_none() {
-                        token_data.account_id =
-                            extract_account_id(&token_data, self.state.as_ref()).await;
-                    }
-                    self.cache.save(&token_data)?;
-                    tracing::info!("Token refreshed successfully");
-                    return Ok(token_data);
-                }
-                Err(e) => {
-                    tracing::warn!("Token refresh failed, will re-authenticate: {}", e);
-                    self.cache.clear();
-                }
+            if let Ok(token) = self.refresh(&token).await {
+                return Ok(token);
             }
         }
 
         tracing::info!("Starting OAuth flow for ChatGPT Codex");
-        let token_data = perform_oauth_flow(self.state.as_ref()).await?;
-        self.cache.save(&token_data)?;
-        Ok(token_data)
+        perform_oauth_flow(self.state.as_ref()).await
+    }
+
+    fn saved(&self) {
+        // The token lives in the secret backend from now on
+        let _ = std::fs::remove_file(get_cache_path());
+    }
+}
+
+#[derive(Debug)]
+struct ChatGptCodexAuthProvider {
+    tokens: Arc<TokenManager>,
+}
+
+impl ChatGptCodexAuthProvider {
+    fn new(state: Arc<ChatGptCodexAuthState>) -> Self {
+        let tokens = TokenManager::shared(
+            CHATGPT_CODEX_PROVIDER_NAME,
+            CHATGPT_CODEX_PROVIDER_NAME,
+            || Arc::new(ChatGptCodexTokens { state }) as Arc<dyn TokenSource>,
+        );
+        Self { tokens }
+    }
+
+    async fn get_valid_token(&self) -> Result<OAuthToken> {
+        self.tokens.token().await
     }
 }
 
@@ -811,7 +799,7 @@ impl ChatGptCodexProvider {
             .map_err(|e| ProviderError::Authentication(e.to_string()))?;
 
         let mut headers = reqwest::header::HeaderMap::new();
-        if let Some(account_id) = &token_data.account_id {
+        if let Some(account_id) = token_data.extra_str(ACCOUNT_ID_KEY) {
             headers.insert(
                 reqwest::header::HeaderName::from_static("chatgpt-account-id"),
                 reqwest::header::HeaderValue::from_str(account_id)
@@ -912,7 +900,7 @@ impl Provider for ChatGptCodexProvider {
             let message_stream = responses_api_to_streaming_message(framed);
             pin!(message_stream);
             while let Some(message) = message_stream.next().await {
-                let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
                 yield (message, usage);
             }
         }))
@@ -942,6 +930,7 @@ mod tests {
     use jsonwebtoken::{Algorithm, EncodingKey, Header};
     use rmcp::model::{CallToolRequestParams, CallToolResult, Content, ErrorCode, ErrorData};
     use rmcp::object;
+    use serde::Serialize;
     use test_case::test_case;
     use wiremock::matchers::{body_string_contains, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
@@ -5,7 +5,9 @@ use futures::future::BoxFuture;
 use rmcp::model::{Role, Tool};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -21,7 +23,7 @@ use super::base::{
 };
 use super::errors::ProviderError;
 use super::utils::filter_extensions_from_system_prompt;
-use crate::config::base::ClaudeCodeCommand;
+use crate::config::base::{ClaudeCodeCommand, ClaudeCodeHistory};
 use crate::config::paths::Paths;
 use crate::config::search_path::SearchPaths;
 use crate::config::{Config, ExtensionConfig, GooseMode};
@@ -31,7 +33,7 @@ use crate::permission::permission_confirmation::PrincipalType;
 use crate::permission::{Permission, PermissionConfirmation};
 use crate::subprocess::configure_subprocess;
 
-use super::cli_common::{error_from_event, extract_usage_tokens};
+use super::cli_common::{error_from_event, extract_usage_tokens, spawn_error};
 
 const CLAUDE_CODE_PROVIDER_NAME: &str = "claude-code";
 pub const CLAUDE_CODE_DEFAULT_MODEL: &str = "default";
@@ -157,6 +159,8 @@ struct CliProcess {
     log_model_update: bool,
     next_request_id: u64,
     needs_drain: bool,
+    /// Fingerprints of the conversation as of the last turn this process completed
+    history_sent: Vec<u64>,
 }
 
 impl std::fmt::Debug for CliProcess {
@@ -244,6 +248,75 @@ impl CliProcess {
     }
 }
 
+/// How much of goose's conversation is sent to the CLI each turn, set with
+/// `CLAUDE_CODE_HISTORY`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+enum HistoryStrategy {
+    /// Only the latest user message; the CLI keeps the rest of the conversation itself.
+    #[default]
+    LastMessage,
+    /// The whole conversation on the first turn of a CLI process, so a resumed or forked
+    /// session starts with its history, then only the latest user message.
+    FullReplayOnNewSession,
+    /// The messages added since the CLI last replied, and the whole conversation again
+    /// when goose's copy no longer starts with what the CLI was sent.
+    Differential,
+}
+
+impl HistoryStrategy {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "last-message" => Some(Self::LastMessage),
+            "full-replay-on-new-session" => Some(Self::FullReplayOnNewSession),
+            "differential" => Some(Self::Differential),
+            _ => None,
+        }
+    }
+}
+
+fn fingerprint(message: &Message) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&(&message.role, &message.content))
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The messages to send this turn, given the fingerprints of the messages the CLI has
+/// been sent so far (empty for a new process).
+fn messages_to_send<'a>(
+    strategy: HistoryStrategy,
+    messages: &'a [Message],
+    sent: &[u64],
+) -> &'a [Message] {
+    let last_user = || match messages.iter().rposition(|m| m.role == Role::User) {
+        Some(index) => &messages[index..=index],
+        None => messages,
+    };
+    match strategy {
+        HistoryStrategy::LastMessage => last_user(),
+        HistoryStrategy::FullReplayOnNewSession if sent.is_empty() => messages,
+        HistoryStrategy::FullReplayOnNewSession => last_user(),
+        HistoryStrategy::Differential => {
+            let unchanged = sent.len() <= messages.len()
+                && messages.iter().zip(sent).all(|(m, f)| fingerprint(m) == *f);
+            if !unchanged {
+                return messages;
+            }
+            // The CLI wrote the assistant replies that follow what it was sent
+            let new = &messages[sent.len()..];
+            let start = new
+                .iter()
+                .position(|m| m.role != Role::Assistant)
+                .unwrap_or(new.len());
+            match &new[start..] {
+                [] => last_user(),
+                new => new,
+            }
+        }
+    }
+}
+
 impl Drop for CliProcess {
     fn drop(&mut self) {
         self.stderr_handle.abort();
@@ -267,6 +340,7 @@ pub struct ClaudeCodeProvider {
     mcp_config_file: Option<NamedTempFile>,
     #[serde(skip)]
     cli_process: tokio::sync::OnceCell<Arc<tokio::sync::Mutex<CliProcess>>>,
+    history: HistoryStrategy,
     #[serde(skip)]
     pending_confirmations:
         Arc<tokio::sync::Mutex<HashMap<String, oneshot::Sender<PermissionConfirmation>>>>,
@@ -275,11 +349,34 @@ pub struct ClaudeCodeProvider {
 impl ClaudeCodeProvider {
     /// Build content blocks from the last user message only — the CLI maintains
     /// conversation context internally per session_id.
-    fn last_user_content_blocks(&self, messages: &[Message]) -> Vec<Value> {
-        let msgs = match messages.iter().rev().find(|m| m.role == Role::User) {
-            Some(msg) => std::slice::from_ref(msg),
-            None => messages,
-        };
+    #[cfg(test)]
+    fn last_user_content_blocks(messages: &[Message]) -> Vec<Value> {
+        Self::content_blocks(messages_to_send(
+            HistoryStrategy::LastMessage,
+            messages,
+            &[],
+        ))
+    }
+
+    /// Build content blocks for the part of the conversation the history strategy sends to
+    /// a process that has completed turns for `sent`.
+    fn history_content_blocks(
+        strategy: HistoryStrategy,
+        messages: &[Message],
+        sent: &[u64],
+    ) -> Vec<Value> {
+        let msgs = messages_to_send(strategy, messages, sent);
+        if msgs.len() > 1 {
+            tracing::debug!(
+                strategy = ?strategy,
+                messages = msgs.len(),
+                "Sending conversation history to claude-code"
+            );
+        }
+        Self::content_blocks(msgs)
+    }
+
+    fn content_blocks(msgs: &[Message]) -> Vec<Value> {
         let mut blocks: Vec<Value> = Vec::new();
         for message in msgs.iter().filter(|m| m.is_agent_visible()) {
             let prefix = match message.role {
@@ -331,6 +428,8 @@ impl ClaudeCodeProvider {
     fn build_stream_json_command(&self) -> Command {
         let mut cmd = Command::new(&self.command);
         configure_subprocess(&mut cmd);
+        // Stop the CLI with the provider, e.g. when goose shuts down
+        cmd.kill_on_drop(true);
         // Allow goose to run inside a Claude Code session.
         cmd.env_remove("CLAUDECODE");
         cmd.arg("--input-format")
@@ -378,12 +477,9 @@ impl ClaudeCodeProvider {
 
         let control_protocol_enabled = Self::apply_permission_flags(&mut cmd)?;
 
-        let mut child = cmd.spawn().map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "Failed to spawn Claude CLI command '{:?}': {}.",
-                self.command, e
-            ))
-        })?;
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| spawn_error(CLAUDE_CODE_PROVIDER_NAME, &self.command, &e))?;
 
         let stdin = child
             .stdin
@@ -413,6 +509,7 @@ impl ClaudeCodeProvider {
             log_model_update: false,
             next_request_id: 0,
             needs_drain: false,
+            history_sent: Vec::new(),
         };
 
         if control_protocol_enabled {
@@ -592,9 +689,10 @@ impl ProviderDef for ClaudeCodeProvider {
             // Only a few agentic choices; fetched dynamically via fetch_supported_models.
             vec![],
             CLAUDE_CODE_DOC_URL,
-            vec![ConfigKey::from_value_type::<ClaudeCodeCommand>(
-                true, false, true,
-            )],
+            vec![
+                ConfigKey::from_value_type::<ClaudeCodeCommand>(true, false, true),
+                ConfigKey::from_value_type::<ClaudeCodeHistory>(false, false, true),
+            ],
         )
     }
 
@@ -606,6 +704,14 @@ impl ProviderDef for ClaudeCodeProvider {
             let config = crate::config::Config::global();
             let command: String = config.get_claude_code_command().unwrap_or_default().into();
             let resolved_command = SearchPaths::builder().with_npm().resolve(command)?;
+            let history: String = config.get_claude_code_history().unwrap_or_default().into();
+            let history = HistoryStrategy::parse(&history).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Unknown CLAUDE_CODE_HISTORY '{}', sending only the last message",
+                    history
+                );
+                HistoryStrategy::LastMessage
+            });
 
             let mut resolved = Vec::with_capacity(extensions.len());
             for ext in extensions {
@@ -622,6 +728,7 @@ impl ProviderDef for ClaudeCodeProvider {
                 name: CLAUDE_CODE_PROVIDER_NAME.to_string(),
                 mcp_config_file,
                 cli_process: tokio::sync::OnceCell::new(),
+                history,
                 pending_confirmations: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
             })
         })
@@ -705,9 +812,12 @@ impl Provider for ClaudeCodeProvider {
         let filtered_system = filter_extensions_from_system_prompt(system);
         let process_arc = Arc::clone(self.get_or_init_process(&filtered_system).await?);
 
-        // Prepare the payload outside the lock — these don't need the process.
-        let blocks = self.last_user_content_blocks(messages);
-        let ndjson_line = build_stream_json_input(&blocks, session_id);
+        // What to send depends on what the process has already seen, so the payload is
+        // built under the lock
+        let history = self.history;
+        let messages = messages.to_vec();
+        let fingerprints: Vec<u64> = messages.iter().map(fingerprint).collect();
+        let session_id = session_id.to_string();
         let model_name = model_config.model_name.clone();
         let message_id = uuid::Uuid::new_v4().to_string();
         let pending_confirmations = Arc::clone(&self.pending_confirmations);
@@ -737,6 +847,8 @@ impl Provider for ClaudeCodeProvider {
             process.drain_pending_response().await;
             process.send_set_model(&model_name).await?;
 
+            let blocks = Self::history_content_blocks(history, &messages, &process.history_sent);
+            let ndjson_line = build_stream_json_input(&blocks, &session_id);
             process
                 .stdin
                 .write_all(ndjson_line.as_bytes())
@@ -832,7 +944,7 @@ impl Provider for ClaudeCodeProvider {
                                 }
                                 Some("error") => {
                                     process.needs_drain = false;
-                                    stream_error = Some(error_from_event("Claude CLI", &parsed));
+                                    stream_error = Some(error_from_event(CLAUDE_CODE_PROVIDER_NAME, &parsed));
                                     break;
                                 }
                                 Some("control_request") => {
@@ -856,16 +968,15 @@ impl Provider for ClaudeCodeProvider {
                                         });
                                         pending_confirmations.lock().await.remove(&request_id);
 
-                                        let perm_resp = match confirmation.permission {
-                                            Permission::AlwaysAllow | Permission::AllowOnce => {
-                                                PermissionResponse::Allow {
-                                                    updated_input: input,
-                                                    tool_use_id,
-                                                }
+                                        let perm_resp = if confirmation.permission.is_allow() {
+                                            PermissionResponse::Allow {
+                                                updated_input: input,
+                                                tool_use_id,
                                             }
-                                            _ => PermissionResponse::Deny {
+                                        } else {
+                                            PermissionResponse::Deny {
                                                 message: "User denied the tool call".to_string(),
-                                            },
+                                            }
                                         };
                                         let resp = ControlResponse::success(request_id, perm_resp);
                                         let mut resp_str = serde_json::to_string(&resp).map_err(|e| {
@@ -905,6 +1016,7 @@ impl Provider for ClaudeCodeProvider {
             if let Some(err) = stream_error {
                 Err(err)?;
             }
+            process.history_sent = fingerprints;
 
             let provider_usage = ProviderUsage::new(model_name, accumulated_usage);
             yield (None, Some(provider_usage));
@@ -954,7 +1066,7 @@ mod tests {
     #[test_case(r#"{"type":"error"}"#, false ; "missing_error_field")]
     fn test_error_from_event(line: &str, is_context_exceeded: bool) {
         let parsed: Value = serde_json::from_str(line).unwrap();
-        let err = error_from_event("Claude CLI", &parsed);
+        let err = error_from_event(CLAUDE_CODE_PROVIDER_NAME, &parsed);
         if is_context_exceeded {
             assert!(matches!(err, ProviderError::ContextLengthExceeded(_)));
         } else {
@@ -1032,11 +1144,42 @@ mod tests {
         ; "tool_response"
     )]
     fn test_last_user_content_blocks(messages: Vec<Message>, expected: &[Value]) {
-        let provider = make_provider();
-        let blocks = provider.last_user_content_blocks(&messages);
+        let blocks = ClaudeCodeProvider::last_user_content_blocks(&messages);
         assert_eq!(blocks, expected);
     }
 
+    #[test_case(HistoryStrategy::LastMessage, 0, 1 ; "last_message_new_process")]
+    #[test_case(HistoryStrategy::FullReplayOnNewSession, 0, 3 ; "replay_new_process")]
+    #[test_case(HistoryStrategy::FullReplayOnNewSession, 1, 1 ; "replay_running_process")]
+    #[test_case(HistoryStrategy::Differential, 0, 3 ; "differential_new_process")]
+    #[test_case(HistoryStrategy::Differential, 1, 1 ; "differential_skips_cli_replies")]
+    fn test_messages_to_send(strategy: HistoryStrategy, sent: usize, expected: usize) {
+        let messages = build_messages(&[
+            ("user", "First", None),
+            ("assistant", "Reply", None),
+            ("user", "Second", None),
+        ]);
+        let sent: Vec<u64> = messages[..sent].iter().map(fingerprint).collect();
+        let to_send = messages_to_send(strategy, &messages, &sent);
+        assert_eq!(to_send.len(), expected);
+        assert_eq!(to_send.last().unwrap().as_concat_text(), "Second");
+    }
+
+    #[test]
+    fn test_differential_replays_an_edited_conversation() {
+        let sent: Vec<u64> = build_messages(&[("user", "Original", None)])
+            .iter()
+            .map(fingerprint)
+            .collect();
+        let messages = build_messages(&[
+            ("user", "Edited", None),
+            ("assistant", "Reply", None),
+            ("user", "Next", None),
+        ]);
+        let to_send = messages_to_send(HistoryStrategy::Differential, &messages, &sent);
+        assert_eq!(to_send.len(), 3);
+    }
+
     #[test_case(
         &[json!({"type":"text","text":"Hello"})],
         json!({"type":"user","session_id":TEST_SESSION_ID,"message":{"role":"user","content":[{"type":"text","text":"Hello"}]}})
@@ -1189,6 +1332,7 @@ mod tests {
             name: "claude-code".to_string(),
             mcp_config_file: None,
             cli_process: tokio::sync::OnceCell::new(),
+            history: HistoryStrategy::LastMessage,
             pending_confirmations: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
         }
     }
@@ -1209,6 +1353,7 @@ mod tests {
             log_model_update: false,
             next_request_id: 0,
             needs_drain: false,
+            history_sent: Vec::new(),
         };
         (process, stdin_reader)
     }
@@ -1312,6 +1457,23 @@ mod tests {
         assert_eq!(String::from_utf8(stdin_bytes).unwrap(), expected_stdin);
     }
 
+    #[test_case(r#"{"type":"result","result":"Done"}"#, 1 ; "recorded_after_a_reply")]
+    #[test_case(r#"{"type":"error","error":"boom"}"#, 0 ; "not_recorded_after_an_error")]
+    #[tokio::test]
+    async fn test_history_is_recorded_only_after_a_reply(last_line: &str, expected: usize) {
+        use futures::StreamExt;
+
+        let (provider, stream, _stdin_reader) = stream_with_canned_stdout(&[
+            r#"{"type":"control_response","response":{"subtype":"success","request_id":"req_0"}}"#,
+            last_line,
+        ])
+        .await;
+        let _: Vec<_> = stream.collect().await;
+
+        let process = provider.cli_process.get().unwrap().lock().await;
+        assert_eq!(process.history_sent.len(), expected);
+    }
+
     #[test_case(
         Permission::AllowOnce,
         json!({"behavior":"allow","updatedInput":{"path":"foo.txt","content":"hello"},"toolUseID":"tu_1"})
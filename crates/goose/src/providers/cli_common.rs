@@ -1,8 +1,25 @@
+use anyhow::{anyhow, Result};
+use async_stream::try_stream;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{Child, ChildStdout, Command};
 
-use super::base::{ProviderUsage, Usage};
+use super::base::{stream_from_single_message, MessageStream, ProviderUsage, Usage};
 use super::errors::ProviderError;
+use super::utils::filter_extensions_from_system_prompt;
+use crate::config::search_path::SearchPaths;
+use crate::config::Config;
 use crate::conversation::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::session::SessionManager;
+use crate::subprocess::configure_subprocess;
 use rmcp::model::Role;
 
 pub(crate) fn extract_usage_tokens(usage_info: &Value) -> Usage {
@@ -25,10 +42,398 @@ pub(crate) fn error_from_event(provider_name: &str, parsed: &Value) -> ProviderE
         .and_then(|e| e.as_str())
         .or_else(|| parsed.get("message").and_then(|m| m.as_str()))
         .unwrap_or("Unknown error");
-    if error_msg.contains("context window exceeded") {
-        ProviderError::ContextLengthExceeded(error_msg.to_string())
+    cli_error(provider_name, error_msg)
+}
+
+/// What goose knows about each CLI provider's command, to turn its failures into advice.
+struct CliInfo {
+    provider_name: &'static str,
+    display_name: &'static str,
+    command_key: &'static str,
+    default_command: &'static str,
+    install: &'static str,
+    /// Arguments that start the CLI's sign-in flow
+    login_args: &'static [&'static str],
+    /// Exit code the CLI uses when it has no usable credentials
+    auth_exit_code: Option<i32>,
+}
+
+const CLI_PROVIDERS: &[CliInfo] = &[
+    CliInfo {
+        provider_name: "claude-code",
+        display_name: "Claude CLI",
+        command_key: "CLAUDE_CODE_COMMAND",
+        default_command: "claude",
+        install: "npm install -g @anthropic-ai/claude-code",
+        // Started interactively without credentials it walks through sign-in; `/login` is a
+        // command inside that session, not an argument
+        login_args: &[],
+        auth_exit_code: None,
+    },
+    CliInfo {
+        provider_name: "codex",
+        display_name: "Codex CLI",
+        command_key: "CODEX_COMMAND",
+        default_command: "codex",
+        install: "npm install -g @openai/codex",
+        login_args: &["login"],
+        auth_exit_code: None,
+    },
+    CliInfo {
+        provider_name: "cursor-agent",
+        display_name: "cursor-agent",
+        command_key: "CURSOR_AGENT_COMMAND",
+        default_command: "cursor-agent",
+        install: "curl https://cursor.com/install -fsS | bash",
+        login_args: &["login"],
+        auth_exit_code: None,
+    },
+    CliInfo {
+        provider_name: "gemini-cli",
+        display_name: "Gemini CLI",
+        command_key: "GEMINI_CLI_COMMAND",
+        default_command: "gemini",
+        install: "npm install -g @google/gemini-cli",
+        // Asks how to sign in when started interactively without credentials
+        login_args: &[],
+        // FatalAuthenticationError
+        auth_exit_code: Some(41),
+    },
+    CliInfo {
+        provider_name: "qwen-code",
+        display_name: "Qwen Code",
+        command_key: "QWEN_CODE_COMMAND",
+        default_command: "qwen",
+        install: "npm install -g @qwen-code/qwen-code",
+        // Same sign-in flow and exit codes as the Gemini CLI it is based on
+        login_args: &[],
+        auth_exit_code: Some(41),
+    },
+    CliInfo {
+        provider_name: "iflow",
+        display_name: "iFlow CLI",
+        command_key: "IFLOW_COMMAND",
+        default_command: "iflow",
+        install: "npm install -g @iflow-ai/iflow-cli",
+        // Same sign-in flow and exit codes as the Gemini CLI it is based on
+        login_args: &[],
+        auth_exit_code: Some(41),
+    },
+];
+
+fn cli_info(provider_name: &str) -> Option<&'static CliInfo> {
+    CLI_PROVIDERS
+        .iter()
+        .find(|info| info.provider_name == provider_name)
+}
+
+fn display_name(provider_name: &str) -> &str {
+    cli_info(provider_name).map_or(provider_name, |info| info.display_name)
+}
+
+fn login_hint(provider_name: &str) -> String {
+    match cli_info(provider_name) {
+        Some(info) => format!(
+            "Sign in with `{}` in a terminal, or run `goose configure` and select {} again.",
+            [info.default_command]
+                .iter()
+                .chain(info.login_args)
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" "),
+            provider_name
+        ),
+        None => "Sign in to the CLI and try again.".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CliFailure {
+    ContextExceeded,
+    NotSignedIn,
+    RateLimited,
+    QuotaExhausted,
+    Overloaded,
+    ContentFiltered,
+    ModelUnavailable,
+}
+
+/// Match a CLI's error output against the phrases the CLIs use for each failure. The phrases
+/// are specific on purpose: CLIs also log routine lines such as "Loaded cached credentials."
+/// to stderr.
+fn classify(message: &str) -> Option<CliFailure> {
+    let message = message.to_lowercase();
+    let any = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+    if any(&["context window", "context_length_exceeded"]) {
+        Some(CliFailure::ContextExceeded)
+    } else if any(&[
+        "not logged in",
+        "not signed in",
+        "not authenticated",
+        "please log in",
+        "please login",
+        "login required",
+        "authentication failed",
+        "invalid api key",
+        "api key not valid",
+        "401 unauthorized",
+        "invalid credentials",
+        "could not load the default credentials",
+    ]) {
+        Some(CliFailure::NotSignedIn)
+    } else if any(&["rate limit", "too many requests"]) {
+        Some(CliFailure::RateLimited)
+    } else if any(&[
+        "quota exceeded",
+        "exceeded your current quota",
+        "insufficient_quota",
+        "usage limit reached",
+        "hit your usage limit",
+    ]) {
+        Some(CliFailure::QuotaExhausted)
+    } else if any(&["overloaded", "server is busy"]) {
+        Some(CliFailure::Overloaded)
+    } else if any(&[
+        "content filter",
+        "content_filter",
+        "content policy",
+        "content_policy",
+    ]) {
+        Some(CliFailure::ContentFiltered)
+    } else if any(&[
+        "model not found",
+        "model_not_found",
+        "unknown model",
+        "invalid model",
+        "model is not available",
+        "does not have access to model",
+        "no access to model",
+    ]) {
+        Some(CliFailure::ModelUnavailable)
     } else {
-        ProviderError::RequestFailed(format!("{provider_name} error: {error_msg}"))
+        None
+    }
+}
+
+/// The typed error for a CLI failure goose recognizes, with what to do about it.
+pub(crate) fn classify_cli_error(provider_name: &str, message: &str) -> Option<ProviderError> {
+    let message = message.trim();
+    Some(failure_error(provider_name, classify(message)?, message))
+}
+
+/// The typed error for a CLI that exited unsuccessfully, from its exit code or its stderr.
+pub(crate) fn classify_cli_exit(
+    provider_name: &str,
+    exit_code: Option<i32>,
+    stderr: &str,
+) -> Option<ProviderError> {
+    let auth_exit_code = cli_info(provider_name).and_then(|info| info.auth_exit_code);
+    if exit_code.is_some() && exit_code == auth_exit_code {
+        let stderr = stderr.trim();
+        let message = match stderr.lines().last() {
+            Some(line) => line.trim().to_string(),
+            None => format!("exit code {}", exit_code.unwrap_or_default()),
+        };
+        return Some(failure_error(
+            provider_name,
+            CliFailure::NotSignedIn,
+            &message,
+        ));
+    }
+    classify_cli_error(provider_name, stderr)
+}
+
+fn failure_error(provider_name: &str, failure: CliFailure, message: &str) -> ProviderError {
+    let cli = display_name(provider_name);
+    match failure {
+        CliFailure::ContextExceeded => ProviderError::ContextLengthExceeded(message.to_string()),
+        CliFailure::NotSignedIn => ProviderError::Authentication(format!(
+            "{cli} is not signed in ({message}). {}",
+            login_hint(provider_name)
+        )),
+        CliFailure::RateLimited => ProviderError::RateLimitExceeded {
+            details: message.to_string(),
+            retry_delay: None,
+        },
+        CliFailure::QuotaExhausted => ProviderError::CreditsExhausted {
+            details: format!("{cli} has used up its quota: {message}"),
+            top_up_url: None,
+        },
+        CliFailure::Overloaded => ProviderError::Overloaded(message.to_string()),
+        CliFailure::ContentFiltered => ProviderError::ContentFiltered(message.to_string()),
+        CliFailure::ModelUnavailable => ProviderError::RequestFailed(format!(
+            "{cli} can't use this model ({message}). \
+             Pick another one with `goose configure`."
+        )),
+    }
+}
+
+/// A CLI error message as a ProviderError.
+pub(crate) fn cli_error(provider_name: &str, message: &str) -> ProviderError {
+    classify_cli_error(provider_name, message).unwrap_or_else(|| {
+        ProviderError::RequestFailed(format!("{} error: {message}", display_name(provider_name)))
+    })
+}
+
+/// The error for a CLI that could not be started.
+pub(crate) fn spawn_error(provider_name: &str, command: &Path, error: &io::Error) -> ProviderError {
+    let cli = display_name(provider_name);
+    match cli_info(provider_name) {
+        Some(info) if error.kind() == io::ErrorKind::NotFound => {
+            ProviderError::RequestFailed(format!(
+                "{cli} is not installed: '{}' was not found. Install it with `{}`, \
+                 or set {} to its path.",
+                command.display(),
+                info.install,
+                info.command_key
+            ))
+        }
+        _ => ProviderError::RequestFailed(format!(
+            "Failed to spawn {cli} command '{}': {error}",
+            command.display()
+        )),
+    }
+}
+
+/// Whether goose can start the sign-in flow of a CLI provider.
+pub fn cli_login_available(provider_name: &str) -> bool {
+    cli_info(provider_name).is_some()
+}
+
+/// Run a CLI provider's sign-in flow in the current terminal. Returns whether it succeeded.
+pub async fn run_cli_login(provider_name: &str) -> Result<bool> {
+    let info = cli_info(provider_name)
+        .ok_or_else(|| anyhow!("{provider_name} has no sign-in flow goose can start"))?;
+    let command = Config::global()
+        .get_param::<String>(info.command_key)
+        .unwrap_or_else(|_| info.default_command.to_string());
+    let command = SearchPaths::builder().with_npm().resolve(command)?;
+    let mut cmd = Command::new(&command);
+    if let Ok(path) = SearchPaths::builder().with_npm().path() {
+        cmd.env("PATH", path);
+    }
+    // The CLI may refuse to start inside a session of its own
+    cmd.env_remove("CLAUDECODE");
+    let status = cmd
+        .args(info.login_args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .await
+        .map_err(|e| spawn_error(provider_name, &command, &e))?;
+    Ok(status.success())
+}
+
+/// Saved CLI sessions older than this are not resumed; the CLIs prune old sessions.
+const CLI_SESSION_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const CLI_SESSION_STATE_VERSION: &str = "v0";
+
+/// The CLI session behind a goose session, kept in the session's extension data so the
+/// CLI's context survives goose restarts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CliSessionState {
+    cli_session_id: String,
+    cwd: PathBuf,
+    updated_at: i64,
+}
+
+impl CliSessionState {
+    /// Why the saved session can't be resumed, if it can't. The CLIs keep their sessions
+    /// per project directory, so one saved elsewhere isn't found.
+    fn stale_reason(&self, cwd: &Path, now: i64) -> Option<&'static str> {
+        if self.cwd != cwd {
+            Some("the working directory changed")
+        } else if now - self.updated_at > CLI_SESSION_MAX_AGE.as_secs() as i64 {
+            Some("the saved session is too old")
+        } else {
+            None
+        }
+    }
+}
+
+/// A CLI provider's session ids by goose session id, backed by the session metadata.
+#[derive(Debug, Clone)]
+pub(crate) struct CliSessions {
+    state_name: &'static str,
+    ids: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl CliSessions {
+    /// `state_name` is the key the sessions are saved under in the extension data.
+    pub fn new(state_name: &'static str) -> Self {
+        Self {
+            state_name,
+            ids: Arc::default(),
+        }
+    }
+
+    /// The CLI session to resume: the one used earlier in this process, or the one saved
+    /// with the goose session if it is still usable.
+    pub async fn resumable(&self, session_id: &str) -> Option<String> {
+        if let Some(id) = self.ids.lock().unwrap().get(session_id).cloned() {
+            return Some(id);
+        }
+        let session = SessionManager::instance()
+            .get_session(session_id, false)
+            .await
+            .ok()?;
+        let state: CliSessionState = session
+            .extension_data
+            .get_extension_state(self.state_name, CLI_SESSION_STATE_VERSION)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())?;
+        let cwd = std::env::current_dir().ok()?;
+        if let Some(reason) = state.stale_reason(&cwd, chrono::Utc::now().timestamp()) {
+            tracing::info!(
+                session_id,
+                cli = self.state_name,
+                "Starting a new CLI session: {}",
+                reason
+            );
+            return None;
+        }
+        self.ids
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), state.cli_session_id.clone());
+        Some(state.cli_session_id)
+    }
+
+    /// Remember the CLI session, and save it with the goose session so its age counts
+    /// from the last turn.
+    pub async fn save(&self, session_id: &str, cli_session_id: &str) {
+        self.ids
+            .lock()
+            .unwrap()
+            .insert(session_id.to_string(), cli_session_id.to_string());
+        if let Err(e) = self.persist(session_id, cli_session_id).await {
+            tracing::debug!(
+                session_id,
+                cli = self.state_name,
+                "Failed to save the CLI session: {}",
+                e
+            );
+        }
+    }
+
+    async fn persist(&self, session_id: &str, cli_session_id: &str) -> Result<()> {
+        let state = CliSessionState {
+            cli_session_id: cli_session_id.to_string(),
+            cwd: std::env::current_dir()?,
+            updated_at: chrono::Utc::now().timestamp(),
+        };
+        SessionManager::instance()
+            .set_extension_state(
+                session_id,
+                self.state_name,
+                CLI_SESSION_STATE_VERSION,
+                &serde_json::to_value(state)?,
+            )
+            .await
+    }
+
+    pub fn forget(&self, session_id: &str) {
+        self.ids.lock().unwrap().remove(session_id);
     }
 }
 
@@ -73,3 +478,496 @@ pub(crate) fn generate_simple_session_description(
         ProviderUsage::new(model_name.to_string(), Usage::default()),
     ))
 }
+
+fn last_user_message_text(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .rev()
+        .find(|m| m.role == Role::User)
+        .map(|m| m.as_concat_text())
+        .unwrap_or_default()
+}
+
+/// Build the prompt for a CLI invocation. When resuming a session the CLI
+/// maintains conversation context internally, so only the latest user
+/// message is needed. On the first turn (no session yet) the system prompt
+/// is prepended — there is typically only one user message at that point.
+pub(crate) fn build_cli_prompt(system: &str, messages: &[Message], resuming: bool) -> String {
+    let user_text = last_user_message_text(messages);
+
+    if resuming {
+        user_text
+    } else {
+        let filtered_system = filter_extensions_from_system_prompt(system);
+        if filtered_system.is_empty() {
+            user_text
+        } else {
+            format!("{filtered_system}\n\n{user_text}")
+        }
+    }
+}
+
+/// Start a CLI command, with its stdout ready to be read line by line.
+pub(crate) fn spawn_cli(
+    provider_name: &str,
+    cmd: &mut Command,
+    command: &Path,
+) -> Result<(Child, BufReader<ChildStdout>), ProviderError> {
+    tracing::debug!(
+        ?command,
+        "Executing {} command",
+        display_name(provider_name)
+    );
+
+    let mut child = cmd
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| spawn_error(provider_name, command, &e))?;
+
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| ProviderError::RequestFailed("Failed to capture stdout".to_string()))?;
+
+    Ok((child, BufReader::new(stdout)))
+}
+
+/// A CLI that runs headless with `-p` and streams stream-json output in the Claude Code
+/// format, resuming its sessions by id. Qwen Code and the iFlow CLI both work this way.
+#[derive(Debug, Serialize)]
+pub(crate) struct StreamJsonCli {
+    #[serde(skip)]
+    provider_name: &'static str,
+    command: PathBuf,
+    #[serde(skip)]
+    resume_flag: &'static str,
+    #[serde(skip)]
+    sessions: CliSessions,
+}
+
+impl StreamJsonCli {
+    /// `state_name` is where the CLI session is kept in the goose session's extension data.
+    pub fn new(
+        provider_name: &'static str,
+        command: PathBuf,
+        resume_flag: &'static str,
+        state_name: &'static str,
+    ) -> Self {
+        Self {
+            provider_name,
+            command,
+            resume_flag,
+            sessions: CliSessions::new(state_name),
+        }
+    }
+
+    fn build_command(&self, prompt: &str, model_name: &str, resume: Option<&str>) -> Command {
+        let mut cmd = Command::new(&self.command);
+        configure_subprocess(&mut cmd);
+
+        if let Ok(path) = SearchPaths::builder().with_npm().path() {
+            cmd.env("PATH", path);
+        }
+
+        cmd.arg("-m").arg(model_name);
+
+        if let Some(sid) = resume {
+            cmd.arg(self.resume_flag).arg(sid);
+        }
+
+        cmd.arg("-p")
+            .arg(prompt)
+            .arg("--output-format")
+            .arg("stream-json")
+            .arg("--yolo");
+
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        cmd
+    }
+
+    /// The command for this turn: resuming the goose session's CLI session when there is
+    /// one, with a fresh session to fall back on in case resuming fails.
+    fn build_attempts(
+        &self,
+        system: &str,
+        messages: &[Message],
+        model_name: &str,
+        resume: Option<&str>,
+    ) -> Vec<(Command, bool)> {
+        let mut attempts = Vec::with_capacity(2);
+        if let Some(sid) = resume {
+            let prompt = build_cli_prompt(system, messages, true);
+            attempts.push((self.build_command(&prompt, model_name, Some(sid)), true));
+        }
+        let prompt = build_cli_prompt(system, messages, false);
+        attempts.push((self.build_command(&prompt, model_name, None), false));
+        attempts
+    }
+
+    pub async fn stream(
+        &self,
+        model_config: &ModelConfig,
+        session_id: &str,
+        system: &str,
+        messages: &[Message],
+    ) -> Result<MessageStream, ProviderError> {
+        if is_session_description_request(system) {
+            let (message, provider_usage) =
+                generate_simple_session_description(&model_config.model_name, messages)?;
+            return Ok(stream_from_single_message(message, provider_usage));
+        }
+
+        let resume = self.sessions.resumable(session_id).await;
+        let attempts = self.build_attempts(
+            system,
+            messages,
+            &model_config.model_name,
+            resume.as_deref(),
+        );
+        let provider_name = self.provider_name;
+        let cli = display_name(provider_name);
+        let command = self.command.clone();
+        let cli_sessions = self.sessions.clone();
+        let session_id = session_id.to_string();
+        let model_name = model_config.model_name.clone();
+        let message_id = uuid::Uuid::new_v4().to_string();
+
+        Ok(Box::pin(try_stream! {
+            let stream_timestamp = chrono::Utc::now().timestamp();
+
+            for (mut cmd, resuming) in attempts {
+                let (mut child, mut reader) = spawn_cli(provider_name, &mut cmd, &command)?;
+                let stderr = child.stderr.take();
+                let stderr_drain = tokio::spawn(async move {
+                    let mut buf = String::new();
+                    if let Some(mut stderr) = stderr {
+                        let _ = AsyncReadExt::read_to_string(&mut stderr, &mut buf).await;
+                    }
+                    buf
+                });
+
+                let mut line = String::new();
+                let mut accumulated_usage = Usage::default();
+                let mut replied = false;
+                let mut failure = None;
+
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line).await {
+                        Ok(0) => break,
+                        Ok(_) => {
+                            let trimmed = line.trim();
+                            if trimmed.is_empty() {
+                                continue;
+                            }
+
+                            if let Ok(parsed) = serde_json::from_str::<Value>(trimmed) {
+                                match parse_stream_event(&parsed) {
+                                    StreamEvent::Init(sid) => {
+                                        cli_sessions.save(&session_id, &sid).await;
+                                    }
+                                    StreamEvent::Text(text) => {
+                                        let mut partial = Message::new(
+                                            Role::Assistant,
+                                            stream_timestamp,
+                                            vec![MessageContent::text(text)],
+                                        );
+                                        partial.id = Some(message_id.clone());
+                                        replied = true;
+                                        yield (Some(partial), None);
+                                    }
+                                    StreamEvent::Result(usage) => {
+                                        accumulated_usage = usage;
+                                        break;
+                                    }
+                                    StreamEvent::Error(message) => {
+                                        failure = Some(cli_error(provider_name, &message));
+                                        break;
+                                    }
+                                    StreamEvent::Other => {}
+                                }
+                            } else {
+                                tracing::warn!(
+                                    line = trimmed,
+                                    "Non-JSON line in stream-json output"
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            failure = Some(ProviderError::RequestFailed(format!(
+                                "Failed to read streaming output: {e}"
+                            )));
+                            break;
+                        }
+                    }
+                }
+
+                let stderr_text = stderr_drain.await.unwrap_or_default();
+                let exit_status = child.wait().await.map_err(|e| {
+                    ProviderError::RequestFailed(format!("Failed to wait for command: {e}"))
+                })?;
+
+                if failure.is_none() && !exit_status.success() {
+                    let stderr_snippet = stderr_text.trim();
+                    let detail = if stderr_snippet.is_empty() {
+                        format!("exit code {:?}", exit_status.code())
+                    } else {
+                        format!("exit code {:?}: {stderr_snippet}", exit_status.code())
+                    };
+                    failure = Some(
+                        classify_cli_exit(provider_name, exit_status.code(), stderr_snippet)
+                            .unwrap_or(ProviderError::RequestFailed(format!(
+                                "{cli} command failed ({detail})"
+                            ))),
+                    );
+                }
+
+                if let Some(error) = failure {
+                    // Signing in or waiting out a quota isn't fixed by a new session
+                    let session_problem = matches!(error, ProviderError::RequestFailed(_));
+                    if resuming && !replied && session_problem {
+                        tracing::warn!(
+                            session_id = %session_id,
+                            "Resuming the {} session failed, starting a new one: {}",
+                            cli,
+                            error
+                        );
+                        cli_sessions.forget(&session_id);
+                        continue;
+                    }
+                    Err(error)?;
+                }
+
+                let provider_usage = ProviderUsage::new(model_name.clone(), accumulated_usage);
+                yield (None, Some(provider_usage));
+                break;
+            }
+        }))
+    }
+}
+
+/// What goose takes from a line of Claude Code format stream-json output.
+#[derive(Debug)]
+enum StreamEvent {
+    Init(String),
+    Text(String),
+    Result(Usage),
+    Error(String),
+    Other,
+}
+
+fn stream_error_message(parsed: &Value) -> String {
+    let error = parsed.get("error");
+    error
+        .and_then(|e| e.get("message"))
+        .or(error)
+        .or_else(|| parsed.get("result"))
+        .and_then(Value::as_str)
+        .unwrap_or("Unknown error")
+        .to_string()
+}
+
+fn parse_stream_event(parsed: &Value) -> StreamEvent {
+    match parsed.get("type").and_then(Value::as_str) {
+        Some("system") if parsed.get("subtype").and_then(Value::as_str) == Some("init") => parsed
+            .get("session_id")
+            .and_then(Value::as_str)
+            .map_or(StreamEvent::Other, |sid| StreamEvent::Init(sid.to_string())),
+        Some("assistant") => {
+            let text: String = parsed
+                .pointer("/message/content")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect();
+            if text.is_empty() {
+                StreamEvent::Other
+            } else {
+                StreamEvent::Text(text)
+            }
+        }
+        Some("result") if parsed.get("is_error").and_then(Value::as_bool) == Some(true) => {
+            StreamEvent::Error(stream_error_message(parsed))
+        }
+        Some("result") => StreamEvent::Result(
+            parsed
+                .get("usage")
+                .map(extract_usage_tokens)
+                .unwrap_or_default(),
+        ),
+        Some("error") => StreamEvent::Error(stream_error_message(parsed)),
+        _ => StreamEvent::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use test_case::test_case;
+
+    #[test_case("Error: not logged in. Please run /login", "auth" ; "claude_not_logged_in")]
+    #[test_case("API key not valid. Please pass a valid API key.", "auth" ; "gemini_bad_key")]
+    #[test_case("Quota exceeded for quota metric 'Gemini 2.5 Pro Requests'", "credits_exhausted" ; "gemini_quota")]
+    #[test_case("You've hit your usage limit. Try again in 3 days.", "credits_exhausted" ; "codex_usage_limit")]
+    #[test_case("Claude AI usage limit reached|1760000000", "credits_exhausted" ; "claude_usage_limit")]
+    #[test_case("API Error: 529 Overloaded", "overloaded" ; "claude_overloaded")]
+    #[test_case("Rate limit reached for gpt-5 on tokens per min", "rate_limit" ; "rate_limit")]
+    #[test_case("Model not found: models/gemini-9", "request" ; "unknown_model")]
+    #[test_case("context window exceeded", "context_length" ; "context")]
+    fn cli_failures_are_classified(message: &str, expected: &str) {
+        let error = classify_cli_error("gemini-cli", message).unwrap();
+        assert_eq!(error.telemetry_type(), expected);
+    }
+
+    #[test]
+    fn routine_gemini_stderr_is_not_a_sign_in_failure() {
+        let stderr = "Loaded cached credentials.\n\
+            Error when talking to Gemini API Full report available at: \
+            /tmp/gemini-client-error-Turn.run-sendMessageStream-2025-09-01T10-00-00.json";
+        assert!(classify_cli_exit("gemini-cli", Some(1), stderr).is_none());
+        assert!(classify_cli_error("gemini-cli", "Checked quota settings").is_none());
+
+        let quota = "Loaded cached credentials.\n\
+            [API Error: Quota exceeded for quota metric 'Gemini 2.5 Pro Requests']";
+        assert_eq!(
+            classify_cli_exit("gemini-cli", Some(1), quota)
+                .unwrap()
+                .telemetry_type(),
+            "credits_exhausted"
+        );
+    }
+
+    #[test]
+    fn sign_in_exit_codes_are_classified() {
+        let stderr = "Please set an Auth method in your settings.json";
+        let error = classify_cli_exit("gemini-cli", Some(41), stderr).unwrap();
+        assert!(matches!(error, ProviderError::Authentication(_)));
+        assert!(classify_cli_exit("codex", Some(41), stderr).is_none());
+        assert!(classify_cli_exit("gemini-cli", Some(41), "")
+            .unwrap()
+            .to_string()
+            .contains("exit code 41"));
+    }
+
+    #[test]
+    fn unrecognized_failures_keep_the_cli_message() {
+        assert!(classify_cli_error("codex", "Model not supported").is_none());
+        assert_eq!(
+            cli_error("codex", "Model not supported"),
+            ProviderError::RequestFailed("Codex CLI error: Model not supported".to_string())
+        );
+    }
+
+    #[test]
+    fn sign_in_errors_say_how_to_sign_in() {
+        let ProviderError::Authentication(message) = cli_error("codex", "401 Unauthorized") else {
+            panic!("expected an authentication error");
+        };
+        assert!(message.contains("`codex login`"));
+
+        let missing = io::Error::new(io::ErrorKind::NotFound, "No such file");
+        let error = spawn_error("gemini-cli", Path::new("gemini"), &missing);
+        assert!(error
+            .to_string()
+            .contains("npm install -g @google/gemini-cli"));
+    }
+
+    #[test]
+    fn saved_cli_sessions_go_stale() {
+        let state = CliSessionState {
+            cli_session_id: "s-1".to_string(),
+            cwd: PathBuf::from("/work"),
+            updated_at: 1_000,
+        };
+        assert_eq!(state.stale_reason(Path::new("/work"), 2_000), None);
+        assert!(state.stale_reason(Path::new("/elsewhere"), 2_000).is_some());
+        let later = 1_000 + CLI_SESSION_MAX_AGE.as_secs() as i64 + 1;
+        assert!(state.stale_reason(Path::new("/work"), later).is_some());
+    }
+
+    #[test]
+    fn test_build_prompt_first_and_resume() {
+        let messages = vec![Message::new(
+            Role::User,
+            0,
+            vec![MessageContent::text("Hello")],
+        )];
+
+        let prompt = build_cli_prompt("You are helpful.", &messages, false);
+        assert!(prompt.contains("You are helpful."));
+        assert!(prompt.contains("Hello"));
+
+        let messages = vec![
+            Message::new(Role::User, 0, vec![MessageContent::text("Hello")]),
+            Message::new(Role::Assistant, 0, vec![MessageContent::text("Hi!")]),
+            Message::new(
+                Role::User,
+                0,
+                vec![MessageContent::text("Follow up question")],
+            ),
+        ];
+        let prompt = build_cli_prompt("You are helpful.", &messages, true);
+        assert_eq!(prompt, "Follow up question");
+    }
+
+    #[test]
+    fn test_parse_stream_event() {
+        let init = json!({"type": "system", "subtype": "init", "session_id": "s-1"});
+        assert!(matches!(parse_stream_event(&init), StreamEvent::Init(sid) if sid == "s-1"));
+
+        let assistant = json!({"type": "assistant", "message": {"content": [
+            {"type": "thinking", "thinking": "hmm"},
+            {"type": "text", "text": "Hello"}
+        ]}});
+        assert!(
+            matches!(parse_stream_event(&assistant), StreamEvent::Text(text) if text == "Hello")
+        );
+
+        let result = json!({"type": "result", "subtype": "success", "is_error": false,
+            "usage": {"input_tokens": 12, "output_tokens": 3, "total_tokens": 15}});
+        let StreamEvent::Result(usage) = parse_stream_event(&result) else {
+            panic!("expected a result");
+        };
+        assert_eq!(usage.total_tokens, Some(15));
+
+        let failed = json!({"type": "result", "subtype": "error_during_execution",
+            "is_error": true, "error": {"message": "Quota exceeded"}});
+        assert!(
+            matches!(parse_stream_event(&failed), StreamEvent::Error(e) if e == "Quota exceeded")
+        );
+    }
+
+    #[test]
+    fn test_resume_falls_back_to_a_fresh_session() {
+        let cli = StreamJsonCli::new("qwen-code", PathBuf::from("qwen"), "--resume", "qwen_code");
+        let messages = vec![Message::new(
+            Role::User,
+            0,
+            vec![MessageContent::text("Hello")],
+        )];
+        let args = |cmd: &Command| -> Vec<String> {
+            cmd.as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let attempts = cli.build_attempts("", &messages, "qwen3-coder-plus", Some("s-1"));
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts[0].1);
+        assert!(args(&attempts[0].0)
+            .windows(2)
+            .any(|w| w == ["--resume", "s-1"]));
+        assert!(!attempts[1].1);
+        assert!(!args(&attempts[1].0).contains(&"--resume".to_string()));
+
+        let attempts = cli.build_attempts("", &messages, "qwen3-coder-plus", None);
+        assert_eq!(attempts.len(), 1);
+    }
+}
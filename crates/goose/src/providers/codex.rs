@@ -13,6 +13,7 @@ use tokio::process::Command;
 use super::base::{
     ConfigKey, MessageStream, Provider, ProviderDef, ProviderMetadata, ProviderUsage, Usage,
 };
+use super::cli_common::{classify_cli_error, cli_error, spawn_error};
 use super::errors::ProviderError;
 use super::utils::{filter_extensions_from_system_prompt, RequestLog};
 use crate::config::base::{CodexCommand, CodexReasoningEffort, CodexSkipGitCheck};
@@ -121,6 +122,8 @@ impl CodexProvider {
 
         let mut cmd = Command::new(&self.command);
         configure_subprocess(&mut cmd);
+        // Stop the CLI with the provider, e.g. when goose shuts down
+        cmd.kill_on_drop(true);
 
         // Propagate extended PATH so the codex subprocess can find Node.js
         // and other dependencies (especially when launched from the desktop app
@@ -171,14 +174,9 @@ impl CodexProvider {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        let mut child = cmd.spawn().map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "Failed to spawn Codex CLI command '{:?}': {}. \
-                Make sure the Codex CLI is installed (npm i -g @openai/codex) \
-                and available in the configured search paths.",
-                self.command, e
-            ))
-        })?;
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| spawn_error(CODEX_PROVIDER_NAME, &self.command, &e))?;
 
         // Write prompt to stdin
         if let Some(mut stdin) = child.stdin.take() {
@@ -236,13 +234,17 @@ impl CodexProvider {
         })?;
 
         // Allow the stderr task to finish
-        let _ = stderr_handle.await;
+        let stderr = stderr_handle.await.unwrap_or_default();
 
         if !exit_status.success() && lines.is_empty() {
-            return Err(ProviderError::RequestFailed(format!(
-                "Codex command failed with exit code: {:?}",
-                exit_status.code()
-            )));
+            return Err(
+                classify_cli_error(CODEX_PROVIDER_NAME, &stderr).unwrap_or_else(|| {
+                    ProviderError::RequestFailed(format!(
+                        "Codex command failed with exit code: {:?}",
+                        exit_status.code()
+                    ))
+                }),
+            );
         }
 
         tracing::debug!("Codex CLI executed successfully, got {} lines", lines.len());
@@ -370,19 +372,7 @@ impl CodexProvider {
 
         if let Some(err) = error_message {
             if all_text_content.is_empty() {
-                if err.contains("context window") || err.contains("context_length_exceeded") {
-                    return Err(ProviderError::ContextLengthExceeded(err));
-                }
-                if err.to_lowercase().contains("rate limit") {
-                    return Err(ProviderError::RateLimitExceeded {
-                        details: err,
-                        retry_delay: None,
-                    });
-                }
-                return Err(ProviderError::RequestFailed(format!(
-                    "Codex CLI error: {}",
-                    err
-                )));
+                return Err(cli_error(CODEX_PROVIDER_NAME, &err));
             }
         }
 
@@ -1079,9 +1069,10 @@ mod tests {
             r#"{"type":"thread.started","thread_id":"test"}"#,
             r#"{"type":"error","message":"You exceeded your current quota, please check your plan and billing details."}"#,
         ],
-        ProviderError::RequestFailed(
-            "Codex CLI error: You exceeded your current quota, please check your plan and billing details.".to_string()
-        )
+        ProviderError::CreditsExhausted {
+            details: "Codex CLI has used up its quota: You exceeded your current quota, please check your plan and billing details.".to_string(),
+            top_up_url: None,
+        }
         ; "quota_exceeded"
     )]
     #[test_case(
@@ -11,6 +11,7 @@ use super::base::{
     stream_from_single_message, ConfigKey, MessageStream, Provider, ProviderDef, ProviderMetadata,
     ProviderUsage, Usage,
 };
+use super::cli_common::spawn_error;
 use super::errors::ProviderError;
 use super::utils::{filter_extensions_from_system_prompt, RequestLog};
 use crate::config::base::CursorAgentCommand;
@@ -200,6 +201,8 @@ impl CursorAgentProvider {
 
         let mut cmd = Command::new(&self.command);
         configure_subprocess(&mut cmd);
+        // Stop the CLI with the provider, e.g. when goose shuts down
+        cmd.kill_on_drop(true);
 
         if let Ok(path) = SearchPaths::builder().with_npm().path() {
             cmd.env("PATH", path);
@@ -219,12 +222,8 @@ impl CursorAgentProvider {
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
         let mut child = cmd
-                .spawn()
-                .map_err(|e| ProviderError::RequestFailed(format!(
-                    "Failed to spawn cursor-agent CLI command '{:?}': {}. \
-                    Make sure the cursor-agent CLI is installed and available in the configured search paths, or set CURSOR_AGENT_COMMAND in your config to the correct path.",
-                    self.command, e
-                )))?;
+            .spawn()
+            .map_err(|e| spawn_error(CURSOR_AGENT_PROVIDER_NAME, &self.command, &e))?;
 
         let stdout = child
             .stdout
@@ -300,7 +300,7 @@ impl Provider for DatabricksProvider {
         let path = self.get_endpoint_path(&model_config.model_name, false);
 
         if Self::is_responses_model(&model_config.model_name) {
-            let mut payload = create_responses_request(model_config, system, messages, tools)?;
+            let mut payload = create_responses_request(model_config, system, messages, tools, &[])?;
             payload["stream"] = Value::Bool(true);
 
             let mut log = RequestLog::start(model_config, &payload)?;
@@ -328,7 +328,7 @@ impl Provider for DatabricksProvider {
                 let message_stream = responses_api_to_streaming_message(framed);
                 pin!(message_stream);
                 while let Some(message) = message_stream.next().await {
-                    let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                    let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
                     log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                     yield (message, usage);
                 }
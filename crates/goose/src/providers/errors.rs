@@ -1,4 +1,6 @@
 use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::time::Duration;
 use thiserror::Error;
 
@@ -39,23 +41,191 @@ pub enum ProviderError {
         details: String,
         top_up_url: Option<String>,
     },
+
+    #[error("Provider overloaded: {0}")]
+    Overloaded(String),
+
+    #[error("Blocked by the provider's content filter: {0}")]
+    ContentFiltered(String),
+
+    #[error("Model {model} is not available from {provider}{}", did_you_mean(.suggestions))]
+    ModelNotFound {
+        provider: String,
+        model: String,
+        /// Available models with names close to the requested one
+        suggestions: Vec<String>,
+    },
+}
+
+fn did_you_mean(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        format!(". Did you mean {}?", suggestions.join(", "))
+    }
+}
+
+/// A machine-readable code for each kind of [`ProviderError`], so retries, messages and
+/// telemetry don't have to match on error text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProviderErrorCode {
+    Auth,
+    CreditsExhausted,
+    RateLimit,
+    ContentFilter,
+    Overloaded,
+    Network,
+    ContextLength,
+    Server,
+    Request,
+    Execution,
+    Usage,
+    NotImplemented,
+    ModelNotFound,
+}
+
+impl ProviderErrorCode {
+    pub const ALL: [Self; 13] = [
+        Self::Auth,
+        Self::CreditsExhausted,
+        Self::RateLimit,
+        Self::ContentFilter,
+        Self::Overloaded,
+        Self::Network,
+        Self::ContextLength,
+        Self::Server,
+        Self::Request,
+        Self::Execution,
+        Self::Usage,
+        Self::NotImplemented,
+        Self::ModelNotFound,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::CreditsExhausted => "credits_exhausted",
+            Self::RateLimit => "rate_limit",
+            Self::ContentFilter => "content_filter",
+            Self::Overloaded => "overloaded",
+            Self::Network => "network",
+            Self::ContextLength => "context_length",
+            Self::Server => "server",
+            Self::Request => "request",
+            Self::Execution => "execution",
+            Self::Usage => "usage",
+            Self::NotImplemented => "not_implemented",
+            Self::ModelNotFound => "model_not_found",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == code)
+    }
+
+    /// Whether sending the same request again may succeed
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimit | Self::Overloaded | Self::Server | Self::Network | Self::Request
+        )
+    }
 }
 
 impl ProviderError {
-    pub fn telemetry_type(&self) -> &'static str {
+    pub fn code(&self) -> ProviderErrorCode {
         match self {
-            ProviderError::Authentication(_) => "auth",
-            ProviderError::ContextLengthExceeded(_) => "context_length",
-            ProviderError::RateLimitExceeded { .. } => "rate_limit",
-            ProviderError::ServerError(_) => "server",
-            ProviderError::NetworkError(_) => "network",
-            ProviderError::RequestFailed(_) => "request",
-            ProviderError::ExecutionError(_) => "execution",
-            ProviderError::UsageError(_) => "usage",
-            ProviderError::NotImplemented(_) => "not_implemented",
-            ProviderError::CreditsExhausted { .. } => "credits_exhausted",
+            ProviderError::Authentication(_) => ProviderErrorCode::Auth,
+            ProviderError::ContextLengthExceeded(_) => ProviderErrorCode::ContextLength,
+            ProviderError::RateLimitExceeded { .. } => ProviderErrorCode::RateLimit,
+            ProviderError::ServerError(_) => ProviderErrorCode::Server,
+            ProviderError::NetworkError(_) => ProviderErrorCode::Network,
+            ProviderError::RequestFailed(_) => ProviderErrorCode::Request,
+            ProviderError::ExecutionError(_) => ProviderErrorCode::Execution,
+            ProviderError::UsageError(_) => ProviderErrorCode::Usage,
+            ProviderError::NotImplemented(_) => ProviderErrorCode::NotImplemented,
+            ProviderError::CreditsExhausted { .. } => ProviderErrorCode::CreditsExhausted,
+            ProviderError::Overloaded(_) => ProviderErrorCode::Overloaded,
+            ProviderError::ContentFiltered(_) => ProviderErrorCode::ContentFilter,
+            ProviderError::ModelNotFound { .. } => ProviderErrorCode::ModelNotFound,
         }
     }
+
+    pub fn telemetry_type(&self) -> &'static str {
+        self.code().as_str()
+    }
+
+    /// The error for an error type or code a provider reports, like Anthropic's
+    /// `overloaded_error` or OpenAI's `insufficient_quota`. None for codes that don't say
+    /// more than the HTTP status does.
+    pub fn from_provider_code(code: &str, message: impl Into<String>) -> Option<Self> {
+        let message = message.into();
+        let error = match code.to_lowercase().as_str() {
+            "authentication_error"
+            | "permission_error"
+            | "invalid_api_key"
+            | "unauthenticated"
+            | "permission_denied" => ProviderError::Authentication(message),
+            "insufficient_quota" | "billing_error" | "billing_hard_limit_reached" => {
+                ProviderError::CreditsExhausted {
+                    details: message,
+                    top_up_url: None,
+                }
+            }
+            "rate_limit_error" | "rate_limit_exceeded" | "resource_exhausted" => {
+                ProviderError::RateLimitExceeded {
+                    details: message,
+                    retry_delay: None,
+                }
+            }
+            "overloaded_error" | "server_overloaded" | "unavailable" => {
+                ProviderError::Overloaded(message)
+            }
+            "content_filter" | "content_policy_violation" | "content_filtered" => {
+                ProviderError::ContentFiltered(message)
+            }
+            "context_length_exceeded" | "string_above_max_length" => {
+                ProviderError::ContextLengthExceeded(message)
+            }
+            _ => return None,
+        };
+        Some(error)
+    }
+
+    /// The error described by an error response body, from its `type`, `code` or `status`
+    pub fn from_error_payload(payload: &Value) -> Option<Self> {
+        let error = payload
+            .get("error")
+            .filter(|e| e.is_object())
+            .unwrap_or(payload);
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| payload.to_string());
+        ["type", "code", "status"]
+            .into_iter()
+            .filter_map(|key| error.get(key).and_then(|v| v.as_str()))
+            .find_map(|code| Self::from_provider_code(code, message.clone()))
+    }
+
+    /// The error for an item of a provider stream that failed, keeping it typed when the
+    /// stream parser recognized it
+    pub fn from_stream_error(error: anyhow::Error) -> Self {
+        error.downcast::<ProviderError>().unwrap_or_else(|error| {
+            ProviderError::RequestFailed(format!("Stream decode error: {}", error))
+        })
+    }
+}
+
+/// The error for an error event in a provider stream: typed when its code is recognized,
+/// described with `context` otherwise
+pub fn stream_error_event(context: &str, error: &Value) -> anyhow::Error {
+    match ProviderError::from_error_payload(error) {
+        Some(error) => anyhow::Error::new(error),
+        None => anyhow::anyhow!("{}: {:?}", context, error),
+    }
 }
 
 fn is_network_error(err: &reqwest::Error) -> bool {
@@ -101,6 +271,10 @@ fn provider_error_from_reqwest(error: &reqwest::Error) -> ProviderError {
 
 impl From<anyhow::Error> for ProviderError {
     fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ProviderError>() {
+            Ok(provider_error) => return provider_error,
+            Err(error) => error,
+        };
         if let Some(reqwest_err) = error.downcast_ref::<reqwest::Error>() {
             return provider_error_from_reqwest(reqwest_err);
         }
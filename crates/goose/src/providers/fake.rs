@@ -0,0 +1,361 @@
+//! A scripted provider for testing agents without a model or an HTTP mock.
+//!
+//! [`FakeProvider`] answers each request with the next response of its script: text, tool
+//! calls, any message, or an error. Every request it gets is kept for the test to look at,
+//! and expectations can be attached to a response to check the request it answers:
+//!
+//! ```
+//! use goose::providers::fake::FakeProvider;
+//! use rmcp::object;
+//!
+//! let provider = FakeProvider::new()
+//!     .tool_call("developer__shell", object!({"command": "ls"}))
+//!     .expect_user_text("list the files")
+//!     .text("There are two files.")
+//!     .expect_tool_result("Cargo.toml");
+//! ```
+//!
+//! Wrap it in an `Arc`, give a clone to the agent and call [`FakeProvider::verify`] at the
+//! end of the test to check every response was used and every expectation held.
+
+use super::base::{stream_from_single_message, MessageStream, Provider, ProviderUsage, Usage};
+use super::errors::ProviderError;
+use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::Conversation;
+use crate::model::ModelConfig;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rmcp::model::{CallToolRequestParams, JsonObject, Role, Tool};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+const FAKE_MODEL: &str = "fake-model";
+
+type Check = Arc<dyn Fn(&FakeRequest) -> bool + Send + Sync>;
+
+/// What the provider was asked
+#[derive(Debug, Clone)]
+pub struct FakeRequest {
+    pub system: String,
+    pub messages: Vec<Message>,
+    pub tools: Vec<Tool>,
+}
+
+impl FakeRequest {
+    /// Text of the last message the user typed, skipping tool results
+    pub fn last_user_text(&self) -> Option<String> {
+        self.messages
+            .iter()
+            .rev()
+            .find(|m| m.role == Role::User && !m.is_tool_response())
+            .map(|m| m.as_concat_text())
+    }
+
+    /// Text of the tool results in the last message
+    pub fn last_tool_result_text(&self) -> Option<String> {
+        let message = self.messages.last().filter(|m| m.is_tool_response())?;
+        let mut text = Vec::new();
+        for content in &message.content {
+            if let MessageContent::ToolResponse(response) = content {
+                match &response.tool_result {
+                    Ok(result) => text.extend(
+                        result
+                            .content
+                            .iter()
+                            .filter_map(|c| c.as_text().map(|t| t.text.clone())),
+                    ),
+                    Err(error) => text.push(error.to_string()),
+                }
+            }
+        }
+        Some(text.join("\n"))
+    }
+
+    pub fn tool_names(&self) -> Vec<String> {
+        self.tools.iter().map(|t| t.name.to_string()).collect()
+    }
+}
+
+struct Step {
+    response: Result<Message, ProviderError>,
+    checks: Vec<(String, Check)>,
+}
+
+#[derive(Default)]
+struct State {
+    steps: VecDeque<Step>,
+    requests: Vec<FakeRequest>,
+    failures: Vec<String>,
+}
+
+/// A provider that plays back a script of responses
+#[derive(Clone)]
+pub struct FakeProvider {
+    state: Arc<Mutex<State>>,
+    next_call_id: usize,
+    usage: Usage,
+}
+
+impl Default for FakeProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeProvider {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::default(),
+            next_call_id: 0,
+            usage: Usage::new(Some(10), Some(5), Some(15)),
+        }
+    }
+
+    /// The usage reported with every response
+    pub fn with_usage(mut self, usage: Usage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    fn push(self, response: Result<Message, ProviderError>) -> Self {
+        self.state.lock().unwrap().steps.push_back(Step {
+            response,
+            checks: Vec::new(),
+        });
+        self
+    }
+
+    /// Answer with an assistant message
+    pub fn message(self, message: Message) -> Self {
+        self.push(Ok(message))
+    }
+
+    /// Answer with text
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.message(Message::assistant().with_text(text))
+    }
+
+    /// Answer with a call to one tool
+    pub fn tool_call(self, name: impl Into<String>, arguments: JsonObject) -> Self {
+        self.tool_calls(vec![(name.into(), arguments)])
+    }
+
+    /// Answer with calls to several tools at once, numbered `call_1`, `call_2`, ...
+    pub fn tool_calls(mut self, calls: Vec<(String, JsonObject)>) -> Self {
+        let mut message = Message::assistant();
+        for (name, arguments) in calls {
+            self.next_call_id += 1;
+            message = message.with_tool_request(
+                format!("call_{}", self.next_call_id),
+                Ok(CallToolRequestParams::new(name).with_arguments(arguments)),
+            );
+        }
+        self.message(message)
+    }
+
+    /// Fail the request
+    pub fn error(self, error: ProviderError) -> Self {
+        self.push(Err(error))
+    }
+
+    /// Check the request the last scripted response answers. A check that fails is
+    /// reported by [`FakeProvider::verify`] and fails that request.
+    pub fn expect(
+        self,
+        description: impl Into<String>,
+        check: impl Fn(&FakeRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            let step = state
+                .steps
+                .back_mut()
+                .expect("expectations follow the response they apply to");
+            step.checks.push((description.into(), Arc::new(check)));
+        }
+        self
+    }
+
+    /// Expect the last message the user typed to contain `text`
+    pub fn expect_user_text(self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        self.expect(format!("user text contains {:?}", text), move |request| {
+            request
+                .last_user_text()
+                .is_some_and(|user_text| user_text.contains(&text))
+        })
+    }
+
+    /// Expect the request to end with tool results containing `text`
+    pub fn expect_tool_result(self, text: impl Into<String>) -> Self {
+        let text = text.into();
+        self.expect(format!("tool result contains {:?}", text), move |request| {
+            request
+                .last_tool_result_text()
+                .is_some_and(|result| result.contains(&text))
+        })
+    }
+
+    /// Expect a tool to be offered to the model
+    pub fn expect_tool(self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        self.expect(format!("tool {} is offered", name), move |request| {
+            request.tools.iter().any(|t| t.name == name)
+        })
+    }
+
+    /// The requests received so far
+    pub fn requests(&self) -> Vec<FakeRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Responses not used yet
+    pub fn remaining(&self) -> usize {
+        self.state.lock().unwrap().steps.len()
+    }
+
+    /// Check that every response was used and every expectation held
+    pub fn verify(&self) -> Result<()> {
+        let state = self.state.lock().unwrap();
+        let mut problems = state.failures.clone();
+        if !state.steps.is_empty() {
+            problems.push(format!(
+                "{} scripted responses were not used",
+                state.steps.len()
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(problems.join("\n")))
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for FakeProvider {
+    fn get_name(&self) -> &str {
+        "fake"
+    }
+
+    async fn stream(
+        &self,
+        _model_config: &ModelConfig,
+        _session_id: &str,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let request = FakeRequest {
+            system: system.to_string(),
+            messages: messages.to_vec(),
+            tools: tools.to_vec(),
+        };
+        let mut state = self.state.lock().unwrap();
+        let number = state.requests.len() + 1;
+        state.requests.push(request.clone());
+
+        let step = state.steps.pop_front().ok_or_else(|| {
+            let failure = format!("Request {} came after the script ran out", number);
+            state.failures.push(failure.clone());
+            ProviderError::ExecutionError(failure)
+        })?;
+        let failed: Vec<String> = step
+            .checks
+            .iter()
+            .filter(|(_, check)| !check(&request))
+            .map(|(description, _)| format!("Request {}: expected {}", number, description))
+            .collect();
+        if !failed.is_empty() {
+            state.failures.extend(failed.iter().cloned());
+            return Err(ProviderError::ExecutionError(failed.join("; ")));
+        }
+
+        let usage = ProviderUsage::new(FAKE_MODEL.to_string(), self.usage);
+        Ok(stream_from_single_message(step.response?, usage))
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        ModelConfig::new_or_fail(FAKE_MODEL)
+    }
+
+    /// Named without a request, so titling a session doesn't use up the script
+    async fn generate_session_name(
+        &self,
+        _session_id: &str,
+        _messages: &Conversation,
+    ) -> Result<String, ProviderError> {
+        Ok("Fake session".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::CallToolResult;
+    use rmcp::object;
+
+    #[tokio::test]
+    async fn plays_the_script_and_checks_requests() {
+        let provider = FakeProvider::new()
+            .tool_call("developer__shell", object!({"command": "ls"}))
+            .expect_user_text("list")
+            .text("Two files")
+            .expect_tool_result("Cargo.toml")
+            .error(ProviderError::RateLimitExceeded {
+                details: "slow down".to_string(),
+                retry_delay: None,
+            });
+        let model_config = provider.get_model_config();
+
+        let prompt = Message::user().with_text("list the files");
+        let (call, _) = provider
+            .complete(&model_config, "s", "system", &[prompt.clone()], &[])
+            .await
+            .unwrap();
+        assert!(call.is_tool_call());
+
+        let result = Message::user().with_tool_response(
+            "call_1",
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                "Cargo.toml src",
+            )])),
+        );
+        let messages = [prompt, call, result];
+        let (answer, usage) = provider
+            .complete(&model_config, "s", "system", &messages, &[])
+            .await
+            .unwrap();
+        assert_eq!(answer.as_concat_text(), "Two files");
+        assert_eq!(usage.usage.total_tokens, Some(15));
+
+        let error = provider
+            .complete(&model_config, "s", "system", &messages, &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ProviderError::RateLimitExceeded { .. }));
+        assert_eq!(provider.requests().len(), 3);
+        provider.verify().unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_expectations_and_extra_requests_are_reported() {
+        let provider = FakeProvider::new()
+            .text("hi")
+            .expect_tool("memory__remember");
+        let model_config = provider.get_model_config();
+        let prompt = [Message::user().with_text("hello")];
+
+        assert!(provider
+            .complete(&model_config, "s", "", &prompt, &[])
+            .await
+            .is_err());
+        assert!(provider
+            .complete(&model_config, "s", "", &prompt, &[])
+            .await
+            .is_err());
+        let report = provider.verify().unwrap_err().to_string();
+        assert!(report.contains("expected tool memory__remember is offered"));
+        assert!(report.contains("Request 2 came after the script ran out"));
+    }
+}
@@ -1,7 +1,9 @@
-use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::message::{AnnotationContent, CitationSource, Message, MessageContent};
+use crate::mcp_utils::structured_content_as_text;
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
-use crate::providers::errors::ProviderError;
+use crate::providers::errors::{stream_error_event, ProviderError};
+use crate::providers::server_tools::{self, ServerTool, ServerToolUsage};
 use crate::providers::utils::{convert_image, ImageFormat};
 use anyhow::{anyhow, Result};
 use rmcp::model::{object, CallToolRequestParams, ErrorCode, ErrorData, JsonObject, Role, Tool};
@@ -99,6 +101,15 @@ const TOOL_USE_ID_FIELD: &str = "tool_use_id";
 const IS_ERROR_FIELD: &str = "is_error";
 const SIGNATURE_FIELD: &str = "signature";
 const DATA_FIELD: &str = "data";
+const CITATIONS_FIELD: &str = "citations";
+const SERVER_TOOL_USE_TYPE: &str = "server_tool_use";
+/// Blocks holding the result of a server tool call, complete when they start
+const SERVER_TOOL_RESULT_TYPES: [&str; 4] = [
+    "web_search_tool_result",
+    "code_execution_tool_result",
+    "bash_code_execution_tool_result",
+    "text_editor_code_execution_tool_result",
+];
 
 /// Convert internal Message format to Anthropic's API message specification
 pub fn format_messages(messages: &[Message]) -> Vec<Value> {
@@ -143,6 +154,7 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                             .content
                             .iter()
                             .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                            .chain(structured_content_as_text(result))
                             .collect::<Vec<_>>()
                             .join("\n");
 
@@ -200,6 +212,10 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                     // Reasoning content is for OpenAI-compatible APIs (e.g., DeepSeek)
                     // Anthropic doesn't use this format, so skip it
                 }
+                MessageContent::Attachment(_) => {
+                    // Attachments are rendered to text and images before they reach the provider
+                }
+                MessageContent::Annotation(_) => {}
             }
         }
 
@@ -286,6 +302,68 @@ pub fn format_tools(tools: &[Tool]) -> Vec<Value> {
     tool_specs
 }
 
+fn server_tool_spec(tool: ServerTool) -> Value {
+    match tool {
+        ServerTool::WebSearch => {
+            json!({TYPE_FIELD: "web_search_20250305", NAME_FIELD: "web_search"})
+        }
+        ServerTool::CodeExecution => {
+            json!({TYPE_FIELD: "code_execution_20250825", NAME_FIELD: "code_execution"})
+        }
+    }
+}
+
+/// A call Anthropic ran, from the name and input of its server_tool_use block and its result
+/// block
+fn server_tool_call(
+    name: &str,
+    input: Value,
+    result: &Value,
+) -> Option<(ServerTool, [MessageContent; 2])> {
+    let tool = match name {
+        "web_search" => ServerTool::WebSearch,
+        "code_execution" | "bash_code_execution" | "text_editor_code_execution" => {
+            ServerTool::CodeExecution
+        }
+        _ => return None,
+    };
+    let id = result.get(TOOL_USE_ID_FIELD)?.as_str()?;
+    let content = result.get(CONTENT_FIELD).unwrap_or(&Value::Null);
+    let error_code = content.get("error_code").and_then(|e| e.as_str());
+
+    let (output, is_error) = if let Some(error) = error_code {
+        (format!("Failed: {}", error), true)
+    } else if let Some(results) = content.as_array() {
+        let results: Vec<String> = results
+            .iter()
+            .filter_map(|result| {
+                let url = result.get("url")?.as_str()?;
+                let title = result.get("title").and_then(|t| t.as_str()).unwrap_or(url);
+                Some(format!("- {} ({})", title, url))
+            })
+            .collect();
+        (results.join("\n"), false)
+    } else {
+        let return_code = content.get("return_code").and_then(|c| c.as_i64());
+        let mut parts: Vec<String> = ["stdout", "stderr", CONTENT_FIELD]
+            .iter()
+            .filter_map(|field| content.get(*field).and_then(|t| t.as_str()))
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+            .collect();
+        if let Some(code) = return_code.filter(|code| *code != 0) {
+            parts.push(format!("Exit code {}", code));
+        }
+        let output = if parts.is_empty() {
+            content.to_string()
+        } else {
+            parts.join("\n")
+        };
+        (output, return_code.is_some_and(|code| code != 0))
+    };
+    Some((tool, server_tools::call(id, tool, input, output, is_error)))
+}
+
 /// Convert system message to Anthropic's API system specification
 pub fn format_system(system: &str) -> Value {
     json!([{
@@ -295,6 +373,40 @@ pub fn format_system(system: &str) -> Value {
     }])
 }
 
+/// An annotation for a citation on a text block spanning characters `start..end` of the
+/// response. Only citations of web pages and search results are kept: goose doesn't send
+/// documents, so document locations have nothing to point at.
+fn citation_to_annotation(citation: &Value, start: usize, end: usize) -> Option<AnnotationContent> {
+    let title = citation
+        .get("title")
+        .and_then(|t| t.as_str())
+        .map(String::from);
+    let source = match citation.get(TYPE_FIELD).and_then(|t| t.as_str())? {
+        "web_search_result_location" => CitationSource::Url {
+            url: citation.get("url")?.as_str()?.to_string(),
+            title,
+        },
+        "search_result_location" => {
+            let source = citation.get("source")?.as_str()?.to_string();
+            if source.starts_with("http://") || source.starts_with("https://") {
+                CitationSource::Url { url: source, title }
+            } else {
+                CitationSource::File {
+                    path: source,
+                    start_line: None,
+                    end_line: None,
+                }
+            }
+        }
+        _ => return None,
+    };
+    let annotation = AnnotationContent::new(source).with_span(start, end);
+    Some(match citation.get("cited_text").and_then(|t| t.as_str()) {
+        Some(cited_text) => annotation.with_cited_text(cited_text),
+        None => annotation,
+    })
+}
+
 /// Convert Anthropic's API response to internal Message format
 pub fn response_to_message(response: &Value) -> Result<Message> {
     let content_blocks = response
@@ -303,12 +415,25 @@ pub fn response_to_message(response: &Value) -> Result<Message> {
         .ok_or_else(|| anyhow!("Invalid response format: missing content array"))?;
 
     let mut message = Message::assistant();
+    let mut text_chars = 0;
+    let mut server_tool_uses: std::collections::HashMap<&str, (&str, &Value)> =
+        std::collections::HashMap::new();
 
     for block in content_blocks {
         match block.get(TYPE_FIELD).and_then(|t| t.as_str()) {
             Some(TEXT_TYPE) => {
                 if let Some(text) = block.get(TEXT_TYPE).and_then(|t| t.as_str()) {
+                    let start = text_chars;
+                    text_chars += text.chars().count();
                     message = message.with_text(text.to_string());
+                    let citations = block.get(CITATIONS_FIELD).and_then(|c| c.as_array());
+                    for citation in citations.into_iter().flatten() {
+                        if let Some(annotation) =
+                            citation_to_annotation(citation, start, text_chars)
+                        {
+                            message = message.with_annotation(annotation);
+                        }
+                    }
                 }
             }
             Some(TOOL_USE_TYPE) => {
@@ -348,6 +473,24 @@ pub fn response_to_message(response: &Value) -> Result<Message> {
                     .ok_or_else(|| anyhow!("Missing redacted_thinking data"))?;
                 message = message.with_redacted_thinking(data);
             }
+            Some(SERVER_TOOL_USE_TYPE) => {
+                let id = block.get(ID_FIELD).and_then(|i| i.as_str());
+                let name = block.get(NAME_FIELD).and_then(|n| n.as_str());
+                if let (Some(id), Some(name)) = (id, name) {
+                    let input = block.get(INPUT_FIELD).unwrap_or(&Value::Null);
+                    server_tool_uses.insert(id, (name, input));
+                }
+            }
+            Some(kind) if SERVER_TOOL_RESULT_TYPES.contains(&kind) => {
+                let call = block
+                    .get(TOOL_USE_ID_FIELD)
+                    .and_then(|id| id.as_str())
+                    .and_then(|id| server_tool_uses.remove(id))
+                    .and_then(|(name, input)| server_tool_call(name, input.clone(), block));
+                if let Some((_, [request, response])) = call {
+                    message = message.with_content(request).with_content(response);
+                }
+            }
             _ => continue,
         }
     }
@@ -489,15 +632,18 @@ fn apply_thinking_config(payload: &mut Value, model_config: &ModelConfig, max_to
     }
 }
 
-/// Create a complete request payload for Anthropic's API
+/// Create a complete request payload for Anthropic's API, with the `server_tools` the
+/// provider runs itself
 pub fn create_request(
     model_config: &ModelConfig,
     system: &str,
     messages: &[Message],
     tools: &[Tool],
+    server_tools: &[ServerTool],
 ) -> Result<Value> {
     let anthropic_messages = format_messages(messages);
-    let tool_specs = format_tools(tools);
+    let mut tool_specs = format_tools(tools);
+    tool_specs.extend(server_tools.iter().copied().map(server_tool_spec));
     let system_spec = format_system(system);
 
     if anthropic_messages.is_empty() {
@@ -565,6 +711,12 @@ where
         let mut accumulated_text = String::new();
         let mut accumulated_tool_calls: std::collections::HashMap<String, (String, String)> = std::collections::HashMap::new();
         let mut current_tool_id: Option<String> = None;
+        // Calls the provider runs itself, kept apart so they aren't sent on as tool requests
+        let mut server_tool_uses: std::collections::HashMap<String, (String, String)> = std::collections::HashMap::new();
+        let mut current_server_tool_id: Option<String> = None;
+        let mut server_tool_usage = ServerToolUsage::default();
+        let mut text_block_start: Option<usize> = None;
+        let mut block_citations: Vec<Value> = Vec::new();
         let mut final_usage: Option<crate::providers::base::ProviderUsage> = None;
         let mut message_id: Option<String> = None;
 
@@ -619,6 +771,10 @@ where
                 "content_block_start" => {
                     // A new content block started
                     if let Some(content_block) = event.data.get("content_block") {
+                        if content_block.get("type") == Some(&json!("text")) {
+                            text_block_start = Some(accumulated_text.chars().count());
+                            block_citations.clear();
+                        }
                         if content_block.get("type") == Some(&json!("tool_use")) {
                             if let Some(id) = content_block.get("id").and_then(|v| v.as_str()) {
                                 current_tool_id = Some(id.to_string());
@@ -627,6 +783,36 @@ where
                                 }
                             }
                         }
+                        if content_block.get("type") == Some(&json!(SERVER_TOOL_USE_TYPE)) {
+                            let id = content_block.get("id").and_then(|v| v.as_str());
+                            let name = content_block.get("name").and_then(|v| v.as_str());
+                            if let (Some(id), Some(name)) = (id, name) {
+                                current_server_tool_id = Some(id.to_string());
+                                server_tool_uses.insert(id.to_string(), (name.to_string(), String::new()));
+                            }
+                        }
+                        let is_server_tool_result = content_block.get("type")
+                            .and_then(|v| v.as_str())
+                            .is_some_and(|kind| SERVER_TOOL_RESULT_TYPES.contains(&kind));
+                        if is_server_tool_result {
+                            let call = content_block.get(TOOL_USE_ID_FIELD)
+                                .and_then(|v| v.as_str())
+                                .and_then(|id| server_tool_uses.remove(id))
+                                .and_then(|(name, input)| {
+                                    let input = serde_json::from_str(&input).unwrap_or_else(|_| json!({}));
+                                    server_tool_call(&name, input, content_block)
+                                });
+                            if let Some((tool, content)) = call {
+                                server_tool_usage.record(tool);
+                                let mut message = Message::new(
+                                    Role::Assistant,
+                                    chrono::Utc::now().timestamp(),
+                                    Vec::from(content),
+                                );
+                                message.id = message_id.clone();
+                                yield (Some(message), None);
+                            }
+                        }
                     }
                     continue;
                 }
@@ -646,6 +832,10 @@ where
                                 message.id = message_id.clone();
                                 yield (Some(message), None);
                             }
+                        } else if delta.get("type") == Some(&json!("citations_delta")) {
+                            if let Some(citation) = delta.get("citation") {
+                                block_citations.push(citation.clone());
+                            }
                         } else if delta.get("type") == Some(&json!("input_json_delta")) {
                             // Tool input delta
                             if let Some(tool_id) = &current_tool_id {
@@ -655,12 +845,38 @@ where
                                     }
                                 }
                             }
+                            if let Some(tool_id) = &current_server_tool_id {
+                                if let Some(partial_json) = delta.get("partial_json").and_then(|v| v.as_str()) {
+                                    if let Some((_name, input)) = server_tool_uses.get_mut(tool_id) {
+                                        input.push_str(partial_json);
+                                    }
+                                }
+                            }
                         }
                     }
                     continue;
                 }
                 "content_block_stop" => {
                     // Content block finished
+                    current_server_tool_id = None;
+                    if let Some(start) = text_block_start.take() {
+                        // Citations cover the whole text block, so they are sent once it ends
+                        let end = accumulated_text.chars().count();
+                        let annotations: Vec<MessageContent> = block_citations
+                            .drain(..)
+                            .filter_map(|citation| citation_to_annotation(&citation, start, end))
+                            .map(MessageContent::Annotation)
+                            .collect();
+                        if !annotations.is_empty() {
+                            let mut message = Message::new(
+                                Role::Assistant,
+                                chrono::Utc::now().timestamp(),
+                                annotations,
+                            );
+                            message.id = message_id.clone();
+                            yield (Some(message), None);
+                        }
+                    }
                     if let Some(tool_id) = current_tool_id.take() {
                         // Tool call finished, yield complete tool call
                         if let Some((name, args)) = accumulated_tool_calls.remove(&tool_id) {
@@ -758,6 +974,10 @@ where
                     }
                     break;
                 }
+                "error" => {
+                    // The request failed after the stream started, e.g. when the API is overloaded
+                    Err(stream_error_event("Anthropic stream error", &event.data))?;
+                }
                 _ => {
                     // Unknown event type, log and continue
                     tracing::debug!("Unknown streaming event type: {}", event.event_type);
@@ -767,7 +987,8 @@ where
         }
 
         // Yield final usage information if available
-        if let Some(usage) = final_usage {
+        if let Some(mut usage) = final_usage {
+            usage.usage.server_tools = server_tool_usage;
             yield (None, Some(usage));
         } else {
             tracing::debug!("🔍 Anthropic no final usage to yield");
@@ -863,6 +1084,140 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_cited_text_response() -> Result<()> {
+        let response = json!({
+            "content": [
+                { "type": "text", "text": "According to the docs, " },
+                {
+                    "type": "text",
+                    "text": "goose runs locally.",
+                    "citations": [{
+                        "type": "web_search_result_location",
+                        "url": "https://block.github.io/goose/",
+                        "title": "goose",
+                        "cited_text": "goose is a local, extensible, open source AI agent"
+                    }]
+                }
+            ]
+        });
+
+        let message = response_to_message(&response)?;
+        let annotation = message.annotations().next().unwrap();
+        assert_eq!(
+            annotation.source,
+            CitationSource::Url {
+                url: "https://block.github.io/goose/".to_string(),
+                title: Some("goose".to_string()),
+            }
+        );
+        let text: String = message.content.iter().filter_map(|c| c.as_text()).collect();
+        assert_eq!(annotation.span(&text), Some("goose runs locally."));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streaming_citations_follow_their_text_block() {
+        use futures::StreamExt;
+
+        let events = [
+            json!({"type": "message_start", "message": {"id": "msg_1"}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "See "}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "citations_delta", "citation": {
+                "type": "web_search_result_location", "url": "https://example.com", "title": "Example", "cited_text": "example"
+            }}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "the example"}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_stop"}),
+        ];
+        let lines: Vec<Result<String>> = events
+            .iter()
+            .map(|event| Ok(format!("data: {}", event)))
+            .collect();
+
+        let messages: Vec<Message> = response_to_streaming_message(futures::stream::iter(lines))
+            .filter_map(|item| async move { item.unwrap().0 })
+            .collect()
+            .await;
+
+        let annotation = messages
+            .iter()
+            .find_map(|m| m.annotations().next())
+            .unwrap();
+        assert_eq!((annotation.start, annotation.end), (Some(4), Some(15)));
+        assert_eq!(annotation.cited_text.as_deref(), Some("example"));
+        assert_eq!(messages.last().unwrap().id.as_deref(), Some("msg_1"));
+    }
+
+    #[tokio::test]
+    async fn test_streaming_server_tool_calls_become_marked_tool_calls() {
+        use futures::StreamExt;
+
+        let events = [
+            json!({"type": "message_start", "message": {"id": "msg_1", "model": "claude-sonnet-4-5", "usage": {"input_tokens": 10, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "server_tool_use", "id": "srvtoolu_1", "name": "web_search", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "input_json_delta", "partial_json": "{\"query\": "}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "input_json_delta", "partial_json": "\"goose\"}"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "web_search_tool_result", "tool_use_id": "srvtoolu_1", "content": [
+                {"type": "web_search_result", "url": "https://example.com/goose", "title": "goose"}
+            ]}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "message_stop"}),
+        ];
+        let lines: Vec<Result<String>> = events
+            .iter()
+            .map(|event| Ok(format!("data: {}", event)))
+            .collect();
+
+        let items: Vec<_> = response_to_streaming_message(futures::stream::iter(lines))
+            .map(|item| item.unwrap())
+            .collect()
+            .await;
+
+        let messages: Vec<&Message> = items.iter().filter_map(|(m, _)| m.as_ref()).collect();
+        assert_eq!(messages.len(), 1);
+        let MessageContent::ToolRequest(request) = &messages[0].content[0] else {
+            panic!("Expected ToolRequest content");
+        };
+        assert!(request.is_server_tool());
+        assert_eq!(
+            request.tool_call.as_ref().unwrap().arguments,
+            Some(object!({"query": "goose"}))
+        );
+        let MessageContent::ToolResponse(response) = &messages[0].content[1] else {
+            panic!("Expected ToolResponse content");
+        };
+        assert!(response.is_server_tool());
+        let result = response.tool_result.as_ref().unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "- goose (https://example.com/goose)"
+        );
+
+        let usage = items.iter().find_map(|(_, u)| u.as_ref()).unwrap();
+        assert_eq!(usage.usage.server_tools.web_search, 1);
+    }
+
+    #[tokio::test]
+    async fn stream_error_events_keep_their_type() {
+        use futures::StreamExt;
+
+        let event = json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}});
+        let lines: Vec<Result<String>> = vec![Ok(format!("data: {}", event))];
+
+        let items: Vec<_> = response_to_streaming_message(futures::stream::iter(lines))
+            .collect()
+            .await;
+        let error =
+            ProviderError::from_stream_error(items.into_iter().next().unwrap().unwrap_err());
+        assert_eq!(error, ProviderError::Overloaded("Overloaded".to_string()));
+        assert!(error.code().is_retryable());
+    }
+
     #[test]
     fn test_parse_thinking_response() -> Result<()> {
         let response = json!({
@@ -1057,7 +1412,7 @@ mod tests {
         let mut config = cfg("claude-opus-4-6");
         config.max_tokens = Some(4096);
         let messages = vec![Message::user().with_text("Hello")];
-        let payload = create_request(&config, "system", &messages, &[])?;
+        let payload = create_request(&config, "system", &messages, &[], &[])?;
 
         assert_eq!(payload["thinking"]["type"], "adaptive");
         assert_eq!(payload["output_config"]["effort"], "high");
@@ -1084,7 +1439,7 @@ mod tests {
         config.request_params = Some(params);
 
         let messages = vec![Message::user().with_text("Hello")];
-        let payload = create_request(&config, "system", &messages, &[])?;
+        let payload = create_request(&config, "system", &messages, &[], &[])?;
 
         assert_eq!(payload["thinking"]["type"], "enabled");
         assert_eq!(payload["thinking"]["budget_tokens"], 10000);
@@ -1102,7 +1457,7 @@ mod tests {
 
         let config = cfg("claude-sonnet-4-20250514");
         let messages = vec![Message::user().with_text("Hello")];
-        let payload = create_request(&config, "system", &messages, &[])?;
+        let payload = create_request(&config, "system", &messages, &[], &[])?;
 
         assert!(payload.get("thinking").is_none());
         assert!(payload.get("output_config").is_none());
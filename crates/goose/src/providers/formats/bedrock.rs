@@ -98,13 +98,22 @@ pub fn to_bedrock_message_content(content: &MessageContent) -> Result<bedrock::C
         }
         MessageContent::ToolResponse(tool_res) => {
             let content = match &tool_res.tool_result {
-                Ok(result) => Some(
-                    result
+                Ok(result) => {
+                    let mut blocks: Vec<bedrock::ToolResultContentBlock> = result
                         .content
                         .iter()
                         .map(|c| to_bedrock_tool_result_content_block(&tool_res.id, c.clone()))
-                        .collect::<Result<_>>()?,
-                ),
+                        .collect::<Result<_>>()?;
+                    // Structured content goes as JSON, in place of the text it was serialized to
+                    if let Some(structured) = &result.structured_content {
+                        blocks.retain(|block| !block.is_text());
+                        blocks.insert(
+                            0,
+                            bedrock::ToolResultContentBlock::Json(to_bedrock_json(structured)),
+                        );
+                    }
+                    Some(blocks)
+                }
                 Err(error) => {
                     // For errors, create a text content block with the error message
                     Some(vec![bedrock::ToolResultContentBlock::Text(format!(
@@ -130,6 +139,11 @@ pub fn to_bedrock_message_content(content: &MessageContent) -> Result<bedrock::C
             // Bedrock doesn't use this format, so skip
             bedrock::ContentBlock::Text("".to_string())
         }
+        MessageContent::Attachment(_) => {
+            // Attachments are rendered to text and images before they reach the provider
+            bedrock::ContentBlock::Text("".to_string())
+        }
+        MessageContent::Annotation(_) => bedrock::ContentBlock::Text("".to_string()),
     })
 }
 
@@ -1,4 +1,5 @@
 use crate::conversation::message::{Message, MessageContent};
+use crate::mcp_utils::structured_content_as_text;
 use crate::model::ModelConfig;
 use crate::providers::utils::{
     convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
@@ -72,6 +73,7 @@ fn format_tool_response(
                     _ => tool_content.push(content.no_annotation()),
                 }
             }
+            tool_content.extend(structured_content_as_text(call_result).map(Content::text));
 
             let tool_response_content: Value = json!(tool_content
                 .iter()
@@ -209,6 +211,10 @@ fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<Data
                     // Reasoning content is for OpenAI-compatible APIs (e.g., DeepSeek)
                     // Databricks doesn't use this format, so skip
                 }
+                MessageContent::Attachment(_) => {
+                    // Attachments are rendered to text and images before they reach the provider
+                }
+                MessageContent::Annotation(_) => {}
             }
         }
 
@@ -220,7 +220,8 @@ fn create_anthropic_request(
     messages: &[Message],
     tools: &[Tool],
 ) -> Result<Value> {
-    let mut request = anthropic::create_request(model_config, system, messages, tools)?;
+    // Vertex AI doesn't run Anthropic's or Gemini's server tools
+    let mut request = anthropic::create_request(model_config, system, messages, tools, &[])?;
 
     let obj = request
         .as_object_mut()
@@ -253,7 +254,7 @@ fn create_google_request(
     messages: &[Message],
     tools: &[Tool],
 ) -> Result<Value> {
-    google::create_request(model_config, system, messages, tools)
+    google::create_request(model_config, system, messages, tools, &[])
 }
 
 /// Creates a provider-specific request payload and context.
@@ -1,6 +1,7 @@
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
+use crate::providers::server_tools::{self, ServerTool, ServerToolUsage};
 use crate::providers::utils::{is_valid_function_name, sanitize_function_name};
 use anyhow::Result;
 use rmcp::model::{
@@ -138,11 +139,17 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                             if text.is_empty() {
                                 text = "Tool call is done.".to_string();
                             }
+                            // Structured content is sent as is; the text of such results is
+                            // usually the same data serialized
+                            let function_result = match &result.structured_content {
+                                Some(Value::Object(structured)) => json!(structured),
+                                Some(structured) => json!({"result": structured}),
+                                None => json!({"content": {"text": text}}),
+                            };
                             let mut part = Map::new();
                             let mut function_response = Map::new();
                             function_response.insert("name".to_string(), json!(response.id));
-                            function_response
-                                .insert("response".to_string(), json!({"content": {"text": text}}));
+                            function_response.insert("response".to_string(), function_result);
                             part.insert("functionResponse".to_string(), json!(function_response));
                             if include_signature {
                                 if let Some(signature) = get_thought_signature(&response.metadata) {
@@ -307,14 +314,83 @@ fn process_response_part_impl(
     }
 }
 
+/// Code runs and searches Gemini did itself, paired up across the parts and chunks of a
+/// response
+#[derive(Default)]
+struct ServerToolCalls {
+    pending_code: Option<Value>,
+    searched: bool,
+    usage: ServerToolUsage,
+}
+
+impl ServerToolCalls {
+    /// What a code execution part adds to the message, or None for any other part
+    fn part(&mut self, part: &Value) -> Option<Vec<MessageContent>> {
+        if let Some(code) = part.get("executableCode") {
+            self.pending_code = Some(code.clone());
+            return Some(Vec::new());
+        }
+        let result = part.get("codeExecutionResult")?;
+        let code = self.pending_code.take().unwrap_or_else(|| json!({}));
+        let outcome = result.get("outcome").and_then(|o| o.as_str());
+        let output = result
+            .get("output")
+            .and_then(|o| o.as_str())
+            .unwrap_or_default();
+        self.usage.record(ServerTool::CodeExecution);
+        let call = server_tools::call(
+            &Uuid::new_v4().to_string(),
+            ServerTool::CodeExecution,
+            code,
+            output.to_string(),
+            outcome.is_some_and(|outcome| outcome != "OUTCOME_OK"),
+        );
+        Some(call.into())
+    }
+
+    /// The search a candidate's answer is grounded on, once per response
+    fn grounding(&mut self, candidate: &Value) -> Option<[MessageContent; 2]> {
+        if self.searched {
+            return None;
+        }
+        let metadata = candidate.get("groundingMetadata")?;
+        let queries = metadata
+            .get("webSearchQueries")?
+            .as_array()
+            .filter(|queries| !queries.is_empty())?;
+        self.searched = true;
+        let sources: Vec<String> = metadata
+            .get("groundingChunks")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|chunk| {
+                let web = chunk.get("web")?;
+                let uri = web.get("uri")?.as_str()?;
+                let title = web.get("title").and_then(|t| t.as_str()).unwrap_or(uri);
+                Some(format!("- {} ({})", title, uri))
+            })
+            .collect();
+        self.usage.record(ServerTool::WebSearch);
+        Some(server_tools::call(
+            &Uuid::new_v4().to_string(),
+            ServerTool::WebSearch,
+            json!({ "queries": queries }),
+            sources.join("\n"),
+            false,
+        ))
+    }
+}
+
 pub fn response_to_message(response: Value) -> Result<Message> {
     let role = Role::Assistant;
     let created = chrono::Utc::now().timestamp();
 
-    let parts = response
+    let candidate = response
         .get("candidates")
         .and_then(|v| v.as_array())
-        .and_then(|c| c.first())
+        .and_then(|c| c.first());
+    let parts = candidate
         .and_then(|c| c.get("content"))
         .and_then(|c| c.get("parts"))
         .and_then(|p| p.as_array());
@@ -325,11 +401,18 @@ pub fn response_to_message(response: Value) -> Result<Message> {
 
     let has_function_calls = parts.iter().any(|p| p.get("functionCall").is_some());
 
-    let mut content = Vec::new();
+    let mut server_tool_calls = ServerToolCalls::default();
+    // The search comes first, as the answer is written from it
+    let mut content: Vec<MessageContent> = candidate
+        .and_then(|c| server_tool_calls.grounding(c))
+        .map(Vec::from)
+        .unwrap_or_default();
     let mut last_signature: Option<String> = None;
 
     for part in parts {
-        if let Some(msg_content) =
+        if let Some(call) = server_tool_calls.part(part) {
+            content.extend(call);
+        } else if let Some(msg_content) =
             process_response_part_non_streaming(part, &mut last_signature, has_function_calls)
         {
             content.push(msg_content);
@@ -383,6 +466,7 @@ where
         let mut last_signature: Option<String> = None;
         let stream_id = Uuid::new_v4().to_string();
         let mut incomplete_data: Option<String> = None;
+        let mut server_tool_calls = ServerToolCalls::default();
 
         while let Some(line_result) = stream.next().await {
             let line = line_result?;
@@ -457,16 +541,28 @@ where
                 }
             }
 
-            let parts = chunk
+            let candidate = chunk
                 .get("candidates")
                 .and_then(|v| v.as_array())
-                .and_then(|c| c.first())
+                .and_then(|c| c.first());
+            let parts = candidate
                 .and_then(|c| c.get("content"))
                 .and_then(|c| c.get("parts"))
                 .and_then(|p| p.as_array());
 
             if let Some(parts) = parts {
                 for part in parts {
+                    if let Some(call) = server_tool_calls.part(part) {
+                        if !call.is_empty() {
+                            let message = Message::new(
+                                Role::Assistant,
+                                chrono::Utc::now().timestamp(),
+                                call,
+                            ).with_id(stream_id.clone());
+                            yield (Some(message), None);
+                        }
+                        continue;
+                    }
                     // Always emit text as regular text during streaming — we can't
                     // know yet whether function calls will follow.
                     if let Some(content) = process_response_part_impl(part, &mut last_signature, SignedTextHandling::SignedTextAsRegularText) {
@@ -479,9 +575,20 @@ where
                     }
                 }
             }
+
+            // Grounding comes with the last chunks, after the answer it backs
+            if let Some(search) = candidate.and_then(|c| server_tool_calls.grounding(c)) {
+                let message = Message::new(
+                    Role::Assistant,
+                    chrono::Utc::now().timestamp(),
+                    Vec::from(search),
+                ).with_id(stream_id.clone());
+                yield (Some(message), None);
+            }
         }
 
-        if let Some(usage) = final_usage {
+        if let Some(mut usage) = final_usage {
+            usage.usage.server_tools = server_tool_calls.usage;
             yield (None, Some(usage));
         }
     }
@@ -500,7 +607,12 @@ struct SystemInstruction<'a> {
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct ToolsWrapper {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     function_declarations: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    google_search: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_execution: Option<Value>,
 }
 
 #[derive(Serialize)]
@@ -568,17 +680,22 @@ fn get_thinking_config(model_config: &ModelConfig) -> Option<ThinkingConfig> {
     Some(ThinkingConfig { thinking_level })
 }
 
+/// Create a request payload for Gemini, with the `server_tools` the provider runs itself
 pub fn create_request(
     model_config: &ModelConfig,
     system: &str,
     messages: &[Message],
     tools: &[Tool],
+    server_tools: &[ServerTool],
 ) -> Result<Value> {
-    let tools_wrapper = if tools.is_empty() {
+    let tools_wrapper = if tools.is_empty() && server_tools.is_empty() {
         None
     } else {
+        let enabled = |tool| server_tools.contains(&tool).then(|| json!({}));
         Some(ToolsWrapper {
             function_declarations: format_tools(tools),
+            google_search: enabled(ServerTool::WebSearch),
+            code_execution: enabled(ServerTool::CodeExecution),
         })
     };
 
@@ -745,6 +862,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_message_to_google_spec_tool_result_structured() {
+        let mut result = CallToolResult::success(vec![Content::text(r#"{"files":2}"#)]);
+        result.structured_content = Some(json!({"files": 2}));
+        let messages = vec![Message::user().with_tool_response("response_id", Ok(result))];
+        let payload = format_messages(&messages);
+        assert_eq!(
+            payload[0]["parts"][0]["functionResponse"]["response"],
+            json!({"files": 2})
+        );
+    }
+
     #[test]
     fn test_message_to_google_spec_tool_result_multiple_texts() {
         let tool_result: Vec<Content> = vec![
@@ -841,6 +970,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_response_to_message_with_server_tool_calls() {
+        let response = json!({
+            "candidates": [{
+                "content": {
+                    "parts": [
+                        {"executableCode": {"language": "PYTHON", "code": "print(1 + 1)"}},
+                        {"codeExecutionResult": {"outcome": "OUTCOME_OK", "output": "2\n"}},
+                        {"text": "It is 2, says goose."}
+                    ]
+                },
+                "groundingMetadata": {
+                    "webSearchQueries": ["goose agent"],
+                    "groundingChunks": [{"web": {"uri": "https://example.com/goose", "title": "goose"}}]
+                }
+            }]
+        });
+        let message = response_to_message(response).unwrap();
+        assert_eq!(message.content.len(), 5);
+
+        let calls: Vec<(String, Value)> = message
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                MessageContent::ToolRequest(request) if request.is_server_tool() => {
+                    let call = request.tool_call.as_ref().unwrap();
+                    Some((call.name.to_string(), json!(call.arguments)))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                (
+                    "web_search".to_string(),
+                    json!({"queries": ["goose agent"]})
+                ),
+                (
+                    "code_execution".to_string(),
+                    json!({"language": "PYTHON", "code": "print(1 + 1)"})
+                ),
+            ]
+        );
+        let MessageContent::ToolResponse(search) = &message.content[1] else {
+            panic!("Expected ToolResponse content");
+        };
+        let result = search.tool_result.as_ref().unwrap();
+        assert_eq!(
+            result.content[0].as_text().unwrap().text,
+            "- goose (https://example.com/goose)"
+        );
+        assert_eq!(message.content[4].as_text(), Some("It is 2, says goose."));
+    }
+
     #[test]
     fn test_response_to_message_with_invalid_function_name() {
         let response = json!({
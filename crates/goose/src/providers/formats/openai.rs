@@ -1,7 +1,10 @@
-use crate::conversation::message::{Message, MessageContent, ProviderMetadata};
-use crate::mcp_utils::extract_text_from_resource;
+use crate::conversation::message::{
+    AnnotationContent, CitationSource, Message, MessageContent, ProviderMetadata,
+};
+use crate::mcp_utils::{extract_text_from_resource, structured_content_as_text};
 use crate::model::ModelConfig;
 use crate::providers::base::{ProviderUsage, Usage};
+use crate::providers::errors::stream_error_event;
 use crate::providers::utils::{
     convert_image, detect_image_path, is_valid_function_name, load_image_file, safely_parse_json,
     sanitize_function_name, ImageFormat,
@@ -55,6 +58,7 @@ struct Delta {
     reasoning_details: Option<Vec<Value>>,
     #[serde(alias = "reasoning")]
     reasoning_content: Option<String>,
+    annotations: Option<Vec<Value>>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -116,6 +120,11 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                 MessageContent::SystemNotification(_) => {
                     continue;
                 }
+                MessageContent::Attachment(_) => {
+                    // Attachments are rendered to text and images before they reach the provider
+                    continue;
+                }
+                MessageContent::Annotation(_) => continue,
                 MessageContent::Reasoning(r) => {
                     reasoning_text.push_str(&r.text);
                 }
@@ -188,6 +197,8 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                                     }
                                 }
                             }
+                            tool_content
+                                .extend(structured_content_as_text(result).map(Content::text));
                             let tool_response_content: Value = json!(tool_content
                                 .iter()
                                 .map(|content| match content.deref() {
@@ -319,6 +330,47 @@ pub fn format_tools(tools: &[Tool]) -> anyhow::Result<Vec<Value>> {
 }
 
 /// Convert OpenAI's API response to internal Message format
+/// Annotations on OpenAI response text, in the chat completions shape
+/// (`{"type": "url_citation", "url_citation": {...}}`) or the flat one of the responses API.
+/// Their indices count from the start of the text they came with, which begins `offset`
+/// characters into the message.
+pub fn annotations_from_openai(annotations: &[Value], offset: usize) -> Vec<MessageContent> {
+    annotations
+        .iter()
+        .filter_map(|annotation| {
+            let kind = annotation.get("type")?.as_str()?;
+            let fields = annotation.get(kind).unwrap_or(annotation);
+            let source = match kind {
+                "url_citation" => CitationSource::Url {
+                    url: fields.get("url")?.as_str()?.to_string(),
+                    title: fields
+                        .get("title")
+                        .and_then(|t| t.as_str())
+                        .map(String::from),
+                },
+                "file_citation" => CitationSource::File {
+                    path: fields
+                        .get("filename")
+                        .or_else(|| fields.get("file_id"))?
+                        .as_str()?
+                        .to_string(),
+                    start_line: None,
+                    end_line: None,
+                },
+                _ => return None,
+            };
+            let index = |key: &str| fields.get(key).and_then(|i| i.as_u64()).map(|i| i as usize);
+            let annotation = AnnotationContent::new(source);
+            Some(MessageContent::Annotation(
+                match (index("start_index"), index("end_index")) {
+                    (Some(start), Some(end)) => annotation.with_span(offset + start, offset + end),
+                    _ => annotation,
+                },
+            ))
+        })
+        .collect()
+}
+
 pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
     let Some(original) = response
         .get("choices")
@@ -357,6 +409,10 @@ pub fn response_to_message(response: &Value) -> anyhow::Result<Message> {
         }
     }
 
+    if let Some(annotations) = original.get("annotations").and_then(|a| a.as_array()) {
+        content.extend(annotations_from_openai(annotations, 0));
+    }
+
     if let Some(tool_calls) = original.get("tool_calls") {
         if let Some(tool_calls_array) = tool_calls.as_array() {
             for tool_call in tool_calls_array {
@@ -553,7 +609,13 @@ where
 
             let chunk: StreamingChunk = serde_json::from_str(line
                 .ok_or_else(|| anyhow!("unexpected stream format"))?)
-                .map_err(|e| anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line))?;
+                .map_err(|e| match line.and_then(|l| serde_json::from_str::<Value>(l).ok()) {
+                    // Providers send an error object in place of a chunk when a stream fails
+                    Some(error) if error.get("error").is_some() => {
+                        stream_error_event("Stream error", &error)
+                    }
+                    _ => anyhow!("Failed to parse streaming chunk: {}: {:?}", e, &line),
+                })?;
 
             if !chunk.choices.is_empty() {
                 if let Some(details) = &chunk.choices[0].delta.reasoning_details {
@@ -701,7 +763,10 @@ where
                     Some(msg),
                     usage,
                 )
-            } else if chunk.choices[0].delta.content.is_some() || chunk.choices[0].delta.reasoning_content.is_some() {
+            } else if chunk.choices[0].delta.content.is_some()
+                || chunk.choices[0].delta.reasoning_content.is_some()
+                || chunk.choices[0].delta.annotations.is_some()
+            {
                 let mut content = Vec::new();
 
                 if let Some(reasoning) = &chunk.choices[0].delta.reasoning_content {
@@ -716,6 +781,11 @@ where
                     }
                 }
 
+                // Search models stream their citations alongside the text
+                if let Some(annotations) = &chunk.choices[0].delta.annotations {
+                    content.extend(annotations_from_openai(annotations, 0));
+                }
+
                 if !content.is_empty() {
                     let mut msg = Message::new(
                         Role::Assistant,
@@ -1166,6 +1236,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_response_to_message_url_citations() -> anyhow::Result<()> {
+        let response = json!({
+            "choices": [{
+                "message": {
+                    "content": "The sky is blue (NASA).",
+                    "annotations": [{
+                        "type": "url_citation",
+                        "url_citation": {
+                            "start_index": 16,
+                            "end_index": 22,
+                            "url": "https://nasa.gov/sky",
+                            "title": "Why is the sky blue?"
+                        }
+                    }, {
+                        "type": "unknown_kind"
+                    }]
+                }
+            }]
+        });
+
+        let message = response_to_message(&response)?;
+        let annotations: Vec<_> = message.annotations().collect();
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(
+            annotations[0].span("The sky is blue (NASA)."),
+            Some("(NASA)")
+        );
+        assert!(matches!(
+            &annotations[0].source,
+            CitationSource::Url { url, .. } if url == "https://nasa.gov/sky"
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_response_to_message_valid_toolrequest() -> anyhow::Result<()> {
         let response: Value = serde_json::from_str(OPENAI_TOOL_USE_RESPONSE)?;
@@ -1331,6 +1437,18 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_format_messages_tool_response_with_only_structured_content() {
+        let mut result = CallToolResult::success(vec![]);
+        result.structured_content = Some(json!({"exit_code": 0}));
+        let message = Message::user().with_tool_response("tool1", Ok(result));
+
+        let spec = format_messages(&[message], &ImageFormat::OpenAi);
+
+        assert_eq!(spec[0]["role"], "tool");
+        assert_eq!(spec[0]["content"], r#"{"exit_code":0}"#);
+    }
+
     #[test]
     fn test_format_messages_tool_request_with_some_arguments() -> anyhow::Result<()> {
         // Test that tool calls with Some arguments are properly JSON-serialized
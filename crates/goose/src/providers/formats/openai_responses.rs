@@ -1,6 +1,10 @@
 use crate::conversation::message::{Message, MessageContent};
+use crate::mcp_utils::structured_content_as_text;
 use crate::model::ModelConfig;
 use crate::providers::base::{ProviderUsage, Usage};
+use crate::providers::errors::stream_error_event;
+use crate::providers::formats::openai::annotations_from_openai;
+use crate::providers::server_tools::{self, ServerTool};
 use anyhow::{anyhow, Error};
 use async_stream::try_stream;
 use chrono;
@@ -47,6 +51,33 @@ pub enum ResponseOutputItem {
         name: String,
         arguments: String,
     },
+    WebSearchCall {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        action: Option<Value>,
+    },
+    CodeInterpreterCall {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        outputs: Option<Vec<Value>>,
+    },
+}
+
+impl ResponseOutputItem {
+    /// The server tool that made this item, if one did
+    fn server_tool(&self) -> Option<ServerTool> {
+        match self {
+            Self::WebSearchCall { .. } => Some(ServerTool::WebSearch),
+            Self::CodeInterpreterCall { .. } => Some(ServerTool::CodeExecution),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -257,6 +288,33 @@ pub enum ResponseOutputItemInfo {
         name: String,
         arguments: String,
     },
+    WebSearchCall {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        action: Option<Value>,
+    },
+    CodeInterpreterCall {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        outputs: Option<Vec<Value>>,
+    },
+}
+
+impl ResponseOutputItemInfo {
+    /// The server tool that made this item, if one did
+    fn server_tool(&self) -> Option<ServerTool> {
+        match self {
+            Self::WebSearchCall { .. } => Some(ServerTool::WebSearch),
+            Self::CodeInterpreterCall { .. } => Some(ServerTool::CodeExecution),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -351,6 +409,7 @@ fn add_message_items(input_items: &mut Vec<Value>, messages: &[Message]) {
                                         None
                                     }
                                 })
+                                .chain(structured_content_as_text(contents))
                                 .collect();
 
                             if !text_content.is_empty() {
@@ -396,6 +455,7 @@ pub fn create_responses_request(
     system: &str,
     messages: &[Message],
     tools: &[Tool],
+    server_tools: &[ServerTool],
 ) -> anyhow::Result<Value, Error> {
     let mut input_items = Vec::new();
 
@@ -417,8 +477,8 @@ pub fn create_responses_request(
         "store": false,  // Don't store responses on server (we replay history ourselves)
     });
 
-    if !tools.is_empty() {
-        let tools_spec: Vec<Value> = tools
+    if !tools.is_empty() || !server_tools.is_empty() {
+        let mut tools_spec: Vec<Value> = tools
             .iter()
             .map(|tool| {
                 json!({
@@ -429,6 +489,12 @@ pub fn create_responses_request(
                 })
             })
             .collect();
+        tools_spec.extend(server_tools.iter().map(|tool| match tool {
+            ServerTool::WebSearch => json!({"type": "web_search"}),
+            ServerTool::CodeExecution => {
+                json!({"type": "code_interpreter", "container": {"type": "auto"}})
+            }
+        }));
 
         payload
             .as_object_mut()
@@ -436,6 +502,17 @@ pub fn create_responses_request(
             .insert("tools".to_string(), json!(tools_spec));
     }
 
+    if !server_tools.is_empty() {
+        // Without these the calls come back without their sources and output
+        payload.as_object_mut().unwrap().insert(
+            "include".to_string(),
+            json!([
+                "web_search_call.action.sources",
+                "code_interpreter_call.outputs"
+            ]),
+        );
+    }
+
     if let Some(temp) = model_config.temperature {
         payload
             .as_object_mut()
@@ -453,6 +530,7 @@ pub fn create_responses_request(
 
 pub fn responses_api_to_message(response: &ResponsesApiResponse) -> anyhow::Result<Message> {
     let mut content = Vec::new();
+    let mut text_chars = 0;
 
     for item in &response.output {
         match item {
@@ -465,10 +543,14 @@ pub fn responses_api_to_message(response: &ResponsesApiResponse) -> anyhow::Resu
             } => {
                 for block in msg_content {
                     match block {
-                        ResponseContentBlock::OutputText { text, .. } => {
+                        ResponseContentBlock::OutputText { text, annotations } => {
                             if !text.is_empty() {
                                 content.push(MessageContent::text(text));
                             }
+                            if let Some(annotations) = annotations {
+                                content.extend(annotations_from_openai(annotations, text_chars));
+                            }
+                            text_chars += text.chars().count();
                         }
                         ResponseContentBlock::ToolCall { id, name, input } => {
                             content.push(MessageContent::tool_request(
@@ -499,6 +581,22 @@ pub fn responses_api_to_message(response: &ResponsesApiResponse) -> anyhow::Resu
                         .with_arguments(object(parsed_args))),
                 ));
             }
+            ResponseOutputItem::WebSearchCall { id, status, action } => {
+                content.extend(web_search_call(id, status.as_deref(), action.as_ref()));
+            }
+            ResponseOutputItem::CodeInterpreterCall {
+                id,
+                status,
+                code,
+                outputs,
+            } => {
+                content.extend(code_interpreter_call(
+                    id,
+                    status.as_deref(),
+                    code.as_deref(),
+                    outputs.as_deref(),
+                ));
+            }
         }
     }
 
@@ -510,13 +608,80 @@ pub fn responses_api_to_message(response: &ResponsesApiResponse) -> anyhow::Resu
 }
 
 pub fn get_responses_usage(response: &ResponsesApiResponse) -> Usage {
-    response.usage.as_ref().map_or_else(Usage::default, |u| {
+    let mut usage = response.usage.as_ref().map_or_else(Usage::default, |u| {
         Usage::new(
             Some(u.input_tokens),
             Some(u.output_tokens),
             Some(u.total_tokens),
         )
-    })
+    });
+    for tool in response
+        .output
+        .iter()
+        .filter_map(ResponseOutputItem::server_tool)
+    {
+        usage.server_tools.record(tool);
+    }
+    usage
+}
+
+/// A web search OpenAI ran, with the sources it read as the result
+fn web_search_call(id: &str, status: Option<&str>, action: Option<&Value>) -> [MessageContent; 2] {
+    let mut arguments = action.cloned().unwrap_or_else(|| json!({}));
+    let sources = arguments
+        .as_object_mut()
+        .and_then(|action| action.remove("sources"));
+    let sources: Vec<String> = sources
+        .as_ref()
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|source| source.get("url").and_then(Value::as_str))
+        .map(|url| format!("- {}", url))
+        .collect();
+    let result = if sources.is_empty() {
+        "No sources returned".to_string()
+    } else {
+        sources.join("\n")
+    };
+    server_tools::call(
+        id,
+        ServerTool::WebSearch,
+        arguments,
+        result,
+        status == Some("failed"),
+    )
+}
+
+/// Code OpenAI ran, with its logs and images as the result
+fn code_interpreter_call(
+    id: &str,
+    status: Option<&str>,
+    code: Option<&str>,
+    outputs: Option<&[Value]>,
+) -> [MessageContent; 2] {
+    let outputs: Vec<String> = outputs
+        .into_iter()
+        .flatten()
+        .filter_map(|output| match output.get("type").and_then(Value::as_str) {
+            Some("logs") => output
+                .get("logs")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            Some("image") => output
+                .get("url")
+                .and_then(Value::as_str)
+                .map(|url| format!("[image: {}]", url)),
+            _ => None,
+        })
+        .collect();
+    server_tools::call(
+        id,
+        ServerTool::CodeExecution,
+        json!({"code": code.unwrap_or_default()}),
+        outputs.join("\n"),
+        status == Some("failed"),
+    )
 }
 
 fn process_streaming_output_items(
@@ -524,6 +689,7 @@ fn process_streaming_output_items(
     is_text_response: bool,
 ) -> Vec<MessageContent> {
     let mut content = Vec::new();
+    let mut text_chars = 0;
 
     for item in output_items {
         match item {
@@ -533,10 +699,17 @@ fn process_streaming_output_items(
             ResponseOutputItemInfo::Message { content: parts, .. } => {
                 for part in parts {
                     match part {
-                        ContentPart::OutputText { text, .. } => {
+                        ContentPart::OutputText {
+                            text, annotations, ..
+                        } => {
                             if !text.is_empty() && !is_text_response {
                                 content.push(MessageContent::text(&text));
                             }
+                            // Sent even when the text was streamed, as they only come at the end
+                            if let Some(annotations) = annotations {
+                                content.extend(annotations_from_openai(&annotations, text_chars));
+                            }
+                            text_chars += text.chars().count();
                         }
                         ContentPart::ToolCall {
                             id,
@@ -575,6 +748,22 @@ fn process_streaming_output_items(
                     Ok(CallToolRequestParams::new(name).with_arguments(object(parsed_args))),
                 ));
             }
+            ResponseOutputItemInfo::WebSearchCall { id, status, action } => {
+                content.extend(web_search_call(&id, status.as_deref(), action.as_ref()));
+            }
+            ResponseOutputItemInfo::CodeInterpreterCall {
+                id,
+                status,
+                code,
+                outputs,
+            } => {
+                content.extend(code_interpreter_call(
+                    &id,
+                    status.as_deref(),
+                    code.as_deref(),
+                    outputs.as_deref(),
+                ));
+            }
         }
     }
 
@@ -695,11 +884,11 @@ where
                 }
 
                 ResponsesStreamEvent::ResponseFailed { error, .. } => {
-                    Err(anyhow!("Responses API failed: {:?}", error))?;
+                    Err(stream_error_event("Responses API failed", &error))?;
                 }
 
                 ResponsesStreamEvent::Error { error } => {
-                    Err(anyhow!("Responses API error: {:?}", error))?;
+                    Err(stream_error_event("Responses API error", &error))?;
                 }
 
                 _ => {
@@ -709,6 +898,11 @@ where
         }
 
         // Process final output items and yield usage data
+        if let Some(usage) = final_usage.as_mut() {
+            for tool in output_items.iter().filter_map(ResponseOutputItemInfo::server_tool) {
+                usage.usage.server_tools.record(tool);
+            }
+        }
         let content = process_streaming_output_items(output_items, is_text_response);
 
         if !content.is_empty() {
@@ -774,6 +968,71 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_responses_stream_keeps_citations_of_streamed_text() -> anyhow::Result<()> {
+        let lines = vec![
+            r#"data: {"type":"response.output_text.delta","sequence_number":1,"item_id":"msg_1","output_index":0,"content_index":0,"delta":"Rust is fast."}"#.to_string(),
+            r#"data: {"type":"response.completed","sequence_number":2,"response":{"id":"resp_1","object":"response","created_at":1737368310,"status":"completed","model":"gpt-5","output":[{"type":"message","id":"msg_1","status":"completed","role":"assistant","content":[{"type":"output_text","text":"Rust is fast.","annotations":[{"type":"url_citation","start_index":8,"end_index":12,"url":"https://www.rust-lang.org","title":"Rust"}]}]}]}}"#.to_string(),
+        ];
+
+        let response_stream = tokio_stream::iter(lines.into_iter().map(Ok));
+        let messages = responses_api_to_streaming_message(response_stream);
+        futures::pin_mut!(messages);
+
+        let mut text = String::new();
+        let mut annotations = Vec::new();
+        while let Some(item) = messages.next().await {
+            if let (Some(msg), _) = item? {
+                text.push_str(&msg.as_concat_text());
+                annotations.extend(msg.annotations().cloned());
+            }
+        }
+
+        assert_eq!(text, "Rust is fast.");
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].span(&text), Some("fast"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_responses_stream_server_tool_calls_are_marked_and_counted() -> anyhow::Result<()>
+    {
+        let lines = vec![
+            r#"data: {"type":"response.completed","sequence_number":1,"response":{"id":"resp_1","object":"response","created_at":1737368310,"status":"completed","model":"gpt-5","output":[{"type":"web_search_call","id":"ws_1","status":"completed","action":{"type":"search","query":"goose agent","sources":[{"type":"url","url":"https://example.com/goose"}]}},{"type":"code_interpreter_call","id":"ci_1","status":"completed","code":"print(1 + 1)","container_id":"cntr_1","outputs":[{"type":"logs","logs":"2"}]}],"usage":{"input_tokens":10,"output_tokens":4,"total_tokens":14}}}"#.to_string(),
+        ];
+
+        let response_stream = tokio_stream::iter(lines.into_iter().map(Ok));
+        let messages = responses_api_to_streaming_message(response_stream);
+        futures::pin_mut!(messages);
+
+        let (message, usage) = messages.next().await.unwrap()?;
+        let message = message.unwrap();
+        assert_eq!(message.content.len(), 4);
+        let MessageContent::ToolRequest(search) = &message.content[0] else {
+            panic!("Expected ToolRequest content");
+        };
+        assert!(search.is_server_tool());
+        assert_eq!(
+            search.tool_call.as_ref().unwrap().arguments,
+            Some(object!({"type": "search", "query": "goose agent"}))
+        );
+        let MessageContent::ToolResponse(output) = &message.content[3] else {
+            panic!("Expected ToolResponse content");
+        };
+        assert!(output.is_server_tool());
+        let result = output.tool_result.as_ref().unwrap();
+        assert_eq!(result.content[0].as_text().unwrap().text, "2");
+
+        let server_tools = usage.unwrap().usage.server_tools;
+        assert_eq!(
+            (server_tools.web_search, server_tools.code_execution),
+            (1, 1)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_responses_stream_error_event_still_returns_error() -> anyhow::Result<()> {
         let lines = vec![
@@ -830,7 +1089,7 @@ mod tests {
                 ),
         ];
 
-        let result = create_responses_request(&model_config, "", &messages, &[]).unwrap();
+        let result = create_responses_request(&model_config, "", &messages, &[], &[]).unwrap();
         let input = result["input"].as_array().unwrap();
 
         let types: Vec<&str> = input
@@ -1,4 +1,5 @@
 use crate::conversation::message::{Message, MessageContent};
+use crate::mcp_utils::structured_content_as_text;
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
@@ -39,6 +40,7 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                             .content
                             .iter()
                             .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                            .chain(structured_content_as_text(result))
                             .collect::<Vec<_>>()
                             .join("\n");
 
@@ -69,6 +71,10 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                     // Reasoning content is for OpenAI-compatible APIs (e.g., DeepSeek)
                     // Snowflake doesn't use this format, so skip
                 }
+                MessageContent::Attachment(_) => {
+                    // Attachments are rendered to text and images before they reach the provider
+                }
+                MessageContent::Annotation(_) => {}
             }
         }
 
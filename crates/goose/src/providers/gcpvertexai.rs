@@ -1,5 +1,4 @@
 use std::io;
-use std::time::Duration;
 
 use anyhow::Result;
 use async_stream::try_stream;
@@ -33,8 +32,6 @@ use rmcp::model::Tool;
 const GCP_VERTEX_AI_PROVIDER_NAME: &str = "gcp_vertex_ai";
 /// Base URL for GCP Vertex AI documentation
 const GCP_VERTEX_AI_DOC_URL: &str = "https://cloud.google.com/vertex-ai";
-/// Default timeout for API requests in seconds
-const DEFAULT_TIMEOUT_SECS: u64 = 600;
 /// Default initial interval for retry (in milliseconds)
 const DEFAULT_INITIAL_RETRY_INTERVAL_MS: u64 = 5000;
 /// Default maximum number of retries
@@ -161,9 +158,7 @@ impl GcpVertexAIProvider {
         let location = Self::determine_location(config)?;
         let host = Self::build_host_url(&location);
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT_SECS))
-            .build()?;
+        let client = super::http_client::shared_client()?;
 
         let auth = GcpAuth::new().await?;
 
@@ -608,7 +603,7 @@ impl Provider for GcpVertexAIProvider {
 
             while let Some(message) = message_stream.next().await {
                 let (message, usage) = message
-                    .map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                    .map_err(ProviderError::from_stream_error)?;
                 log.write(&message, usage.as_ref().map(|u| &u.usage))?;
                 yield (message, usage);
             }
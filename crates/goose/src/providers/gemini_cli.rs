@@ -3,17 +3,18 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::path::PathBuf;
 use std::process::Stdio;
-use std::sync::{Arc, OnceLock};
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt};
 use tokio::process::Command;
 
 use super::base::{
     stream_from_single_message, MessageStream, Provider, ProviderDef, ProviderMetadata,
     ProviderUsage, Usage,
 };
-use super::cli_common::{error_from_event, extract_usage_tokens};
+use super::cli_common::{
+    build_cli_prompt, classify_cli_exit, error_from_event, extract_usage_tokens, spawn_cli,
+    CliSessions,
+};
 use super::errors::ProviderError;
-use super::utils::filter_extensions_from_system_prompt;
 use crate::config::base::GeminiCliCommand;
 use crate::config::search_path::SearchPaths;
 use crate::config::Config;
@@ -36,6 +37,9 @@ pub const GEMINI_CLI_KNOWN_MODELS: &[&str] = &[
 
 pub const GEMINI_CLI_DOC_URL: &str = "https://ai.google.dev/gemini-api/docs";
 
+/// Where the CLI session is kept in the goose session's extension data
+const GEMINI_CLI_STATE_NAME: &str = "gemini_cli";
+
 #[derive(Debug, serde::Serialize)]
 pub struct GeminiCliProvider {
     command: PathBuf,
@@ -43,7 +47,7 @@ pub struct GeminiCliProvider {
     #[serde(skip)]
     name: String,
     #[serde(skip)]
-    cli_session_id: Arc<OnceLock<String>>,
+    cli_sessions: CliSessions,
 }
 
 impl GeminiCliProvider {
@@ -56,43 +60,11 @@ impl GeminiCliProvider {
             command: resolved_command,
             model,
             name: GEMINI_CLI_PROVIDER_NAME.to_string(),
-            cli_session_id: Arc::new(OnceLock::new()),
+            cli_sessions: CliSessions::new(GEMINI_CLI_STATE_NAME),
         })
     }
 
-    fn session_id(&self) -> Option<&str> {
-        self.cli_session_id.get().map(|s| s.as_str())
-    }
-
-    fn last_user_message_text(messages: &[Message]) -> String {
-        messages
-            .iter()
-            .rev()
-            .find(|m| m.role == Role::User)
-            .map(|m| m.as_concat_text())
-            .unwrap_or_default()
-    }
-
-    /// Build the prompt for the CLI invocation. When resuming a session the CLI
-    /// maintains conversation context internally, so only the latest user
-    /// message is needed. On the first turn (no session yet) the system prompt
-    /// is prepended — there is typically only one user message at that point.
-    fn build_prompt(&self, system: &str, messages: &[Message]) -> String {
-        let user_text = Self::last_user_message_text(messages);
-
-        if self.session_id().is_some() {
-            user_text
-        } else {
-            let filtered_system = filter_extensions_from_system_prompt(system);
-            if filtered_system.is_empty() {
-                user_text
-            } else {
-                format!("{filtered_system}\n\n{user_text}")
-            }
-        }
-    }
-
-    fn build_command(&self, prompt: &str, model_name: &str) -> Command {
+    fn build_command(&self, prompt: &str, model_name: &str, resume: Option<&str>) -> Command {
         let mut cmd = Command::new(&self.command);
         configure_subprocess(&mut cmd);
 
@@ -102,7 +74,7 @@ impl GeminiCliProvider {
 
         cmd.arg("-m").arg(model_name);
 
-        if let Some(sid) = self.session_id() {
+        if let Some(sid) = resume {
             cmd.arg("-r").arg(sid);
         }
 
@@ -119,38 +91,23 @@ impl GeminiCliProvider {
         cmd
     }
 
-    fn spawn_command(
+    /// The command for this turn: resuming the goose session's CLI session when there is
+    /// one, with a fresh session to fall back on in case resuming fails.
+    fn build_attempts(
         &self,
         system: &str,
         messages: &[Message],
         model_name: &str,
-    ) -> Result<
-        (
-            tokio::process::Child,
-            BufReader<tokio::process::ChildStdout>,
-        ),
-        ProviderError,
-    > {
-        let prompt = self.build_prompt(system, messages);
-
-        tracing::debug!(command = ?self.command, "Executing Gemini CLI command");
-
-        let mut cmd = self.build_command(&prompt, model_name);
-
-        let mut child = cmd.kill_on_drop(true).spawn().map_err(|e| {
-            ProviderError::RequestFailed(format!(
-                "Failed to spawn Gemini CLI command '{}': {e}. \
-                Make sure the Gemini CLI is installed and available in the configured search paths.",
-                self.command.display()
-            ))
-        })?;
-
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| ProviderError::RequestFailed("Failed to capture stdout".to_string()))?;
-
-        Ok((child, BufReader::new(stdout)))
+        resume: Option<&str>,
+    ) -> Vec<(Command, bool)> {
+        let mut attempts = Vec::with_capacity(2);
+        if let Some(sid) = resume {
+            let prompt = build_cli_prompt(system, messages, true);
+            attempts.push((self.build_command(&prompt, model_name, Some(sid)), true));
+        }
+        let prompt = build_cli_prompt(system, messages, false);
+        attempts.push((self.build_command(&prompt, model_name, None), false));
+        attempts
     }
 }
 
@@ -203,7 +160,7 @@ impl Provider for GeminiCliProvider {
     async fn stream(
         &self,
         model_config: &ModelConfig,
-        _session_id: &str, // CLI has no external session-id flag to propagate.
+        session_id: &str,
         system: &str,
         messages: &[Message],
         _tools: &[Tool],
@@ -216,106 +173,147 @@ impl Provider for GeminiCliProvider {
             return Ok(stream_from_single_message(message, provider_usage));
         }
 
-        let (mut child, mut reader) =
-            self.spawn_command(system, messages, &model_config.model_name)?;
-        let session_id_lock = Arc::clone(&self.cli_session_id);
+        let resume = self.cli_sessions.resumable(session_id).await;
+        let attempts = self.build_attempts(
+            system,
+            messages,
+            &model_config.model_name,
+            resume.as_deref(),
+        );
+        let command = self.command.clone();
+        let cli_sessions = self.cli_sessions.clone();
+        let session_id = session_id.to_string();
         let model_name = model_config.model_name.clone();
         let message_id = uuid::Uuid::new_v4().to_string();
 
-        let stderr = child.stderr.take();
-        let stderr_drain = tokio::spawn(async move {
-            let mut buf = String::new();
-            if let Some(mut stderr) = stderr {
-                let _ = AsyncReadExt::read_to_string(&mut stderr, &mut buf).await;
-            }
-            buf
-        });
-
         Ok(Box::pin(try_stream! {
-            let mut line = String::new();
-            let mut accumulated_usage = Usage::default();
             let stream_timestamp = chrono::Utc::now().timestamp();
 
-            loop {
-                line.clear();
-                match reader.read_line(&mut line).await {
-                    Ok(0) => break,
-                    Ok(_) => {
-                        let trimmed = line.trim();
-                        if trimmed.is_empty() {
-                            continue;
-                        }
+            for (mut cmd, resuming) in attempts {
+                let (mut child, mut reader) = spawn_cli(GEMINI_CLI_PROVIDER_NAME, &mut cmd, &command)?;
+                let stderr = child.stderr.take();
+                let stderr_drain = tokio::spawn(async move {
+                    let mut buf = String::new();
+                    if let Some(mut stderr) = stderr {
+                        let _ = AsyncReadExt::read_to_string(&mut stderr, &mut buf).await;
+                    }
+                    buf
+                });
+
+                let mut line = String::new();
+                let mut accumulated_usage = Usage::default();
+                let mut replied = false;
+                let mut failure = None;
+
+                loop {
+                    line.clear();
+                    match reader.read_line(&mut line).await {
+                        Ok(0) => break,
+                        Ok(_) => {
+                            let trimmed = line.trim();
+                            if trimmed.is_empty() {
+                                continue;
+                            }
 
-                        if let Ok(parsed) = serde_json::from_str::<Value>(trimmed) {
-                            match parsed.get("type").and_then(|t| t.as_str()) {
-                                Some("init") => {
-                                    if let Some(sid) =
-                                        parsed.get("session_id").and_then(|s| s.as_str())
-                                    {
-                                        let _ = session_id_lock.set(sid.to_string());
+                            if let Ok(parsed) = serde_json::from_str::<Value>(trimmed) {
+                                match parsed.get("type").and_then(|t| t.as_str()) {
+                                    Some("init") => {
+                                        if let Some(sid) =
+                                            parsed.get("session_id").and_then(|s| s.as_str())
+                                        {
+                                            cli_sessions.save(&session_id, sid).await;
+                                        }
                                     }
-                                }
-                                Some("message") => {
-                                    let is_assistant = parsed.get("role").and_then(|r| r.as_str())
-                                        == Some("assistant");
-                                    let content = parsed
-                                        .get("content")
-                                        .and_then(|c| c.as_str())
-                                        .unwrap_or("");
-                                    if is_assistant && !content.is_empty() {
-                                        let mut partial = Message::new(
-                                            Role::Assistant,
-                                            stream_timestamp,
-                                            vec![MessageContent::text(content)],
-                                        );
-                                        partial.id = Some(message_id.clone());
-                                        yield (Some(partial), None);
+                                    Some("message") => {
+                                        let is_assistant =
+                                            parsed.get("role").and_then(|r| r.as_str())
+                                                == Some("assistant");
+                                        let content = parsed
+                                            .get("content")
+                                            .and_then(|c| c.as_str())
+                                            .unwrap_or("");
+                                        if is_assistant && !content.is_empty() {
+                                            let mut partial = Message::new(
+                                                Role::Assistant,
+                                                stream_timestamp,
+                                                vec![MessageContent::text(content)],
+                                            );
+                                            partial.id = Some(message_id.clone());
+                                            replied = true;
+                                            yield (Some(partial), None);
+                                        }
                                     }
-                                }
-                                Some("result") => {
-                                    if let Some(stats) = parsed.get("stats") {
-                                        accumulated_usage = extract_usage_tokens(stats);
+                                    Some("result") => {
+                                        if let Some(stats) = parsed.get("stats") {
+                                            accumulated_usage = extract_usage_tokens(stats);
+                                        }
+                                        break;
                                     }
-                                    break;
-                                }
-                                Some("error") => {
-                                    let _ = child.wait().await;
-                                    Err(error_from_event("Gemini CLI", &parsed))?;
+                                    Some("error") => {
+                                        failure = Some(error_from_event(GEMINI_CLI_PROVIDER_NAME, &parsed));
+                                        break;
+                                    }
+                                    _ => {}
                                 }
-                                _ => {}
+                            } else {
+                                tracing::warn!(
+                                    line = trimmed,
+                                    "Non-JSON line in stream-json output"
+                                );
                             }
-                        } else {
-                            tracing::warn!(line = trimmed, "Non-JSON line in stream-json output");
+                        }
+                        Err(e) => {
+                            failure = Some(ProviderError::RequestFailed(format!(
+                                "Failed to read streaming output: {e}"
+                            )));
+                            break;
                         }
                     }
-                    Err(e) => {
-                        let _ = child.wait().await;
-                        Err(ProviderError::RequestFailed(format!(
-                            "Failed to read streaming output: {e}"
-                        )))?;
+                }
+
+                let stderr_text = stderr_drain.await.unwrap_or_default();
+                let exit_status = child.wait().await.map_err(|e| {
+                    ProviderError::RequestFailed(format!("Failed to wait for command: {e}"))
+                })?;
+
+                if failure.is_none() && !exit_status.success() {
+                    let stderr_snippet = stderr_text.trim();
+                    let detail = if stderr_snippet.is_empty() {
+                        format!("exit code {:?}", exit_status.code())
+                    } else {
+                        format!("exit code {:?}: {stderr_snippet}", exit_status.code())
+                    };
+                    failure = Some(
+                        classify_cli_exit(
+                            GEMINI_CLI_PROVIDER_NAME,
+                            exit_status.code(),
+                            stderr_snippet,
+                        )
+                        .unwrap_or(ProviderError::RequestFailed(format!(
+                            "Gemini CLI command failed ({detail})"
+                        ))),
+                    );
+                }
+
+                if let Some(error) = failure {
+                    // Signing in or waiting out a quota isn't fixed by a new session
+                    let session_problem = matches!(error, ProviderError::RequestFailed(_));
+                    if resuming && !replied && session_problem {
+                        tracing::warn!(
+                            session_id = %session_id,
+                            "Resuming the Gemini CLI session failed, starting a new one: {}",
+                            error
+                        );
+                        cli_sessions.forget(&session_id);
+                        continue;
                     }
+                    Err(error)?;
                 }
-            }
 
-            let stderr_text = stderr_drain.await.unwrap_or_default();
-            let exit_status = child.wait().await.map_err(|e| {
-                ProviderError::RequestFailed(format!("Failed to wait for command: {e}"))
-            })?;
-
-            if !exit_status.success() {
-                let stderr_snippet = stderr_text.trim();
-                let detail = if stderr_snippet.is_empty() {
-                    format!("exit code {:?}", exit_status.code())
-                } else {
-                    format!("exit code {:?}: {stderr_snippet}", exit_status.code())
-                };
-                Err(ProviderError::RequestFailed(format!(
-                    "Gemini CLI command failed ({detail})"
-                )))?;
+                let provider_usage = ProviderUsage::new(model_name.clone(), accumulated_usage);
+                yield (None, Some(provider_usage));
+                break;
             }
-
-            let provider_usage = ProviderUsage::new(model_name, accumulated_usage);
-            yield (None, Some(provider_usage));
         }))
     }
 }
@@ -329,34 +327,33 @@ mod tests {
             command: PathBuf::from("gemini"),
             model: ModelConfig::new("gemini-2.5-pro").unwrap(),
             name: "gemini-cli".to_string(),
-            cli_session_id: Arc::new(OnceLock::new()),
+            cli_sessions: CliSessions::new(GEMINI_CLI_STATE_NAME),
         }
     }
 
     #[test]
-    fn test_build_prompt_first_and_resume() {
+    fn test_resume_falls_back_to_a_fresh_session() {
         let provider = make_provider();
         let messages = vec![Message::new(
             Role::User,
             0,
             vec![MessageContent::text("Hello")],
         )];
-
-        let prompt = provider.build_prompt("You are helpful.", &messages);
-        assert!(prompt.contains("You are helpful."));
-        assert!(prompt.contains("Hello"));
-
-        let _ = provider.cli_session_id.set("session-123".to_string());
-        let messages = vec![
-            Message::new(Role::User, 0, vec![MessageContent::text("Hello")]),
-            Message::new(Role::Assistant, 0, vec![MessageContent::text("Hi!")]),
-            Message::new(
-                Role::User,
-                0,
-                vec![MessageContent::text("Follow up question")],
-            ),
-        ];
-        let prompt = provider.build_prompt("You are helpful.", &messages);
-        assert_eq!(prompt, "Follow up question");
+        let args = |cmd: &Command| -> Vec<String> {
+            cmd.as_std()
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let attempts = provider.build_attempts("", &messages, "gemini-2.5-pro", Some("s-1"));
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts[0].1);
+        assert!(args(&attempts[0].0).windows(2).any(|w| w == ["-r", "s-1"]));
+        assert!(!attempts[1].1);
+        assert!(!args(&attempts[1].0).contains(&"-r".to_string()));
+
+        let attempts = provider.build_attempts("", &messages, "gemini-2.5-pro", None);
+        assert_eq!(attempts.len(), 1);
     }
 }
@@ -1,23 +1,21 @@
-use crate::config::paths::Paths;
 use crate::providers::api_client::{ApiClient, AuthMethod};
 use crate::providers::openai_compatible::{handle_status_openai_compat, stream_openai_compat};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use axum::http;
 use chrono::{DateTime, Utc};
-use reqwest::{Client, Response};
+use reqwest::{Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::time::Duration;
+use std::sync::Arc;
 
 use super::base::{Provider, ProviderDef, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
 use super::formats::openai::{create_request, get_usage, response_to_message};
 use super::openai_compatible::handle_response_openai_compat;
 use super::retry::ProviderRetry;
+use super::token_manager::{OAuthToken, TokenManager, TokenSource};
 use super::utils::{get_model, ImageFormat, RequestLog};
 
 use crate::config::{Config, ConfigError};
@@ -64,6 +62,60 @@ struct DeviceCodeInfo {
     device_code: String,
     user_code: String,
     verification_uri: String,
+    /// Seconds to wait between polls
+    #[serde(default = "default_poll_interval")]
+    interval: u64,
+    /// Seconds until the code expires
+    #[serde(default = "default_code_lifetime")]
+    expires_in: u64,
+}
+
+fn default_poll_interval() -> u64 {
+    5
+}
+
+fn default_code_lifetime() -> u64 {
+    900
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessTokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    #[serde(flatten)]
+    _extra: HashMap<String, Value>,
+}
+
+/// What to do after polling for the device-code access token.
+#[derive(Debug, PartialEq)]
+enum PollStep {
+    Token(String),
+    Wait,
+    /// Wait, and poll less often from now on
+    SlowDown,
+    Fail(String),
+}
+
+impl AccessTokenResponse {
+    fn step(self) -> PollStep {
+        if let Some(token) = self.access_token {
+            return PollStep::Token(token);
+        }
+        match self.error.as_deref() {
+            Some("authorization_pending") => PollStep::Wait,
+            Some("slow_down") => PollStep::SlowDown,
+            Some("expired_token") => {
+                PollStep::Fail("the code expired before it was entered".to_string())
+            }
+            Some("access_denied") => PollStep::Fail("the authorization was denied".to_string()),
+            error => PollStep::Fail(
+                self.error_description
+                    .or(error.map(str::to_string))
+                    .unwrap_or_else(|| "no access token in the response".to_string()),
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -84,40 +136,25 @@ struct CopilotTokenInfo {
     _extra: HashMap<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct CopilotState {
-    expires_at: DateTime<Utc>,
-    info: CopilotTokenInfo,
-}
+/// Kept with the Copilot token, which is only good for this endpoint
+const API_ENDPOINT_KEY: &str = "api_endpoint";
 
-#[derive(Debug)]
-struct DiskCache {
-    cache_path: PathBuf,
-}
-
-impl DiskCache {
-    fn new() -> Self {
-        let cache_path = Paths::in_config_dir("githubcopilot/info.json");
-        Self { cache_path }
-    }
-
-    async fn load(&self) -> Option<CopilotState> {
-        if let Ok(contents) = tokio::fs::read_to_string(&self.cache_path).await {
-            if let Ok(info) = serde_json::from_str::<CopilotState>(&contents) {
-                return Some(info);
-            }
+impl From<CopilotTokenInfo> for OAuthToken {
+    fn from(info: CopilotTokenInfo) -> Self {
+        let expires_at = DateTime::<Utc>::from_timestamp(info.expires_at, 0)
+            .unwrap_or_else(|| Utc::now() + chrono::Duration::seconds(info.refresh_in));
+        OAuthToken {
+            expires_at: Some(expires_at),
+            ..OAuthToken::new(info.token, None, None)
         }
-        None
+        .with_extra(API_ENDPOINT_KEY, info.endpoints.api)
     }
+}
 
-    async fn save(&self, info: &CopilotState) -> Result<()> {
-        if let Some(parent) = self.cache_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
-        let contents = serde_json::to_string(info)?;
-        tokio::fs::write(&self.cache_path, contents).await?;
-        Ok(())
-    }
+/// Gets Copilot tokens by exchanging the GitHub sign-in, which the device flow gets when
+/// there is none
+struct CopilotTokens {
+    client: Client,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -125,9 +162,7 @@ pub struct GithubCopilotProvider {
     #[serde(skip)]
     client: Client,
     #[serde(skip)]
-    cache: DiskCache,
-    #[serde(skip)]
-    mu: tokio::sync::Mutex<RefCell<Option<CopilotState>>>,
+    tokens: Arc<TokenManager>,
     model: ModelConfig,
     #[serde(skip)]
     name: String,
@@ -158,15 +193,18 @@ impl GithubCopilotProvider {
     }
 
     pub async fn from_env(model: ModelConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(600))
-            .build()?;
-        let cache = DiskCache::new();
-        let mu = tokio::sync::Mutex::new(RefCell::new(None));
+        let client = super::http_client::shared_client()?;
+        let source = CopilotTokens {
+            client: client.clone(),
+        };
+        let tokens = TokenManager::shared(
+            GITHUB_COPILOT_PROVIDER_NAME,
+            GITHUB_COPILOT_PROVIDER_NAME,
+            || Arc::new(source) as Arc<dyn TokenSource>,
+        );
         Ok(Self {
             client,
-            cache,
-            mu,
+            tokens,
             model,
             name: GITHUB_COPILOT_PROVIDER_NAME.to_string(),
         })
@@ -177,53 +215,66 @@ impl GithubCopilotProvider {
         session_id: Option<&str>,
         payload: &mut Value,
     ) -> Result<Response, ProviderError> {
-        let (endpoint, token) = self.get_api_info().await?;
-        let auth = AuthMethod::BearerToken(token);
-        let mut headers = self.get_github_headers();
+        let mut headers = github_headers();
         if Self::payload_contains_image(payload) {
             headers.insert("Copilot-Vision-Request", "true".parse().unwrap());
         }
-        let api_client = ApiClient::new(endpoint.clone(), auth)?.with_headers(headers)?;
 
-        api_client
-            .response_post(session_id, "chat/completions", payload)
-            .await
-            .map_err(|e| e.into())
+        let mut refreshed = false;
+        loop {
+            let (endpoint, token) = self.get_api_info().await?;
+            let auth = AuthMethod::BearerToken(token);
+            let api_client =
+                ApiClient::new(endpoint.clone(), auth)?.with_headers(headers.clone())?;
+            let response = api_client
+                .response_post(session_id, "chat/completions", payload)
+                .await?;
+            // The Copilot token can be revoked before it expires; get a new one once
+            if response.status() == StatusCode::UNAUTHORIZED && !refreshed {
+                tracing::debug!("Copilot token was rejected, refreshing it");
+                self.invalidate_api_info().await;
+                refreshed = true;
+                continue;
+            }
+            return Ok(response);
+        }
+    }
+
+    /// Forget the Copilot token so the next request exchanges the GitHub token again.
+    async fn invalidate_api_info(&self) {
+        self.tokens.invalidate().await;
     }
 
     async fn get_api_info(&self) -> Result<(String, String)> {
-        let guard = self.mu.lock().await;
+        let token = self.tokens.token().await?;
+        let endpoint = token
+            .extra_str(API_ENDPOINT_KEY)
+            .ok_or_else(|| anyhow!("the Copilot token came without an API endpoint"))?
+            .to_string();
+        Ok((endpoint, token.access_token))
+    }
+}
 
-        if let Some(state) = guard.borrow().as_ref() {
-            if state.expires_at > Utc::now() {
-                return Ok((state.info.endpoints.api.clone(), state.info.token.clone()));
-            }
-        }
+#[async_trait]
+impl TokenSource for CopilotTokens {
+    async fn refresh(&self, _token: &OAuthToken) -> Result<OAuthToken> {
+        self.exchange().await
+    }
 
-        if let Some(state) = self.cache.load().await {
-            if guard.borrow().is_none() {
-                guard.replace(Some(state.clone()));
-            }
-            if state.expires_at > Utc::now() {
-                return Ok((state.info.endpoints.api, state.info.token));
-            }
-        }
+    async fn sign_in(&self) -> Result<OAuthToken> {
+        self.exchange().await
+    }
+}
 
+impl CopilotTokens {
+    async fn exchange(&self) -> Result<OAuthToken> {
         const MAX_ATTEMPTS: i32 = 3;
         for attempt in 0..MAX_ATTEMPTS {
             tracing::trace!("attempt {} to refresh api info", attempt + 1);
-            let info = match self.refresh_api_info().await {
-                Ok(data) => data,
-                Err(err) => {
-                    tracing::warn!("failed to refresh api info: {}", err);
-                    continue;
-                }
-            };
-            let expires_at = Utc::now() + chrono::Duration::seconds(info.refresh_in);
-            let new_state = CopilotState { info, expires_at };
-            self.cache.save(&new_state).await?;
-            guard.replace(Some(new_state.clone()));
-            return Ok((new_state.info.endpoints.api, new_state.info.token));
+            match self.refresh_api_info().await {
+                Ok(info) => return Ok(info.into()),
+                Err(err) => tracing::warn!("failed to refresh api info: {}", err),
+            }
         }
         Err(anyhow!("failed to get api info after 3 attempts"))
     }
@@ -247,13 +298,19 @@ impl GithubCopilotProvider {
         let resp = self
             .client
             .get(GITHUB_COPILOT_API_KEY_URL)
-            .headers(self.get_github_headers())
+            .headers(github_headers())
             .header(http::header::AUTHORIZATION, format!("bearer {}", &token))
             .send()
-            .await?
-            .error_for_status()?
-            .text()
             .await?;
+        if resp.status() == StatusCode::UNAUTHORIZED {
+            // The GitHub token was revoked; a new one needs the device flow again
+            let _ = config.delete_secret("GITHUB_COPILOT_TOKEN");
+            return Err(anyhow!(
+                "GitHub rejected the saved Copilot sign-in. \
+                 Run `goose configure` and select GitHub Copilot to sign in again."
+            ));
+        }
+        let resp = resp.error_for_status()?.text().await?;
         tracing::trace!("copilot token response: {}", resp);
         let info: CopilotTokenInfo = serde_json::from_str(&resp)?;
         Ok(info)
@@ -278,8 +335,7 @@ impl GithubCopilotProvider {
             device_code_info.verification_uri, device_code_info.user_code
         );
 
-        self.poll_for_access_token(&device_code_info.device_code)
-            .await
+        self.poll_for_access_token(&device_code_info).await
     }
 
     async fn get_device_code(&self) -> Result<DeviceCodeInfo> {
@@ -290,7 +346,7 @@ impl GithubCopilotProvider {
         }
         self.client
             .post(GITHUB_COPILOT_DEVICE_CODE_URL)
-            .headers(self.get_github_headers())
+            .headers(github_headers())
             .json(&DeviceCodeRequest {
                 client_id: GITHUB_COPILOT_CLIENT_ID.to_string(),
                 scope: "read:user".to_string(),
@@ -305,30 +361,26 @@ impl GithubCopilotProvider {
             .context("failed to parse device code response")
     }
 
-    async fn poll_for_access_token(&self, device_code: &str) -> Result<String> {
+    async fn poll_for_access_token(&self, device_code_info: &DeviceCodeInfo) -> Result<String> {
         #[derive(Serialize)]
         struct AccessTokenRequest {
             client_id: String,
             device_code: String,
             grant_type: String,
         }
-        #[derive(Debug, Deserialize)]
-        struct AccessTokenResponse {
-            access_token: Option<String>,
-            error: Option<String>,
-            #[serde(flatten)]
-            _extra: HashMap<String, Value>,
-        }
 
-        const MAX_ATTEMPTS: i32 = 36;
-        for attempt in 0..MAX_ATTEMPTS {
+        let deadline = tokio::time::Instant::now()
+            + tokio::time::Duration::from_secs(device_code_info.expires_in);
+        let mut interval = tokio::time::Duration::from_secs(device_code_info.interval);
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(interval).await;
             let resp = self
                 .client
                 .post(GITHUB_COPILOT_ACCESS_TOKEN_URL)
-                .headers(self.get_github_headers())
+                .headers(github_headers())
                 .json(&AccessTokenRequest {
                     client_id: GITHUB_COPILOT_CLIENT_ID.to_string(),
-                    device_code: device_code.to_string(),
+                    device_code: device_code_info.device_code.clone(),
                     grant_type: "urn:ietf:params:oauth:grant-type:device_code".to_string(),
                 })
                 .send()
@@ -339,44 +391,37 @@ impl GithubCopilotProvider {
                 .json::<AccessTokenResponse>()
                 .await
                 .context("failed to parse response while polling for access token")?;
-            if resp.access_token.is_some() {
-                tracing::trace!("successful authorization: {:#?}", resp,);
-            }
-            if let Some(access_token) = resp.access_token {
-                return Ok(access_token);
-            } else if resp
-                .error
-                .as_ref()
-                .is_some_and(|err| err == "authorization_pending")
-            {
-                tracing::debug!(
-                    "authorization pending (attempt {}/{})",
-                    attempt + 1,
-                    MAX_ATTEMPTS
-                );
-            } else {
-                tracing::debug!("unexpected response: {:#?}", resp);
+            match resp.step() {
+                PollStep::Token(access_token) => {
+                    tracing::trace!("successful authorization");
+                    return Ok(access_token);
+                }
+                PollStep::Wait => tracing::debug!("authorization pending"),
+                PollStep::SlowDown => {
+                    interval += tokio::time::Duration::from_secs(5);
+                    tracing::debug!(?interval, "polling for the access token less often");
+                }
+                PollStep::Fail(reason) => return Err(anyhow!("GitHub sign-in failed: {reason}")),
             }
-            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         }
-        Err(anyhow!("failed to get access token"))
+        Err(anyhow!("the code expired before it was entered"))
     }
+}
 
-    fn get_github_headers(&self) -> http::HeaderMap {
-        let mut headers = http::HeaderMap::new();
-        headers.insert(http::header::ACCEPT, "application/json".parse().unwrap());
-        headers.insert(
-            http::header::CONTENT_TYPE,
-            "application/json".parse().unwrap(),
-        );
-        headers.insert(
-            http::header::USER_AGENT,
-            "GithubCopilot/1.155.0".parse().unwrap(),
-        );
-        headers.insert("editor-version", "vscode/1.85.1".parse().unwrap());
-        headers.insert("editor-plugin-version", "copilot/1.155.0".parse().unwrap());
-        headers
-    }
+fn github_headers() -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(http::header::ACCEPT, "application/json".parse().unwrap());
+    headers.insert(
+        http::header::CONTENT_TYPE,
+        "application/json".parse().unwrap(),
+    );
+    headers.insert(
+        http::header::USER_AGENT,
+        "GithubCopilot/1.155.0".parse().unwrap(),
+    );
+    headers.insert("editor-version", "vscode/1.85.1".parse().unwrap());
+    headers.insert("editor-plugin-version", "copilot/1.155.0".parse().unwrap());
+    headers
 }
 
 impl ProviderDef for GithubCopilotProvider {
@@ -516,37 +561,20 @@ impl Provider for GithubCopilotProvider {
         );
 
         let response = self.client.get(url).headers(headers).send().await?;
-
-        let json: serde_json::Value = response.json().await?;
-
-        let arr = json.get("data").and_then(|v| v.as_array()).ok_or_else(|| {
-            ProviderError::RequestFailed(
-                "Missing 'data' array in GitHub Copilot models response".to_string(),
-            )
-        })?;
-        let mut models: Vec<String> = arr
-            .iter()
-            .filter_map(|m| {
-                if let Some(s) = m.as_str() {
-                    Some(s.to_string())
-                } else if let Some(obj) = m.as_object() {
-                    obj.get("id").and_then(|v| v.as_str()).map(str::to_string)
-                } else {
-                    None
-                }
-            })
-            .collect();
-        models.sort();
-        Ok(models)
+        let json = handle_response_openai_compat(response).await?;
+        chat_model_ids(&json)
     }
 
     async fn configure_oauth(&self) -> Result<(), ProviderError> {
         let config = Config::global();
+        let auth = CopilotTokens {
+            client: self.client.clone(),
+        };
 
         // Check if token already exists and is valid
         if config.get_secret::<String>("GITHUB_COPILOT_TOKEN").is_ok() {
             // Try to refresh API info to validate the token
-            match self.refresh_api_info().await {
+            match auth.refresh_api_info().await {
                 Ok(_) => return Ok(()), // Token is valid
                 Err(_) => {
                     // Token is invalid, continue with OAuth flow
@@ -556,7 +584,7 @@ impl Provider for GithubCopilotProvider {
         }
 
         // Start OAuth device code flow
-        let token = self
+        let token = auth
             .get_access_token()
             .await
             .map_err(|e| ProviderError::Authentication(format!("OAuth flow failed: {}", e)))?;
@@ -570,6 +598,33 @@ impl Provider for GithubCopilotProvider {
     }
 }
 
+/// The models in a Copilot `/models` response that can be chatted with: not embedding
+/// models, and not ones the organization's policy turns off.
+fn chat_model_ids(json: &Value) -> Result<Vec<String>, ProviderError> {
+    let arr = json.get("data").and_then(|v| v.as_array()).ok_or_else(|| {
+        ProviderError::RequestFailed(
+            "Missing 'data' array in GitHub Copilot models response".to_string(),
+        )
+    })?;
+    let mut models: Vec<String> = arr
+        .iter()
+        .filter_map(|m| {
+            if let Some(s) = m.as_str() {
+                return Some(s.to_string());
+            }
+            let kind = m.pointer("/capabilities/type").and_then(|v| v.as_str());
+            let policy = m.pointer("/policy/state").and_then(|v| v.as_str());
+            if kind.is_some_and(|kind| kind != "chat") || policy == Some("disabled") {
+                return None;
+            }
+            m.get("id").and_then(|v| v.as_str()).map(str::to_string)
+        })
+        .collect();
+    models.sort();
+    models.dedup();
+    Ok(models)
+}
+
 // Copilot sometimes returns multiple choices in a completion response for
 // Claude models and places the `tool_calls` payload in a non-zero index choice.
 // Example:
@@ -611,9 +666,49 @@ fn promote_tool_choice(response: Value) -> Value {
 
 #[cfg(test)]
 mod tests {
-    use super::promote_tool_choice;
+    use super::{chat_model_ids, promote_tool_choice, AccessTokenResponse, PollStep};
     use serde_json::json;
 
+    #[test]
+    fn lists_only_enabled_chat_models() {
+        let response = json!({"data": [
+            {"id": "gpt-4.1", "capabilities": {"type": "chat"}, "policy": {"state": "enabled"}},
+            {"id": "gpt-4.1", "capabilities": {"type": "chat"}},
+            {"id": "text-embedding-3-small", "capabilities": {"type": "embeddings"}},
+            {"id": "claude-opus-4", "capabilities": {"type": "chat"}, "policy": {"state": "disabled"}},
+            {"id": "claude-sonnet-4"}
+        ]});
+        assert_eq!(
+            chat_model_ids(&response).unwrap(),
+            vec!["claude-sonnet-4", "gpt-4.1"]
+        );
+    }
+
+    #[test]
+    fn device_flow_poll_steps() {
+        let step = |body: serde_json::Value| {
+            serde_json::from_value::<AccessTokenResponse>(body)
+                .unwrap()
+                .step()
+        };
+        assert_eq!(
+            step(json!({"access_token": "gho_x", "token_type": "bearer"})),
+            PollStep::Token("gho_x".to_string())
+        );
+        assert_eq!(
+            step(json!({"error": "authorization_pending"})),
+            PollStep::Wait
+        );
+        assert_eq!(
+            step(json!({"error": "slow_down", "interval": 10})),
+            PollStep::SlowDown
+        );
+        assert!(matches!(
+            step(json!({"error": "access_denied"})),
+            PollStep::Fail(_)
+        ));
+    }
+
     #[test]
     fn promotes_choice_with_tool_call() {
         let response = json!({
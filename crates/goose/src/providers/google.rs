@@ -9,6 +9,7 @@ use crate::conversation::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::base::{ConfigKey, Provider, ProviderDef, ProviderMetadata};
 use crate::providers::formats::google::{create_request, response_to_streaming_message};
+use crate::providers::server_tools;
 use anyhow::Result;
 use async_stream::try_stream;
 use async_trait::async_trait;
@@ -173,7 +174,8 @@ impl Provider for GoogleProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let payload = create_request(model_config, system, messages, tools)?;
+        let server_tools = server_tools::for_request(model_config, tools);
+        let payload = create_request(model_config, system, messages, tools, &server_tools)?;
         let mut log = RequestLog::start(model_config, &payload)?;
 
         let response = self
@@ -196,9 +198,7 @@ impl Provider for GoogleProvider {
             let message_stream = response_to_streaming_message(framed);
             pin!(message_stream);
             while let Some(message) = message_stream.next().await {
-                let (message, usage) = message.map_err(|e|
-                    ProviderError::RequestFailed(format!("Stream decode error: {}", e))
-                )?;
+                let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
                 if message.is_some() || usage.is_some() {
                     log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                 }
@@ -0,0 +1,161 @@
+//! The HTTP client providers share.
+//!
+//! Every provider sends its requests through one connection pool per TLS setup instead of
+//! building a client of its own, so connections (and HTTP/2 sessions, where the server
+//! offers them) are reused across providers, lead/worker pairs and subagents. The pool is
+//! tuned with these settings:
+//!
+//! - `GOOSE_HTTP_POOL_MAX_IDLE_PER_HOST`: idle connections kept per host (default 32)
+//! - `GOOSE_HTTP_POOL_IDLE_TIMEOUT`: seconds an idle connection is kept (default 90)
+//! - `GOOSE_HTTP_CONNECT_TIMEOUT`: seconds to wait for a connection (default 30)
+//! - `GOOSE_HTTP_KEEPALIVE`: seconds between TCP and HTTP/2 keepalive pings (default 30)
+//! - `GOOSE_HTTP_TIMEOUT`: seconds a request may take unless the provider sets its own
+//!   (default 600)
+
+use super::api_client::TlsConfig;
+use crate::config::Config;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use reqwest::{Client, ClientBuilder, Response};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+static CLIENTS: Lazy<Mutex<HashMap<String, Client>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpClientSettings {
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    pub connect_timeout: Duration,
+    pub keepalive: Duration,
+    pub timeout: Duration,
+}
+
+impl Default for HttpClientSettings {
+    fn default() -> Self {
+        Self {
+            pool_max_idle_per_host: 32,
+            pool_idle_timeout: Duration::from_secs(90),
+            connect_timeout: Duration::from_secs(30),
+            keepalive: Duration::from_secs(30),
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl HttpClientSettings {
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        let defaults = Self::default();
+        let seconds = |key: &str, default: Duration| {
+            config
+                .get_param::<u64>(key)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        Self {
+            pool_max_idle_per_host: config
+                .get_param("GOOSE_HTTP_POOL_MAX_IDLE_PER_HOST")
+                .unwrap_or(defaults.pool_max_idle_per_host),
+            pool_idle_timeout: seconds("GOOSE_HTTP_POOL_IDLE_TIMEOUT", defaults.pool_idle_timeout),
+            connect_timeout: seconds("GOOSE_HTTP_CONNECT_TIMEOUT", defaults.connect_timeout),
+            keepalive: seconds("GOOSE_HTTP_KEEPALIVE", defaults.keepalive),
+            timeout: seconds("GOOSE_HTTP_TIMEOUT", defaults.timeout),
+        }
+    }
+
+    pub fn builder(&self) -> ClientBuilder {
+        Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.keepalive)
+            .http2_keep_alive_interval(self.keepalive)
+            .http2_keep_alive_while_idle(true)
+            .http2_adaptive_window(true)
+    }
+}
+
+fn pool_key(tls_config: Option<&TlsConfig>) -> String {
+    let Some(tls_config) = tls_config.filter(|tls| tls.is_configured()) else {
+        return String::new();
+    };
+    let identity = tls_config
+        .client_identity
+        .as_ref()
+        .map(|pair| format!("{}:{}", pair.cert_path.display(), pair.key_path.display()));
+    format!(
+        "{}|{}",
+        identity.unwrap_or_default(),
+        tls_config
+            .ca_cert_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default()
+    )
+}
+
+/// The pooled client for `tls_config`, built the first time it is asked for
+pub fn shared_client_with_tls(tls_config: Option<&TlsConfig>) -> Result<Client> {
+    let key = pool_key(tls_config);
+    let mut clients = CLIENTS.lock().unwrap();
+    if let Some(client) = clients.get(&key) {
+        return Ok(client.clone());
+    }
+
+    let mut builder = HttpClientSettings::from_config().builder();
+    if let Some(tls_config) = tls_config {
+        if let Some(identity) = tls_config.load_identity()? {
+            builder = builder.identity(identity);
+        }
+        for ca_cert in tls_config.load_ca_certificates()? {
+            builder = builder.add_root_certificate(ca_cert);
+        }
+    }
+    let client = builder.build()?;
+    tracing::info!(
+        monotonic_counter.goose.http_clients = 1,
+        custom_tls = !key.is_empty(),
+        "Built a pooled HTTP client"
+    );
+    clients.insert(key, client.clone());
+    Ok(client)
+}
+
+/// The pooled client for the TLS settings in the config
+pub fn shared_client() -> Result<Client> {
+    shared_client_with_tls(TlsConfig::from_config()?.as_ref())
+}
+
+/// Record a response for the request metrics. Requests over a reused connection skip the
+/// connect and TLS handshake, so a rise in time to headers with a flat request count
+/// points at connections no longer being reused.
+pub fn record_response(started: Instant, response: &Response) {
+    tracing::info!(
+        monotonic_counter.goose.http_requests = 1,
+        histogram.goose.http_time_to_headers_ms = started.elapsed().as_millis() as u64,
+        http_version = ?response.version(),
+        status = response.status().as_u16(),
+        "Provider HTTP response"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn clients_are_shared_per_tls_setup() {
+        let tls = TlsConfig::new().with_ca_cert(PathBuf::from("/etc/ssl/corp.pem"));
+        assert_eq!(pool_key(None), "");
+        assert_eq!(pool_key(Some(&TlsConfig::new())), "");
+        assert_ne!(pool_key(Some(&tls)), pool_key(None));
+
+        shared_client_with_tls(None).unwrap();
+        shared_client_with_tls(None).unwrap();
+        assert!(CLIENTS.lock().unwrap().contains_key(""));
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::base::{MessageStream, Provider, ProviderDef, ProviderMetadata};
+use super::cli_common::StreamJsonCli;
+use super::errors::ProviderError;
+use crate::config::base::IflowCommand;
+use crate::config::search_path::SearchPaths;
+use crate::config::Config;
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use crate::providers::base::ConfigKey;
+use futures::future::BoxFuture;
+use rmcp::model::Tool;
+
+const IFLOW_PROVIDER_NAME: &str = "iflow";
+pub const IFLOW_DEFAULT_MODEL: &str = "glm-4.6";
+pub const IFLOW_KNOWN_MODELS: &[&str] = &[
+    "glm-4.6",
+    "qwen3-coder-plus",
+    "kimi-k2-0905",
+    "deepseek-v3.2",
+];
+
+pub const IFLOW_DOC_URL: &str = "https://github.com/iflow-ai/iflow-cli";
+
+/// Where the CLI session is kept in the goose session's extension data
+const IFLOW_STATE_NAME: &str = "iflow";
+
+#[derive(Debug, serde::Serialize)]
+pub struct IflowProvider {
+    #[serde(flatten)]
+    cli: StreamJsonCli,
+    model: ModelConfig,
+    #[serde(skip)]
+    name: String,
+}
+
+impl IflowProvider {
+    pub async fn from_env(model: ModelConfig) -> Result<Self> {
+        let config = Config::global();
+        let command: String = config.get_iflow_command().unwrap_or_default().into();
+        let resolved_command = SearchPaths::builder().with_npm().resolve(&command)?;
+
+        Ok(Self {
+            cli: StreamJsonCli::new(
+                IFLOW_PROVIDER_NAME,
+                resolved_command,
+                "--resume",
+                IFLOW_STATE_NAME,
+            ),
+            model,
+            name: IFLOW_PROVIDER_NAME.to_string(),
+        })
+    }
+}
+
+impl ProviderDef for IflowProvider {
+    type Provider = Self;
+
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            IFLOW_PROVIDER_NAME,
+            "iFlow CLI",
+            "Execute models via the iflow CLI tool",
+            IFLOW_DEFAULT_MODEL,
+            IFLOW_KNOWN_MODELS.to_vec(),
+            IFLOW_DOC_URL,
+            vec![ConfigKey::from_value_type::<IflowCommand>(
+                true, false, true,
+            )],
+        )
+    }
+
+    fn from_env(
+        model: ModelConfig,
+        _extensions: Vec<crate::config::ExtensionConfig>,
+    ) -> BoxFuture<'static, Result<Self::Provider>> {
+        Box::pin(Self::from_env(model))
+    }
+}
+
+#[async_trait]
+impl Provider for IflowProvider {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Vec<String>, ProviderError> {
+        Ok(IFLOW_KNOWN_MODELS.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[tracing::instrument(
+        skip(self, model_config, system, messages, _tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        model_config: &ModelConfig,
+        session_id: &str,
+        system: &str,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        self.cli
+            .stream(model_config, session_id, system, messages)
+            .await
+    }
+}
@@ -15,6 +15,7 @@ use super::{
     gemini_cli::GeminiCliProvider,
     githubcopilot::GithubCopilotProvider,
     google::GoogleProvider,
+    iflow::IflowProvider,
     lead_worker::LeadWorkerProvider,
     litellm::LiteLLMProvider,
     local_inference::LocalInferenceProvider,
@@ -22,6 +23,7 @@ use super::{
     openai::OpenAiProvider,
     openrouter::OpenRouterProvider,
     provider_registry::ProviderRegistry,
+    qwen_code::QwenCodeProvider,
     sagemaker_tgi::SageMakerTgiProvider,
     snowflake::SnowflakeProvider,
     tetrate::TetrateProvider,
@@ -60,10 +62,12 @@ async fn init_registry() -> RwLock<ProviderRegistry> {
         registry.register::<GeminiCliProvider>(false);
         registry.register::<GithubCopilotProvider>(false);
         registry.register::<GoogleProvider>(true);
+        registry.register::<IflowProvider>(false);
         registry.register::<LiteLLMProvider>(false);
         registry.register::<OllamaProvider>(true);
         registry.register::<OpenAiProvider>(true);
         registry.register::<OpenRouterProvider>(true);
+        registry.register::<QwenCodeProvider>(false);
         registry.register::<SageMakerTgiProvider>(false);
         registry.register::<SnowflakeProvider>(false);
         registry.register::<TetrateProvider>(true);
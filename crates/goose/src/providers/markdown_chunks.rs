@@ -0,0 +1,303 @@
+//! Re-chunking streamed text so every chunk ends at a point where the markdown so far still
+//! renders sensibly.
+//!
+//! Providers stream text in whatever pieces the model emits, so a frontend can be handed
+//! half a code fence marker, half a table row or an open inline code span, and renders it
+//! wrong until the next chunk arrives. With `GOOSE_MARKDOWN_CHUNKING` on, text is held back
+//! until it reaches a safe boundary:
+//!
+//! - the end of a line, anywhere
+//! - inside a paragraph, the last space outside inline code and link text
+//! - never inside a code block line, a table row or a line that may become a fence
+//!
+//! The last chunk of a message closes a code block left open and line endings are
+//! normalized, so the message the chunks add up to is well-formed markdown. Tool calls and
+//! other content pass through as they come, after any text held before them.
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+use crate::providers::base::MessageStream;
+use async_stream::try_stream;
+use futures::StreamExt;
+
+pub const MARKDOWN_CHUNKING_CONFIG_KEY: &str = "GOOSE_MARKDOWN_CHUNKING";
+
+/// Longest text held back waiting for a boundary before it is sent anyway
+const MAX_HELD_BYTES: usize = 2048;
+
+pub fn enabled() -> bool {
+    Config::global()
+        .get_param::<bool>(MARKDOWN_CHUNKING_CONFIG_KEY)
+        .unwrap_or(false)
+}
+
+/// The fence character a line opens or closes a code block with
+fn fence_marker(line: &str) -> Option<char> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    ['`', '~']
+        .into_iter()
+        .find(|c| trimmed.starts_with(&c.to_string().repeat(3)))
+}
+
+/// Whether an unfinished line can't be cut before it ends
+fn holds_line(partial: &str) -> bool {
+    let trimmed = partial.trim_start();
+    trimmed.is_empty() || trimmed.starts_with(['`', '~', '|'])
+}
+
+/// The last place in an unfinished paragraph line that can be cut after
+fn last_space(partial: &str) -> Option<usize> {
+    let mut backticks = 0;
+    let mut brackets = 0i32;
+    let mut cut = None;
+    for (idx, c) in partial.char_indices() {
+        match c {
+            '`' => backticks += 1,
+            '[' => brackets += 1,
+            ']' => brackets -= 1,
+            ' ' | '\t' if backticks % 2 == 0 && brackets <= 0 => cut = Some(idx + 1),
+            _ => {}
+        }
+    }
+    cut
+}
+
+#[derive(Debug, Default)]
+struct Scan {
+    /// Bytes that can be sent
+    cut: usize,
+    /// The open code block's fence character, at the cut
+    fence: Option<char>,
+    /// Whether the text after the cut starts a line
+    line_start: bool,
+}
+
+/// Buffers the text of one streamed message and releases it at safe boundaries
+#[derive(Debug)]
+pub struct MarkdownChunker {
+    buffer: String,
+    fence: Option<char>,
+    line_start: bool,
+    /// The message being chunked, without its content
+    message: Option<Message>,
+}
+
+impl Default for MarkdownChunker {
+    fn default() -> Self {
+        Self {
+            buffer: String::new(),
+            fence: None,
+            line_start: true,
+            message: None,
+        }
+    }
+}
+
+impl MarkdownChunker {
+    fn scan(&self) -> Scan {
+        let mut scan = Scan {
+            cut: 0,
+            fence: self.fence,
+            line_start: self.line_start,
+        };
+        let mut pos = 0;
+        while let Some(newline) = self.buffer[pos..].find('\n') {
+            let line = &self.buffer[pos..pos + newline];
+            if scan.line_start {
+                match (scan.fence, fence_marker(line)) {
+                    (None, Some(marker)) => scan.fence = Some(marker),
+                    (Some(open), Some(marker))
+                        if open == marker && line.trim().trim_start_matches(marker).is_empty() =>
+                    {
+                        scan.fence = None
+                    }
+                    _ => {}
+                }
+            }
+            pos += newline + 1;
+            scan.cut = pos;
+            scan.line_start = true;
+        }
+
+        let partial = &self.buffer[pos..];
+        if partial.len() > MAX_HELD_BYTES {
+            scan.cut = self.buffer.len();
+            scan.line_start = false;
+        } else if scan.fence.is_none() && !(scan.line_start && holds_line(partial)) {
+            if let Some(space) = last_space(partial) {
+                scan.cut = pos + space;
+                scan.line_start = false;
+            }
+        }
+        scan
+    }
+
+    /// Whether `message` continues the message being chunked
+    fn continues(&self, message: &Message) -> bool {
+        self.message
+            .as_ref()
+            .is_none_or(|held| held.id == message.id && held.role == message.role)
+    }
+
+    fn chunk(&self, text: String) -> Option<Message> {
+        let mut message = self.message.clone()?;
+        message.content = vec![MessageContent::text(text.replace("\r\n", "\n"))];
+        Some(message)
+    }
+
+    /// Add a text-only message. Returns what of the text so far can be sent.
+    pub fn push(&mut self, message: Message) -> Option<Message> {
+        for content in &message.content {
+            if let MessageContent::Text(text) = content {
+                self.buffer.push_str(&text.text);
+            }
+        }
+        if self.message.is_none() {
+            self.message = Some(Message {
+                content: Vec::new(),
+                ..message
+            });
+        }
+
+        let scan = self.scan();
+        if scan.cut == 0 {
+            return None;
+        }
+        let released: String = self.buffer.drain(..scan.cut).collect();
+        self.fence = scan.fence;
+        self.line_start = scan.line_start;
+        self.chunk(released)
+    }
+
+    /// The rest of the text, closing a code block left open
+    pub fn finish(&mut self) -> Option<Message> {
+        self.buffer.push('\n');
+        let scan = self.scan();
+        let mut rest = std::mem::take(&mut self.buffer);
+        rest.pop();
+        if let Some(marker) = scan.fence {
+            if !rest.is_empty() && !rest.ends_with('\n') {
+                rest.push('\n');
+            }
+            rest.push_str(&marker.to_string().repeat(3));
+        }
+        let chunk = if rest.is_empty() {
+            None
+        } else {
+            self.chunk(rest)
+        };
+        *self = Self::default();
+        chunk
+    }
+}
+
+fn is_text_only(message: &Message) -> bool {
+    !message.content.is_empty()
+        && message
+            .content
+            .iter()
+            .all(|content| matches!(content, MessageContent::Text(_)))
+}
+
+/// Re-chunk the text of a provider stream at markdown boundaries
+pub fn chunk_markdown(mut stream: MessageStream) -> MessageStream {
+    Box::pin(try_stream! {
+        let mut chunker = MarkdownChunker::default();
+        while let Some(item) = stream.next().await {
+            let (message, usage) = item?;
+            match message {
+                Some(message) if is_text_only(&message) => {
+                    if !chunker.continues(&message) {
+                        if let Some(rest) = chunker.finish() {
+                            yield (Some(rest), None);
+                        }
+                    }
+                    let chunk = chunker.push(message);
+                    if chunk.is_some() || usage.is_some() {
+                        yield (chunk, usage);
+                    }
+                }
+                other => {
+                    if let Some(rest) = chunker.finish() {
+                        yield (Some(rest), None);
+                    }
+                    if other.is_some() || usage.is_some() {
+                        yield (other, usage);
+                    }
+                }
+            }
+        }
+        if let Some(rest) = chunker.finish() {
+            yield (Some(rest), None);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::errors::ProviderError;
+
+    fn chunks(pieces: &[&str]) -> Vec<String> {
+        let mut chunker = MarkdownChunker::default();
+        let mut out: Vec<String> = pieces
+            .iter()
+            .filter_map(|piece| chunker.push(Message::assistant().with_text(*piece)))
+            .map(|m| m.as_concat_text())
+            .collect();
+        out.extend(chunker.finish().map(|m| m.as_concat_text()));
+        out
+    }
+
+    #[test]
+    fn fences_and_table_rows_are_not_split() {
+        assert_eq!(
+            chunks(&["Here:\n`", "``rust\nfn main", "() {}\n``", "`\ndone"]),
+            ["Here:\n", "```rust\n", "fn main() {}\n", "```\n", "done"]
+        );
+        assert_eq!(
+            chunks(&["| a | b", " |\n| 1 ", "| 2 |\n"]),
+            ["| a | b |\n", "| 1 | 2 |\n"]
+        );
+    }
+
+    #[test]
+    fn paragraphs_are_cut_at_spaces_outside_code_and_links() {
+        assert_eq!(
+            chunks(&["run `cargo ", "test` and see [the ", "docs](x) now"]),
+            ["run ", "`cargo test` and see ", "[the docs](x) ", "now"]
+        );
+    }
+
+    #[test]
+    fn the_last_chunk_closes_an_open_code_block() {
+        let out = chunks(&["```\r\nlet x = 1;\r\n", "let y"]);
+        assert_eq!(out.concat(), "```\nlet x = 1;\nlet y\n```");
+    }
+
+    #[tokio::test]
+    async fn text_is_flushed_before_other_content() {
+        let items: Vec<Result<_, ProviderError>> = vec![
+            Ok((Some(Message::assistant().with_text("Let me check")), None)),
+            Ok((
+                Some(
+                    Message::assistant()
+                        .with_tool_request("1", Ok(rmcp::model::CallToolRequestParams::new("ls"))),
+                ),
+                None,
+            )),
+        ];
+        let stream = chunk_markdown(Box::pin(futures::stream::iter(items)));
+        let messages: Vec<Message> = stream
+            .filter_map(|item| async move { item.unwrap().0 })
+            .collect()
+            .await;
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].as_concat_text(), "Let me ");
+        assert_eq!(messages[1].as_concat_text(), "check");
+        assert!(messages[2].is_tool_call());
+    }
+}
@@ -10,34 +10,42 @@ pub mod canonical;
 pub mod catalog;
 pub mod chatgpt_codex;
 pub mod claude_code;
-pub(crate) mod cli_common;
+pub mod cli_common;
 pub mod codex;
 pub mod cursor_agent;
 pub mod databricks;
 pub mod embedding;
 pub mod errors;
+pub mod fake;
 pub mod formats;
 mod gcpauth;
 pub mod gcpvertexai;
 pub mod gemini_cli;
 pub mod githubcopilot;
 pub mod google;
+pub mod http_client;
+pub mod iflow;
 mod init;
 pub mod lead_worker;
 pub mod litellm;
 pub mod local_inference;
+pub mod markdown_chunks;
 pub mod oauth;
 pub mod ollama;
 pub mod openai;
 pub mod openai_compatible;
 pub mod openrouter;
+pub mod preflight;
 pub mod provider_registry;
 pub mod provider_test;
+pub mod qwen_code;
 mod retry;
 pub mod sagemaker_tgi;
+pub mod server_tools;
 pub mod snowflake;
 pub mod testprovider;
 pub mod tetrate;
+pub mod token_manager;
 pub mod toolshim;
 pub mod usage_estimator;
 pub mod utils;
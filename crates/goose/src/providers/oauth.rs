@@ -1,10 +1,11 @@
+use super::token_manager::{OAuthToken, TokenManager, TokenSource};
 use crate::config::paths::Paths;
 use anyhow::Result;
+use async_trait::async_trait;
 use axum::{extract::Query, response::Html, routing::get, Router};
 use base64::Engine;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha2::Digest;
 use std::{collections::HashMap, fs, net::SocketAddr, path::PathBuf, sync::Arc};
@@ -19,20 +20,16 @@ struct OidcEndpoints {
     token_endpoint: String,
 }
 
-#[derive(Serialize, Deserialize)]
-struct TokenData {
-    /// The access token used to authenticate API requests
-    access_token: String,
-
-    /// Optional refresh token that can be used to obtain a new access token
-    /// when the current one expires, enabling offline access without user interaction
-    refresh_token: Option<String>,
-
-    /// When the access token expires (if known)
-    /// Used to determine when a token needs to be refreshed
-    expires_at: Option<DateTime<Utc>>,
+/// Identifies a sign-in by the workspace, client and scopes it is for
+fn token_hash(host: &str, client_id: &str, scopes: &[String]) -> String {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(host.as_bytes());
+    hasher.update(client_id.as_bytes());
+    hasher.update(scopes.join(",").as_bytes());
+    format!("{:x}", hasher.finalize())
 }
 
+/// Where tokens were kept before they moved to the secret backend
 struct TokenCache {
     cache_path: PathBuf,
 }
@@ -43,47 +40,23 @@ fn get_base_path() -> PathBuf {
 
 impl TokenCache {
     fn new(host: &str, client_id: &str, scopes: &[String]) -> Self {
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(host.as_bytes());
-        hasher.update(client_id.as_bytes());
-        hasher.update(scopes.join(",").as_bytes());
-        let hash = format!("{:x}", hasher.finalize());
-
-        fs::create_dir_all(get_base_path()).unwrap();
+        let hash = token_hash(host, client_id, scopes);
         let cache_path = get_base_path().join(format!("{}.json", hash));
-
         Self { cache_path }
     }
 
-    fn load_token(&self) -> Option<TokenData> {
-        if let Ok(contents) = fs::read_to_string(&self.cache_path) {
-            if let Ok(token_data) = serde_json::from_str::<TokenData>(&contents) {
-                // Only return tokens that have a refresh token
-                if token_data.refresh_token.is_some() {
-                    // If token is not expired, return it for immediate use
-                    if let Some(expires_at) = token_data.expires_at {
-                        if expires_at > Utc::now() {
-                            return Some(token_data);
-                        }
-                        // If token is expired but has refresh token, return it so we can refresh
-                        return Some(token_data);
-                    }
-                    // No expiration time but has refresh token, return it
-                    return Some(token_data);
-                }
-                // Token doesn't have a refresh token, ignore it to force a new OAuth flow
-            }
-        }
-        None
+    /// The cached token. Tokens without a refresh token are ignored, so they are replaced by a
+    /// new sign-in.
+    fn read_token(&self) -> Option<OAuthToken> {
+        let contents = fs::read_to_string(&self.cache_path).ok()?;
+        serde_json::from_str::<OAuthToken>(&contents)
+            .ok()
+            .filter(|token| token.refresh_token.is_some())
     }
 
-    fn save_token(&self, token_data: &TokenData) -> Result<()> {
-        if let Some(parent) = self.cache_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let contents = serde_json::to_string(token_data)?;
-        fs::write(&self.cache_path, contents)?;
-        Ok(())
+    /// Remove the file once its token lives in the secret backend
+    fn remove(&self) {
+        let _ = fs::remove_file(&self.cache_path);
     }
 }
 
@@ -161,7 +134,7 @@ impl OAuthFlow {
     ///   some providers don't return a new refresh token with every refresh operation.
     ///
     /// # Returns
-    /// A Result containing the TokenData with access_token, refresh_token (if available)
+    /// A Result containing the OAuthToken with access_token, refresh_token (if available)
     ///
     /// # Error
     /// Returns an error if the required access_token is missing from the response.
@@ -169,7 +142,7 @@ impl OAuthFlow {
         &self,
         token_response: &Value,
         old_refresh_token: Option<&str>,
-    ) -> Result<TokenData> {
+    ) -> Result<OAuthToken> {
         // Extract access token (required)
         let access_token = token_response
             .get("access_token")
@@ -198,10 +171,11 @@ impl OAuthFlow {
                 None
             };
 
-        Ok(TokenData {
+        Ok(OAuthToken {
             access_token,
             refresh_token,
             expires_at,
+            extra: Default::default(),
         })
     }
 
@@ -232,7 +206,7 @@ impl OAuthFlow {
         &self,
         code: &str,
         redirect_url: &str,
-    ) -> Result<TokenData> {
+    ) -> Result<OAuthToken> {
         let params = [
             ("grant_type", "authorization_code"),
             ("code", code),
@@ -261,7 +235,7 @@ impl OAuthFlow {
         self.extract_token_data(&token_response, None)
     }
 
-    async fn refresh_token(&self, refresh_token: &str) -> Result<TokenData> {
+    async fn refresh_token(&self, refresh_token: &str) -> Result<OAuthToken> {
         let params = [
             ("grant_type", "refresh_token"),
             ("refresh_token", refresh_token),
@@ -287,7 +261,7 @@ impl OAuthFlow {
         self.extract_token_data(&token_response, Some(refresh_token))
     }
 
-    async fn execute(&self) -> Result<TokenData> {
+    async fn execute(&self) -> Result<OAuthToken> {
         // Create a channel that will send the auth code from the app process
         let (tx, rx) = oneshot::channel();
         let state = self.state.clone();
@@ -371,90 +345,80 @@ impl OAuthFlow {
     }
 }
 
-pub(crate) async fn get_oauth_token_async(
-    host: &str,
-    client_id: &str,
-    redirect_url: &str,
-    scopes: &[String],
-) -> Result<String> {
-    // Acquire the global mutex to ensure only one OAuth flow runs at a time
-    let _guard = OAUTH_MUTEX.lock().await;
+struct DatabricksTokens {
+    host: String,
+    client_id: String,
+    redirect_url: String,
+    scopes: Vec<String>,
+}
 
-    let token_cache = TokenCache::new(host, client_id, scopes);
+impl DatabricksTokens {
+    async fn flow(&self) -> Result<OAuthFlow> {
+        let endpoints = get_workspace_endpoints(&self.host).await?;
+        Ok(OAuthFlow::new(
+            endpoints,
+            self.client_id.clone(),
+            self.redirect_url.clone(),
+            self.scopes.clone(),
+        ))
+    }
+}
 
-    // Try cache first
-    if let Some(token) = token_cache.load_token() {
-        // If token has an expiration time, check if it's expired
-        if let Some(expires_at) = token.expires_at {
-            if expires_at > Utc::now() {
-                return Ok(token.access_token);
-            }
-            // Token is expired, will try to refresh below
-            tracing::debug!("Token is expired, attempting to refresh");
-        } else {
-            // No expiration time was provided by the server
-            // We'll use the token without checking expiration
-            // This is safe because we'll fall back to refresh token if the server rejects it
-            tracing::debug!("Token has no expiration time, using it without expiration check");
-            return Ok(token.access_token);
-        }
+#[async_trait]
+impl TokenSource for DatabricksTokens {
+    async fn refresh(&self, token: &OAuthToken) -> Result<OAuthToken> {
+        let refresh_token = token
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("the token has no refresh token"))?;
+        // NOTE: Per OAuth 2.0 RFC 6749, the authorization server MAY issue a new
+        // refresh_token; without one the old one is kept.
+        self.flow().await?.refresh_token(refresh_token).await
+    }
 
-        // Token is expired or has no expiration, try to refresh if we have a refresh token
-        if let Some(refresh_token) = token.refresh_token {
-            // Get endpoints for token refresh
-            match get_workspace_endpoints(host).await {
-                Ok(endpoints) => {
-                    let flow = OAuthFlow::new(
-                        endpoints,
-                        client_id.to_string(),
-                        redirect_url.to_string(),
-                        scopes.to_vec(),
-                    );
-
-                    // Try to refresh the token
-                    match flow.refresh_token(&refresh_token).await {
-                        Ok(new_token) => {
-                            // NOTE: Per OAuth 2.0 RFC 6749, the authorization server MAY issue
-                            // a new refresh_token. We save the entire token response so that we
-                            // capture all updated token data, even if no new refresh_token is returned.
-                            if let Err(e) = token_cache.save_token(&new_token) {
-                                tracing::warn!("Failed to save refreshed token: {}", e);
-                            }
-                            tracing::info!("Successfully refreshed token");
-                            return Ok(new_token.access_token);
-                        }
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to refresh token, will try new auth flow: {}",
-                                e
-                            );
-                            // Continue to new auth flow
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get endpoints for token refresh: {}", e);
-                    // Continue to new auth flow
-                }
+    async fn sign_in(&self) -> Result<OAuthToken> {
+        // Only one browser flow runs at a time
+        let _guard = OAUTH_MUTEX.lock().await;
+
+        let cache = TokenCache::new(&self.host, &self.client_id, &self.scopes);
+        if let Some(token) = cache.read_token() {
+            if token
+                .expires_at
+                .is_none_or(|expires_at| expires_at > Utc::now())
+            {
+                return Ok(token);
+            }
+            if let Ok(token) = self.refresh(&token).await {
+                return Ok(token);
             }
         }
+
+        self.flow().await?.execute().await
+    }
+
+    fn saved(&self) {
+        TokenCache::new(&self.host, &self.client_id, &self.scopes).remove();
     }
+}
 
-    // Get endpoints and execute flow for a new token
-    let endpoints = get_workspace_endpoints(host).await?;
-    let flow = OAuthFlow::new(
-        endpoints,
-        client_id.to_string(),
-        redirect_url.to_string(),
-        scopes.to_vec(),
-    );
-
-    // Execute the OAuth flow and get token
-    let token = flow.execute().await?;
-
-    // Cache and return
-    token_cache.save_token(&token)?;
-    Ok(token.access_token)
+pub(crate) async fn get_oauth_token_async(
+    host: &str,
+    client_id: &str,
+    redirect_url: &str,
+    scopes: &[String],
+) -> Result<String> {
+    let name = format!("databricks_{}", &token_hash(host, client_id, scopes)[..16]);
+    let source = DatabricksTokens {
+        host: host.to_string(),
+        client_id: client_id.to_string(),
+        redirect_url: redirect_url.to_string(),
+        scopes: scopes.to_vec(),
+    };
+    TokenManager::shared("databricks", &name, || {
+        Arc::new(source) as Arc<dyn TokenSource>
+    })
+    .access_token()
+    .await
 }
 
 #[cfg(test)]
@@ -498,37 +462,28 @@ mod tests {
             "test-client",
             &["scope1".to_string()],
         );
+        fs::create_dir_all(get_base_path())?;
 
-        // Test with expiration time
-        let token_data = TokenData {
-            access_token: "test-token".to_string(),
-            refresh_token: Some("test-refresh-token".to_string()),
-            expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
-        };
-
-        cache.save_token(&token_data)?;
-
-        let loaded_token = cache.load_token().unwrap();
-        assert_eq!(loaded_token.access_token, token_data.access_token);
-        assert_eq!(loaded_token.refresh_token, token_data.refresh_token);
-        assert!(loaded_token.expires_at.is_some());
-
-        // Test without expiration time
-        let token_data_no_expiry = TokenData {
-            access_token: "test-token-2".to_string(),
-            refresh_token: Some("test-refresh-token-2".to_string()),
-            expires_at: None,
-        };
-
-        cache.save_token(&token_data_no_expiry)?;
-
-        let loaded_token = cache.load_token().unwrap();
-        assert_eq!(loaded_token.access_token, token_data_no_expiry.access_token);
-        assert_eq!(
-            loaded_token.refresh_token,
-            token_data_no_expiry.refresh_token
-        );
-        assert!(loaded_token.expires_at.is_none());
+        let cached = serde_json::json!({
+            "access_token": "test-token",
+            "refresh_token": "test-refresh-token",
+            "expires_at": Utc::now() + chrono::Duration::hours(1),
+        });
+        fs::write(&cache.cache_path, cached.to_string())?;
+
+        let token = cache.read_token().unwrap();
+        assert_eq!(token.access_token, "test-token");
+        assert_eq!(token.refresh_token.as_deref(), Some("test-refresh-token"));
+        assert!(token.expires_at.is_some());
+        // The file stays until the token is saved in the secret backend
+        assert!(cache.read_token().is_some());
+        cache.remove();
+        assert!(cache.read_token().is_none());
+
+        // Without a refresh token the cached token is not worth keeping
+        fs::write(&cache.cache_path, r#"{"access_token": "test-token-2"}"#)?;
+        assert!(cache.read_token().is_none());
+        cache.remove();
 
         Ok(())
     }
@@ -4,6 +4,7 @@ use super::errors::ProviderError;
 use super::openai_compatible::handle_status_openai_compat;
 use super::retry::ProviderRetry;
 use super::utils::{ImageFormat, RequestLog};
+use crate::agents::lifecycle::{self, LifecycleEvent};
 use crate::config::declarative_providers::DeclarativeProviderConfig;
 use crate::config::GooseMode;
 use crate::conversation::message::Message;
@@ -16,6 +17,7 @@ use futures::future::BoxFuture;
 use futures::TryStreamExt;
 use reqwest::Response;
 use rmcp::model::Tool;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::time::Duration;
 use tokio::pin;
@@ -36,6 +38,75 @@ pub const OLLAMA_KNOWN_MODELS: &[&str] = &[
     "qwen3-coder:480b-cloud",
 ];
 pub const OLLAMA_DOC_URL: &str = "https://ollama.com/library";
+/// Upper bound on a whole pull; large models take a while on slow links
+const PULL_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+/// A pull that reports no progress for this long has stalled
+const PULL_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A model installed on the Ollama server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    /// Bytes on disk
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    #[serde(default)]
+    pub details: OllamaModelDetails,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OllamaModelDetails {
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    #[serde(default)]
+    pub quantization_level: Option<String>,
+}
+
+/// A model the Ollama server currently holds in memory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadedOllamaModel {
+    pub name: String,
+    /// Bytes of memory in use, of which `size_vram` are on the GPU
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub size_vram: u64,
+    /// When the server unloads the model if it stays unused
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullProgress {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    total: Option<u64>,
+}
+
+/// Whether `name` from the server's model list is `model`; a model without a tag is
+/// installed as `:latest`.
+fn same_model(name: &str, model: &str) -> bool {
+    name == model || (!model.contains(':') && name == format!("{model}:latest"))
+}
+
+/// Ollama answers requests for a model it doesn't have with a 404 that says to pull it.
+fn not_installed_error(error: ProviderError, model: &str) -> ProviderError {
+    if error.to_string().contains("try pulling it") {
+        ProviderError::RequestFailed(format!(
+            "The Ollama model {model} isn't installed. Pull it with `ollama pull {model}`, \
+             or run `goose configure` and select it to have goose pull it."
+        ))
+    } else {
+        error
+    }
+}
 
 #[derive(serde::Serialize)]
 pub struct OllamaProvider {
@@ -168,6 +239,113 @@ impl OllamaProvider {
             name: config.name.clone(),
         })
     }
+
+    async fn get_json(&self, path: &str) -> Result<Value, ProviderError> {
+        let response = self
+            .api_client
+            .request(None, path)
+            .response_get()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to reach Ollama: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ProviderError::RequestFailed(format!(
+                "Ollama request to {} failed: HTTP {}",
+                path,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<Value>()
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {}", e)))
+    }
+
+    /// The models installed on the server, with their sizes.
+    pub async fn list_local_models(&self) -> Result<Vec<OllamaModel>, ProviderError> {
+        let json_response = self.get_json("api/tags").await?;
+        let models = json_response.get("models").cloned().ok_or_else(|| {
+            ProviderError::RequestFailed("No models array in response".to_string())
+        })?;
+        serde_json::from_value(models)
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {}", e)))
+    }
+
+    /// The models loaded into memory right now.
+    pub async fn loaded_models(&self) -> Result<Vec<LoadedOllamaModel>, ProviderError> {
+        let json_response = self.get_json("api/ps").await?;
+        let models = json_response
+            .get("models")
+            .cloned()
+            .unwrap_or_else(|| json!([]));
+        serde_json::from_value(models)
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to parse response: {}", e)))
+    }
+
+    pub async fn is_installed(&self, model: &str) -> Result<bool, ProviderError> {
+        Ok(self
+            .list_local_models()
+            .await?
+            .iter()
+            .any(|installed| same_model(&installed.name, model)))
+    }
+
+    /// Download a model to the server. Progress is published as
+    /// [`LifecycleEvent::ModelPullProgress`] for `session_id`. The provider's request timeout
+    /// doesn't apply; the pull fails once it stops making progress instead.
+    pub async fn pull_model(&self, model: &str, session_id: &str) -> Result<(), ProviderError> {
+        let response = self
+            .api_client
+            .request(None, "api/pull")
+            .timeout(PULL_TIMEOUT)
+            .response_post(&json!({"model": model, "stream": true}))
+            .await
+            .map_err(|e| ProviderError::RequestFailed(format!("Failed to reach Ollama: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(ProviderError::RequestFailed(format!(
+                "Failed to pull {}: HTTP {}",
+                model,
+                response.status()
+            )));
+        }
+
+        let stream = response.bytes_stream().map_err(std::io::Error::other);
+        let mut lines = FramedRead::new(StreamReader::new(stream), LinesCodec::new());
+        loop {
+            let line = match tokio::time::timeout(PULL_IDLE_TIMEOUT, lines.next()).await {
+                Ok(Some(line)) => line.map_err(|e| {
+                    ProviderError::RequestFailed(format!("Failed to read pull progress: {}", e))
+                })?,
+                Ok(None) => break,
+                Err(_) => {
+                    return Err(ProviderError::RequestFailed(format!(
+                        "Pulling {} stalled: no progress for {} seconds",
+                        model,
+                        PULL_IDLE_TIMEOUT.as_secs()
+                    )))
+                }
+            };
+            let Ok(progress) = serde_json::from_str::<PullProgress>(&line) else {
+                continue;
+            };
+            if let Some(error) = progress.error {
+                return Err(ProviderError::RequestFailed(format!(
+                    "Failed to pull {}: {}",
+                    model, error
+                )));
+            }
+            lifecycle::publish(LifecycleEvent::ModelPullProgress {
+                session_id: session_id.to_string(),
+                provider: self.name.clone(),
+                model: model.to_string(),
+                status: progress.status,
+                completed: progress.completed,
+                total: progress.total,
+            });
+        }
+        Ok(())
+    }
 }
 
 impl ProviderDef for OllamaProvider {
@@ -248,6 +426,7 @@ impl Provider for OllamaProvider {
                 handle_status_openai_compat(resp).await
             })
             .await
+            .map_err(|e| not_installed_error(e, &model_config.model_name))
             .inspect_err(|e| {
                 let _ = log.error(e);
             })?;
@@ -255,34 +434,11 @@ impl Provider for OllamaProvider {
     }
 
     async fn fetch_supported_models(&self) -> Result<Vec<String>, ProviderError> {
-        let response = self
-            .api_client
-            .request(None, "api/tags")
-            .response_get()
-            .await
-            .map_err(|e| ProviderError::RequestFailed(format!("Failed to fetch models: {}", e)))?;
-
-        if !response.status().is_success() {
-            return Err(ProviderError::RequestFailed(format!(
-                "Failed to fetch models: HTTP {}",
-                response.status()
-            )));
-        }
-
-        let json_response = response.json::<Value>().await.map_err(|e| {
-            ProviderError::RequestFailed(format!("Failed to parse response: {}", e))
-        })?;
-
-        let models = json_response
-            .get("models")
-            .and_then(|m| m.as_array())
-            .ok_or_else(|| {
-                ProviderError::RequestFailed("No models array in response".to_string())
-            })?;
-
-        let mut model_names: Vec<String> = models
-            .iter()
-            .filter_map(|model| model.get("name").and_then(|n| n.as_str()).map(String::from))
+        let mut model_names: Vec<String> = self
+            .list_local_models()
+            .await?
+            .into_iter()
+            .map(|model| model.name)
             .collect();
 
         model_names.sort();
@@ -305,9 +461,7 @@ fn stream_ollama(response: Response, mut log: RequestLog) -> Result<MessageStrea
         let message_stream = response_to_streaming_message_ollama(framed);
         pin!(message_stream);
         while let Some(message) = message_stream.next().await {
-            let (message, usage) = message.map_err(|e|
-                ProviderError::RequestFailed(format!("Stream decode error: {}", e))
-            )?;
+            let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
             log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
             yield (message, usage);
         }
@@ -340,6 +494,32 @@ mod tests {
         assert_eq!(payload["options"]["num_ctx"], 12_000);
     }
 
+    #[test]
+    fn test_model_listing_parses_sizes_and_tags() {
+        let models: Vec<OllamaModel> = serde_json::from_value(json!([{
+            "name": "qwen3:latest",
+            "size": 5_225_388_164u64,
+            "modified_at": "2025-06-01T10:00:00Z",
+            "details": {"parameter_size": "8.2B", "quantization_level": "Q4_K_M"}
+        }]))
+        .unwrap();
+        assert_eq!(models[0].size, 5_225_388_164);
+        assert_eq!(models[0].details.parameter_size.as_deref(), Some("8.2B"));
+
+        assert!(same_model("qwen3:latest", "qwen3"));
+        assert!(same_model("qwen3:8b", "qwen3:8b"));
+        assert!(!same_model("qwen3:8b", "qwen3"));
+    }
+
+    #[test]
+    fn test_missing_model_error_suggests_pulling() {
+        let error = ProviderError::RequestFailed(
+            "model \"llama9\" not found, try pulling it first".to_string(),
+        );
+        let error = not_installed_error(error, "llama9");
+        assert!(error.to_string().contains("ollama pull llama9"));
+    }
+
     #[test]
     fn test_apply_ollama_options_skips_when_no_limit() {
         let _guard = env_lock::lock_env([("GOOSE_INPUT_LIMIT", None::<&str>)]);
@@ -11,6 +11,7 @@ use super::openai_compatible::{
     handle_response_openai_compat, handle_status_openai_compat, stream_openai_compat,
 };
 use super::retry::ProviderRetry;
+use super::server_tools;
 use super::utils::ImageFormat;
 use crate::config::declarative_providers::DeclarativeProviderConfig;
 use crate::conversation::message::Message;
@@ -380,8 +381,19 @@ impl Provider for OpenAiProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        if Self::should_use_responses_api(&model_config.model_name, &self.base_path) {
-            let mut payload = create_responses_request(model_config, system, messages, tools)?;
+        // Server tools only exist in the Responses API, which is used for them unless the
+        // base path was changed
+        let server_tools =
+            if Self::normalize_base_path(&self.base_path) == OPEN_AI_DEFAULT_BASE_PATH {
+                server_tools::for_request(model_config, tools)
+            } else {
+                Vec::new()
+            };
+        if !server_tools.is_empty()
+            || Self::should_use_responses_api(&model_config.model_name, &self.base_path)
+        {
+            let mut payload =
+                create_responses_request(model_config, system, messages, tools, &server_tools)?;
             payload["stream"] = serde_json::Value::Bool(self.supports_streaming);
 
             let mut log = RequestLog::start(model_config, &payload)?;
@@ -418,7 +430,7 @@ impl Provider for OpenAiProvider {
                     let message_stream = responses_api_to_streaming_message(framed);
                     pin!(message_stream);
                     while let Some(message) = message_stream.next().await {
-                        let (message, usage) = message.map_err(|e| ProviderError::RequestFailed(format!("Stream decode error: {}", e)))?;
+                        let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
                         log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
                         yield (message, usage);
                     }
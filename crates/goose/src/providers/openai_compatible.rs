@@ -166,7 +166,10 @@ pub fn map_http_error_to_provider_error(
             .unwrap_or_else(|| payload.as_ref().map(|p| p.to_string()).unwrap_or_default())
     };
 
-    let error = match status {
+    // The error type or code in the body says more than the status
+    let typed = payload.as_ref().and_then(ProviderError::from_error_payload);
+
+    let error = typed.unwrap_or_else(|| match status {
         StatusCode::OK => unreachable!("Should not call this function with OK status"),
         StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ProviderError::Authentication(format!(
             "Authentication failed. Status: {}. Response: {}",
@@ -193,6 +196,9 @@ pub fn map_http_error_to_provider_error(
             details: extract_message(),
             retry_delay: None,
         },
+        StatusCode::SERVICE_UNAVAILABLE => ProviderError::Overloaded(extract_message()),
+        // Anthropic's status for an overloaded API
+        _ if status.as_u16() == 529 => ProviderError::Overloaded(extract_message()),
         _ if status.is_server_error() => {
             ProviderError::ServerError(format!("Server error ({}): {}", status, extract_message()))
         }
@@ -201,7 +207,7 @@ pub fn map_http_error_to_provider_error(
             status,
             extract_message()
         )),
-    };
+    });
 
     if !status.is_success() {
         tracing::warn!(
@@ -247,9 +253,7 @@ pub fn stream_openai_compat(
         let message_stream = response_to_streaming_message(framed);
         pin!(message_stream);
         while let Some(message) = message_stream.next().await {
-            let (message, usage) = message.map_err(|e|
-                ProviderError::RequestFailed(format!("Stream decode error: {}", e))
-            )?;
+            let (message, usage) = message.map_err(ProviderError::from_stream_error)?;
             log.write(&message, usage.as_ref().map(|f| f.usage).as_ref())?;
             yield (message, usage);
         }
@@ -298,6 +302,24 @@ mod tests {
         "ServerError"
         ; "500 server error"
     )]
+    #[test_case(
+        StatusCode::from_u16(529).unwrap(),
+        Some(json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}})),
+        "Overloaded"
+        ; "529 anthropic overloaded"
+    )]
+    #[test_case(
+        StatusCode::BAD_REQUEST,
+        Some(json!({"error": {"code": "content_filter", "message": "The response was filtered"}})),
+        "ContentFiltered"
+        ; "400 content filter"
+    )]
+    #[test_case(
+        StatusCode::TOO_MANY_REQUESTS,
+        Some(json!({"error": {"type": "insufficient_quota", "message": "You exceeded your current quota"}})),
+        "CreditsExhausted"
+        ; "429 out of quota"
+    )]
     fn http_status_maps_to_expected_error(
         status: StatusCode,
         payload: Option<Value>,
@@ -311,6 +333,8 @@ mod tests {
             "Authentication" => "auth",
             "ContextLengthExceeded" => "context_length",
             "ServerError" => "server",
+            "Overloaded" => "overloaded",
+            "ContentFiltered" => "content_filter",
             other => panic!("Unknown variant: {other}"),
         };
         assert_eq!(
@@ -0,0 +1,229 @@
+//! Checking that the configured model exists before the first turn.
+//!
+//! The provider's model list is asked first; a provider that can't list its models is not
+//! checked at all. A model missing from the list gets a one-token request in a session of its
+//! own, since some models are served without being listed; when the provider answers that
+//! request saying the model doesn't exist, the check fails with [`ProviderError::ModelNotFound`]
+//! naming the listed models closest to the requested one. Any other failure lets the session
+//! start, so a slow model list or a flaky network never blocks it. Models that passed are
+//! remembered for the life of the process.
+//!
+//! `GOOSE_MODEL_PREFLIGHT: false` turns the check off.
+
+use super::base::Provider;
+use super::errors::ProviderError;
+use crate::config::Config;
+use crate::conversation::message::Message;
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+pub const MODEL_PREFLIGHT_CONFIG_KEY: &str = "GOOSE_MODEL_PREFLIGHT";
+
+const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(15);
+const MAX_SUGGESTIONS: usize = 3;
+/// The probe runs apart from the user's session, so it never shows in its usage or state
+const PROBE_SESSION_ID: &str = "model-preflight";
+
+/// Provider and model pairs that passed the check
+static CHECKED: LazyLock<Mutex<HashSet<(String, String)>>> = LazyLock::new(Default::default);
+
+pub fn enabled() -> bool {
+    Config::global()
+        .get_param::<bool>(MODEL_PREFLIGHT_CONFIG_KEY)
+        .unwrap_or(true)
+}
+
+/// Fails with [`ProviderError::ModelNotFound`] when the provider doesn't have the model it
+/// is configured with
+pub async fn check_model(provider: &dyn Provider) -> Result<(), ProviderError> {
+    // Lead/worker providers switch models as they go
+    if !enabled() || provider.as_lead_worker().is_some() {
+        return Ok(());
+    }
+    let model_config = provider.get_model_config();
+    let key = (
+        provider.get_name().to_string(),
+        model_config.model_name.clone(),
+    );
+    let checked = CHECKED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(&key);
+    if checked {
+        return Ok(());
+    }
+
+    let result = match tokio::time::timeout(PREFLIGHT_TIMEOUT, async {
+        let models = match provider.fetch_supported_models().await {
+            Ok(models) if !models.is_empty() => models,
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                tracing::debug!("Couldn't list the models of {}: {}", key.0, e);
+                return Ok(());
+            }
+        };
+        if lists_model(&models, &key.1) {
+            return Ok(());
+        }
+        let probe = provider
+            .complete(
+                &model_config.clone().with_max_tokens(Some(1)),
+                PROBE_SESSION_ID,
+                "Reply with one word.",
+                &[Message::user().with_text("ping")],
+                &[],
+            )
+            .await;
+        match probe {
+            Err(e) if says_model_missing(&e) => Err(ProviderError::ModelNotFound {
+                provider: key.0.clone(),
+                model: key.1.clone(),
+                suggestions: close_matches(&key.1, &models),
+            }),
+            Err(e) => {
+                tracing::debug!("Model check of {} was inconclusive: {}", key.1, e);
+                Ok(())
+            }
+            Ok(_) => Ok(()),
+        }
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => {
+            tracing::debug!("Model check of {} timed out", key.1);
+            return Ok(());
+        }
+    };
+    if result.is_ok() {
+        CHECKED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key);
+    }
+    result
+}
+
+/// Whether `models` has `model`, allowing for Ollama's implied `:latest` tag and Gemini's
+/// `models/` prefix
+fn lists_model(models: &[String], model: &str) -> bool {
+    let bare = |name: &str| {
+        let name = name.strip_prefix("models/").unwrap_or(name);
+        name.strip_suffix(":latest").unwrap_or(name).to_lowercase()
+    };
+    let model = bare(model);
+    models.iter().any(|listed| bare(listed) == model)
+}
+
+/// Whether a provider's error says the requested model doesn't exist
+fn says_model_missing(error: &ProviderError) -> bool {
+    if matches!(error, ProviderError::ModelNotFound { .. }) {
+        return true;
+    }
+    let text = error.to_string().to_lowercase();
+    text.contains("model")
+        && [
+            "not found",
+            "not_found",
+            "does not exist",
+            "doesn't exist",
+            "no such model",
+            "unknown model",
+            "invalid model",
+            "404",
+        ]
+        .iter()
+        .any(|phrase| text.contains(phrase))
+}
+
+/// The models whose names are closest to `model`, closest first
+fn close_matches(model: &str, models: &[String]) -> Vec<String> {
+    let model = model.to_lowercase();
+    let limit = (model.chars().count() / 3).max(3);
+    let mut matches: Vec<(usize, &String)> = models
+        .iter()
+        .filter_map(|listed| {
+            let name = listed.to_lowercase();
+            let distance = edit_distance(&model, &name);
+            let related = name.starts_with(&model) || model.starts_with(&name);
+            (distance <= limit || related).then_some((distance, listed))
+        })
+        .collect();
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, listed)| listed.clone())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_models_come_with_close_matches() {
+        let models: Vec<String> = ["gpt-4o", "gpt-4o-mini", "gpt-4.1", "o3", "text-embedding-3"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            close_matches("gpt-4o-mini-2024", &models),
+            ["gpt-4o-mini", "gpt-4o"]
+        );
+        assert_eq!(close_matches("gtp-4o", &models)[0], "gpt-4o");
+        assert!(close_matches("claude-sonnet-4-5", &models).is_empty());
+
+        assert!(lists_model(&["llama3.2:latest".to_string()], "llama3.2"));
+        assert!(lists_model(
+            &["models/gemini-2.5-pro".to_string()],
+            "gemini-2.5-pro"
+        ));
+        assert!(!lists_model(&models, "gpt-4o-mini-2024"));
+
+        let error = ProviderError::ModelNotFound {
+            provider: "openai".to_string(),
+            model: "gpt-4o-mini-2024".to_string(),
+            suggestions: vec!["gpt-4o-mini".to_string()],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Model gpt-4o-mini-2024 is not available from openai. Did you mean gpt-4o-mini?"
+        );
+    }
+
+    #[test]
+    fn provider_errors_about_missing_models_are_recognized() {
+        assert!(says_model_missing(&ProviderError::RequestFailed(
+            "Request failed with status: 404 Not Found. Message: The model `gpt-5-nano-x` does not exist"
+                .to_string()
+        )));
+        assert!(says_model_missing(&ProviderError::RequestFailed(
+            r#"{"type":"not_found_error","message":"model: claude-sonet-4"}"#.to_string()
+        )));
+        assert!(!says_model_missing(&ProviderError::Authentication(
+            "invalid x-api-key".to_string()
+        )));
+        assert!(!says_model_missing(&ProviderError::RequestFailed(
+            "max_tokens is too small for this model".to_string()
+        )));
+    }
+}
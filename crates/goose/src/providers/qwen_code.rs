@@ -0,0 +1,111 @@
+use anyhow::Result;
+use async_trait::async_trait;
+
+use super::base::{MessageStream, Provider, ProviderDef, ProviderMetadata};
+use super::cli_common::StreamJsonCli;
+use super::errors::ProviderError;
+use crate::config::base::QwenCodeCommand;
+use crate::config::search_path::SearchPaths;
+use crate::config::Config;
+use crate::conversation::message::Message;
+use crate::model::ModelConfig;
+use crate::providers::base::ConfigKey;
+use futures::future::BoxFuture;
+use rmcp::model::Tool;
+
+const QWEN_CODE_PROVIDER_NAME: &str = "qwen-code";
+pub const QWEN_CODE_DEFAULT_MODEL: &str = "qwen3-coder-plus";
+pub const QWEN_CODE_KNOWN_MODELS: &[&str] = &["qwen3-coder-plus", "qwen3-coder-flash"];
+
+pub const QWEN_CODE_DOC_URL: &str = "https://github.com/QwenLM/qwen-code";
+
+/// Where the CLI session is kept in the goose session's extension data
+const QWEN_CODE_STATE_NAME: &str = "qwen_code";
+
+#[derive(Debug, serde::Serialize)]
+pub struct QwenCodeProvider {
+    #[serde(flatten)]
+    cli: StreamJsonCli,
+    model: ModelConfig,
+    #[serde(skip)]
+    name: String,
+}
+
+impl QwenCodeProvider {
+    pub async fn from_env(model: ModelConfig) -> Result<Self> {
+        let config = Config::global();
+        let command: String = config.get_qwen_code_command().unwrap_or_default().into();
+        let resolved_command = SearchPaths::builder().with_npm().resolve(&command)?;
+
+        Ok(Self {
+            cli: StreamJsonCli::new(
+                QWEN_CODE_PROVIDER_NAME,
+                resolved_command,
+                "--resume",
+                QWEN_CODE_STATE_NAME,
+            ),
+            model,
+            name: QWEN_CODE_PROVIDER_NAME.to_string(),
+        })
+    }
+}
+
+impl ProviderDef for QwenCodeProvider {
+    type Provider = Self;
+
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            QWEN_CODE_PROVIDER_NAME,
+            "Qwen Code",
+            "Execute Qwen models via the qwen CLI tool",
+            QWEN_CODE_DEFAULT_MODEL,
+            QWEN_CODE_KNOWN_MODELS.to_vec(),
+            QWEN_CODE_DOC_URL,
+            vec![ConfigKey::from_value_type::<QwenCodeCommand>(
+                true, false, true,
+            )],
+        )
+    }
+
+    fn from_env(
+        model: ModelConfig,
+        _extensions: Vec<crate::config::ExtensionConfig>,
+    ) -> BoxFuture<'static, Result<Self::Provider>> {
+        Box::pin(Self::from_env(model))
+    }
+}
+
+#[async_trait]
+impl Provider for QwenCodeProvider {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    async fn fetch_supported_models(&self) -> Result<Vec<String>, ProviderError> {
+        Ok(QWEN_CODE_KNOWN_MODELS
+            .iter()
+            .map(|s| s.to_string())
+            .collect())
+    }
+
+    #[tracing::instrument(
+        skip(self, model_config, system, messages, _tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        model_config: &ModelConfig,
+        session_id: &str,
+        system: &str,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        self.cli
+            .stream(model_config, session_id, system, messages)
+            .await
+    }
+}
@@ -72,13 +72,7 @@ impl RetryConfig {
 }
 
 pub fn should_retry(error: &ProviderError) -> bool {
-    matches!(
-        error,
-        ProviderError::RateLimitExceeded { .. }
-            | ProviderError::ServerError(_)
-            | ProviderError::NetworkError(_)
-            | ProviderError::RequestFailed(_)
-    )
+    error.code().is_retryable()
 }
 
 pub async fn retry_operation<F, Fut, T>(
@@ -0,0 +1,301 @@
+//! Tools the model provider runs on its side.
+//!
+//! OpenAI, Anthropic and Gemini can search the web and run code during a response, without
+//! goose taking part. `GOOSE_SERVER_TOOLS` turns these on per model; a name ending in `*`
+//! matches every model it prefixes:
+//!
+//! ```yaml
+//! GOOSE_SERVER_TOOLS:
+//!   claude-sonnet-4-5: [web_search, code_execution]
+//!   gpt-5*: [web_search]
+//! ```
+//!
+//! A model's `server_tools` request parameter takes precedence over the config, and setting the
+//! web extension's search backend to `provider` turns on web_search for every model.
+//!
+//! Only the Anthropic, OpenAI and Google providers send them, working out the tools once per
+//! request with [`for_request`] and passing them to the shared request builders. Other
+//! providers built on the same formats, like Vertex AI and Databricks, never do.
+//!
+//! The calls the provider reports become tool requests and responses marked with
+//! [`SERVER_TOOL_METADATA_KEY`], both on the assistant message. The agent doesn't run them,
+//! and they are sent back to the model as text, since providers take back only their own calls
+//! in their own format. Each call is counted in the response's usage, and its list price is
+//! added to the cost.
+
+use crate::agents::platform_extensions::web::search::{SearchConfig, WEB_SEARCH_CONFIG_KEY};
+use crate::config::{Config, ConfigError};
+use crate::conversation::message::{Message, MessageContent, ProviderMetadata};
+use crate::model::ModelConfig;
+use rmcp::model::{object, CallToolRequestParams, CallToolResult, Content, Tool};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Add;
+
+pub const SERVER_TOOLS_CONFIG_KEY: &str = "GOOSE_SERVER_TOOLS";
+/// Set in the metadata of tool requests and responses the provider ran
+pub const SERVER_TOOL_METADATA_KEY: &str = "server_tool";
+const SERVER_TOOLS_PARAM: &str = "server_tools";
+
+/// Results longer than this are cut short when sent back to the model
+const MAX_RESULT_CHARS: usize = 4_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerTool {
+    WebSearch,
+    CodeExecution,
+}
+
+impl ServerTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            ServerTool::WebSearch => "web_search",
+            ServerTool::CodeExecution => "code_execution",
+        }
+    }
+}
+
+/// Calls to server tools made during a response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerToolUsage {
+    #[serde(default)]
+    pub web_search: u32,
+    #[serde(default)]
+    pub code_execution: u32,
+}
+
+impl ServerToolUsage {
+    pub fn is_empty(&self) -> bool {
+        self.web_search == 0 && self.code_execution == 0
+    }
+
+    pub fn record(&mut self, tool: ServerTool) {
+        match tool {
+            ServerTool::WebSearch => self.web_search += 1,
+            ServerTool::CodeExecution => self.code_execution += 1,
+        }
+    }
+
+    /// The list price of the calls in USD. Containers billed by the hour and calls to
+    /// providers without a known price count as free.
+    pub fn cost(&self, provider: &str) -> f64 {
+        let price = |tool| price_per_call(provider, tool).unwrap_or(0.0);
+        self.web_search as f64 * price(ServerTool::WebSearch)
+            + self.code_execution as f64 * price(ServerTool::CodeExecution)
+    }
+}
+
+impl Add for ServerToolUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            web_search: self.web_search + other.web_search,
+            code_execution: self.code_execution + other.code_execution,
+        }
+    }
+}
+
+fn price_per_call(provider: &str, tool: ServerTool) -> Option<f64> {
+    match (provider, tool) {
+        ("anthropic", ServerTool::WebSearch) => Some(0.01),
+        ("openai", ServerTool::WebSearch) => Some(0.01),
+        ("openai", ServerTool::CodeExecution) => Some(0.03),
+        ("google", ServerTool::WebSearch) => Some(0.035),
+        _ => None,
+    }
+}
+
+/// The server tools turned on for a model
+fn enabled(model_config: &ModelConfig) -> Vec<ServerTool> {
+    let mut tools = model_config
+        .request_params
+        .as_ref()
+        .and_then(|params| params.get(SERVER_TOOLS_PARAM))
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_else(|| configured_for(&model_config.model_name));
+    let provider_search = Config::global()
+        .get_param::<SearchConfig>(WEB_SEARCH_CONFIG_KEY)
+        .is_ok_and(|config| config == SearchConfig::Provider);
+    if provider_search && !tools.contains(&ServerTool::WebSearch) {
+        tools.push(ServerTool::WebSearch);
+    }
+    tools
+}
+
+/// The server tools to send with a request, leaving out any whose name one of goose's own
+/// tools already has
+pub fn for_request(model_config: &ModelConfig, tools: &[Tool]) -> Vec<ServerTool> {
+    enabled(model_config)
+        .into_iter()
+        .filter(|server_tool| {
+            let taken = tools.iter().any(|tool| tool.name == server_tool.name());
+            if taken {
+                tracing::warn!(
+                    "Not sending the provider's {} tool, goose has a tool of that name",
+                    server_tool.name()
+                );
+            }
+            !taken
+        })
+        .collect()
+}
+
+fn configured_for(model: &str) -> Vec<ServerTool> {
+    match Config::global().get_param::<HashMap<String, Vec<ServerTool>>>(SERVER_TOOLS_CONFIG_KEY) {
+        Ok(models) => tools_for_model(&models, model),
+        Err(ConfigError::NotFound(_)) => Vec::new(),
+        Err(e) => {
+            tracing::warn!("Ignoring malformed {}: {}", SERVER_TOOLS_CONFIG_KEY, e);
+            Vec::new()
+        }
+    }
+}
+
+/// The tools for `model`: its own entry, else the one with the longest matching `prefix*`
+fn tools_for_model(models: &HashMap<String, Vec<ServerTool>>, model: &str) -> Vec<ServerTool> {
+    if let Some(tools) = models.get(model) {
+        return tools.clone();
+    }
+    models
+        .iter()
+        .filter_map(|(pattern, tools)| {
+            let prefix = pattern.strip_suffix('*')?;
+            model.starts_with(prefix).then_some((prefix.len(), tools))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, tools)| tools.clone())
+        .unwrap_or_default()
+}
+
+fn metadata() -> ProviderMetadata {
+    let mut metadata = ProviderMetadata::new();
+    metadata.insert(SERVER_TOOL_METADATA_KEY.to_string(), Value::Bool(true));
+    metadata
+}
+
+/// A call the provider ran, as a tool request and its response
+pub fn call(
+    id: &str,
+    tool: ServerTool,
+    arguments: Value,
+    result: String,
+    is_error: bool,
+) -> [MessageContent; 2] {
+    let metadata = metadata();
+    let request = CallToolRequestParams::new(tool.name()).with_arguments(object(arguments));
+    let result = if is_error {
+        CallToolResult::error(vec![Content::text(result)])
+    } else {
+        CallToolResult::success(vec![Content::text(result)])
+    };
+    [
+        MessageContent::tool_request_with_metadata(id, Ok(request), Some(&metadata)),
+        MessageContent::tool_response_with_metadata(id, Ok(result), Some(&metadata)),
+    ]
+}
+
+/// `messages` with the calls providers ran replaced by text, for sending to a provider
+pub fn calls_as_text(messages: Vec<Message>) -> Vec<Message> {
+    messages
+        .into_iter()
+        .map(|mut message| {
+            let has_calls = message.content.iter().any(|content| match content {
+                MessageContent::ToolRequest(request) => request.is_server_tool(),
+                MessageContent::ToolResponse(response) => response.is_server_tool(),
+                _ => false,
+            });
+            if !has_calls {
+                return message;
+            }
+            message.content = std::mem::take(&mut message.content)
+                .into_iter()
+                .map(|content| match &content {
+                    MessageContent::ToolRequest(request) if request.is_server_tool() => {
+                        let text = match &request.tool_call {
+                            Ok(call) => format!(
+                                "[Ran {} with {}]",
+                                call.name,
+                                serde_json::to_string(&call.arguments).unwrap_or_default()
+                            ),
+                            Err(e) => format!("[Ran a provider tool: {}]", e.message),
+                        };
+                        MessageContent::text(text)
+                    }
+                    MessageContent::ToolResponse(response) if response.is_server_tool() => {
+                        let text = match &response.tool_result {
+                            Ok(result) => result
+                                .content
+                                .iter()
+                                .filter_map(|content| content.as_text().map(|t| t.text.as_str()))
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                            Err(e) => e.message.to_string(),
+                        };
+                        let text = match text.char_indices().nth(MAX_RESULT_CHARS) {
+                            Some((end, _)) => format!("{}\n[cut short]", &text[..end]),
+                            None => text,
+                        };
+                        MessageContent::text(format!("[Result]\n{}", text))
+                    }
+                    _ => content,
+                })
+                .collect();
+            message
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn models_match_their_entry_or_the_longest_prefix() {
+        let models: HashMap<String, Vec<ServerTool>> = serde_yaml::from_str(
+            "gpt-5*: [web_search]\ngpt-5.2*: [web_search, code_execution]\ngpt-5-mini: []\n",
+        )
+        .unwrap();
+        assert_eq!(
+            tools_for_model(&models, "gpt-5.2-pro"),
+            [ServerTool::WebSearch, ServerTool::CodeExecution]
+        );
+        assert_eq!(tools_for_model(&models, "gpt-5"), [ServerTool::WebSearch]);
+        assert!(tools_for_model(&models, "gpt-5-mini").is_empty());
+        assert!(tools_for_model(&models, "claude-sonnet-4-5").is_empty());
+    }
+
+    #[test]
+    fn provider_calls_go_back_as_text() {
+        let [request, response] = call(
+            "srvtoolu_1",
+            ServerTool::WebSearch,
+            json!({"query": "goose agent"}),
+            "- goose (https://example.com/goose)".to_string(),
+            false,
+        );
+        let message = Message::assistant()
+            .with_content(request)
+            .with_content(response)
+            .with_text("goose is an agent");
+        let sent = calls_as_text(vec![message]);
+
+        let texts: Vec<String> = sent[0]
+            .content
+            .iter()
+            .filter_map(|content| content.as_text().map(str::to_string))
+            .collect();
+        assert_eq!(
+            texts,
+            [
+                r#"[Ran web_search with {"query":"goose agent"}]"#,
+                "[Result]\n- goose (https://example.com/goose)",
+                "goose is an agent",
+            ]
+        );
+    }
+}
@@ -1,20 +1,26 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, LazyLock, Mutex};
 
 #[cfg(test)]
 use super::base::stream_from_single_message;
 use super::base::{MessageStream, Provider, ProviderDef, ProviderMetadata, ProviderUsage};
 use super::errors::ProviderError;
-use crate::conversation::message::{Message, ToolResponse};
+use crate::conversation::message::{Message, MessageContent, ToolResponse};
 use crate::model::ModelConfig;
 use futures::future::BoxFuture;
-use rmcp::model::{CallToolResult, Tool};
+use rmcp::model::{CallToolResult, Role, Tool};
+
+/// The hourly timestamp in the system prompt, which would otherwise stop a recording from
+/// replaying an hour later
+static PROMPT_TIMESTAMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}").unwrap());
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TestInput {
@@ -73,14 +79,31 @@ impl TestProvider {
         Ok(())
     }
 
-    fn hash_input(messages: &[Message]) -> String {
-        use crate::conversation::message::MessageContent;
+    /// The key of a call in a recording. Calls with the same messages but different system
+    /// prompts, like an agent turn and the grader reading it, get their own records.
+    fn hash_input(system: &str, messages: &[Message]) -> String {
+        let system = PROMPT_TIMESTAMP.replace_all(system, "<timestamp>");
+        Self::digest(&(system, Self::stable_messages(messages)))
+    }
+
+    /// The key of recordings made before the system prompt was part of it
+    fn legacy_hash_input(messages: &[Message]) -> String {
+        Self::digest(&Self::stable_messages(messages))
+    }
 
+    fn digest(input: &impl Serialize) -> String {
+        let serialized = serde_json::to_string(input).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(serialized.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn stable_messages(messages: &[Message]) -> Vec<(Role, Vec<MessageContent>)> {
         // Strip internal metadata (e.g. tool_meta/_meta) from content before hashing.
         // This metadata is used for internal routing (like goose_extension ownership)
         // and isn't part of the semantic input the LLM sees, so it shouldn't affect
         // replay matching.
-        let stable_messages: Vec<_> = messages
+        messages
             .iter()
             .map(|msg| {
                 let mut cleaned_content: Vec<_> = msg.content.to_vec();
@@ -90,28 +113,27 @@ impl TestProvider {
                         MessageContent::ToolRequest(ref mut req) => {
                             req.tool_meta = None;
                         }
-                        MessageContent::ToolResponse(ToolResponse {
-                            tool_result:
-                                Ok(
-                                    ref mut result @ CallToolResult {
-                                        is_error: Some(false),
-                                        ..
-                                    },
-                                ),
-                            ..
-                        }) => {
-                            result.is_error = None;
+                        MessageContent::ToolResponse(response) => {
+                            if let ToolResponse {
+                                tool_result:
+                                    Ok(
+                                        ref mut result @ CallToolResult {
+                                            is_error: Some(false),
+                                            ..
+                                        },
+                                    ),
+                                ..
+                            } = Arc::make_mut(response)
+                            {
+                                result.is_error = None;
+                            }
                         }
                         _ => {}
                     }
                 }
                 (msg.role.clone(), cleaned_content)
             })
-            .collect();
-        let serialized = serde_json::to_string(&stable_messages).unwrap_or_default();
-        let mut hasher = Sha256::new();
-        hasher.update(serialized.as_bytes());
-        format!("{:x}", hasher.finalize())
+            .collect()
     }
 
     fn load_records(file_path: &str) -> Result<HashMap<String, TestRecord>> {
@@ -173,7 +195,7 @@ impl Provider for TestProvider {
         messages: &[Message],
         tools: &[Tool],
     ) -> Result<MessageStream, ProviderError> {
-        let hash = Self::hash_input(messages);
+        let hash = Self::hash_input(system, messages);
 
         if let Some(inner) = &self.inner {
             // Call inner provider's stream and collect it
@@ -202,7 +224,10 @@ impl Provider for TestProvider {
             Ok(super::base::stream_from_single_message(message, usage))
         } else {
             let records = self.records.lock().unwrap();
-            if let Some(record) = records.get(&hash) {
+            let record = records
+                .get(&hash)
+                .or_else(|| records.get(&Self::legacy_hash_input(messages)));
+            if let Some(record) = record {
                 let message = record.output.message.clone();
                 let usage = record.output.usage.clone();
                 Ok(super::base::stream_from_single_message(message, usage))
@@ -332,6 +357,18 @@ mod tests {
         let _ = fs::remove_file(temp_file);
     }
 
+    #[test]
+    fn test_hash_covers_the_system_prompt_but_not_its_timestamp() {
+        let messages = vec![Message::user().with_text("Grade this")];
+        let agent = TestProvider::hash_input("You are goose. It is 2025-06-01 14:00.", &messages);
+        let later = TestProvider::hash_input("You are goose. It is 2025-06-02 09:00.", &messages);
+        let grader = TestProvider::hash_input("You are a strict grader.", &messages);
+
+        assert_eq!(agent, later);
+        assert_ne!(agent, grader);
+        assert_ne!(agent, TestProvider::legacy_hash_input(&messages));
+    }
+
     #[tokio::test]
     async fn test_replay_missing_record() {
         let temp_file = format!(
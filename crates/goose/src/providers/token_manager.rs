@@ -0,0 +1,389 @@
+//! Keeping the access tokens of providers that sign in with OAuth fresh.
+//!
+//! A [`TokenManager`] holds one sign-in's token and keeps it in the secret backend under
+//! `oauth_token_<name>`. A token asked for within a few minutes of expiring is refreshed first,
+//! with a random jitter on that window so that goose processes sharing a sign-in don't all
+//! refresh it at the same moment, and a token another process already refreshed is picked up
+//! from the secret backend instead. Nothing refreshes a token that isn't being used.
+//! When refreshing fails the token is used until it expires; after that a
+//! [`LifecycleEvent::ReauthRequired`] is published and the provider's sign-in runs again.
+//!
+//! Managers are shared by name, so every session using a sign-in refreshes it once.
+
+use crate::agents::lifecycle::{self, LifecycleEvent};
+use crate::config::{Config, ConfigError};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::Mutex as TokioMutex;
+
+/// Tokens are refreshed this many seconds before they expire, or a fifth of their remaining
+/// life when that is shorter
+const REFRESH_MARGIN_SECS: i64 = 300;
+/// Up to this many seconds more are taken off at random
+const MAX_JITTER_SECS: i64 = 60;
+/// How long to wait before trying again after a failed refresh
+const RETRY_SECS: i64 = 30;
+
+static MANAGERS: LazyLock<Mutex<HashMap<String, Arc<TokenManager>>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthToken {
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Unknown for tokens the server didn't give a lifetime; those are never refreshed early
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Provider-specific details kept with the token, like an id token or an API endpoint
+    #[serde(flatten)]
+    pub extra: HashMap<String, Value>,
+}
+
+impl OAuthToken {
+    pub fn new(
+        access_token: impl Into<String>,
+        refresh_token: Option<String>,
+        expires_in: Option<i64>,
+    ) -> Self {
+        Self {
+            access_token: access_token.into(),
+            refresh_token,
+            expires_at: expires_in.map(|secs| Utc::now() + Duration::seconds(secs)),
+            extra: HashMap::new(),
+        }
+    }
+
+    pub fn with_extra(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.extra.insert(key.to_string(), value.into());
+        self
+    }
+
+    pub fn extra_str(&self, key: &str) -> Option<&str> {
+        self.extra.get(key).and_then(Value::as_str)
+    }
+
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Where a provider's tokens come from
+#[async_trait]
+pub trait TokenSource: Send + Sync {
+    /// A new token in place of `token`, without the user's involvement
+    async fn refresh(&self, token: &OAuthToken) -> Result<OAuthToken>;
+
+    /// A token from signing in from scratch, which usually needs the user
+    async fn sign_in(&self) -> Result<OAuthToken>;
+
+    /// Called once a token is saved in the secret backend, e.g. to remove a legacy copy
+    fn saved(&self) {}
+}
+
+struct Held {
+    token: OAuthToken,
+    refresh_at: Option<DateTime<Utc>>,
+}
+
+impl Held {
+    fn new(token: OAuthToken, now: DateTime<Utc>) -> Self {
+        let refresh_at = refresh_at(&token, now, rand::random());
+        Self { token, refresh_at }
+    }
+
+    fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.refresh_at.is_some_and(|refresh_at| refresh_at <= now)
+    }
+}
+
+/// When to refresh `token`, with `jitter` between 0 and 1 picking how much earlier than the
+/// margin
+fn refresh_at(token: &OAuthToken, now: DateTime<Utc>, jitter: f64) -> Option<DateTime<Utc>> {
+    let expires_at = token.expires_at?;
+    let remaining = (expires_at - now).num_seconds().max(0);
+    let margin = (remaining / 5).min(REFRESH_MARGIN_SECS);
+    let jitter = (jitter.clamp(0.0, 1.0) * (margin / 2).min(MAX_JITTER_SECS) as f64) as i64;
+    Some(expires_at - Duration::seconds(margin + jitter))
+}
+
+pub struct TokenManager {
+    provider: String,
+    secret_key: String,
+    source: Arc<dyn TokenSource>,
+    config: &'static Config,
+    held: TokioMutex<Option<Held>>,
+}
+
+impl std::fmt::Debug for TokenManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenManager")
+            .field("provider", &self.provider)
+            .field("secret_key", &self.secret_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TokenManager {
+    /// The manager for the sign-in called `name`, made with `source` the first time it's asked
+    /// for
+    pub fn shared(
+        provider: &str,
+        name: &str,
+        source: impl FnOnce() -> Arc<dyn TokenSource>,
+    ) -> Arc<Self> {
+        MANAGERS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(Self::new(provider, name, source(), Config::global())))
+            .clone()
+    }
+
+    fn new(
+        provider: &str,
+        name: &str,
+        source: Arc<dyn TokenSource>,
+        config: &'static Config,
+    ) -> Self {
+        Self {
+            provider: provider.to_string(),
+            secret_key: format!("oauth_token_{}", name),
+            source,
+            config,
+            held: TokioMutex::new(None),
+        }
+    }
+
+    pub async fn access_token(&self) -> Result<String> {
+        Ok(self.token().await?.access_token)
+    }
+
+    /// A token that isn't about to expire, refreshing it or signing in again as needed
+    pub async fn token(&self) -> Result<OAuthToken> {
+        let mut held = self.held.lock().await;
+        let now = Utc::now();
+        if held.as_ref().is_none_or(|held| held.is_due(now)) {
+            // Another process may have refreshed it already
+            if let Some(stored) = self.load() {
+                if held.as_ref().is_none_or(|held| held.token != stored) {
+                    *held = Some(Held::new(stored, now));
+                }
+            }
+        }
+
+        if let Some(current) = held.as_mut() {
+            if !current.is_due(now) {
+                return Ok(current.token.clone());
+            }
+            match self.source.refresh(&current.token).await {
+                Ok(token) => {
+                    tracing::debug!("Refreshed the {} token", self.provider);
+                    return Ok(self.hold(&mut held, token));
+                }
+                Err(e) if !current.token.is_expired(now) => {
+                    tracing::warn!(
+                        "Couldn't refresh the {} token, using it until it expires: {}",
+                        self.provider,
+                        e
+                    );
+                    let retry_at = now + Duration::seconds(RETRY_SECS);
+                    current.refresh_at = current
+                        .token
+                        .expires_at
+                        .map(|expires_at| expires_at.min(retry_at));
+                    return Ok(current.token.clone());
+                }
+                Err(e) => {
+                    tracing::warn!("Couldn't refresh the {} token: {}", self.provider, e);
+                    lifecycle::publish(LifecycleEvent::ReauthRequired {
+                        provider: self.provider.clone(),
+                        reason: e.to_string(),
+                    });
+                    *held = None;
+                    self.forget();
+                }
+            }
+        }
+
+        tracing::info!("Signing in to {}", self.provider);
+        let token = self.source.sign_in().await?;
+        Ok(self.hold(&mut held, token))
+    }
+
+    /// Forget the token, so the next request refreshes nothing and signs in again
+    pub async fn invalidate(&self) {
+        *self.held.lock().await = None;
+        self.forget();
+    }
+
+    fn hold(&self, held: &mut Option<Held>, token: OAuthToken) -> OAuthToken {
+        match self.config.set_secret(&self.secret_key, &token) {
+            Ok(()) => self.source.saved(),
+            Err(e) => tracing::warn!("Couldn't save the {} token: {}", self.provider, e),
+        }
+        *held = Some(Held::new(token.clone(), Utc::now()));
+        token
+    }
+
+    fn load(&self) -> Option<OAuthToken> {
+        match self
+            .config
+            .get_stored_secret::<OAuthToken>(&self.secret_key)
+        {
+            Ok(token) => Some(token),
+            Err(ConfigError::NotFound(_)) => None,
+            Err(e) => {
+                tracing::warn!("Couldn't read the saved {} token: {}", self.provider, e);
+                None
+            }
+        }
+    }
+
+    fn forget(&self) {
+        match self.config.delete_secret(&self.secret_key) {
+            Ok(()) | Err(ConfigError::NotFound(_)) => {}
+            Err(e) => tracing::warn!("Couldn't remove the {} token: {}", self.provider, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn tokens_are_refreshed_early_with_jitter() {
+        let now = Utc::now();
+        let token = OAuthToken {
+            expires_at: Some(now + Duration::hours(1)),
+            ..OAuthToken::new("a", None, None)
+        };
+        let expires_at = token.expires_at.unwrap();
+        assert_eq!(
+            refresh_at(&token, now, 0.0),
+            Some(expires_at - Duration::seconds(REFRESH_MARGIN_SECS))
+        );
+        assert_eq!(
+            refresh_at(&token, now, 1.0),
+            Some(expires_at - Duration::seconds(REFRESH_MARGIN_SECS + MAX_JITTER_SECS))
+        );
+
+        // Short-lived tokens keep most of their life
+        let short = OAuthToken {
+            expires_at: Some(now + Duration::seconds(100)),
+            ..token
+        };
+        assert_eq!(
+            refresh_at(&short, now, 1.0),
+            Some(now + Duration::seconds(70))
+        );
+        assert_eq!(
+            refresh_at(&OAuthToken::new("a", None, None), now, 1.0),
+            None
+        );
+    }
+
+    struct FakeSource {
+        refreshes: AtomicUsize,
+        refresh_works: bool,
+    }
+
+    #[async_trait]
+    impl TokenSource for FakeSource {
+        async fn refresh(&self, token: &OAuthToken) -> Result<OAuthToken> {
+            let n = self.refreshes.fetch_add(1, Ordering::SeqCst) + 1;
+            if !self.refresh_works {
+                anyhow::bail!("invalid_grant");
+            }
+            Ok(OAuthToken::new(
+                format!("refreshed-{}", n),
+                token.refresh_token.clone(),
+                Some(3600),
+            ))
+        }
+
+        async fn sign_in(&self) -> Result<OAuthToken> {
+            Ok(OAuthToken::new(
+                "signed-in",
+                Some("r".to_string()),
+                Some(3600),
+            ))
+        }
+    }
+
+    fn manager(refresh_works: bool) -> (TokenManager, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("secrets.yaml"),
+        )
+        .unwrap();
+        let source = Arc::new(FakeSource {
+            refreshes: AtomicUsize::new(0),
+            refresh_works,
+        });
+        let manager = TokenManager::new("test", "test", source, Box::leak(Box::new(config)));
+        (manager, dir)
+    }
+
+    async fn hold_due(manager: &TokenManager, token: OAuthToken) {
+        *manager.held.lock().await = Some(Held {
+            token,
+            refresh_at: Some(Utc::now() - Duration::seconds(1)),
+        });
+    }
+
+    #[tokio::test]
+    async fn tokens_are_refreshed_before_they_expire_and_saved() {
+        let (manager, _dir) = manager(true);
+        hold_due(
+            &manager,
+            OAuthToken::new("old", Some("r".to_string()), Some(60)),
+        )
+        .await;
+
+        assert_eq!(manager.access_token().await.unwrap(), "refreshed-1");
+        assert_eq!(manager.access_token().await.unwrap(), "refreshed-1");
+        assert_eq!(manager.load().unwrap().access_token, "refreshed-1");
+    }
+
+    #[tokio::test]
+    async fn failed_refreshes_ask_to_sign_in_again() {
+        let mut events = lifecycle::subscribe();
+        let (manager, _dir) = manager(false);
+
+        hold_due(
+            &manager,
+            OAuthToken::new("old", Some("r".to_string()), Some(60)),
+        )
+        .await;
+        assert_eq!(manager.access_token().await.unwrap(), "old");
+
+        let expired = OAuthToken::new("older", Some("r".to_string()), Some(-60));
+        manager.invalidate().await;
+        manager
+            .config
+            .set_secret(&manager.secret_key, &expired)
+            .unwrap();
+        assert_eq!(manager.access_token().await.unwrap(), "signed-in");
+
+        // Other tests publish on the same bus
+        loop {
+            if let LifecycleEvent::ReauthRequired { provider, reason } =
+                events.recv().await.unwrap()
+            {
+                if provider == "test" {
+                    assert_eq!(reason, "invalid_grant");
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -21,9 +21,19 @@
 //!
 //! ### Implementations
 //!
-//! The module provides an implementation for Ollama:
+//! The module provides two implementations:
 //!
-//! - `OllamaInterpreter`: Uses Ollama's structured output API to interpret tool calls
+//! - `JsonToolCallExtractor`: Parses the JSON tool calls the model was asked to write straight
+//!   out of its reply, repairing the usual slips in an otherwise complete object (trailing
+//!   commas, smart-quoted keys and strings, Python literals) and matching names against the
+//!   available tools
+//! - `OllamaInterpreter`: Uses Ollama's structured output API to interpret tool calls, with
+//!   the output constrained by a JSON schema that only admits the available tools and their
+//!   arguments
+//!
+//! `GOOSE_TOOLSHIM_INTERPRETER` picks between them: `model` (default) always asks the
+//! interpreter model, `auto` extracts and only asks the interpreter model when the reply looks
+//! like a tool call that could not be parsed, and `extract` never runs a second model.
 //!
 //! ### Helper Functions
 //!
@@ -40,9 +50,9 @@ use crate::providers::formats::openai::create_request;
 use anyhow::Result;
 use reqwest::Client;
 use rmcp::model::{object, CallToolRequestParams, RawContent, Tool};
+use serde::Deserialize;
 use serde_json::{json, Value};
 use std::ops::Deref;
-use std::time::Duration;
 use uuid::Uuid;
 
 /// Default model to use for tool interpretation
@@ -51,6 +61,7 @@ pub const DEFAULT_INTERPRETER_MODEL_OLLAMA: &str = "mistral-nemo";
 /// Environment variables that affect behavior:
 /// - GOOSE_TOOLSHIM: When set to "true" or "1", enables using the tool shim in the standard OllamaProvider (default: false)
 /// - GOOSE_TOOLSHIM_OLLAMA_MODEL: Ollama model to use as the tool interpreter (default: DEFAULT_INTERPRETER_MODEL)
+/// - GOOSE_TOOLSHIM_INTERPRETER: auto, extract or model; see `InterpreterMode` (default: model)
 /// A trait for models that can interpret text into structured tool call JSON format
 #[async_trait::async_trait]
 pub trait ToolInterpreter {
@@ -62,6 +73,240 @@ pub trait ToolInterpreter {
     ) -> Result<Vec<CallToolRequestParams>, ProviderError>;
 }
 
+/// How tool calls are recovered from the reply of a model without native tool calling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterpreterMode {
+    /// Extract the JSON tool calls from the reply, falling back to the interpreter model
+    /// when the reply looks like a tool call that could not be parsed
+    Auto,
+    /// Only extract; never run a second model
+    Extract,
+    /// Always ask the interpreter model
+    #[default]
+    Model,
+}
+
+impl InterpreterMode {
+    pub fn from_config() -> Self {
+        crate::config::Config::global()
+            .get_param::<InterpreterMode>("GOOSE_TOOLSHIM_INTERPRETER")
+            .unwrap_or_default()
+    }
+}
+
+/// Reads tool calls directly from the model's reply, without a second model
+pub struct JsonToolCallExtractor;
+
+#[async_trait::async_trait]
+impl ToolInterpreter for JsonToolCallExtractor {
+    async fn interpret_to_tool_calls(
+        &self,
+        content: &str,
+        tools: &[Tool],
+    ) -> Result<Vec<CallToolRequestParams>, ProviderError> {
+        Ok(extract_tool_calls(content, tools))
+    }
+}
+
+/// Spans of `text` that may hold a JSON object: each top-level `{...}` that is closed. An
+/// object cut off before its closing brace is left out rather than guessed at.
+fn json_candidates(text: &str) -> Vec<&str> {
+    let mut candidates = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' if depth > 0 => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    start = idx;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    candidates.push(&text[start..=idx]);
+                }
+            }
+            _ => {}
+        }
+    }
+    candidates
+}
+
+fn trim_trailing_comma(out: &mut String) {
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    if out.ends_with(',') {
+        out.pop();
+    }
+}
+
+/// Fix the mistakes small models make when writing JSON by hand: smart quotes around keys
+/// and strings, trailing commas and Python's True/False/None. Smart quotes inside a string are
+/// part of its text and kept. Returns None for a truncated or unbalanced object, which is
+/// never completed by guessing.
+fn repair_json(candidate: &str) -> Option<String> {
+    let mut out = String::with_capacity(candidate.len() + 8);
+    let mut closers = Vec::new();
+    // The quote the open string started with
+    let mut string: Option<char> = None;
+    let mut escaped = false;
+    let mut chars = candidate.chars().peekable();
+    while let Some(c) = chars.next() {
+        if let Some(open) = string {
+            if escaped {
+                escaped = false;
+                out.push(c);
+                continue;
+            }
+            match c {
+                '\\' => escaped = true,
+                '"' if open == '"' => string = None,
+                '"' => {
+                    out.push_str("\\\"");
+                    continue;
+                }
+                '\u{201c}' | '\u{201d}' if open != '"' => {
+                    string = None;
+                    out.push('"');
+                    continue;
+                }
+                _ => {}
+            }
+            out.push(c);
+            continue;
+        }
+        match c {
+            '"' => string = Some('"'),
+            '\u{201c}' | '\u{201d}' => {
+                string = Some(c);
+                out.push('"');
+                continue;
+            }
+            '{' => closers.push('}'),
+            '[' => closers.push(']'),
+            '}' | ']' => {
+                trim_trailing_comma(&mut out);
+                if closers.pop() != Some(c) {
+                    return None;
+                }
+            }
+            c if c.is_ascii_alphabetic() => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !next.is_ascii_alphanumeric() {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                out.push_str(match word.as_str() {
+                    "True" => "true",
+                    "False" => "false",
+                    "None" => "null",
+                    _ => &word,
+                });
+                continue;
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    (string.is_none() && closers.is_empty()).then_some(out)
+}
+
+fn parse_candidate(candidate: &str) -> Option<Value> {
+    serde_json::from_str(candidate)
+        .ok()
+        .or_else(|| serde_json::from_str(&repair_json(candidate)?).ok())
+}
+
+/// The available tool a model meant by `name`: an exact match, or the one tool whose
+/// name ends with `__name` when the model left off the extension prefix
+fn resolve_tool_name(name: &str, tools: &[Tool]) -> Option<String> {
+    if tools.iter().any(|tool| tool.name == name) {
+        return Some(name.to_string());
+    }
+    let suffix = format!("__{}", name);
+    let mut matches = tools.iter().filter(|tool| tool.name.ends_with(&suffix));
+    match (matches.next(), matches.next()) {
+        (Some(tool), None) => Some(tool.name.to_string()),
+        _ => None,
+    }
+}
+
+fn collect_tool_calls(value: &Value, tools: &[Tool], calls: &mut Vec<CallToolRequestParams>) {
+    if let Some(items) = value.as_array() {
+        for item in items {
+            collect_tool_calls(item, tools, calls);
+        }
+        return;
+    }
+    if let Some(items) = value.get("tool_calls") {
+        collect_tool_calls(items, tools, calls);
+        return;
+    }
+
+    let function = value.get("function").unwrap_or(value);
+    let Some(name) = function
+        .get("name")
+        .or_else(|| value.get("tool"))
+        .and_then(Value::as_str)
+    else {
+        return;
+    };
+    let Some(name) = resolve_tool_name(name, tools) else {
+        tracing::debug!("Ignoring a tool call to unknown tool {}", name);
+        return;
+    };
+    let arguments = ["arguments", "parameters", "args", "input"]
+        .iter()
+        .find_map(|key| function.get(*key))
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+    let arguments = match arguments {
+        Value::String(text) => parse_candidate(&text).unwrap_or(Value::Null),
+        other => other,
+    };
+    if !arguments.is_object() {
+        return;
+    }
+    calls.push(CallToolRequestParams::new(name).with_arguments(object(arguments)));
+}
+
+/// The tool calls written as JSON in a model's reply
+pub fn extract_tool_calls(text: &str, tools: &[Tool]) -> Vec<CallToolRequestParams> {
+    let mut calls = Vec::new();
+    for candidate in json_candidates(text) {
+        if let Some(value) = parse_candidate(candidate) {
+            collect_tool_calls(&value, tools, &mut calls);
+        }
+    }
+    calls
+}
+
+/// Whether a reply seems to attempt a tool call, so finding none is worth a second look
+fn looks_like_tool_call(text: &str) -> bool {
+    text.contains('{')
+        && ["\"name\"", "\"arguments\"", "\"tool_calls\"", "\"tool\""]
+            .iter()
+            .any(|key| text.contains(key))
+}
+
 /// Ollama-specific implementation of the ToolInterpreter trait
 pub struct OllamaInterpreter {
     client: Client,
@@ -70,10 +315,8 @@ pub struct OllamaInterpreter {
 
 impl OllamaInterpreter {
     pub fn new() -> Result<Self, ProviderError> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(600))
-            .build()
-            .expect("Failed to create HTTP client");
+        let client = super::http_client::shared_client()
+            .map_err(|e| ProviderError::ExecutionError(e.to_string()))?;
 
         let base_url = Self::get_ollama_base_url()?;
 
@@ -113,26 +356,36 @@ impl OllamaInterpreter {
         Ok(base_url.to_string())
     }
 
-    fn tool_structured_output_format_schema() -> Value {
+    /// Output schema that only admits calls to `tools` with their own argument schemas, so
+    /// the grammar Ollama derives from it rules out unknown tools and malformed arguments
+    fn tool_structured_output_format_schema(tools: &[Tool]) -> Value {
+        let mut calls: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "const": tool.name },
+                        "arguments": Value::Object(tool.input_schema.as_ref().clone())
+                    },
+                    "required": ["name", "arguments"]
+                })
+            })
+            .collect();
+        calls.push(json!({
+            "type": "object",
+            "properties": {
+                "name": { "const": "noop" },
+                "arguments": { "type": "object" }
+            },
+            "required": ["name", "arguments"]
+        }));
         json!({
             "type": "object",
             "properties": {
                 "tool_calls": {
                     "type": "array",
-                    "items": {
-                        "type": "object",
-                        "properties": {
-                            "name": {
-                                "type": "string",
-                                "description": "The name of the tool to call"
-                            },
-                            "arguments": {
-                                "type": "object",
-                                "description": "The arguments to pass to the tool"
-                            }
-                        },
-                        "required": ["name", "arguments"]
-                    }
+                    "items": { "anyOf": calls }
                 }
             },
             "required": ["tool_calls"]
@@ -281,7 +534,7 @@ Otherwise, if no JSON tool requests are provided, use the no-op tool:
         let format_instruction = format!("{}\nRequest: {}\n\n", system_prompt, last_assistant_msg);
 
         // Define the JSON schema for tool call format
-        let format_schema = OllamaInterpreter::tool_structured_output_format_schema();
+        let format_schema = OllamaInterpreter::tool_structured_output_format_schema(tools);
 
         // Determine which model to use for interpretation (from env var or default)
         let interpreter_model = std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL")
@@ -444,3 +697,111 @@ pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
 
     Ok(final_message)
 }
+
+/// Augment a message with tool calls, using the interpreter `GOOSE_TOOLSHIM_INTERPRETER`
+/// selects
+pub async fn augment_message_with_configured_interpreter(
+    message: Message,
+    tools: &[Tool],
+) -> Result<Message, ProviderError> {
+    let mode = InterpreterMode::from_config();
+    if mode != InterpreterMode::Model {
+        let augmented =
+            augment_message_with_tool_calls(&JsonToolCallExtractor, message.clone(), tools).await?;
+        let extracted = augmented
+            .content
+            .iter()
+            .any(|content| matches!(content, MessageContent::ToolRequest(_)));
+        if extracted
+            || mode == InterpreterMode::Extract
+            || !looks_like_tool_call(&message.as_concat_text())
+        {
+            return Ok(augmented);
+        }
+        tracing::debug!("No tool call could be extracted, asking the interpreter model");
+    }
+
+    let interpreter = OllamaInterpreter::new()?;
+    augment_message_with_tool_calls(&interpreter, message, tools).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_case::test_case;
+
+    fn tools() -> Vec<Tool> {
+        [
+            "developer__shell",
+            "developer__text_editor",
+            "memory__shell",
+        ]
+        .into_iter()
+        .map(|name| {
+            Tool::new(
+                name.to_string(),
+                String::new(),
+                rmcp::object!({ "type": "object" }),
+            )
+        })
+        .collect()
+    }
+
+    #[test_case(r#"{"name": "developer__shell", "arguments": {"command": "ls"}}"# ; "plain")]
+    #[test_case("Running it:\n```json\n{\"name\": \"developer__shell\", \"arguments\": {\"command\": \"ls\",},}\n```" ; "fenced with trailing commas")]
+    #[test_case("{\u{201c}name\u{201d}: \u{201c}developer__shell\u{201d}, \u{201c}arguments\u{201d}: {\u{201c}command\u{201d}: \u{201c}ls\u{201d}}}" ; "smart quotes")]
+    #[test_case(r#"{"tool_calls": [{"function": {"name": "developer__shell", "arguments": "{\"command\": \"ls\"}"}}]}"# ; "openai shape")]
+    fn extracts_a_shell_call(reply: &str) {
+        let calls = extract_tool_calls(reply, &tools());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "developer__shell");
+        assert_eq!(calls[0].arguments.as_ref().unwrap()["command"], "ls");
+    }
+
+    #[test]
+    fn repairs_python_literals() {
+        assert_eq!(
+            repair_json(r#"{"a": True, "b": None, "c": "True"}"#).as_deref(),
+            Some(r#"{"a": true, "b": null, "c": "True"}"#)
+        );
+    }
+
+    #[test]
+    fn keeps_smart_quotes_inside_strings() {
+        let reply = "{\"name\": \"developer__shell\", \"arguments\": {\"command\": \"echo \u{201c}hi\u{201d}\",}}";
+        let calls = extract_tool_calls(reply, &tools());
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            calls[0].arguments.as_ref().unwrap()["command"],
+            "echo \u{201c}hi\u{201d}"
+        );
+    }
+
+    #[test]
+    fn truncated_calls_are_not_completed() {
+        let reply = r#"{"name": "developer__shell", "arguments": {"command": "rm -rf /tmp/x"#;
+        assert!(extract_tool_calls(reply, &tools()).is_empty());
+        assert_eq!(repair_json(r#"{"a": [1, 2}"#), None);
+        assert_eq!(repair_json(r#"{"a": "b}"#), None);
+    }
+
+    #[test]
+    fn resolves_names_without_the_extension_prefix_only_when_unambiguous() {
+        assert_eq!(
+            resolve_tool_name("text_editor", &tools()).as_deref(),
+            Some("developer__text_editor")
+        );
+        assert_eq!(resolve_tool_name("shell", &tools()), None);
+        assert_eq!(resolve_tool_name("noop", &tools()), None);
+    }
+
+    #[test]
+    fn structured_output_schema_only_admits_known_tools() {
+        let schema = OllamaInterpreter::tool_structured_output_format_schema(&tools());
+        let calls = schema["properties"]["tool_calls"]["items"]["anyOf"]
+            .as_array()
+            .unwrap();
+        assert_eq!(calls.len(), 4);
+        assert_eq!(calls[0]["properties"]["name"]["const"], "developer__shell");
+    }
+}
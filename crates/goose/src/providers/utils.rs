@@ -161,6 +161,9 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
         StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND => {
             let mut error_msg = "Unknown error".to_string();
             if let Some(payload) = &payload {
+                if let Some(error) = ProviderError::from_error_payload(payload) {
+                    return Err(error);
+                }
                 if let Some(error) = payload.get("error") {
                     error_msg = error.get("message").and_then(|m| m.as_str()).unwrap_or("Unknown error").to_string();
                     let error_status = error.get("status").and_then(|s| s.as_str()).unwrap_or("Unknown status");
@@ -181,6 +184,9 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
                 retry_delay,
             })
         }
+        StatusCode::SERVICE_UNAVAILABLE => Err(ProviderError::Overloaded(
+            format_server_error_message(final_status, payload.as_ref()),
+        )),
         _ if final_status.is_server_error() => Err(ProviderError::ServerError(
             format_server_error_message(final_status, payload.as_ref()),
         )),
@@ -148,11 +148,53 @@ where
             let file_path = param_map.get(&param.key).unwrap();
             let file_content = read_parameter_file_content(file_path)?;
             param_map.insert(param.key.clone(), file_content);
+            continue;
+        }
+        if let Some(value) = param_map.get(&param.key) {
+            validate_parameter_value(&param, value)?;
         }
     }
     Ok((param_map, missing_params))
 }
 
+/// Check a parameter value against the parameter's declared type
+fn validate_parameter_value(param: &RecipeParameter, value: &str) -> Result<()> {
+    if value.is_empty() && matches!(param.requirement, RecipeParameterRequirement::Optional) {
+        return Ok(());
+    }
+    let expected = match &param.input_type {
+        RecipeParameterInputType::String | RecipeParameterInputType::File => return Ok(()),
+        RecipeParameterInputType::Number if value.trim().parse::<f64>().is_ok() => return Ok(()),
+        RecipeParameterInputType::Number => "a number".to_string(),
+        RecipeParameterInputType::Boolean
+            if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") =>
+        {
+            return Ok(())
+        }
+        RecipeParameterInputType::Boolean => "true or false".to_string(),
+        RecipeParameterInputType::Date
+            if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+                || chrono::DateTime::parse_from_rfc3339(value).is_ok() =>
+        {
+            return Ok(())
+        }
+        RecipeParameterInputType::Date => "a date (YYYY-MM-DD)".to_string(),
+        RecipeParameterInputType::Select => {
+            let options = param.options.as_deref().unwrap_or_default();
+            if options.iter().any(|option| option == value) {
+                return Ok(());
+            }
+            format!("one of {}", options.join(", "))
+        }
+    };
+    Err(anyhow::anyhow!(
+        "Invalid value '{}' for parameter '{}': expected {}",
+        value,
+        param.key,
+        expected
+    ))
+}
+
 pub fn resolve_sub_recipe_path(
     sub_recipe_path: &str,
     parent_recipe_dir: &Path,
@@ -296,6 +296,60 @@ fn test_build_recipe_from_template_wrong_input_type_in_recipe_file() {
     }
 }
 
+#[test]
+fn test_build_recipe_from_template_checks_parameter_types() {
+    let instructions_and_parameters = r#"
+                "instructions": "Deploy to {{ env }} with {{ replicas }} replicas",
+                "parameters": [
+                    {
+                        "key": "env",
+                        "input_type": "select",
+                        "requirement": "required",
+                        "description": "Target environment",
+                        "options": ["staging", "production"]
+                    },
+                    {
+                        "key": "replicas",
+                        "input_type": "number",
+                        "requirement": "optional",
+                        "description": "Replica count",
+                        "default": "2"
+                    }
+                ]"#;
+    let (_temp_dir, recipe_content, recipe_dir) = setup_recipe_file(instructions_and_parameters);
+    let build = |params: &[(&str, &str)]| {
+        build_recipe_from_template(
+            recipe_content.clone(),
+            &recipe_dir,
+            params
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            NO_USER_PROMPT,
+        )
+    };
+
+    let recipe = build(&[("env", "staging")]).unwrap();
+    assert_eq!(
+        recipe.instructions.unwrap(),
+        "Deploy to staging with 2 replicas"
+    );
+
+    match build(&[("env", "qa")]).unwrap_err() {
+        RecipeError::Invalid { source } => assert_eq!(
+            source.to_string(),
+            "Invalid value 'qa' for parameter 'env': expected one of staging, production"
+        ),
+        err => panic!("Expected Invalid error, got: {:?}", err),
+    }
+    match build(&[("env", "staging"), ("replicas", "many")]).unwrap_err() {
+        RecipeError::Invalid { source } => {
+            assert!(source.to_string().contains("expected a number"))
+        }
+        err => panic!("Expected Invalid error, got: {:?}", err),
+    }
+}
+
 #[test]
 fn test_build_recipe_from_template_success_without_parameters() {
     let instructions_and_parameters = r#"
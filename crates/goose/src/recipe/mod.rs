@@ -5,7 +5,10 @@ use std::fmt;
 use std::path::Path;
 
 use crate::agents::extension::ExtensionConfig;
+use crate::agents::platform_extensions::developer::sandbox::SandboxConfig;
+use crate::agents::prompt_layout::PromptLayout;
 use crate::agents::types::RetryConfig;
+use crate::context_mgmt::recovery::ContextRecovery;
 use crate::recipe::read_recipe_file_content::read_recipe_file;
 use crate::recipe::yaml_format_utils::reformat_fields_with_multiline_values;
 use crate::utils::contains_unicode_tags;
@@ -83,6 +86,9 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub retry: Option<RetryConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success_criteria: Option<Vec<String>>, // what must hold for the task to count as done
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -107,6 +113,23 @@ pub struct Settings {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_turns: Option<usize>,
+
+    /// Run developer shell and file tools inside an ephemeral container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+
+    /// Permission profile from `GOOSE_PERMISSION_PROFILES` to apply to sessions from this recipe
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_profile: Option<String>,
+
+    /// How to arrange the system prompt sections, on top of `GOOSE_PROMPT_SECTIONS`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_sections: Option<PromptLayout>,
+
+    /// How to recover when a request exceeds the context window, on top of
+    /// `GOOSE_CONTEXT_RECOVERY`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_recovery: Option<ContextRecovery>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
@@ -223,6 +246,7 @@ pub struct RecipeBuilder {
     response: Option<Response>,
     sub_recipes: Option<Vec<SubRecipe>>,
     retry: Option<RetryConfig>,
+    success_criteria: Option<Vec<String>>,
 }
 
 impl Recipe {
@@ -279,6 +303,15 @@ impl Recipe {
             return true;
         }
 
+        if let Some(criteria) = &self.success_criteria {
+            if criteria
+                .iter()
+                .any(|criterion| contains_unicode_tags(criterion))
+            {
+                return true;
+            }
+        }
+
         if let Some(activities) = &self.activities {
             return activities
                 .iter()
@@ -288,6 +321,26 @@ impl Recipe {
         false
     }
 
+    /// The system instructions to run the recipe with, followed by its success criteria.
+    /// Frontends use this rather than `instructions` so every one of them runs a recipe alike.
+    pub fn system_instructions(&self) -> Option<String> {
+        let criteria = self.success_criteria.as_deref().unwrap_or_default();
+        if criteria.is_empty() {
+            return self.instructions.clone();
+        }
+
+        let mut instructions = self.instructions.clone().unwrap_or_default();
+        if !instructions.is_empty() {
+            instructions.push_str("\n\n");
+        }
+        instructions.push_str("The task is only complete when all of the following hold:");
+        for criterion in criteria {
+            instructions.push_str("\n- ");
+            instructions.push_str(criterion);
+        }
+        Some(instructions)
+    }
+
     pub fn to_yaml(&self) -> Result<String> {
         let recipe_yaml = serde_yaml::to_string(self)
             .map_err(|err| anyhow::anyhow!("Failed to serialize recipe: {}", err))?;
@@ -311,6 +364,7 @@ impl Recipe {
             response: None,
             sub_recipes: None,
             retry: None,
+            success_criteria: None,
         }
     }
 
@@ -406,6 +460,11 @@ impl RecipeBuilder {
         self
     }
 
+    pub fn success_criteria(mut self, success_criteria: Vec<String>) -> Self {
+        self.success_criteria = Some(success_criteria);
+        self
+    }
+
     pub fn build(self) -> Result<Recipe, &'static str> {
         let title = self.title.ok_or("Title is required")?;
         let description = self.description.ok_or("Description is required")?;
@@ -428,6 +487,7 @@ impl RecipeBuilder {
             response: self.response,
             sub_recipes: self.sub_recipes,
             retry: self.retry,
+            success_criteria: self.success_criteria,
         })
     }
 }
@@ -754,6 +814,37 @@ isGlobal: true"#;
         assert_eq!(extensions.len(), 0);
     }
 
+    #[test]
+    fn test_system_instructions_include_success_criteria() {
+        let recipe = Recipe::builder()
+            .title("Deploy")
+            .description("Deploy the service")
+            .instructions("Deploy the service.")
+            .build()
+            .unwrap();
+        assert_eq!(
+            recipe.system_instructions().as_deref(),
+            Some("Deploy the service.")
+        );
+
+        let recipe = Recipe::builder()
+            .title("Deploy")
+            .description("Deploy the service")
+            .prompt("Deploy it")
+            .success_criteria(vec![
+                "the health check passes".to_string(),
+                "no errors are logged".to_string(),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(
+            recipe.system_instructions().unwrap(),
+            "The task is only complete when all of the following hold:\n\
+             - the health check passes\n\
+             - no errors are logged"
+        );
+    }
+
     #[test]
     fn test_check_for_security_warnings() {
         let mut recipe = Recipe {
@@ -770,6 +861,7 @@ isGlobal: true"#;
             response: None,
             sub_recipes: None,
             retry: None,
+            success_criteria: None,
         };
 
         assert!(!recipe.check_for_security_warnings());
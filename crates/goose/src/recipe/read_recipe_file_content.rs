@@ -24,6 +24,9 @@ pub fn read_recipe_file<P: AsRef<Path>>(recipe_path: P) -> Result<RecipeFile> {
         )
     })?;
 
+    // Recipes can declare extensions and commands, so project-provided ones must be trusted.
+    crate::security::signing::check_project_file(&canonical, content.as_bytes(), "recipe")?;
+
     let parent_dir = canonical
         .parent()
         .ok_or_else(|| anyhow!("Resolved path has no parent: {}", canonical.display()))?
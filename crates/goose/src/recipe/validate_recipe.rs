@@ -45,6 +45,7 @@ pub fn validate_recipe_template_from_content(
 
     validate_prompt_or_instructions(&recipe)?;
     validate_retry_config(&recipe)?;
+    validate_success_criteria(&recipe)?;
     if let Some(response) = &recipe.response {
         if let Some(json_schema) = &response.json_schema {
             validate_json_schema(json_schema)?;
@@ -66,6 +67,15 @@ fn validate_retry_config(recipe: &Recipe) -> Result<()> {
     Ok(())
 }
 
+fn validate_success_criteria(recipe: &Recipe) -> Result<()> {
+    if let Some(criteria) = &recipe.success_criteria {
+        if criteria.iter().any(|criterion| criterion.trim().is_empty()) {
+            return Err(anyhow::anyhow!("Success criteria must not be empty"));
+        }
+    }
+    Ok(())
+}
+
 fn validate_prompt_or_instructions(recipe: &Recipe) -> Result<()> {
     let has_instructions = recipe
         .instructions
@@ -0,0 +1,398 @@
+//! A directory of documents on disk.
+//!
+//! Text and markdown files are split into passages at paragraph breaks. The index refreshes
+//! on a blocking thread before a lookup, at most every few seconds, re-reading only files whose
+//! size or modification time changed, and keeps passage embeddings by content hash, so
+//! unchanged documents are embedded once per process. A lookup embeds a bounded number of new
+//! passages, so a large knowledge base is embedded over several lookups rather than holding up
+//! the first. Without embeddings, passages are ranked by how many of the query's terms they
+//! contain.
+
+use super::{Document, Query, Retriever};
+use crate::conversation::message::CitationSource;
+use crate::providers::base::Provider;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+const EXTENSIONS: &[&str] = &["md", "markdown", "mdx", "txt", "rst", "adoc", "org"];
+const MAX_FILES: usize = 10_000;
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Passages end at the first paragraph break after this many characters
+const PASSAGE_CHARS: usize = 1_200;
+const EMBEDDING_BATCH: usize = 64;
+/// Most passages embedded for one lookup
+const MAX_EMBEDDED_PER_LOOKUP: usize = 8 * EMBEDDING_BATCH;
+/// An index refreshed this recently is used as it is
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+/// Passages less similar to the query than this aren't relevant
+const MIN_SIMILARITY: f32 = 0.3;
+/// Share of the query's terms a passage needs when ranking by keyword
+const MIN_KEYWORD_SHARE: f32 = 0.5;
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "what", "how", "why", "when", "where", "which", "who", "this",
+    "that", "does", "from", "are", "you", "can", "about", "have", "into", "our", "there",
+];
+
+static INDEXES: LazyLock<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<LocalIndex>>>>> =
+    LazyLock::new(Default::default);
+
+#[derive(Debug, Clone)]
+struct Passage {
+    start_line: usize,
+    end_line: usize,
+    text: String,
+    hash: blake3::Hash,
+}
+
+#[derive(Debug)]
+struct IndexedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// The file's first markdown heading
+    title: Option<String>,
+    passages: Vec<Passage>,
+}
+
+/// The passages of the documents under one directory
+pub struct LocalIndex {
+    root: PathBuf,
+    files: HashMap<PathBuf, IndexedFile>,
+    embeddings: HashMap<blake3::Hash, Vec<f32>>,
+    /// The provider the embeddings came from, as another one's don't compare
+    embedded_with: Option<String>,
+    refreshed_at: Option<Instant>,
+}
+
+impl LocalIndex {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            files: HashMap::new(),
+            embeddings: HashMap::new(),
+            embedded_with: None,
+            refreshed_at: None,
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.refreshed_at
+            .is_none_or(|refreshed_at| refreshed_at.elapsed() >= REFRESH_INTERVAL)
+    }
+
+    /// Bring the index up to date with the files on disk
+    pub fn refresh(&mut self) {
+        let mut seen = HashSet::new();
+        let entries = WalkBuilder::new(&self.root)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            })
+            .take(MAX_FILES);
+        for entry in entries {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.len() > MAX_FILE_BYTES {
+                continue;
+            }
+            let path = entry.path();
+            let relative = path.strip_prefix(&self.root).unwrap_or(path).to_path_buf();
+            seen.insert(relative.clone());
+
+            let modified = metadata.modified().ok();
+            if self
+                .files
+                .get(&relative)
+                .is_some_and(|file| file.modified == modified && file.len == metadata.len())
+            {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let title = text
+                .lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string());
+            self.files.insert(
+                relative,
+                IndexedFile {
+                    modified,
+                    len: metadata.len(),
+                    title,
+                    passages: split_passages(&text),
+                },
+            );
+        }
+        self.files.retain(|path, _| seen.contains(path));
+
+        let live: HashSet<blake3::Hash> = self
+            .files
+            .values()
+            .flat_map(|file| file.passages.iter().map(|passage| passage.hash))
+            .collect();
+        self.embeddings.retain(|hash, _| live.contains(hash));
+        self.refreshed_at = Some(Instant::now());
+    }
+
+    /// Embed up to [`MAX_EMBEDDED_PER_LOOKUP`] of the passages that do not have an embedding
+    /// from `provider` yet
+    pub async fn embed_pending(&mut self, provider: &dyn Provider, session_id: &str) -> Result<()> {
+        if self.embedded_with.as_deref() != Some(provider.get_name()) {
+            self.embeddings.clear();
+            self.embedded_with = Some(provider.get_name().to_string());
+        }
+        let mut pending: Vec<(blake3::Hash, String)> = Vec::new();
+        let mut queued = HashSet::new();
+        for passage in self.files.values().flat_map(|file| &file.passages) {
+            if pending.len() == MAX_EMBEDDED_PER_LOOKUP {
+                break;
+            }
+            if !self.embeddings.contains_key(&passage.hash) && queued.insert(passage.hash) {
+                pending.push((passage.hash, passage.text.clone()));
+            }
+        }
+        for batch in pending.chunks(EMBEDDING_BATCH) {
+            let texts = batch.iter().map(|(_, text)| text.clone()).collect();
+            let vectors = provider.create_embeddings(session_id, texts).await?;
+            for ((hash, _), vector) in batch.iter().zip(vectors) {
+                self.embeddings.insert(*hash, vector);
+            }
+        }
+        Ok(())
+    }
+
+    /// Passages close enough to the query embedding, closest first
+    fn semantic_search(&self, source: &str, query: &[f32], limit: usize) -> Vec<Document> {
+        self.rank(source, limit, |passage| {
+            self.embeddings
+                .get(&passage.hash)
+                .map(|embedding| cosine_similarity(query, embedding))
+                .filter(|score| *score >= MIN_SIMILARITY)
+        })
+    }
+
+    /// Passages holding enough of the query's terms, most first
+    fn keyword_search(&self, source: &str, query: &str, limit: usize) -> Vec<Document> {
+        let terms: HashSet<String> = query
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .filter(|term| term.chars().count() > 2 && !STOPWORDS.contains(&term.as_str()))
+            .collect();
+        if terms.is_empty() {
+            return Vec::new();
+        }
+        self.rank(source, limit, |passage| {
+            let text = passage.text.to_lowercase();
+            let matched = terms
+                .iter()
+                .filter(|term| text.contains(term.as_str()))
+                .count();
+            Some(matched as f32 / terms.len() as f32).filter(|share| *share >= MIN_KEYWORD_SHARE)
+        })
+    }
+
+    fn rank(
+        &self,
+        source: &str,
+        limit: usize,
+        score: impl Fn(&Passage) -> Option<f32>,
+    ) -> Vec<Document> {
+        let mut hits: Vec<(&PathBuf, &IndexedFile, &Passage, f32)> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| file.passages.iter().map(move |p| (path, file, p)))
+            .filter_map(|(path, file, passage)| {
+                score(passage).map(|score| (path, file, passage, score))
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            b.3.total_cmp(&a.3)
+                .then_with(|| a.0.cmp(b.0))
+                .then(a.2.start_line.cmp(&b.2.start_line))
+        });
+        hits.into_iter()
+            .take(limit)
+            .map(|(path, file, passage, score)| Document {
+                source: source.to_string(),
+                title: file.title.clone(),
+                text: passage.text.clone(),
+                score,
+                location: Some(CitationSource::File {
+                    path: self.root.join(path).display().to_string(),
+                    start_line: Some(passage.start_line as u32),
+                    end_line: Some(passage.end_line as u32),
+                }),
+            })
+            .collect()
+    }
+}
+
+/// Split a document into passages of whole paragraphs, cutting long paragraphs too
+fn split_passages(text: &str) -> Vec<Passage> {
+    let mut passages = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    let mut start_line = 1;
+    let mut chars = 0;
+    let mut finish = |lines: &mut Vec<&str>, start_line: usize| {
+        let text = lines.join("\n");
+        if !text.trim().is_empty() {
+            passages.push(Passage {
+                start_line,
+                end_line: start_line + lines.len() - 1,
+                hash: blake3::hash(text.as_bytes()),
+                text,
+            });
+        }
+        lines.clear();
+    };
+    for (index, line) in text.lines().enumerate() {
+        if lines.is_empty() {
+            start_line = index + 1;
+        }
+        lines.push(line);
+        chars += line.chars().count() + 1;
+        let paragraph_break = line.trim().is_empty() && chars >= PASSAGE_CHARS;
+        if paragraph_break || chars >= 2 * PASSAGE_CHARS {
+            finish(&mut lines, start_line);
+            chars = 0;
+        }
+    }
+    if !lines.is_empty() {
+        finish(&mut lines, start_line);
+    }
+    passages
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+fn index_for(root: &Path) -> Arc<tokio::sync::Mutex<LocalIndex>> {
+    let mut indexes = INDEXES.lock().unwrap_or_else(|e| e.into_inner());
+    indexes
+        .entry(root.to_path_buf())
+        .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(LocalIndex::new(root))))
+        .clone()
+}
+
+pub struct LocalRetriever {
+    name: String,
+    root: PathBuf,
+}
+
+impl LocalRetriever {
+    pub fn new(name: &str, root: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            root,
+        }
+    }
+}
+
+#[async_trait]
+impl Retriever for LocalRetriever {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn retrieve(&self, query: &Query<'_>) -> Result<Vec<Document>> {
+        if !self.root.is_dir() {
+            bail!("{} is not a directory", self.root.display());
+        }
+        let mut index = index_for(&self.root).lock_owned().await;
+        if index.is_stale() {
+            // Walking and reading the directory blocks
+            index = tokio::task::spawn_blocking(move || {
+                index.refresh();
+                index
+            })
+            .await?;
+        }
+
+        let Some(provider) = query.embedder else {
+            return Ok(index.keyword_search(&self.name, query.text, query.limit));
+        };
+        let embedded = async {
+            index.embed_pending(provider, query.session_id).await?;
+            provider
+                .create_embeddings(query.session_id, vec![query.text.to_string()])
+                .await?
+                .pop()
+                .ok_or_else(|| anyhow!("The provider returned no embedding"))
+        }
+        .await;
+        Ok(match embedded {
+            Ok(vector) => index.semantic_search(&self.name, &vector, query.limit),
+            Err(e) => {
+                tracing::warn!("Failed to embed knowledge base {}: {}", self.name, e);
+                index.keyword_search(&self.name, query.text, query.limit)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn passages_end_at_paragraph_breaks() {
+        let paragraph = "word ".repeat(PASSAGE_CHARS / 10);
+        let text = format!("# Title\n{p}\n{p}\n\n{p}\n", p = paragraph);
+        let passages = split_passages(&text);
+
+        assert_eq!(passages.len(), 2);
+        assert_eq!((passages[0].start_line, passages[0].end_line), (1, 4));
+        assert_eq!((passages[1].start_line, passages[1].end_line), (5, 5));
+        assert_eq!(passages[1].text, paragraph);
+    }
+
+    #[test]
+    fn keyword_search_needs_most_of_the_terms() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("deploy.md"),
+            "# Deploying\n\nReleases deploy nightly from the main branch.\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("team.txt"), "The team meets weekly.\n").unwrap();
+        fs::write(dir.path().join("notes.rs"), "// releases deploy nightly\n").unwrap();
+        let mut index = LocalIndex::new(dir.path());
+        index.refresh();
+
+        let found = index.keyword_search("handbook", "When do releases deploy?", 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title.as_deref(), Some("Deploying"));
+        assert_eq!(
+            found[0].location,
+            Some(CitationSource::File {
+                path: dir.path().join("deploy.md").display().to_string(),
+                start_line: Some(1),
+                end_line: Some(3),
+            })
+        );
+        assert!(index
+            .keyword_search("handbook", "What does the weekly report say?", 5)
+            .is_empty());
+    }
+}
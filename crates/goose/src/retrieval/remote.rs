@@ -0,0 +1,116 @@
+//! A search endpoint in front of a knowledge base.
+
+use super::{Document, Query, Retriever};
+use crate::conversation::message::CitationSource;
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    documents: Vec<RemoteDocument>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteDocument {
+    text: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    score: Option<f32>,
+}
+
+pub struct RemoteRetriever {
+    name: String,
+    url: String,
+    api_key: Option<String>,
+    client: Client,
+}
+
+impl RemoteRetriever {
+    pub fn new(name: &str, url: &str, api_key: Option<String>) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            url: url.to_string(),
+            api_key,
+            client: Client::builder().timeout(REQUEST_TIMEOUT).build()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Retriever for RemoteRetriever {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn retrieve(&self, query: &Query<'_>) -> Result<Vec<Document>> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .json(&json!({"query": query.text, "limit": query.limit}));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response: SearchResponse = request.send().await?.error_for_status()?.json().await?;
+        Ok(documents(&self.name, response))
+    }
+}
+
+fn documents(source: &str, response: SearchResponse) -> Vec<Document> {
+    response
+        .documents
+        .into_iter()
+        .filter(|document| !document.text.trim().is_empty())
+        .map(|document| Document {
+            source: source.to_string(),
+            location: document.url.map(|url| CitationSource::Url {
+                url,
+                title: document.title.clone(),
+            }),
+            title: document.title,
+            text: document.text,
+            score: document.score.unwrap_or_default(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoint_documents_cite_their_url() {
+        let response: SearchResponse = serde_json::from_value(json!({"documents": [
+            {"text": "Deploys run nightly.", "title": "Deploying",
+             "url": "https://wiki.example/deploy", "score": 0.8},
+            {"text": "Untitled and unlinked"},
+            {"text": "  "},
+        ]}))
+        .unwrap();
+        let found = documents("wiki", response);
+
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            found[0].location,
+            Some(CitationSource::Url {
+                url: "https://wiki.example/deploy".to_string(),
+                title: Some("Deploying".to_string()),
+            })
+        );
+        assert_eq!(
+            found[0].label(),
+            "wiki: Deploying (https://wiki.example/deploy)"
+        );
+        assert_eq!(found[1].location, None);
+        assert_eq!(found[1].score, 0.0);
+    }
+}
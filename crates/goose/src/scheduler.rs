@@ -7,21 +7,29 @@ use std::sync::Arc;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Local, Utc};
+use rmcp::model::Role;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 use tokio_util::sync::CancellationToken;
 
+use crate::agents::extension_manager::ToolSetSnapshot;
 use crate::agents::AgentEvent;
 use crate::agents::{Agent, SessionConfig};
 use crate::config::paths::Paths;
 use crate::config::{resolve_extensions_for_new_session, Config};
 use crate::conversation::message::Message;
 use crate::conversation::Conversation;
+use crate::headless::{files_changed, TaskUsage};
+use crate::hooks::{HookEvent, HookRuntime};
+use crate::i18n;
+use crate::notifications::{self, Notification, NotificationKind};
+use crate::permission::profiles::{configured_profiles, PermissionProfileState};
 use crate::posthog;
 use crate::providers::create;
 use crate::recipe::Recipe;
 use crate::scheduler_trait::SchedulerTrait;
+use crate::session::extension_data::ExtensionState;
 use crate::session::session_manager::SessionType;
 use crate::session::{Session, SessionManager};
 
@@ -50,6 +58,7 @@ pub enum SchedulerError {
     AgentSetupError(String),
     PersistError(String),
     CronParseError(String),
+    InvalidRunSettings(String),
     SchedulerInternalError(String),
     AnyhowError(anyhow::Error),
 }
@@ -64,6 +73,7 @@ impl std::fmt::Display for SchedulerError {
             SchedulerError::AgentSetupError(e) => write!(f, "Agent setup error: {}", e),
             SchedulerError::PersistError(e) => write!(f, "Failed to persist schedules: {}", e),
             SchedulerError::CronParseError(e) => write!(f, "Invalid cron string: {}", e),
+            SchedulerError::InvalidRunSettings(e) => write!(f, "Invalid run settings: {}", e),
             SchedulerError::SchedulerInternalError(e) => {
                 write!(f, "Scheduler internal error: {}", e)
             }
@@ -100,6 +110,175 @@ impl From<anyhow::Error> for SchedulerError {
     }
 }
 
+/// How a job runs, overriding the recipe's settings and the global config
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize, Debug, utoipa::ToSchema)]
+pub struct JobRunSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Permission profile from `GOOSE_PERMISSION_PROFILES` the job's sessions run under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_profile: Option<String>,
+}
+
+impl JobRunSettings {
+    /// Rejects a permission profile that is not a plain name from `GOOSE_PERMISSION_PROFILES`
+    pub fn validate(&self) -> Result<(), SchedulerError> {
+        let Some(profile) = &self.permission_profile else {
+            return Ok(());
+        };
+        if profile.is_empty()
+            || !profile
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(SchedulerError::InvalidRunSettings(format!(
+                "Permission profile '{}' may only contain letters, digits, '-' and '_'",
+                profile
+            )));
+        }
+        if !configured_profiles().contains_key(profile) {
+            return Err(SchedulerError::InvalidRunSettings(format!(
+                "Unknown permission profile '{}'",
+                profile
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunOutcome {
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobRunOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobRunOutcome::Completed => "completed",
+            JobRunOutcome::Failed => "failed",
+            JobRunOutcome::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Result of the most recent run of a job
+#[derive(Clone, Serialize, Deserialize, Debug, utoipa::ToSchema)]
+pub struct JobRunStatus {
+    pub outcome: JobRunOutcome,
+    pub finished_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why a TaskCompleted hook flagged the run for follow-up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_up: Option<String>,
+}
+
+impl JobRunStatus {
+    fn new(result: &Result<String>, session_id: Option<String>, cancelled: bool) -> Self {
+        let outcome = match result {
+            Ok(_) if cancelled => JobRunOutcome::Cancelled,
+            Ok(_) => JobRunOutcome::Completed,
+            Err(_) => JobRunOutcome::Failed,
+        };
+        Self {
+            outcome,
+            finished_at: Utc::now(),
+            session_id,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            follow_up: None,
+        }
+    }
+}
+
+/// Report a job's last run to TaskCompleted hooks, flagging it for follow-up if one blocks
+async fn report_run_completed(jobs: &Arc<Mutex<JobsMap>>, job_id: &str) {
+    let status = {
+        let jobs_guard = jobs.lock().await;
+        match jobs_guard
+            .get(job_id)
+            .and_then(|(_, job)| job.last_run_status.clone())
+        {
+            Some(status) => status,
+            None => return,
+        }
+    };
+    let session = match &status.session_id {
+        Some(session_id) => SessionManager::instance()
+            .get_session(session_id, true)
+            .await
+            .ok(),
+        None => None,
+    };
+    let cwd = match &session {
+        Some(session) => session.working_dir.clone(),
+        None => std::env::current_dir().unwrap_or_default(),
+    };
+    let conversation = session
+        .as_ref()
+        .and_then(|session| session.conversation.clone())
+        .unwrap_or_default();
+    let output = conversation
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::Assistant)
+        .map(|message| message.as_concat_text())
+        .find(|text| !text.trim().is_empty())
+        .unwrap_or_default();
+    let usage = session
+        .as_ref()
+        .map(|session| TaskUsage {
+            input_tokens: session.accumulated_input_tokens,
+            output_tokens: session.accumulated_output_tokens,
+            total_tokens: session.accumulated_total_tokens,
+        })
+        .unwrap_or_default();
+
+    notifications::notify(Notification::new(
+        NotificationKind::TaskFinished,
+        status.session_id.clone().unwrap_or_default(),
+        Some(SessionType::Scheduled),
+        i18n::text_with(
+            "notify.scheduled_job",
+            &[("job", &job_id), ("status", &status.outcome.as_str())],
+        ),
+    ));
+
+    let follow_up = HookRuntime::load(&cwd)
+        .emit(
+            HookEvent::TaskCompleted {
+                session_id: status.session_id.clone().unwrap_or_default(),
+                task_id: job_id.to_string(),
+                task_type: "scheduled".to_string(),
+                status: status.outcome.as_str().to_string(),
+                output,
+                error: status.error.clone(),
+                files_changed: files_changed(&conversation, &cwd),
+                usage,
+                cwd: cwd.clone(),
+            },
+            &cwd,
+            CancellationToken::new(),
+        )
+        .await
+        .follow_up();
+    if follow_up.is_none() {
+        return;
+    }
+    tracing::info!("Job '{}' was flagged for follow-up", job_id);
+    if let Some((_, job)) = jobs.lock().await.get_mut(job_id) {
+        if let Some(status) = job.last_run_status.as_mut() {
+            status.follow_up = follow_up;
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, utoipa::ToSchema)]
 pub struct ScheduledJob {
     pub id: String,
@@ -114,6 +293,10 @@ pub struct ScheduledJob {
     pub current_session_id: Option<String>,
     #[serde(default)]
     pub process_start_time: Option<DateTime<Utc>>,
+    #[serde(default, flatten)]
+    pub settings: JobRunSettings,
+    #[serde(default)]
+    pub last_run_status: Option<JobRunStatus>,
 }
 
 async fn persist_jobs(
@@ -254,10 +437,15 @@ impl Scheduler {
                     let mut jobs_guard = current_jobs_arc.lock().await;
                     if let Some((_, job)) = jobs_guard.get_mut(&task_job_id) {
                         job.currently_running = false;
-                        job.current_session_id = None;
+                        job.last_run_status = Some(JobRunStatus::new(
+                            &result,
+                            job.current_session_id.take(),
+                            cancel_token.is_cancelled(),
+                        ));
                         job.process_start_time = None;
                     }
                 }
+                report_run_completed(&current_jobs_arc, &task_job_id).await;
 
                 if let Err(e) = persist_jobs(&local_storage_path, &current_jobs_arc).await {
                     tracing::error!("Failed to persist job completion: {}", e);
@@ -280,6 +468,7 @@ impl Scheduler {
         original_job_spec: ScheduledJob,
         make_copy: bool,
     ) -> Result<(), SchedulerError> {
+        original_job_spec.settings.validate()?;
         {
             let jobs_guard = self.jobs.lock().await;
             if jobs_guard.contains_key(&original_job_spec.id) {
@@ -359,6 +548,8 @@ impl Scheduler {
                         paused: false,
                         current_session_id: None,
                         process_start_time: None,
+                        settings: JobRunSettings::default(),
+                        last_run_status: None,
                     };
                     self.add_scheduled_job(job, false).await
                 }
@@ -637,11 +828,16 @@ impl Scheduler {
             let mut jobs_guard = self.jobs.lock().await;
             if let Some((_, job)) = jobs_guard.get_mut(sched_id) {
                 job.currently_running = false;
-                job.current_session_id = None;
+                job.last_run_status = Some(JobRunStatus::new(
+                    &result,
+                    job.current_session_id.take(),
+                    cancel_token.is_cancelled(),
+                ));
                 job.process_start_time = None;
                 job.last_run = Some(Utc::now());
             }
         }
+        report_run_completed(&self.jobs, sched_id).await;
 
         persist_jobs(&self.storage_path, &self.jobs).await?;
 
@@ -734,6 +930,23 @@ impl Scheduler {
         persist_jobs(&self.storage_path, &self.jobs).await
     }
 
+    pub async fn update_run_settings(
+        &self,
+        sched_id: &str,
+        settings: JobRunSettings,
+    ) -> Result<(), SchedulerError> {
+        settings.validate()?;
+        {
+            let mut jobs_guard = self.jobs.lock().await;
+            match jobs_guard.get_mut(sched_id) {
+                Some((_, job)) => job.settings = settings,
+                None => return Err(SchedulerError::JobNotFound(sched_id.to_string())),
+            }
+        }
+
+        persist_jobs(&self.storage_path, &self.jobs).await
+    }
+
     pub async fn kill_running_job(&self, sched_id: &str) -> Result<(), SchedulerError> {
         {
             let jobs_guard = self.jobs.lock().await;
@@ -807,12 +1020,29 @@ async fn execute_job(
     let agent = Agent::new();
 
     let config = Config::global();
-    let provider_name = config.get_goose_provider()?;
-    let model_name = config.get_goose_model()?;
+    let recipe_settings = recipe.settings.as_ref();
+    let provider_name = match job
+        .settings
+        .provider
+        .clone()
+        .or_else(|| recipe_settings.and_then(|s| s.goose_provider.clone()))
+    {
+        Some(provider) => provider,
+        None => config.get_goose_provider()?,
+    };
+    let model_name = match job
+        .settings
+        .model
+        .clone()
+        .or_else(|| recipe_settings.and_then(|s| s.goose_model.clone()))
+    {
+        Some(model) => model,
+        None => config.get_goose_model()?,
+    };
     let model_config =
         crate::model::ModelConfig::new(&model_name)?.with_canonical_limits(&provider_name);
 
-    let session = agent
+    let mut session = agent
         .config
         .session_manager
         .create_session(
@@ -822,10 +1052,26 @@ async fn execute_job(
         )
         .await?;
 
-    let extensions = resolve_extensions_for_new_session(recipe.extensions.as_deref(), None);
-    for ext in &extensions {
-        agent.add_extension(ext.clone(), &session.id).await?;
+    // Tag the session and apply the recipe and permission profile before the run starts,
+    // so tool calls are checked against them and approvals go to the remote channel
+    if let Some(profile) = &job.settings.permission_profile {
+        PermissionProfileState::new(Some(profile.clone()))
+            .to_extension_data(&mut session.extension_data)?;
     }
+    agent
+        .config
+        .session_manager
+        .update(&session.id)
+        .schedule_id(Some(job.id.clone()))
+        .recipe(Some(recipe.clone()))
+        .extension_data(session.extension_data.clone())
+        .apply()
+        .await?;
+
+    let extensions = resolve_extensions_for_new_session(recipe.extensions.as_deref(), None);
+    agent
+        .apply_tool_set(&ToolSetSnapshot::new(extensions.clone()), &session.id)
+        .await?;
 
     let agent_provider = create(&provider_name, model_config, extensions).await?;
     agent.update_provider(agent_provider, &session.id).await?;
@@ -897,15 +1143,6 @@ async fn execute_job(
         }
     }
 
-    agent
-        .config
-        .session_manager
-        .update(&session.id)
-        .schedule_id(Some(job.id.clone()))
-        .recipe(Some(recipe))
-        .apply()
-        .await?;
-
     let duration_secs = start_time.elapsed().as_secs();
     tokio::spawn(async move {
         let mut props = HashMap::new();
@@ -987,6 +1224,14 @@ impl SchedulerTrait for Scheduler {
         self.update_schedule(sched_id, new_cron).await
     }
 
+    async fn update_run_settings(
+        &self,
+        sched_id: &str,
+        settings: JobRunSettings,
+    ) -> Result<(), SchedulerError> {
+        self.update_run_settings(sched_id, settings).await
+    }
+
     async fn kill_running_job(&self, sched_id: &str) -> Result<(), SchedulerError> {
         self.kill_running_job(sched_id).await
     }
@@ -1034,6 +1279,8 @@ mod tests {
             paused: false,
             current_session_id: None,
             process_start_time: None,
+            settings: JobRunSettings::default(),
+            last_run_status: None,
         };
 
         scheduler.add_scheduled_job(job, true).await.unwrap();
@@ -1066,6 +1313,8 @@ mod tests {
             paused: false,
             current_session_id: None,
             process_start_time: None,
+            settings: JobRunSettings::default(),
+            last_run_status: None,
         };
 
         scheduler.add_scheduled_job(job, true).await.unwrap();
@@ -1105,6 +1354,8 @@ mod tests {
             paused: false,
             current_session_id: None,
             process_start_time: None,
+            settings: JobRunSettings::default(),
+            last_run_status: None,
         };
 
         // Schedule the job and let it run — should not panic
@@ -1118,4 +1369,53 @@ mod tests {
             "Job should have attempted to run without panicking"
         );
     }
+
+    #[test]
+    fn test_job_run_settings_are_stored_inline() {
+        let stored: ScheduledJob = serde_json::from_value(serde_json::json!({
+            "id": "nightly",
+            "source": "/recipes/nightly.yaml",
+            "cron": "0 0 2 * * *",
+            "last_run": null,
+            "currently_running": false
+        }))
+        .unwrap();
+        assert_eq!(stored.settings, JobRunSettings::default());
+        assert!(stored.last_run_status.is_none());
+
+        let job = ScheduledJob {
+            settings: JobRunSettings {
+                provider: Some("anthropic".to_string()),
+                model: None,
+                permission_profile: Some("readonly".to_string()),
+            },
+            last_run_status: Some(JobRunStatus::new(
+                &Err(anyhow!("provider unavailable")),
+                Some("20250101_1".to_string()),
+                false,
+            )),
+            ..stored
+        };
+        let value = serde_json::to_value(&job).unwrap();
+        assert_eq!(value["provider"], "anthropic");
+        assert_eq!(value["permission_profile"], "readonly");
+        assert!(value.get("model").is_none());
+        assert_eq!(value["last_run_status"]["outcome"], "failed");
+        assert_eq!(value["last_run_status"]["session_id"], "20250101_1");
+    }
+
+    #[test]
+    fn test_run_settings_reject_profile_names_that_are_not_plain() {
+        assert!(JobRunSettings::default().validate().is_ok());
+        for profile in ["", "../readonly", "read only", "a/b"] {
+            let settings = JobRunSettings {
+                permission_profile: Some(profile.to_string()),
+                ..Default::default()
+            };
+            assert!(matches!(
+                settings.validate(),
+                Err(SchedulerError::InvalidRunSettings(_))
+            ));
+        }
+    }
 }
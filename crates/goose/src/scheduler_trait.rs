@@ -2,7 +2,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::path::PathBuf;
 
-use crate::scheduler::{ScheduledJob, SchedulerError};
+use crate::scheduler::{JobRunSettings, ScheduledJob, SchedulerError};
 use crate::session::Session;
 
 #[async_trait]
@@ -33,6 +33,11 @@ pub trait SchedulerTrait: Send + Sync {
     ) -> Result<Vec<(String, Session)>, SchedulerError>;
     async fn update_schedule(&self, sched_id: &str, new_cron: String)
         -> Result<(), SchedulerError>;
+    async fn update_run_settings(
+        &self,
+        sched_id: &str,
+        settings: JobRunSettings,
+    ) -> Result<(), SchedulerError>;
     async fn kill_running_job(&self, sched_id: &str) -> Result<(), SchedulerError>;
     async fn get_running_job_info(
         &self,
@@ -0,0 +1,695 @@
+//! A stable facade for embedding goose in Rust applications.
+//!
+//! [`GooseClient`] creates and resumes sessions, sends prompts and streams back what the
+//! agent does as [`ClientEvent`]s, answers tool approvals and manages a session's extensions.
+//! It only exposes its own types, so applications built on it don't have to follow changes
+//! to the agent, extension manager or provider internals.
+//!
+//! ```no_run
+//! use futures::StreamExt;
+//! use goose::sdk::{Approval, ClientEvent, Extension, GooseClient, Mode};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = GooseClient::builder()
+//!     .provider("anthropic")
+//!     .model("claude-sonnet-4-5")
+//!     .mode(Mode::SmartApprove)
+//!     .extension(Extension::builtin("developer"))
+//!     .build();
+//! let session_id = client.create_session("/path/to/project").await?;
+//!
+//! let mut events = client.send(&session_id, "What does this project do?").await?;
+//! while let Some(event) = events.next().await {
+//!     match event? {
+//!         ClientEvent::Text { text } => print!("{}", text),
+//!         ClientEvent::ApprovalRequested { request_id, .. } => {
+//!             client.approve(&session_id, &request_id, Approval::AllowOnce).await?;
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Sessions are stored with the user's other goose sessions unless the client is given its
+//! own data directory.
+
+use crate::agents::extension::{Envs, ExtensionConfig, PLATFORM_EXTENSIONS};
+use crate::agents::{Agent, AgentConfig, AgentEvent, GoosePlatform, SessionConfig};
+use crate::config::extensions::resolve_extensions_for_new_session;
+use crate::config::permission::PermissionManager;
+use crate::config::{Config, GooseMode, DEFAULT_EXTENSION_TIMEOUT};
+use crate::conversation::message::{ActionRequiredData, Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::Provider;
+use crate::session::session_manager::SessionType;
+use crate::session::SessionManager;
+use anyhow::{anyhow, Context, Result};
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// What the agent did while answering a prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ClientEvent {
+    /// Text from the assistant, streamed in pieces
+    Text {
+        text: String,
+    },
+    ToolCall {
+        id: String,
+        name: String,
+        arguments: Value,
+    },
+    ToolResult {
+        id: String,
+        output: String,
+        is_error: bool,
+    },
+    /// A tool call waits for [`GooseClient::approve`]
+    ApprovalRequested {
+        request_id: String,
+        tool_name: String,
+        arguments: Value,
+        /// Why goose flagged the call, e.g. a security finding
+        warning: Option<String>,
+    },
+    /// A status message from goose, like compaction starting
+    Notice {
+        message: String,
+    },
+    ModelChanged {
+        model: String,
+    },
+}
+
+pub type ClientEventStream = BoxStream<'static, Result<ClientEvent>>;
+
+/// An answer to [`ClientEvent::ApprovalRequested`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Approval {
+    AllowOnce,
+    /// Allow the tool for the rest of the session
+    AllowForSession,
+    AlwaysAllow,
+    Deny,
+}
+
+impl From<Approval> for Permission {
+    fn from(approval: Approval) -> Self {
+        match approval {
+            Approval::AllowOnce => Permission::AllowOnce,
+            Approval::AllowForSession => Permission::AllowForSession,
+            Approval::AlwaysAllow => Permission::AlwaysAllow,
+            Approval::Deny => Permission::DenyOnce,
+        }
+    }
+}
+
+/// How tool calls are approved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Mode {
+    /// Run every tool call without asking
+    Auto,
+    /// Ask before every tool call
+    Approve,
+    /// Ask only before tool calls that look risky
+    SmartApprove,
+    /// Don't call tools
+    Chat,
+}
+
+impl From<Mode> for GooseMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Auto => GooseMode::Auto,
+            Mode::Approve => GooseMode::Approve,
+            Mode::SmartApprove => GooseMode::SmartApprove,
+            Mode::Chat => GooseMode::Chat,
+        }
+    }
+}
+
+/// The kind of application goose runs in, which extensions and prompts adapt to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Platform {
+    #[default]
+    Cli,
+    Desktop,
+}
+
+impl From<Platform> for GoosePlatform {
+    fn from(platform: Platform) -> Self {
+        match platform {
+            Platform::Cli => GoosePlatform::GooseCli,
+            Platform::Desktop => GoosePlatform::GooseDesktop,
+        }
+    }
+}
+
+/// An extension to give a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Extension {
+    /// One of the extensions that ship with goose, like `developer` or `memory`
+    Builtin { name: String },
+    /// An MCP server started as a child process
+    Stdio {
+        name: String,
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        envs: HashMap<String, String>,
+    },
+    /// An MCP server reached over streamable HTTP
+    Http {
+        name: String,
+        uri: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+}
+
+impl Extension {
+    pub fn builtin(name: impl Into<String>) -> Self {
+        Self::Builtin { name: name.into() }
+    }
+
+    pub fn stdio(name: impl Into<String>, command: impl Into<String>, args: Vec<String>) -> Self {
+        Self::Stdio {
+            name: name.into(),
+            command: command.into(),
+            args,
+            envs: HashMap::new(),
+        }
+    }
+
+    pub fn http(name: impl Into<String>, uri: impl Into<String>) -> Self {
+        Self::Http {
+            name: name.into(),
+            uri: uri.into(),
+            headers: HashMap::new(),
+        }
+    }
+
+    fn into_config(self) -> ExtensionConfig {
+        match self {
+            Extension::Builtin { name } if PLATFORM_EXTENSIONS.contains_key(name.as_str()) => {
+                ExtensionConfig::Platform {
+                    description: name.clone(),
+                    name,
+                    display_name: None,
+                    bundled: None,
+                    available_tools: Vec::new(),
+                }
+            }
+            Extension::Builtin { name } => ExtensionConfig::Builtin {
+                description: name.clone(),
+                name,
+                display_name: None,
+                timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+                bundled: None,
+                available_tools: Vec::new(),
+            },
+            Extension::Stdio {
+                name,
+                command,
+                args,
+                envs,
+            } => ExtensionConfig::Stdio {
+                description: name.clone(),
+                name,
+                cmd: command,
+                args,
+                envs: Envs::new(envs),
+                env_keys: Vec::new(),
+                timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+                bundled: None,
+                available_tools: Vec::new(),
+            },
+            Extension::Http { name, uri, headers } => ExtensionConfig::StreamableHttp {
+                description: name.clone(),
+                name,
+                uri,
+                envs: Envs::default(),
+                env_keys: Vec::new(),
+                headers,
+                timeout: Some(DEFAULT_EXTENSION_TIMEOUT),
+                bundled: None,
+                available_tools: Vec::new(),
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GooseClientBuilder {
+    provider: Option<String>,
+    model: Option<String>,
+    mode: Option<Mode>,
+    platform: Platform,
+    instructions: Option<String>,
+    extensions: Option<Vec<Extension>>,
+    data_dir: Option<PathBuf>,
+    #[cfg(test)]
+    provider_instance: Option<Arc<dyn Provider>>,
+}
+
+impl GooseClientBuilder {
+    /// The provider to use instead of the configured GOOSE_PROVIDER
+    pub fn provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// The model to use instead of the configured GOOSE_MODEL
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// How tool calls are approved, instead of the configured GOOSE_MODE
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// The kind of application embedding goose, a terminal one unless set
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Extra instructions appended to the system prompt
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Start new sessions with this extension instead of the ones enabled in the config
+    pub fn extension(mut self, extension: Extension) -> Self {
+        self.extensions.get_or_insert_with(Vec::new).push(extension);
+        self
+    }
+
+    /// Start new sessions without the extensions enabled in the config
+    pub fn no_extensions(mut self) -> Self {
+        self.extensions.get_or_insert_with(Vec::new);
+        self
+    }
+
+    /// Keep sessions and permissions in `data_dir` instead of the user's goose directories
+    pub fn data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    #[cfg(test)]
+    fn provider_instance(mut self, provider: Arc<dyn Provider>) -> Self {
+        self.provider_instance = Some(provider);
+        self
+    }
+
+    pub fn build(self) -> GooseClient {
+        let (session_manager, permission_manager) = match &self.data_dir {
+            Some(dir) => (
+                Arc::new(SessionManager::new(dir.clone())),
+                Arc::new(PermissionManager::new(dir.clone())),
+            ),
+            None => (
+                Arc::new(SessionManager::instance()),
+                PermissionManager::instance(),
+            ),
+        };
+        GooseClient {
+            options: self,
+            session_manager,
+            permission_manager,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+struct ClientSession {
+    agent: Arc<Agent>,
+    /// Cancels the reply in flight, if any
+    reply: Option<CancellationToken>,
+}
+
+/// Runs goose sessions on behalf of an application
+pub struct GooseClient {
+    options: GooseClientBuilder,
+    session_manager: Arc<SessionManager>,
+    permission_manager: Arc<PermissionManager>,
+    sessions: Mutex<HashMap<String, ClientSession>>,
+}
+
+impl GooseClient {
+    pub fn builder() -> GooseClientBuilder {
+        GooseClientBuilder::default()
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, ClientSession>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn agent(&self, session_id: &str) -> Result<Arc<Agent>> {
+        self.sessions()
+            .get(session_id)
+            .map(|session| session.agent.clone())
+            .ok_or_else(|| anyhow!("Session {} is not open in this client", session_id))
+    }
+
+    fn new_agent(&self) -> Arc<Agent> {
+        let mode = match self.options.mode {
+            Some(mode) => mode.into(),
+            None => Config::global().get_goose_mode().unwrap_or(GooseMode::Auto),
+        };
+        Arc::new(Agent::with_config(AgentConfig::new(
+            self.session_manager.clone(),
+            self.permission_manager.clone(),
+            None,
+            mode,
+            false,
+            self.options.platform.into(),
+        )))
+    }
+
+    fn open(&self, session_id: &str, agent: Arc<Agent>) {
+        self.sessions()
+            .insert(session_id.to_string(), ClientSession { agent, reply: None });
+    }
+
+    async fn create_provider(&self, extensions: Vec<ExtensionConfig>) -> Result<Arc<dyn Provider>> {
+        #[cfg(test)]
+        if let Some(provider) = &self.options.provider_instance {
+            return Ok(provider.clone());
+        }
+        let config = Config::global();
+        let provider_name = match &self.options.provider {
+            Some(provider) => provider.clone(),
+            None => config
+                .get_goose_provider()
+                .context("No provider configured. Run 'goose configure' first")?,
+        };
+        let model_name = match &self.options.model {
+            Some(model) => model.clone(),
+            None => config
+                .get_goose_model()
+                .context("No model configured. Run 'goose configure' first")?,
+        };
+        let model_config = ModelConfig::new(&model_name)?.with_canonical_limits(&provider_name);
+        crate::providers::create(&provider_name, model_config, extensions).await
+    }
+
+    /// Start a session working in `working_dir` and return its id
+    pub async fn create_session(&self, working_dir: impl Into<PathBuf>) -> Result<String> {
+        let extensions: Vec<ExtensionConfig> = match &self.options.extensions {
+            Some(extensions) => extensions
+                .iter()
+                .cloned()
+                .map(Extension::into_config)
+                .collect(),
+            None => resolve_extensions_for_new_session(None, None),
+        };
+
+        let agent = self.new_agent();
+        let session = self
+            .session_manager
+            .create_session(
+                working_dir.into(),
+                "New session".to_string(),
+                SessionType::User,
+            )
+            .await?;
+        for extension in extensions.iter().cloned() {
+            agent.add_extension(extension, &session.id).await?;
+        }
+        if let Some(instructions) = &self.options.instructions {
+            agent
+                .extend_system_prompt("sdk_client".to_string(), instructions.clone())
+                .await;
+        }
+        let provider = self.create_provider(extensions).await?;
+        agent.update_provider(provider, &session.id).await?;
+        self.open(&session.id, agent);
+        Ok(session.id)
+    }
+
+    /// Open a session created earlier, with the extensions and model it was using
+    pub async fn resume_session(&self, session_id: &str) -> Result<()> {
+        let session = self.session_manager.get_session(session_id, false).await?;
+        let agent = self.new_agent();
+        for result in agent.load_extensions_from_session(&session).await {
+            if let Some(error) = result.error {
+                tracing::warn!("Failed to load extension {}: {}", result.name, error);
+            }
+        }
+        agent.restore_provider_from_session(&session).await?;
+        self.open(session_id, agent);
+        Ok(())
+    }
+
+    /// Stop using a session in this client. The session itself is kept.
+    pub fn close_session(&self, session_id: &str) {
+        if let Some(session) = self.sessions().remove(session_id) {
+            if let Some(reply) = session.reply {
+                reply.cancel();
+            }
+        }
+    }
+
+    /// Send a prompt and stream what the agent does until it answers
+    pub async fn send(&self, session_id: &str, prompt: &str) -> Result<ClientEventStream> {
+        let agent = self.agent(session_id)?;
+        let cancel_token = CancellationToken::new();
+        if let Some(session) = self.sessions().get_mut(session_id) {
+            if let Some(previous) = session.reply.replace(cancel_token.clone()) {
+                previous.cancel();
+            }
+        }
+
+        let session_config = SessionConfig {
+            id: session_id.to_string(),
+            schedule_id: None,
+            max_turns: None,
+            retry_config: None,
+        };
+        let prompt = Message::user().with_text(prompt);
+        Ok(Box::pin(async_stream::try_stream! {
+            let mut stream = agent
+                .reply(prompt, session_config, Some(cancel_token))
+                .await?;
+            while let Some(event) = stream.next().await {
+                for client_event in client_events(event?) {
+                    yield client_event;
+                }
+            }
+        }))
+    }
+
+    /// Stop the reply in flight for a session
+    pub fn cancel(&self, session_id: &str) {
+        if let Some(session) = self.sessions().get_mut(session_id) {
+            if let Some(reply) = session.reply.take() {
+                reply.cancel();
+            }
+        }
+    }
+
+    /// Answer an approval request from [`ClientEvent::ApprovalRequested`]
+    pub async fn approve(
+        &self,
+        session_id: &str,
+        request_id: &str,
+        approval: Approval,
+    ) -> Result<()> {
+        self.agent(session_id)?
+            .handle_confirmation(
+                request_id.to_string(),
+                PermissionConfirmation {
+                    principal_type: PrincipalType::Tool,
+                    permission: approval.into(),
+                },
+            )
+            .await;
+        Ok(())
+    }
+
+    pub async fn add_extension(&self, session_id: &str, extension: Extension) -> Result<()> {
+        self.agent(session_id)?
+            .add_extension(extension.into_config(), session_id)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn remove_extension(&self, session_id: &str, name: &str) -> Result<()> {
+        self.agent(session_id)?
+            .remove_extension(name, session_id)
+            .await
+    }
+
+    /// Names of the extensions the session is using
+    pub async fn extensions(&self, session_id: &str) -> Result<Vec<String>> {
+        Ok(self.agent(session_id)?.list_extensions().await)
+    }
+}
+
+fn client_events(event: AgentEvent) -> Vec<ClientEvent> {
+    match event {
+        AgentEvent::Message(message) => message_events(&message),
+        AgentEvent::ModelChange { model, .. } => vec![ClientEvent::ModelChanged { model }],
+        AgentEvent::McpNotification(_) | AgentEvent::HistoryReplaced(_) => Vec::new(),
+    }
+}
+
+fn message_events(message: &Message) -> Vec<ClientEvent> {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::Text(text) if message.role == Role::Assistant => {
+                Some(ClientEvent::Text {
+                    text: text.text.clone(),
+                })
+            }
+            MessageContent::ToolRequest(request) => {
+                let call = request.tool_call.as_ref().ok()?;
+                Some(ClientEvent::ToolCall {
+                    id: request.id.clone(),
+                    name: call.name.to_string(),
+                    arguments: Value::Object(call.arguments.clone().unwrap_or_default()),
+                })
+            }
+            MessageContent::ToolResponse(response) => {
+                let (output, is_error) = match &response.tool_result {
+                    Ok(result) => (
+                        result
+                            .content
+                            .iter()
+                            .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        result.is_error.unwrap_or(false),
+                    ),
+                    Err(error) => (error.to_string(), true),
+                };
+                Some(ClientEvent::ToolResult {
+                    id: response.id.clone(),
+                    output,
+                    is_error,
+                })
+            }
+            MessageContent::ActionRequired(action) => match &action.data {
+                ActionRequiredData::ToolConfirmation {
+                    id,
+                    tool_name,
+                    arguments,
+                    prompt,
+                } => Some(ClientEvent::ApprovalRequested {
+                    request_id: id.clone(),
+                    tool_name: tool_name.clone(),
+                    arguments: Value::Object(arguments.clone()),
+                    warning: prompt.clone(),
+                }),
+                _ => None,
+            },
+            MessageContent::SystemNotification(notification) => Some(ClientEvent::Notice {
+                message: notification.msg.clone(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::fake::FakeProvider;
+    use rmcp::object;
+
+    #[test]
+    fn messages_become_client_events() {
+        let message = Message::assistant()
+            .with_text("Checking")
+            .with_action_required(
+                "req_1",
+                "developer__shell".to_string(),
+                object!({"command": "rm -rf build"}),
+                Some("Deletes files".to_string()),
+            );
+        assert_eq!(
+            message_events(&message),
+            [
+                ClientEvent::Text {
+                    text: "Checking".to_string()
+                },
+                ClientEvent::ApprovalRequested {
+                    request_id: "req_1".to_string(),
+                    tool_name: "developer__shell".to_string(),
+                    arguments: serde_json::json!({"command": "rm -rf build"}),
+                    warning: Some("Deletes files".to_string()),
+                },
+            ]
+        );
+        // The prompt echoed back isn't assistant text
+        assert!(message_events(&Message::user().with_text("hi")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn sends_prompts_and_streams_the_answer() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let provider = Arc::new(
+            FakeProvider::new()
+                .text("Hello from goose")
+                .expect_user_text("say hello"),
+        );
+        let client = GooseClient::builder()
+            .data_dir(data_dir.path())
+            .mode(Mode::Auto)
+            .no_extensions()
+            .provider_instance(provider.clone())
+            .build();
+
+        let session_id = client.create_session(data_dir.path()).await.unwrap();
+        let events: Vec<ClientEvent> = client
+            .send(&session_id, "say hello")
+            .await
+            .unwrap()
+            .map(|event| event.unwrap())
+            .collect()
+            .await;
+        let text: String = events
+            .iter()
+            .filter_map(|event| match event {
+                ClientEvent::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello from goose");
+        assert!(client.extensions(&session_id).await.unwrap().is_empty());
+        provider.verify().unwrap();
+
+        client.close_session(&session_id);
+        assert!(client.send(&session_id, "again").await.is_err());
+    }
+}
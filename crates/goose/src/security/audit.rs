@@ -0,0 +1,489 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, LazyLock, Mutex};
+use utoipa::ToSchema;
+
+use crate::config::paths::Paths;
+use crate::config::Config;
+use crate::conversation::message::ToolRequest;
+use crate::permission::permission_judge::PermissionCheckResult;
+use crate::permission::Permission;
+
+const AUDIT_LOG_FILE: &str = "audit/audit.jsonl";
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+static AUDIT_LOG: LazyLock<AuditLog> =
+    LazyLock::new(|| AuditLog::new(Paths::in_state_dir(AUDIT_LOG_FILE)));
+
+/// Entries recorded through [`AuditLog::record`] are written in order by a dedicated thread so
+/// the fsync never blocks an async worker.
+static WRITER: LazyLock<mpsc::Sender<PendingEntry>> = LazyLock::new(spawn_writer);
+
+struct PendingEntry {
+    session_id: Option<String>,
+    timestamp: DateTime<Utc>,
+    event: AuditEvent,
+}
+
+fn spawn_writer() -> mpsc::Sender<PendingEntry> {
+    let (tx, rx) = mpsc::channel::<PendingEntry>();
+    let spawned = std::thread::Builder::new()
+        .name("goose-audit".to_string())
+        .spawn(move || {
+            for pending in rx {
+                if let Err(e) = AuditLog::global().write_entry(
+                    pending.session_id,
+                    pending.timestamp,
+                    pending.event,
+                ) {
+                    tracing::error!("Failed to write audit log entry: {}", e);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        tracing::error!("Failed to start audit log writer: {}", e);
+    }
+    tx
+}
+
+/// Security-relevant events recorded in the audit trail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// A tool call was allowed or denied, by policy or by a person
+    PermissionDecision {
+        request_id: String,
+        tool_name: String,
+        decision: String,
+        /// Who decided: "policy" for automatic decisions, "confirmation" for approval prompts
+        source: String,
+    },
+    /// A tool inspector produced a finding for a tool call
+    Inspection {
+        request_id: String,
+        tool_name: String,
+        inspector: String,
+        action: String,
+        reason: String,
+        confidence: f32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        finding_id: Option<String>,
+    },
+    /// A configured hook command ran
+    HookExecution {
+        event: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tool_name: Option<String>,
+        command: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        exit_code: Option<i32>,
+        timed_out: bool,
+        blocked: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub event: AuditEvent,
+    /// Hash of the previous entry, chaining the log so edits and deletions are detectable
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// The hashed portion of an entry: everything except its own hash
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    seq: u64,
+    timestamp: &'a DateTime<Utc>,
+    session_id: &'a Option<String>,
+    event: &'a AuditEvent,
+    prev_hash: &'a str,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> String {
+        let fields = HashedFields {
+            seq: self.seq,
+            timestamp: &self.timestamp,
+            session_id: &self.session_id,
+            event: &self.event,
+            prev_hash: &self.prev_hash,
+        };
+        let bytes = serde_json::to_vec(&fields).expect("audit entry serializes");
+        format!("{:x}", Sha256::digest(bytes))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub entries: u64,
+    /// Sequence number of the first entry that fails verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_invalid_seq: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append-only, hash-chained audit log. Disabled unless `GOOSE_AUDIT_LOG_ENABLED` is true.
+///
+/// Several goose processes can share one log, so every append holds an exclusive lock on the
+/// file and takes the chain head from the entry currently at its tail.
+pub struct AuditLog {
+    path: PathBuf,
+    write: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write: Mutex::new(()),
+        }
+    }
+
+    pub fn global() -> &'static AuditLog {
+        &AUDIT_LOG
+    }
+
+    pub fn is_enabled() -> bool {
+        Config::global()
+            .get_param::<bool>("GOOSE_AUDIT_LOG_ENABLED")
+            .unwrap_or(false)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue an event for the global log if auditing is enabled. Entries are written in order off
+    /// the calling thread; failures are logged, not raised, so auditing never interrupts the agent.
+    pub fn record(session_id: Option<&str>, event: AuditEvent) {
+        if !Self::is_enabled() {
+            return;
+        }
+        let pending = PendingEntry {
+            session_id: session_id.map(str::to_string),
+            timestamp: Utc::now(),
+            event,
+        };
+        if let Err(mpsc::SendError(pending)) = WRITER.send(pending) {
+            if let Err(e) =
+                Self::global().write_entry(pending.session_id, pending.timestamp, pending.event)
+            {
+                tracing::error!("Failed to write audit log entry: {}", e);
+            }
+        }
+    }
+
+    /// Append an event synchronously. This does blocking file IO and should not run on an async
+    /// worker thread.
+    pub fn append(&self, session_id: Option<&str>, event: AuditEvent) -> Result<AuditEntry> {
+        self.write_entry(session_id.map(str::to_string), Utc::now(), event)
+    }
+
+    fn write_entry(
+        &self,
+        session_id: Option<String>,
+        timestamp: DateTime<Utc>,
+        event: AuditEvent,
+    ) -> Result<AuditEntry> {
+        let _guard = self.write.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open audit log {}", self.path.display()))?;
+        file.lock_exclusive()
+            .with_context(|| format!("Failed to lock audit log {}", self.path.display()))?;
+
+        let (seq, prev_hash) = match last_entry(&mut file)? {
+            Some(head) => (head.seq + 1, head.hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        let mut entry = AuditEntry {
+            seq,
+            timestamp,
+            session_id,
+            event,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+        file.sync_data()?;
+        Ok(entry)
+    }
+
+    /// Open the log for reading under a shared lock, so readers never see a half-written entry
+    fn open_for_read(&self) -> Result<Option<File>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        file.lock_shared()
+            .with_context(|| format!("Failed to lock audit log {}", self.path.display()))?;
+        Ok(Some(file))
+    }
+
+    fn entries(&self) -> Result<Vec<AuditEntry>> {
+        let Some(file) = self.open_for_read()? else {
+            return Ok(Vec::new());
+        };
+        BufReader::new(file)
+            .lines()
+            .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+            .map(|line| Ok(serde_json::from_str(&line?)?))
+            .collect()
+    }
+
+    /// Export entries, optionally limited to one session
+    pub fn export(&self, session_id: Option<&str>) -> Result<Vec<AuditEntry>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|entry| session_id.is_none() || entry.session_id.as_deref() == session_id)
+            .collect())
+    }
+
+    /// Walk the chain and check every entry's hash and link to its predecessor
+    pub fn verify(&self) -> Result<AuditVerification> {
+        let Some(file) = self.open_for_read()? else {
+            return Ok(AuditVerification {
+                valid: true,
+                entries: 0,
+                first_invalid_seq: None,
+                error: None,
+            });
+        };
+
+        let mut expected_prev = GENESIS_HASH.to_string();
+        let mut count = 0u64;
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let failure = |error: String| AuditVerification {
+                valid: false,
+                entries: count,
+                first_invalid_seq: Some(count),
+                error: Some(error),
+            };
+            let entry: AuditEntry = match serde_json::from_str(&line) {
+                Ok(entry) => entry,
+                Err(e) => return Ok(failure(format!("Unparseable entry: {}", e))),
+            };
+            if entry.seq != count {
+                return Ok(failure(format!(
+                    "Expected sequence {} but found {}",
+                    count, entry.seq
+                )));
+            }
+            if entry.prev_hash != expected_prev {
+                return Ok(failure(
+                    "Entry does not link to its predecessor".to_string(),
+                ));
+            }
+            if entry.compute_hash() != entry.hash {
+                return Ok(failure(
+                    "Entry hash does not match its contents".to_string(),
+                ));
+            }
+            expected_prev = entry.hash;
+            count += 1;
+        }
+
+        Ok(AuditVerification {
+            valid: true,
+            entries: count,
+            first_invalid_seq: None,
+            error: None,
+        })
+    }
+}
+
+/// Read the last entry by scanning backwards from the end of the file
+fn last_entry(file: &mut File) -> Result<Option<AuditEntry>> {
+    const BLOCK: u64 = 4096;
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    let mut tail: Vec<u8> = Vec::new();
+    loop {
+        let trimmed = tail.trim_ascii_end();
+        if let Some(newline) = trimmed.iter().rposition(|&b| b == b'\n') {
+            return parse_line(&trimmed[newline + 1..]);
+        }
+        if pos == 0 {
+            return parse_line(trimmed);
+        }
+        let read = BLOCK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut block = vec![0; read as usize];
+        file.read_exact(&mut block)?;
+        block.extend_from_slice(&tail);
+        tail = block;
+    }
+}
+
+fn parse_line(line: &[u8]) -> Result<Option<AuditEntry>> {
+    let line = line.trim_ascii();
+    if line.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(line)
+        .map(Some)
+        .context("Failed to parse the last audit log entry")
+}
+
+fn tool_name(request: &ToolRequest) -> String {
+    request
+        .tool_call
+        .as_ref()
+        .map(|call| call.name.to_string())
+        .unwrap_or_default()
+}
+
+/// Record the automatic allow/deny decisions made by the permission policy
+pub fn record_policy_decisions(session_id: &str, result: &PermissionCheckResult) {
+    if !AuditLog::is_enabled() {
+        return;
+    }
+    let decisions = [("allow", &result.approved), ("deny", &result.denied)];
+    for (decision, requests) in decisions {
+        for request in requests {
+            AuditLog::record(
+                Some(session_id),
+                AuditEvent::PermissionDecision {
+                    request_id: request.id.clone(),
+                    tool_name: tool_name(request),
+                    decision: decision.to_string(),
+                    source: "policy".to_string(),
+                },
+            );
+        }
+    }
+}
+
+/// Record the answer to an approval prompt, from the user or a remote approver
+pub fn record_confirmation(session_id: &str, request: &ToolRequest, permission: &Permission) {
+    if !AuditLog::is_enabled() {
+        return;
+    }
+    let decision = serde_json::to_value(permission)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    AuditLog::record(
+        Some(session_id),
+        AuditEvent::PermissionDecision {
+            request_id: request.id.clone(),
+            tool_name: tool_name(request),
+            decision,
+            source: "confirmation".to_string(),
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(tool_name: &str) -> AuditEvent {
+        AuditEvent::PermissionDecision {
+            request_id: "req".to_string(),
+            tool_name: tool_name.to_string(),
+            decision: "allow_once".to_string(),
+            source: "user".to_string(),
+        }
+    }
+
+    #[test]
+    fn entries_are_chained() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+
+        let first = log.append(Some("s1"), decision("a")).unwrap();
+        let second = log.append(Some("s2"), decision("b")).unwrap();
+
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(second.seq, 1);
+        assert_eq!(log.export(Some("s2")).unwrap(), vec![second]);
+        assert!(log.verify().unwrap().valid);
+    }
+
+    #[test]
+    fn chain_resumes_after_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let first = AuditLog::new(path.clone())
+            .append(None, decision("a"))
+            .unwrap();
+
+        let reopened = AuditLog::new(path);
+        let second = reopened.append(None, decision("b")).unwrap();
+        assert_eq!(second.seq, 1);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(reopened.verify().unwrap().entries, 2);
+    }
+
+    #[test]
+    fn interleaved_writers_keep_one_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let first = AuditLog::new(path.clone());
+        let second = AuditLog::new(path);
+
+        first.append(None, decision("a")).unwrap();
+        second.append(None, decision("b")).unwrap();
+        let last = first.append(None, decision("c")).unwrap();
+
+        assert_eq!(last.seq, 2);
+        let verification = second.verify().unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.entries, 3);
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLog::new(path.clone());
+        for tool in ["a", "b", "c"] {
+            log.append(None, decision(tool)).unwrap();
+        }
+
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replacen("\"b\"", "\"x\"", 1)).unwrap();
+        let result = log.verify().unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.first_invalid_seq, Some(1));
+
+        let lines: Vec<&str> = content.lines().collect();
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let result = log.verify().unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.first_invalid_seq, Some(1));
+    }
+}
@@ -1,7 +1,11 @@
+pub mod audit;
 pub mod classification_client;
 pub mod patterns;
+pub mod pii;
 pub mod scanner;
+pub mod secrets;
 pub mod security_inspector;
+pub mod signing;
 
 use crate::config::Config;
 use crate::conversation::message::{Message, ToolRequest};
@@ -0,0 +1,447 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_stream::try_stream;
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use rmcp::model::RawContent;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::conversation::message::{Message, MessageContent};
+use crate::providers::base::MessageStream;
+
+const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+/// Longest streamed text held back because it may be the start of a pseudonym
+const MAX_HELD_BYTES: usize = 64;
+const PHONE_PATTERN: &str = r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-])\d{3}[\s.-]\d{4}\b|\+\d{1,3}(?:[\s.-]\d{2,4}){3,4}\b";
+
+lazy_static! {
+    static ref PSEUDONYM: Regex = Regex::new(r"\[([A-Z][A-Z0-9_]*)_(\d+)\]").unwrap();
+}
+
+/// Built-in detectors that can be switched on in `GOOSE_PII_REDACTION`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiDetector {
+    Email,
+    Phone,
+    /// Matches the names listed under `names`
+    Name,
+}
+
+/// A user-defined detector, e.g. for customer IDs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPiiPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+/// `GOOSE_PII_REDACTION` settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiRedactionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_detectors")]
+    pub detectors: Vec<PiiDetector>,
+    /// Names to pseudonymize when the `name` detector is on
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub custom: Vec<CustomPiiPattern>,
+}
+
+fn default_detectors() -> Vec<PiiDetector> {
+    vec![PiiDetector::Email, PiiDetector::Phone]
+}
+
+impl Default for PiiRedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            detectors: default_detectors(),
+            names: Vec::new(),
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// Reversible mapping between pseudonyms and original values for one provider request.
+/// Kept only in memory: the session history holds the original values and is pseudonymized
+/// afresh for every request, in the same order, so pseudonyms stay stable without a copy of
+/// the originals on disk.
+#[derive(Debug, Default)]
+pub struct PiiVault {
+    /// Pseudonym -> original value
+    entries: BTreeMap<String, String>,
+    reverse: HashMap<String, String>,
+}
+
+impl PiiVault {
+    /// Return the stable pseudonym for a value, allocating one on first sight
+    fn pseudonym(&mut self, label: &str, value: &str) -> String {
+        if let Some(token) = self.reverse.get(value) {
+            return token.clone();
+        }
+        let prefix = format!("[{}_", label);
+        let next = self
+            .entries
+            .keys()
+            .filter(|token| token.starts_with(&prefix))
+            .count()
+            + 1;
+        let token = format!("[{}_{}]", label, next);
+        self.entries.insert(token.clone(), value.to_string());
+        self.reverse.insert(value.to_string(), token.clone());
+        token
+    }
+
+    pub fn restore(&self, text: &str) -> String {
+        if self.entries.is_empty() {
+            return text.to_string();
+        }
+        PSEUDONYM
+            .replace_all(text, |caps: &Captures| {
+                self.entries
+                    .get(&caps[0])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    fn restore_json(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => *s = self.restore(s),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.restore_json(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.restore_json(v)),
+            _ => {}
+        }
+    }
+
+    /// Put original values back into a model response so the user and tools see real data
+    pub fn restore_message(&self, mut message: Message) -> Message {
+        if self.entries.is_empty() {
+            return message;
+        }
+        for content in message.content.iter_mut() {
+            match content {
+                MessageContent::Text(text) => text.text = self.restore(&text.text),
+                MessageContent::ToolRequest(request) => {
+                    if let Ok(call) = request.tool_call.as_mut() {
+                        if let Some(arguments) = call.arguments.as_mut() {
+                            arguments.values_mut().for_each(|v| self.restore_json(v));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        message
+    }
+}
+
+/// Whether streamed text ending in `tail`, which starts with `[`, may still become a pseudonym
+fn may_be_pseudonym(tail: &str) -> bool {
+    tail.len() < MAX_HELD_BYTES
+        && tail[1..]
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Restores streamed text, holding back a trailing piece that may be the start of a
+/// pseudonym split across chunks until the rest of it arrives
+#[derive(Debug, Default)]
+struct StreamRestorer {
+    held: String,
+    /// The message the held text belongs to, without its content
+    message: Option<Message>,
+}
+
+impl StreamRestorer {
+    fn chunk(&self, text: &str, vault: &PiiVault) -> Option<Message> {
+        let mut message = self.message.clone()?;
+        message.content = vec![MessageContent::text(vault.restore(text))];
+        Some(message)
+    }
+
+    /// Whether `message` continues the message being restored
+    fn continues(&self, message: &Message) -> bool {
+        self.message
+            .as_ref()
+            .is_none_or(|held| held.id == message.id && held.role == message.role)
+    }
+
+    /// Add a text-only message. Returns what of the text so far can be sent.
+    fn push(&mut self, message: Message, vault: &PiiVault) -> Option<Message> {
+        for content in &message.content {
+            if let MessageContent::Text(text) = content {
+                self.held.push_str(&text.text);
+            }
+        }
+        self.message = Some(Message {
+            content: Vec::new(),
+            ..message
+        });
+
+        let cut = match self.held.rfind('[') {
+            Some(open) if may_be_pseudonym(&self.held[open..]) => open,
+            _ => self.held.len(),
+        };
+        if cut == 0 {
+            return None;
+        }
+        let released: String = self.held.drain(..cut).collect();
+        self.chunk(&released, vault)
+    }
+
+    /// The text held back, once nothing more of it is coming
+    fn finish(&mut self, vault: &PiiVault) -> Option<Message> {
+        let rest = std::mem::take(&mut self.held);
+        let chunk = if rest.is_empty() {
+            None
+        } else {
+            self.chunk(&rest, vault)
+        };
+        self.message = None;
+        chunk
+    }
+}
+
+fn is_text_only(message: &Message) -> bool {
+    !message.content.is_empty()
+        && message
+            .content
+            .iter()
+            .all(|content| matches!(content, MessageContent::Text(_)))
+}
+
+/// Put original values back into a provider stream. Streamed text is restored at boundaries
+/// where no pseudonym is cut in two; other content is restored as it comes.
+pub fn restore_stream(mut stream: MessageStream, vault: PiiVault) -> MessageStream {
+    Box::pin(try_stream! {
+        let mut restorer = StreamRestorer::default();
+        while let Some(item) = stream.next().await {
+            let (message, usage) = item?;
+            match message {
+                Some(message) if is_text_only(&message) => {
+                    if !restorer.continues(&message) {
+                        if let Some(rest) = restorer.finish(&vault) {
+                            yield (Some(rest), None);
+                        }
+                    }
+                    let chunk = restorer.push(message, &vault);
+                    if chunk.is_some() || usage.is_some() {
+                        yield (chunk, usage);
+                    }
+                }
+                Some(message) => {
+                    if let Some(rest) = restorer.finish(&vault) {
+                        yield (Some(rest), None);
+                    }
+                    yield (Some(vault.restore_message(message)), usage);
+                }
+                None => yield (None, usage),
+            }
+        }
+        if let Some(rest) = restorer.finish(&vault) {
+            yield (Some(rest), None);
+        }
+    })
+}
+
+/// Detects PII in provider-bound content and replaces it with stable pseudonyms
+pub struct PiiRedactor {
+    detectors: Vec<(String, Regex)>,
+}
+
+impl PiiRedactor {
+    pub fn new(config: &PiiRedactionConfig) -> Self {
+        let mut detectors = Vec::new();
+        for detector in &config.detectors {
+            let (label, pattern) = match detector {
+                PiiDetector::Email => ("EMAIL", EMAIL_PATTERN.to_string()),
+                PiiDetector::Phone => ("PHONE", PHONE_PATTERN.to_string()),
+                PiiDetector::Name => {
+                    if config.names.is_empty() {
+                        continue;
+                    }
+                    let names = config
+                        .names
+                        .iter()
+                        .map(|n| regex::escape(n.trim()))
+                        .collect::<Vec<_>>()
+                        .join("|");
+                    ("NAME", format!(r"(?i)\b(?:{})\b", names))
+                }
+            };
+            if let Ok(regex) = Regex::new(&pattern) {
+                detectors.push((label.to_string(), regex));
+            }
+        }
+        for custom in &config.custom {
+            match Regex::new(&custom.pattern) {
+                Ok(regex) => detectors.push((custom.name.to_uppercase(), regex)),
+                Err(e) => tracing::warn!("Ignoring invalid PII pattern '{}': {}", custom.name, e),
+            }
+        }
+        Self { detectors }
+    }
+
+    /// Build a redactor from `GOOSE_PII_REDACTION`, or None when redaction is off
+    pub fn from_config() -> Option<Self> {
+        let config = Config::global()
+            .get_param::<PiiRedactionConfig>("GOOSE_PII_REDACTION")
+            .unwrap_or_default();
+        config.enabled.then(|| Self::new(&config))
+    }
+
+    pub fn scrub(&self, text: &str, vault: &mut PiiVault) -> String {
+        let mut scrubbed = text.to_string();
+        for (label, regex) in &self.detectors {
+            if !regex.is_match(&scrubbed) {
+                continue;
+            }
+            scrubbed = regex
+                .replace_all(&scrubbed, |caps: &Captures| {
+                    vault.pseudonym(label, &caps[0])
+                })
+                .into_owned();
+        }
+        scrubbed
+    }
+
+    fn scrub_json(&self, value: &mut serde_json::Value, vault: &mut PiiVault) {
+        match value {
+            serde_json::Value::String(s) => *s = self.scrub(s, vault),
+            serde_json::Value::Array(items) => {
+                for item in items {
+                    self.scrub_json(item, vault);
+                }
+            }
+            serde_json::Value::Object(map) => {
+                for item in map.values_mut() {
+                    self.scrub_json(item, vault);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Pseudonymize text, tool arguments and tool results in a message
+    pub fn scrub_message(&self, message: &Message, vault: &mut PiiVault) -> Message {
+        let mut message = message.clone();
+        for content in message.content.iter_mut() {
+            match content {
+                MessageContent::Text(text) => text.text = self.scrub(&text.text, vault),
+                MessageContent::ToolRequest(request) => {
+                    if let Ok(call) = request.tool_call.as_mut() {
+                        if let Some(arguments) = call.arguments.as_mut() {
+                            for value in arguments.values_mut() {
+                                self.scrub_json(value, vault);
+                            }
+                        }
+                    }
+                }
+                MessageContent::ToolResponse(response) => {
+                    if let Ok(result) = std::sync::Arc::make_mut(response).tool_result.as_mut() {
+                        for item in result.content.iter_mut() {
+                            if let RawContent::Text(text) = &mut item.raw {
+                                text.text = self.scrub(&text.text, vault);
+                            }
+                        }
+                        if let Some(structured) = result.structured_content.as_mut() {
+                            self.scrub_json(structured, vault);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{CallToolRequestParams, CallToolResult, Content};
+    use rmcp::object;
+
+    fn redactor() -> PiiRedactor {
+        PiiRedactor::new(&PiiRedactionConfig {
+            enabled: true,
+            detectors: vec![PiiDetector::Email, PiiDetector::Phone, PiiDetector::Name],
+            names: vec!["Jane Doe".to_string()],
+            custom: vec![CustomPiiPattern {
+                name: "customer_id".to_string(),
+                pattern: r"CUST-\d{6}".to_string(),
+            }],
+        })
+    }
+
+    #[test]
+    fn pseudonyms_are_stable_and_reversible() {
+        let redactor = redactor();
+        let mut vault = PiiVault::default();
+        let text = "Contact Jane Doe at jane@example.com or (555) 123-4567 about CUST-004211. \
+                    Again: jane@example.com";
+        let scrubbed = redactor.scrub(text, &mut vault);
+
+        assert_eq!(
+            scrubbed,
+            "Contact [NAME_1] at [EMAIL_1] or [PHONE_1] about [CUSTOMER_ID_1]. \
+             Again: [EMAIL_1]"
+        );
+        assert_eq!(vault.restore(&scrubbed), text);
+    }
+
+    #[test]
+    fn pseudonyms_split_across_chunks_are_restored() {
+        let redactor = redactor();
+        let mut vault = PiiVault::default();
+        redactor.scrub("jane@example.com", &mut vault);
+
+        let mut restorer = StreamRestorer::default();
+        let mut out: Vec<String> = ["Mail [EMA", "IL_1] now, [x", "] and [PHONE_"]
+            .iter()
+            .filter_map(|piece| restorer.push(Message::assistant().with_text(*piece), &vault))
+            .map(|m| m.as_concat_text())
+            .collect();
+        out.extend(restorer.finish(&vault).map(|m| m.as_concat_text()));
+        assert_eq!(
+            out,
+            ["Mail ", "jane@example.com now, [x", "] and ", "[PHONE_"]
+        );
+    }
+
+    #[test]
+    fn leaves_code_untouched() {
+        let redactor = redactor();
+        let mut vault = PiiVault::default();
+        let code = "let timeout = 30000; version = \"1.2.3\"; port 8080";
+        assert_eq!(redactor.scrub(code, &mut vault), code);
+    }
+
+    #[test]
+    fn scrubs_tool_results_and_restores_tool_calls() {
+        let redactor = redactor();
+        let mut vault = PiiVault::default();
+        let response = Message::user().with_tool_response(
+            "1",
+            Ok(CallToolResult::success(vec![Content::text(
+                "owner: jane@example.com",
+            )])),
+        );
+        let scrubbed = redactor.scrub_message(&response, &mut vault);
+        assert!(!format!("{:?}", scrubbed).contains("jane@example.com"));
+
+        let request = Message::assistant().with_tool_request(
+            "2",
+            Ok(CallToolRequestParams::new("shell")
+                .with_arguments(object!({"command": "mail [EMAIL_1]"}))),
+        );
+        let restored = vault.restore_message(request);
+        assert!(format!("{:?}", restored).contains("mail jane@example.com"));
+    }
+}
@@ -0,0 +1,437 @@
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::config::paths::Paths;
+use crate::config::Config;
+
+/// Detached signatures live next to the signed file, e.g. `settings.json.minisig`
+pub const SIGNATURE_EXTENSION: &str = "minisig";
+
+const SIGNATURE_ALGORITHM: &[u8; 2] = b"Ed";
+const PREHASHED_SIGNATURE_ALGORITHM: &[u8; 2] = b"ED";
+const TRUSTED_COMMENT_PREFIX: &str = "trusted comment: ";
+
+/// What to do with project-provided files that are not signed by a trusted key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignaturePolicy {
+    #[default]
+    Off,
+    /// Load the file but log a warning
+    Warn,
+    /// Refuse to load the file
+    Enforce,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Verified { key_id: String },
+    Unsigned,
+    Invalid(String),
+}
+
+/// An ed25519 public key in minisign format
+#[derive(Debug, Clone)]
+pub struct TrustedKey {
+    key_id: [u8; 8],
+    public_key: [u8; 32],
+}
+
+impl TrustedKey {
+    /// Parse a minisign public key, either the bare base64 line or the full `.pub` file
+    pub fn parse(encoded: &str) -> Result<Self> {
+        let line = encoded
+            .lines()
+            .map(str::trim)
+            .rfind(|l| !l.is_empty() && !l.starts_with("untrusted comment:"))
+            .ok_or_else(|| anyhow!("Empty public key"))?;
+        let bytes = STANDARD.decode(line)?;
+        if bytes.len() != 42 || &bytes[..2] != SIGNATURE_ALGORITHM {
+            bail!("Not a minisign ed25519 public key");
+        }
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&bytes[2..10]);
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&bytes[10..]);
+        Ok(Self { key_id, public_key })
+    }
+
+    pub fn key_id(&self) -> String {
+        // minisign displays key ids as little-endian hex
+        self.key_id
+            .iter()
+            .rev()
+            .map(|b| format!("{:02X}", b))
+            .collect()
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(message, signature)
+            .is_ok()
+    }
+}
+
+/// Verifies detached minisign signatures on project-provided hooks, recipes and extension
+/// definitions against the keys in the global config
+pub struct SignatureVerifier {
+    policy: SignaturePolicy,
+    keys: Vec<TrustedKey>,
+}
+
+impl SignatureVerifier {
+    pub fn new(policy: SignaturePolicy, keys: Vec<TrustedKey>) -> Self {
+        Self { policy, keys }
+    }
+
+    /// Reads `GOOSE_TRUSTED_SIGNING_KEYS` and `GOOSE_SIGNATURE_POLICY`.
+    /// Configuring keys without a policy defaults to `warn`.
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        let keys: Vec<TrustedKey> = config
+            .get_param::<Vec<String>>("GOOSE_TRUSTED_SIGNING_KEYS")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|key| match TrustedKey::parse(key) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid trusted signing key: {}", e);
+                    None
+                }
+            })
+            .collect();
+        let policy = config
+            .get_param::<SignaturePolicy>("GOOSE_SIGNATURE_POLICY")
+            .unwrap_or(if keys.is_empty() {
+                SignaturePolicy::Off
+            } else {
+                SignaturePolicy::Warn
+            });
+        Self::new(policy, keys)
+    }
+
+    pub fn policy(&self) -> SignaturePolicy {
+        self.policy
+    }
+
+    pub fn signature_path(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".");
+        name.push(SIGNATURE_EXTENSION);
+        PathBuf::from(name)
+    }
+
+    pub fn verify(&self, path: &Path, content: &[u8]) -> SignatureStatus {
+        let signature = std::fs::read_to_string(Self::signature_path(path)).ok();
+        self.verify_detached(content, signature.as_deref())
+    }
+
+    /// Verify content against the text of its minisign signature, if it has one
+    pub fn verify_detached(&self, content: &[u8], signature: Option<&str>) -> SignatureStatus {
+        let Some(signature) = signature else {
+            return SignatureStatus::Unsigned;
+        };
+        match self.verify_signature(content, signature) {
+            Ok(key_id) => SignatureStatus::Verified { key_id },
+            Err(e) => SignatureStatus::Invalid(e.to_string()),
+        }
+    }
+
+    fn verify_signature(&self, content: &[u8], signature_file: &str) -> Result<String> {
+        let mut lines = signature_file.lines().map(str::trim);
+        let _untrusted_comment = lines.next();
+        let signature = STANDARD.decode(lines.next().unwrap_or_default())?;
+        if signature.len() != 74 {
+            bail!("Malformed signature");
+        }
+        if &signature[..2] == PREHASHED_SIGNATURE_ALGORITHM {
+            bail!("Prehashed signatures are not supported; sign with `minisign -S -l`");
+        }
+        if &signature[..2] != SIGNATURE_ALGORITHM {
+            bail!("Unsupported signature algorithm");
+        }
+
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.key_id == signature[2..10])
+            .ok_or_else(|| anyhow!("Signed with an untrusted key"))?;
+        let signature_bytes = &signature[10..];
+        if !key.verify(content, signature_bytes) {
+            bail!("Signature does not match file contents");
+        }
+
+        // The trusted comment is covered by a second signature over signature || comment
+        if let Some(comment_line) = lines.next() {
+            let comment = comment_line
+                .strip_prefix(TRUSTED_COMMENT_PREFIX)
+                .ok_or_else(|| anyhow!("Malformed trusted comment"))?;
+            let global_signature = STANDARD.decode(lines.next().unwrap_or_default())?;
+            let mut signed = signature_bytes.to_vec();
+            signed.extend_from_slice(comment.as_bytes());
+            if !key.verify(&signed, &global_signature) {
+                bail!("Trusted comment signature does not match");
+            }
+        }
+
+        Ok(key.key_id())
+    }
+
+    /// Apply the policy to a file about to be loaded. Returns an error if it must be refused.
+    pub fn check(&self, path: &Path, content: &[u8], kind: &str) -> Result<()> {
+        if self.policy == SignaturePolicy::Off {
+            return Ok(());
+        }
+        self.apply_policy(
+            self.verify(path, content),
+            &path.display().to_string(),
+            kind,
+        )
+    }
+
+    /// Like `check`, for content that did not come from a file, e.g. a download. `source`
+    /// names where it came from in messages.
+    pub fn check_detached(
+        &self,
+        source: &str,
+        content: &[u8],
+        signature: Option<&str>,
+        kind: &str,
+    ) -> Result<()> {
+        if self.policy == SignaturePolicy::Off {
+            return Ok(());
+        }
+        self.apply_policy(self.verify_detached(content, signature), source, kind)
+    }
+
+    fn apply_policy(&self, status: SignatureStatus, source: &str, kind: &str) -> Result<()> {
+        let problem = match status {
+            SignatureStatus::Verified { key_id } => {
+                tracing::debug!("Verified {} {} signed by {}", kind, source, key_id);
+                return Ok(());
+            }
+            SignatureStatus::Unsigned => "is not signed".to_string(),
+            SignatureStatus::Invalid(reason) => format!("has an invalid signature: {}", reason),
+        };
+
+        match self.policy {
+            SignaturePolicy::Enforce => Err(anyhow!(
+                "Refusing to load {} {}: file {}",
+                kind,
+                source,
+                problem
+            )),
+            _ => {
+                tracing::warn!("Loading {} {} although it {}", kind, source, problem);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Check a project-provided file against the signature policy. Files in the goose config
+/// directory belong to the user and are always trusted.
+pub fn check_project_file(path: &Path, content: &[u8], kind: &str) -> Result<()> {
+    let config_dir = Paths::config_dir();
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if canonical.starts_with(config_dir.canonicalize().unwrap_or(config_dir)) {
+        return Ok(());
+    }
+    SignatureVerifier::from_config().check(path, content, kind)
+}
+
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sort_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(sort_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// The bytes an extension definition is signed as: compact JSON with sorted keys and no
+/// trailing newline, the output of `jq -cSj`
+pub fn canonical_json(value: &Value) -> Vec<u8> {
+    serde_json::to_vec(&sort_keys(value)).unwrap_or_default()
+}
+
+/// Check an extension definition about to be installed against the signature policy.
+/// `signature` is the text of a minisign signature over its [`canonical_json`].
+pub fn check_extension_definition(
+    name: &str,
+    definition: &Value,
+    signature: Option<&str>,
+) -> Result<()> {
+    SignatureVerifier::from_config().check_detached(
+        &format!("extension {}", name),
+        &canonical_json(definition),
+        signature,
+        "extension definition",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn keypair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    fn public_key(pair: &Ed25519KeyPair) -> String {
+        let mut bytes = SIGNATURE_ALGORITHM.to_vec();
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(pair.public_key().as_ref());
+        format!(
+            "untrusted comment: minisign public key\n{}",
+            STANDARD.encode(bytes)
+        )
+    }
+
+    fn sign(pair: &Ed25519KeyPair, content: &[u8]) -> String {
+        let signature = pair.sign(content);
+        let mut bytes = SIGNATURE_ALGORITHM.to_vec();
+        bytes.extend_from_slice(&KEY_ID);
+        bytes.extend_from_slice(signature.as_ref());
+
+        let comment = "timestamp:0";
+        let mut global = signature.as_ref().to_vec();
+        global.extend_from_slice(comment.as_bytes());
+        format!(
+            "untrusted comment: signature\n{}\n{}{}\n{}\n",
+            STANDARD.encode(bytes),
+            TRUSTED_COMMENT_PREFIX,
+            comment,
+            STANDARD.encode(pair.sign(&global).as_ref())
+        )
+    }
+
+    fn write_signed(dir: &Path, content: &str, pair: &Ed25519KeyPair) -> PathBuf {
+        let path = dir.join("settings.json");
+        std::fs::write(&path, content).unwrap();
+        std::fs::write(
+            SignatureVerifier::signature_path(&path),
+            sign(pair, content.as_bytes()),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn verifies_signed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let pair = keypair();
+        let path = write_signed(dir.path(), "{}", &pair);
+        let verifier = SignatureVerifier::new(
+            SignaturePolicy::Enforce,
+            vec![TrustedKey::parse(&public_key(&pair)).unwrap()],
+        );
+
+        assert_eq!(
+            verifier.verify(&path, b"{}"),
+            SignatureStatus::Verified {
+                key_id: "0807060504030201".to_string()
+            }
+        );
+        assert!(verifier.check(&path, b"{}", "hooks config").is_ok());
+    }
+
+    #[test]
+    fn enforce_refuses_modified_and_unsigned_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let pair = keypair();
+        let path = write_signed(dir.path(), "{}", &pair);
+        let verifier = SignatureVerifier::new(
+            SignaturePolicy::Enforce,
+            vec![TrustedKey::parse(&public_key(&pair)).unwrap()],
+        );
+
+        let tampered = br#"{"hooks": {}}"#;
+        assert!(matches!(
+            verifier.verify(&path, tampered),
+            SignatureStatus::Invalid(_)
+        ));
+        assert!(verifier.check(&path, tampered, "hooks config").is_err());
+
+        let unsigned = dir.path().join("recipe.yaml");
+        assert_eq!(verifier.verify(&unsigned, b""), SignatureStatus::Unsigned);
+        assert!(verifier.check(&unsigned, b"", "recipe").is_err());
+    }
+
+    #[test]
+    fn untrusted_key_is_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_signed(dir.path(), "{}", &keypair());
+        let other = keypair();
+        let mut other_key = TrustedKey::parse(&public_key(&other)).unwrap();
+        other_key.key_id = [9; 8];
+        let verifier = SignatureVerifier::new(SignaturePolicy::Enforce, vec![other_key]);
+
+        assert_eq!(
+            verifier.verify(&path, b"{}"),
+            SignatureStatus::Invalid("Signed with an untrusted key".to_string())
+        );
+    }
+
+    #[test]
+    fn extension_definitions_are_signed_in_canonical_form() {
+        let pair = keypair();
+        let verifier = SignatureVerifier::new(
+            SignaturePolicy::Enforce,
+            vec![TrustedKey::parse(&public_key(&pair)).unwrap()],
+        );
+        let signed = serde_json::json!({"type": "stdio", "cmd": "npx", "args": ["-y", "pkg"]});
+        let signature = sign(&pair, &canonical_json(&signed));
+        assert_eq!(
+            canonical_json(&signed),
+            br#"{"args":["-y","pkg"],"cmd":"npx","type":"stdio"}"#
+        );
+
+        let reordered = serde_json::json!({"args": ["-y", "pkg"], "type": "stdio", "cmd": "npx"});
+        assert!(verifier
+            .check_detached(
+                "extension pkg",
+                &canonical_json(&reordered),
+                Some(&signature),
+                "extension definition"
+            )
+            .is_ok());
+
+        let tampered = serde_json::json!({"type": "stdio", "cmd": "curl", "args": ["-y", "pkg"]});
+        assert!(verifier
+            .check_detached(
+                "extension pkg",
+                &canonical_json(&tampered),
+                Some(&signature),
+                "extension definition"
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn warn_and_off_policies_load_unsigned_files() {
+        let path = Path::new("/nonexistent/settings.json");
+        for policy in [SignaturePolicy::Warn, SignaturePolicy::Off] {
+            let verifier = SignatureVerifier::new(policy, vec![]);
+            assert!(verifier.check(path, b"{}", "hooks config").is_ok());
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Editing a past prompt and regenerating an answer without losing the original.
+//!
+//! Both start a branch: a copy of the session rewound to just before the prompt, which the
+//! client then sends again, edited or as it was. The original session is left as it was.
+//! A branch can be given its own model or temperature; it's saved with the branch, so the
+//! provider the agent restores for it is the one asked for, and the conversation the
+//! provider sees is the rewound one.
+
+use crate::conversation::message::{Message, MessageContent};
+use crate::conversation::Conversation;
+use crate::model::ModelConfig;
+use crate::session::extension_data::ExtensionState;
+use crate::session::{Session, SessionManager};
+use anyhow::{anyhow, Result};
+use rmcp::model::Role;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchReason {
+    /// A prompt was edited and sent again
+    Edit,
+    /// The last answer was generated again
+    Regenerate,
+}
+
+/// Where a branch came from, saved in the branch's extension data
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchState {
+    pub parent_session_id: String,
+    /// Number of messages of the parent the branch kept
+    pub message_count: usize,
+    pub reason: BranchReason,
+}
+
+impl ExtensionState for BranchState {
+    const EXTENSION_NAME: &'static str = "branch";
+    const VERSION: &'static str = "v0";
+}
+
+/// Model settings for a branch; unset fields keep the parent's
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchModel {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+impl BranchModel {
+    fn is_empty(&self) -> bool {
+        self.provider.is_none() && self.model.is_none() && self.temperature.is_none()
+    }
+}
+
+/// A new branch and the prompt to send in it
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub session: Session,
+    pub prompt: Message,
+}
+
+/// `original` with its text replaced by `text`. Attachments and other content stay.
+fn edited_prompt(original: &Message, text: &str) -> Message {
+    let mut prompt = Message::user().with_text(text);
+    for content in &original.content {
+        if !matches!(content, MessageContent::Text(_)) {
+            prompt = prompt.with_content(content.clone());
+        }
+    }
+    prompt
+}
+
+impl SessionManager {
+    async fn branch(
+        &self,
+        parent: &Session,
+        messages: &[Message],
+        reason: BranchReason,
+        model: &BranchModel,
+    ) -> Result<Session> {
+        let session = self.copy_session(&parent.id, parent.name.clone()).await?;
+
+        let mut extension_data = session.extension_data.clone();
+        BranchState {
+            parent_session_id: parent.id.clone(),
+            message_count: messages.len(),
+            reason,
+        }
+        .to_extension_data(&mut extension_data)?;
+        let mut update = self.update(&session.id).extension_data(extension_data);
+
+        if !model.is_empty() {
+            let provider = model
+                .provider
+                .clone()
+                .or_else(|| parent.provider_name.clone())
+                .ok_or_else(|| anyhow!("Session {} has no provider to branch", parent.id))?;
+            let mut model_config = match (&model.model, &parent.model_config) {
+                (Some(name), _) => ModelConfig::new(name)?.with_canonical_limits(&provider),
+                (None, Some(config)) => config.clone(),
+                (None, None) => return Err(anyhow!("Session {} has no model", parent.id)),
+            };
+            if model.temperature.is_some() {
+                model_config = model_config.with_temperature(model.temperature);
+            }
+            update = update.provider_name(provider).model_config(model_config);
+        }
+        update.apply().await?;
+
+        self.replace_conversation(
+            &session.id,
+            &Conversation::new_unvalidated(messages.iter().cloned()),
+        )
+        .await?;
+        self.get_session(&session.id, false).await
+    }
+
+    /// Branch the session at a prompt the user sent, with its text replaced
+    pub async fn edit_message(
+        &self,
+        session_id: &str,
+        message_id: &str,
+        text: &str,
+        model: &BranchModel,
+    ) -> Result<Branch> {
+        let parent = self.get_session(session_id, true).await?;
+        let conversation = parent.conversation.clone().unwrap_or_default();
+        let messages = conversation.messages();
+        let index = messages
+            .iter()
+            .position(|m| m.id.as_deref() == Some(message_id))
+            .ok_or_else(|| anyhow!("Message {} not found in session {}", message_id, session_id))?;
+        let original = &messages[index];
+        if original.role != Role::User || original.is_tool_response() {
+            return Err(anyhow!("Only messages the user sent can be edited"));
+        }
+
+        let session = self
+            .branch(&parent, &messages[..index], BranchReason::Edit, model)
+            .await?;
+        Ok(Branch {
+            session,
+            prompt: edited_prompt(original, text),
+        })
+    }
+
+    /// Branch the session before its last turn, to answer the same prompt again
+    pub async fn regenerate(&self, session_id: &str, model: &BranchModel) -> Result<Branch> {
+        let parent = self.get_session(session_id, true).await?;
+        let conversation = parent.conversation.clone().unwrap_or_default();
+        let last = conversation
+            .turns()
+            .last()
+            .map(|turn| turn.id)
+            .ok_or_else(|| anyhow!("Session {} has nothing to regenerate", session_id))?;
+        let (before, prompt) = conversation
+            .before_turn(last)
+            .ok_or_else(|| anyhow!("Turn {} not found in session {}", last, session_id))?;
+        let prompt = prompt.ok_or_else(|| anyhow!("The last turn has no prompt to send again"))?;
+
+        let session = self
+            .branch(&parent, before.messages(), BranchReason::Regenerate, model)
+            .await?;
+        Ok(Branch {
+            session,
+            // Sent as a new message, in a turn of its own
+            prompt: Message {
+                content: prompt.content,
+                ..Message::user()
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::SessionType;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn branches_leave_the_parent_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let parent = sm
+            .create_session(PathBuf::from("/tmp"), "parent".into(), SessionType::User)
+            .await
+            .unwrap();
+        sm.update(&parent.id)
+            .provider_name("openai")
+            .model_config(ModelConfig::new("gpt-4o").unwrap())
+            .apply()
+            .await
+            .unwrap();
+        let conversation = Conversation::new_unvalidated(vec![
+            Message::user().with_text("one").with_id("u1").with_turn(1),
+            Message::assistant().with_text("first").with_turn(1),
+            Message::user().with_text("two").with_id("u2").with_turn(2),
+            Message::assistant().with_text("second").with_turn(2),
+        ]);
+        sm.replace_conversation(&parent.id, &conversation)
+            .await
+            .unwrap();
+
+        let edit = sm
+            .edit_message(&parent.id, "u2", "two, edited", &BranchModel::default())
+            .await
+            .unwrap();
+        assert_ne!(edit.session.id, parent.id);
+        assert_eq!(edit.session.message_count, 2);
+        assert_eq!(edit.prompt.as_concat_text(), "two, edited");
+        let state = BranchState::from_extension_data(&edit.session.extension_data).unwrap();
+        assert_eq!(state.parent_session_id, parent.id);
+        assert_eq!(state.reason, BranchReason::Edit);
+
+        let model = BranchModel {
+            temperature: Some(0.2),
+            ..Default::default()
+        };
+        let regenerated = sm.regenerate(&parent.id, &model).await.unwrap();
+        assert_eq!(regenerated.prompt.as_concat_text(), "two");
+        let model_config = regenerated.session.model_config.unwrap();
+        assert_eq!(model_config.model_name, "gpt-4o");
+        assert_eq!(model_config.temperature, Some(0.2));
+
+        let parent = sm.get_session(&parent.id, false).await.unwrap();
+        assert_eq!(parent.message_count, 4);
+        assert!(sm
+            .edit_message(&parent.id, "missing", "x", &BranchModel::default())
+            .await
+            .is_err());
+    }
+}
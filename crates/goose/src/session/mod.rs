@@ -1,3 +1,4 @@
+pub mod branch;
 mod chat_history_search;
 mod diagnostics;
 pub mod extension_data;
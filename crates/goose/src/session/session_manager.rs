@@ -1,28 +1,42 @@
+use crate::checkpoints::CheckpointStore;
 use crate::config::paths::Paths;
+use crate::config::Config;
 use crate::conversation::message::Message;
+use crate::conversation::turns::TurnSummary;
 use crate::conversation::Conversation;
 use crate::model::ModelConfig;
 use crate::providers::base::{Provider, MSG_COUNT_FOR_SESSION_NAME_GENERATION};
 use crate::recipe::Recipe;
 use crate::session::extension_data::ExtensionData;
+use crate::shutdown::ShutdownCoordinator;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use rmcp::model::Role;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Pool, Sqlite};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 use tracing::{info, warn};
 use utoipa::ToSchema;
 
-pub const CURRENT_SCHEMA_VERSION: i32 = 7;
+pub const CURRENT_SCHEMA_VERSION: i32 = 8;
 pub const SESSIONS_FOLDER: &str = "sessions";
 pub const DB_NAME: &str = "sessions.db";
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq, Default)]
+/// Sessions are named again every this many turns; unset or 0 keeps the first name
+pub const SESSION_NAME_INTERVAL_CONFIG_KEY: &str = "GOOSE_SESSION_NAME_INTERVAL";
+/// How long added messages may wait before they are written, so the messages of one agent
+/// step are saved in one transaction
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, ToSchema, JsonSchema, PartialEq, Eq, Default,
+)]
 #[serde(rename_all = "snake_case")]
 pub enum SessionType {
     #[default]
@@ -249,9 +263,9 @@ pub struct SessionManager {
 
 impl SessionManager {
     pub fn new(data_dir: PathBuf) -> Self {
-        Self {
-            storage: Arc::new(SessionStorage::new(data_dir)),
-        }
+        let storage = Arc::new(SessionStorage::new(data_dir));
+        ShutdownCoordinator::global().track_storage(&storage);
+        Self { storage }
     }
 
     pub fn instance() -> Self {
@@ -287,8 +301,59 @@ impl SessionManager {
         self.storage.apply_update(builder).await
     }
 
+    /// Number a new turn of the session. Messages added after it without a turn of their
+    /// own are stamped with it.
+    pub async fn begin_turn(&self, id: &str) -> Result<u32> {
+        let last = match self.storage.current_turn(id) {
+            Some(turn) => Some(turn),
+            None => self
+                .get_session(id, true)
+                .await?
+                .conversation
+                .and_then(|conversation| conversation.turns().last().map(|turn| turn.id)),
+        };
+        let turn = last.map_or(1, |turn| turn + 1);
+        self.storage.set_current_turn(id, turn);
+        Ok(turn)
+    }
+
+    /// Queue a message for the session. It is written within [`AUTOSAVE_INTERVAL`], at the
+    /// end of the turn, or before anything else reads or changes the sessions, whichever
+    /// comes first. Messages that fail to save stay queued and are retried; until they are
+    /// saved, adding a message reports the failure.
     pub async fn add_message(&self, id: &str, message: &Message) -> Result<()> {
-        self.storage.add_message(id, message).await
+        let stamped;
+        let message = match (message.metadata.turn, self.storage.current_turn(id)) {
+            (Some(turn), _) => {
+                self.storage.set_current_turn(id, turn);
+                message
+            }
+            (None, Some(turn)) => {
+                stamped = message.clone().with_turn(turn);
+                &stamped
+            }
+            (None, None) => message,
+        };
+        if self.storage.add_message(id, message)? {
+            let storage = Arc::clone(&self.storage);
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(AUTOSAVE_INTERVAL).await;
+                    if let Err(e) = storage.flush().await {
+                        warn!("Failed to save session messages: {}", e);
+                    }
+                    if storage.pending.lock().unwrap().is_empty() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Write the messages queued by [`Self::add_message`]
+    pub async fn flush(&self) -> Result<()> {
+        self.storage.flush().await
     }
 
     pub async fn replace_conversation(&self, id: &str, conversation: &Conversation) -> Result<()> {
@@ -304,7 +369,67 @@ impl SessionManager {
     }
 
     pub async fn delete_session(&self, id: &str) -> Result<()> {
-        self.storage.delete_session(id).await
+        let working_dir = self
+            .storage
+            .get_session(id, false)
+            .await
+            .ok()
+            .map(|session| session.working_dir);
+        self.storage.current_turns.lock().unwrap().remove(id);
+        self.storage.delete_session(id).await?;
+
+        if let Some(working_dir) = working_dir {
+            if let Some(store) = CheckpointStore::open(&working_dir, id).await {
+                if let Err(e) = store.delete().await {
+                    warn!("Failed to delete checkpoints of session {}: {}", id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Set one extension's state without rewriting the states of the others, so writers of
+    /// different extensions don't overwrite each other
+    pub async fn set_extension_state(
+        &self,
+        id: &str,
+        extension_name: &str,
+        version: &str,
+        state: &Value,
+    ) -> Result<()> {
+        self.storage
+            .set_extension_state(id, &format!("{}.{}", extension_name, version), state)
+            .await
+    }
+
+    pub async fn list_turns(&self, id: &str) -> Result<Vec<TurnSummary>> {
+        let session = self.get_session(id, true).await?;
+        Ok(session
+            .conversation
+            .map(|conversation| conversation.turns().iter().map(|t| t.summary()).collect())
+            .unwrap_or_default())
+    }
+
+    /// Remove a turn's messages from the session
+    pub async fn delete_turn(&self, id: &str, turn: u32) -> Result<()> {
+        let conversation = self.get_session(id, true).await?.conversation;
+        let remaining = conversation
+            .and_then(|conversation| conversation.without_turn(turn))
+            .ok_or_else(|| anyhow::anyhow!("Turn {} not found in session {}", turn, id))?;
+        self.replace_conversation(id, &remaining).await
+    }
+
+    /// Rewind the session to before a turn, returning the prompt that started it so it can
+    /// be sent again
+    pub async fn retry_turn(&self, id: &str, turn: u32) -> Result<Message> {
+        let conversation = self.get_session(id, true).await?.conversation;
+        let (before, prompt) = conversation
+            .and_then(|conversation| conversation.before_turn(turn))
+            .ok_or_else(|| anyhow::anyhow!("Turn {} not found in session {}", turn, id))?;
+        let prompt =
+            prompt.ok_or_else(|| anyhow::anyhow!("Turn {} has no prompt to retry", turn))?;
+        self.replace_conversation(id, &before).await?;
+        Ok(prompt)
     }
 
     pub async fn get_insights(&self) -> Result<SessionInsights> {
@@ -329,6 +454,9 @@ impl SessionManager {
             .await
     }
 
+    /// Name the session after its conversation, unless the user named it. New sessions are
+    /// named on each of their first messages, and renamed every GOOSE_SESSION_NAME_INTERVAL
+    /// turns after that when it is set.
     pub async fn maybe_update_name(&self, id: &str, provider: Arc<dyn Provider>) -> Result<()> {
         let session = self.get_session(id, true).await?;
 
@@ -345,13 +473,41 @@ impl SessionManager {
             .iter()
             .filter(|m| matches!(m.role, Role::User))
             .count();
+        let interval = Config::global()
+            .get_param::<usize>(SESSION_NAME_INTERVAL_CONFIG_KEY)
+            .unwrap_or(0);
+        let turns = conversation.turns().len();
 
-        if user_message_count <= MSG_COUNT_FOR_SESSION_NAME_GENERATION {
-            let name = provider.generate_session_name(id, &conversation).await?;
-            self.update(id).system_generated_name(name).apply().await
-        } else {
-            Ok(())
+        if user_message_count > MSG_COUNT_FOR_SESSION_NAME_GENERATION
+            && (interval == 0 || turns == 0 || !turns.is_multiple_of(interval))
+        {
+            return Ok(());
+        }
+        let name = provider.generate_session_name(id, &conversation).await?;
+        // The user may have renamed the session while the name was generated
+        if self.get_session(id, false).await?.user_set_name {
+            return Ok(());
+        }
+        self.update(id).system_generated_name(name).apply().await
+    }
+
+    /// Name the session after its conversation now, replacing a name the user gave it, so
+    /// that it is named automatically again from then on
+    pub async fn regenerate_name(&self, id: &str, provider: Arc<dyn Provider>) -> Result<String> {
+        let conversation = self
+            .get_session(id, true)
+            .await?
+            .conversation
+            .ok_or_else(|| anyhow::anyhow!("No messages found"))?;
+        let name = provider.generate_session_name(id, &conversation).await?;
+        if name.trim().is_empty() {
+            anyhow::bail!("The model didn't come up with a name");
         }
+        self.update(id)
+            .system_generated_name(name.clone())
+            .apply()
+            .await?;
+        Ok(name.trim().to_string())
     }
 
     pub async fn search_chat_history(
@@ -384,6 +540,73 @@ pub struct SessionStorage {
     pool: Pool<Sqlite>,
     initialized: tokio::sync::OnceCell<()>,
     session_dir: PathBuf,
+    /// Messages added since the last flush, by session, in the order they were added
+    pending: std::sync::Mutex<Vec<(String, StoredMessage)>>,
+    /// Held while pending messages are written, so readers wait for a flush in progress
+    flush_lock: tokio::sync::Mutex<()>,
+    /// Why the last flush failed, until one succeeds
+    flush_error: std::sync::Mutex<Option<String>>,
+    /// The last turn numbered in each session since it was loaded. Turn numbers only grow,
+    /// so a retried or deleted turn's number isn't reused while the session is open.
+    current_turns: std::sync::Mutex<HashMap<String, u32>>,
+}
+
+/// A message serialized for the messages table
+struct StoredMessage {
+    message_id: Option<String>,
+    role: &'static str,
+    content_json: String,
+    created_timestamp: i64,
+    metadata_json: String,
+    content_hash: String,
+}
+
+impl StoredMessage {
+    fn new(message: &Message) -> Result<Self> {
+        let role = role_to_string(&message.role);
+        let content_json = serde_json::to_string(&message.content)?;
+        let metadata_json = serde_json::to_string(&message.metadata)?;
+
+        let mut hasher = blake3::Hasher::new();
+        for part in [role, content_json.as_str(), metadata_json.as_str()] {
+            hasher.update(part.as_bytes());
+            hasher.update(&[0]);
+        }
+        hasher.update(&message.created.to_le_bytes());
+
+        Ok(Self {
+            message_id: message.id.clone(),
+            role,
+            content_json,
+            created_timestamp: message.created,
+            metadata_json,
+            content_hash: hasher.finalize().to_hex().to_string(),
+        })
+    }
+
+    async fn insert(&self, tx: &mut sqlx::Transaction<'_, Sqlite>, session_id: &str) -> Result<()> {
+        let message_id = self
+            .message_id
+            .clone()
+            .unwrap_or_else(|| format!("msg_{}_{}", session_id, uuid::Uuid::new_v4()));
+
+        sqlx::query(
+            r#"
+            INSERT INTO messages (message_id, session_id, role, content_json, created_timestamp, metadata_json, content_hash)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+        )
+        .bind(message_id)
+        .bind(session_id)
+        .bind(self.role)
+        .bind(&self.content_json)
+        .bind(self.created_timestamp)
+        .bind(&self.metadata_json)
+        .bind(&self.content_hash)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
 }
 
 fn role_to_string(role: &Role) -> &'static str {
@@ -507,10 +730,30 @@ impl SessionStorage {
             pool: Self::create_pool(&db_path),
             initialized: tokio::sync::OnceCell::new(),
             session_dir,
+            pending: std::sync::Mutex::new(Vec::new()),
+            flush_lock: tokio::sync::Mutex::new(()),
+            flush_error: std::sync::Mutex::new(None),
+            current_turns: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
+    fn current_turn(&self, session_id: &str) -> Option<u32> {
+        self.current_turns.lock().unwrap().get(session_id).copied()
+    }
+
+    fn set_current_turn(&self, session_id: &str, turn: u32) {
+        let mut turns = self.current_turns.lock().unwrap();
+        let current = turns.entry(session_id.to_string()).or_insert(turn);
+        *current = (*current).max(turn);
+    }
+
+    /// The pool, after writing any pending messages so reads and changes see them
     async fn pool(&self) -> Result<&Pool<Sqlite>> {
+        self.flush().await?;
+        self.ready_pool().await
+    }
+
+    async fn ready_pool(&self) -> Result<&Pool<Sqlite>> {
         self.initialized
             .get_or_try_init(|| async {
                 let schema_exists = sqlx::query_scalar::<_, bool>(
@@ -597,7 +840,8 @@ impl SessionStorage {
                 created_timestamp INTEGER NOT NULL,
                 timestamp TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
                 tokens INTEGER,
-                metadata_json TEXT
+                metadata_json TEXT,
+                content_hash TEXT
             )
         "#,
         )
@@ -887,6 +1131,17 @@ impl SessionStorage {
                     .execute(&mut **tx)
                     .await?;
             }
+            8 => {
+                // Existing rows keep a NULL hash and are rewritten once, the next time their
+                // conversation is replaced
+                sqlx::query(
+                    r#"
+                    ALTER TABLE messages ADD COLUMN content_hash TEXT
+                "#,
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
             _ => {
                 anyhow::bail!("Unknown migration version: {}", version);
             }
@@ -1082,6 +1337,23 @@ impl SessionStorage {
         Ok(())
     }
 
+    async fn set_extension_state(&self, session_id: &str, key: &str, state: &Value) -> Result<()> {
+        let pool = self.pool().await?;
+        let result = sqlx::query(
+            "UPDATE sessions SET extension_data = json_set(COALESCE(extension_data, '{}'), ?, json(?)), \
+             updated_at = datetime('now') WHERE id = ?",
+        )
+        .bind(format!("$.\"{}\"", key))
+        .bind(serde_json::to_string(state)?)
+        .bind(session_id)
+        .execute(pool)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(anyhow::anyhow!("Session not found"));
+        }
+        Ok(())
+    }
+
     async fn get_conversation(&self, session_id: &str) -> Result<Conversation> {
         let pool = self.pool().await?;
         let rows = sqlx::query_as::<_, (String, String, i64, Option<String>, Option<String>)>(
@@ -1120,36 +1392,65 @@ impl SessionStorage {
         Ok(Conversation::new_unvalidated(messages))
     }
 
-    async fn add_message(&self, session_id: &str, message: &Message) -> Result<()> {
-        let pool = self.pool().await?;
-        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
+    /// Queue a message, returning whether it is the first one since the last flush. The
+    /// message is queued even when an earlier flush failed, but the failure is returned.
+    fn add_message(&self, session_id: &str, message: &Message) -> Result<bool> {
+        let stored = StoredMessage::new(message)?;
+        let first = {
+            let mut pending = self.pending.lock().unwrap();
+            pending.push((session_id.to_string(), stored));
+            pending.len() == 1
+        };
+        if let Some(error) = self.flush_error.lock().unwrap().as_ref() {
+            anyhow::bail!("Failed to save session messages: {}", error);
+        }
+        Ok(first)
+    }
 
-        let metadata_json = serde_json::to_string(&message.metadata)?;
+    /// Write the queued messages in one transaction. If it fails they are queued again,
+    /// ahead of anything added meanwhile, for the next flush to retry.
+    pub async fn flush(&self) -> Result<()> {
+        let _guard = self.flush_lock.lock().await;
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return Ok(());
+        }
 
-        let message_id = message
-            .id
-            .clone()
-            .unwrap_or_else(|| format!("msg_{}_{}", session_id, uuid::Uuid::new_v4()));
+        let result = self.write_messages(&pending).await;
+        match &result {
+            Ok(()) => *self.flush_error.lock().unwrap() = None,
+            Err(e) => {
+                *self.flush_error.lock().unwrap() = Some(e.to_string());
+                self.pending.lock().unwrap().splice(0..0, pending);
+            }
+        }
+        result
+    }
 
-        sqlx::query(
-            r#"
-            INSERT INTO messages (message_id, session_id, role, content_json, created_timestamp, metadata_json)
-            VALUES (?, ?, ?, ?, ?, ?)
-        "#,
-        )
-        .bind(message_id)
-        .bind(session_id)
-        .bind(role_to_string(&message.role))
-        .bind(serde_json::to_string(&message.content)?)
-        .bind(message.created)
-        .bind(metadata_json)
-        .execute(&mut *tx)
-        .await?;
+    /// Insert messages in one transaction, dropping those of sessions deleted since they
+    /// were queued
+    async fn write_messages(&self, messages: &[(String, StoredMessage)]) -> Result<()> {
+        let pool = self.ready_pool().await?;
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
 
-        sqlx::query("UPDATE sessions SET updated_at = datetime('now') WHERE id = ?")
-            .bind(session_id)
-            .execute(&mut *tx)
-            .await?;
+        let session_ids: BTreeSet<&str> = messages.iter().map(|(id, _)| id.as_str()).collect();
+        let mut live = BTreeSet::new();
+        for session_id in session_ids {
+            let updated =
+                sqlx::query("UPDATE sessions SET updated_at = datetime('now') WHERE id = ?")
+                    .bind(session_id)
+                    .execute(&mut *tx)
+                    .await?;
+            if updated.rows_affected() > 0 {
+                live.insert(session_id);
+            }
+        }
+
+        for (session_id, message) in messages {
+            if live.contains(session_id.as_str()) {
+                message.insert(&mut tx, session_id).await?;
+            }
+        }
 
         tx.commit().await?;
         Ok(())
@@ -1160,36 +1461,47 @@ impl SessionStorage {
         session_id: &str,
         conversation: &Conversation,
     ) -> Result<()> {
-        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
-
-        sqlx::query("DELETE FROM messages WHERE session_id = ?")
-            .bind(session_id)
-            .execute(&mut *tx)
-            .await?;
+        let messages = conversation
+            .messages()
+            .iter()
+            .map(StoredMessage::new)
+            .collect::<Result<Vec<_>>>()?;
 
-        for message in conversation.messages() {
-            let metadata_json = serde_json::to_string(&message.metadata)?;
+        let mut tx = pool.begin_with("BEGIN IMMEDIATE").await?;
 
-            let message_id = message
-                .id
-                .clone()
-                .unwrap_or_else(|| format!("msg_{}_{}", session_id, uuid::Uuid::new_v4()));
+        // Keep the stored rows the new conversation starts with, so saving a long session
+        // after a turn only writes the messages that changed
+        let stored = sqlx::query_as::<_, (i64, i64, Option<String>, Option<String>)>(
+            "SELECT id, created_timestamp, message_id, content_hash FROM messages WHERE session_id = ? ORDER BY created_timestamp, id",
+        )
+        .bind(session_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        let unchanged = stored
+            .iter()
+            .zip(&messages)
+            .take_while(|((_, _, stored_id, stored_hash), message)| {
+                stored_hash.as_deref() == Some(message.content_hash.as_str())
+                    && message
+                        .message_id
+                        .as_ref()
+                        .is_none_or(|id| stored_id.as_ref() == Some(id))
+            })
+            .count();
 
+        if let Some((id, created_timestamp, _, _)) = stored.get(unchanged) {
             sqlx::query(
-                r#"
-            INSERT INTO messages (message_id, session_id, role, content_json, created_timestamp, metadata_json)
-            VALUES (?, ?, ?, ?, ?, ?)
-        "#,
+                "DELETE FROM messages WHERE session_id = ? AND (created_timestamp, id) >= (?, ?)",
             )
-            .bind(message_id)
             .bind(session_id)
-            .bind(role_to_string(&message.role))
-            .bind(serde_json::to_string(&message.content)?)
-            .bind(message.created)
-            .bind(metadata_json)
+            .bind(*created_timestamp)
+            .bind(*id)
             .execute(&mut *tx)
             .await?;
         }
+        for message in &messages[unchanged..] {
+            message.insert(&mut tx, session_id).await?;
+        }
 
         tx.commit().await?;
         Ok(())
@@ -1345,7 +1657,7 @@ impl SessionStorage {
         let new_session = self
             .create_session(
                 original_session.working_dir.clone(),
-                new_name,
+                new_name.clone(),
                 original_session.session_type,
             )
             .await?;
@@ -1357,6 +1669,11 @@ impl SessionStorage {
             .recipe(original_session.recipe)
             .user_recipe_values(original_session.user_recipe_values);
 
+        // A name the user gave stays theirs in the copy
+        if original_session.user_set_name {
+            builder = builder.user_provided_name(new_name);
+        }
+
         // Preserve provider and model config from original session
         if let Some(provider_name) = original_session.provider_name {
             builder = builder.provider_name(provider_name);
@@ -1438,8 +1755,9 @@ impl SessionStorage {
         let new_metadata = f(current_metadata);
         let metadata_json = serde_json::to_string(&new_metadata)?;
 
+        // The hash no longer matches; the row is rewritten if its conversation is replaced
         sqlx::query(
-            "UPDATE messages SET metadata_json = ? WHERE message_id = ? AND session_id = ?",
+            "UPDATE messages SET metadata_json = ?, content_hash = NULL WHERE message_id = ? AND session_id = ?",
         )
         .bind(metadata_json)
         .bind(message_id)
@@ -1636,6 +1954,251 @@ mod tests {
         assert_eq!(insights.total_tokens, expected_tokens as i64);
     }
 
+    async fn message_row_ids(sm: &SessionManager, session_id: &str) -> Vec<i64> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT id FROM messages WHERE session_id = ? ORDER BY created_timestamp, id",
+        )
+        .bind(session_id)
+        .fetch_all(sm.storage().pool().await.unwrap())
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_replace_conversation_keeps_unchanged_rows() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let session = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "incremental".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+
+        let message = |id: &str, text: &str| Message::user().with_text(text).with_id(id);
+
+        let first = vec![message("a", "one"), message("b", "two")];
+        sm.replace_conversation(&session.id, &Conversation::new_unvalidated(first.clone()))
+            .await
+            .unwrap();
+        let before = message_row_ids(&sm, &session.id).await;
+
+        let mut appended = first.clone();
+        appended.push(message("c", "three"));
+        sm.replace_conversation(&session.id, &Conversation::new_unvalidated(appended))
+            .await
+            .unwrap();
+        let after = message_row_ids(&sm, &session.id).await;
+        assert_eq!(after.len(), 3);
+        assert_eq!(after[..2], before[..]);
+
+        let edited = vec![first[0].clone(), message("b", "two, edited")];
+        sm.replace_conversation(&session.id, &Conversation::new_unvalidated(edited))
+            .await
+            .unwrap();
+        let after_edit = message_row_ids(&sm, &session.id).await;
+        assert_eq!(after_edit.len(), 2);
+        assert_eq!(after_edit[0], before[0]);
+
+        let loaded = sm.get_session(&session.id, true).await.unwrap();
+        let texts: Vec<String> = loaded
+            .conversation
+            .unwrap()
+            .messages()
+            .iter()
+            .map(|m| m.as_concat_text())
+            .collect();
+        assert_eq!(texts, vec!["one", "two, edited"]);
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_are_saved_before_reads() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let session = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "autosave".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+
+        for text in ["one", "two", "three"] {
+            sm.add_message(&session.id, &Message::user().with_text(text))
+                .await
+                .unwrap();
+        }
+        assert_eq!(sm.storage().pending.lock().unwrap().len(), 3);
+
+        let saved = sm.get_session(&session.id, true).await.unwrap();
+        assert_eq!(saved.conversation.unwrap().messages().len(), 3);
+        assert!(sm.storage().pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_of_deleted_sessions_do_not_block_others() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let kept = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "kept".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        let deleted = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "deleted".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        sm.delete_session(&deleted.id).await.unwrap();
+
+        sm.add_message(&deleted.id, &Message::user().with_text("late"))
+            .await
+            .unwrap();
+        sm.add_message(&kept.id, &Message::user().with_text("kept"))
+            .await
+            .unwrap();
+        sm.flush().await.unwrap();
+
+        assert!(sm.storage().pending.lock().unwrap().is_empty());
+        let saved = sm.get_session(&kept.id, true).await.unwrap();
+        assert_eq!(saved.conversation.unwrap().messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_messages_are_stamped_with_the_current_turn() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let session = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "turns".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+
+        for prompt in ["one", "two"] {
+            let turn = sm.begin_turn(&session.id).await.unwrap();
+            sm.add_message(
+                &session.id,
+                &Message::user().with_text(prompt).with_turn(turn),
+            )
+            .await
+            .unwrap();
+            sm.add_message(&session.id, &Message::assistant().with_text("ok"))
+                .await
+                .unwrap();
+        }
+        let turns = sm.list_turns(&session.id).await.unwrap();
+        assert_eq!(turns.iter().map(|t| t.turn).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(turns[1].message_count, 2);
+
+        let prompt = sm.retry_turn(&session.id, 1).await.unwrap();
+        assert_eq!(prompt.as_concat_text(), "one");
+        assert!(sm.list_turns(&session.id).await.unwrap().is_empty());
+        // Numbers of removed turns aren't reused
+        assert_eq!(sm.begin_turn(&session.id).await.unwrap(), 3);
+        assert!(sm.delete_turn(&session.id, 1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_user_names_outlast_automatic_naming() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let provider: Arc<dyn Provider> = Arc::new(crate::providers::fake::FakeProvider::new());
+        let session = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "naming".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+        sm.add_message(&session.id, &Message::user().with_text("fix the build"))
+            .await
+            .unwrap();
+
+        sm.maybe_update_name(&session.id, provider.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            sm.get_session(&session.id, false).await.unwrap().name,
+            "Fake session"
+        );
+
+        sm.update(&session.id)
+            .user_provided_name("Build fixes")
+            .apply()
+            .await
+            .unwrap();
+        sm.maybe_update_name(&session.id, provider.clone())
+            .await
+            .unwrap();
+        let renamed = sm.get_session(&session.id, false).await.unwrap();
+        assert_eq!(renamed.name, "Build fixes");
+        assert!(renamed.user_set_name);
+
+        let copy = sm.copy_session(&session.id, renamed.name).await.unwrap();
+        assert!(copy.user_set_name);
+
+        let name = sm.regenerate_name(&session.id, provider).await.unwrap();
+        assert_eq!(name, "Fake session");
+        assert!(
+            !sm.get_session(&session.id, false)
+                .await
+                .unwrap()
+                .user_set_name
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extension_states_are_set_independently() {
+        let temp_dir = TempDir::new().unwrap();
+        let sm = SessionManager::new(temp_dir.path().to_path_buf());
+        let session = sm
+            .create_session(
+                PathBuf::from("/tmp/test"),
+                "states".to_string(),
+                SessionType::User,
+            )
+            .await
+            .unwrap();
+
+        sm.set_extension_state(&session.id, "todo", "v0", &serde_json::json!({"items": 1}))
+            .await
+            .unwrap();
+        sm.set_extension_state(&session.id, "gemini_cli", "v1", &serde_json::json!("abc"))
+            .await
+            .unwrap();
+
+        let data = sm
+            .get_session(&session.id, false)
+            .await
+            .unwrap()
+            .extension_data;
+        assert_eq!(
+            data.get_extension_state("todo", "v0"),
+            Some(&serde_json::json!({"items": 1}))
+        );
+        assert_eq!(
+            data.get_extension_state("gemini_cli", "v1"),
+            Some(&serde_json::json!("abc"))
+        );
+        assert!(sm
+            .set_extension_state("missing", "todo", "v0", &serde_json::json!({}))
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_export_import_roundtrip() {
         const DESCRIPTION: &str = "Original session";
@@ -0,0 +1,271 @@
+//! Shutting goose down in order, on exit or SIGTERM.
+//!
+//! Replies, extension managers and anything else holding a process or a stream register
+//! with the [`ShutdownCoordinator`] while they're alive. [`ShutdownCoordinator::shutdown`]
+//! then runs each step with a bounded grace period:
+//!
+//! 1. cancel the replies in flight, so provider streams stop
+//! 2. fire SessionEnd hooks for the sessions that replied in this process
+//! 3. run the registered cleanups, which stop extensions and their child processes
+//! 4. flush session persistence, for every session store opened in this process
+//! 5. drain the OTLP exporters, last so the steps above are exported
+//!
+//! A step that runs out of time is logged and skipped, so a stuck hook or extension can't
+//! keep goose from exiting.
+
+use crate::hooks::{HookEvent, HookRuntime};
+use crate::session::session_manager::SessionStorage;
+use crate::session::SessionManager;
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How long each step may take
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+type Cleanup = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+#[derive(Default)]
+struct Registry {
+    replies: HashMap<u64, CancellationToken>,
+    cleanups: HashMap<u64, Cleanup>,
+    /// Session id -> working directory, for the open sessions that replied
+    sessions: HashMap<String, PathBuf>,
+    /// Session stores other than the global one, such as the ACP server's
+    storages: Vec<Weak<SessionStorage>>,
+}
+
+static GLOBAL: LazyLock<ShutdownCoordinator> = LazyLock::new(ShutdownCoordinator::default);
+
+/// Keeps a reply or cleanup registered until dropped
+#[must_use]
+pub struct ShutdownGuard {
+    coordinator: &'static ShutdownCoordinator,
+    id: u64,
+}
+
+impl Drop for ShutdownGuard {
+    fn drop(&mut self) {
+        let mut registry = self.coordinator.registry();
+        registry.replies.remove(&self.id);
+        registry.cleanups.remove(&self.id);
+    }
+}
+
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    registry: Mutex<Registry>,
+    next_id: AtomicU64,
+    shutting_down: AtomicBool,
+}
+
+impl ShutdownCoordinator {
+    pub fn global() -> &'static Self {
+        &GLOBAL
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, Registry> {
+        self.registry.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn next_guard(&'static self) -> ShutdownGuard {
+        ShutdownGuard {
+            coordinator: self,
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+        }
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Cancel `token` when goose shuts down, for as long as the guard lives
+    pub fn track_reply(&'static self, token: CancellationToken) -> ShutdownGuard {
+        if self.is_shutting_down() {
+            token.cancel();
+        }
+        let guard = self.next_guard();
+        self.registry().replies.insert(guard.id, token);
+        guard
+    }
+
+    /// Fire SessionEnd for this session when goose shuts down
+    pub fn track_session(&self, session_id: &str, working_dir: PathBuf) {
+        self.registry()
+            .sessions
+            .insert(session_id.to_string(), working_dir);
+    }
+
+    /// The session ended before goose shut down, so no SessionEnd fires for it
+    pub fn untrack_session(&self, session_id: &str) {
+        self.registry().sessions.remove(session_id);
+    }
+
+    /// Flush `storage` when goose shuts down, for as long as it's alive
+    pub fn track_storage(&self, storage: &Arc<SessionStorage>) {
+        let mut registry = self.registry();
+        registry
+            .storages
+            .retain(|storage| storage.strong_count() > 0);
+        registry.storages.push(Arc::downgrade(storage));
+    }
+
+    /// Run `cleanup` when goose shuts down, unless the guard was dropped before
+    pub fn on_shutdown<F, Fut>(&'static self, cleanup: F) -> ShutdownGuard
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let guard = self.next_guard();
+        self.registry()
+            .cleanups
+            .insert(guard.id, Box::new(move || Box::pin(cleanup())));
+        guard
+    }
+
+    /// Start shutting down: cancel the replies in flight and any started from now on.
+    /// Servers call this as soon as the signal arrives, so open reply streams end and
+    /// connections can drain.
+    pub fn begin(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        for token in self.registry().replies.values() {
+            token.cancel();
+        }
+    }
+
+    async fn end_sessions(&self, reason: &str) {
+        let sessions = std::mem::take(&mut self.registry().sessions);
+        if sessions.is_empty() {
+            return;
+        }
+        // Hooks still running when the grace period ends are cancelled
+        let cancel_token = CancellationToken::new();
+        let deadline = cancel_token.clone();
+        let timer = tokio::spawn(async move {
+            tokio::time::sleep(GRACE_PERIOD).await;
+            deadline.cancel();
+        });
+        let ends = sessions.into_iter().map(|(session_id, cwd)| {
+            let cancel_token = cancel_token.clone();
+            async move {
+                HookRuntime::load(&cwd)
+                    .emit(
+                        HookEvent::SessionEnd {
+                            session_id,
+                            reason: reason.to_string(),
+                            cwd: cwd.clone(),
+                        },
+                        &cwd,
+                        cancel_token,
+                    )
+                    .await;
+            }
+        });
+        futures::future::join_all(ends).await;
+        timer.abort();
+    }
+
+    async fn run_cleanups(&self) {
+        let cleanups: Vec<Cleanup> = self.registry().cleanups.drain().map(|(_, c)| c).collect();
+        within_grace_period(
+            "extension cleanup",
+            futures::future::join_all(cleanups.into_iter().map(|cleanup| cleanup())),
+        )
+        .await;
+    }
+
+    /// Shut goose down, giving each step a bounded grace period. `reason` is passed to
+    /// SessionEnd hooks, e.g. `exit` or `terminated`.
+    pub async fn shutdown(&self, reason: &str) {
+        self.begin();
+        self.end_sessions(reason).await;
+        self.run_cleanups().await;
+
+        let mut storages: Vec<Arc<SessionStorage>> = self
+            .registry()
+            .storages
+            .drain(..)
+            .filter_map(|storage| storage.upgrade())
+            .collect();
+        storages.push(Arc::clone(SessionManager::instance().storage()));
+        let flushes = storages.iter().map(|storage| storage.flush());
+        if let Some(results) =
+            within_grace_period("session flush", futures::future::join_all(flushes)).await
+        {
+            for e in results.into_iter().filter_map(|result| result.err()) {
+                tracing::warn!("Failed to save session messages: {}", e);
+            }
+        }
+
+        if crate::otel::otlp::is_otlp_initialized() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            within_grace_period(
+                "telemetry export",
+                tokio::task::spawn_blocking(crate::otel::otlp::shutdown_otlp),
+            )
+            .await;
+        }
+    }
+}
+
+async fn within_grace_period<F: Future>(step: &str, future: F) -> Option<F::Output> {
+    match tokio::time::timeout(GRACE_PERIOD, future).await {
+        Ok(output) => Some(output),
+        Err(_) => {
+            tracing::warn!("Shutdown step '{}' timed out", step);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn begin_cancels_replies_and_cleanups_run_while_registered() {
+        let coordinator: &'static ShutdownCoordinator =
+            Box::leak(Box::new(ShutdownCoordinator::default()));
+        let reply = CancellationToken::new();
+        let _reply_guard = coordinator.track_reply(reply.clone());
+        let finished = CancellationToken::new();
+        drop(coordinator.track_reply(finished.clone()));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let live = tx.clone();
+        let _cleanup_guard = coordinator.on_shutdown(move || async move {
+            live.send("live").unwrap();
+        });
+        drop(coordinator.on_shutdown(move || async move {
+            tx.send("dropped").unwrap();
+        }));
+
+        coordinator.begin();
+        coordinator.run_cleanups().await;
+        assert!(reply.is_cancelled());
+        assert!(!finished.is_cancelled());
+        assert_eq!(rx.recv().await, Some("live"));
+        assert!(rx.recv().await.is_none());
+
+        // Replies started while shutting down stop straight away
+        let late = CancellationToken::new();
+        let _late_guard = coordinator.track_reply(late.clone());
+        assert!(late.is_cancelled());
+    }
+
+    #[test]
+    fn ended_sessions_are_untracked() {
+        let coordinator = ShutdownCoordinator::default();
+        coordinator.track_session("open", PathBuf::from("/tmp"));
+        coordinator.track_session("stopped", PathBuf::from("/tmp"));
+        coordinator.untrack_session("stopped");
+        let registry = coordinator.registry();
+        assert!(registry.sessions.contains_key("open"));
+        assert!(!registry.sessions.contains_key("stopped"));
+    }
+}
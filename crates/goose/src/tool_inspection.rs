@@ -1,11 +1,15 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use rmcp::model::CallToolResult;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::config::GooseMode;
 use crate::conversation::message::{Message, ToolRequest};
 use crate::permission::permission_inspector::PermissionInspector;
 use crate::permission::permission_judge::PermissionCheckResult;
+use crate::security::audit::{AuditEvent, AuditLog};
+use crate::session::extension_data::ExtensionData;
 
 /// Result of inspecting a tool call
 #[derive(Debug, Clone)]
@@ -29,6 +33,16 @@ pub enum InspectionAction {
     RequireApproval(Option<String>),
 }
 
+impl InspectionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InspectionAction::Allow => "allow",
+            InspectionAction::Deny => "deny",
+            InspectionAction::RequireApproval(_) => "require_approval",
+        }
+    }
+}
+
 /// Trait for all tool inspectors
 #[async_trait]
 pub trait ToolInspector: Send + Sync {
@@ -49,29 +63,176 @@ pub trait ToolInspector: Send + Sync {
         true
     }
 
+    /// Inspectors, by name, that must finish before this one starts. Inspectors without
+    /// dependencies run concurrently.
+    fn depends_on(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Allow downcasting to concrete types
     fn as_any(&self) -> &dyn std::any::Any;
 }
 
+/// Context passed to output inspectors for a single tool result
+#[derive(Debug, Clone)]
+pub struct OutputInspectionContext {
+    pub session_id: String,
+    pub tool_name: String,
+    pub extension_data: ExtensionData,
+}
+
+/// Trait for inspectors that run on tool results before they enter the conversation
+pub trait ToolOutputInspector: Send + Sync {
+    /// Name of this inspector (for logging/debugging)
+    fn name(&self) -> &'static str;
+
+    /// Whether this inspector is enabled for the given session
+    fn is_enabled(&self, _context: &OutputInspectionContext) -> bool {
+        true
+    }
+
+    /// Inspect and optionally rewrite a successful tool result
+    fn inspect_output(
+        &self,
+        context: &OutputInspectionContext,
+        result: CallToolResult,
+    ) -> CallToolResult;
+}
+
+/// Run output inspectors in order over a tool result
+pub fn inspect_tool_output(
+    inspectors: &[Arc<dyn ToolOutputInspector>],
+    context: &OutputInspectionContext,
+    mut result: CallToolResult,
+) -> CallToolResult {
+    for inspector in inspectors {
+        if !inspector.is_enabled(context) {
+            continue;
+        }
+        result = inspector.inspect_output(context, result);
+    }
+    result
+}
+
+fn record_inspection(session_id: &str, tool_requests: &[ToolRequest], result: &InspectionResult) {
+    let tool_name = tool_requests
+        .iter()
+        .find(|r| r.id == result.tool_request_id)
+        .and_then(|r| r.tool_call.as_ref().ok())
+        .map(|call| call.name.to_string())
+        .unwrap_or_default();
+    AuditLog::record(
+        Some(session_id),
+        AuditEvent::Inspection {
+            request_id: result.tool_request_id.clone(),
+            tool_name,
+            inspector: result.inspector_name.clone(),
+            action: result.action.as_str().to_string(),
+            reason: result.reason.clone(),
+            confidence: result.confidence,
+            finding_id: result.finding_id.clone(),
+        },
+    );
+}
+
+/// Group inspectors into stages that run one after another; the inspectors within a stage
+/// run concurrently. An inspector goes in the stage after the last of its dependencies.
+/// Dependencies on unknown or later-registered inspectors are ignored, so registration
+/// order always gives a valid schedule.
+fn inspection_stages(inspectors: &[(&str, &[&str])]) -> Vec<Vec<usize>> {
+    let mut stage_of: Vec<usize> = Vec::with_capacity(inspectors.len());
+    for (index, (_, depends_on)) in inspectors.iter().enumerate() {
+        let stage = inspectors[..index]
+            .iter()
+            .zip(&stage_of)
+            .filter(|((name, _), _)| depends_on.contains(name))
+            .map(|(_, stage)| stage + 1)
+            .max()
+            .unwrap_or(0);
+        stage_of.push(stage);
+    }
+
+    let mut stages: Vec<Vec<usize>> = Vec::new();
+    for (index, stage) in stage_of.into_iter().enumerate() {
+        if stages.len() <= stage {
+            stages.resize_with(stage + 1, Vec::new);
+        }
+        stages[stage].push(index);
+    }
+    stages
+}
+
+async fn run_inspector(
+    inspector: &dyn ToolInspector,
+    session_id: &str,
+    tool_requests: &[ToolRequest],
+    messages: &[Message],
+    goose_mode: GooseMode,
+) -> Vec<InspectionResult> {
+    tracing::debug!(
+        inspector_name = inspector.name(),
+        tool_count = tool_requests.len(),
+        "Running tool inspector"
+    );
+
+    match inspector
+        .inspect(session_id, tool_requests, messages, goose_mode)
+        .await
+    {
+        Ok(results) => {
+            tracing::debug!(
+                inspector_name = inspector.name(),
+                result_count = results.len(),
+                "Tool inspector completed"
+            );
+            results
+        }
+        Err(e) => {
+            tracing::error!(
+                inspector_name = inspector.name(),
+                error = %e,
+                "Tool inspector failed"
+            );
+            // Continue with other inspectors even if one fails
+            Vec::new()
+        }
+    }
+}
+
 /// Manages all tool inspectors and coordinates their results
 pub struct ToolInspectionManager {
     inspectors: Vec<Box<dyn ToolInspector>>,
+    output_inspectors: Vec<Arc<dyn ToolOutputInspector>>,
 }
 
 impl ToolInspectionManager {
     pub fn new() -> Self {
         Self {
             inspectors: Vec::new(),
+            output_inspectors: Vec::new(),
         }
     }
 
+    /// Add an output inspector to the manager
+    /// Output inspectors run in the order they are added
+    pub fn add_output_inspector(&mut self, inspector: Arc<dyn ToolOutputInspector>) {
+        self.output_inspectors.push(inspector);
+    }
+
+    /// Output inspectors, cloned so they can be moved into tool result futures
+    pub fn output_inspectors(&self) -> Vec<Arc<dyn ToolOutputInspector>> {
+        self.output_inspectors.clone()
+    }
+
     /// Add an inspector to the manager
     /// Inspectors run in the order they are added
     pub fn add_inspector(&mut self, inspector: Box<dyn ToolInspector>) {
         self.inspectors.push(inspector);
     }
 
-    /// Run all inspectors on the tool requests
+    /// Run all inspectors on the tool requests. Independent inspectors run concurrently;
+    /// results are returned in registration order whatever order they finish in, and
+    /// [`apply_inspection_results_to_permissions`] lets the most restrictive one win.
     pub async fn inspect_tools(
         &self,
         session_id: &str,
@@ -79,39 +240,38 @@ impl ToolInspectionManager {
         messages: &[Message],
         goose_mode: GooseMode,
     ) -> Result<Vec<InspectionResult>> {
-        let mut all_results = Vec::new();
-
-        for inspector in &self.inspectors {
-            if !inspector.is_enabled() {
-                continue;
+        let enabled: Vec<&dyn ToolInspector> = self
+            .inspectors
+            .iter()
+            .filter(|inspector| inspector.is_enabled())
+            .map(|inspector| inspector.as_ref())
+            .collect();
+        let dependencies: Vec<(&str, &[&str])> = enabled
+            .iter()
+            .map(|inspector| (inspector.name(), inspector.depends_on()))
+            .collect();
+
+        let mut results: Vec<Vec<InspectionResult>> = vec![Vec::new(); enabled.len()];
+        for stage in inspection_stages(&dependencies) {
+            let outcomes = futures::future::join_all(stage.iter().map(|&index| {
+                run_inspector(
+                    enabled[index],
+                    session_id,
+                    tool_requests,
+                    messages,
+                    goose_mode,
+                )
+            }))
+            .await;
+            for (index, outcome) in stage.into_iter().zip(outcomes) {
+                results[index] = outcome;
             }
+        }
+        let all_results: Vec<InspectionResult> = results.into_iter().flatten().collect();
 
-            tracing::debug!(
-                inspector_name = inspector.name(),
-                tool_count = tool_requests.len(),
-                "Running tool inspector"
-            );
-
-            match inspector
-                .inspect(session_id, tool_requests, messages, goose_mode)
-                .await
-            {
-                Ok(results) => {
-                    tracing::debug!(
-                        inspector_name = inspector.name(),
-                        result_count = results.len(),
-                        "Tool inspector completed"
-                    );
-                    all_results.extend(results);
-                }
-                Err(e) => {
-                    tracing::error!(
-                        inspector_name = inspector.name(),
-                        error = %e,
-                        "Tool inspector failed"
-                    );
-                    // Continue with other inspectors even if one fails
-                }
+        if AuditLog::is_enabled() {
+            for result in &all_results {
+                record_inspection(session_id, tool_requests, result);
             }
         }
 
@@ -123,6 +283,11 @@ impl ToolInspectionManager {
         self.inspectors.iter().map(|i| i.name()).collect()
     }
 
+    /// Get list of registered output inspector names
+    pub fn output_inspector_names(&self) -> Vec<&'static str> {
+        self.output_inspectors.iter().map(|i| i.name()).collect()
+    }
+
     fn get_permission_inspector(&self) -> Option<&PermissionInspector> {
         self.inspectors
             .iter()
@@ -136,6 +301,19 @@ impl ToolInspectionManager {
         }
     }
 
+    pub fn add_permission_grant(
+        &self,
+        tool_name: &str,
+        scope: crate::config::permission::GrantScope,
+        ttl: Option<chrono::Duration>,
+    ) {
+        if let Some(inspector) = self.get_permission_inspector() {
+            inspector
+                .permission_manager
+                .add_grant(tool_name, scope, ttl);
+        }
+    }
+
     pub async fn update_permission_manager(
         &self,
         tool_name: &str,
@@ -224,7 +402,8 @@ pub fn apply_inspection_results_to_permissions(
                 }
             }
             InspectionAction::RequireApproval(_) => {
-                // Remove from approved, add to needs_approval if not already there
+                // Remove from approved, add to needs_approval if not already there.
+                // A denial from another inspector stands.
                 permission_result
                     .approved
                     .retain(|req| req.id != *request_id);
@@ -233,6 +412,7 @@ pub fn apply_inspection_results_to_permissions(
                     if !permission_result
                         .needs_approval
                         .iter()
+                        .chain(permission_result.denied.iter())
                         .any(|req| req.id == *request_id)
                     {
                         permission_result.needs_approval.push(request.clone());
@@ -299,4 +479,56 @@ mod tests {
         assert_eq!(updated_result.denied.len(), 1);
         assert_eq!(updated_result.denied[0].id, "req_1");
     }
+
+    #[test]
+    fn test_inspection_stages() {
+        let independent: &[(&str, &[&str])] = &[("security", &[]), ("permission", &[])];
+        assert_eq!(inspection_stages(independent), vec![vec![0, 1]]);
+
+        let dependent: &[(&str, &[&str])] = &[
+            ("security", &[]),
+            ("permission", &["security"]),
+            ("repetition", &[]),
+            ("loop_guard", &["permission", "missing"]),
+        ];
+        assert_eq!(
+            inspection_stages(dependent),
+            vec![vec![0, 2], vec![1], vec![3]]
+        );
+
+        // Dependencies on later inspectors are ignored
+        let backwards: &[(&str, &[&str])] = &[("security", &["permission"]), ("permission", &[])];
+        assert_eq!(inspection_stages(backwards), vec![vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_require_approval_does_not_override_denial() {
+        let tool_request = ToolRequest {
+            id: "req_1".to_string(),
+            tool_call: Ok(CallToolRequestParams::new("test_tool").with_arguments(object!({}))),
+            metadata: None,
+            tool_meta: None,
+        };
+
+        let permission_result = PermissionCheckResult {
+            approved: vec![],
+            needs_approval: vec![],
+            denied: vec![tool_request],
+        };
+
+        let inspection_results = vec![InspectionResult {
+            tool_request_id: "req_1".to_string(),
+            action: InspectionAction::RequireApproval(Some("preview".to_string())),
+            reason: "Dry run".to_string(),
+            confidence: 1.0,
+            inspector_name: "dry_run".to_string(),
+            finding_id: None,
+        }];
+
+        let updated_result =
+            apply_inspection_results_to_permissions(permission_result, &inspection_results);
+
+        assert!(updated_result.needs_approval.is_empty());
+        assert_eq!(updated_result.denied.len(), 1);
+    }
 }
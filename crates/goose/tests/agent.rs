@@ -18,7 +18,7 @@ mod tests {
         use goose::agents::AgentConfig;
         use goose::config::permission::PermissionManager;
         use goose::config::GooseMode;
-        use goose::scheduler::{ScheduledJob, SchedulerError};
+        use goose::scheduler::{JobRunSettings, ScheduledJob, SchedulerError};
         use goose::scheduler_trait::SchedulerTrait;
         use goose::session::{Session, SessionManager};
         use std::path::PathBuf;
@@ -104,6 +104,14 @@ mod tests {
                 Ok(())
             }
 
+            async fn update_run_settings(
+                &self,
+                _sched_id: &str,
+                _settings: JobRunSettings,
+            ) -> Result<(), SchedulerError> {
+                Ok(())
+            }
+
             async fn kill_running_job(&self, _sched_id: &str) -> Result<(), SchedulerError> {
                 Ok(())
             }